@@ -0,0 +1,54 @@
+//! Per-transaction decode path: selector classification and amount decoding.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use ethers::abi::AbiEncode;
+use ethers::types::{Address, Bytes, Transaction, U256};
+use liquidio::blockchain::{BorrowCall, DepositCall, RepayCall, TransferCall, WithdrawCall};
+use liquidio::mempool_streamer::TransactionClassifier;
+
+fn calldata(call: impl AbiEncode) -> Bytes {
+    call.encode().into()
+}
+
+fn bench_classifier(c: &mut Criterion) {
+    // Protocol calls plus an ERC20 transfer, as a realistic mempool mix
+    let inputs = [
+        calldata(DepositCall),
+        calldata(BorrowCall { amount: U256::from(1_000) }),
+        calldata(WithdrawCall { amount: U256::from(500) }),
+        calldata(RepayCall { amount: U256::from(250) }),
+        calldata(TransferCall { to: Address::zero(), amount: U256::one() }),
+    ];
+    let txs: Vec<Transaction> = inputs
+        .iter()
+        .map(|input| Transaction { input: input.clone(), ..Default::default() })
+        .collect();
+
+    let mut group = c.benchmark_group("classifier");
+    group.throughput(Throughput::Elements(inputs.len() as u64));
+    group.bench_function("classify_calldata", |b| {
+        b.iter(|| {
+            for input in &inputs {
+                black_box(TransactionClassifier::classify_calldata(black_box(input)));
+            }
+        })
+    });
+    group.bench_function("classify_transaction", |b| {
+        b.iter(|| {
+            for tx in &txs {
+                black_box(TransactionClassifier::classify_transaction(black_box(tx)));
+            }
+        })
+    });
+    group.bench_function("decode_amount", |b| {
+        b.iter(|| {
+            for input in &inputs {
+                black_box(TransactionClassifier::decode_amount(black_box(input)));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_classifier);
+criterion_main!(benches);
@@ -0,0 +1,144 @@
+//! Hot-path components behind the latency targets checked in `main.rs`:
+//! the detector's liquidation check, the simulator's profit math, and
+//! liquidation transaction encoding and signing.
+//!
+//! None of these touch the network; the detector reads a synthetic population.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Eip1559TransactionRequest, Transaction, U256};
+use liquidio::executor::LiquidationExecutor;
+use liquidio::fixed_point::wad_to_f64;
+use liquidio::liquidation_detector::{LiquidationDetector, PositionSource};
+use liquidio::mempool_streamer::{TrafficProfile, TransactionType, DEPOSIT_SELECTOR};
+use liquidio::metrics::LatencyMetrics;
+use liquidio::oracle::PriceQuote;
+use liquidio::population::{PopulationModel, UserPopulation};
+use liquidio::protocol::AssetRiskParams;
+use liquidio::simulator::liquidation_profit_usd;
+use liquidio::testkit::offline_client;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::Arc;
+
+const USERS: usize = 1_000;
+/// Anvil's first dev account
+const DEV_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+fn bench_check_liquidation(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let protocol = Address::zero();
+
+    // Borrowers near their target HF, then a price drop pushes part of them under 1.0
+    let population = Arc::new(UserPopulation::new(
+        PopulationModel { price_volatility: 0.0, price_drift: -0.01, ..Default::default() },
+        USERS,
+        7,
+    ));
+    let mut rng = StdRng::seed_from_u64(7);
+    let profile = TrafficProfile::default();
+    for user in population.users() {
+        population.apply(&mut rng, *user, TransactionType::Deposit, &profile);
+        population.apply(&mut rng, *user, TransactionType::Borrow, &profile);
+    }
+    for _ in 0..20 {
+        population.step_price(&mut rng);
+    }
+
+    let detector = runtime.block_on(async {
+        let blockchain = offline_client().await;
+        LiquidationDetector::with_source(blockchain, PositionSource::Synthetic(population.clone()))
+    });
+
+    // Track every user by feeding it one deposit
+    let deposits: Vec<Transaction> = population
+        .users()
+        .iter()
+        .map(|user| Transaction {
+            from: *user,
+            to: Some(protocol),
+            input: Bytes::from_static(&DEPOSIT_SELECTOR),
+            ..Default::default()
+        })
+        .collect();
+    runtime.block_on(async {
+        for tx in &deposits {
+            detector.process_transaction(tx, protocol).await.unwrap();
+        }
+    });
+
+    let mut group = c.benchmark_group("detector");
+    group.bench_function("check_liquidation", |b| {
+        let mut users = population.users().iter().cycle();
+        b.to_async(&runtime).iter(|| {
+            let user = *users.next().unwrap();
+            let detector = &detector;
+            async move {
+                let mut metrics = LatencyMetrics::new();
+                black_box(detector.check_liquidation(user, &mut metrics).await.unwrap())
+            }
+        })
+    });
+    group.bench_function("process_transaction", |b| {
+        let mut txs = deposits.iter().cycle();
+        b.to_async(&runtime).iter(|| {
+            let tx = txs.next().unwrap();
+            let detector = &detector;
+            async move { black_box(detector.process_transaction(tx, protocol).await.unwrap()) }
+        })
+    });
+    group.finish();
+}
+
+fn bench_simulator_math(c: &mut Criterion) {
+    let params = AssetRiskParams::default();
+    let debt = U256::from(1_500u64) * U256::exp10(18);
+    let eth_price_e8 = U256::from(2_000u64) * U256::exp10(8);
+    let quote = PriceQuote { price_usd: 2_000.0, confidence_usd: 3.0 };
+
+    let mut group = c.benchmark_group("simulator");
+    group.bench_function("seize_and_profit", |b| {
+        b.iter(|| {
+            let debt_to_cover = params.max_repayable(black_box(debt));
+            let collateral = params.with_bonus(debt_to_cover * U256::exp10(8) / eth_price_e8);
+            liquidation_profit_usd(wad_to_f64(collateral), wad_to_f64(debt_to_cover), black_box(4.2), black_box(quote), 1.0)
+        })
+    });
+    group.finish();
+}
+
+fn bench_tx_encoding(c: &mut Criterion) {
+    let wallet: LocalWallet = DEV_KEY.parse::<LocalWallet>().unwrap().with_chain_id(31337u64);
+    let user = Address::from_low_u64_be(0xbeef);
+    let debt = U256::from(1_500u64) * U256::exp10(18);
+
+    let request = |user, debt| -> TypedTransaction {
+        Eip1559TransactionRequest::new()
+            .from(wallet.address())
+            .to(Address::from_low_u64_be(1))
+            .data(LiquidationExecutor::encode_liquidate_call(user, debt))
+            .gas(350_000)
+            .max_fee_per_gas(100_000_000_000u64)
+            .max_priority_fee_per_gas(2_000_000_000u64)
+            .nonce(42)
+            .chain_id(31337)
+            .into()
+    };
+
+    let mut group = c.benchmark_group("tx_encoding");
+    group.bench_function("encode_liquidate_call", |b| {
+        b.iter(|| LiquidationExecutor::encode_liquidate_call(black_box(user), black_box(debt)))
+    });
+    group.bench_function("sign_and_rlp", |b| {
+        b.iter(|| {
+            let tx = request(black_box(user), black_box(debt));
+            let signature = wallet.sign_transaction_sync(&tx).unwrap();
+            tx.rlp_signed(&signature)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_check_liquidation, bench_simulator_math, bench_tx_encoding);
+criterion_main!(benches);
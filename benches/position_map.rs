@@ -0,0 +1,98 @@
+//! Position map contention: one `RwLock<HashMap>` (the detector's old store)
+//! against the sharded `DashMap` it uses now, under concurrent updates and
+//! price-tick style reads over a large user set.
+//!
+//! The difference only shows with several cores: `cargo bench --bench position_map`
+//! on a single-core machine measures lock overhead, not contention.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use dashmap::DashMap;
+use ethers::types::{Address, U256};
+use liquidio::liquidation_detector::UserPosition;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::thread;
+
+const USERS: usize = 50_000;
+const OPS_PER_THREAD: usize = 10_000;
+/// One write per this many operations; the rest are reads, as in a price tick
+const WRITE_EVERY: usize = 5;
+
+trait PositionMap: Sync {
+    fn insert(&self, user: Address, position: UserPosition);
+    fn health_factor(&self, user: &Address) -> Option<U256>;
+}
+
+impl PositionMap for RwLock<HashMap<Address, UserPosition>> {
+    fn insert(&self, user: Address, position: UserPosition) {
+        self.write().unwrap().insert(user, position);
+    }
+
+    fn health_factor(&self, user: &Address) -> Option<U256> {
+        self.read().unwrap().get(user).map(|p| p.health_factor)
+    }
+}
+
+impl PositionMap for DashMap<Address, UserPosition> {
+    fn insert(&self, user: Address, position: UserPosition) {
+        DashMap::insert(self, user, position);
+    }
+
+    fn health_factor(&self, user: &Address) -> Option<U256> {
+        self.get(user).map(|p| p.health_factor)
+    }
+}
+
+fn position(i: usize) -> UserPosition {
+    UserPosition {
+        collateral: U256::from(i) * U256::exp10(18),
+        debt: U256::from(i) * U256::exp10(20),
+        health_factor: U256::from(100 + i % 50),
+        last_updated: 0,
+        updated_block: None,
+    }
+}
+
+fn run(map: &impl PositionMap, users: &[Address], threads: usize) {
+    thread::scope(|scope| {
+        for t in 0..threads {
+            scope.spawn(move || {
+                // Each thread strides through the user set from its own offset
+                for op in 0..OPS_PER_THREAD {
+                    let i = (t * 7919 + op * 31) % users.len();
+                    if op % WRITE_EVERY == 0 {
+                        map.insert(users[i], position(op));
+                    } else {
+                        criterion::black_box(map.health_factor(&users[i]));
+                    }
+                }
+            });
+        }
+    });
+}
+
+fn bench_position_map(c: &mut Criterion) {
+    let users: Vec<Address> = (0..USERS as u64).map(Address::from_low_u64_be).collect();
+
+    let locked = RwLock::new(HashMap::new());
+    let sharded = DashMap::new();
+    for (i, user) in users.iter().enumerate() {
+        PositionMap::insert(&locked, *user, position(i));
+        PositionMap::insert(&sharded, *user, position(i));
+    }
+
+    let mut group = c.benchmark_group("position_map");
+    for threads in [1, 4, 8] {
+        group.throughput(Throughput::Elements((threads * OPS_PER_THREAD) as u64));
+        group.bench_with_input(BenchmarkId::new("rwlock_hashmap", threads), &threads, |b, &threads| {
+            b.iter(|| run(&locked, &users, threads))
+        });
+        group.bench_with_input(BenchmarkId::new("dashmap", threads), &threads, |b, &threads| {
+            b.iter(|| run(&sharded, &users, threads))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_position_map);
+criterion_main!(benches);
@@ -0,0 +1,8 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        // Vendored, so building the gRPC API needs no system protoc
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path().expect("No vendored protoc for this platform"));
+        tonic_build::compile_protos("proto/liquidio.proto").expect("Failed to compile proto/liquidio.proto");
+    }
+}
@@ -0,0 +1,390 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.21;
+
+interface IERC20 {
+    function transfer(address to, uint256 amount) external returns (bool);
+    function transferFrom(address from, address to, uint256 amount) external returns (bool);
+    function approve(address spender, uint256 amount) external returns (bool);
+    function balanceOf(address account) external view returns (uint256);
+}
+
+interface IWETH is IERC20 {
+    function deposit() external payable;
+}
+
+interface ILendingProtocol {
+    function liquidate(address user, uint256 debtToCover) external;
+}
+
+struct MarketParams {
+    address loanToken;
+    address collateralToken;
+    address oracle;
+    address irm;
+    uint256 lltv;
+}
+
+interface IMorpho {
+    function market(bytes32 id)
+        external
+        view
+        returns (
+            uint128 totalSupplyAssets,
+            uint128 totalSupplyShares,
+            uint128 totalBorrowAssets,
+            uint128 totalBorrowShares,
+            uint128 lastUpdate,
+            uint128 fee
+        );
+    function liquidate(
+        MarketParams memory marketParams,
+        address borrower,
+        uint256 seizedAssets,
+        uint256 repaidShares,
+        bytes memory data
+    ) external returns (uint256, uint256);
+}
+
+interface IBalancerVault {
+    function flashLoan(
+        address recipient,
+        address[] memory tokens,
+        uint256[] memory amounts,
+        bytes memory userData
+    ) external;
+}
+
+interface IUniswapV3Pool {
+    function token0() external view returns (address);
+    function flash(address recipient, uint256 amount0, uint256 amount1, bytes calldata data) external;
+    function swap(
+        address recipient,
+        bool zeroForOne,
+        int256 amountSpecified,
+        uint160 sqrtPriceLimitX96,
+        bytes calldata data
+    ) external returns (int256 amount0, int256 amount1);
+}
+
+/**
+ * @title LiquidationHelper
+ * @dev Borrows the debt asset, liquidates, sells the seized ETH and repays the
+ * lender in one transaction, so a liquidation needs no inventory and either
+ * completes profitably or reverts as a whole.
+ *
+ * Funding sources:
+ * - 0 inventory: the debt asset is pulled from the calling operator, who also
+ *   receives the seized ETH
+ * - 1 Balancer vault flash loan
+ * - 2 Uniswap V3 pool flash (must not be `swapPool`, which is locked during it)
+ *
+ * Morpho Blue liquidations need no lender: Morpho sends the seized collateral
+ * first and calls `onMorphoLiquidate` before pulling the repayment.
+ *
+ * `liquidateBatch` liquidates several positions with one loan, one sale and
+ * one transaction, so small positions share the fixed gas overhead.
+ */
+contract LiquidationHelper {
+    uint8 public constant INVENTORY = 0;
+    uint8 public constant BALANCER = 1;
+    uint8 public constant UNISWAP_V3 = 2;
+    uint8 public constant MORPHO = 3;
+
+    // Morpho Blue virtual shares and assets (SharesMathLib)
+    uint256 internal constant VIRTUAL_SHARES = 1e6;
+    uint256 internal constant VIRTUAL_ASSETS = 1;
+
+    // Price limits that let a swap run through the whole curve
+    uint160 internal constant MIN_SQRT_RATIO = 4295128739;
+    uint160 internal constant MAX_SQRT_RATIO = 1461446703485210103287393025260910908135665734;
+
+    address public immutable owner;
+    ILendingProtocol public immutable protocol;
+    IERC20 public immutable debtAsset;
+    IWETH public immutable weth;
+    // WETH/debt asset pool the seized collateral is sold into
+    IUniswapV3Pool public immutable swapPool;
+
+    mapping(address => bool) public operators;
+
+    // Lender whose callback is expected while a flash liquidation runs
+    address private activeLender;
+
+    event Liquidated(
+        address indexed user,
+        uint8 source,
+        uint256 debtRepaid,
+        uint256 collateralSeized,
+        uint256 fee,
+        uint256 profit
+    );
+
+    // Totals of a `liquidateBatch`; each position also gets a `Liquidated` with no fee or profit
+    event BatchLiquidated(
+        uint256 count,
+        uint8 source,
+        uint256 debtRepaid,
+        uint256 collateralSeized,
+        uint256 fee,
+        uint256 profit
+    );
+
+    constructor(address _protocol, address _debtAsset, address _weth, address _swapPool) {
+        owner = msg.sender;
+        operators[msg.sender] = true;
+        protocol = ILendingProtocol(_protocol);
+        debtAsset = IERC20(_debtAsset);
+        weth = IWETH(_weth);
+        swapPool = IUniswapV3Pool(_swapPool);
+    }
+
+    modifier onlyOperator() {
+        require(operators[msg.sender], "Not an operator");
+        _;
+    }
+
+    function setOperator(address operator, bool allowed) external {
+        require(msg.sender == owner, "Not the owner");
+        operators[operator] = allowed;
+    }
+
+    /**
+     * @dev Liquidate `user`, funding `debtToCover` from `source` (`lender` is the
+     * Balancer vault or Uniswap V3 pool). Flash liquidations revert unless the
+     * sold collateral repays the lender with at least `minProfit` left over.
+     */
+    function liquidate(
+        address user,
+        uint256 debtToCover,
+        uint8 source,
+        address lender,
+        uint256 minProfit
+    ) external onlyOperator {
+        if (source == INVENTORY) {
+            require(debtAsset.transferFrom(msg.sender, address(this), debtToCover), "Transfer failed");
+            uint256 seized = _liquidate(user, debtToCover);
+            (bool success, ) = msg.sender.call{value: seized}("");
+            require(success, "ETH transfer failed");
+            emit Liquidated(user, INVENTORY, debtToCover, seized, 0, 0);
+            return;
+        }
+
+        address[] memory users = new address[](1);
+        uint256[] memory debtsToCover = new uint256[](1);
+        users[0] = user;
+        debtsToCover[0] = debtToCover;
+        _flashLiquidate(users, debtsToCover, debtToCover, source, lender, minProfit);
+    }
+
+    /**
+     * @dev Liquidate each `users[i]` for `debtsToCover[i]`, funding the total
+     * once from `source`. The batch reverts as a whole unless the sold
+     * collateral repays the lender with at least `minProfit` left over.
+     */
+    function liquidateBatch(
+        address[] calldata users,
+        uint256[] calldata debtsToCover,
+        uint8 source,
+        address lender,
+        uint256 minProfit
+    ) external onlyOperator {
+        require(users.length > 0 && users.length == debtsToCover.length, "Length mismatch");
+        uint256 total;
+        for (uint256 i = 0; i < debtsToCover.length; i++) {
+            total += debtsToCover[i];
+        }
+
+        if (source == INVENTORY) {
+            require(debtAsset.transferFrom(msg.sender, address(this), total), "Transfer failed");
+            uint256 seized = _liquidateAll(users, debtsToCover, INVENTORY);
+            (bool success, ) = msg.sender.call{value: seized}("");
+            require(success, "ETH transfer failed");
+            emit BatchLiquidated(users.length, INVENTORY, total, seized, 0, 0);
+            return;
+        }
+        _flashLiquidate(users, debtsToCover, total, source, lender, minProfit);
+    }
+
+    function _flashLiquidate(
+        address[] memory users,
+        uint256[] memory debtsToCover,
+        uint256 total,
+        uint8 source,
+        address lender,
+        uint256 minProfit
+    ) internal {
+        bytes memory job = abi.encode(users, debtsToCover, minProfit);
+        activeLender = lender;
+        if (source == BALANCER) {
+            address[] memory tokens = new address[](1);
+            uint256[] memory amounts = new uint256[](1);
+            tokens[0] = address(debtAsset);
+            amounts[0] = total;
+            IBalancerVault(lender).flashLoan(address(this), tokens, amounts, job);
+        } else if (source == UNISWAP_V3) {
+            bool debtIsToken0 = IUniswapV3Pool(lender).token0() == address(debtAsset);
+            IUniswapV3Pool(lender).flash(
+                address(this),
+                debtIsToken0 ? total : 0,
+                debtIsToken0 ? 0 : total,
+                job
+            );
+        } else {
+            revert("Unknown funding source");
+        }
+        activeLender = address(0);
+    }
+
+    /**
+     * @dev Liquidate `borrower` on the Morpho Blue market `marketParams`, repaying
+     * about `debtToCover` of its loan. The market must lend `debtAsset` against
+     * WETH; the seized WETH is sold in the callback, so no funding is needed.
+     */
+    function liquidateMorpho(
+        address morpho,
+        MarketParams calldata marketParams,
+        address borrower,
+        uint256 debtToCover,
+        uint256 minProfit
+    ) external onlyOperator {
+        require(
+            marketParams.loanToken == address(debtAsset) && marketParams.collateralToken == address(weth),
+            "Unsupported market"
+        );
+        // Shares at the last accrual, rounded down so the repayment never exceeds the debt
+        (, , uint128 totalBorrowAssets, uint128 totalBorrowShares, , ) = IMorpho(morpho).market(
+            keccak256(abi.encode(marketParams))
+        );
+        uint256 repaidShares = (debtToCover * (uint256(totalBorrowShares) + VIRTUAL_SHARES)) /
+            (uint256(totalBorrowAssets) + VIRTUAL_ASSETS);
+
+        activeLender = morpho;
+        (uint256 seized, uint256 repaid) = IMorpho(morpho).liquidate(
+            marketParams,
+            borrower,
+            0,
+            repaidShares,
+            abi.encode(minProfit)
+        );
+        activeLender = address(0);
+
+        uint256 profit = debtAsset.balanceOf(address(this));
+        require(debtAsset.transfer(owner, profit), "Transfer failed");
+        emit Liquidated(borrower, MORPHO, repaid, seized, 0, profit);
+    }
+
+    /**
+     * @dev Morpho Blue liquidation callback: sell the seized WETH and approve
+     * the repayment Morpho pulls once this returns
+     */
+    function onMorphoLiquidate(uint256 repaidAssets, bytes calldata data) external {
+        require(msg.sender == activeLender, "Unexpected lender");
+        uint256 minProfit = abi.decode(data, (uint256));
+        _swapWeth(weth.balanceOf(address(this)));
+
+        require(debtAsset.balanceOf(address(this)) >= repaidAssets + minProfit, "Unprofitable");
+        require(debtAsset.approve(msg.sender, repaidAssets), "Approve failed");
+    }
+
+    /**
+     * @dev Balancer vault callback
+     */
+    function receiveFlashLoan(
+        address[] memory,
+        uint256[] memory,
+        uint256[] memory feeAmounts,
+        bytes memory userData
+    ) external {
+        require(msg.sender == activeLender, "Unexpected lender");
+        _settle(userData, feeAmounts[0], BALANCER);
+    }
+
+    /**
+     * @dev Uniswap V3 flash callback
+     */
+    function uniswapV3FlashCallback(uint256 fee0, uint256 fee1, bytes calldata data) external {
+        require(msg.sender == activeLender, "Unexpected lender");
+        _settle(data, fee0 + fee1, UNISWAP_V3);
+    }
+
+    /**
+     * @dev Uniswap V3 swap callback: pay the WETH sold
+     */
+    function uniswapV3SwapCallback(int256 amount0Delta, int256 amount1Delta, bytes calldata) external {
+        require(msg.sender == address(swapPool), "Unexpected pool");
+        uint256 owed = uint256(amount0Delta > 0 ? amount0Delta : amount1Delta);
+        require(weth.transfer(msg.sender, owed), "Transfer failed");
+    }
+
+    /**
+     * @dev Liquidate with borrowed funds, sell the collateral and repay the loan
+     * plus `fee` to the lender (the caller), keeping the rest for the owner
+     */
+    function _settle(bytes memory job, uint256 fee, uint8 source) internal {
+        (address[] memory users, uint256[] memory debtsToCover, uint256 minProfit) = abi.decode(
+            job,
+            (address[], uint256[], uint256)
+        );
+        uint256 seized = _liquidateAll(users, debtsToCover, source);
+        _sell(seized);
+
+        uint256 owed = fee;
+        for (uint256 i = 0; i < debtsToCover.length; i++) {
+            owed += debtsToCover[i];
+        }
+        uint256 balance = debtAsset.balanceOf(address(this));
+        require(balance >= owed + minProfit, "Unprofitable");
+        require(debtAsset.transfer(msg.sender, owed), "Repayment failed");
+        require(debtAsset.transfer(owner, balance - owed), "Transfer failed");
+
+        if (users.length == 1) {
+            emit Liquidated(users[0], source, debtsToCover[0], seized, fee, balance - owed);
+        } else {
+            emit BatchLiquidated(users.length, source, owed - fee, seized, fee, balance - owed);
+        }
+    }
+
+    /**
+     * @dev Liquidate each position in turn, returning the total ETH seized
+     */
+    function _liquidateAll(
+        address[] memory users,
+        uint256[] memory debtsToCover,
+        uint8 source
+    ) internal returns (uint256 seized) {
+        for (uint256 i = 0; i < users.length; i++) {
+            uint256 userSeized = _liquidate(users[i], debtsToCover[i]);
+            seized += userSeized;
+            if (users.length > 1) {
+                emit Liquidated(users[i], source, debtsToCover[i], userSeized, 0, 0);
+            }
+        }
+    }
+
+    function _liquidate(address user, uint256 debtToCover) internal returns (uint256 seized) {
+        require(debtAsset.approve(address(protocol), debtToCover), "Approve failed");
+        uint256 before = address(this).balance;
+        protocol.liquidate(user, debtToCover);
+        seized = address(this).balance - before;
+    }
+
+    function _sell(uint256 amount) internal {
+        weth.deposit{value: amount}();
+        _swapWeth(amount);
+    }
+
+    function _swapWeth(uint256 amount) internal {
+        require(address(swapPool) != address(0), "No swap pool");
+        bool zeroForOne = swapPool.token0() == address(weth);
+        swapPool.swap(
+            address(this),
+            zeroForOne,
+            int256(amount),
+            zeroForOne ? MIN_SQRT_RATIO + 1 : MAX_SQRT_RATIO - 1,
+            ""
+        );
+    }
+
+    // Seized collateral arrives as ETH
+    receive() external payable {}
+}
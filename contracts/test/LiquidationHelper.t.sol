@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.21;
+
+import "forge-std/Test.sol";
+import "../SimpleLendingProtocol.sol";
+import "../MockERC20.sol";
+import "../LiquidationHelper.sol";
+
+contract LiquidationHelperTest is Test {
+    SimpleLendingProtocol public protocol;
+    MockERC20 public stablecoin;
+    LiquidationHelper public helper;
+
+    address public user1 = address(0x1);
+    address public user2 = address(0x2);
+    address public operator = address(0x3);
+
+    function setUp() public {
+        stablecoin = new MockERC20("USD Stablecoin", "USDC", 1_000_000 * 1e18);
+        protocol = new SimpleLendingProtocol(address(stablecoin));
+        stablecoin.transfer(address(protocol), 500_000 * 1e18);
+
+        // No WETH or swap pool: only the inventory route is usable
+        helper = new LiquidationHelper(address(protocol), address(stablecoin), address(0), address(0));
+        helper.setOperator(operator, true);
+
+        vm.deal(user1, 100 ether);
+        stablecoin.transfer(operator, 100_000 * 1e18);
+
+        vm.prank(user1);
+        protocol.deposit{value: 10 ether}();
+        vm.prank(user1);
+        protocol.borrow(10_000 * 1e18);
+
+        vm.deal(user2, 100 ether);
+        vm.prank(user2);
+        protocol.deposit{value: 2 ether}();
+        vm.prank(user2);
+        protocol.borrow(2_000 * 1e18);
+        protocol.setEthPrice(1300 * 1e18);
+    }
+
+    function testInventoryLiquidation() public {
+        vm.startPrank(operator);
+        stablecoin.approve(address(helper), 10_000 * 1e18);
+
+        uint256 ethBefore = operator.balance;
+        helper.liquidate(user1, 10_000 * 1e18, helper.INVENTORY(), address(0), 0);
+        vm.stopPrank();
+
+        // Seized collateral is forwarded to the operator; nothing stays in the helper
+        assertGt(operator.balance, ethBefore);
+        assertEq(address(helper).balance, 0);
+        (, uint256 debtAfter, ) = protocol.getPosition(user1);
+        assertEq(debtAfter, 0);
+    }
+
+    function testBatchLiquidation() public {
+        address[] memory users = new address[](2);
+        uint256[] memory debts = new uint256[](2);
+        users[0] = user1;
+        users[1] = user2;
+        debts[0] = 10_000 * 1e18;
+        debts[1] = 2_000 * 1e18;
+
+        vm.startPrank(operator);
+        stablecoin.approve(address(helper), 12_000 * 1e18);
+        uint256 ethBefore = operator.balance;
+        helper.liquidateBatch(users, debts, helper.INVENTORY(), address(0), 0);
+        vm.stopPrank();
+
+        // Both positions are closed in one transaction, all collateral goes to the operator
+        assertGt(operator.balance, ethBefore);
+        assertEq(address(helper).balance, 0);
+        (, uint256 debt1, ) = protocol.getPosition(user1);
+        (, uint256 debt2, ) = protocol.getPosition(user2);
+        assertEq(debt1 + debt2, 0);
+
+        vm.prank(operator);
+        vm.expectRevert("Length mismatch");
+        helper.liquidateBatch(users, new uint256[](1), 0, address(0), 0);
+    }
+
+    function testOnlyOperatorsLiquidate() public {
+        vm.prank(address(0x4));
+        vm.expectRevert("Not an operator");
+        helper.liquidate(user1, 10_000 * 1e18, 0, address(0), 0);
+
+        vm.prank(operator);
+        vm.expectRevert("Not the owner");
+        helper.setOperator(address(0x4), true);
+    }
+
+    function testMorphoMarketMustMatchSwapPool() public {
+        MarketParams memory market = MarketParams(address(stablecoin), address(0xdead), address(0), address(0), 0.86e18);
+        vm.prank(operator);
+        vm.expectRevert("Unsupported market");
+        helper.liquidateMorpho(address(0x5), market, user1, 1_000 * 1e18, 0);
+
+        // The callback only runs inside a liquidation the helper started
+        vm.prank(address(0x5));
+        vm.expectRevert("Unexpected lender");
+        helper.onMorphoLiquidate(1_000 * 1e18, abi.encode(uint256(0)));
+    }
+
+    function testUnknownFundingSourceReverts() public {
+        vm.prank(operator);
+        vm.expectRevert("Unknown funding source");
+        helper.liquidate(user1, 10_000 * 1e18, 7, address(0), 0);
+    }
+}
@@ -80,6 +80,29 @@ PROTOCOL_OUTPUT=$(forge create --rpc-url http://127.0.0.1:8545 \
 PROTOCOL_ADDRESS=$(echo "$PROTOCOL_OUTPUT" | grep "Deployed to:" | awk '{print $3}')
 echo "   [OK] Lending Protocol deployed at: $PROTOCOL_ADDRESS"
 
+# Deploy LiquidationHelper (no WETH or swap pool locally, so inventory funding only)
+echo "   Deploying LiquidationHelper..."
+HELPER_OUTPUT=$(forge create --rpc-url http://127.0.0.1:8545 \
+    --private-key 0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80 \
+    --broadcast \
+    contracts/LiquidationHelper.sol:LiquidationHelper \
+    --constructor-args "$PROTOCOL_ADDRESS" "$STABLECOIN_ADDRESS" \
+        0x0000000000000000000000000000000000000000 0x0000000000000000000000000000000000000000)
+
+HELPER_ADDRESS=$(echo "$HELPER_OUTPUT" | grep "Deployed to:" | awk '{print $3}')
+echo "   [OK] LiquidationHelper deployed at: $HELPER_ADDRESS"
+
+# Let the bot's wallet (Account #1) liquidate through the helper from its inventory
+cast send $HELPER_ADDRESS "setOperator(address,bool)" 0x70997970C51812dc3A010C7d01b50e0d17dc79C8 true \
+    --rpc-url http://127.0.0.1:8545 \
+    --private-key 0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80 \
+    --silent
+cast send $STABLECOIN_ADDRESS "approve(address,uint256)" $HELPER_ADDRESS \
+    115792089237316195423570985008687907853269984665640564039457584007913129639935 \
+    --rpc-url http://127.0.0.1:8545 \
+    --private-key 0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d \
+    --silent
+
 # Fund protocol with stablecoin
 echo "Funding protocol with stablecoin..."
 cast send $STABLECOIN_ADDRESS "transfer(address,uint256)" $PROTOCOL_ADDRESS 500000000000000000000000 \
@@ -95,7 +118,7 @@ cat > .env << EOF
 # Liquidio Configuration
 # Auto-generated by deploy_contracts.sh
 
-# Network Configuration
+# Network Configuration (ANVIL_RPC_URL may also be a node's IPC socket path)
 ANVIL_RPC_URL=http://127.0.0.1:8545
 ANVIL_WS_URL=ws://127.0.0.1:8545
 CHAIN_ID=31337
@@ -106,12 +129,303 @@ MOCK_TOKEN_ADDRESS=$STABLECOIN_ADDRESS
 
 # Bot Configuration
 LIQUIDATOR_PRIVATE_KEY=0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d
+# Comma-separated LIQUIDATOR_PRIVATE_KEYS rotates across several signers
+WALLET_ROTATION=round_robin
 MIN_PROFIT_THRESHOLD_USD=10.0
+# Per-market overrides (the highest matching one applies), e.g.
+# MIN_PROFIT_BY_COLLATERAL_USD=0x<token>=50 for volatile collateral
+MIN_PROFIT_BY_PROTOCOL_USD=
+MIN_PROFIT_BY_DEBT_ASSET_USD=
+MIN_PROFIT_BY_COLLATERAL_USD=
 MAX_GAS_PRICE_GWEI=100
+# Gas limit = simulated gas * GAS_LIMIT_MULTIPLIER
+GAS_LIMIT_MULTIPLIER=1.2
+# true for chains without EIP-1559 (type-0 transactions)
+LEGACY_TRANSACTIONS=false
+# Presign watchlist liquidations, reusable for this long (ms); 0 disables
+PRESIGN_MAX_AGE_MS=0
+# Sign each liquidation while it is simulated, discarding it if unprofitable
+SPECULATIVE_BUILD=false
+# Refuse to act on signals older than this (ms, 0 = no limit) or read more than
+# SIGNAL_MAX_BLOCK_LAG blocks ago (empty = no limit)
+SIGNAL_MAX_AGE_MS=12000
+SIGNAL_MAX_BLOCK_LAG=
+# Liquidatable positions read more than this many blocks ago are re-read
+# (refresh) or signalled at lower priority (down-rank); empty = never stale
+POSITION_MAX_BLOCK_LAG=
+STALE_POSITION_POLICY=refresh
+# Gas cost model: auto (from CHAIN_ID), l1, optimism, arbitrum, bsc
+GAS_MODEL=auto
+# analytical | snapshot (execute on Anvil inside evm_snapshot/evm_revert)
+SIMULATION_BACKEND=analytical
 
 # Performance Tuning
 MEMPOOL_BATCH_SIZE=100
+# Buffer ahead of the detector and what to do when it fills:
+# block | drop-oldest | drop-non-protocol | expand (doubling up to MEMPOOL_BUFFER_MAX_SIZE)
+MEMPOOL_BUFFER_SIZE=1000
+BACKPRESSURE_POLICY=block
+MEMPOOL_BUFFER_MAX_SIZE=10000
+# Coalesce position/receipt/gas lookups made within this window into JSON-RPC batches (0 = off)
+RPC_BATCH_WINDOW_MS=0
+RPC_BATCH_MAX_SIZE=100
+# Reuse position/gas/ETH price reads until the next block, for at most this long (0 = off)
+READ_CACHE_TTL_MS=0
+# Requests per second per RPC endpoint (empty = unlimited), overridable per
+# provider with host=rps pairs, e.g. alchemy.com=25,infura.io=10
+RPC_RATE_LIMIT=
+RPC_RATE_LIMITS=
 HEALTH_CHECK_INTERVAL_MS=100
+MAX_CONCURRENT_EXECUTIONS=4
+# Simulations run at once, each attributed to its worker (0 = one per execution slot)
+SIMULATION_WORKERS=0
+# Signals waiting for a worker (served highest score first) and how long they stay valid
+OPPORTUNITY_QUEUE_CAPACITY=256
+OPPORTUNITY_MAX_AGE_MS=12000
+
+# Health probes (/healthz, /readyz); leave HEALTH_PORT unset to disable
+HEALTH_PORT=9090
+MAX_FEED_LAG_MS=5000
+# gRPC API (builds with --features grpc); empty disables
+GRPC_PORT=
+
+# Operator control socket (pause, resume, set min_profit, positions top N); empty disables
+CONTROL_SOCKET_PATH=
+
+# Staleness monitoring: extra RPCs (comma-separated) used to establish the chain head
+MONITOR_RPC_URLS=
+MAX_BLOCK_LAG=2
+PAUSE_ON_STALE_DATA=false
+
+# Skip liquidations in markets the protocol has paused or frozen; each market's
+# status is re-read at most this often (0 disables the check)
+MARKET_STATUS_TTL_MS=12000
+
+# Private submission: builder names (flashbots,beaverbuild,rsync,titan) or name=url;
+# empty disables. BUILDER_AUTH_KEY signs the Flashbots auth header (random if unset)
+BUILDERS=
+# Most consecutive blocks a bundle is submitted for (cancelled once included)
+BUNDLE_TARGET_BLOCKS=3
+# Resends of the same signed bundle when no builder accepts it (0 = none); each
+# waits SUBMISSION_RETRY_BACKOFF_MS, doubling, and first checks that the user
+# wasn't liquidated in the meantime
+SUBMISSION_RETRIES=2
+SUBMISSION_RETRY_BACKOFF_MS=200
+# Routes a liquidation may go out on (public,protect,bundle), picked per opportunity:
+# backruns are bundled and anything over PUBLIC_ROUTE_MAX_PROFIT_USD stays private.
+# Empty bundles with BUILDERS and only logs otherwise
+SUBMISSION_ROUTES=
+PROTECT_RPC_URL=https://rpc.flashbots.net/fast
+PUBLIC_ROUTE_MAX_PROFIT_USD=25
+
+# Capital / inventory: per-liquidation cap and gas reserve; sweeps to
+# COLD_WALLET_ADDRESS and collateral conversion stay off until thresholds are set
+MAX_CAPITAL_PER_OPPORTUNITY_USD=100000
+GAS_RESERVE_ETH=0.05
+MAX_ETH_BALANCE=
+MAX_DEBT_ASSET_BALANCE=
+COLLATERAL_CONVERT_THRESHOLD_ETH=
+COLD_WALLET_ADDRESS=
+# SwapRouter02 wallets short of the debt asset buy it through with ETH, paying up
+# to TOP_UP_SLIPPAGE_BPS over the quote, and sell seized collateral through
+# (needs the quoter and WETH; empty = off)
+UNISWAP_V3_SWAP_ROUTER=
+TOP_UP_SLIPPAGE_BPS=50
+
+# Exposure caps on debt repaid within EXPOSURE_WINDOW_SECS (empty = uncapped).
+# Per-entry overrides: PROTOCOL_EXPOSURE_LIMITS_USD=simple-lending=50000,
+# ASSET_EXPOSURE_LIMITS_USD=0x<token>=25000
+EXPOSURE_WINDOW_SECS=3600
+MAX_PROTOCOL_EXPOSURE_USD=
+MAX_ASSET_EXPOSURE_USD=
+PROTOCOL_EXPOSURE_LIMITS_USD=
+ASSET_EXPOSURE_LIMITS_USD=
+
+# Pause execution once realized losses (gas on reverted attempts, slippage)
+# within DRAWDOWN_WINDOW_SECS reach MAX_DRAWDOWN_USD, or after MAX_LOSING_STREAK
+# losses in a row (empty = no limit). Past DRAWDOWN_REDUCE_AT of the loss
+# limit, only DRAWDOWN_REDUCED_SIZE of the usual debt is repaid.
+DRAWDOWN_WINDOW_SECS=86400
+MAX_DRAWDOWN_USD=
+MAX_LOSING_STREAK=
+DRAWDOWN_REDUCE_AT=0.5
+DRAWDOWN_REDUCED_SIZE=0.5
+
+# Borrowers never liquidated (partners, contract wallets known to revert) and,
+# if non-empty, the only ones that are; comma-separated addresses. Labels are
+# 0x<address>=name pairs shown in logs and skip reasons
+ADDRESS_BLOCKLIST=
+ADDRESS_ALLOWLIST=
+ADDRESS_LABELS=
+
+# Simulated vs realized profit/gas over the last CALIBRATION_WINDOW settlements;
+# alert once at least CALIBRATION_MIN_SAMPLES are in and the mean error exceeds a limit
+CALIBRATION_WINDOW=50
+CALIBRATION_MIN_SAMPLES=10
+MAX_PROFIT_BIAS_USD=10
+MAX_GAS_BIAS_PCT=15
+
+# Per-opportunity audit trail (JSONL, appended), e.g. benchmark_results/audit.jsonl;
+# each stage is a flushed write on the execution path, so it is off unless set
+AUDIT_LOG_PATH=
+
+# Rolling 1- and 5-minute live summaries, logged every HEALTH_CHECK_INTERVAL_MS
+# and appended here as JSON lines; empty only logs them
+LIVE_SUMMARY_PATH=benchmark_results/live_summary.jsonl
+
+# Report directory and file names; {mode}, {chain} and {timestamp} are filled in
+# (--out-dir and --report-name override these)
+REPORT_DIR=benchmark_results
+REPORT_NAME_TEMPLATE={mode}
+
+# liquidio bench --assert: P99 targets in ms per stage (e.g. simulation_us=3;
+# unlisted stages keep 10/2/5/1ms for end-to-end/detection/simulation/construction),
+# allowed P99 increase over the baseline, and the baseline stress test report
+LATENCY_TARGETS_MS=
+BENCH_MAX_REGRESSION=0.10
+BENCH_BASELINE_PATH=benchmark_results/bench_baseline.json
+
+# Arbitrum sequencer feed as transaction source (e.g. wss://arb1.arbitrum.io/feed); empty disables
+SEQUENCER_FEED_URL=
+SEQUENCER_FEED_MAX_TXS=10000
+
+# Bundler exposing debug_bundler_dumpMempool, to watch ERC-4337 UserOperations; empty disables
+# EntryPoint defaults to the canonical v0.6 deployment
+BUNDLER_URL=
+ENTRY_POINT_ADDRESS=0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789
+USER_OPERATION_MAX_OPS=1000
+USER_OPERATION_POLL_MS=500
+
+# Recorded pending transactions (JSONL) to replay as a backtest; empty disables
+# Speed 1.0 keeps the recorded pace, 0 replays without pauses
+MEMPOOL_CAPTURE_PATH=
+MEMPOOL_CAPTURE_SPEED=1.0
+MEMPOOL_CAPTURE_MAX_TXS=100000
+
+# ETH/USD price sources (unset all to use a fixed \$2000)
+CHAINLINK_ETH_USD_FEED=
+CHAINLINK_MAX_AGE_SECS=3600
+PYTH_CONTRACT_ADDRESS=
+PYTH_ETH_USD_PRICE_ID=0xff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace
+PYTH_MAX_AGE_SECS=60
+# Confidence intervals the price may move against a liquidation (0 disables)
+PRICE_CONFIDENCE_BAND=1.0
+TWAP_POOL_ADDRESS=
+TWAP_WINDOW_SECS=1800
+TWAP_ETH_IS_TOKEN0=false
+MAX_PRICE_DIVERGENCE_BPS=200
+
+# Flash liquidity to fund repayments: Balancer vault and comma-separated Uniswap V3
+# pools; the cheapest route after fees and gas is chosen (empty = wallet inventory)
+BALANCER_VAULT_ADDRESS=
+UNISWAP_V3_FLASH_POOLS=
+
+# Liquidations go through this contract (flash loan + liquidate + swap in one tx);
+# empty calls the protocol directly. HELPER_SWAP_POOL is used by
+# \`liquidio deploy-helper\` when deploying a new one
+LIQUIDATION_HELPER_ADDRESS=$HELPER_ADDRESS
+WETH_ADDRESS=
+HELPER_SWAP_POOL=
+# Send up to BATCH_MAX_SIZE liquidations found together as one helper
+# liquidateBatch, for positions repaying at most BATCH_MAX_REPAY_USD (0 = off)
+BATCH_MAX_SIZE=0
+BATCH_MAX_REPAY_USD=5000
+# Venus on BNB Chain: Comptroller to read risk params from, and the vTokens
+# to liquidateBorrow (debt repaid into the first, collateral seized from the second)
+VENUS_COMPTROLLER_ADDRESS=
+VENUS_BORROWED_VTOKEN=
+VENUS_COLLATERAL_VTOKEN=
+# Morpho Blue singleton and market id to liquidate through the helper
+# (its loan token must be the debt asset and its collateral WETH)
+MORPHO_BLUE_ADDRESS=
+MORPHO_MARKET_ID=
+# Euler V2 liability vault; bonus follows its health-score discount curve
+EULER_LIABILITY_VAULT=
+
+# Value collateral worth over SLIPPAGE_MIN_VALUE_USD at what these venues pay for it
+# (Uniswap V3 QuoterV2 needs WETH_ADDRESS); empty values it at the oracle price
+UNISWAP_V3_QUOTER_ADDRESS=
+COLLATERAL_SWAP_FEE_TIER=500
+CURVE_POOL_ADDRESS=
+CURVE_ETH_INDEX=0
+CURVE_DEBT_INDEX=1
+SLIPPAGE_MIN_VALUE_USD=10000
+
+# Backtests sell seized collateral at scenario prices after this swap fee, with
+# price impact from a constant-product pool this deep (USD per side; inf = none)
+BACKTEST_SALE_FEE_BPS=30
+BACKTEST_SALE_DEPTH_USD=5000000
+
+# Re-read positions below this health factor on every price tick
+WATCHLIST_HF_BAND=1.05
+# ...and on every new block (interest accrues and oracles update per block)
+WATCHLIST_BLOCK_SWEEP=true
+
+# Health factor encoding of getPosition: percent (100 = 1.0), wad (1e18 = 1.0,
+# Aave-style) or the raw value of 1.0; liquidatable below LIQUIDATION_HF_THRESHOLD
+HEALTH_FACTOR_SCALE=percent
+LIQUIDATION_HF_THRESHOLD=1.0
+
+# Per-user health factor history (empty path keeps it in memory only)
+POSITION_HISTORY_PATH=benchmark_results/position_history.jsonl
+POSITION_HISTORY_MAX_SAMPLES=1000
+# Recent opportunities ranked on /opportunities
+OPPORTUNITY_FEED_SIZE=1000
+# Shortest gap between snapshots pushed to /dashboard
+DASHBOARD_PUSH_MS=1000
+# Events an /events subscriber may fall behind by before missing some
+EVENT_BUFFER_SIZE=1024
+
+# Optional subgraph listing active borrowers, read once at startup to seed the
+# detector: entities with id = borrower address and a decimal health factor
+SUBGRAPH_URL=
+SUBGRAPH_ENTITY=users
+SUBGRAPH_HEALTH_FACTOR_FIELD=healthFactor
+SUBGRAPH_PAGE_SIZE=1000
+
+# Report stage latencies with nanosecond precision (default whole microseconds)
+LATENCY_NANOSECONDS=false
+
+# Commit recorded in reports when running outside a git checkout (empty = git rev-parse HEAD)
+GIT_COMMIT=
+
+# Backtesting (synthetic mempool traffic)
+BACKTEST_SEED=42
+SYNTHETIC_USER_POPULATION=1000
+SYNTHETIC_TX_MIX=4,3,2,1
+# Mean transactions per second, spaced fixed, poisson, bursty (runs of
+# SYNTHETIC_BURST_SIZE back-to-back) or firehose (no delay, for throughput runs)
+SYNTHETIC_TPS=10000
+SYNTHETIC_ARRIVALS=fixed
+SYNTHETIC_BURST_SIZE=50
+# Share of synthetic transactions that don't touch the protocol (ERC20 transfers,
+# DEX swaps, other contracts), so backtests pay the classifier's filtering cost
+SYNTHETIC_NOISE_RATIO=0
+# Track synthetic users instead of on-chain positions (off = chain-backed detector)
+SYNTHETIC_POPULATION=false
+
+# Rival bots raced for every backtest opportunity (0 = capture everything)
+COMPETITOR_BOTS=0
+COMPETITOR_LATENCY_MS=1,50
+COMPETITOR_BID_SHARE=0.1,0.5
+AUCTION_BID_SHARE=0.3
+BLOCK_TIME_MS=12000
+
+# Monte Carlo backtest: repeated runs with seeds BACKTEST_SEED, +1, ... (0 = off)
+MONTE_CARLO_RUNS=0
+MONTE_CARLO_TXS_PER_RUN=5000
+
+# Parameter sweep: comma-separated values per parameter; every combination is
+# backtested on the same traffic (all empty = off)
+SWEEP_MIN_PROFIT_USD=
+SWEEP_BID_SHARE=
+SWEEP_WATCH_BAND=
+SWEEP_TXS_PER_RUN=5000
+
+# Stress scenarios: comma-separated JSON scenario files added to the built-in
+# ones, and the scenario whose base-fee trajectory backtests price gas at
+STRESS_SCENARIO_FILES=
+BACKTEST_GAS_SCENARIO=
 
 # Logging
 RUST_LOG=info,liquidio=debug
@@ -142,6 +456,7 @@ echo "Deployment Complete!"
 echo "===================="
 echo "Lending Protocol: $PROTOCOL_ADDRESS"
 echo "Stablecoin: $STABLECOIN_ADDRESS"
+echo "Liquidation Helper: $HELPER_ADDRESS"
 echo "Anvil PID: $ANVIL_PID"
 echo ""
 echo "Configuration saved to .env"
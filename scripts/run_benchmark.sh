@@ -38,17 +38,18 @@ fi
 echo "[OK] Build complete"
 echo ""
 
-# Run the bot
+# Run the bot (extra arguments, e.g. --out-dir, are passed through)
 echo "Running benchmark suite..."
 echo ""
-cargo run --release
+cargo run --release -- "$@"
 
 echo ""
 echo "[OK] Benchmark complete!"
 echo ""
-echo "Results saved to:"
+echo "Results saved to (unless --out-dir or --report-name changed them):"
 echo "   - benchmark_results/transaction_stream_backtest.csv"
 echo "   - benchmark_results/transaction_stream_backtest.json"
 echo "   - benchmark_results/latency_stress_test.csv"
 echo "   - benchmark_results/latency_stress_test.json"
+echo "   - benchmark_results/stress_*.json"
 echo ""
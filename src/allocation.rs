@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::thread::ThreadId;
+use std::time::Instant;
+
+#[cfg(feature = "alloc-profiling")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    pub static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    pub static BYTES: AtomicU64 = AtomicU64::new(0);
+
+    thread_local! {
+        // Const-initialized without a destructor, so reading it never allocates
+        pub static THREAD: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+    }
+
+    fn count(size: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(size as u64, Ordering::Relaxed);
+        let _ = THREAD.try_with(|counts| {
+            let (allocations, bytes) = counts.get();
+            counts.set((allocations + 1, bytes + size as u64));
+        });
+    }
+
+    /// System allocator counting every allocation and reallocation
+    pub struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count(layout.size());
+            System.alloc(layout)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            count(layout.size());
+            System.alloc_zeroed(layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count(new_size);
+            System.realloc(ptr, layout, new_size)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+}
+
+/// Whether this build counts allocations (`--features alloc-profiling`);
+/// otherwise every mark is None and reports carry no memory profile
+pub fn enabled() -> bool {
+    cfg!(feature = "alloc-profiling")
+}
+
+/// Allocations (and bytes) made so far by the whole process
+pub fn totals() -> Option<(u64, u64)> {
+    #[cfg(feature = "alloc-profiling")]
+    {
+        use std::sync::atomic::Ordering;
+        Some((counting::ALLOCATIONS.load(Ordering::Relaxed), counting::BYTES.load(Ordering::Relaxed)))
+    }
+    #[cfg(not(feature = "alloc-profiling"))]
+    None
+}
+
+/// Allocation counters of the current thread at one point in the pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocMark {
+    thread: ThreadId,
+    allocations: u64,
+    bytes: u64,
+}
+
+impl AllocMark {
+    /// This thread's counters now (None unless allocations are counted)
+    pub fn now() -> Option<Self> {
+        #[cfg(feature = "alloc-profiling")]
+        {
+            let thread = std::thread::current().id();
+            let (allocations, bytes) = counting::THREAD.try_with(|counts| counts.get()).ok()?;
+            Some(Self { thread, allocations, bytes })
+        }
+        #[cfg(not(feature = "alloc-profiling"))]
+        None
+    }
+
+    /// Allocations and bytes between this mark and `later`
+    ///
+    /// Only a stage that stayed on one thread can be attributed; a task that
+    /// moved between workers at an await gives None.
+    pub fn until(&self, later: &AllocMark) -> Option<(u64, u64)> {
+        (self.thread == later.thread && later.allocations >= self.allocations)
+            .then(|| (later.allocations - self.allocations, later.bytes - self.bytes))
+    }
+
+    #[cfg(test)]
+    fn on_this_thread(allocations: u64, bytes: u64) -> Self {
+        Self { thread: std::thread::current().id(), allocations, bytes }
+    }
+}
+
+/// Allocations made by one pipeline stage across a run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageAllocations {
+    /// Stages attributed; those that hopped threads are left out
+    pub samples: u64,
+    pub allocations: u64,
+    pub bytes: u64,
+    /// Time spent in the attributed samples, for the allocation rate
+    pub busy_us: f64,
+}
+
+impl StageAllocations {
+    pub fn record(&mut self, allocations: u64, bytes: u64, busy_us: f64) {
+        self.samples += 1;
+        self.allocations += allocations;
+        self.bytes += bytes;
+        self.busy_us += busy_us;
+    }
+
+    pub fn allocations_per_op(&self) -> f64 {
+        self.allocations as f64 / self.samples.max(1) as f64
+    }
+
+    pub fn bytes_per_op(&self) -> f64 {
+        self.bytes as f64 / self.samples.max(1) as f64
+    }
+
+    /// Allocations per second of time spent in the stage
+    pub fn allocations_per_sec(&self) -> f64 {
+        if self.busy_us <= 0.0 {
+            return 0.0;
+        }
+        self.allocations as f64 / (self.busy_us / 1e6)
+    }
+}
+
+/// Peak RSS, process-wide allocation rate and per-stage allocations of one run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryProfile {
+    /// High-water resident set size (Linux `VmHWM`)
+    pub peak_rss_bytes: Option<u64>,
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+    pub allocations_per_sec: f64,
+    pub stages: BTreeMap<String, StageAllocations>,
+}
+
+impl MemoryProfile {
+    pub fn stage(&mut self, stage: &str) -> &mut StageAllocations {
+        self.stages.entry(stage.to_string()).or_default()
+    }
+}
+
+/// Process-wide counters at the start of a run
+#[derive(Debug, Clone, Copy)]
+pub struct AllocationWindow {
+    started: Instant,
+    allocations: u64,
+    bytes: u64,
+}
+
+impl AllocationWindow {
+    /// None unless allocations are counted
+    pub fn start() -> Option<Self> {
+        let (allocations, bytes) = totals()?;
+        Some(Self { started: Instant::now(), allocations, bytes })
+    }
+
+    /// Fill in `profile`'s run totals and peak RSS
+    pub fn finish(&self, profile: &mut MemoryProfile) {
+        let (allocations, bytes) = totals().unwrap_or((self.allocations, self.bytes));
+        profile.allocations = allocations - self.allocations;
+        profile.allocated_bytes = bytes - self.bytes;
+        profile.allocations_per_sec = profile.allocations as f64 / self.started.elapsed().as_secs_f64().max(1e-9);
+        profile.peak_rss_bytes = peak_rss_bytes();
+    }
+}
+
+/// Peak resident set size of this process, where the OS reports it
+pub fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_hwm(&status)
+}
+
+fn parse_vm_hwm(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.trim_start_matches("VmHWM:").trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_attribution() {
+        let before = AllocMark::on_this_thread(10, 1_000);
+        let after = AllocMark::on_this_thread(13, 1_600);
+        assert_eq!(before.until(&after), Some((3, 600)));
+
+        let elsewhere = std::thread::spawn(|| AllocMark::on_this_thread(20, 2_000)).join().unwrap();
+        assert_eq!(before.until(&elsewhere), None);
+
+        let mut stage = StageAllocations::default();
+        stage.record(3, 600, 2.0);
+        stage.record(1, 200, 2.0);
+        assert_eq!(stage.allocations_per_op(), 2.0);
+        assert_eq!(stage.allocations_per_sec(), 1e6);
+
+        assert_eq!(parse_vm_hwm("Name:\tliquidio\nVmHWM:\t  204800 kB\n"), Some(204_800 * 1024));
+    }
+}
@@ -0,0 +1,224 @@
+use anyhow::{Context, Result};
+use ethers::types::{Address, H256};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::liquidation_detector::LiquidationSignal;
+use crate::metrics::{inclusion_latency, now_ms};
+
+/// How long a submitted transaction is polled for a receipt before it counts as dropped
+const RECEIPT_POLL_ATTEMPTS: u32 = 60;
+
+/// Pipeline stage an audit event records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditStage {
+    Detected,
+    Simulated,
+    /// Dropped before submission (unprofitable, paused, over exposure limit)
+    Skipped,
+    Constructed,
+    Submitted,
+    Mined,
+    Reverted,
+    /// Simulation, construction or submission errored, or the tx was never mined
+    Failed,
+}
+
+/// One line of the audit journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub opportunity_id: String,
+    pub stage: AuditStage,
+    pub timestamp_ms: u64,
+    pub user: Address,
+    #[serde(default)]
+    pub details: Value,
+}
+
+/// Append-only JSONL journal of every opportunity's path through the pipeline
+///
+/// Each event is flushed as it is written so a crash mid-run still leaves a
+/// complete trail up to that point for post-mortems.
+pub struct AuditJournal {
+    path: PathBuf,
+    file: Mutex<File>,
+    /// Prefix keeping opportunity ids unique across runs appending to the same file
+    run_id: u64,
+    next_id: AtomicU64,
+}
+
+impl AuditJournal {
+    /// Open (or create) the journal at `path` for appending
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open audit journal {}", path.display()))?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            run_id: now_ms(),
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Assign an opportunity id to `signal` and record its detection
+    pub fn begin(&self, signal: &LiquidationSignal) -> String {
+        let id = format!("{}-{}", self.run_id, self.next_id.fetch_add(1, Ordering::Relaxed));
+        let detection_us = signal.metrics.latency_signal_detection().map(|d| d.as_secs_f64() * 1e6);
+        self.record(&id, AuditStage::Detected, signal.user, json!({
+            "collateral": signal.collateral.raw,
+            "collateral_decimals": signal.collateral.decimals,
+            "debt": signal.debt.raw,
+            "debt_decimals": signal.debt.decimals,
+            "health_factor": signal.health_factor.raw,
+            "health_factor_one": signal.health_factor.scale.one,
+            "collateral_asset": signal.collateral_asset,
+            "debt_asset": signal.debt_asset,
+            "observed_block": signal.observed_block,
+            "signal_detection_us": detection_us,
+        }));
+        id
+    }
+
+    /// Append one event; journal write errors are logged, never propagated into the pipeline
+    pub fn record(&self, opportunity_id: &str, stage: AuditStage, user: Address, details: Value) {
+        let event = AuditEvent {
+            opportunity_id: opportunity_id.to_string(),
+            stage,
+            timestamp_ms: now_ms(),
+            user,
+            details,
+        };
+        let line = match serde_json::to_string(&event) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize audit event: {}", e);
+                return;
+            }
+        };
+
+        let mut file = self.file.lock().unwrap();
+        if let Err(e) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+            warn!("Failed to write audit journal {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// Poll for `tx_hash`'s receipt and record whether it was mined or reverted
+    pub async fn track_receipt(
+        self: Arc<Self>,
+        blockchain: Arc<BlockchainClient>,
+        opportunity_id: String,
+        user: Address,
+        tx_hash: H256,
+        sent_unix_ns: Option<u64>,
+    ) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(1));
+        for _ in 0..RECEIPT_POLL_ATTEMPTS {
+            ticker.tick().await;
+            match blockchain.get_transaction_receipt(tx_hash).await {
+                Ok(Some(receipt)) => {
+                    let stage = if receipt.status.map(|s| s.as_u64()) == Some(1) {
+                        AuditStage::Mined
+                    } else {
+                        AuditStage::Reverted
+                    };
+                    let block = match receipt.block_number {
+                        Some(number) => blockchain.get_block(number.as_u64()).await.ok().flatten(),
+                        None => None,
+                    };
+                    let block_timestamp = block.map(|b| b.timestamp.low_u64());
+                    let submit_to_inclusion_ms = sent_unix_ns
+                        .zip(block_timestamp)
+                        .map(|(sent, timestamp)| inclusion_latency(sent, timestamp).as_secs_f64() * 1e3);
+                    self.record(&opportunity_id, stage, user, json!({
+                        "tx_hash": tx_hash,
+                        "block_number": receipt.block_number,
+                        "submit_to_inclusion_ms": submit_to_inclusion_ms,
+                        "gas_used": receipt.gas_used,
+                        "effective_gas_price": receipt.effective_gas_price,
+                    }));
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => debug!("Receipt poll for {:?} failed: {}", tx_hash, e),
+            }
+        }
+        self.record(&opportunity_id, AuditStage::Failed, user, json!({
+            "tx_hash": tx_hash,
+            "error": "no receipt before timeout",
+        }));
+    }
+}
+
+/// Read every event from a journal, e.g. for a post-mortem of one opportunity
+pub fn read_journal(path: &Path) -> Result<Vec<AuditEvent>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map(|l| !l.trim().is_empty()).unwrap_or(true))
+        .map(|(i, line)| {
+            let line = line?;
+            serde_json::from_str(&line).with_context(|| format!("Invalid audit event on line {}", i + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::LatencyMetrics;
+    use crate::protocol::HealthFactorScale;
+    use crate::units::{HealthFactor, TokenAmount};
+    use ethers::types::U256;
+
+    #[test]
+    fn test_journal_round_trip() {
+        let path = std::env::temp_dir().join(format!("liquidio_audit_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let journal = AuditJournal::open(&path).unwrap();
+        let signal = LiquidationSignal {
+            user: Address::from_low_u64_be(7),
+            collateral: TokenAmount::wad(U256::from(5u64)),
+            debt: TokenAmount::wad(U256::from(9u64)),
+            health_factor: HealthFactor::new(U256::from(90), HealthFactorScale::PERCENT),
+            collateral_asset: Address::zero(),
+            debt_asset: Address::zero(),
+            trigger: None,
+            observed_block: None,
+            stale: false,
+            metrics: LatencyMetrics::new(),
+        };
+
+        let id = journal.begin(&signal);
+        journal.record(&id, AuditStage::Skipped, signal.user, json!({ "reason": "unprofitable" }));
+
+        let events = read_journal(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.opportunity_id == id && e.user == signal.user));
+        assert_eq!(events[0].stage, AuditStage::Detected);
+        assert_eq!(events[0].details["health_factor"], json!(U256::from(90)));
+        assert_eq!(events[1].details["reason"], "unprofitable");
+    }
+}
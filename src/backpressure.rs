@@ -0,0 +1,253 @@
+use ethers::types::{Address, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+use crate::mempool_streamer::TransactionClassifier;
+
+/// What a transaction source does when the detector falls behind and its buffer is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Wait for the detector (the source stalls)
+    Block,
+    /// Discard the oldest buffered transaction to make room
+    DropOldest,
+    /// Discard transactions not sent to the protocol, oldest first; protocol
+    /// transactions are never dropped and wait for room instead
+    DropNonProtocol,
+    /// Double the buffer up to `max_capacity`, then wait
+    Expand { max_capacity: usize },
+}
+
+impl BackpressurePolicy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackpressurePolicy::Block => "block",
+            BackpressurePolicy::DropOldest => "drop-oldest",
+            BackpressurePolicy::DropNonProtocol => "drop-non-protocol",
+            BackpressurePolicy::Expand { .. } => "expand",
+        }
+    }
+}
+
+/// Buffer between a transaction source and the detector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferConfig {
+    pub capacity: usize,
+    pub policy: BackpressurePolicy,
+    /// Lending protocol whose transactions `DropNonProtocol` keeps
+    pub protocol: Address,
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self { capacity: 1000, policy: BackpressurePolicy::Block, protocol: Address::zero() }
+    }
+}
+
+/// How the buffer coped with overload
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackpressureStats {
+    pub policy: String,
+    /// Current capacity (grows under `expand`)
+    pub capacity: usize,
+    /// Most transactions buffered at once
+    pub high_water_mark: usize,
+    /// Sends that had to wait for the detector
+    pub blocked_sends: usize,
+    pub dropped_oldest: usize,
+    pub dropped_non_protocol: usize,
+    pub expansions: usize,
+}
+
+impl BackpressureStats {
+    pub fn dropped(&self) -> usize {
+        self.dropped_oldest + self.dropped_non_protocol
+    }
+}
+
+struct Shared {
+    queue: Mutex<(VecDeque<Transaction>, BackpressureStats)>,
+    policy: BackpressurePolicy,
+    protocol: Address,
+    // Wakes the receiver on a new transaction, and a blocked sender on free room
+    readable: Notify,
+    writable: Notify,
+    sender_closed: AtomicBool,
+    receiver_closed: AtomicBool,
+}
+
+/// Bounded transaction channel applying `config.policy` when full
+pub fn channel(config: BufferConfig) -> (TxSender, TxReceiver) {
+    let capacity = config.capacity.max(1);
+    let stats = BackpressureStats { policy: config.policy.name().to_string(), capacity, ..Default::default() };
+    let shared = Arc::new(Shared {
+        queue: Mutex::new((VecDeque::with_capacity(capacity), stats)),
+        policy: config.policy,
+        protocol: config.protocol,
+        readable: Notify::new(),
+        writable: Notify::new(),
+        sender_closed: AtomicBool::new(false),
+        receiver_closed: AtomicBool::new(false),
+    });
+    (TxSender { shared: shared.clone() }, TxReceiver { shared })
+}
+
+/// Receiver side is gone; the transaction is handed back
+#[derive(Debug)]
+pub struct Closed(pub Transaction);
+
+pub struct TxSender {
+    shared: Arc<Shared>,
+}
+
+impl TxSender {
+    /// Buffer `tx`, waiting or dropping according to the policy when full
+    pub async fn send(&self, tx: Transaction) -> Result<(), Closed> {
+        let mut waited = false;
+        loop {
+            if self.shared.receiver_closed.load(Ordering::Acquire) {
+                return Err(Closed(tx));
+            }
+            if self.try_push(&tx, waited) {
+                self.shared.readable.notify_one();
+                return Ok(());
+            }
+            waited = true;
+            self.shared.writable.notified().await;
+        }
+    }
+
+    /// Push `tx` if the policy makes room for it; false if the sender must wait
+    fn try_push(&self, tx: &Transaction, waited: bool) -> bool {
+        let mut guard = self.shared.queue.lock().unwrap();
+        let (queue, stats) = &mut *guard;
+        if queue.len() >= stats.capacity {
+            match self.shared.policy {
+                BackpressurePolicy::Block => {}
+                BackpressurePolicy::DropOldest => {
+                    queue.pop_front();
+                    stats.dropped_oldest += 1;
+                }
+                BackpressurePolicy::DropNonProtocol => {
+                    let protocol = self.shared.protocol;
+                    if !TransactionClassifier::is_protocol_transaction(tx, protocol) {
+                        stats.dropped_non_protocol += 1;
+                        return true;
+                    }
+                    let evict = queue.iter().position(|queued| !TransactionClassifier::is_protocol_transaction(queued, protocol));
+                    if let Some(i) = evict {
+                        queue.remove(i);
+                        stats.dropped_non_protocol += 1;
+                    }
+                }
+                BackpressurePolicy::Expand { max_capacity } => {
+                    if stats.capacity < max_capacity {
+                        stats.capacity = (stats.capacity * 2).min(max_capacity);
+                        stats.expansions += 1;
+                    }
+                }
+            }
+        }
+        if queue.len() >= stats.capacity {
+            if !waited {
+                stats.blocked_sends += 1;
+            }
+            return false;
+        }
+        queue.push_back(tx.clone());
+        stats.high_water_mark = stats.high_water_mark.max(queue.len());
+        true
+    }
+}
+
+impl Drop for TxSender {
+    fn drop(&mut self) {
+        self.shared.sender_closed.store(true, Ordering::Release);
+        self.shared.readable.notify_one();
+    }
+}
+
+pub struct TxReceiver {
+    shared: Arc<Shared>,
+}
+
+impl TxReceiver {
+    /// Next buffered transaction; None once the sender is dropped and the buffer drained
+    pub async fn recv(&mut self) -> Option<Transaction> {
+        loop {
+            if let Some(tx) = self.shared.queue.lock().unwrap().0.pop_front() {
+                self.shared.writable.notify_one();
+                return Some(tx);
+            }
+            if self.shared.sender_closed.load(Ordering::Acquire) {
+                // The sender may have pushed right before closing
+                return self.shared.queue.lock().unwrap().0.pop_front();
+            }
+            self.shared.readable.notified().await;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared.queue.lock().unwrap().0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> BackpressureStats {
+        self.shared.queue.lock().unwrap().1.clone()
+    }
+}
+
+impl Drop for TxReceiver {
+    fn drop(&mut self) {
+        self.shared.receiver_closed.store(true, Ordering::Release);
+        self.shared.writable.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(nonce: u64, to: Address) -> Transaction {
+        Transaction { nonce: nonce.into(), to: Some(to), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn test_overload_policies() {
+        let protocol = Address::from_low_u64_be(1);
+        let other = Address::from_low_u64_be(2);
+
+        let buffer = |policy| BufferConfig { capacity: 2, policy, protocol };
+        let (sender, mut rx) = channel(buffer(BackpressurePolicy::DropOldest));
+        for nonce in 0..4 {
+            sender.send(tx(nonce, protocol)).await.unwrap();
+        }
+        drop(sender);
+        assert_eq!(rx.stats().dropped_oldest, 2);
+        assert_eq!(rx.recv().await.map(|t| t.nonce.as_u64()), Some(2));
+        assert_eq!(rx.recv().await.map(|t| t.nonce.as_u64()), Some(3));
+        assert!(rx.recv().await.is_none());
+
+        // Protocol transactions push out the buffered non-protocol one; later noise is discarded
+        let (sender, rx) = channel(buffer(BackpressurePolicy::DropNonProtocol));
+        sender.send(tx(0, other)).await.unwrap();
+        sender.send(tx(1, protocol)).await.unwrap();
+        sender.send(tx(2, protocol)).await.unwrap();
+        sender.send(tx(3, other)).await.unwrap();
+        assert_eq!(rx.len(), 2);
+        assert_eq!(rx.stats().dropped_non_protocol, 2);
+
+        let (sender, rx) = channel(buffer(BackpressurePolicy::Expand { max_capacity: 5 }));
+        for nonce in 0..5 {
+            sender.send(tx(nonce, other)).await.unwrap();
+        }
+        let stats = rx.stats();
+        assert_eq!((stats.capacity, stats.expansions, stats.high_water_mark), (5, 2, 5));
+    }
+}
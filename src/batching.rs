@@ -0,0 +1,101 @@
+use ethers::types::U256;
+
+use crate::simulator::SimulationResult;
+
+/// Gas a liquidation transaction pays once however many positions it closes:
+/// the intrinsic 21k plus the helper call and the loan and sale around it
+pub const SHARED_GAS: u64 = 60_000;
+
+/// Which simultaneous opportunities go out together through the helper's
+/// `liquidateBatch`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BatchPolicy {
+    /// Most liquidations in one transaction (below 2 never batches)
+    pub max_size: usize,
+    /// Only positions repaying at most this much are batched; larger ones
+    /// carry their own overhead easily and shouldn't risk a shared revert
+    pub max_repay_usd: f64,
+}
+
+impl BatchPolicy {
+    pub fn enabled(&self) -> bool {
+        self.max_size >= 2
+    }
+
+    /// Split `simulations` into groups to send as one transaction each, by index
+    ///
+    /// A batch borrows its total from one lender, so only liquidations funded
+    /// the same way are grouped. Large positions, and small ones left without
+    /// a partner, come back as groups of one.
+    pub fn plan(&self, simulations: &[&SimulationResult]) -> Vec<Vec<usize>> {
+        let mut singles = Vec::new();
+        let mut batches: Vec<Vec<usize>> = Vec::new();
+        for (index, simulation) in simulations.iter().enumerate() {
+            if !self.enabled() || simulation.repay_usd() > self.max_repay_usd {
+                singles.push(vec![index]);
+                continue;
+            }
+            let open = batches.iter_mut().find(|batch| {
+                batch.len() < self.max_size && simulations[batch[0]].funding.source == simulation.funding.source
+            });
+            match open {
+                Some(batch) => batch.push(index),
+                None => batches.push(vec![index]),
+            }
+        }
+        batches.extend(singles);
+        batches
+    }
+}
+
+/// Gas a batch of `size` liquidations saves over sending each on its own
+pub fn shared_gas_saved(size: usize) -> U256 {
+    U256::from(SHARED_GAS * size.saturating_sub(1) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::funding::{FundingQuote, FundingSource};
+    use crate::units::TokenAmount;
+    use ethers::types::Address;
+
+    fn simulation(repay_usd: u64, funding: FundingQuote) -> SimulationResult {
+        SimulationResult {
+            profitable: true,
+            expected_profit_usd: 20.0,
+            worst_case_profit_usd: 20.0,
+            collateral_to_seize: TokenAmount::wad(U256::exp10(18)),
+            debt_to_cover: TokenAmount::wad(U256::from(repay_usd) * U256::exp10(18)),
+            estimated_gas: U256::from(200_000),
+            estimated_gas_cost_usd: 10.0,
+            l1_data_fee_usd: 0.0,
+            liquidation_bonus_bps: 1_000,
+            close_factor_bps: 5_000,
+            eth_price_usd: 2000.0,
+            funding,
+            funding_cost_usd: 0.0,
+            slippage_usd: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_plan_groups_small_positions_by_funding() {
+        let policy = BatchPolicy { max_size: 2, max_repay_usd: 1_000.0 };
+        let vault = Address::from_low_u64_be(1);
+        let flash = FundingQuote { source: FundingSource::BalancerFlashLoan { vault }, ..FundingQuote::inventory() };
+        let simulations = [
+            simulation(500, FundingQuote::inventory()),
+            simulation(800, flash),
+            simulation(50_000, FundingQuote::inventory()),
+            simulation(300, FundingQuote::inventory()),
+            simulation(200, FundingQuote::inventory()),
+            simulation(900, flash),
+        ];
+        let simulations: Vec<_> = simulations.iter().collect();
+
+        assert_eq!(policy.plan(&simulations), vec![vec![0, 3], vec![1, 5], vec![4], vec![2]]);
+        assert_eq!(BatchPolicy::default().plan(&simulations).len(), 6);
+        assert_eq!(shared_gas_saved(3), U256::from(120_000));
+    }
+}
@@ -1,11 +1,18 @@
-use anyhow::Result;
 use ethers::{
-    providers::{Provider, Ws, Http, Middleware},
-    types::{Block, Transaction, TransactionReceipt, Address, U256, H256},
-    contract::abigen,
+    abi::{AbiDecode, AbiError},
+    providers::{call_raw::{spoof, RawCall}, Provider, ProviderError, Ws, Middleware},
+    types::{Block, BlockNumber, Bytes, Transaction, TransactionReceipt, Address, U256, U64, H256},
+    contract::{abigen, ContractError, LogMeta},
 };
+use futures::StreamExt;
 use std::sync::Arc;
-use tracing::{debug, info};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+use crate::read_cache::{CacheKey, CacheStats, ReadCache};
+use crate::rpc_batch::{BatchStats, RpcBatcher};
+use crate::transport::RpcTransport;
 
 // Generate contract bindings
 abigen!(
@@ -19,6 +26,10 @@ abigen!(
         function getHealthFactor(address user) external view returns (uint256)
         function isLiquidatable(address user) external view returns (bool)
         function getPosition(address user) external view returns (uint256 collateral, uint256 debt, uint256 healthFactor)
+        function ethPriceUSD() external view returns (uint256)
+        function LIQUIDATION_BONUS() external view returns (uint256)
+        function PRECISION() external view returns (uint256)
+        function setEthPrice(uint256 newPrice) external
         event Deposit(address indexed user, uint256 amount)
         event Withdraw(address indexed user, uint256 amount)
         event Borrow(address indexed user, uint256 amount)
@@ -34,17 +45,60 @@ abigen!(
         function transfer(address to, uint256 amount) external returns (bool)
         function balanceOf(address account) external view returns (uint256)
         function allowance(address owner, address spender) external view returns (uint256)
+        function mint(address to, uint256 amount) external
     ]"#
 );
 
-pub type HttpProvider = Provider<Http>;
+pub type RpcProvider = Provider<RpcTransport>;
 pub type WsProvider = Provider<Ws>;
 
+pub type Result<T, E = RpcError> = std::result::Result<T, E>;
+
+/// Why a request to the node failed
+#[derive(Debug, thiserror::Error)]
+pub enum RpcError {
+    /// Transport failure or an error response from the node
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// A contract call reverted or could not be made
+    #[error(transparent)]
+    Contract(#[from] ContractError<RpcProvider>),
+    /// Call output that doesn't match the expected ABI
+    #[error("Undecodable call output: {0}")]
+    Decode(#[from] AbiError),
+    /// A request sent through the JSON-RPC batcher failed
+    #[error(transparent)]
+    Batch(anyhow::Error),
+    /// Could not connect to the endpoint
+    #[error(transparent)]
+    Connect(anyhow::Error),
+    #[error("Connected node reports chain id {actual} but CHAIN_ID is {expected}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
+    /// The node lacks something the request needs (a block, a subscription)
+    #[error("{0}")]
+    Unavailable(&'static str),
+}
+
+impl RpcError {
+    /// Worth retrying as is: the node or the connection failed, rather than
+    /// the request itself being wrong
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RpcError::Provider(_) | RpcError::Batch(_) | RpcError::Connect(_) => true,
+            RpcError::Contract(e) => e.as_revert().is_none(),
+            RpcError::Decode(_) | RpcError::ChainIdMismatch { .. } | RpcError::Unavailable(_) => false,
+        }
+    }
+}
+
 pub struct BlockchainClient {
-    pub http_provider: Arc<HttpProvider>,
+    /// Request/response provider, over HTTP or a local IPC socket
+    pub http_provider: Arc<RpcProvider>,
     pub ws_provider: Option<Arc<WsProvider>>,
-    pub lending_protocol: LendingProtocol<HttpProvider>,
-    pub token: ERC20<HttpProvider>,
+    pub lending_protocol: LendingProtocol<RpcProvider>,
+    pub token: ERC20<RpcProvider>,
+    batcher: Option<RpcBatcher>,
+    read_cache: Option<ReadCache>,
 }
 
 impl BlockchainClient {
@@ -56,8 +110,21 @@ impl BlockchainClient {
     ) -> Result<Self> {
         info!("Connecting to blockchain at {}", rpc_url);
         
-        let http_provider = Provider::<Http>::try_from(rpc_url)?;
-        let http_provider = Arc::new(http_provider);
+        // A path or ipc:// endpoint connects over IPC to a co-located node
+        let transport = RpcTransport::connect(rpc_url).await.map_err(RpcError::Connect)?;
+        Self::with_transport(transport, ws_url, protocol_address, token_address).await
+    }
+    
+    /// Client sending requests over an already configured `transport`
+    /// (e.g. one carrying a rate limit)
+    pub async fn with_transport(
+        transport: RpcTransport,
+        ws_url: Option<&str>,
+        protocol_address: Address,
+        token_address: Address,
+    ) -> Result<Self> {
+        debug!("RPC transport: {}", transport.name());
+        let http_provider = Arc::new(Provider::new(transport));
         
         let ws_provider = if let Some(ws_url) = ws_url {
             debug!("Connecting WebSocket at {}", ws_url);
@@ -77,14 +144,109 @@ impl BlockchainClient {
             ws_provider,
             lending_protocol,
             token,
+            batcher: None,
+            read_cache: None,
         })
     }
     
+    /// Send position, receipt, gas price and block number lookups made within
+    /// `window` of each other as one JSON-RPC batch (at most `max_batch` each)
+    ///
+    /// IPC calls are cheap enough that batching is skipped there.
+    pub fn with_batching(mut self, window: Duration, max_batch: usize) -> Self {
+        let transport = self.http_provider.as_ref().as_ref();
+        match transport.url() {
+            Some(url) => self.batcher = Some(RpcBatcher::new(url.as_str(), window, max_batch, transport.rate_limit().cloned())),
+            None => debug!("Not batching RPC lookups over IPC"),
+        }
+        self
+    }
+    
+    /// Lookups sent through the batcher and the batches they shared
+    pub fn batch_stats(&self) -> Option<BatchStats> {
+        self.batcher.as_ref().map(RpcBatcher::stats)
+    }
+    
+    /// Reuse position and gas price reads until a newer block is observed,
+    /// and for at most `ttl` in case no new head is reported
+    pub fn with_read_cache(mut self, ttl: Duration) -> Self {
+        self.read_cache = Some(ReadCache::new(ttl));
+        self
+    }
+    
+    /// Record `block` as the chain head, invalidating reads cached for older blocks
+    pub fn observe_block(&self, block: u64) {
+        if let Some(cache) = &self.read_cache {
+            cache.observe_block(block);
+        }
+    }
+    
+    /// `fetch`, unless a value for `key` was already read in this block
+    pub async fn cached<T, E, F, Fut>(&self, key: CacheKey, fetch: F) -> Result<T, E>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<T, E>>,
+    {
+        match &self.read_cache {
+            Some(cache) => cache.get_or_fetch(key, fetch).await,
+            None => fetch().await,
+        }
+    }
+    
+    /// Reads served from the cache and those that went to the node
+    pub fn read_cache_stats(&self) -> Option<CacheStats> {
+        self.read_cache.as_ref().map(ReadCache::stats)
+    }
+    
+    pub async fn get_chain_id(&self) -> Result<u64> {
+        Ok(self.http_provider.get_chainid().await?.as_u64())
+    }
+    
+    /// The node's chain id, failing if it is not `expected`: transactions signed
+    /// for the wrong chain would be rejected (or replayable elsewhere)
+    pub async fn ensure_chain_id(&self, expected: u64) -> Result<u64> {
+        let chain_id = self.get_chain_id().await?;
+        if chain_id != expected {
+            return Err(RpcError::ChainIdMismatch { expected, actual: chain_id });
+        }
+        Ok(chain_id)
+    }
+    
     pub async fn get_block_number(&self) -> Result<u64> {
-        let block_num = self.http_provider.get_block_number().await?;
+        let block_num = match &self.batcher {
+            Some(batcher) => batcher.request::<_, U64>("eth_blockNumber", ()).await.map_err(RpcError::Batch)?,
+            None => self.http_provider.get_block_number().await?,
+        };
+        self.observe_block(block_num.as_u64());
         Ok(block_num.as_u64())
     }
     
+    /// Latest head number from a WebSocket new-heads subscription; the
+    /// receiver sees the sender close when the subscription ends
+    pub fn subscribe_heads(&self) -> Result<watch::Receiver<u64>> {
+        let ws = self.ws_provider.clone().ok_or(RpcError::Unavailable("New heads need a WebSocket connection"))?;
+        let (tx, rx) = watch::channel(0);
+        tokio::spawn(async move {
+            let mut heads = match ws.subscribe_blocks().await {
+                Ok(heads) => heads,
+                Err(e) => {
+                    warn!("Cannot subscribe to new heads: {}", e);
+                    return;
+                }
+            };
+            while let Some(block) = heads.next().await {
+                if let Some(number) = block.number {
+                    if tx.send(number.as_u64()).is_err() {
+                        return;
+                    }
+                }
+            }
+            warn!("New heads subscription ended");
+        });
+        Ok(rx)
+    }
+    
     pub async fn get_block(&self, block_number: u64) -> Result<Option<Block<H256>>> {
         Ok(self.http_provider.get_block(block_number).await?)
     }
@@ -93,10 +255,28 @@ impl BlockchainClient {
         Ok(self.http_provider.get_transaction(tx_hash).await?)
     }
     
+    /// Hand a signed transaction to the node for its public mempool
+    pub async fn send_raw_transaction(&self, raw: Bytes) -> Result<H256> {
+        Ok(self.http_provider.send_raw_transaction(raw).await?.tx_hash())
+    }
+    
     pub async fn get_transaction_receipt(&self, tx_hash: H256) -> Result<Option<TransactionReceipt>> {
+        if let Some(batcher) = &self.batcher {
+            return batcher.request("eth_getTransactionReceipt", [tx_hash]).await.map_err(RpcError::Batch);
+        }
         Ok(self.http_provider.get_transaction_receipt(tx_hash).await?)
     }
     
+    /// `Liquidate` events against `user` from `from_block` on, oldest first
+    pub async fn liquidations_of(&self, user: Address, from_block: u64) -> Result<Vec<(LiquidateFilter, LogMeta)>> {
+        Ok(self.lending_protocol
+            .liquidate_filter()
+            .topic2(user)
+            .from_block(from_block)
+            .query_with_meta()
+            .await?)
+    }
+    
     pub async fn get_health_factor(&self, user: Address) -> Result<U256> {
         Ok(self.lending_protocol.get_health_factor(user).call().await?)
     }
@@ -106,13 +286,88 @@ impl BlockchainClient {
     }
     
     pub async fn get_position(&self, user: Address) -> Result<(U256, U256, U256)> {
+        self.cached(CacheKey::Position(user), || self.fetch_position(user)).await
+    }
+    
+    async fn fetch_position(&self, user: Address) -> Result<(U256, U256, U256)> {
+        if let Some(batcher) = &self.batcher {
+            let tx = self.lending_protocol.get_position(user).tx;
+            let output: Bytes = batcher.request("eth_call", (tx, "latest")).await.map_err(RpcError::Batch)?;
+            let position = GetPositionReturn::decode(output)?;
+            return Ok((position.collateral, position.debt, position.health_factor));
+        }
         Ok(self.lending_protocol.get_position(user).call().await?)
     }
     
+    /// `getPosition` with `state` overriding chain state (`eth_call` state override set)
+    pub async fn get_position_at(&self, user: Address, state: &spoof::State) -> Result<(U256, U256, U256)> {
+        let tx = self.lending_protocol.get_position(user).tx;
+        let output = self.http_provider.call_raw(&tx).state(state).await?;
+        let position = GetPositionReturn::decode(output)?;
+        Ok((position.collateral, position.debt, position.health_factor))
+    }
+    
+    pub async fn get_pending_nonce(&self, address: Address) -> Result<U256> {
+        Ok(self.http_provider
+            .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+            .await?)
+    }
+    
+    /// Gas still unused in the block being built (the pending block, or the
+    /// latest one if the node has no pending block)
+    pub async fn block_gas_headroom(&self) -> Result<U256> {
+        let block = match self.http_provider.get_block(BlockNumber::Pending).await? {
+            Some(block) => block,
+            None => self.http_provider
+                .get_block(BlockNumber::Latest)
+                .await?
+                .ok_or(RpcError::Unavailable("No latest block"))?,
+        };
+        Ok(block.gas_limit.saturating_sub(block.gas_used))
+    }
+    
+    /// Base fees of the last `blocks` blocks, oldest first, followed by the next block's
+    pub async fn base_fee_history(&self, blocks: u64) -> Result<Vec<U256>> {
+        let history = self.http_provider.fee_history(blocks, BlockNumber::Latest, &[]).await?;
+        Ok(history.base_fee_per_gas)
+    }
+    
     pub async fn get_gas_price(&self) -> Result<U256> {
+        self.cached(CacheKey::GasPrice, || self.fetch_gas_price()).await
+    }
+    
+    async fn fetch_gas_price(&self) -> Result<U256> {
+        if let Some(batcher) = &self.batcher {
+            return batcher.request("eth_gasPrice", ()).await.map_err(RpcError::Batch);
+        }
         Ok(self.http_provider.get_gas_price().await?)
     }
     
+    /// Take an Anvil state snapshot (`evm_snapshot`) and return its id
+    pub async fn snapshot(&self) -> Result<U256> {
+        Ok(self.http_provider.request("evm_snapshot", ()).await?)
+    }
+    
+    /// Roll state back to a snapshot taken with `snapshot` (`evm_revert`)
+    pub async fn revert_to_snapshot(&self, id: U256) -> Result<bool> {
+        // State changes without a new block, so cached reads no longer hold
+        if let Some(cache) = &self.read_cache {
+            cache.clear();
+        }
+        Ok(self.http_provider.request("evm_revert", [id]).await?)
+    }
+    
+    /// Let unsigned transactions be sent from `address` (Anvil only)
+    pub async fn impersonate_account(&self, address: Address) -> Result<()> {
+        self.http_provider.request::<_, ()>("anvil_impersonateAccount", [address]).await?;
+        Ok(())
+    }
+    
+    pub async fn stop_impersonating_account(&self, address: Address) -> Result<()> {
+        self.http_provider.request::<_, ()>("anvil_stopImpersonatingAccount", [address]).await?;
+        Ok(())
+    }
+    
     pub async fn estimate_gas_liquidation(
         &self,
         user: Address,
@@ -127,18 +382,22 @@ impl BlockchainClient {
 mod tests {
     use super::*;
 
+    use crate::testkit::TestEnvironment;
+
     #[tokio::test]
-    #[ignore] // Requires running Anvil instance
+    #[ignore = "needs anvil and forge artifacts"]
     async fn test_blockchain_connection() {
+        let env = TestEnvironment::spawn().await.unwrap();
         let client = BlockchainClient::new(
-            "http://127.0.0.1:8545",
-            None,
-            Address::zero(),
-            Address::zero(),
+            &env.anvil.endpoint(),
+            Some(&env.anvil.ws_endpoint()),
+            env.protocol_address,
+            env.token_address,
         )
         .await;
         
         assert!(client.is_ok());
+        assert!(client.unwrap().get_block_number().await.is_ok());
     }
 }
 
@@ -0,0 +1,389 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::signers::LocalWallet;
+use ethers::utils::keccak256;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::metrics::inclusion_latency;
+
+/// Per-builder request timeout; a slow builder must not hold up the others
+const SUBMIT_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Block builder accepting `eth_sendBundle`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuilderEndpoint {
+    pub name: String,
+    pub url: String,
+    /// Substring the builder writes into `extraData`, used to attribute inclusions
+    pub extra_data_tag: Option<String>,
+}
+
+impl BuilderEndpoint {
+    /// Well-known mainnet builders by short name
+    pub fn known(name: &str) -> Option<Self> {
+        let (url, tag) = match name {
+            "flashbots" => ("https://relay.flashbots.net", "Illuminate Dmocratize Dstribute"),
+            "beaverbuild" => ("https://rpc.beaverbuild.org", "beaverbuild.org"),
+            "rsync" => ("https://rsync-builder.xyz", "rsync-builder.xyz"),
+            "titan" => ("https://rpc.titanbuilder.xyz", "Titan"),
+            _ => return None,
+        };
+        Some(Self { name: name.to_string(), url: url.to_string(), extra_data_tag: Some(tag.to_string()) })
+    }
+
+    /// Parse `name` (a known builder) or `name=url` (a custom one)
+    pub fn parse(raw: &str) -> Result<Self> {
+        match raw.split_once('=') {
+            Some((name, url)) => Ok(Self {
+                name: name.trim().to_string(),
+                url: url.trim().to_string(),
+                extra_data_tag: None,
+            }),
+            None => Self::known(raw.trim()).with_context(|| format!("Unknown builder {}", raw)),
+        }
+    }
+}
+
+/// Outcome of sending one bundle to one builder
+#[derive(Debug, Clone)]
+pub struct BuilderSubmission {
+    pub builder: String,
+    pub accepted: bool,
+    pub bundle_hash: Option<String>,
+    pub error: Option<String>,
+    pub latency_us: f64,
+}
+
+/// Running submission and inclusion counts for one builder
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BuilderStats {
+    pub submitted: usize,
+    pub accepted: usize,
+    pub included: usize,
+    /// Bundles for later blocks withdrawn after an earlier one was included
+    #[serde(default)]
+    pub cancelled: usize,
+    pub total_latency_us: f64,
+    /// Summed send-to-block-timestamp time of included bundles
+    #[serde(default)]
+    pub total_inclusion_latency_us: f64,
+}
+
+impl BuilderStats {
+    pub fn inclusion_rate(&self) -> f64 {
+        if self.submitted == 0 { 0.0 } else { self.included as f64 / self.submitted as f64 }
+    }
+}
+
+/// One bundle of a multi-block submission, cancellable by its replacement uuid
+#[derive(Debug, Clone)]
+pub struct TargetedBundle {
+    pub block: u64,
+    pub replacement_uuid: String,
+    pub submissions: Vec<BuilderSubmission>,
+}
+
+impl TargetedBundle {
+    pub fn accepted(&self) -> bool {
+        self.submissions.iter().any(|s| s.accepted)
+    }
+}
+
+/// Fans signed liquidation bundles out to several block builders in parallel
+pub struct BuilderRelay {
+    client: reqwest::Client,
+    builders: Vec<BuilderEndpoint>,
+    /// Key that signs the Flashbots auth header; carries no funds
+    auth_signer: LocalWallet,
+    stats: Mutex<BTreeMap<String, BuilderStats>>,
+}
+
+impl BuilderRelay {
+    pub fn new(builders: Vec<BuilderEndpoint>, auth_signer: LocalWallet) -> Self {
+        let stats = builders.iter().map(|b| (b.name.clone(), BuilderStats::default())).collect();
+        Self {
+            client: reqwest::Client::new(),
+            builders,
+            auth_signer,
+            stats: Mutex::new(stats),
+        }
+    }
+
+    pub fn builders(&self) -> &[BuilderEndpoint] {
+        &self.builders
+    }
+
+    /// Send `raw_txs` as a bundle for `target_block` to every builder at once
+    pub async fn submit_bundle(&self, raw_txs: &[Bytes], target_block: u64) -> Vec<BuilderSubmission> {
+        self.send_bundle(raw_txs, target_block, None).await
+    }
+
+    /// Send `raw_txs` as one bundle per block in `target_blocks`, each under its
+    /// own replacement uuid so the rest can be cancelled once one lands
+    pub async fn submit_bundles(&self, raw_txs: &[Bytes], target_blocks: &[u64]) -> Vec<TargetedBundle> {
+        let sends = target_blocks.iter().map(|&block| async move {
+            let replacement_uuid = replacement_uuid();
+            let submissions = self.send_bundle(raw_txs, block, Some(&replacement_uuid)).await;
+            TargetedBundle { block, replacement_uuid, submissions }
+        });
+        futures::future::join_all(sends).await
+    }
+
+    /// Withdraw `bundles` from every builder (`eth_cancelBundle`)
+    pub async fn cancel_bundles(&self, bundles: &[TargetedBundle]) {
+        for bundle in bundles {
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_cancelBundle",
+                "params": [{ "replacementUuid": bundle.replacement_uuid }],
+            })
+            .to_string();
+            let signature = match self.auth_header(&body).await {
+                Ok(signature) => signature,
+                Err(e) => {
+                    warn!("Failed to sign builder auth header: {}", e);
+                    return;
+                }
+            };
+            let sends = self.builders.iter().map(|builder| self.send(builder, &body, &signature));
+            let replies = futures::future::join_all(sends).await;
+
+            let mut stats = self.stats.lock().unwrap();
+            for reply in replies.iter().filter(|reply| reply.accepted) {
+                stats.entry(reply.builder.clone()).or_default().cancelled += 1;
+            }
+            debug!("Cancelled bundle for block {}", bundle.block);
+        }
+    }
+
+    async fn send_bundle(&self, raw_txs: &[Bytes], target_block: u64, replacement_uuid: Option<&str>) -> Vec<BuilderSubmission> {
+        let mut bundle = json!({
+            "txs": raw_txs,
+            "blockNumber": format!("0x{:x}", target_block),
+        });
+        if let Some(uuid) = replacement_uuid {
+            bundle["replacementUuid"] = json!(uuid);
+        }
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_sendBundle",
+            "params": [bundle],
+        })
+        .to_string();
+
+        let signature = match self.auth_header(&body).await {
+            Ok(signature) => signature,
+            Err(e) => {
+                warn!("Failed to sign builder auth header: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let sends = self.builders.iter().map(|builder| self.send(builder, &body, &signature));
+        let submissions = futures::future::join_all(sends).await;
+
+        let mut stats = self.stats.lock().unwrap();
+        for submission in &submissions {
+            let entry = stats.entry(submission.builder.clone()).or_default();
+            entry.submitted += 1;
+            entry.accepted += submission.accepted as usize;
+            entry.total_latency_us += submission.latency_us;
+        }
+        let accepted = submissions.iter().filter(|s| s.accepted).count();
+        info!("Bundle for block {} accepted by {}/{} builders", target_block, accepted, submissions.len());
+
+        submissions
+    }
+
+    /// `X-Flashbots-Signature`: address and signature over the hex body hash
+    async fn auth_header(&self, body: &str) -> Result<String> {
+        let digest = format!("0x{}", hex::encode(keccak256(body.as_bytes())));
+        let signature = self.auth_signer.sign_message(digest).await?;
+        Ok(format!("{:?}:0x{}", self.auth_signer.address(), signature))
+    }
+
+    async fn send(&self, builder: &BuilderEndpoint, body: &str, signature: &str) -> BuilderSubmission {
+        let started = Instant::now();
+        let response = self
+            .client
+            .post(&builder.url)
+            .header("Content-Type", "application/json")
+            .header("X-Flashbots-Signature", signature)
+            .body(body.to_string())
+            .timeout(SUBMIT_TIMEOUT)
+            .send()
+            .await;
+        let latency_us = started.elapsed().as_secs_f64() * 1e6;
+
+        let result: Result<Value> = match response {
+            Ok(resp) => resp.json().await.map_err(Into::into),
+            Err(e) => Err(e.into()),
+        };
+
+        let (accepted, bundle_hash, error) = match result {
+            Ok(reply) if reply.get("error").is_none() => {
+                let hash = reply["result"]["bundleHash"].as_str().map(String::from);
+                (true, hash, None)
+            }
+            Ok(reply) => (false, None, Some(reply["error"].to_string())),
+            Err(e) => (false, None, Some(e.to_string())),
+        };
+        if let Some(error) = &error {
+            debug!("Builder {} rejected bundle: {}", builder.name, error);
+        }
+
+        BuilderSubmission { builder: builder.name.clone(), accepted, bundle_hash, error, latency_us }
+    }
+
+    /// Check whether `tx_hash` (sent at `sent_unix_ns`) landed in `block` and
+    /// credit the builder that built it
+    pub async fn track_inclusion(
+        &self,
+        blockchain: &BlockchainClient,
+        tx_hash: H256,
+        block: u64,
+        sent_unix_ns: u64,
+    ) -> Result<Option<String>> {
+        let Some(block) = blockchain.http_provider.get_block(block).await? else {
+            return Ok(None);
+        };
+        if !block.transactions.contains(&tx_hash) {
+            return Ok(None);
+        }
+
+        let builder = self.attribute(&block.extra_data);
+        if let Some(name) = &builder {
+            let latency = inclusion_latency(sent_unix_ns, block.timestamp.low_u64());
+            let mut stats = self.stats.lock().unwrap();
+            let entry = stats.entry(name.clone()).or_default();
+            entry.included += 1;
+            entry.total_inclusion_latency_us += latency.as_secs_f64() * 1e6;
+        }
+        Ok(builder)
+    }
+
+    /// Follow `bundles` block by block, recording which builder (if any) included
+    /// `tx_hash` and cancelling the bundles for blocks after the one it landed in
+    pub async fn watch_inclusion(
+        self: Arc<Self>,
+        blockchain: Arc<BlockchainClient>,
+        tx_hash: H256,
+        bundles: Vec<TargetedBundle>,
+        sent_unix_ns: u64,
+    ) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(1));
+        let mut pending = bundles.as_slice();
+        // Give up after ~30s per targeted block
+        for _ in 0..30 * bundles.len() {
+            let Some(bundle) = pending.first() else { return };
+            ticker.tick().await;
+            match blockchain.get_block_number().await {
+                Ok(head) if head >= bundle.block => {
+                    pending = &pending[1..];
+                    match self.track_inclusion(&blockchain, tx_hash, bundle.block, sent_unix_ns).await {
+                        Ok(Some(builder)) => info!("{:?} included by {}", tx_hash, builder),
+                        Ok(None) => {
+                            debug!("{:?} not included in block {}", tx_hash, bundle.block);
+                            continue;
+                        }
+                        Err(e) => warn!("Inclusion check failed for {:?}: {}", tx_hash, e),
+                    }
+                    // Landed (or can't tell): later blocks would only hold a spent nonce
+                    if !pending.is_empty() {
+                        self.cancel_bundles(pending).await;
+                    }
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => debug!("Head poll failed while tracking {:?}: {}", tx_hash, e),
+            }
+        }
+    }
+
+    /// Builder whose `extraData` tag appears in the block header
+    fn attribute(&self, extra_data: &Bytes) -> Option<String> {
+        let extra = String::from_utf8_lossy(extra_data);
+        self.builders
+            .iter()
+            .find(|b| b.extra_data_tag.as_deref().is_some_and(|tag| extra.contains(tag)))
+            .map(|b| b.name.clone())
+    }
+
+    pub fn stats(&self) -> BTreeMap<String, BuilderStats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    pub fn print_stats(&self) {
+        info!("=== Builder Inclusion ===");
+        for (name, stats) in self.stats() {
+            let mean_latency_ms = if stats.submitted == 0 { 0.0 } else { stats.total_latency_us / stats.submitted as f64 / 1000.0 };
+            let mean_inclusion_s = if stats.included == 0 { 0.0 } else { stats.total_inclusion_latency_us / stats.included as f64 / 1e6 };
+            info!(
+                "{}: submitted {}, accepted {}, included {} ({:.1}%), cancelled {}, mean latency {:.2}ms, mean time to inclusion {:.1}s",
+                name,
+                stats.submitted,
+                stats.accepted,
+                stats.included,
+                stats.inclusion_rate() * 100.0,
+                stats.cancelled,
+                mean_latency_ms,
+                mean_inclusion_s,
+            );
+        }
+    }
+}
+
+/// Random RFC 4122 version 4 uuid, as builders expect for `replacementUuid`
+fn replacement_uuid() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_parsing_and_attribution() {
+        let titan = BuilderEndpoint::parse("titan").unwrap();
+        assert_eq!(titan.url, "https://rpc.titanbuilder.xyz");
+        let custom = BuilderEndpoint::parse("local=http://127.0.0.1:9999").unwrap();
+        assert_eq!(custom.extra_data_tag, None);
+        assert!(BuilderEndpoint::parse("unknown").is_err());
+
+        let relay = BuilderRelay::new(vec![titan, custom], LocalWallet::new(&mut rand::thread_rng()));
+        assert_eq!(relay.attribute(&Bytes::from(b"Titan (titanbuilder.xyz)".to_vec())), Some("titan".to_string()));
+        assert_eq!(relay.attribute(&Bytes::from(b"geth".to_vec())), None);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_builder_is_recorded() {
+        let builder = BuilderEndpoint::parse("local=http://127.0.0.1:1").unwrap();
+        let relay = BuilderRelay::new(vec![builder], LocalWallet::new(&mut rand::thread_rng()));
+
+        let submissions = relay.submit_bundle(&[Bytes::from(vec![0x02])], 100).await;
+
+        assert_eq!(submissions.len(), 1);
+        assert!(!submissions[0].accepted);
+        assert_eq!(relay.stats()["local"].submitted, 1);
+        assert_eq!(relay.stats()["local"].accepted, 0);
+
+        let bundles = relay.submit_bundles(&[Bytes::from(vec![0x02])], &[101, 102]).await;
+        assert_eq!(bundles.iter().map(|b| b.block).collect::<Vec<_>>(), vec![101, 102]);
+        assert!(!bundles.iter().any(TargetedBundle::accepted));
+        assert_ne!(bundles[0].replacement_uuid, bundles[1].replacement_uuid);
+        assert_eq!(bundles[0].replacement_uuid.len(), 36);
+        assert_eq!(relay.stats()["local"].submitted, 3);
+    }
+}
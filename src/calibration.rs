@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tracing::warn;
+
+use crate::inclusion::Settlement;
+use crate::simulator::SimulationResult;
+
+/// How far one settled liquidation landed from its simulation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimulationDelta {
+    /// Realized minus expected profit
+    pub profit_usd: f64,
+    /// Gas used relative to the estimate, minus one (0.1 = 10% over)
+    pub gas_ratio: f64,
+}
+
+impl SimulationDelta {
+    pub fn new(simulation: &SimulationResult, settlement: &Settlement) -> Self {
+        let estimated = simulation.estimated_gas.as_u128() as f64;
+        let gas_ratio = if estimated > 0.0 { settlement.gas_used.as_u128() as f64 / estimated - 1.0 } else { 0.0 };
+        Self { profit_usd: settlement.realized_profit_usd - simulation.expected_profit_usd, gas_ratio }
+    }
+}
+
+/// Bias (mean error) and mean absolute error of profit and gas simulations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationStats {
+    pub samples: usize,
+    pub profit_bias_usd: f64,
+    pub profit_mae_usd: f64,
+    pub gas_bias_ratio: f64,
+    pub gas_mae_ratio: f64,
+}
+
+impl CalibrationStats {
+    /// Fold `delta` into the running means
+    pub fn record(&mut self, delta: &SimulationDelta) {
+        self.samples += 1;
+        let n = self.samples as f64;
+        self.profit_bias_usd += (delta.profit_usd - self.profit_bias_usd) / n;
+        self.profit_mae_usd += (delta.profit_usd.abs() - self.profit_mae_usd) / n;
+        self.gas_bias_ratio += (delta.gas_ratio - self.gas_bias_ratio) / n;
+        self.gas_mae_ratio += (delta.gas_ratio.abs() - self.gas_mae_ratio) / n;
+    }
+
+    pub fn from_deltas<'a>(deltas: impl IntoIterator<Item = &'a SimulationDelta>) -> Self {
+        let mut stats = Self::default();
+        for delta in deltas {
+            stats.record(delta);
+        }
+        stats
+    }
+}
+
+/// Largest simulator bias tolerated before alerting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftThresholds {
+    /// Settlements needed in the window before drift is judged
+    pub min_samples: usize,
+    pub max_profit_bias_usd: f64,
+    pub max_gas_bias_ratio: f64,
+}
+
+impl Default for DriftThresholds {
+    fn default() -> Self {
+        Self { min_samples: 10, max_profit_bias_usd: 10.0, max_gas_bias_ratio: 0.15 }
+    }
+}
+
+/// Calibration of the profitability gate over the last `window` settlements
+///
+/// Bias in either direction means the gate is wrong: optimistic simulations
+/// submit losing liquidations, pessimistic ones skip winning ones.
+#[derive(Debug)]
+pub struct CalibrationTracker {
+    window: usize,
+    thresholds: DriftThresholds,
+    recent: Mutex<VecDeque<SimulationDelta>>,
+}
+
+impl CalibrationTracker {
+    pub fn new(window: usize, thresholds: DriftThresholds) -> Self {
+        Self { window: window.max(1), thresholds, recent: Mutex::new(VecDeque::new()) }
+    }
+
+    /// Add a settlement; returns the drift reasons if the window now exceeds a threshold
+    pub fn record(&self, delta: SimulationDelta) -> Option<Vec<String>> {
+        let stats = {
+            let mut recent = self.recent.lock().unwrap();
+            if recent.len() == self.window {
+                recent.pop_front();
+            }
+            recent.push_back(delta);
+            CalibrationStats::from_deltas(recent.iter())
+        };
+        let reasons = self.drift(&stats);
+        if reasons.is_empty() {
+            return None;
+        }
+        warn!("[ALERT] Simulator drift over the last {} settlements: {}", stats.samples, reasons.join("; "));
+        Some(reasons)
+    }
+
+    fn drift(&self, stats: &CalibrationStats) -> Vec<String> {
+        let mut reasons = Vec::new();
+        if stats.samples < self.thresholds.min_samples {
+            return reasons;
+        }
+        if stats.profit_bias_usd.abs() > self.thresholds.max_profit_bias_usd {
+            reasons.push(format!("profit bias {:+.2} USD (limit {:.2})", stats.profit_bias_usd, self.thresholds.max_profit_bias_usd));
+        }
+        if stats.gas_bias_ratio.abs() > self.thresholds.max_gas_bias_ratio {
+            reasons.push(format!(
+                "gas bias {:+.1}% (limit {:.1}%)",
+                stats.gas_bias_ratio * 100.0,
+                self.thresholds.max_gas_bias_ratio * 100.0,
+            ));
+        }
+        reasons
+    }
+
+    /// Calibration over the current window
+    pub fn stats(&self) -> CalibrationStats {
+        CalibrationStats::from_deltas(self.recent.lock().unwrap().iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bias_mae_and_drift() {
+        let deltas = [
+            SimulationDelta { profit_usd: -4.0, gas_ratio: 0.1 },
+            SimulationDelta { profit_usd: 2.0, gas_ratio: -0.1 },
+        ];
+        let stats = CalibrationStats::from_deltas(&deltas);
+        assert_eq!((stats.samples, stats.profit_bias_usd, stats.profit_mae_usd), (2, -1.0, 3.0));
+        assert!(stats.gas_bias_ratio.abs() < 1e-12 && (stats.gas_mae_ratio - 0.1).abs() < 1e-12);
+
+        let thresholds = DriftThresholds { min_samples: 3, max_profit_bias_usd: 5.0, max_gas_bias_ratio: 0.2 };
+        let tracker = CalibrationTracker::new(3, thresholds);
+        // Not judged until the window holds enough settlements
+        assert!(tracker.record(SimulationDelta { profit_usd: -20.0, gas_ratio: 0.0 }).is_none());
+        assert!(tracker.record(SimulationDelta { profit_usd: -20.0, gas_ratio: 0.0 }).is_none());
+        let reasons = tracker.record(SimulationDelta { profit_usd: -20.0, gas_ratio: 0.5 }).unwrap();
+        assert_eq!(reasons.len(), 1, "{:?}", reasons);
+        // Accurate settlements push the optimistic ones out of the window
+        for _ in 0..3 {
+            tracker.record(SimulationDelta { profit_usd: 1.0, gas_ratio: 0.05 });
+        }
+        assert_eq!(tracker.stats().samples, 3);
+        assert!(tracker.record(SimulationDelta { profit_usd: 1.0, gas_ratio: 0.05 }).is_none());
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::metrics::AggregateMetrics;
+
+/// Latency metrics compared between runs
+const LATENCY_METRICS: [&str; 5] = [
+    "decode_us",
+    "signal_detection_us",
+    "simulation_us",
+    "construction_us",
+    "end_to_end_us",
+];
+
+/// Percentiles compared for each latency metric
+const PERCENTILES: [f64; 3] = [50.0, 95.0, 99.0];
+
+/// How much worse run B may be than run A before a change counts as a regression
+#[derive(Debug, Clone)]
+pub struct RegressionThresholds {
+    /// Maximum relative latency increase (0.10 = +10%)
+    pub max_latency_increase: f64,
+    /// Maximum absolute drop in detection rate (0.01 = 1 percentage point)
+    pub max_detection_rate_drop: f64,
+    /// Maximum absolute drop in success rate
+    pub max_success_rate_drop: f64,
+    /// Maximum relative drop in total expected profit
+    pub max_profit_drop: f64,
+}
+
+impl Default for RegressionThresholds {
+    fn default() -> Self {
+        Self {
+            max_latency_increase: 0.10,
+            max_detection_rate_drop: 0.01,
+            max_success_rate_drop: 0.05,
+            max_profit_drop: 0.10,
+        }
+    }
+}
+
+/// One compared value between baseline (A) and candidate (B)
+#[derive(Debug, Clone)]
+pub struct MetricDelta {
+    pub name: String,
+    pub baseline: f64,
+    pub candidate: f64,
+    pub regression: bool,
+}
+
+impl MetricDelta {
+    /// Relative change from baseline to candidate (0 when baseline is 0)
+    pub fn relative_change(&self) -> f64 {
+        if self.baseline == 0.0 {
+            return 0.0;
+        }
+        (self.candidate - self.baseline) / self.baseline
+    }
+}
+
+/// Metric-by-metric diff between two backtest runs
+#[derive(Debug, Clone, Default)]
+pub struct ComparisonReport {
+    pub deltas: Vec<MetricDelta>,
+}
+
+impl ComparisonReport {
+    /// Compare candidate run `b` against baseline run `a`
+    pub fn compare(a: &AggregateMetrics, b: &AggregateMetrics, thresholds: &RegressionThresholds) -> Self {
+        let mut deltas = Vec::new();
+
+        for metric in LATENCY_METRICS {
+            for p in PERCENTILES {
+                if let (Some(baseline), Some(candidate)) = (a.percentile(metric, p), b.percentile(metric, p)) {
+                    let limit = baseline * (1.0 + thresholds.max_latency_increase);
+                    deltas.push(MetricDelta {
+                        name: format!("{} P{}", metric, p),
+                        baseline,
+                        candidate,
+                        regression: candidate > limit,
+                    });
+                }
+            }
+        }
+
+        let detection = (a.detection_rate(), b.detection_rate());
+        deltas.push(MetricDelta {
+            name: "detection_rate".to_string(),
+            baseline: detection.0,
+            candidate: detection.1,
+            regression: detection.0 - detection.1 > thresholds.max_detection_rate_drop,
+        });
+
+        let success = (a.success_rate(), b.success_rate());
+        deltas.push(MetricDelta {
+            name: "success_rate".to_string(),
+            baseline: success.0,
+            candidate: success.1,
+            regression: success.0 - success.1 > thresholds.max_success_rate_drop,
+        });
+
+        let profit = (a.total_expected_profit_usd, b.total_expected_profit_usd);
+        deltas.push(MetricDelta {
+            name: "expected_profit_usd".to_string(),
+            baseline: profit.0,
+            candidate: profit.1,
+            regression: profit.0 > 0.0 && profit.1 < profit.0 * (1.0 - thresholds.max_profit_drop),
+        });
+
+        Self { deltas }
+    }
+
+    pub fn regressions(&self) -> impl Iterator<Item = &MetricDelta> {
+        self.deltas.iter().filter(|d| d.regression)
+    }
+
+    pub fn has_regressions(&self) -> bool {
+        self.regressions().next().is_some()
+    }
+
+    pub fn print(&self) {
+        info!("=== Run Comparison (A = baseline, B = candidate) ===");
+        for delta in &self.deltas {
+            let line = format!(
+                "{}: A={:.4} B={:.4} ({:+.2}%)",
+                delta.name,
+                delta.baseline,
+                delta.candidate,
+                delta.relative_change() * 100.0,
+            );
+            if delta.regression {
+                warn!("[REGRESSION] {}", line);
+            } else {
+                info!("{}", line);
+            }
+        }
+    }
+}
+
+/// Load an AggregateMetrics JSON report written by `generate_report`
+pub fn load_metrics(path: &Path) -> Result<AggregateMetrics> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("Invalid metrics JSON in {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn run(e2e_us: f64, attempts: usize, processed: usize, profit: f64) -> AggregateMetrics {
+        let mut metrics = AggregateMetrics::new();
+        metrics.total_attempts = attempts;
+        metrics.successful_liquidations = attempts;
+        metrics.transactions_processed = processed;
+        metrics.total_expected_profit_usd = profit;
+        metrics.latencies = (0..100)
+            .map(|_| HashMap::from([("end_to_end_us".to_string(), e2e_us)]))
+            .collect();
+        metrics
+    }
+
+    #[test]
+    fn test_comparison_flags_regressions() {
+        let thresholds = RegressionThresholds::default();
+        let baseline = run(100.0, 50, 1000, 1000.0);
+
+        let same = ComparisonReport::compare(&baseline, &run(105.0, 50, 1000, 990.0), &thresholds);
+        assert!(!same.has_regressions());
+
+        let slower = ComparisonReport::compare(&baseline, &run(150.0, 50, 1000, 1000.0), &thresholds);
+        let names: Vec<_> = slower.regressions().map(|d| d.name.as_str()).collect();
+        assert!(names.contains(&"end_to_end_us P99"));
+
+        let poorer = ComparisonReport::compare(&baseline, &run(100.0, 20, 1000, 400.0), &thresholds);
+        let names: Vec<_> = poorer.regressions().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["detection_rate", "expected_profit_usd"]);
+    }
+}
@@ -0,0 +1,168 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Rival liquidators a backtest races against for every opportunity
+///
+/// Each race is a priority gas auction for the next block: bots that submit
+/// before the block is built compete on their bid, and if nobody makes it the
+/// whole field competes for the block after.
+#[derive(Debug, Clone)]
+pub struct CompetitorModel {
+    /// Number of rival bots watching the same positions
+    pub rivals: usize,
+    /// Range a rival's detection-to-submission latency is drawn from, in microseconds
+    pub latency_us: (f64, f64),
+    /// Range of the share of an opportunity's gross profit a rival bids as priority fee
+    pub bid_share: (f64, f64),
+    /// Share of gross profit we bid
+    pub our_bid_share: f64,
+    /// Time between blocks; the next block is built uniformly within it
+    pub block_time_us: f64,
+    pub seed: u64,
+}
+
+impl Default for CompetitorModel {
+    fn default() -> Self {
+        Self {
+            rivals: 3,
+            latency_us: (1_000.0, 50_000.0),
+            bid_share: (0.1, 0.5),
+            our_bid_share: 0.3,
+            block_time_us: 12_000_000.0,
+            seed: 42,
+        }
+    }
+}
+
+/// How one opportunity's auction ended for us
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RaceResult {
+    /// We won and paid `bid_usd` of the gross profit as priority fee
+    Won { bid_usd: f64 },
+    /// A rival submitted before the block was built and we did not
+    TooSlow,
+    /// We were in the same block as a rival that bid more
+    Outbid { winning_bid_usd: f64, our_bid_usd: f64 },
+}
+
+impl CompetitorModel {
+    /// Race the rivals for an opportunity worth `gross_profit_usd` before any
+    /// priority fee, with us submitting `our_latency_us` after detection
+    pub fn race(&self, rng: &mut StdRng, our_latency_us: f64, gross_profit_usd: f64) -> RaceResult {
+        let our_bid_usd = gross_profit_usd * self.our_bid_share;
+        let block_cutoff_us = rng.gen_range(0.0..self.block_time_us.max(1.0));
+        let rivals: Vec<(f64, f64)> = (0..self.rivals)
+            .map(|_| (sample(rng, self.latency_us), gross_profit_usd * sample(rng, self.bid_share)))
+            .collect();
+
+        let we_made_it = our_latency_us <= block_cutoff_us;
+        let rivals_in_block: Vec<f64> = rivals
+            .iter()
+            .filter(|(latency, _)| *latency <= block_cutoff_us)
+            .map(|(_, bid)| *bid)
+            .collect();
+
+        // Nobody made the next block: everyone competes for the one after
+        let competing_bids = match (we_made_it, rivals_in_block.is_empty()) {
+            (false, false) => return RaceResult::TooSlow,
+            (true, _) => rivals_in_block,
+            (false, true) => rivals.iter().map(|(_, bid)| *bid).collect(),
+        };
+
+        match competing_bids.into_iter().max_by(f64::total_cmp) {
+            Some(winning_bid_usd) if winning_bid_usd > our_bid_usd => RaceResult::Outbid { winning_bid_usd, our_bid_usd },
+            _ => RaceResult::Won { bid_usd: our_bid_usd },
+        }
+    }
+}
+
+fn sample(rng: &mut StdRng, range: (f64, f64)) -> f64 {
+    if range.1 > range.0 { rng.gen_range(range.0..range.1) } else { range.0 }
+}
+
+/// Auction results over a backtest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompetitionStats {
+    pub races: usize,
+    pub won: usize,
+    pub too_slow: usize,
+    pub outbid: usize,
+    /// Profit of the races we won, after our priority fee
+    pub captured_profit_usd: f64,
+    pub priority_fees_paid_usd: f64,
+    /// Expected profit of the races we lost
+    pub forgone_profit_usd: f64,
+    /// How far above our bid the winner was, per race lost on price
+    pub losing_bid_gaps_usd: Vec<f64>,
+}
+
+impl CompetitionStats {
+    /// Record `result` for an opportunity expected to make `expected_profit_usd`
+    /// before priority fees; returns the profit actually captured
+    pub fn record(&mut self, result: RaceResult, expected_profit_usd: f64) -> f64 {
+        self.races += 1;
+        match result {
+            RaceResult::Won { bid_usd } => {
+                self.won += 1;
+                self.priority_fees_paid_usd += bid_usd;
+                self.captured_profit_usd += expected_profit_usd - bid_usd;
+                return expected_profit_usd - bid_usd;
+            }
+            RaceResult::TooSlow => self.too_slow += 1,
+            RaceResult::Outbid { winning_bid_usd, our_bid_usd } => {
+                self.outbid += 1;
+                self.losing_bid_gaps_usd.push(winning_bid_usd - our_bid_usd);
+            }
+        }
+        self.forgone_profit_usd += expected_profit_usd;
+        0.0
+    }
+
+    /// Fraction of detected opportunities we actually captured
+    pub fn capture_rate(&self) -> f64 {
+        if self.races == 0 { 0.0 } else { self.won as f64 / self.races as f64 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_races_depend_on_latency_and_bids() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let model = CompetitorModel {
+            rivals: 1,
+            latency_us: (10.0, 10.0),
+            bid_share: (0.5, 0.5),
+            our_bid_share: 0.3,
+            block_time_us: 1_000.0,
+            seed: 1,
+        };
+
+        // No rivals: every opportunity is ours at our bid
+        let alone = CompetitorModel { rivals: 0, ..model.clone() };
+        assert_eq!(alone.race(&mut rng, 5_000.0, 100.0), RaceResult::Won { bid_usd: 30.0 });
+
+        // Slower than the block but the rival is not: it takes it
+        assert_eq!(model.race(&mut rng, 5_000.0, 100.0), RaceResult::TooSlow);
+
+        // Bidding above the rival wins whenever we are in the block alongside it
+        let generous = CompetitorModel { our_bid_share: 0.6, ..model.clone() };
+        let mut stats = CompetitionStats::default();
+        for _ in 0..100 {
+            let result = generous.race(&mut rng, 0.0, 100.0);
+            stats.record(result, 80.0);
+        }
+        assert_eq!(stats.won + stats.too_slow, 100);
+        assert!(stats.won > 90, "{:?}", stats);
+        assert!((stats.captured_profit_usd - stats.won as f64 * 20.0).abs() < 1e-6);
+
+        let mut stats = CompetitionStats::default();
+        stats.record(model.race(&mut rng, 0.0, 100.0), 80.0);
+        assert_eq!((stats.outbid, stats.losing_bid_gaps_usd.clone()), (1, vec![20.0]));
+        assert_eq!(stats.forgone_profit_usd, 80.0);
+    }
+}
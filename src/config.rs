@@ -1,6 +1,37 @@
 use anyhow::{Context, Result};
-use ethers::types::{Address, H256};
+use ethers::types::{Address, H256, U256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
+use std::time::Duration;
+
+use crate::address_policy::AddressPolicy;
+use crate::backpressure::{BackpressurePolicy, BufferConfig};
+use crate::backtesting::ReportSink;
+use crate::builders::BuilderEndpoint;
+use crate::calibration::DriftThresholds;
+use crate::competition::CompetitorModel;
+use crate::executor::SubmissionRetries;
+use crate::batching::BatchPolicy;
+use crate::exposure::{DrawdownLimits, ExposureLimits};
+use crate::funding::{FundingSource, SwapRoute};
+use crate::gas::GasModel;
+use crate::inventory::InventoryLimits;
+use crate::liquidation_detector::{PositionStaleness, SignalTtl, StalePolicy};
+use crate::mempool_streamer::{ArrivalProcess, TrafficProfile};
+use crate::oracle::OracleSources;
+use crate::performance::PerformanceTargets;
+use crate::protocol::HealthFactorScale;
+use crate::rate_limit::RateLimits;
+use crate::simulator::{ProfitThresholds, SimulationBackend};
+use crate::submission::{RouteKind, RoutePolicy, FLASHBOTS_PROTECT_URL};
+use crate::run_metadata;
+use crate::stress::StressScenario;
+use crate::subgraph::SubgraphBootstrap;
+use crate::sweep::SweepGrid;
+use crate::user_operations::ENTRY_POINT_V06;
+use crate::valuation::SwapVenue;
+use crate::venus::VenusMarket;
+use crate::wallets::RotationStrategy;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -9,18 +40,165 @@ pub struct Config {
     pub chain_id: u64,
     pub lending_protocol_address: Address,
     pub mock_token_address: Address,
-    pub liquidator_private_key: Option<H256>,
+    pub liquidator_private_keys: Vec<H256>,
+    pub wallet_rotation: RotationStrategy,
     pub min_profit_threshold_usd: f64,
+    pub min_profit_by_protocol_usd: HashMap<String, f64>,
+    pub min_profit_by_debt_asset_usd: HashMap<Address, f64>,
+    pub min_profit_by_collateral_usd: HashMap<Address, f64>,
     pub max_gas_price_gwei: u64,
+    pub gas_limit_multiplier: f64,
+    pub legacy_transactions: bool,
+    pub presign_max_age_ms: u64,
+    pub speculative_build: bool,
+    pub signal_max_age_ms: u64,
+    pub signal_max_block_lag: Option<u64>,
+    pub position_max_block_lag: Option<u64>,
+    pub stale_position_policy: StalePolicy,
     pub mempool_batch_size: usize,
+    pub mempool_buffer_size: usize,
+    pub rpc_batch_window_ms: u64,
+    pub rpc_batch_max_size: usize,
+    pub read_cache_ttl_ms: u64,
+    pub rpc_rate_limit: Option<f64>,
+    pub rpc_rate_limits: HashMap<String, f64>,
+    pub backpressure_policy: BackpressurePolicy,
     pub health_check_interval_ms: u64,
+    pub max_concurrent_executions: usize,
+    pub simulation_workers: usize,
+    pub opportunity_queue_capacity: usize,
+    pub opportunity_max_age_ms: u64,
+    pub backtest_seed: u64,
+    pub synthetic_user_population: usize,
+    pub synthetic_tx_mix: [u32; 4],
+    pub synthetic_tps: f64,
+    pub synthetic_arrivals: ArrivalProcess,
+    pub synthetic_noise_ratio: f64,
+    pub synthetic_population: bool,
+    pub competitor_bots: usize,
+    pub competitor_latency_ms: (f64, f64),
+    pub competitor_bid_share: (f64, f64),
+    pub auction_bid_share: f64,
+    pub block_time_ms: u64,
+    pub monte_carlo_runs: usize,
+    pub monte_carlo_txs_per_run: usize,
+    pub sweep_min_profit_usd: Vec<f64>,
+    pub sweep_bid_share: Vec<f64>,
+    pub sweep_watch_band: Vec<f64>,
+    pub sweep_txs_per_run: usize,
+    pub stress_scenario_files: Vec<String>,
+    pub backtest_gas_scenario: Option<String>,
+    pub simulation_backend: SimulationBackend,
+    pub health_port: Option<u16>,
+    pub grpc_port: Option<u16>,
+    pub control_socket_path: Option<String>,
+    pub max_feed_lag_ms: u64,
+    pub monitor_rpc_urls: Vec<String>,
+    pub max_block_lag: u64,
+    pub pause_on_stale_data: bool,
+    pub market_status_ttl_ms: u64,
+    pub builders: Vec<BuilderEndpoint>,
+    pub builder_auth_key: Option<H256>,
+    pub bundle_target_blocks: u64,
+    pub submission_retries: u32,
+    pub submission_retry_backoff_ms: u64,
+    pub submission_routes: Vec<RouteKind>,
+    pub protect_rpc_url: String,
+    pub public_route_max_profit_usd: f64,
+    pub max_capital_per_opportunity_usd: f64,
+    pub gas_reserve_eth: f64,
+    pub top_up_slippage_bps: u32,
+    pub max_eth_balance: Option<f64>,
+    pub max_debt_asset_balance: Option<f64>,
+    pub collateral_convert_threshold_eth: Option<f64>,
+    pub cold_wallet_address: Option<Address>,
+    pub exposure_window_secs: u64,
+    pub max_protocol_exposure_usd: Option<f64>,
+    pub max_asset_exposure_usd: Option<f64>,
+    pub protocol_exposure_limits_usd: HashMap<String, f64>,
+    pub asset_exposure_limits_usd: HashMap<Address, f64>,
+    pub drawdown_window_secs: u64,
+    pub max_drawdown_usd: Option<f64>,
+    pub drawdown_reduce_at: f64,
+    pub drawdown_reduced_size: f64,
+    pub max_losing_streak: Option<usize>,
+    pub address_allowlist: HashSet<Address>,
+    pub address_blocklist: HashSet<Address>,
+    pub address_labels: HashMap<Address, String>,
+    pub audit_log_path: Option<String>,
+    pub live_summary_path: Option<String>,
+    pub report_dir: String,
+    pub report_name_template: String,
+    pub latency_targets_ms: HashMap<String, f64>,
+    pub bench_max_regression: f64,
+    pub bench_baseline_path: String,
+    pub calibration_window: usize,
+    pub calibration_min_samples: usize,
+    pub max_profit_bias_usd: f64,
+    pub max_gas_bias_pct: f64,
+    pub gas_model: GasModel,
+    pub sequencer_feed_url: Option<String>,
+    pub sequencer_feed_max_txs: usize,
+    pub mempool_capture_path: Option<String>,
+    pub mempool_capture_speed: f64,
+    pub mempool_capture_max_txs: usize,
+    pub bundler_url: Option<String>,
+    pub entry_point_address: Address,
+    pub user_operation_max_ops: usize,
+    pub user_operation_poll_ms: u64,
+    pub chainlink_eth_usd_feed: Option<Address>,
+    pub chainlink_max_age_secs: u64,
+    pub pyth_contract_address: Option<Address>,
+    pub pyth_eth_usd_price_id: H256,
+    pub pyth_max_age_secs: u64,
+    pub price_confidence_band: f64,
+    pub twap_pool_address: Option<Address>,
+    pub twap_window_secs: u32,
+    pub twap_eth_is_token0: bool,
+    pub max_price_divergence_bps: u32,
+    pub balancer_vault_address: Option<Address>,
+    pub uniswap_v3_flash_pools: Vec<Address>,
+    pub liquidation_helper_address: Option<Address>,
+    pub venus_comptroller_address: Option<Address>,
+    pub venus_borrowed_vtoken: Option<Address>,
+    pub venus_collateral_vtoken: Option<Address>,
+    pub morpho_blue_address: Option<Address>,
+    pub morpho_market_id: Option<H256>,
+    pub euler_liability_vault: Option<Address>,
+    pub weth_address: Option<Address>,
+    pub helper_swap_pool: Option<Address>,
+    pub batch_max_size: usize,
+    pub batch_max_repay_usd: f64,
+    pub uniswap_v3_quoter_address: Option<Address>,
+    pub uniswap_v3_swap_router: Option<Address>,
+    pub collateral_swap_fee_tier: u32,
+    pub curve_pool_address: Option<Address>,
+    pub curve_eth_index: i128,
+    pub curve_debt_index: i128,
+    pub slippage_min_value_usd: f64,
+    pub backtest_sale_fee_bps: u32,
+    pub backtest_sale_depth_usd: f64,
+    pub watchlist_hf_band: f64,
+    pub watchlist_block_sweep: bool,
+    pub health_factor_scale: HealthFactorScale,
+    pub position_history_path: Option<String>,
+    pub position_history_max_samples: usize,
+    pub opportunity_feed_size: usize,
+    pub dashboard_push_ms: u64,
+    pub event_buffer_size: usize,
+    pub subgraph_url: Option<String>,
+    pub subgraph_entity: String,
+    pub subgraph_health_factor_field: String,
+    pub subgraph_page_size: usize,
+    pub latency_nanoseconds: bool,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
         dotenv::dotenv().ok(); // Load .env file if it exists
 
-        Ok(Config {
+        let mut config = Config {
+            // HTTP URL, or the IPC socket path of a co-located node
             anvil_rpc_url: env::var("ANVIL_RPC_URL")
                 .unwrap_or_else(|_| "http://127.0.0.1:8545".to_string()),
             
@@ -35,39 +213,956 @@ impl Config {
             lending_protocol_address: env::var("LENDING_PROTOCOL_ADDRESS")
                 .ok()
                 .and_then(|s| s.parse().ok())
-                .unwrap_or_else(|| Address::zero()),
+                .unwrap_or_else(Address::zero),
             
             mock_token_address: env::var("MOCK_TOKEN_ADDRESS")
                 .ok()
                 .and_then(|s| s.parse().ok())
-                .unwrap_or_else(|| Address::zero()),
+                .unwrap_or_else(Address::zero),
             
-            liquidator_private_key: env::var("LIQUIDATOR_PRIVATE_KEY")
-                .ok()
-                .and_then(|s| s.parse().ok()),
+            // LIQUIDATOR_PRIVATE_KEYS takes a comma-separated list; the single-key
+            // LIQUIDATOR_PRIVATE_KEY is still honoured when the list is absent
+            liquidator_private_keys: env::var("LIQUIDATOR_PRIVATE_KEYS")
+                .or_else(|_| env::var("LIQUIDATOR_PRIVATE_KEY"))
+                .map(|keys| keys
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(|key| key.parse().context("Invalid liquidator private key"))
+                    .collect::<Result<Vec<H256>>>())
+                .unwrap_or_else(|_| Ok(Vec::new()))?,
+            
+            wallet_rotation: env::var("WALLET_ROTATION")
+                .unwrap_or_else(|_| "round_robin".to_string())
+                .parse()
+                .context("Invalid WALLET_ROTATION")?,
             
             min_profit_threshold_usd: env::var("MIN_PROFIT_THRESHOLD_USD")
                 .unwrap_or_else(|_| "10.0".to_string())
                 .parse()
                 .context("Invalid MIN_PROFIT_THRESHOLD_USD")?,
             
+            // Per-market overrides as key=usd pairs (protocol name or asset address);
+            // the highest matching one applies
+            min_profit_by_protocol_usd: parse_limit_map("MIN_PROFIT_BY_PROTOCOL_USD")?,
+            min_profit_by_debt_asset_usd: parse_limit_map("MIN_PROFIT_BY_DEBT_ASSET_USD")?,
+            min_profit_by_collateral_usd: parse_limit_map("MIN_PROFIT_BY_COLLATERAL_USD")?,
+            
             max_gas_price_gwei: env::var("MAX_GAS_PRICE_GWEI")
                 .unwrap_or_else(|_| "100".to_string())
                 .parse()
                 .context("Invalid MAX_GAS_PRICE_GWEI")?,
             
+            // Gas limit = simulated gas * this margin
+            gas_limit_multiplier: env::var("GAS_LIMIT_MULTIPLIER")
+                .unwrap_or_else(|_| "1.2".to_string())
+                .parse()
+                .context("Invalid GAS_LIMIT_MULTIPLIER")?,
+            
+            // Type-0 transactions with a single gas price, for chains without EIP-1559
+            legacy_transactions: env::var("LEGACY_TRANSACTIONS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("Invalid LEGACY_TRANSACTIONS")?,
+            
+            // How long a presigned watchlist liquidation stays sendable; 0 disables presigning
+            presign_max_age_ms: env::var("PRESIGN_MAX_AGE_MS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .context("Invalid PRESIGN_MAX_AGE_MS")?,
+            
+            // Sign each liquidation while it is simulated, dropping it if unprofitable
+            speculative_build: env::var("SPECULATIVE_BUILD")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("Invalid SPECULATIVE_BUILD")?,
+            
+            // Signals older than this are not simulated or submitted; 0 disables
+            signal_max_age_ms: env::var("SIGNAL_MAX_AGE_MS")
+                .unwrap_or_else(|_| "12000".to_string())
+                .parse()
+                .context("Invalid SIGNAL_MAX_AGE_MS")?,
+            
+            // Blocks the head may advance past the one a signal was read at (unset = no limit)
+            signal_max_block_lag: optional_var("SIGNAL_MAX_BLOCK_LAG")?,
+            
+            // Liquidatable positions read more than this many blocks ago are stale
+            // (unset = never); STALE_POSITION_POLICY: refresh | down-rank
+            position_max_block_lag: optional_var("POSITION_MAX_BLOCK_LAG")?,
+            stale_position_policy: parse_stale_policy(
+                &env::var("STALE_POSITION_POLICY").unwrap_or_else(|_| "refresh".to_string()),
+            )?,
+            
             mempool_batch_size: env::var("MEMPOOL_BATCH_SIZE")
                 .unwrap_or_else(|_| "100".to_string())
                 .parse()
                 .context("Invalid MEMPOOL_BATCH_SIZE")?,
             
+            // Coalesce RPC lookups made within this window into one batch; 0 disables
+            rpc_batch_window_ms: env::var("RPC_BATCH_WINDOW_MS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .context("Invalid RPC_BATCH_WINDOW_MS")?,
+            
+            rpc_batch_max_size: env::var("RPC_BATCH_MAX_SIZE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .context("Invalid RPC_BATCH_MAX_SIZE")?,
+            
+            // Reuse reads within a block, for at most this long; 0 disables
+            read_cache_ttl_ms: env::var("READ_CACHE_TTL_MS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .context("Invalid READ_CACHE_TTL_MS")?,
+            
+            // Requests per second per RPC endpoint (unset = unlimited); RPC_RATE_LIMITS
+            // overrides it per provider as host=rps pairs, comma-separated
+            rpc_rate_limit: optional_var("RPC_RATE_LIMIT")?,
+            rpc_rate_limits: parse_limit_map("RPC_RATE_LIMITS")?,
+            
+            // Transactions buffered ahead of the detector
+            mempool_buffer_size: env::var("MEMPOOL_BUFFER_SIZE")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .context("Invalid MEMPOOL_BUFFER_SIZE")?,
+            
+            // What to do when that buffer is full: block | drop-oldest | drop-non-protocol | expand
+            backpressure_policy: parse_backpressure_policy(
+                &env::var("BACKPRESSURE_POLICY").unwrap_or_else(|_| "block".to_string()),
+                env::var("MEMPOOL_BUFFER_MAX_SIZE")
+                    .unwrap_or_else(|_| "10000".to_string())
+                    .parse()
+                    .context("Invalid MEMPOOL_BUFFER_MAX_SIZE")?,
+            )?,
+            
             health_check_interval_ms: env::var("HEALTH_CHECK_INTERVAL_MS")
                 .unwrap_or_else(|_| "100".to_string())
                 .parse()
                 .context("Invalid HEALTH_CHECK_INTERVAL_MS")?,
-        })
+            
+            max_concurrent_executions: env::var("MAX_CONCURRENT_EXECUTIONS")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()
+                .context("Invalid MAX_CONCURRENT_EXECUTIONS")?,
+            
+            // Simulations run at once; 0 gives one worker per execution slot
+            simulation_workers: env::var("SIMULATION_WORKERS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .context("Invalid SIMULATION_WORKERS")?,
+            
+            // Signals waiting for a free worker, served best first
+            opportunity_queue_capacity: env::var("OPPORTUNITY_QUEUE_CAPACITY")
+                .unwrap_or_else(|_| "256".to_string())
+                .parse()
+                .context("Invalid OPPORTUNITY_QUEUE_CAPACITY")?,
+            
+            // Queued signals older than this are dropped as stale
+            opportunity_max_age_ms: env::var("OPPORTUNITY_MAX_AGE_MS")
+                .unwrap_or_else(|_| "12000".to_string())
+                .parse()
+                .context("Invalid OPPORTUNITY_MAX_AGE_MS")?,
+            
+            backtest_seed: env::var("BACKTEST_SEED")
+                .unwrap_or_else(|_| "42".to_string())
+                .parse()
+                .context("Invalid BACKTEST_SEED")?,
+            
+            synthetic_user_population: env::var("SYNTHETIC_USER_POPULATION")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .context("Invalid SYNTHETIC_USER_POPULATION")?,
+            
+            // Relative deposit,borrow,withdraw,repay weights
+            synthetic_tx_mix: parse_tx_mix(
+                &env::var("SYNTHETIC_TX_MIX").unwrap_or_else(|_| "4,3,2,1".to_string())
+            )?,
+            
+            // Mean synthetic transactions per second
+            synthetic_tps: env::var("SYNTHETIC_TPS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .context("Invalid SYNTHETIC_TPS")?,
+            
+            // fixed, poisson, bursty (SYNTHETIC_BURST_SIZE back-to-back) or firehose (no delay)
+            synthetic_arrivals: parse_arrival_process(
+                &env::var("SYNTHETIC_ARRIVALS").unwrap_or_else(|_| "fixed".to_string()),
+                env::var("SYNTHETIC_BURST_SIZE")
+                    .unwrap_or_else(|_| "50".to_string())
+                    .parse()
+                    .context("Invalid SYNTHETIC_BURST_SIZE")?,
+            )?,
+            
+            // Share of synthetic transactions that are unrelated transfers, swaps and calls
+            synthetic_noise_ratio: env::var("SYNTHETIC_NOISE_RATIO")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .context("Invalid SYNTHETIC_NOISE_RATIO")?,
+            
+            // Synthetic users instead of the chain-backed detector (opt-in)
+            synthetic_population: env::var("SYNTHETIC_POPULATION")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("Invalid SYNTHETIC_POPULATION")?,
+            
+            // COMPETITOR_BOTS=0 assumes every detected opportunity is captured
+            competitor_bots: env::var("COMPETITOR_BOTS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .context("Invalid COMPETITOR_BOTS")?,
+            
+            competitor_latency_ms: parse_range(
+                "COMPETITOR_LATENCY_MS",
+                &env::var("COMPETITOR_LATENCY_MS").unwrap_or_else(|_| "1,50".to_string()),
+            )?,
+            
+            competitor_bid_share: parse_range(
+                "COMPETITOR_BID_SHARE",
+                &env::var("COMPETITOR_BID_SHARE").unwrap_or_else(|_| "0.1,0.5".to_string()),
+            )?,
+            
+            auction_bid_share: env::var("AUCTION_BID_SHARE")
+                .unwrap_or_else(|_| "0.3".to_string())
+                .parse()
+                .context("Invalid AUCTION_BID_SHARE")?,
+            
+            block_time_ms: env::var("BLOCK_TIME_MS")
+                .unwrap_or_else(|_| "12000".to_string())
+                .parse()
+                .context("Invalid BLOCK_TIME_MS")?,
+            
+            // MONTE_CARLO_RUNS=0 skips the Monte Carlo backtest
+            monte_carlo_runs: env::var("MONTE_CARLO_RUNS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .context("Invalid MONTE_CARLO_RUNS")?,
+            
+            monte_carlo_txs_per_run: env::var("MONTE_CARLO_TXS_PER_RUN")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .context("Invalid MONTE_CARLO_TXS_PER_RUN")?,
+            
+            // Comma-separated values to sweep; setting none skips the parameter sweep
+            sweep_min_profit_usd: parse_list("SWEEP_MIN_PROFIT_USD")?,
+            sweep_bid_share: parse_list("SWEEP_BID_SHARE")?,
+            sweep_watch_band: parse_list("SWEEP_WATCH_BAND")?,
+            sweep_txs_per_run: env::var("SWEEP_TXS_PER_RUN")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .context("Invalid SWEEP_TXS_PER_RUN")?,
+            
+            // Comma-separated JSON scenario files run alongside the built-in ones
+            stress_scenario_files: parse_list("STRESS_SCENARIO_FILES")?,
+            // Scenario whose base-fee trajectory the simulator follows in backtests
+            backtest_gas_scenario: optional_var("BACKTEST_GAS_SCENARIO")?,
+            
+            simulation_backend: parse_simulation_backend(
+                &env::var("SIMULATION_BACKEND").unwrap_or_else(|_| "analytical".to_string()),
+                env::var("SNAPSHOT_LIQUIDATOR_ADDRESS").ok(),
+            )?,
+            
+            health_port: env::var("HEALTH_PORT")
+                .ok()
+                .map(|port| port.parse())
+                .transpose()
+                .context("Invalid HEALTH_PORT")?,
+            
+            // gRPC API (builds with the `grpc` feature); unset disables it
+            grpc_port: optional_var("GRPC_PORT")?,
+            
+            // Local pause/resume/tuning socket; unset disables it
+            control_socket_path: env::var("CONTROL_SOCKET_PATH").ok().filter(|path| !path.is_empty()),
+            
+            max_feed_lag_ms: env::var("MAX_FEED_LAG_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .context("Invalid MAX_FEED_LAG_MS")?,
+            
+            // Extra RPCs polled only to establish the network head
+            monitor_rpc_urls: env::var("MONITOR_RPC_URLS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(String::from)
+                .collect(),
+            
+            max_block_lag: env::var("MAX_BLOCK_LAG")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .context("Invalid MAX_BLOCK_LAG")?,
+            
+            pause_on_stale_data: env::var("PAUSE_ON_STALE_DATA")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("Invalid PAUSE_ON_STALE_DATA")?,
+            
+            // How long a market's paused/frozen status is trusted; 0 never checks
+            market_status_ttl_ms: env::var("MARKET_STATUS_TTL_MS")
+                .unwrap_or_else(|_| "12000".to_string())
+                .parse()
+                .context("Invalid MARKET_STATUS_TTL_MS")?,
+            
+            // Builder names (flashbots, beaverbuild, rsync, titan) or name=url pairs
+            builders: env::var("BUILDERS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|builder| !builder.is_empty())
+                .map(BuilderEndpoint::parse)
+                .collect::<Result<Vec<_>>>()
+                .context("Invalid BUILDERS")?,
+            
+            builder_auth_key: env::var("BUILDER_AUTH_KEY")
+                .ok()
+                .map(|key| key.parse())
+                .transpose()
+                .context("Invalid BUILDER_AUTH_KEY")?,
+            
+            // Most consecutive blocks one liquidation bundle is submitted for
+            bundle_target_blocks: env::var("BUNDLE_TARGET_BLOCKS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .context("Invalid BUNDLE_TARGET_BLOCKS")?,
+            
+            // Resends of a bundle no builder accepted, after checking the user wasn't liquidated meanwhile
+            submission_retries: env::var("SUBMISSION_RETRIES")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .context("Invalid SUBMISSION_RETRIES")?,
+            
+            submission_retry_backoff_ms: env::var("SUBMISSION_RETRY_BACKOFF_MS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .context("Invalid SUBMISSION_RETRY_BACKOFF_MS")?,
+            
+            // Routes liquidations may be sent on (public, protect, bundle); empty means bundle with BUILDERS
+            submission_routes: env::var("SUBMISSION_ROUTES")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|route| !route.is_empty())
+                .map(RouteKind::parse)
+                .collect::<Result<Vec<_>>>()
+                .context("Invalid SUBMISSION_ROUTES")?,
+            
+            protect_rpc_url: env::var("PROTECT_RPC_URL")
+                .unwrap_or_else(|_| FLASHBOTS_PROTECT_URL.to_string()),
+            
+            // Expected profit above which a liquidation is kept out of the public mempool
+            public_route_max_profit_usd: env::var("PUBLIC_ROUTE_MAX_PROFIT_USD")
+                .unwrap_or_else(|_| "25".to_string())
+                .parse()
+                .context("Invalid PUBLIC_ROUTE_MAX_PROFIT_USD")?,
+            
+            max_capital_per_opportunity_usd: env::var("MAX_CAPITAL_PER_OPPORTUNITY_USD")
+                .unwrap_or_else(|_| "100000".to_string())
+                .parse()
+                .context("Invalid MAX_CAPITAL_PER_OPPORTUNITY_USD")?,
+            
+            gas_reserve_eth: env::var("GAS_RESERVE_ETH")
+                .unwrap_or_else(|_| "0.05".to_string())
+                .parse()
+                .context("Invalid GAS_RESERVE_ETH")?,
+            
+            // ETH sent above the quote when a wallet buys a repayment it's short of
+            top_up_slippage_bps: env::var("TOP_UP_SLIPPAGE_BPS")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .context("Invalid TOP_UP_SLIPPAGE_BPS")?,
+            
+            // Sweep/convert thresholds are off unless set
+            max_eth_balance: optional_var("MAX_ETH_BALANCE")?,
+            max_debt_asset_balance: optional_var("MAX_DEBT_ASSET_BALANCE")?,
+            collateral_convert_threshold_eth: optional_var("COLLATERAL_CONVERT_THRESHOLD_ETH")?,
+            cold_wallet_address: optional_var("COLD_WALLET_ADDRESS")?,
+            
+            exposure_window_secs: env::var("EXPOSURE_WINDOW_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .context("Invalid EXPOSURE_WINDOW_SECS")?,
+            
+            // Defaults for any protocol/asset without its own entry below
+            max_protocol_exposure_usd: optional_var("MAX_PROTOCOL_EXPOSURE_USD")?,
+            max_asset_exposure_usd: optional_var("MAX_ASSET_EXPOSURE_USD")?,
+            
+            // name=usd / 0xasset=usd pairs, comma-separated
+            protocol_exposure_limits_usd: parse_limit_map("PROTOCOL_EXPOSURE_LIMITS_USD")?,
+            asset_exposure_limits_usd: parse_limit_map("ASSET_EXPOSURE_LIMITS_USD")?,
+            
+            // Realized PnL window the drawdown and losing-streak limits apply to
+            drawdown_window_secs: env::var("DRAWDOWN_WINDOW_SECS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()
+                .context("Invalid DRAWDOWN_WINDOW_SECS")?,
+            
+            // Net loss / consecutive losses that pause execution (unset = no limit)
+            max_drawdown_usd: optional_var("MAX_DRAWDOWN_USD")?,
+            max_losing_streak: optional_var("MAX_LOSING_STREAK")?,
+            
+            // Past this share of MAX_DRAWDOWN_USD, repay only DRAWDOWN_REDUCED_SIZE of the usual amount
+            drawdown_reduce_at: env::var("DRAWDOWN_REDUCE_AT")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()
+                .context("Invalid DRAWDOWN_REDUCE_AT")?,
+            drawdown_reduced_size: env::var("DRAWDOWN_REDUCED_SIZE")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()
+                .context("Invalid DRAWDOWN_REDUCED_SIZE")?,
+            
+            // Borrowers never to liquidate / the only ones to liquidate (empty = all)
+            address_blocklist: parse_address_list("ADDRESS_BLOCKLIST")?,
+            address_allowlist: parse_address_list("ADDRESS_ALLOWLIST")?,
+            // 0xaddress=label pairs, comma-separated
+            address_labels: parse_address_labels("ADDRESS_LABELS")?,
+            
+            // Settlements simulator calibration is judged over, and the bias that raises a drift alert
+            calibration_window: env::var("CALIBRATION_WINDOW")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .context("Invalid CALIBRATION_WINDOW")?,
+            
+            calibration_min_samples: env::var("CALIBRATION_MIN_SAMPLES")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .context("Invalid CALIBRATION_MIN_SAMPLES")?,
+            
+            max_profit_bias_usd: env::var("MAX_PROFIT_BIAS_USD")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .context("Invalid MAX_PROFIT_BIAS_USD")?,
+            
+            max_gas_bias_pct: env::var("MAX_GAS_BIAS_PCT")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .context("Invalid MAX_GAS_BIAS_PCT")?,
+            
+            // The journal writes every stage synchronously, so it is off unless set
+            audit_log_path: optional_var("AUDIT_LOG_PATH")?,
+            
+            // Rolling 1- and 5-minute summaries, one JSON line each; empty only logs them
+            live_summary_path: Some(
+                env::var("LIVE_SUMMARY_PATH")
+                    .unwrap_or_else(|_| "benchmark_results/live_summary.jsonl".to_string()),
+            )
+            .filter(|path| !path.trim().is_empty()),
+            
+            // Where reports go (overridden by --out-dir)
+            report_dir: env::var("REPORT_DIR").unwrap_or_else(|_| "benchmark_results".to_string()),
+            
+            // {mode}, {chain} and {timestamp} are filled in (overridden by --report-name)
+            report_name_template: env::var("REPORT_NAME_TEMPLATE").unwrap_or_else(|_| "{mode}".to_string()),
+            
+            // P99 targets (ms) per stage, e.g. simulation_us=3; unlisted stages keep their defaults
+            latency_targets_ms: parse_limit_map("LATENCY_TARGETS_MS")?,
+            
+            // How much slower than its baseline a stage's P99 may get before `bench --assert` fails
+            bench_max_regression: env::var("BENCH_MAX_REGRESSION")
+                .unwrap_or_else(|_| "0.10".to_string())
+                .parse()
+                .context("Invalid BENCH_MAX_REGRESSION")?,
+            
+            // Stress test report `bench --assert` compares against (--baseline overrides)
+            bench_baseline_path: env::var("BENCH_BASELINE_PATH")
+                .unwrap_or_else(|_| "benchmark_results/bench_baseline.json".to_string()),
+            
+            gas_model: GasModel::L1,
+            
+            // Arbitrum has no public mempool; read pre-confirmation txs from the sequencer
+            sequencer_feed_url: env::var("SEQUENCER_FEED_URL").ok().filter(|url| !url.trim().is_empty()),
+            
+            sequencer_feed_max_txs: env::var("SEQUENCER_FEED_MAX_TXS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .context("Invalid SEQUENCER_FEED_MAX_TXS")?,
+            
+            // Recorded pending transactions to replay as a backtest (JSONL, see README)
+            mempool_capture_path: optional_var("MEMPOOL_CAPTURE_PATH")?,
+            
+            // 1.0 keeps the recorded pace, 10.0 replays ten times faster, 0 without pauses
+            mempool_capture_speed: env::var("MEMPOOL_CAPTURE_SPEED")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .context("Invalid MEMPOOL_CAPTURE_SPEED")?,
+            
+            mempool_capture_max_txs: env::var("MEMPOOL_CAPTURE_MAX_TXS")
+                .unwrap_or_else(|_| "100000".to_string())
+                .parse()
+                .context("Invalid MEMPOOL_CAPTURE_MAX_TXS")?,
+            
+            // Bundler exposing debug_bundler_dumpMempool, to watch smart-account UserOperations
+            bundler_url: optional_var("BUNDLER_URL")?,
+            
+            entry_point_address: env::var("ENTRY_POINT_ADDRESS")
+                .unwrap_or_else(|_| ENTRY_POINT_V06.to_string())
+                .parse()
+                .context("Invalid ENTRY_POINT_ADDRESS")?,
+            
+            user_operation_max_ops: env::var("USER_OPERATION_MAX_OPS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .context("Invalid USER_OPERATION_MAX_OPS")?,
+            
+            // How often the bundler mempool is polled
+            user_operation_poll_ms: env::var("USER_OPERATION_POLL_MS")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .context("Invalid USER_OPERATION_POLL_MS")?,
+            
+            // Price sources; with neither set the simulator uses a fixed ETH price
+            chainlink_eth_usd_feed: optional_var("CHAINLINK_ETH_USD_FEED")?,
+            
+            chainlink_max_age_secs: env::var("CHAINLINK_MAX_AGE_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .context("Invalid CHAINLINK_MAX_AGE_SECS")?,
+            
+            pyth_contract_address: optional_var("PYTH_CONTRACT_ADDRESS")?,
+            
+            // Pyth's ETH/USD feed id, the same on every chain
+            pyth_eth_usd_price_id: env::var("PYTH_ETH_USD_PRICE_ID")
+                .unwrap_or_else(|_| "0xff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace".to_string())
+                .parse()
+                .context("Invalid PYTH_ETH_USD_PRICE_ID")?,
+            
+            pyth_max_age_secs: env::var("PYTH_MAX_AGE_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .context("Invalid PYTH_MAX_AGE_SECS")?,
+            
+            price_confidence_band: env::var("PRICE_CONFIDENCE_BAND")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .context("Invalid PRICE_CONFIDENCE_BAND")?,
+            
+            twap_pool_address: optional_var("TWAP_POOL_ADDRESS")?,
+            
+            twap_window_secs: env::var("TWAP_WINDOW_SECS")
+                .unwrap_or_else(|_| "1800".to_string())
+                .parse()
+                .context("Invalid TWAP_WINDOW_SECS")?,
+            
+            twap_eth_is_token0: env::var("TWAP_ETH_IS_TOKEN0")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("Invalid TWAP_ETH_IS_TOKEN0")?,
+            
+            max_price_divergence_bps: env::var("MAX_PRICE_DIVERGENCE_BPS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .context("Invalid MAX_PRICE_DIVERGENCE_BPS")?,
+            
+            // Flash liquidity the simulator may fund repayments from
+            balancer_vault_address: optional_var("BALANCER_VAULT_ADDRESS")?,
+            
+            uniswap_v3_flash_pools: env::var("UNISWAP_V3_FLASH_POOLS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|pool| !pool.is_empty())
+                .map(|pool| pool.parse().context("Invalid UNISWAP_V3_FLASH_POOLS"))
+                .collect::<Result<Vec<Address>>>()?,
+            
+            // LiquidationHelper to route liquidations through (`liquidio deploy-helper`)
+            liquidation_helper_address: optional_var("LIQUIDATION_HELPER_ADDRESS")?,
+            
+            // Venus Comptroller on BNB Chain; risk params are read from it when set
+            venus_comptroller_address: optional_var("VENUS_COMPTROLLER_ADDRESS")?,
+            
+            // vTokens liquidated with liquidateBorrow: debt repaid into the first,
+            // collateral seized from the second
+            venus_borrowed_vtoken: optional_var("VENUS_BORROWED_VTOKEN")?,
+            venus_collateral_vtoken: optional_var("VENUS_COLLATERAL_VTOKEN")?,
+            
+            // Morpho Blue singleton and the market (bytes32 id) to watch and liquidate
+            morpho_blue_address: optional_var("MORPHO_BLUE_ADDRESS")?,
+            morpho_market_id: optional_var("MORPHO_MARKET_ID")?,
+            
+            // Euler V2 (EVK) vault whose borrowers are watched; its discount curve sets the bonus
+            euler_liability_vault: optional_var("EULER_LIABILITY_VAULT")?,
+            
+            // Wrapped ETH, sold by the helper and quoted on Uniswap
+            weth_address: optional_var("WETH_ADDRESS")?,
+            
+            // WETH/debt asset pool a newly deployed helper sells collateral into
+            helper_swap_pool: optional_var("HELPER_SWAP_POOL")?,
+            
+            // Liquidations found together sent as one helper transaction (below 2 = off)
+            batch_max_size: env::var("BATCH_MAX_SIZE")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .context("Invalid BATCH_MAX_SIZE")?,
+            
+            // Only positions repaying at most this much are batched
+            batch_max_repay_usd: env::var("BATCH_MAX_REPAY_USD")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .context("Invalid BATCH_MAX_REPAY_USD")?,
+            
+            // Venues quoting what seized collateral actually sells for
+            uniswap_v3_quoter_address: optional_var("UNISWAP_V3_QUOTER_ADDRESS")?,
+            
+            // SwapRouter02 wallets buy a missing repayment through, with ETH
+            uniswap_v3_swap_router: optional_var("UNISWAP_V3_SWAP_ROUTER")?,
+            
+            collateral_swap_fee_tier: env::var("COLLATERAL_SWAP_FEE_TIER")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .context("Invalid COLLATERAL_SWAP_FEE_TIER")?,
+            
+            curve_pool_address: optional_var("CURVE_POOL_ADDRESS")?,
+            
+            curve_eth_index: env::var("CURVE_ETH_INDEX")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .context("Invalid CURVE_ETH_INDEX")?,
+            
+            curve_debt_index: env::var("CURVE_DEBT_INDEX")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .context("Invalid CURVE_DEBT_INDEX")?,
+            
+            // Smaller collateral is valued at the oracle price without quoting
+            slippage_min_value_usd: env::var("SLIPPAGE_MIN_VALUE_USD")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .context("Invalid SLIPPAGE_MIN_VALUE_USD")?,
+            
+            // Backtests sell seized collateral after this fee into a constant-product
+            // pool this deep (USD per side; "inf" for no price impact)
+            backtest_sale_fee_bps: env::var("BACKTEST_SALE_FEE_BPS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .context("Invalid BACKTEST_SALE_FEE_BPS")?,
+            
+            backtest_sale_depth_usd: env::var("BACKTEST_SALE_DEPTH_USD")
+                .unwrap_or_else(|_| "5000000".to_string())
+                .parse()
+                .context("Invalid BACKTEST_SALE_DEPTH_USD")?,
+            
+            // Positions between the liquidation threshold and this are re-read on every price tick
+            watchlist_hf_band: env::var("WATCHLIST_HF_BAND")
+                .unwrap_or_else(|_| "1.05".to_string())
+                .parse()
+                .context("Invalid WATCHLIST_HF_BAND")?,
+            
+            // Also re-read the watchlist once per block (chain positions only)
+            watchlist_block_sweep: env::var("WATCHLIST_BLOCK_SWEEP")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .context("Invalid WATCHLIST_BLOCK_SWEEP")?,
+            
+            // How getPosition encodes HF 1.0: percent (100), wad (1e18, Aave) or the raw
+            // value; positions below LIQUIDATION_HF_THRESHOLD are liquidatable
+            health_factor_scale: parse_health_factor_scale(
+                &env::var("HEALTH_FACTOR_SCALE").unwrap_or_else(|_| "percent".to_string()),
+                env::var("LIQUIDATION_HF_THRESHOLD")
+                    .unwrap_or_else(|_| "1.0".to_string())
+                    .parse()
+                    .context("Invalid LIQUIDATION_HF_THRESHOLD")?,
+            )?,
+            
+            // Set POSITION_HISTORY_PATH= (empty) to keep history in memory only
+            position_history_path: Some(
+                env::var("POSITION_HISTORY_PATH")
+                    .unwrap_or_else(|_| "benchmark_results/position_history.jsonl".to_string()),
+            )
+            .filter(|path| !path.trim().is_empty()),
+            
+            position_history_max_samples: env::var("POSITION_HISTORY_MAX_SAMPLES")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .context("Invalid POSITION_HISTORY_MAX_SAMPLES")?,
+            
+            // Recent opportunities kept for the ranked /opportunities feed
+            opportunity_feed_size: env::var("OPPORTUNITY_FEED_SIZE")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .context("Invalid OPPORTUNITY_FEED_SIZE")?,
+            
+            // Shortest gap between snapshots pushed to a /dashboard client
+            dashboard_push_ms: env::var("DASHBOARD_PUSH_MS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .context("Invalid DASHBOARD_PUSH_MS")?,
+            
+            // Events an /events subscriber may fall behind by before missing some
+            event_buffer_size: env::var("EVENT_BUFFER_SIZE")
+                .unwrap_or_else(|_| "1024".to_string())
+                .parse()
+                .context("Invalid EVENT_BUFFER_SIZE")?,
+            
+            // Subgraph listing active borrowers, read once at startup to seed the detector
+            subgraph_url: optional_var("SUBGRAPH_URL")?,
+            subgraph_entity: env::var("SUBGRAPH_ENTITY").unwrap_or_else(|_| "users".to_string()),
+            subgraph_health_factor_field: env::var("SUBGRAPH_HEALTH_FACTOR_FIELD")
+                .unwrap_or_else(|_| "healthFactor".to_string()),
+            subgraph_page_size: env::var("SUBGRAPH_PAGE_SIZE")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .context("Invalid SUBGRAPH_PAGE_SIZE")?,
+            
+            latency_nanoseconds: env::var("LATENCY_NANOSECONDS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .context("Invalid LATENCY_NANOSECONDS")?,
+        };
+        
+        // GAS_MODEL=auto (default) picks the model from CHAIN_ID
+        config.gas_model = match env::var("GAS_MODEL").unwrap_or_else(|_| "auto".to_string()).as_str() {
+            "auto" => GasModel::for_chain(config.chain_id),
+            model => model.parse().context("Invalid GAS_MODEL")?,
+        };
+        Ok(config)
     }
 
+    /// Synthetic mempool traffic profile for backtests
+    pub fn traffic_profile(&self) -> TrafficProfile {
+        TrafficProfile {
+            seed: self.backtest_seed,
+            tx_mix: self.synthetic_tx_mix,
+            user_population: self.synthetic_user_population,
+            target_tps: self.synthetic_tps,
+            arrivals: self.synthetic_arrivals,
+            noise_ratio: self.synthetic_noise_ratio,
+            ..TrafficProfile::default()
+        }
+    }
+    
+    /// Rival bots to race in backtests, if any are configured
+    pub fn competitor_model(&self) -> Option<CompetitorModel> {
+        (self.competitor_bots > 0).then_some(CompetitorModel {
+            rivals: self.competitor_bots,
+            latency_us: (self.competitor_latency_ms.0 * 1e3, self.competitor_latency_ms.1 * 1e3),
+            bid_share: self.competitor_bid_share,
+            our_bid_share: self.auction_bid_share,
+            block_time_us: self.block_time_ms as f64 * 1e3,
+            seed: self.backtest_seed,
+        })
+    }
+    
+    /// Capital limits and rebalancing thresholds for the executor wallets
+    pub fn inventory_limits(&self) -> InventoryLimits {
+        let wei = |amount: f64| U256::from((amount.max(0.0) * 1e6) as u128) * U256::exp10(12);
+        InventoryLimits {
+            max_capital_per_opportunity_usd: self.max_capital_per_opportunity_usd,
+            gas_reserve: wei(self.gas_reserve_eth),
+            max_eth_balance: self.max_eth_balance.map(wei),
+            max_debt_asset_balance: self.max_debt_asset_balance.map(wei),
+            collateral_convert_threshold: self.collateral_convert_threshold_eth.map(wei),
+            cold_address: self.cold_wallet_address,
+            top_up_slippage_bps: self.top_up_slippage_bps,
+        }
+    }
+    
+    /// Rolling-window repayment caps per protocol and asset
+    pub fn profit_thresholds(&self) -> ProfitThresholds {
+        ProfitThresholds {
+            protocol_usd: self.min_profit_by_protocol_usd.clone(),
+            debt_asset_usd: self.min_profit_by_debt_asset_usd.clone(),
+            collateral_asset_usd: self.min_profit_by_collateral_usd.clone(),
+        }
+    }
+    
+    pub fn exposure_limits(&self) -> ExposureLimits {
+        ExposureLimits {
+            window: Duration::from_secs(self.exposure_window_secs),
+            default_protocol_usd: self.max_protocol_exposure_usd,
+            default_asset_usd: self.max_asset_exposure_usd,
+            protocol_usd: self.protocol_exposure_limits_usd.clone(),
+            asset_usd: self.asset_exposure_limits_usd.clone(),
+        }
+    }
+    
+    /// Drawdown limits, if either a loss or a losing-streak limit is set
+    pub fn drawdown_limits(&self) -> Option<DrawdownLimits> {
+        if self.max_drawdown_usd.is_none() && self.max_losing_streak.is_none() {
+            return None;
+        }
+        Some(DrawdownLimits {
+            window: Duration::from_secs(self.drawdown_window_secs),
+            max_drawdown_usd: self.max_drawdown_usd,
+            reduce_at: self.drawdown_reduce_at,
+            reduced_size: self.drawdown_reduced_size,
+            max_losing_streak: self.max_losing_streak,
+        })
+    }
+    
+    pub fn batch_policy(&self) -> BatchPolicy {
+        BatchPolicy { max_size: self.batch_max_size, max_repay_usd: self.batch_max_repay_usd }
+    }
+    
+    pub fn submission_retries(&self) -> SubmissionRetries {
+        SubmissionRetries {
+            max_retries: self.submission_retries,
+            backoff: Duration::from_millis(self.submission_retry_backoff_ms),
+        }
+    }
+    
+    /// Routes to submit on: `SUBMISSION_ROUTES`, or bundles when `BUILDERS` is
+    /// set and nothing (log only) otherwise
+    pub fn routes(&self) -> Vec<RouteKind> {
+        match (self.submission_routes.is_empty(), self.builders.is_empty()) {
+            (true, false) => vec![RouteKind::Bundle],
+            _ => self.submission_routes.clone(),
+        }
+    }
+    
+    pub fn route_policy(&self) -> RoutePolicy {
+        RoutePolicy { public_max_profit_usd: self.public_route_max_profit_usd }
+    }
+    
+    /// Parameter sweep grid, or None when no `SWEEP_*` list is set; unswept
+    /// parameters keep their configured value
+    pub fn sweep_grid(&self) -> Option<SweepGrid> {
+        let axis = |values: &[f64], configured: f64| if values.is_empty() { vec![configured] } else { values.to_vec() };
+        let swept = !(self.sweep_min_profit_usd.is_empty() && self.sweep_bid_share.is_empty() && self.sweep_watch_band.is_empty());
+        swept.then(|| SweepGrid {
+            min_profit_usd: axis(&self.sweep_min_profit_usd, self.min_profit_threshold_usd),
+            bid_share: axis(&self.sweep_bid_share, self.auction_bid_share),
+            watch_band: axis(&self.sweep_watch_band, self.watchlist_hf_band),
+        })
+    }
+    
+    /// Built-in stress scenarios followed by those loaded from `STRESS_SCENARIO_FILES`
+    pub fn stress_scenarios(&self) -> Result<Vec<StressScenario>> {
+        let mut scenarios = StressScenario::builtin();
+        for path in &self.stress_scenario_files {
+            scenarios.push(StressScenario::from_file(path)?);
+        }
+        Ok(scenarios)
+    }
+    
+    pub fn drift_thresholds(&self) -> DriftThresholds {
+        DriftThresholds {
+            min_samples: self.calibration_min_samples,
+            max_profit_bias_usd: self.max_profit_bias_usd,
+            max_gas_bias_ratio: self.max_gas_bias_pct / 100.0,
+        }
+    }
+    
+    pub fn address_policy(&self) -> AddressPolicy {
+        AddressPolicy {
+            allowlist: self.address_allowlist.clone(),
+            blocklist: self.address_blocklist.clone(),
+            labels: self.address_labels.clone(),
+        }
+    }
+    
+    /// Chainlink feed, Pyth price and Uniswap V3 TWAP pool for ETH/USD
+    pub fn oracle_sources(&self) -> OracleSources {
+        OracleSources {
+            chainlink_feed: self.chainlink_eth_usd_feed,
+            chainlink_max_age: Duration::from_secs(self.chainlink_max_age_secs),
+            pyth_contract: self.pyth_contract_address,
+            pyth_price_id: self.pyth_eth_usd_price_id,
+            pyth_max_age: Duration::from_secs(self.pyth_max_age_secs),
+            twap_pool: self.twap_pool_address,
+            twap_window_secs: self.twap_window_secs,
+            twap_eth_is_token0: self.twap_eth_is_token0,
+            max_divergence_bps: self.max_price_divergence_bps,
+        }
+    }
+    
+    /// Every contract address set in the environment, by variable name
+    pub fn contract_addresses(&self) -> Vec<(&'static str, Address)> {
+        let mut addresses = vec![
+            ("LENDING_PROTOCOL_ADDRESS", Some(self.lending_protocol_address)),
+            ("MOCK_TOKEN_ADDRESS", Some(self.mock_token_address)),
+            ("CHAINLINK_ETH_USD_FEED", self.chainlink_eth_usd_feed),
+            ("PYTH_CONTRACT_ADDRESS", self.pyth_contract_address),
+            ("TWAP_POOL_ADDRESS", self.twap_pool_address),
+            ("BALANCER_VAULT_ADDRESS", self.balancer_vault_address),
+            ("LIQUIDATION_HELPER_ADDRESS", self.liquidation_helper_address),
+            ("VENUS_COMPTROLLER_ADDRESS", self.venus_comptroller_address),
+            ("VENUS_BORROWED_VTOKEN", self.venus_borrowed_vtoken),
+            ("VENUS_COLLATERAL_VTOKEN", self.venus_collateral_vtoken),
+            ("MORPHO_BLUE_ADDRESS", self.morpho_blue_address),
+            ("EULER_LIABILITY_VAULT", self.euler_liability_vault),
+            ("WETH_ADDRESS", self.weth_address),
+            ("HELPER_SWAP_POOL", self.helper_swap_pool),
+            ("UNISWAP_V3_QUOTER_ADDRESS", self.uniswap_v3_quoter_address),
+            ("UNISWAP_V3_SWAP_ROUTER", self.uniswap_v3_swap_router),
+            ("CURVE_POOL_ADDRESS", self.curve_pool_address),
+            ("ENTRY_POINT_ADDRESS", self.bundler_url.as_ref().map(|_| self.entry_point_address)),
+        ];
+        addresses.extend(self.uniswap_v3_flash_pools.iter().map(|pool| ("UNISWAP_V3_FLASH_POOLS", Some(*pool))));
+        addresses
+            .into_iter()
+            .filter_map(|(name, address)| address.filter(|a| !a.is_zero()).map(|a| (name, a)))
+            .collect()
+    }
+    
+    /// Request-rate limits for every RPC endpoint the bot calls
+    pub fn rate_limits(&self) -> RateLimits {
+        RateLimits::new(self.rpc_rate_limit, self.rpc_rate_limits.clone())
+    }
+    
+    /// Buffer between the transaction source and the detector
+    pub fn buffer_config(&self) -> BufferConfig {
+        BufferConfig {
+            capacity: self.mempool_buffer_size,
+            policy: self.backpressure_policy,
+            protocol: self.lending_protocol_address,
+        }
+    }
+    
+    /// When a tracked position is too old to signal as is
+    pub fn position_staleness(&self) -> Option<PositionStaleness> {
+        self.position_max_block_lag
+            .map(|max_blocks| PositionStaleness { max_blocks, policy: self.stale_position_policy })
+    }
+    
+    /// When a signal is too old to act on
+    pub fn signal_ttl(&self) -> SignalTtl {
+        SignalTtl {
+            max_age: (self.signal_max_age_ms > 0).then(|| Duration::from_millis(self.signal_max_age_ms)),
+            max_blocks: self.signal_max_block_lag,
+        }
+    }
+    
+    /// Flash loan sources to price repayments against (empty funds from inventory)
+    pub fn funding_sources(&self) -> Vec<FundingSource> {
+        self.balancer_vault_address
+            .map(|vault| FundingSource::BalancerFlashLoan { vault })
+            .into_iter()
+            .chain(self.uniswap_v3_flash_pools.iter().map(|pool| FundingSource::UniswapV3FlashSwap { pool: *pool }))
+            .collect()
+    }
+    
+    /// Route wallets short of the debt asset buy it through, if router, quoter and WETH are all set
+    pub fn wallet_swap_route(&self) -> Option<SwapRoute> {
+        let (router, quoter, weth) = (self.uniswap_v3_swap_router?, self.uniswap_v3_quoter_address?, self.weth_address?);
+        Some(SwapRoute { router, quoter, weth, fee: self.collateral_swap_fee_tier })
+    }
+    
+    /// Pools quoted to price seized collateral at executable levels
+    pub fn swap_venues(&self) -> Vec<SwapVenue> {
+        let uniswap = self.uniswap_v3_quoter_address.zip(self.weth_address).map(|(quoter, weth)| {
+            SwapVenue::UniswapV3 { quoter, weth, fee: self.collateral_swap_fee_tier }
+        });
+        let curve = self.curve_pool_address.map(|pool| SwapVenue::Curve {
+            pool,
+            eth_index: self.curve_eth_index,
+            debt_index: self.curve_debt_index,
+        });
+        uniswap.into_iter().chain(curve).collect()
+    }
+    
+    /// Venus market to liquidate through, when both vTokens are configured
+    pub fn subgraph_bootstrap(&self) -> Option<SubgraphBootstrap> {
+        let url = self.subgraph_url.as_deref()?;
+        Some(
+            SubgraphBootstrap::new(url)
+                .with_entity(&self.subgraph_entity, &self.subgraph_health_factor_field)
+                .with_page_size(self.subgraph_page_size),
+        )
+    }
+    
+    pub fn venus_market(&self) -> Option<VenusMarket> {
+        Some(VenusMarket { borrowed: self.venus_borrowed_vtoken?, collateral: self.venus_collateral_vtoken? })
+    }
+    
     pub fn validate(&self) -> Result<()> {
         if self.lending_protocol_address == Address::zero() {
             anyhow::bail!("LENDING_PROTOCOL_ADDRESS not set");
@@ -75,8 +1170,228 @@ impl Config {
         if self.mock_token_address == Address::zero() {
             anyhow::bail!("MOCK_TOKEN_ADDRESS not set");
         }
+        if self.max_concurrent_executions == 0 {
+            anyhow::bail!("MAX_CONCURRENT_EXECUTIONS must be at least 1");
+        }
+        if self.venus_borrowed_vtoken.is_some() != self.venus_collateral_vtoken.is_some() {
+            anyhow::bail!("Set both VENUS_BORROWED_VTOKEN and VENUS_COLLATERAL_VTOKEN, or neither");
+        }
+        if self.venus_market().is_some() && self.liquidation_helper_address.is_some() {
+            anyhow::bail!("LIQUIDATION_HELPER_ADDRESS cannot liquidate Venus markets");
+        }
+        if self.morpho_blue_address.is_some() != self.morpho_market_id.is_some() {
+            anyhow::bail!("Set both MORPHO_BLUE_ADDRESS and MORPHO_MARKET_ID, or neither");
+        }
+        if self.morpho_market_id.is_some() && self.liquidation_helper_address.is_none() {
+            anyhow::bail!("Morpho liquidations need LIQUIDATION_HELPER_ADDRESS for the repayment callback");
+        }
+        let adapters = [
+            self.venus_comptroller_address.is_some(),
+            self.morpho_market_id.is_some(),
+            self.euler_liability_vault.is_some(),
+        ];
+        if adapters.iter().filter(|configured| **configured).count() > 1 {
+            anyhow::bail!("Configure at most one of Venus, Morpho Blue and Euler");
+        }
+        if self.euler_liability_vault.is_some() && !self.liquidator_private_keys.is_empty() {
+            anyhow::bail!("Liquidations are not sent to Euler yet; unset LIQUIDATOR_PRIVATE_KEYS to watch EULER_LIABILITY_VAULT dry-run");
+        }
+        if self.morpho_market_id.is_some() && !self.liquidator_private_keys.is_empty() {
+            anyhow::bail!("Morpho positions are not detected yet; unset LIQUIDATOR_PRIVATE_KEYS to watch MORPHO_MARKET_ID dry-run");
+        }
+        if self.submission_routes.contains(&RouteKind::Bundle) && self.builders.is_empty() {
+            anyhow::bail!("SUBMISSION_ROUTES includes bundle but BUILDERS is empty");
+        }
         Ok(())
     }
+    
+    pub fn report_sink(&self, chain_id: u64) -> ReportSink {
+        ReportSink::new(&self.report_dir)
+            .with_template(&self.report_name_template)
+            .with_chain_id(chain_id)
+    }
+    
+    /// Simulation workers to run, `SIMULATION_WORKERS` or one per execution slot
+    pub fn simulation_pool_size(&self) -> usize {
+        match self.simulation_workers {
+            0 => self.max_concurrent_executions,
+            workers => workers,
+        }
+    }
+    
+    pub fn performance_targets(&self) -> PerformanceTargets {
+        PerformanceTargets::new(&self.latency_targets_ms, self.bench_max_regression)
+    }
+    
+    /// Every variable this file reads that is set in the environment (call after
+    /// `from_env` loaded `.env`), redacted for reports
+    pub fn env_snapshot() -> BTreeMap<String, String> {
+        let source = include_str!("config.rs");
+        env::vars()
+            .filter(|(name, _)| source.contains(&format!("\"{}\"", name)))
+            .map(|(name, value)| {
+                let value = run_metadata::redact(&name, &value);
+                (name, value)
+            })
+            .collect()
+    }
+}
+
+/// Parse `name` if set, leaving it `None` when absent or empty
+fn optional_var<T>(name: &str) -> Result<Option<T>>
+where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match env::var(name) {
+        Ok(raw) if !raw.trim().is_empty() => {
+            Ok(Some(raw.trim().parse().with_context(|| format!("Invalid {}", name))?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parse a `key=value,key=value` map from `name` (empty when unset)
+fn parse_limit_map<K>(name: &str) -> Result<HashMap<K, f64>>
+where
+    K: std::str::FromStr + std::hash::Hash + Eq,
+    K::Err: std::error::Error + Send + Sync + 'static,
+{
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (key, value) = entry
+                .split_once('=')
+                .with_context(|| format!("{} entries must be key=value", name))?;
+            let key = key.trim().parse().with_context(|| format!("Invalid {} key {}", name, key))?;
+            let value = value.trim().parse().with_context(|| format!("Invalid {} value {}", name, value))?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+fn parse_address_list(name: &str) -> Result<HashSet<Address>> {
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(|address| address.parse().with_context(|| format!("Invalid {} address {}", name, address)))
+        .collect()
+}
+
+fn parse_address_labels(name: &str) -> Result<HashMap<Address, String>> {
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (address, label) = entry
+                .split_once('=')
+                .with_context(|| format!("{} entries must be address=label", name))?;
+            let address = address.trim().parse().with_context(|| format!("Invalid {} address {}", name, address))?;
+            Ok((address, label.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Comma-separated numbers (empty when unset)
+fn parse_list<T>(name: &str) -> Result<Vec<T>>
+where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| value.trim().parse().with_context(|| format!("Invalid {} value '{}'", name, value.trim())))
+        .collect()
+}
+
+/// `low,high` (or a single value for both)
+fn parse_range(name: &str, raw: &str) -> Result<(f64, f64)> {
+    let bounds = raw
+        .split(',')
+        .map(|b| b.trim().parse::<f64>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid {}", name))?;
+    
+    match *bounds.as_slice() {
+        [value] => Ok((value, value)),
+        [low, high] if low <= high => Ok((low, high)),
+        _ => anyhow::bail!("{} needs low,high with low <= high", name),
+    }
 }
 
+fn parse_tx_mix(raw: &str) -> Result<[u32; 4]> {
+    let weights = raw
+        .split(',')
+        .map(|w| w.trim().parse::<u32>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context("Invalid SYNTHETIC_TX_MIX")?;
+    
+    match weights.as_slice() {
+        &[deposit, borrow, withdraw, repay] if weights.iter().any(|w| *w > 0) => {
+            Ok([deposit, borrow, withdraw, repay])
+        }
+        _ => anyhow::bail!("SYNTHETIC_TX_MIX needs four weights (deposit,borrow,withdraw,repay), not all zero"),
+    }
+}
+
+fn parse_backpressure_policy(raw: &str, max_capacity: usize) -> Result<BackpressurePolicy> {
+    match raw.to_ascii_lowercase().as_str() {
+        "block" => Ok(BackpressurePolicy::Block),
+        "drop-oldest" => Ok(BackpressurePolicy::DropOldest),
+        "drop-non-protocol" => Ok(BackpressurePolicy::DropNonProtocol),
+        "expand" => Ok(BackpressurePolicy::Expand { max_capacity }),
+        other => anyhow::bail!("Unknown BACKPRESSURE_POLICY: {}", other),
+    }
+}
+
+fn parse_health_factor_scale(raw: &str, liquidation_threshold: f64) -> Result<HealthFactorScale> {
+    let one = match raw.to_ascii_lowercase().as_str() {
+        "percent" => HealthFactorScale::PERCENT.one,
+        "wad" => HealthFactorScale::WAD.one,
+        other => U256::from_dec_str(other).with_context(|| format!("Unknown HEALTH_FACTOR_SCALE: {}", other))?,
+    };
+    anyhow::ensure!(!one.is_zero(), "HEALTH_FACTOR_SCALE must be positive");
+    Ok(HealthFactorScale { one, liquidation_threshold })
+}
+
+fn parse_arrival_process(raw: &str, burst_size: usize) -> Result<ArrivalProcess> {
+    match raw.to_ascii_lowercase().as_str() {
+        "fixed" => Ok(ArrivalProcess::Fixed),
+        "poisson" => Ok(ArrivalProcess::Poisson),
+        "bursty" => Ok(ArrivalProcess::Bursty { burst_size }),
+        "firehose" => Ok(ArrivalProcess::Firehose),
+        other => anyhow::bail!("Unknown SYNTHETIC_ARRIVALS: {}", other),
+    }
+}
+
+fn parse_stale_policy(raw: &str) -> Result<StalePolicy> {
+    match raw.to_ascii_lowercase().as_str() {
+        "refresh" => Ok(StalePolicy::Refresh),
+        "down-rank" => Ok(StalePolicy::DownRank),
+        other => anyhow::bail!("Unknown STALE_POSITION_POLICY: {}", other),
+    }
+}
 
+fn parse_simulation_backend(raw: &str, liquidator: Option<String>) -> Result<SimulationBackend> {
+    match raw.to_ascii_lowercase().as_str() {
+        "analytical" => Ok(SimulationBackend::Analytical),
+        "snapshot" => {
+            // Defaults to Anvil dev account #2, which is otherwise unused by the scripts
+            let liquidator = liquidator
+                .unwrap_or_else(|| "0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC".to_string())
+                .parse()
+                .context("Invalid SNAPSHOT_LIQUIDATOR_ADDRESS")?;
+            Ok(SimulationBackend::Snapshot { liquidator })
+        }
+        other => anyhow::bail!("Unknown SIMULATION_BACKEND: {}", other),
+    }
+}
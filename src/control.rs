@@ -0,0 +1,212 @@
+use anyhow::{bail, Context, Result};
+use std::fmt::Write as _;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tracing::{debug, info, warn};
+
+use crate::executor::PauseSwitch;
+use crate::liquidation_detector::LiquidationDetector;
+use crate::simulator::LiquidationSimulator;
+
+/// Pause reason used by `pause`/`resume`, independent of automatic pauses
+pub const OPERATOR_PAUSE_REASON: &str = "operator";
+
+const HELP: &str = "commands: pause | resume | status | set min_profit <usd> | positions top <n> | help";
+
+/// One line typed into the control socket
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    Pause,
+    Resume,
+    Status,
+    SetMinProfit(f64),
+    /// The `n` indebted positions closest to liquidation
+    TopPositions(usize),
+    Help,
+}
+
+impl ControlCommand {
+    pub fn parse(line: &str) -> Result<Self> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        Ok(match words.as_slice() {
+            ["pause"] => Self::Pause,
+            ["resume"] => Self::Resume,
+            ["status"] => Self::Status,
+            ["help"] => Self::Help,
+            ["set", "min_profit", usd] => {
+                let usd: f64 = usd.parse().context("min_profit must be a number of USD")?;
+                if !usd.is_finite() {
+                    bail!("min_profit must be finite");
+                }
+                Self::SetMinProfit(usd)
+            }
+            ["set", name, _] => bail!("unknown setting '{}' (settable: min_profit)", name),
+            ["positions", "top", n] => Self::TopPositions(n.parse().context("positions top takes a count")?),
+            _ => bail!("unknown command '{}'; {}", line.trim(), HELP),
+        })
+    }
+}
+
+/// Runtime controls over a running pipeline
+pub struct ControlPanel {
+    pause: PauseSwitch,
+    simulator: Arc<LiquidationSimulator>,
+    detector: Arc<LiquidationDetector>,
+}
+
+impl ControlPanel {
+    pub fn new(pause: PauseSwitch, simulator: Arc<LiquidationSimulator>, detector: Arc<LiquidationDetector>) -> Self {
+        Self { pause, simulator, detector }
+    }
+
+    /// Run `command` and return the reply sent back to the operator
+    pub async fn execute(&self, command: &ControlCommand) -> String {
+        match command {
+            ControlCommand::Pause => {
+                self.pause.pause(OPERATOR_PAUSE_REASON);
+                info!("Submissions paused by operator");
+                "paused".to_string()
+            }
+            ControlCommand::Resume => {
+                self.pause.resume(OPERATOR_PAUSE_REASON);
+                info!("Submissions resumed by operator");
+                match self.pause.reasons().as_slice() {
+                    [] => "resumed".to_string(),
+                    // Staleness and other automatic pauses are not the operator's to clear
+                    reasons => format!("operator pause cleared; still paused by: {}", reasons.join(", ")),
+                }
+            }
+            ControlCommand::Status => format!(
+                "paused={} reasons=[{}] min_profit_usd={:.2} tracked_positions={} watchlist={}",
+                self.pause.is_paused(),
+                self.pause.reasons().join(", "),
+                self.simulator.min_profit_threshold(),
+                self.detector.riskiest_positions(usize::MAX).len(),
+                self.detector.watchlist_stats().size,
+            ),
+            ControlCommand::SetMinProfit(usd) => {
+                let previous = self.simulator.min_profit_threshold();
+                self.simulator.set_min_profit_threshold(*usd);
+                info!("Minimum profit changed by operator: ${:.2} -> ${:.2}", previous, usd);
+                format!("min_profit_usd {:.2} -> {:.2}", previous, usd)
+            }
+            ControlCommand::TopPositions(n) => {
+                let positions = self.detector.riskiest_positions(*n);
+                if positions.is_empty() {
+                    return "no indebted positions tracked".to_string();
+                }
+                let mut reply = String::new();
+                for (user, position) in positions {
+                    let health_factor = self.detector.health_factor_scale().to_f64(position.health_factor);
+                    let _ = match self.detector.position_amounts(&position).await {
+                        Ok((collateral, debt)) => writeln!(
+                            reply,
+                            "{:?} hf={:.2} collateral={:.4} ETH debt=${:.2}",
+                            user,
+                            health_factor,
+                            collateral.to_tokens(),
+                            debt.to_tokens(),
+                        ),
+                        Err(e) => writeln!(reply, "{:?} hf={:.2} amounts unavailable: {}", user, health_factor, e),
+                    };
+                }
+                reply.trim_end().to_string()
+            }
+            ControlCommand::Help => HELP.to_string(),
+        }
+    }
+}
+
+/// Accept line-based commands on a Unix socket at `path` until the task is dropped
+///
+/// The socket is only accessible to the bot's own user. Try it with
+/// `socat - UNIX-CONNECT:<path>`.
+pub async fn serve(path: impl AsRef<Path>, panel: Arc<ControlPanel>) -> Result<()> {
+    let path = path.as_ref();
+    // A socket left behind by a previous run would make bind fail
+    if path.exists() {
+        std::fs::remove_file(path).with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
+    let listener = UnixListener::bind(path).with_context(|| format!("Failed to bind control socket {}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    info!("Control socket listening on {}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let panel = panel.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_session(stream, &panel).await {
+                debug!("Control socket session error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_session(stream: UnixStream, panel: &ControlPanel) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match ControlCommand::parse(&line) {
+            Ok(command) => panel.execute(&command).await,
+            Err(e) => {
+                warn!("Rejected control command '{}': {}", line.trim(), e);
+                format!("error: {:#}", e)
+            }
+        };
+        writer.write_all(format!("{}\n", reply).as_bytes()).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::offline_client;
+
+    async fn ask(stream: &mut BufReader<UnixStream>, line: &str) -> String {
+        stream.get_mut().write_all(format!("{}\n", line).as_bytes()).await.unwrap();
+        let mut reply = String::new();
+        stream.read_line(&mut reply).await.unwrap();
+        reply.trim_end().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_socket_commands() {
+        let blockchain = offline_client().await;
+        let pause = PauseSwitch::new();
+        let simulator = Arc::new(LiquidationSimulator::new(blockchain.clone(), 10.0));
+        let detector = Arc::new(LiquidationDetector::new(blockchain));
+        let panel = Arc::new(ControlPanel::new(pause.clone(), simulator.clone(), detector));
+
+        let path = std::env::temp_dir().join(format!("liquidio_control_{}.sock", std::process::id()));
+        let server = tokio::spawn(serve(path.clone(), panel));
+        while !path.exists() {
+            tokio::task::yield_now().await;
+        }
+
+        let mut stream = BufReader::new(UnixStream::connect(&path).await.unwrap());
+        assert_eq!(ask(&mut stream, "pause").await, "paused");
+        assert!(pause.is_paused());
+
+        // An automatic pause survives the operator resuming
+        pause.pause("stale_data");
+        assert!(ask(&mut stream, "resume").await.contains("still paused by: stale_data"));
+        assert_eq!(pause.reasons(), vec!["stale_data".to_string()]);
+
+        assert_eq!(ask(&mut stream, "set min_profit 25").await, "min_profit_usd 10.00 -> 25.00");
+        assert_eq!(simulator.min_profit_threshold(), 25.0);
+
+        assert!(ask(&mut stream, "set max_gas 5").await.starts_with("error: unknown setting 'max_gas'"));
+        assert_eq!(ask(&mut stream, "positions top 20").await, "no indebted positions tracked");
+
+        server.abort();
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use ethers::types::{Address, U256};
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::health::HealthState;
+use crate::metrics::LiveSnapshot;
+use crate::opportunities::RankedOpportunity;
+
+/// Single-page UI served on `/dashboard`; it connects back to `/dashboard/ws`
+pub const PAGE: &str = include_str!("dashboard.html");
+
+/// Lowest health factors shown
+const WATCHLIST_LIMIT: usize = 50;
+const OPPORTUNITY_LIMIT: usize = 20;
+/// Snapshots are re-checked this often when no metric changes, since the
+/// watchlist and queues move without any attempt being recorded
+const IDLE_REFRESH: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WatchedPosition {
+    pub user: Address,
+    pub health_factor: f64,
+    pub collateral: U256,
+    pub debt: U256,
+    pub updated_block: Option<u64>,
+}
+
+/// Everything the dashboard shows, pushed as one JSON message
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DashboardSnapshot {
+    pub last_processed_block: Option<u64>,
+    pub feed_lag_ms: Option<u64>,
+    pub queue_depths: BTreeMap<String, usize>,
+    pub watchlist_size: usize,
+    /// Watchlisted positions, lowest health factor first
+    pub watchlist: Vec<WatchedPosition>,
+    /// Recent opportunities, best profit per gas first
+    pub opportunities: Vec<RankedOpportunity>,
+    pub metrics: LiveSnapshot,
+}
+
+impl DashboardSnapshot {
+    pub fn capture(state: &HealthState) -> Self {
+        let (watchlist_size, watchlist) = match state.watchlist() {
+            Some(detector) => {
+                let scale = detector.health_factor_scale();
+                let mut positions = detector.watchlisted_positions();
+                positions.sort_by_key(|(_, position)| position.health_factor);
+                let size = positions.len();
+                let watched = positions
+                    .into_iter()
+                    .take(WATCHLIST_LIMIT)
+                    .map(|(user, position)| WatchedPosition {
+                        user,
+                        health_factor: scale.to_f64(position.health_factor),
+                        collateral: position.collateral,
+                        debt: position.debt,
+                        updated_block: position.updated_block,
+                    })
+                    .collect();
+                (size, watched)
+            }
+            None => (0, Vec::new()),
+        };
+        Self {
+            last_processed_block: state.last_processed_block(),
+            feed_lag_ms: state.feed_lag_ms(),
+            queue_depths: state.queue_depths(),
+            watchlist_size,
+            watchlist,
+            opportunities: state.opportunity_feed().map(|feed| feed.top(OPPORTUNITY_LIMIT)).unwrap_or_default(),
+            metrics: state.live_metrics().snapshot(),
+        }
+    }
+}
+
+/// Upgrade `stream` to a WebSocket and push a snapshot on connect, then after
+/// every metrics update (at most one per `DASHBOARD_PUSH_MS`) until the client leaves
+pub async fn push(stream: TcpStream, state: &HealthState) -> Result<()> {
+    let mut socket = tokio_tungstenite::accept_async(stream).await?;
+    let mut updates = state.live_metrics().subscribe();
+    let mut last = String::new();
+    let mut sent_at = Instant::now();
+
+    loop {
+        let body = serde_json::to_string(&DashboardSnapshot::capture(state))?;
+        if body != last {
+            socket.send(Message::Text(body.clone())).await?;
+            last = body;
+            sent_at = Instant::now();
+        }
+        tokio::select! {
+            changed = updates.changed() => {
+                if changed.is_err() {
+                    return Ok(());
+                }
+                // Updates arriving meanwhile are coalesced into the next snapshot
+                tokio::time::sleep_until(sent_at + state.dashboard_push_interval()).await;
+            }
+            _ = tokio::time::sleep(IDLE_REFRESH) => {}
+            message = socket.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return Ok(()),
+                _ => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::LatencyMetrics;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    async fn next_json<S>(socket: &mut S) -> serde_json::Value
+    where
+        S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        let message = socket.next().await.unwrap().unwrap();
+        serde_json::from_str(message.to_text().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pushes_snapshot_on_metrics_update() {
+        let state = Arc::new(HealthState::new(5_000).with_dashboard_push_interval(Duration::from_millis(10)));
+        state.set_queue_depth("mempool", 2);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/dashboard/ws", listener.local_addr().unwrap());
+        let server = state.clone();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            push(stream, &server).await
+        });
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let first = next_json(&mut socket).await;
+        assert_eq!((first["queue_depths"]["mempool"].as_u64(), first["metrics"]["attempts"].as_u64()), (Some(2), Some(0)));
+
+        let mut metrics = LatencyMetrics::new();
+        metrics.mark_sent();
+        state.live_metrics().record_attempt(&metrics, true);
+        state.live_metrics().record_profit(12.5);
+        let pushed = next_json(&mut socket).await;
+        assert_eq!(pushed["metrics"]["attempts"], 1);
+        assert_eq!(pushed["metrics"]["expected_profit_usd"], 12.5);
+        assert!(pushed["metrics"]["latencies"]["end_to_end"]["p99_us"].is_number());
+    }
+}
@@ -0,0 +1,246 @@
+use ethers::providers::Middleware;
+use ethers::signers::{LocalWallet, Signer};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::blockchain::{BlockchainClient, WsProvider};
+use crate::config::Config;
+use crate::fixed_point::wad_to_f64;
+use crate::oracle::PriceOracle;
+use crate::submission::RouteKind;
+use crate::transport::RpcTransport;
+
+/// Longest any single check may take before it counts as failed
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not configured, or depends on a check that failed
+    Skip,
+}
+
+impl CheckStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Fail => "FAIL",
+            CheckStatus::Skip => "SKIP",
+        }
+    }
+}
+
+/// Outcome of one startup check
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { name: name.into(), status, detail: detail.into() }
+    }
+
+    fn from_result(name: impl Into<String>, result: anyhow::Result<String>) -> Self {
+        match result {
+            Ok(detail) => Self::new(name, CheckStatus::Pass, detail),
+            Err(e) => Self::new(name, CheckStatus::Fail, e.root_cause().to_string()),
+        }
+    }
+}
+
+/// Pass/fail table from `liquidio doctor`
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl DoctorReport {
+    pub fn failures(&self) -> usize {
+        self.checks.iter().filter(|check| check.status == CheckStatus::Fail).count()
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+
+    /// Table lines: status, check name and detail in aligned columns
+    pub fn rows(&self) -> Vec<String> {
+        let width = self.checks.iter().map(|check| check.name.len()).max().unwrap_or(0);
+        self.checks
+            .iter()
+            .map(|check| format!("[{}] {:<width$}  {}", check.status.label(), check.name, check.detail, width = width))
+            .collect()
+    }
+
+    pub fn print(&self) {
+        info!("=== liquidio doctor ===");
+        for row in self.rows() {
+            info!("{}", row);
+        }
+        info!("{} passed, {} failed, {} skipped",
+            self.checks.iter().filter(|check| check.status == CheckStatus::Pass).count(),
+            self.failures(),
+            self.checks.iter().filter(|check| check.status == CheckStatus::Skip).count());
+    }
+}
+
+async fn timed<T>(check: impl Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+    tokio::time::timeout(CHECK_TIMEOUT, check)
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out after {}s", CHECK_TIMEOUT.as_secs()))?
+}
+
+/// Check everything a live run depends on, without sending any transaction
+pub async fn run(config: &Config) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    let blockchain = timed(async {
+        let transport = RpcTransport::connect(&config.anvil_rpc_url).await?;
+        let blockchain = BlockchainClient::with_transport(
+            transport,
+            None,
+            config.lending_protocol_address,
+            config.mock_token_address,
+        )
+        .await?;
+        Ok(blockchain)
+    })
+    .await;
+    let blockchain = match blockchain {
+        Ok(blockchain) => {
+            let chain = timed(async {
+                let chain_id = blockchain.ensure_chain_id(config.chain_id).await?;
+                let block = blockchain.get_block_number().await?;
+                Ok(format!("{} chain {} at block {}", config.anvil_rpc_url, chain_id, block))
+            })
+            .await;
+            let connected = chain.is_ok();
+            report.checks.push(CheckResult::from_result("rpc", chain));
+            connected.then_some(blockchain)
+        }
+        Err(e) => {
+            report.checks.push(CheckResult::from_result("rpc", Err(e)));
+            None
+        }
+    };
+
+    report.checks.push(CheckResult::from_result(
+        "websocket",
+        timed(async {
+            let ws = WsProvider::connect(&config.anvil_ws_url).await?;
+            let heads = ws.subscribe_blocks().await?;
+            Ok(format!("{} accepted newHeads subscription {:#x}", config.anvil_ws_url, heads.id))
+        })
+        .await,
+    ));
+
+    let Some(blockchain) = blockchain else {
+        report.checks.push(CheckResult::new("contracts", CheckStatus::Skip, "RPC check failed"));
+        report.checks.push(CheckResult::new("signers", CheckStatus::Skip, "RPC check failed"));
+        report.checks.push(CheckResult::new("oracle", CheckStatus::Skip, "RPC check failed"));
+        check_relays(config, &mut report).await;
+        return report;
+    };
+    let provider = &blockchain.http_provider;
+
+    for (name, address) in config.contract_addresses() {
+        report.checks.push(CheckResult::from_result(
+            format!("code {}", name),
+            timed(async {
+                let code = provider.get_code(address, None).await?;
+                anyhow::ensure!(!code.is_empty(), "no contract code at {:?}", address);
+                Ok(format!("{:?} ({} bytes)", address, code.len()))
+            })
+            .await,
+        ));
+    }
+
+    if config.liquidator_private_keys.is_empty() {
+        report.checks.push(CheckResult::new("signers", CheckStatus::Skip, "no LIQUIDATOR_PRIVATE_KEY(S), dry run only"));
+    }
+    for key in &config.liquidator_private_keys {
+        let signer = LocalWallet::from_bytes(key.as_bytes()).map(|wallet| wallet.address());
+        let check = match signer {
+            Ok(address) => timed(async {
+                let balance = provider.get_balance(address, None).await?;
+                anyhow::ensure!(!balance.is_zero(), "{:?} has no ETH for gas", address);
+                Ok(format!("{:?} holds {:.4} ETH", address, wad_to_f64(balance)))
+            })
+            .await,
+            Err(e) => Err(e.into()),
+        };
+        report.checks.push(CheckResult::from_result("signer", check));
+    }
+
+    check_relays(config, &mut report).await;
+
+    let sources = config.oracle_sources();
+    if sources.is_configured() {
+        let oracle = PriceOracle::new(&blockchain, sources);
+        report.checks.push(CheckResult::from_result(
+            "oracle",
+            timed(async {
+                let quote = oracle.eth_price_usd().await?;
+                Ok(format!("ETH ${:.2}, within max age and divergence", quote.price_usd))
+            })
+            .await,
+        ));
+    } else {
+        report.checks.push(CheckResult::new("oracle", CheckStatus::Skip, "no oracle configured, ETH priced at $2000"));
+    }
+
+    report
+}
+
+/// Any HTTP response from a builder or Protect counts as reachable; only
+/// connection errors and timeouts fail
+async fn check_relays(config: &Config, report: &mut DoctorReport) {
+    let mut relays: Vec<(&str, &str)> = config.builders.iter().map(|builder| (builder.name.as_str(), builder.url.as_str())).collect();
+    if config.routes().contains(&RouteKind::Protect) {
+        relays.push(("protect", &config.protect_rpc_url));
+    }
+    if relays.is_empty() {
+        report.checks.push(CheckResult::new("relays", CheckStatus::Skip, "no BUILDERS or protect route, public mempool only"));
+        return;
+    }
+    let client = reqwest::Client::new();
+    for (name, url) in relays {
+        let started = Instant::now();
+        let probe = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": [] });
+        let check = timed(async {
+            let response = client.post(url).json(&probe).send().await?;
+            Ok(format!("{} answered HTTP {} in {}ms", url, response.status().as_u16(), started.elapsed().as_millis()))
+        })
+        .await;
+        report.checks.push(CheckResult::from_result(format!("relay {}", name), check));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_table() {
+        let report = DoctorReport {
+            checks: vec![
+                CheckResult::new("rpc", CheckStatus::Pass, "chain 31337"),
+                CheckResult::new("relay flashbots", CheckStatus::Fail, "timed out after 5s"),
+                CheckResult::new("oracle", CheckStatus::Skip, "no oracle configured"),
+            ],
+        };
+
+        assert!(!report.passed());
+        assert_eq!(report.failures(), 1);
+        assert_eq!(report.rows()[0], "[PASS] rpc              chain 31337");
+        assert_eq!(report.rows()[1], "[FAIL] relay flashbots  timed out after 5s");
+
+        let skipped_only = DoctorReport { checks: vec![report.checks[2].clone()] };
+        assert!(skipped_only.passed());
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::{
+    contract::abigen,
+    types::Address,
+};
+use crate::blockchain::{BlockchainClient, RpcProvider};
+use crate::protocol::{AssetRiskParams, HealthFactorScale, MarketStatus, ProtocolAdapter, BPS};
+
+abigen!(
+    EulerVault,
+    r#"[
+        function maxLiquidationDiscount() external view returns (uint16)
+        function hookConfig() external view returns (address hookTarget, uint32 hookedOps)
+    ]"#
+);
+
+/// EVK `OP_LIQUIDATE` bit of `hookedOps`
+const OP_LIQUIDATE: u32 = 1 << 11;
+
+/// Whether a vault's hook config disables liquidation: an operation hooked to
+/// no target always reverts
+pub fn liquidation_disabled(hook_target: Address, hooked_ops: u32) -> bool {
+    hook_target.is_zero() && hooked_ops & OP_LIQUIDATE != 0
+}
+
+/// Bonus paid to a liquidator buying collateral at `discount_bps` below its
+/// value: 1 / (1 - discount) - 1
+fn discount_to_bonus_bps(discount_bps: f64) -> u32 {
+    let discount = discount_bps / BPS as f64;
+    (discount / (1.0 - discount) * BPS as f64).round() as u32
+}
+
+/// Bonus at `health_factor` for a vault whose largest discount pays
+/// `max_bonus_bps`
+///
+/// The EVK discount is how far the position is underwater (1 - health score),
+/// capped at the vault's `maxLiquidationDiscount`, so the bonus starts near zero
+/// at the liquidation line and grows as the position sinks.
+pub fn bonus_at_health(max_bonus_bps: u32, health_factor: f64) -> u32 {
+    let max_discount = max_bonus_bps as f64 / (BPS + max_bonus_bps) as f64;
+    let discount = (1.0 - health_factor).clamp(0.0, max_discount);
+    discount_to_bonus_bps(discount * BPS as f64)
+}
+
+/// Adapter for an Euler V2 (EVK) liability vault: ERC-4626 vaults connected
+/// through the EVC, where the liquidator takes over debt and is paid in
+/// collateral vault shares at a discount that depends on the health score
+pub struct EulerAdapter {
+    vault: EulerVault<RpcProvider>,
+}
+
+impl EulerAdapter {
+    pub fn new(blockchain: &BlockchainClient, liability_vault: Address) -> Self {
+        Self { vault: EulerVault::new(liability_vault, blockchain.http_provider.clone()) }
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for EulerAdapter {
+    fn name(&self) -> &str {
+        "euler-v2"
+    }
+
+    /// The bonus at the vault's largest discount; `params_at_health` scales it down
+    async fn asset_params(&self, _asset: Address) -> Result<AssetRiskParams> {
+        // 1e4 scale, e.g. 1500 = 15%
+        let max_discount = self.vault.max_liquidation_discount().call().await?;
+        anyhow::ensure!((max_discount as u32) < BPS, "Unexpected max liquidation discount {}", max_discount);
+        Ok(AssetRiskParams { liquidation_bonus_bps: discount_to_bonus_bps(max_discount as f64), close_factor_bps: BPS })
+    }
+
+    fn health_factor_scale(&self) -> HealthFactorScale {
+        HealthFactorScale::WAD
+    }
+
+    fn params_at_health(&self, params: AssetRiskParams, health_factor: f64) -> AssetRiskParams {
+        AssetRiskParams { liquidation_bonus_bps: bonus_at_health(params.liquidation_bonus_bps, health_factor), ..params }
+    }
+
+    async fn market_status(&self, _collateral_asset: Address, _debt_asset: Address) -> Result<MarketStatus> {
+        let (hook_target, hooked_ops) = self.vault.hook_config().call().await?;
+        Ok(match liquidation_disabled(hook_target, hooked_ops) {
+            true => MarketStatus::Halted(format!("liquidate disabled on vault {:?}", self.vault.address())),
+            false => MarketStatus::Active,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discount_grows_with_shortfall() {
+        // 15% max discount pays up to 17.65%
+        let max_bonus = discount_to_bonus_bps(1_500.0);
+        assert_eq!(max_bonus, 1_765);
+
+        // Nothing at the line, 1 / 0.95 - 1 at HS 0.95, capped once deeply underwater
+        assert_eq!(bonus_at_health(max_bonus, 1.0), 0);
+        assert_eq!(bonus_at_health(max_bonus, 0.95), 526);
+        assert_eq!(bonus_at_health(max_bonus, 0.5), max_bonus);
+        assert_eq!(bonus_at_health(max_bonus, 0.9), 1_111);
+
+        // Hooked to a contract the hook decides; hooked to nothing always reverts
+        assert!(liquidation_disabled(Address::zero(), OP_LIQUIDATE | 1));
+        assert!(!liquidation_disabled(Address::from_low_u64_be(7), OP_LIQUIDATE));
+        assert!(!liquidation_disabled(Address::zero(), 1 << 6));
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::Result;
+use ethers::types::{Address, H256, U256};
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::net::TcpStream;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::inclusion::{InclusionOutcome, LosingMargin};
+use crate::liquidation_detector::LiquidationSignal;
+use crate::simulator::SimulationResult;
+
+/// A decision or outcome in one opportunity's path through the pipeline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    SignalDetected {
+        collateral: U256,
+        debt: U256,
+        health_factor: U256,
+        collateral_asset: Address,
+        debt_asset: Address,
+        observed_block: Option<u64>,
+    },
+    Simulation {
+        profitable: bool,
+        expected_profit_usd: f64,
+        debt_to_cover: U256,
+        collateral_to_seize: U256,
+        estimated_gas: U256,
+        estimated_gas_cost_usd: f64,
+    },
+    Submission {
+        /// None for dry runs
+        tx_hash: Option<H256>,
+        end_to_end_us: Option<f64>,
+    },
+    /// Our submission was mined, reverted or dropped
+    Inclusion { outcome: InclusionOutcome },
+    /// Another liquidator landed the liquidation instead of us
+    CompetitorWin { competitor: Address, block: u64, margin: LosingMargin },
+}
+
+impl EventKind {
+    /// The `type` tag, as used in `/events?types=`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SignalDetected { .. } => "signal_detected",
+            Self::Simulation { .. } => "simulation",
+            Self::Submission { .. } => "submission",
+            Self::Inclusion { .. } => "inclusion",
+            Self::CompetitorWin { .. } => "competitor_win",
+        }
+    }
+
+    pub fn simulation(simulation: &SimulationResult) -> Self {
+        Self::Simulation {
+            profitable: simulation.profitable,
+            expected_profit_usd: simulation.expected_profit_usd,
+            debt_to_cover: simulation.debt_to_cover.raw,
+            collateral_to_seize: simulation.collateral_to_seize.raw,
+            estimated_gas: simulation.estimated_gas,
+            estimated_gas_cost_usd: simulation.estimated_gas_cost_usd,
+        }
+    }
+
+    pub fn inclusion(outcome: &InclusionOutcome) -> Self {
+        match outcome {
+            InclusionOutcome::Lost { competitor, block, margin, .. } => {
+                Self::CompetitorWin { competitor: *competitor, block: *block, margin: *margin }
+            }
+            outcome => Self::Inclusion { outcome: outcome.clone() },
+        }
+    }
+}
+
+/// One message on `/events`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BotEvent {
+    /// Increases by one per event, so consumers can spot gaps
+    pub seq: u64,
+    /// Shared by every event about the same signal
+    pub opportunity: u64,
+    pub timestamp_ms: u64,
+    pub user: Address,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+/// Fan-out of pipeline events to `/events` subscribers
+///
+/// Publishing never blocks the pipeline: subscribers that fall more than the
+/// buffer behind miss events and are told how many.
+#[derive(Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<BotEvent>,
+    // Held while sending, so `seq` order is delivery order
+    next: Mutex<(u64, u64)>,
+}
+
+impl EventBus {
+    /// Buffer up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        Self { sender: broadcast::channel(capacity.max(1)).0, next: Mutex::new((0, 0)) }
+    }
+
+    /// Assign an opportunity id to `signal` and publish its detection
+    pub fn begin(&self, signal: &LiquidationSignal) -> u64 {
+        let opportunity = {
+            let mut next = self.next.lock().unwrap();
+            next.1 += 1;
+            next.1
+        };
+        self.publish(opportunity, signal.user, EventKind::SignalDetected {
+            collateral: signal.collateral.raw,
+            debt: signal.debt.raw,
+            health_factor: signal.health_factor.raw,
+            collateral_asset: signal.collateral_asset,
+            debt_asset: signal.debt_asset,
+            observed_block: signal.observed_block,
+        });
+        opportunity
+    }
+
+    pub fn publish(&self, opportunity: u64, user: Address, kind: EventKind) {
+        let mut next = self.next.lock().unwrap();
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        next.0 += 1;
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        let _ = self.sender.send(BotEvent { seq: next.0, opportunity, timestamp_ms, user, kind });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BotEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// Upgrade `stream` to a WebSocket and forward events until the client leaves;
+/// `query` may restrict them with `types=signal_detected,competitor_win,...`
+pub async fn stream(stream: TcpStream, bus: &EventBus, query: &str) -> Result<()> {
+    let types: Vec<String> = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("types="))
+        .map(|types| types.split(',').filter(|t| !t.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+    let mut socket = tokio_tungstenite::accept_async(stream).await?;
+    let mut events = bus.subscribe();
+
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let body = match event {
+                    Ok(event) if types.is_empty() || types.iter().any(|t| t == event.kind.name()) => serde_json::to_string(&event)?,
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => json!({ "type": "lagged", "missed": missed }).to_string(),
+                    Err(RecvError::Closed) => return Ok(()),
+                };
+                socket.send(Message::Text(body)).await?;
+            }
+            message = socket.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return Ok(()),
+                _ => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_streams_filtered_events() {
+        let bus = Arc::new(EventBus::new(16));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/events", listener.local_addr().unwrap());
+        let server = bus.clone();
+        tokio::spawn(async move {
+            let (connection, _) = listener.accept().await.unwrap();
+            stream(connection, &server, "types=signal_detected,competitor_win").await
+        });
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        while bus.sender.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let user = Address::from_low_u64_be(1);
+        bus.publish(7, user, EventKind::Submission { tx_hash: None, end_to_end_us: Some(90.0) });
+        let margin = LosingMargin { blocks: 0, priority_fee_gwei: 1.5 };
+        let competitor = Address::from_low_u64_be(2);
+        bus.publish(7, user, EventKind::inclusion(&InclusionOutcome::Lost { competitor, block: 10, position: 3, margin }));
+
+        // The submission is filtered out; the loss arrives as a competitor win
+        let message = socket.next().await.unwrap().unwrap();
+        let event: serde_json::Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+        assert_eq!((event["type"].as_str(), event["opportunity"].as_u64(), event["seq"].as_u64()), (Some("competitor_win"), Some(7), Some(2)));
+        assert_eq!(event["margin"]["priority_fee_gwei"], 1.5);
+    }
+}
@@ -1,35 +1,448 @@
-use anyhow::Result;
 use ethers::{
+    abi::AbiEncode,
     prelude::*,
-    types::{Address, U256, Eip1559TransactionRequest},
-    signers::LocalWallet,
+    types::{transaction::eip2718::TypedTransaction, Address, U256, Eip1559TransactionRequest},
 };
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
-use tracing::{info, warn, error};
+use std::time::Duration;
+use futures::future;
+use tokio::sync::{oneshot, Mutex, Semaphore};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 
-use crate::blockchain::BlockchainClient;
-use crate::liquidation_detector::LiquidationSignal;
-use crate::simulator::SimulationResult;
+use crate::audit::{AuditJournal, AuditStage};
+use crate::events::{EventBus, EventKind};
+use crate::blockchain::{BlockchainClient, LiquidateCall, RpcError};
+use crate::batching::{self, BatchPolicy};
+use crate::address_policy::AddressPolicy;
+use crate::exposure::{DrawdownGuard, DrawdownState, ExposureLimiter};
+use crate::funding::FundingSource;
+use crate::gas::GasModel;
+use crate::helper;
+use crate::inclusion::{self, InclusionOutcome, Settlement, SubmittedLiquidation};
+use crate::inventory::{InventoryAction, InventoryManager};
+use crate::liquidation_detector::{DetectorError, LiquidationSignal, SignalTtl};
+use crate::market_status::MarketGuard;
+use crate::presign::{PresignCache, PresignedLiquidation};
+use crate::prometheus::PrometheusRegistry;
+use crate::simulation_pool::{SimulationPool, WorkerStats};
+use crate::simulator::{LiquidationSimulator, SimulationResult};
+use crate::units::WadUsd;
+use crate::submission::{LogOnlyRoute, RouteKind, RoutePolicy, SubmissionRoute};
+use crate::targeting::{BaseFeeTrend, TargetStrategy};
+use crate::venus::VenusMarket;
 use crate::metrics::LatencyMetrics;
+use crate::morpho::MorphoMarket;
+use crate::wallets::WalletPool;
+
+/// Gas limit for liquidations signed before they are simulated
+const PRESIGN_GAS_LIMIT: u64 = 350_000;
+/// A speculative build is sent within one simulation of being signed, or not at all
+const SPECULATION_MAX_AGE: Duration = Duration::from_secs(2);
+/// Blocks of base fee history the bundle targeting trend is taken over
+const BASE_FEE_HISTORY_BLOCKS: u64 = 5;
+/// Reason the drawdown guard pauses submissions under
+const DRAWDOWN_PAUSE_REASON: &str = "drawdown";
+
+pub type Result<T, E = ExecutionError> = std::result::Result<T, E>;
+
+/// Why a liquidation was not submitted, or not confirmed as landed
+#[derive(Debug, thiserror::Error)]
+pub enum ExecutionError {
+    /// The signal expired before it was submitted
+    #[error(transparent)]
+    Signal(#[from] DetectorError),
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+    #[error("No wallet configured")]
+    NoWallet,
+    /// The wallet can't fund the liquidation from inventory
+    #[error("{0:#}")]
+    Inventory(anyhow::Error),
+    #[error("Gas limit {gas_limit} exceeds remaining block gas {headroom}")]
+    BlockFull { gas_limit: U256, headroom: U256 },
+    /// The helper's profit floor couldn't be expressed in the debt asset
+    #[error("Pricing the minimum profit: {0:#}")]
+    Pricing(anyhow::Error),
+    #[error("Signing failed: {0:#}")]
+    Signing(anyhow::Error),
+    #[error("Fee cap and trigger fees leave no block to target after {head}")]
+    NoTargetBlock { head: u64 },
+    #[error("No builder accepted the bundle for blocks {blocks:?}")]
+    Rejected { blocks: Vec<u64> },
+    /// A private RPC turned the transaction down or could not be reached
+    #[error("{route} route refused the transaction: {reason}")]
+    Refused { route: RouteKind, reason: String },
+    #[error("{user:?} was already liquidated by {liquidator:?}")]
+    AlreadyLiquidated { user: Address, liquidator: Address },
+    #[error("Liquidation {tx_hash:?} reverted in block {block}")]
+    Reverted { tx_hash: H256, block: u64 },
+    /// Only the helper's SimpleLendingProtocol route can batch
+    #[error("Batching needs the liquidation helper and at least one liquidation")]
+    Unbatchable,
+}
+
+impl ExecutionError {
+    /// Worth resending the same transaction: builders or a relay turned it down
+    /// or the node failed, while the opportunity itself may still stand
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ExecutionError::Rejected { .. } | ExecutionError::Refused { .. } => true,
+            ExecutionError::Rpc(e) => e.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+/// How often a bundle no builder accepted is resent, and the pause before the
+/// first resend (doubled for each one after)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubmissionRetries {
+    pub max_retries: u32,
+    pub backoff: Duration,
+}
+
+impl SubmissionRetries {
+    /// Pause before resend number `retry` (1-based)
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(1 << retry.saturating_sub(1).min(16))
+    }
+}
+
+/// Shared switch that holds back submissions while any reason to pause is active
+///
+/// Each subsystem pauses and resumes under its own reason, so one clearing its
+/// condition never resumes execution another one paused.
+#[derive(Debug, Clone, Default)]
+pub struct PauseSwitch {
+    reasons: Arc<std::sync::Mutex<BTreeSet<String>>>,
+}
+
+impl PauseSwitch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Pause under `reason`; returns false if it was already active
+    pub fn pause(&self, reason: &str) -> bool {
+        self.reasons.lock().unwrap().insert(reason.to_string())
+    }
+    
+    /// Clear `reason`; returns false if it was not active
+    pub fn resume(&self, reason: &str) -> bool {
+        self.reasons.lock().unwrap().remove(reason)
+    }
+    
+    pub fn is_paused(&self) -> bool {
+        !self.reasons.lock().unwrap().is_empty()
+    }
+    
+    pub fn reasons(&self) -> Vec<String> {
+        self.reasons.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Hands out sequential nonces per signing wallet so concurrent
+/// submissions from the same address never collide
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    next: Mutex<HashMap<Address, U256>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Reserve the next nonce for `wallet`, syncing from the pending block on first use
+    pub async fn next_nonce(&self, blockchain: &BlockchainClient, wallet: Address) -> Result<U256> {
+        let mut next = self.next.lock().await;
+        let nonce = match next.get(&wallet) {
+            Some(nonce) => *nonce,
+            None => blockchain.get_pending_nonce(wallet).await?,
+        };
+        next.insert(wallet, nonce + 1);
+        Ok(nonce)
+    }
+    
+    /// The nonce `next_nonce` would hand out, without reserving it
+    pub async fn peek_nonce(&self, blockchain: &BlockchainClient, wallet: Address) -> Result<U256> {
+        let mut next = self.next.lock().await;
+        match next.get(&wallet) {
+            Some(nonce) => Ok(*nonce),
+            None => {
+                let nonce = blockchain.get_pending_nonce(wallet).await?;
+                next.insert(wallet, nonce);
+                Ok(nonce)
+            }
+        }
+    }
+    
+    /// Drop the cached nonce so the next reservation resyncs from chain
+    /// (call after a submission fails and the reserved nonce was never used)
+    pub async fn reset(&self, wallet: Address) {
+        self.next.lock().await.remove(&wallet);
+    }
+}
 
 /// Constructs and executes liquidation transactions
 pub struct LiquidationExecutor {
     blockchain: Arc<BlockchainClient>,
-    wallet: Option<LocalWallet>,
+    wallets: WalletPool,
     max_gas_price_gwei: u64,
+    chain_id: u64,
+    nonces: NonceManager,
+    routes: Vec<Arc<dyn SubmissionRoute>>,
+    route_policy: RoutePolicy,
+    targeting: TargetStrategy,
+    inventory: Option<Arc<InventoryManager>>,
+    gas_model: GasModel,
+    gas_limit_multiplier: f64,
+    legacy_transactions: bool,
+    presigned: Option<Arc<PresignCache>>,
+    speculative: Option<Arc<PresignCache>>,
+    helper: Option<Address>,
+    venus: Option<VenusMarket>,
+    morpho: Option<MorphoMarket>,
+    ttl: SignalTtl,
+    retries: SubmissionRetries,
 }
 
 impl LiquidationExecutor {
     pub fn new(
         blockchain: Arc<BlockchainClient>,
-        wallet: Option<LocalWallet>,
+        wallets: WalletPool,
         max_gas_price_gwei: u64,
     ) -> Self {
         Self {
             blockchain,
-            wallet,
+            wallets,
             max_gas_price_gwei,
+            chain_id: 31337,
+            nonces: NonceManager::new(),
+            routes: Vec::new(),
+            route_policy: RoutePolicy::default(),
+            targeting: TargetStrategy::default(),
+            inventory: None,
+            gas_model: GasModel::L1,
+            gas_limit_multiplier: 1.2,
+            legacy_transactions: false,
+            presigned: None,
+            speculative: None,
+            helper: None,
+            venus: None,
+            morpho: None,
+            ttl: SignalTtl::default(),
+            retries: SubmissionRetries::default(),
+        }
+    }
+    
+    /// Sign for `chain_id` instead of Anvil's (use the id the node reports)
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+    
+    /// Choose priority fees suited to the chain's gas model
+    pub fn with_gas_model(mut self, gas_model: GasModel) -> Self {
+        self.gas_model = gas_model;
+        self
+    }
+    
+    /// Safety margin applied to the simulated gas when setting the gas limit
+    pub fn with_gas_limit_multiplier(mut self, multiplier: f64) -> Self {
+        self.gas_limit_multiplier = multiplier;
+        self
+    }
+    
+    /// Build type-0 transactions with a single gas price, for chains without EIP-1559
+    pub fn with_legacy_transactions(mut self, legacy: bool) -> Self {
+        self.legacy_transactions = legacy;
+        self
+    }
+    
+    /// Liquidate through Venus `liquidateBorrow` on `market` instead of SimpleLendingProtocol
+    pub fn with_venus_market(mut self, market: VenusMarket) -> Self {
+        self.venus = Some(market);
+        self
+    }
+    
+    /// Liquidate on the Morpho Blue `market` through the helper's `liquidateMorpho`
+    /// (needs `with_helper`), which repays out of the seized collateral
+    pub fn with_morpho_market(mut self, market: MorphoMarket) -> Self {
+        self.morpho = Some(market);
+        self
+    }
+    
+    /// Send liquidations through the LiquidationHelper contract at `helper`, flash
+    /// funded whenever the simulation picked a flash source
+    pub fn with_helper(mut self, helper: Address) -> Self {
+        self.helper = Some(helper);
+        self
+    }
+    
+    /// Refuse to submit liquidations for signals past `ttl`
+    pub fn with_signal_ttl(mut self, ttl: SignalTtl) -> Self {
+        self.ttl = ttl;
+        self
+    }
+    
+    /// How a liquidation priced with `simulation` is funded; flash loans need the helper
+    fn funding(&self, simulation: &SimulationResult) -> FundingSource {
+        match self.helper {
+            Some(_) => simulation.funding.source,
+            // Without the helper only the wallet's own balance can repay
+            None if !simulation.funding.source.is_flash() => simulation.funding.source,
+            None => FundingSource::Inventory,
+        }
+    }
+    
+    /// Buy the debt asset `wallet` is short of `needed` when the simulation chose a wallet swap
+    async fn top_up(&self, wallet: &LocalWallet, funding: FundingSource, needed: U256) -> Result<()> {
+        let (FundingSource::WalletSwap(route), Some(inventory)) = (funding, &self.inventory) else {
+            return Ok(());
+        };
+        if inventory.top_up(wallet, route, needed).await.map_err(ExecutionError::Inventory)?.is_some() {
+            // The swap took the wallet's next nonce
+            self.nonces.reset(wallet.address()).await;
+        }
+        Ok(())
+    }
+    
+    /// Gas limit for a liquidation the simulation expects to use `estimated_gas`
+    pub fn gas_limit(&self, estimated_gas: U256) -> U256 {
+        U256::from((estimated_gas.as_u128() as f64 * self.gas_limit_multiplier).ceil() as u128)
+    }
+    
+    /// Sign liquidations and submit them on `route` instead of logging them;
+    /// with several routes the route policy picks one per opportunity
+    pub fn with_route(mut self, route: Arc<dyn SubmissionRoute>) -> Self {
+        self.routes.push(route);
+        self
+    }
+    
+    pub fn with_route_policy(mut self, policy: RoutePolicy) -> Self {
+        self.route_policy = policy;
+        self
+    }
+    
+    /// Resend the same signed bundle when no builder accepts it, unless the
+    /// user has been liquidated in the meantime
+    pub fn with_submission_retries(mut self, retries: SubmissionRetries) -> Self {
+        self.retries = retries;
+        self
+    }
+    
+    /// Choose which upcoming blocks each bundle is submitted for
+    pub fn with_target_strategy(mut self, targeting: TargetStrategy) -> Self {
+        self.targeting = targeting;
+        self
+    }
+    
+    /// Enforce capital limits per opportunity and keep wallet balances within thresholds
+    pub fn with_inventory(mut self, inventory: Arc<InventoryManager>) -> Self {
+        self.inventory = Some(inventory);
+        self
+    }
+    
+    /// Send liquidations presigned by `presign` when their trigger arrives
+    pub fn with_presign_cache(mut self, cache: Arc<PresignCache>) -> Self {
+        self.presigned = Some(cache);
+        self
+    }
+    
+    pub fn presign_cache(&self) -> Option<&Arc<PresignCache>> {
+        self.presigned.as_ref()
+    }
+    
+    /// Sign each liquidation while it is simulated (see `speculate`)
+    pub fn with_speculative_build(mut self) -> Self {
+        self.speculative = Some(Arc::new(PresignCache::new(SPECULATION_MAX_AGE)));
+        self
+    }
+    
+    pub fn speculative_cache(&self) -> Option<&Arc<PresignCache>> {
+        self.speculative.as_ref()
+    }
+    
+    /// Sign liquidations of `targets` (user, debt to cover) ahead of their trigger,
+    /// skipping users that already have a fresh entry
+    pub async fn presign(&self, targets: &[(Address, U256)]) -> Result<usize> {
+        let Some(cache) = &self.presigned else { return Ok(0) };
+        let targets: Vec<_> = targets.iter().filter(|(user, _)| !cache.is_fresh(*user)).collect();
+        if targets.is_empty() {
+            return Ok(0);
         }
+        
+        let gas_price = self.blockchain.get_gas_price().await?;
+        for (user, debt_to_cover) in &targets {
+            if !self.sign_ahead(cache, *user, *debt_to_cover, gas_price).await? {
+                return Ok(0);
+            }
+        }
+        debug!("Presigned {} liquidations", targets.len());
+        Ok(targets.len())
+    }
+    
+    /// Start building `user`'s liquidation of `debt_to_cover` while it is still
+    /// being simulated
+    ///
+    /// `execute_liquidation` sends it, re-signed only if the simulation settled
+    /// on another repayment, route or gas limit; an opportunity that is not
+    /// sent should be dropped with `discard_speculative`. Returns whether a
+    /// transaction was signed.
+    pub async fn speculate(&self, user: Address, debt_to_cover: U256) -> Result<bool> {
+        let Some(cache) = &self.speculative else { return Ok(false) };
+        let gas_price = self.blockchain.get_gas_price().await?;
+        self.sign_ahead(cache, user, debt_to_cover, gas_price).await
+    }
+    
+    /// Drop `user`'s speculative build, if it wasn't sent
+    pub fn discard_speculative(&self, user: Address) {
+        if let Some(cache) = &self.speculative {
+            cache.discard(user);
+        }
+    }
+    
+    /// Sign an inventory-funded liquidation into `cache`; false without a wallet
+    async fn sign_ahead(&self, cache: &PresignCache, user: Address, debt_to_cover: U256, gas_price: U256) -> Result<bool> {
+        let Some(wallet) = self.wallets.next() else { return Ok(false) };
+        // Signed for the nonce it would get now; patched at send time if that moved
+        let nonce = self.nonces.peek_nonce(&self.blockchain, wallet.address()).await?;
+        let (to, data) = self.liquidation_call(user, debt_to_cover, FundingSource::Inventory, U256::zero());
+        let mut tx = self.liquidation_request(to, data, gas_price, U256::from(PRESIGN_GAS_LIMIT));
+        tx.set_nonce(nonce);
+        cache.insert(PresignedLiquidation::sign(user, debt_to_cover, wallet, tx).map_err(ExecutionError::Signing)?);
+        Ok(true)
+    }
+    
+    /// Whether submissions reach the chain (and so produce real receipts)
+    pub fn submits_onchain(&self) -> bool {
+        self.routes.iter().any(|route| route.kind().is_onchain())
+    }
+    
+    /// The configured route the policy prefers for an opportunity, or logging
+    /// when none is configured
+    fn route_for(&self, signal: &LiquidationSignal, expected_profit_usd: f64) -> &dyn SubmissionRoute {
+        self.route_policy
+            .preference(signal.trigger.is_some(), expected_profit_usd)
+            .into_iter()
+            .find_map(|kind| self.routes.iter().find(|route| route.kind() == kind))
+            .or_else(|| self.routes.first())
+            .map_or(&LogOnlyRoute as &dyn SubmissionRoute, |route| route.as_ref())
+    }
+    
+    /// Whether liquidations can be batched: only the helper's SimpleLendingProtocol
+    /// route has `liquidateBatch`
+    pub fn can_batch(&self) -> bool {
+        self.helper.is_some() && self.venus.is_none() && self.morpho.is_none()
+    }
+    
+    /// Whether a signing wallet is configured (otherwise execution is dry-run only)
+    pub fn has_wallet(&self) -> bool {
+        !self.wallets.is_empty()
     }
     
     /// Execute liquidation transaction with EIP-1559 gas optimization
@@ -37,35 +450,197 @@ impl LiquidationExecutor {
         &self,
         signal: &LiquidationSignal,
         simulation: &SimulationResult,
-        mut metrics: LatencyMetrics,
-    ) -> Result<H256> {
-        let _wallet = match &self.wallet {
+        metrics: &mut LatencyMetrics,
+    ) -> Result<SubmittedLiquidation> {
+        // Simulation and queueing take time; the position may have moved since
+        self.ttl.check_now(signal, &self.blockchain).await?;
+        
+        // A speculative or presigned transaction fixes the wallet; otherwise rotate as usual
+        let presigned = [&self.speculative, &self.presigned]
+            .into_iter()
+            .flatten()
+            .find_map(|cache| cache.take(signal.user).map(|entry| (entry, cache)))
+            .and_then(|(entry, cache)| self.wallets.get(entry.wallet).map(|wallet| (entry, cache, wallet)));
+        let wallet = match presigned.as_ref().map(|(_, _, wallet)| *wallet).or_else(|| self.wallets.next()) {
             Some(w) => w,
             None => {
                 warn!("No wallet configured, skipping execution");
-                return Err(anyhow::anyhow!("No wallet configured"));
+                return Err(ExecutionError::NoWallet);
             }
         };
         
         info!("Executing liquidation for user {}", signal.user);
         
-        // Construct transaction
-        let tx_request = self.build_liquidation_transaction(
-            signal.user,
-            simulation.debt_to_cover,
-        ).await?;
+        let from = wallet.address();
+        let funding = self.funding(simulation);
+        let flash_funded = funding.is_flash();
+        let debt_to_cover = simulation.debt_to_cover.raw;
+        self.top_up(wallet, funding, debt_to_cover).await?;
+        if let Some(inventory) = &self.inventory {
+            inventory.check_opportunity(from, simulation, flash_funded).await.map_err(ExecutionError::Inventory)?;
+        }
+        
+        // A liquidation that can't fit in the block being built would only sit in the mempool
+        let gas_limit = self.gas_limit(simulation.estimated_gas);
+        let headroom = self.blockchain.block_gas_headroom().await?;
+        if gas_limit > headroom {
+            return Err(ExecutionError::BlockFull { gas_limit, headroom });
+        }
+        
+        // Through the helper, a flash liquidation reverts unless selling the collateral pays for its gas
+        let gas_cost = WadUsd::from_usd(simulation.estimated_gas_cost_usd);
+        let min_profit = simulation.debt_amount(gas_cost).map_err(ExecutionError::Pricing)?.raw;
+        let (to, data) = self.liquidation_call(signal.user, debt_to_cover, funding, min_profit);
+        
+        // Reserve a nonce up front so parallel executions don't race for it
+        let nonce = self.nonces.next_nonce(&self.blockchain, from).await?;
+        
+        // Construct and sign the transaction, or patch the presigned one
+        let signed = match presigned {
+            Some((entry, cache, _)) => entry
+                .patch(wallet, nonce, debt_to_cover, data, gas_limit)
+                .map(|(entry, patched)| {
+                    cache.record_use(patched);
+                    entry
+                })
+                .map_err(ExecutionError::Signing),
+            None => match self.build_liquidation_transaction(to, data, gas_limit).await {
+                Ok(mut tx) => {
+                    tx.set_nonce(nonce);
+                    PresignedLiquidation::sign(signal.user, debt_to_cover, wallet, tx).map_err(ExecutionError::Signing)
+                }
+                Err(e) => Err(e),
+            },
+        };
+        let signed = match signed {
+            Ok(signed) => signed,
+            Err(e) => {
+                self.nonces.reset(from).await;
+                return Err(e);
+            }
+        };
+        
+        let submitted = self.send_signed(signal, &[signal.user], simulation.expected_profit_usd, from, &signed, metrics).await?;
+        self.record_inventory(from, simulation, flash_funded);
+        Ok(submitted)
+    }
+    
+    /// Sign several small liquidations into one `liquidateBatch` through the
+    /// helper, sharing one loan, one collateral sale and the transaction overhead
+    ///
+    /// Every liquidation in `batch` must be funded the same way; the batch is
+    /// sent or fails as a whole.
+    pub async fn execute_batch(
+        &self,
+        batch: &[(&LiquidationSignal, &SimulationResult)],
+        metrics: &mut LatencyMetrics,
+    ) -> Result<SubmittedLiquidation> {
+        let Some(helper) = self.helper.filter(|_| self.can_batch()) else {
+            return Err(ExecutionError::Unbatchable);
+        };
+        let Some(&(first, first_simulation)) = batch.first() else {
+            return Err(ExecutionError::Unbatchable);
+        };
+        for (signal, _) in batch {
+            self.ttl.check_now(signal, &self.blockchain).await?;
+        }
+        let Some(wallet) = self.wallets.next() else {
+            warn!("No wallet configured, skipping execution");
+            return Err(ExecutionError::NoWallet);
+        };
+        
+        info!("Executing batch of {} liquidations", batch.len());
+        
+        let from = wallet.address();
+        let funding = self.funding(first_simulation);
+        let flash_funded = funding.is_flash();
+        let needed = batch.iter().fold(U256::zero(), |total, (_, simulation)| total + simulation.debt_to_cover.raw);
+        self.top_up(wallet, funding, needed).await?;
+        if let Some(inventory) = &self.inventory {
+            let simulations: Vec<_> = batch.iter().map(|(_, simulation)| *simulation).collect();
+            inventory.check_batch(from, &simulations, flash_funded).await.map_err(ExecutionError::Inventory)?;
+        }
+        
+        // The transaction's fixed overhead is paid once rather than per liquidation
+        let estimated_gas = batch.iter().fold(U256::zero(), |gas, (_, simulation)| gas + simulation.estimated_gas);
+        let batch_gas = estimated_gas.saturating_sub(batching::shared_gas_saved(batch.len()));
+        let gas_limit = self.gas_limit(batch_gas);
+        let headroom = self.blockchain.block_gas_headroom().await?;
+        if gas_limit > headroom {
+            return Err(ExecutionError::BlockFull { gas_limit, headroom });
+        }
+        
+        let gas_cost_usd: f64 = batch.iter().map(|(_, simulation)| simulation.estimated_gas_cost_usd.max(0.0)).sum();
+        let gas_cost_usd = gas_cost_usd * batch_gas.as_u128() as f64 / estimated_gas.as_u128().max(1) as f64;
+        let min_profit = first_simulation.debt_amount(WadUsd::from_usd(gas_cost_usd)).map_err(ExecutionError::Pricing)?.raw;
+        let liquidations: Vec<_> = batch.iter().map(|(signal, simulation)| (signal.user, simulation.debt_to_cover.raw)).collect();
+        let data = helper::encode_liquidate_batch(&liquidations, funding, min_profit);
         
+        let nonce = self.nonces.next_nonce(&self.blockchain, from).await?;
+        let signed = match self.build_liquidation_transaction(helper, data, gas_limit).await {
+            Ok(mut tx) => {
+                tx.set_nonce(nonce);
+                PresignedLiquidation::sign(first.user, needed, wallet, tx).map_err(ExecutionError::Signing)
+            }
+            Err(e) => Err(e),
+        };
+        let signed = match signed {
+            Ok(signed) => signed,
+            Err(e) => {
+                self.nonces.reset(from).await;
+                return Err(e);
+            }
+        };
+        
+        let expected_profit_usd = batch.iter().map(|(_, simulation)| simulation.expected_profit_usd).sum();
+        let users: Vec<_> = batch.iter().map(|(signal, _)| signal.user).collect();
+        let submitted = self.send_signed(first, &users, expected_profit_usd, from, &signed, metrics).await?;
+        for (_, simulation) in batch {
+            self.record_inventory(from, simulation, flash_funded);
+        }
+        Ok(submitted)
+    }
+    
+    /// Send a signed liquidation of `signal` from `from` on the route the policy
+    /// picks for it, or log it when no route is configured (POC)
+    ///
+    /// `users` are everyone the transaction liquidates: `signal`'s user alone,
+    /// or every user of a batch.
+    async fn send_signed(
+        &self,
+        signal: &LiquidationSignal,
+        users: &[Address],
+        expected_profit_usd: f64,
+        from: Address,
+        signed: &PresignedLiquidation,
+        metrics: &mut LatencyMetrics,
+    ) -> Result<SubmittedLiquidation> {
         metrics.mark_constructed();
         
-        // For POC: we log the transaction instead of actually sending it
-        // In production with real funds, you would send via private relay (Flashbots)
-        info!("Transaction constructed:");
-        info!("   To: {:?}", tx_request.to);
-        info!("   Value: {:?}", tx_request.value);
-        info!("   Gas limit: {:?}", tx_request.gas);
-        info!("   Max fee per gas: {:?}", tx_request.max_fee_per_gas);
-        info!("   Max priority fee: {:?}", tx_request.max_priority_fee_per_gas);
+        let max_priority_fee_per_gas = Self::priority_bid(&signed.tx);
+        let liquidator = self.helper.unwrap_or(from);
+        let route = self.route_for(signal, expected_profit_usd);
+        if route.kind().is_onchain() {
+            let submitted = self.submit_on_route(route, signal, users, liquidator, signed, metrics).await;
+            return match submitted {
+                Ok((tx_hash, target_block)) => {
+                    info!("Liquidation of {:?} sent via {} route: {:?}", users, route.kind(), tx_hash);
+                    Ok(SubmittedLiquidation {
+                        tx_hash,
+                        liquidator,
+                        max_priority_fee_per_gas,
+                        target_block: Some(target_block),
+                        route: route.kind(),
+                    })
+                }
+                Err(e) => {
+                    self.nonces.reset(from).await;
+                    Err(e)
+                }
+            };
+        }
         
+        route.send(signed, &[]).await?;
         metrics.mark_sent();
         
         // Calculate latencies
@@ -85,97 +660,992 @@ impl LiquidationExecutor {
         let mock_hash = H256::random();
         info!("[OK] Liquidation executed (simulated): {:?}", mock_hash);
         
-        Ok(mock_hash)
+        Ok(SubmittedLiquidation {
+            tx_hash: mock_hash,
+            liquidator,
+            max_priority_fee_per_gas,
+            target_block: None,
+            route: route.kind(),
+        })
+    }
+    
+    fn record_inventory(&self, wallet: Address, simulation: &SimulationResult, flash_funded: bool) {
+        if let Some(inventory) = &self.inventory {
+            inventory.record_liquidation(wallet, simulation, flash_funded);
+        }
+    }
+    
+    /// Run any sweeps or conversions the inventory manager has planned
+    pub async fn rebalance_inventory(&self) {
+        let Some(inventory) = &self.inventory else { return };
+        for action in inventory.plan_rebalance() {
+            let wallet = match action {
+                InventoryAction::Sweep { wallet, .. } | InventoryAction::Convert { wallet, .. } => wallet,
+            };
+            let Some(signer) = self.wallets.get(wallet) else { continue };
+            // Reserved like a liquidation's, so the two never reuse a nonce
+            let nonce = match self.nonces.next_nonce(&self.blockchain, wallet).await {
+                Ok(nonce) => nonce,
+                Err(e) => {
+                    warn!("Inventory action {:?} skipped: {}", action, e);
+                    continue;
+                }
+            };
+            match inventory.execute(&action, signer, nonce).await {
+                Ok(Some(_)) => {}
+                // Nothing was sent, or the send may not have used the nonce
+                Ok(None) => self.nonces.reset(wallet).await,
+                Err(e) => {
+                    self.nonces.reset(wallet).await;
+                    warn!("Inventory action {:?} failed: {}", action, e);
+                }
+            }
+        }
+    }
+    
+    /// Send `signed` on `route`, aimed at the blocks the target strategy picks
+    /// for bundles and at the next block otherwise, returning its hash and the
+    /// first of those blocks
+    ///
+    /// Transient RPC failures and sends the route turned down are retried with the same
+    /// transaction (same nonce, same payload). Before each retry the chain is
+    /// checked for a liquidation of any of `users` that already landed, so a
+    /// resend can never liquidate twice.
+    async fn submit_on_route(
+        &self,
+        route: &dyn SubmissionRoute,
+        signal: &LiquidationSignal,
+        users: &[Address],
+        liquidator: Address,
+        signed: &PresignedLiquidation,
+        metrics: &mut LatencyMetrics,
+    ) -> Result<(H256, u64)> {
+        let tx_hash = signed.tx_hash;
+        // Head before the first send; nothing of ours can have landed before it
+        let mut first_head = None;
+        let mut retry = 0;
+        loop {
+            if retry > 0 {
+                tokio::time::sleep(self.retries.delay(retry)).await;
+                if let Some(since) = first_head {
+                    if let Some(landed) = self.landed_liquidation(users, liquidator, tx_hash, since).await? {
+                        info!("Liquidation of {:?} already landed in {:?}, not resending", users, landed.0);
+                        return Ok(landed);
+                    }
+                }
+            }
+            
+            let sent = self.send_once(route, signed, signal.trigger.as_ref(), &mut first_head, metrics).await;
+            let error = match sent {
+                Ok(first_block) => return Ok((tx_hash, first_block)),
+                Err(error) => error,
+            };
+            if retry >= self.retries.max_retries || !error.is_retryable() {
+                return Err(error);
+            }
+            retry += 1;
+            warn!("Submission for {:?} failed ({}), retry {}/{}", users, error, retry, self.retries.max_retries);
+        }
+    }
+    
+    /// One round of `submit_on_route`, noting the head in `first_head` once
+    /// something has been sent
+    async fn send_once(
+        &self,
+        route: &dyn SubmissionRoute,
+        signed: &PresignedLiquidation,
+        trigger: Option<&Transaction>,
+        first_head: &mut Option<u64>,
+        metrics: &mut LatencyMetrics,
+    ) -> Result<u64> {
+        let max_fee_per_gas = signed.tx.gas_price().unwrap_or_default();
+        // Only bundles can be aimed past the next block
+        let (head, history) = if route.kind() == RouteKind::Bundle {
+            let (head, history) = tokio::join!(
+                self.blockchain.get_block_number(),
+                self.blockchain.base_fee_history(BASE_FEE_HISTORY_BLOCKS),
+            );
+            (head?, history.ok())
+        } else {
+            (self.blockchain.get_block_number().await?, None)
+        };
+        // Without fee history (e.g. a pre-London chain) just aim at the next block
+        let targets = match history.as_deref().and_then(BaseFeeTrend::from_history) {
+            Some(trend) => self.targeting.plan(head, &trend, max_fee_per_gas, trigger),
+            None => vec![head + 1],
+        };
+        let Some(&first_block) = targets.first() else {
+            return Err(ExecutionError::NoTargetBlock { head });
+        };
+        
+        first_head.get_or_insert(head);
+        let sent = route.send(signed, &targets).await;
+        metrics.mark_sent();
+        sent?;
+        
+        Ok(first_block)
+    }
+    
+    /// Idempotency guard for resends: our transaction and block if an earlier
+    /// send of `tx_hash` (or another liquidation from `liquidator`) already
+    /// liquidated any of `users` since `from_block`; an error if someone else
+    /// did or the chain can't be checked
+    async fn landed_liquidation(&self, users: &[Address], liquidator: Address, tx_hash: H256, from_block: u64) -> Result<Option<(H256, u64)>> {
+        if let Some(receipt) = self.blockchain.get_transaction_receipt(tx_hash).await? {
+            let block = receipt.block_number.unwrap_or_default().as_u64();
+            if receipt.status.map(|s| s.as_u64()) != Some(1) {
+                return Err(ExecutionError::Reverted { tx_hash, block });
+            }
+            return Ok(Some((tx_hash, block)));
+        }
+        for &user in users {
+            let Some((event, meta)) = self.blockchain.liquidations_of(user, from_block).await?.into_iter().next() else {
+                continue;
+            };
+            if event.liquidator != liquidator {
+                return Err(ExecutionError::AlreadyLiquidated { user, liquidator: event.liquidator });
+            }
+            return Ok(Some((meta.transaction_hash, meta.block_number.as_u64())));
+        }
+        Ok(None)
     }
     
     /// Build EIP-1559 transaction with optimized gas pricing
     async fn build_liquidation_transaction(
         &self,
-        user: Address,
-        debt_to_cover: U256,
-    ) -> Result<Eip1559TransactionRequest> {
+        to: Address,
+        call_data: Bytes,
+        gas_limit: U256,
+    ) -> Result<TypedTransaction> {
         // Get current base fee
         let gas_price = self.blockchain.get_gas_price().await?;
+        Ok(self.liquidation_request(to, call_data, gas_price, gas_limit))
+    }
+    
+    /// Target and calldata liquidating `user`: the protocol directly, a Venus vToken,
+    /// or the helper funding `debt_to_cover` from `funding` (Morpho repays from the
+    /// seized collateral instead)
+    fn liquidation_call(&self, user: Address, debt_to_cover: U256, funding: FundingSource, min_profit: U256) -> (Address, Bytes) {
+        if let Some(market) = &self.venus {
+            return market.liquidate_borrow_call(user, debt_to_cover);
+        }
+        match (self.helper, &self.morpho) {
+            (Some(helper), Some(market)) => (helper, helper::encode_liquidate_morpho(market, user, debt_to_cover, min_profit)),
+            (Some(helper), None) => (helper, helper::encode_liquidate(user, debt_to_cover, funding, min_profit)),
+            (None, _) => (self.blockchain.lending_protocol.address(), Self::encode_liquidate_call(user, debt_to_cover)),
+        }
+    }
+    
+    /// Liquidation request calling `to` with `call_data`, priced off `gas_price`
+    /// (nonce and sender left unset)
+    fn liquidation_request(&self, to: Address, call_data: Bytes, gas_price: U256, gas_limit: U256) -> TypedTransaction {
+        let max_priority_fee = self.gas_model.priority_fee();
+        let max_allowed = U256::from(self.max_gas_price_gwei) * U256::from(1_000_000_000u64);
+        
+        if self.legacy_transactions || self.gas_model.legacy_only() {
+            // One price covers base fee and tip; whatever is left over is burned or kept by the miner
+            let gas_price = std::cmp::min(gas_price + max_priority_fee, max_allowed);
+            return TransactionRequest::new()
+                .to(to)
+                .data(call_data)
+                .gas(gas_limit)
+                .gas_price(gas_price)
+                .chain_id(self.chain_id)
+                .into();
+        }
         
         // Calculate EIP-1559 fees
         let base_fee = gas_price;
-        let max_priority_fee = U256::from(2_000_000_000u64); // 2 gwei tip
         let max_fee_per_gas = base_fee * 2 + max_priority_fee; // 2x base fee + tip
         
         // Cap at max gas price
-        let max_allowed = U256::from(self.max_gas_price_gwei) * U256::from(1_000_000_000u64);
         let max_fee_per_gas = std::cmp::min(max_fee_per_gas, max_allowed);
         
-        // Encode liquidate function call
-        let protocol_address = self.blockchain.lending_protocol.address();
-        let call_data = self.encode_liquidate_call(user, debt_to_cover);
-        
-        let tx = Eip1559TransactionRequest::new()
-            .to(protocol_address)
+        Eip1559TransactionRequest::new()
+            .to(to)
             .data(call_data)
-            .gas(U256::from(350_000)) // Gas limit
+            .gas(gas_limit)
             .max_fee_per_gas(max_fee_per_gas)
             .max_priority_fee_per_gas(max_priority_fee)
-            .chain_id(31337);
-        
-        Ok(tx)
+            .chain_id(self.chain_id)
+            .into()
+    }
+    
+    /// What the transaction bids per gas over competitors: the priority fee, or the
+    /// whole gas price for a legacy transaction
+    fn priority_bid(tx: &TypedTransaction) -> U256 {
+        match tx {
+            TypedTransaction::Eip1559(tx) => tx.max_priority_fee_per_gas,
+            tx => tx.gas_price(),
+        }
+        .unwrap_or_default()
     }
     
     /// Encode liquidate(address user, uint256 debtToCover) function call
-    fn encode_liquidate_call(&self, user: Address, debt_to_cover: U256) -> Bytes {
-        // liquidate(address,uint256) selector: 0x26cdbe1a
-        let mut data = hex::decode("26cdbe1a").unwrap();
-        
-        // Encode address (padded to 32 bytes)
-        let mut user_bytes = [0u8; 32];
-        user_bytes[12..32].copy_from_slice(user.as_bytes());
-        data.extend_from_slice(&user_bytes);
+    pub fn encode_liquidate_call(user: Address, debt_to_cover: U256) -> Bytes {
+        LiquidateCall { user, debt_to_cover }.encode().into()
+    }
+}
+
+/// Result of running one opportunity through the execution pool
+#[derive(Debug, Clone)]
+pub struct ExecutionOutcome {
+    pub signal: LiquidationSignal,
+    pub simulation: Option<SimulationResult>,
+    pub tx_hash: Option<H256>,
+    pub success: bool,
+    pub metrics: LatencyMetrics,
+    pub submission: Option<SubmittedLiquidation>,
+    /// Set for on-chain submissions once they are mined, beaten or dropped
+    pub inclusion: Option<InclusionOutcome>,
+    /// What a won submission's receipt shows it actually did
+    pub settlement: Option<Settlement>,
+}
+
+impl ExecutionOutcome {
+    /// What the submission made or lost once its inclusion resolved: settled
+    /// profit less expected slippage, or the gas a reverted attempt burnt
+    pub fn realized_pnl_usd(&self) -> Option<f64> {
+        let simulation = self.simulation.as_ref()?;
+        match (&self.inclusion, &self.settlement) {
+            (Some(InclusionOutcome::Won { .. }), Some(settlement)) => Some(settlement.realized_profit_usd - simulation.slippage_usd),
+            (Some(InclusionOutcome::Reverted { .. }), _) => Some(-simulation.estimated_gas_cost_usd),
+            _ => None,
+        }
+    }
+}
+
+/// Bounded worker pool that simulates and submits several opportunities in parallel
+///
+/// At most `max_concurrent` opportunities are in flight at once; `dispatch`
+/// waits for a free slot, which pushes backpressure onto the caller. Their
+/// simulations run on a `SimulationPool` of the same size unless
+/// `with_simulation_workers` resizes it.
+pub struct ExecutionPool {
+    worker: PoolWorker,
+    permits: Arc<Semaphore>,
+    max_concurrent: usize,
+}
+
+/// An opportunity that passed every check and holds its exposure reservation
+struct ReadyLiquidation {
+    signal: LiquidationSignal,
+    simulation: SimulationResult,
+    metrics: LatencyMetrics,
+    audit_id: Option<String>,
+    event_id: Option<u64>,
+    reservation: Option<u64>,
+}
+
+/// Everything one dispatched opportunity needs, cloned into its task
+#[derive(Clone)]
+struct PoolWorker {
+    simulator: Arc<LiquidationSimulator>,
+    simulations: Arc<SimulationPool>,
+    executor: Arc<LiquidationExecutor>,
+    pause: PauseSwitch,
+    exposure: Option<Arc<ExposureLimiter>>,
+    drawdown: Option<Arc<DrawdownGuard>>,
+    address_policy: Option<Arc<AddressPolicy>>,
+    audit: Option<Arc<AuditJournal>>,
+    events: Option<Arc<EventBus>>,
+    prometheus: Option<Arc<PrometheusRegistry>>,
+    market_guard: Option<Arc<MarketGuard>>,
+    batching: BatchPolicy,
+}
+
+impl ExecutionPool {
+    pub fn new(
+        simulator: Arc<LiquidationSimulator>,
+        executor: Arc<LiquidationExecutor>,
+        max_concurrent: usize,
+    ) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            worker: PoolWorker {
+                simulations: Arc::new(SimulationPool::new(simulator.clone(), max_concurrent)),
+                simulator,
+                executor,
+                pause: PauseSwitch::new(),
+                exposure: None,
+                drawdown: None,
+                address_policy: None,
+                audit: None,
+                events: None,
+                prometheus: None,
+                market_guard: None,
+                batching: BatchPolicy::default(),
+            },
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent,
+        }
+    }
+    
+    /// Reserve each opportunity's repayment against per-protocol/per-asset caps
+    pub fn with_exposure(mut self, exposure: Arc<ExposureLimiter>) -> Self {
+        self.worker.exposure = Some(exposure);
+        self
+    }
+    
+    /// Shrink repayments, then pause, as `guard` sees realized losses mount
+    pub fn with_drawdown_guard(mut self, guard: Arc<DrawdownGuard>) -> Self {
+        self.worker.drawdown = Some(guard);
+        self
+    }
+    
+    /// Skip borrowers `policy` blocks, however profitable
+    pub fn with_address_policy(mut self, policy: Arc<AddressPolicy>) -> Self {
+        self.worker.address_policy = Some(policy);
+        self
+    }
+    
+    /// Journal every stage of each dispatched opportunity
+    pub fn with_audit(mut self, audit: Arc<AuditJournal>) -> Self {
+        self.worker.audit = Some(audit);
+        self
+    }
+    
+    /// Publish each opportunity's detection, simulation, submission and inclusion
+    pub fn with_events(mut self, events: Arc<EventBus>) -> Self {
+        self.worker.events = Some(events);
+        self
+    }
+    
+    /// Drop opportunities in markets the protocol has paused or frozen before
+    /// simulating them
+    pub fn with_market_guard(mut self, guard: Arc<MarketGuard>) -> Self {
+        self.worker.market_guard = Some(guard);
+        self
+    }
+    
+    /// Group small opportunities found together into `liquidateBatch` calls
+    pub fn with_batching(mut self, policy: BatchPolicy) -> Self {
+        self.worker.batching = policy;
+        self
+    }
+    
+    /// Run at most `workers` simulations at once, however many opportunities
+    /// are in flight
+    pub fn with_simulation_workers(mut self, workers: usize) -> Self {
+        self.worker.simulations = Arc::new(SimulationPool::new(self.worker.simulator.clone(), workers));
+        self
+    }
+    
+    /// Count simulations run in `prometheus`, by worker
+    pub fn with_prometheus(mut self, prometheus: Arc<PrometheusRegistry>) -> Self {
+        self.worker.prometheus = Some(prometheus);
+        self
+    }
+    
+    /// Switch that suspends submissions from this pool (simulation keeps running)
+    pub fn pause_switch(&self) -> PauseSwitch {
+        self.worker.pause.clone()
+    }
+    
+    /// Number of worker slots currently free
+    pub fn available_workers(&self) -> usize {
+        self.permits.available_permits()
+    }
+    
+    /// Number of opportunities currently being simulated or submitted
+    pub fn busy_workers(&self) -> usize {
+        self.max_concurrent - self.available_workers()
+    }
+    
+    /// Simulations each simulation worker ran since the last call
+    pub fn take_simulation_stats(&self) -> Vec<WorkerStats> {
+        self.worker.simulations.take_stats()
+    }
+    
+    /// Wait for a free worker, then simulate and execute the signal in the background
+    ///
+    /// For on-chain submissions the handle resolves once the transaction is
+    /// mined, beaten by a competitor or dropped.
+    pub async fn dispatch(&self, signal: LiquidationSignal) -> JoinHandle<ExecutionOutcome> {
+        let permit = self.permits.clone()
+            .acquire_owned()
+            .await
+            .expect("execution pool semaphore closed");
+        let worker = self.worker.clone();
         
-        // Encode uint256
-        let mut amount_bytes = [0u8; 32];
-        debt_to_cover.to_big_endian(&mut amount_bytes);
-        data.extend_from_slice(&amount_bytes);
+        tokio::spawn(async move {
+            let event_id = worker.events.as_ref().map(|events| events.begin(&signal));
+            let outcome = worker.process(signal, event_id).await;
+            drop(permit);
+            if outcome.tx_hash.is_some() {
+                worker.executor.rebalance_inventory().await;
+            }
+            // Wait for the race to resolve outside the worker slot
+            worker.resolve(outcome, event_id).await
+        })
+    }
+    
+    /// Dispatch opportunities found together (e.g. after one price update),
+    /// sending the small ones the batch policy groups as one `liquidateBatch`
+    ///
+    /// Returns one handle per signal, in order. Without batching, or without
+    /// the helper to batch through, each signal is dispatched on its own.
+    pub async fn dispatch_batch(&self, signals: Vec<LiquidationSignal>) -> Vec<JoinHandle<ExecutionOutcome>> {
+        if signals.len() < 2 || !self.worker.batching.enabled() || !self.worker.executor.can_batch() {
+            let mut handles = Vec::with_capacity(signals.len());
+            for signal in signals {
+                handles.push(self.dispatch(signal).await);
+            }
+            return handles;
+        }
+        let permit = self.permits.clone()
+            .acquire_many_owned(signals.len().min(self.max_concurrent) as u32)
+            .await
+            .expect("execution pool semaphore closed");
+        let worker = self.worker.clone();
         
-        Bytes::from(data)
+        let (senders, handles): (Vec<_>, Vec<_>) = signals
+            .iter()
+            .map(|_| {
+                let (tx, rx) = oneshot::channel::<ExecutionOutcome>();
+                (tx, tokio::spawn(async move { rx.await.expect("batch task dropped an outcome") }))
+            })
+            .unzip();
+        tokio::spawn(async move {
+            let event_ids: Vec<_> = signals.iter().map(|signal| worker.events.as_ref().map(|events| events.begin(signal))).collect();
+            let prepared = future::join_all(signals.into_iter().zip(&event_ids).map(|(signal, id)| worker.prepare(signal, *id))).await;
+            
+            // Skipped opportunities keep their outcome; the rest are grouped and sent
+            let mut outcomes = Vec::with_capacity(prepared.len());
+            let mut ready = Vec::new();
+            for (index, prepared) in prepared.into_iter().enumerate() {
+                match prepared {
+                    Ok(liquidation) => {
+                        ready.push(Some((index, liquidation)));
+                        outcomes.push(None);
+                    }
+                    Err(outcome) => outcomes.push(Some(*outcome)),
+                }
+            }
+            let simulations: Vec<_> = ready.iter().flatten().map(|(_, liquidation)| &liquidation.simulation).collect();
+            let groups = worker.batching.plan(&simulations);
+            let groups: Vec<Vec<_>> = groups
+                .into_iter()
+                .map(|group| group.into_iter().filter_map(|index| ready[index].take()).collect())
+                .collect();
+            
+            let sent = future::join_all(groups.into_iter().map(|group| {
+                let worker = &worker;
+                async move {
+                    let (indices, mut group): (Vec<_>, Vec<_>) = group.into_iter().unzip();
+                    let outcomes = match group.len() {
+                        1 => vec![worker.submit(group.remove(0)).await],
+                        _ => worker.submit_batch(group).await,
+                    };
+                    indices.into_iter().zip(outcomes).collect::<Vec<_>>()
+                }
+            }))
+            .await;
+            for (index, outcome) in sent.into_iter().flatten() {
+                outcomes[index] = Some(outcome);
+            }
+            drop(permit);
+            
+            let outcomes: Vec<_> = outcomes.into_iter().map(|outcome| outcome.expect("every signal has an outcome")).collect();
+            if outcomes.iter().any(|outcome| outcome.tx_hash.is_some()) {
+                worker.executor.rebalance_inventory().await;
+            }
+            let resolved = future::join_all(outcomes.into_iter().zip(event_ids).map(|(outcome, id)| worker.resolve(outcome, id))).await;
+            for (sender, outcome) in senders.into_iter().zip(resolved) {
+                let _ = sender.send(outcome);
+            }
+        });
+        handles
+    }
+}
+
+impl PoolWorker {
+    fn audit(&self, id: Option<&str>, stage: AuditStage, user: Address, details: serde_json::Value) {
+        if let (Some(audit), Some(id)) = (&self.audit, id) {
+            audit.record(id, stage, user, details);
+        }
     }
     
-    /// Submit transaction via private relay (Flashbots simulation)
-    /// In production, this would send to actual Flashbots relay
-    pub async fn submit_via_private_relay(
-        &self,
-        _tx: Eip1559TransactionRequest,
-    ) -> Result<H256> {
-        info!("Submitting to private relay (simulated)");
-        info!("   In production, this would use Flashbots RPC");
+    fn publish(&self, id: Option<u64>, user: Address, event: impl FnOnce() -> EventKind) {
+        if let (Some(events), Some(id)) = (&self.events, id) {
+            events.publish(id, user, event());
+        }
+    }
+    
+    /// Pause or resize repayments as the drawdown guard says
+    fn apply_drawdown(&self, state: DrawdownState) {
+        match state {
+            DrawdownState::Paused(_) => {
+                self.pause.pause(DRAWDOWN_PAUSE_REASON);
+            }
+            DrawdownState::Reduced(fraction) => {
+                self.pause.resume(DRAWDOWN_PAUSE_REASON);
+                self.simulator.set_repay_fraction(fraction);
+            }
+            DrawdownState::Normal => {
+                self.pause.resume(DRAWDOWN_PAUSE_REASON);
+                self.simulator.set_repay_fraction(1.0);
+            }
+        }
+    }
+    
+    async fn process(&self, signal: LiquidationSignal, event_id: Option<u64>) -> ExecutionOutcome {
+        match self.prepare(signal, event_id).await {
+            Ok(ready) => self.submit(ready).await,
+            Err(outcome) => *outcome,
+        }
+    }
+    
+    /// Simulate `signal` and run every check short of submitting it; the
+    /// error is the outcome of an opportunity skipped or failed on the way
+    async fn prepare(&self, signal: LiquidationSignal, event_id: Option<u64>) -> std::result::Result<ReadyLiquidation, Box<ExecutionOutcome>> {
+        let user = signal.user;
+        let prepared = self.simulate_and_check(signal, event_id).await;
+        if prepared.is_err() {
+            // Whatever was built while simulating won't be sent
+            self.executor.discard_speculative(user);
+        }
+        prepared
+    }
+    
+    /// Sign `signal`'s liquidation in parallel with its simulation, when the
+    /// executor builds speculatively
+    async fn speculate(&self, signal: &LiquidationSignal) {
+        if self.executor.speculative_cache().is_none() || !self.executor.has_wallet() {
+            return;
+        }
+        let debt_to_cover = match self.simulator.repayment(signal).await {
+            Ok(debt_to_cover) => debt_to_cover,
+            Err(e) => {
+                debug!("Speculative build for {} skipped: {}", signal.user, e);
+                return;
+            }
+        };
+        if let Err(e) = self.executor.speculate(signal.user, debt_to_cover).await {
+            debug!("Speculative build for {} failed: {}", signal.user, e);
+        }
+    }
+    
+    async fn simulate_and_check(&self, signal: LiquidationSignal, event_id: Option<u64>) -> std::result::Result<ReadyLiquidation, Box<ExecutionOutcome>> {
+        let mut metrics = signal.metrics.clone();
+        let audit_key = self.audit.as_ref().map(|audit| audit.begin(&signal));
+        let audit_id = audit_key.as_deref();
+        let user = signal.user;
+        
+        if let Some(guard) = &self.market_guard {
+            if let Some(why) = guard.halted(signal.collateral_asset, signal.debt_asset).await {
+                debug!("Skipping {}: market halted ({})", user, why);
+                self.audit(audit_id, AuditStage::Skipped, user, json!({ "reason": format!("market halted: {}", why) }));
+                return Err(Box::new(ExecutionOutcome { signal, simulation: None, tx_hash: None, success: false, metrics, submission: None, inclusion: None, settlement: None }));
+            }
+        }
+        // Losses age out of the window while paused, so re-check before sizing
+        if let Some(guard) = &self.drawdown {
+            self.apply_drawdown(guard.state());
+        }
+        
+        let (run, ()) = tokio::join!(self.simulations.simulate(&signal), self.speculate(&signal));
+        if let Some(prometheus) = &self.prometheus {
+            prometheus.record_simulation(run.worker, run.elapsed_us);
+        }
+        let simulation = match run.result {
+            Ok(sim) => sim,
+            Err(e) => {
+                warn!("Simulation failed: {}", e);
+                self.audit(audit_id, AuditStage::Failed, user, json!({ "error": format!("simulation: {}", e) }));
+                return Err(Box::new(ExecutionOutcome { signal, simulation: None, tx_hash: None, success: false, metrics, submission: None, inclusion: None, settlement: None }));
+            }
+        };
+        metrics.mark_simulated();
+        self.publish(event_id, user, || EventKind::simulation(&simulation));
+        self.audit(audit_id, AuditStage::Simulated, user, json!({
+            "profitable": simulation.profitable,
+            "expected_profit_usd": simulation.expected_profit_usd,
+            "worst_case_profit_usd": simulation.worst_case_profit_usd,
+            "collateral_to_seize": simulation.collateral_to_seize.raw,
+            "debt_to_cover": simulation.debt_to_cover.raw,
+            "estimated_gas": simulation.estimated_gas,
+            "estimated_gas_cost_usd": simulation.estimated_gas_cost_usd,
+            "l1_data_fee_usd": simulation.l1_data_fee_usd,
+            "liquidation_bonus_bps": simulation.liquidation_bonus_bps,
+            "close_factor_bps": simulation.close_factor_bps,
+            "funding": simulation.funding.source.name(),
+            "funding_cost_usd": simulation.funding_cost_usd,
+            "slippage_usd": simulation.slippage_usd,
+            "simulation_us": metrics.latency_simulation().map(|d| d.as_secs_f64() * 1e6),
+            "simulation_worker": run.worker,
+        }));
+        
+        let skip = |reason: String| {
+            self.audit(audit_id, AuditStage::Skipped, user, json!({ "reason": reason }));
+        };
+        
+        if !simulation.profitable {
+            skip("unprofitable".to_string());
+            return Err(Box::new(ExecutionOutcome { signal, simulation: Some(simulation), tx_hash: None, success: false, metrics, submission: None, inclusion: None, settlement: None }));
+        }
+        
+        if let Some(Err(e)) = self.address_policy.as_ref().map(|policy| policy.check(user)) {
+            debug!("Skipping {}: {}", user, e);
+            skip(e.to_string());
+            return Err(Box::new(ExecutionOutcome { signal, simulation: Some(simulation), tx_hash: None, success: false, metrics, submission: None, inclusion: None, settlement: None }));
+        }
         
-        // Simulate successful submission
-        Ok(H256::random())
+        if self.pause.is_paused() {
+            debug!("Execution paused ({:?}), skipping {}", self.pause.reasons(), signal.user);
+            skip(format!("paused: {}", self.pause.reasons().join(", ")));
+            return Err(Box::new(ExecutionOutcome { signal, simulation: Some(simulation), tx_hash: None, success: false, metrics, submission: None, inclusion: None, settlement: None }));
+        }
+        
+        let repay_usd = simulation.repay_usd();
+        let protocol = self.simulator.protocol_name();
+        let reservation = match self.exposure.as_ref().map(|e| e.reserve(protocol, signal.debt_asset, repay_usd)) {
+            Some(Err(e)) => {
+                debug!("Skipping {}: {}", signal.user, e);
+                skip(e.to_string());
+                return Err(Box::new(ExecutionOutcome { signal, simulation: Some(simulation), tx_hash: None, success: false, metrics, submission: None, inclusion: None, settlement: None }));
+            }
+            Some(Ok(id)) => Some(id),
+            None => None,
+        };
+        
+        Ok(ReadyLiquidation { signal, simulation, metrics, audit_id: audit_key, event_id, reservation })
+    }
+    
+    /// Execute one checked liquidation, or fake its submission without a wallet
+    async fn submit(&self, mut ready: ReadyLiquidation) -> ExecutionOutcome {
+        let dry_run = !self.executor.has_wallet();
+        let (submission, success) = if !dry_run {
+            match self.executor.execute_liquidation(&ready.signal, &ready.simulation, &mut ready.metrics).await {
+                Ok(submission) => (Some(submission), true),
+                Err(e) => {
+                    warn!("Execution failed for {}: {}", ready.signal.user, e);
+                    self.fail(&ready, &e);
+                    (None, false)
+                }
+            }
+        } else {
+            // Dry run: no signer, so treat the opportunity as constructed and sent
+            debug!("Dry-run execution for {}", ready.signal.user);
+            ready.metrics.mark_constructed();
+            ready.metrics.mark_sent();
+            (None, true)
+        };
+        self.finish(ready, submission, success, 1)
+    }
+    
+    /// Execute checked liquidations as one `liquidateBatch`; they land or fail together
+    async fn submit_batch(&self, mut batch: Vec<ReadyLiquidation>) -> Vec<ExecutionOutcome> {
+        let dry_run = !self.executor.has_wallet();
+        let result = if !dry_run {
+            let mut metrics = batch[0].metrics.clone();
+            let liquidations: Vec<_> = batch.iter().map(|ready| (&ready.signal, &ready.simulation)).collect();
+            let result = self.executor.execute_batch(&liquidations, &mut metrics).await;
+            batch[0].metrics = metrics;
+            result.map(Some)
+        } else {
+            debug!("Dry-run batch of {} liquidations", batch.len());
+            Ok(None)
+        };
+    
+        let size = batch.len();
+        // Every user's receipt tracking and inclusion latency starts from the one send
+        let sent_unix_ns = batch[0].metrics.sent_unix_ns;
+        match result {
+            Ok(submission) => batch
+                .into_iter()
+                .enumerate()
+                .map(|(index, mut ready)| {
+                    if dry_run || index > 0 {
+                        ready.metrics.mark_constructed();
+                        ready.metrics.mark_sent();
+                    }
+                    if !dry_run {
+                        ready.metrics.sent_unix_ns = sent_unix_ns;
+                    }
+                    self.finish(ready, submission.clone(), true, size)
+                })
+                .collect(),
+            Err(e) => {
+                warn!("Batch of {} liquidations failed: {}", size, e);
+                batch
+                    .into_iter()
+                    .map(|ready| {
+                        self.fail(&ready, &e);
+                        self.finish(ready, None, false, size)
+                    })
+                    .collect()
+            }
+        }
+    }
+    
+    /// Release the exposure a liquidation that was never sent reserved
+    fn fail(&self, ready: &ReadyLiquidation, error: &ExecutionError) {
+        if let (Some(exposure), Some(id)) = (&self.exposure, ready.reservation) {
+            exposure.release(id);
+        }
+        self.audit(ready.audit_id.as_deref(), AuditStage::Failed, ready.signal.user, json!({ "error": error.to_string() }));
+    }
+    
+    /// Journal and publish a liquidation sent alone or in a batch of `batch_size`
+    fn finish(&self, ready: ReadyLiquidation, submission: Option<SubmittedLiquidation>, success: bool, batch_size: usize) -> ExecutionOutcome {
+        let ReadyLiquidation { signal, simulation, metrics, audit_id, event_id, .. } = ready;
+        let audit_id = audit_id.as_deref();
+        let user = signal.user;
+        // Sent alone it was used; batched or failed it never will be
+        self.executor.discard_speculative(user);
+        let dry_run = !self.executor.has_wallet();
+        let tx_hash = submission.as_ref().map(|s| s.tx_hash);
+        let route = submission.as_ref().map(|s| s.route);
+    
+        if success {
+            let latencies = metrics.get_all_latencies();
+            self.audit(audit_id, AuditStage::Constructed, user, json!({
+                "construction_us": latencies.get("construction_us"),
+            }));
+            self.audit(audit_id, AuditStage::Submitted, user, json!({
+                "tx_hash": tx_hash,
+                "route": route,
+                "dry_run": dry_run,
+                "batch_size": batch_size,
+                "end_to_end_us": latencies.get("end_to_end_us"),
+            }));
+            self.publish(event_id, user, || EventKind::Submission { tx_hash, end_to_end_us: latencies.get("end_to_end_us").copied() });
+    
+            if let (Some(audit), Some(id), Some(hash)) = (&self.audit, audit_id, tx_hash) {
+                if self.executor.submits_onchain() {
+                    let blockchain = self.executor.blockchain.clone();
+                    tokio::spawn(audit.clone().track_receipt(blockchain, id.to_string(), user, hash, metrics.sent_unix_ns));
+                }
+            }
+        }
+    
+        ExecutionOutcome { signal, simulation: Some(simulation), tx_hash, success, metrics, submission, inclusion: None, settlement: None }
+    }
+    
+    /// Wait for an on-chain submission's race to resolve and read back its settlement
+    async fn resolve(&self, mut outcome: ExecutionOutcome, event_id: Option<u64>) -> ExecutionOutcome {
+        if let (Some(submission), true) = (&outcome.submission, self.executor.submits_onchain()) {
+            let user = outcome.signal.user;
+            let resolved = inclusion::resolve(&self.executor.blockchain, user, submission).await;
+            if let InclusionOutcome::Won { timestamp, .. } = resolved {
+                if timestamp > 0 {
+                    outcome.metrics.mark_included(timestamp);
+                }
+                if let Some(simulation) = &outcome.simulation {
+                    outcome.settlement = inclusion::settle(&self.executor.blockchain, submission.tx_hash, user, simulation).await;
+                }
+            }
+            self.publish(event_id, user, || EventKind::inclusion(&resolved));
+            outcome.inclusion = Some(resolved);
+        }
+        if let (Some(guard), Some(pnl)) = (&self.drawdown, outcome.realized_pnl_usd()) {
+            self.apply_drawdown(guard.record(pnl));
+        }
+        outcome
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::funding::FundingQuote;
+    use crate::mempool_streamer::{TransactionClassifier, TransactionType, LIQUIDATE_SELECTOR};
+    use crate::protocol::{AssetRiskParams, HealthFactorScale};
+    use crate::testkit::{offline_client, FixedRiskParams};
+    use crate::units::{HealthFactor, TokenAmount};
+    use crate::wallets::RotationStrategy;
+    use ethers::abi::AbiDecode;
 
     #[test]
     fn test_liquidate_call_encoding() {
-        let executor = LiquidationExecutor::new(
-            Arc::new(BlockchainClient::new(
-                "http://127.0.0.1:8545",
-                None,
-                Address::zero(),
-                Address::zero(),
-            ).await.unwrap()),
+        let user = Address::from_low_u64_be(1);
+        let debt = U256::from(1000);
+        let encoded = LiquidationExecutor::encode_liquidate_call(user, debt);
+        
+        // The selector the classifier matches liquidations on, and the arguments decode back
+        assert_eq!(encoded[..4], LIQUIDATE_SELECTOR);
+        assert_eq!(TransactionClassifier::classify_calldata(&encoded), Some(TransactionType::Liquidate));
+        let decoded = LiquidateCall::decode(&encoded).unwrap();
+        assert_eq!((decoded.user, decoded.debt_to_cover), (user, debt));
+    }
+    
+    #[test]
+    fn test_retry_backoff_doubles() {
+        let retries = SubmissionRetries { max_retries: 3, backoff: Duration::from_millis(200) };
+        let delays: Vec<_> = (1..=3).map(|retry| retries.delay(retry).as_millis()).collect();
+        assert_eq!(delays, [200, 400, 800]);
+        assert_eq!(SubmissionRetries::default().delay(1), Duration::ZERO);
+    }
+    
+    #[test]
+    fn test_retryable_errors() {
+        assert!(ExecutionError::Rejected { blocks: vec![1] }.is_retryable());
+        assert!(ExecutionError::Rpc(RpcError::Batch(anyhow::anyhow!("timed out"))).is_retryable());
+        // Resending can't help once someone else liquidated or the chain is wrong
+        let user = Address::from_low_u64_be(1);
+        assert!(!ExecutionError::AlreadyLiquidated { user, liquidator: user }.is_retryable());
+        assert!(!ExecutionError::Rpc(RpcError::ChainIdMismatch { expected: 1, actual: 5 }).is_retryable());
+    }
+    
+    #[tokio::test]
+    async fn test_gas_limit_margin() {
+        let blockchain = offline_client().await;
+        let executor = LiquidationExecutor::new(blockchain, WalletPool::empty(), 100).with_gas_limit_multiplier(1.25);
+        assert_eq!(executor.gas_limit(U256::from(200_000)), U256::from(250_000));
+        // Rounded up, never below the estimate
+        assert_eq!(executor.gas_limit(U256::from(3)), U256::from(4));
+    }
+    
+    #[tokio::test]
+    async fn test_legacy_request() {
+        let blockchain = offline_client().await;
+        let executor = LiquidationExecutor::new(blockchain, WalletPool::empty(), 100)
+            .with_chain_id(56)
+            .with_legacy_transactions(true);
+        let gwei = U256::exp10(9);
+        let (to, data) = executor.liquidation_call(Address::from_low_u64_be(1), U256::exp10(21), FundingSource::Inventory, U256::zero());
+        let tx = executor.liquidation_request(to, data, gwei * 30, U256::from(300_000));
+        
+        assert!(matches!(tx, TypedTransaction::Legacy(_)));
+        // Base price plus the L1 tip, as one gas price
+        assert_eq!(tx.gas_price(), Some(gwei * 32));
+        assert_eq!(LiquidationExecutor::priority_bid(&tx), gwei * 32);
+        assert_eq!(tx.gas(), Some(&U256::from(300_000)));
+        assert_eq!(tx.chain_id(), Some(56.into()));
+        
+        // The BSC gas model forces type 0; Venus liquidations go to the borrowed vToken
+        let blockchain = offline_client().await;
+        let market = VenusMarket { borrowed: Address::from_low_u64_be(7), collateral: Address::from_low_u64_be(8) };
+        let executor = LiquidationExecutor::new(blockchain, WalletPool::empty(), 100)
+            .with_gas_model(GasModel::Bsc)
+            .with_venus_market(market);
+        let (to, data) = executor.liquidation_call(Address::from_low_u64_be(1), U256::exp10(21), FundingSource::Inventory, U256::zero());
+        let tx = executor.liquidation_request(to, data, gwei, U256::from(300_000));
+        assert!(matches!(tx, TypedTransaction::Legacy(_)));
+        assert_eq!(tx.gas_price(), Some(gwei));
+        assert_eq!(tx.to_addr(), Some(&market.borrowed));
+    }
+    
+    #[tokio::test]
+    async fn test_configured_wallets_reach_execution() {
+        let signal = LiquidationSignal {
+            user: Address::from_low_u64_be(1),
+            collateral: TokenAmount::wad(U256::exp10(18)),
+            debt: TokenAmount::wad(U256::exp10(21)),
+            health_factor: HealthFactor::new(U256::from(80), HealthFactorScale::PERCENT),
+            collateral_asset: Address::zero(),
+            debt_asset: Address::zero(),
+            trigger: None,
+            observed_block: None,
+            stale: false,
+            metrics: LatencyMetrics::new(),
+        };
+        let simulation = SimulationResult {
+            profitable: true,
+            expected_profit_usd: 20.0,
+            worst_case_profit_usd: 20.0,
+            collateral_to_seize: TokenAmount::wad(U256::exp10(18)),
+            debt_to_cover: TokenAmount::wad(U256::exp10(21)),
+            estimated_gas: U256::from(200_000),
+            estimated_gas_cost_usd: 10.0,
+            l1_data_fee_usd: 0.0,
+            liquidation_bonus_bps: 1_000,
+            close_factor_bps: 5_000,
+            eth_price_usd: 2000.0,
+            funding: FundingQuote::inventory(),
+            funding_cost_usd: 0.0,
+            slippage_usd: 0.0,
+        };
+        
+        let blockchain = offline_client().await;
+        let executor = LiquidationExecutor::new(blockchain.clone(), WalletPool::empty(), 100);
+        assert!(!executor.has_wallet());
+        let result = executor.execute_liquidation(&signal, &simulation, &mut LatencyMetrics::new()).await;
+        assert!(matches!(result, Err(ExecutionError::NoWallet)));
+        
+        // With keys configured a wallet is picked and execution goes on to the
+        // chain (unreachable here)
+        let keys = [H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let wallets = WalletPool::from_keys(&keys, 31337, RotationStrategy::RoundRobin).unwrap();
+        let executor = LiquidationExecutor::new(blockchain, wallets, 100);
+        assert!(executor.has_wallet());
+        let result = executor.execute_liquidation(&signal, &simulation, &mut LatencyMetrics::new()).await;
+        assert!(matches!(result, Err(ExecutionError::Rpc(_))), "{:?}", result);
+    }
+    
+    #[tokio::test]
+    async fn test_speculative_build_discarded() {
+        let blockchain = offline_client().await;
+        let executor = LiquidationExecutor::new(blockchain, WalletPool::empty(), 100);
+        assert!(!executor.speculate(Address::zero(), U256::one()).await.unwrap());
+        
+        let executor = executor.with_speculative_build();
+        let wallet: LocalWallet = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let user = Address::from_low_u64_be(7);
+        let (to, data) = executor.liquidation_call(user, U256::exp10(21), FundingSource::Inventory, U256::zero());
+        let mut tx = executor.liquidation_request(to, data, U256::exp10(9), U256::from(PRESIGN_GAS_LIMIT));
+        tx.set_nonce(0);
+        let cache = executor.speculative_cache().unwrap();
+        cache.insert(PresignedLiquidation::sign(user, U256::exp10(21), &wallet, tx).unwrap());
+        
+        // Dropping an opportunity drops its build, once
+        executor.discard_speculative(user);
+        executor.discard_speculative(user);
+        assert!(!cache.is_fresh(user));
+        assert_eq!(cache.stats().discarded, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_execution_pool_dry_run() {
+        let blockchain = Arc::new(BlockchainClient::new(
+            "http://127.0.0.1:8545",
             None,
-            100,
+            Address::zero(),
+            Address::zero(),
+        ).await.unwrap());
+        let simulator = Arc::new(
+            LiquidationSimulator::new(blockchain.clone(), 10.0)
+                .with_adapter(Arc::new(FixedRiskParams(AssetRiskParams::default()))),
         );
+        let executor = Arc::new(LiquidationExecutor::new(blockchain.clone(), WalletPool::empty(), 100));
+        let pool = ExecutionPool::new(simulator.clone(), executor, 2);
         
-        let user = Address::from_low_u64_be(1);
-        let debt = U256::from(1000);
-        let encoded = executor.encode_liquidate_call(user, debt);
+        let signal = LiquidationSignal {
+            user: Address::from_low_u64_be(1),
+            collateral: TokenAmount::wad(U256::from(5u64) * U256::exp10(18)),
+            debt: TokenAmount::wad(U256::from(8000u64) * U256::exp10(18)),
+            health_factor: HealthFactor::new(U256::from(80), HealthFactorScale::PERCENT),
+            collateral_asset: Address::zero(),
+            debt_asset: Address::zero(),
+            trigger: None,
+            observed_block: None,
+            stale: false,
+            metrics: LatencyMetrics::new(),
+        };
         
-        // Check selector
-        assert_eq!(&encoded[..4], &hex::decode("26cdbe1a").unwrap());
+        let handles = vec![
+            pool.dispatch(signal.clone()).await,
+            pool.dispatch(signal.clone()).await,
+        ];
+        for handle in handles {
+            let outcome = handle.await.unwrap();
+            assert!(outcome.success);
+            assert!(outcome.tx_hash.is_none());
+        }
+        assert_eq!(pool.available_workers(), 2);
+        assert_eq!(pool.take_simulation_stats().iter().map(|worker| worker.simulations).sum::<usize>(), 2);
+        
+        // Signals dispatched together come back in order, one outcome each
+        let helper = Address::from_low_u64_be(0x4e1);
+        let executor = Arc::new(LiquidationExecutor::new(blockchain, WalletPool::empty(), 100).with_helper(helper));
+        let pool = ExecutionPool::new(simulator, executor, 2)
+            .with_batching(BatchPolicy { max_size: 2, max_repay_usd: 1e6 });
+        let users = [Address::from_low_u64_be(1), Address::from_low_u64_be(2)];
+        let signals = users.iter().map(|user| LiquidationSignal { user: *user, ..signal.clone() }).collect();
+        let outcomes: Vec<_> = futures::future::join_all(pool.dispatch_batch(signals).await).await;
+        assert_eq!(outcomes.iter().map(|outcome| outcome.as_ref().unwrap().signal.user).collect::<Vec<_>>(), users);
+        assert!(outcomes.iter().all(|outcome| outcome.as_ref().unwrap().success));
+        assert_eq!(pool.available_workers(), 2);
     }
 }
-
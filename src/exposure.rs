@@ -0,0 +1,295 @@
+use anyhow::Result;
+use ethers::types::Address;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Caps on debt repaid into each protocol and asset over a rolling window
+#[derive(Debug, Clone)]
+pub struct ExposureLimits {
+    pub window: Duration,
+    /// Cap for protocols without an explicit entry (None = uncapped)
+    pub default_protocol_usd: Option<f64>,
+    /// Cap for assets without an explicit entry (None = uncapped)
+    pub default_asset_usd: Option<f64>,
+    /// Per-protocol caps keyed by adapter name
+    pub protocol_usd: HashMap<String, f64>,
+    /// Per-asset caps keyed by debt asset address
+    pub asset_usd: HashMap<Address, f64>,
+}
+
+impl Default for ExposureLimits {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(3600),
+            default_protocol_usd: None,
+            default_asset_usd: None,
+            protocol_usd: HashMap::new(),
+            asset_usd: HashMap::new(),
+        }
+    }
+}
+
+impl ExposureLimits {
+    fn protocol_limit(&self, protocol: &str) -> Option<f64> {
+        self.protocol_usd.get(protocol).copied().or(self.default_protocol_usd)
+    }
+
+    fn asset_limit(&self, asset: Address) -> Option<f64> {
+        self.asset_usd.get(&asset).copied().or(self.default_asset_usd)
+    }
+}
+
+#[derive(Debug)]
+struct ExposureEntry {
+    id: u64,
+    at: Instant,
+    protocol: String,
+    asset: Address,
+    usd: f64,
+}
+
+#[derive(Debug, Default)]
+struct ExposureLedger {
+    next_id: u64,
+    entries: VecDeque<ExposureEntry>,
+}
+
+/// Rolling-window ledger of debt repaid per protocol and asset
+///
+/// Opportunities reserve their repayment before submission, so a bug or an
+/// oracle anomaly producing a burst of "profitable" signals in one market
+/// stops at the cap instead of draining the wallet into it.
+#[derive(Debug)]
+pub struct ExposureLimiter {
+    limits: ExposureLimits,
+    ledger: Mutex<ExposureLedger>,
+}
+
+impl ExposureLimiter {
+    pub fn new(limits: ExposureLimits) -> Self {
+        Self { limits, ledger: Mutex::new(ExposureLedger::default()) }
+    }
+
+    /// Reserve `usd` of repayment against `protocol` and `asset`, returning a
+    /// reservation id to `release` if the liquidation is not submitted
+    pub fn reserve(&self, protocol: &str, asset: Address, usd: f64) -> Result<u64> {
+        self.reserve_at(Instant::now(), protocol, asset, usd)
+    }
+
+    fn reserve_at(&self, now: Instant, protocol: &str, asset: Address, usd: f64) -> Result<u64> {
+        let mut ledger = self.ledger.lock().unwrap();
+        while ledger.entries.front().is_some_and(|e| now.duration_since(e.at) >= self.limits.window) {
+            ledger.entries.pop_front();
+        }
+
+        if let Some(limit) = self.limits.protocol_limit(protocol) {
+            let outstanding: f64 = ledger.entries.iter().filter(|e| e.protocol == protocol).map(|e| e.usd).sum();
+            if outstanding + usd > limit {
+                warn!("Exposure limit hit for protocol {}: ${:.2} + ${:.2} > ${:.2}", protocol, outstanding, usd, limit);
+                anyhow::bail!("Protocol {} exposure limit ${:.2} reached", protocol, limit);
+            }
+        }
+        if let Some(limit) = self.limits.asset_limit(asset) {
+            let outstanding: f64 = ledger.entries.iter().filter(|e| e.asset == asset).map(|e| e.usd).sum();
+            if outstanding + usd > limit {
+                warn!("Exposure limit hit for asset {:?}: ${:.2} + ${:.2} > ${:.2}", asset, outstanding, usd, limit);
+                anyhow::bail!("Asset {:?} exposure limit ${:.2} reached", asset, limit);
+            }
+        }
+
+        let id = ledger.next_id;
+        ledger.next_id += 1;
+        ledger.entries.push_back(ExposureEntry { id, at: now, protocol: protocol.to_string(), asset, usd });
+        Ok(id)
+    }
+
+    /// Drop a reservation whose liquidation was never submitted
+    pub fn release(&self, id: u64) {
+        self.ledger.lock().unwrap().entries.retain(|e| e.id != id);
+    }
+
+    /// Repayment currently counted against `protocol` within the window
+    pub fn protocol_exposure_usd(&self, protocol: &str) -> f64 {
+        let ledger = self.ledger.lock().unwrap();
+        let now = Instant::now();
+        ledger
+            .entries
+            .iter()
+            .filter(|e| e.protocol == protocol && now.duration_since(e.at) < self.limits.window)
+            .map(|e| e.usd)
+            .sum()
+    }
+}
+
+/// Realized losses that shrink repayments, then stop execution
+#[derive(Debug, Clone)]
+pub struct DrawdownLimits {
+    pub window: Duration,
+    /// Net loss over the window that pauses execution (None = no limit)
+    pub max_drawdown_usd: Option<f64>,
+    /// Share of `max_drawdown_usd` lost before repayments are scaled down
+    pub reduce_at: f64,
+    /// Fraction of the full repayment used while scaled down
+    pub reduced_size: f64,
+    /// Consecutive losing liquidations within the window that pause execution
+    pub max_losing_streak: Option<usize>,
+}
+
+impl Default for DrawdownLimits {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(86_400),
+            max_drawdown_usd: None,
+            reduce_at: 0.5,
+            reduced_size: 0.5,
+            max_losing_streak: None,
+        }
+    }
+}
+
+/// What the drawdown guard allows right now
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawdownState {
+    Normal,
+    /// Repay only this fraction of what the close factor allows
+    Reduced(f64),
+    Paused(String),
+}
+
+#[derive(Debug)]
+struct DrawdownLedger {
+    entries: VecDeque<(Instant, f64)>,
+    state: DrawdownState,
+}
+
+/// Rolling-window net PnL of settled liquidations
+///
+/// Gas burnt by reverted attempts and slippage count as losses, so a strategy
+/// that has stopped working backs off on its own instead of bleeding until an
+/// operator notices. Losses age out of the window, which lifts the limits again.
+#[derive(Debug)]
+pub struct DrawdownGuard {
+    limits: DrawdownLimits,
+    ledger: Mutex<DrawdownLedger>,
+}
+
+impl DrawdownGuard {
+    pub fn new(limits: DrawdownLimits) -> Self {
+        Self {
+            limits,
+            ledger: Mutex::new(DrawdownLedger { entries: VecDeque::new(), state: DrawdownState::Normal }),
+        }
+    }
+
+    /// Record one resolved liquidation's realized PnL (negative for a loss)
+    pub fn record(&self, pnl_usd: f64) -> DrawdownState {
+        self.record_at(Instant::now(), pnl_usd)
+    }
+
+    fn record_at(&self, now: Instant, pnl_usd: f64) -> DrawdownState {
+        let mut ledger = self.ledger.lock().unwrap();
+        ledger.entries.push_back((now, pnl_usd));
+        self.update(&mut ledger, now)
+    }
+
+    /// Current state, after dropping results older than the window
+    pub fn state(&self) -> DrawdownState {
+        self.state_at(Instant::now())
+    }
+
+    fn state_at(&self, now: Instant) -> DrawdownState {
+        self.update(&mut self.ledger.lock().unwrap(), now)
+    }
+
+    /// Net realized PnL within the window
+    pub fn net_pnl_usd(&self) -> f64 {
+        let now = Instant::now();
+        let ledger = self.ledger.lock().unwrap();
+        ledger.entries.iter().filter(|(at, _)| now.duration_since(*at) < self.limits.window).map(|(_, pnl)| pnl).sum()
+    }
+
+    fn update(&self, ledger: &mut DrawdownLedger, now: Instant) -> DrawdownState {
+        while ledger.entries.front().is_some_and(|(at, _)| now.duration_since(*at) >= self.limits.window) {
+            ledger.entries.pop_front();
+        }
+        let loss = -ledger.entries.iter().map(|(_, pnl)| pnl).sum::<f64>();
+        let streak = ledger.entries.iter().rev().take_while(|(_, pnl)| *pnl < 0.0).count();
+
+        let state = match (self.limits.max_drawdown_usd, self.limits.max_losing_streak) {
+            (Some(limit), _) if loss >= limit => {
+                DrawdownState::Paused(format!("drawdown ${:.2} reached limit ${:.2}", loss, limit))
+            }
+            (_, Some(limit)) if streak >= limit => DrawdownState::Paused(format!("{} losing liquidations in a row", streak)),
+            (Some(limit), _) if loss >= limit * self.limits.reduce_at => DrawdownState::Reduced(self.limits.reduced_size),
+            _ => DrawdownState::Normal,
+        };
+        if state != ledger.state {
+            match &state {
+                DrawdownState::Paused(why) => warn!("[ALERT] Pausing execution: {}", why),
+                DrawdownState::Reduced(size) => {
+                    warn!("[ALERT] Drawdown ${:.2}, repaying {:.0}% of the usual size", loss, size * 100.0)
+                }
+                DrawdownState::Normal => info!("Drawdown ${:.2} back under limits, full size resumed", loss.max(0.0)),
+            }
+            ledger.state = state.clone();
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_apply_per_protocol_asset_and_window() {
+        let usdc = Address::from_low_u64_be(1);
+        let dai = Address::from_low_u64_be(2);
+        let limiter = ExposureLimiter::new(ExposureLimits {
+            window: Duration::from_secs(60),
+            default_protocol_usd: Some(10_000.0),
+            asset_usd: HashMap::from([(usdc, 6_000.0)]),
+            ..Default::default()
+        });
+        let start = Instant::now();
+
+        limiter.reserve_at(start, "simple-lending", usdc, 5_000.0).unwrap();
+        assert!(limiter.reserve_at(start, "simple-lending", usdc, 2_000.0).is_err());
+        let dai_id = limiter.reserve_at(start, "simple-lending", dai, 5_000.0).unwrap();
+        assert!(limiter.reserve_at(start, "simple-lending", dai, 1.0).is_err());
+        assert!(limiter.reserve_at(start, "other", dai, 1_000.0).is_ok());
+
+        limiter.release(dai_id);
+        assert!(limiter.reserve_at(start, "simple-lending", dai, 1_000.0).is_ok());
+
+        // Once the window has passed, earlier repayments no longer count
+        let later = start + Duration::from_secs(61);
+        assert!(limiter.reserve_at(later, "simple-lending", usdc, 6_000.0).is_ok());
+    }
+
+    #[test]
+    fn test_drawdown_reduces_then_pauses_until_losses_age_out() {
+        let guard = DrawdownGuard::new(DrawdownLimits {
+            window: Duration::from_secs(60),
+            max_drawdown_usd: Some(100.0),
+            max_losing_streak: Some(4),
+            ..Default::default()
+        });
+        let start = Instant::now();
+
+        assert_eq!(guard.record_at(start, 30.0), DrawdownState::Normal);
+        assert_eq!(guard.record_at(start, -90.0), DrawdownState::Reduced(0.5));
+        assert!(matches!(guard.record_at(start, -45.0), DrawdownState::Paused(_)));
+        assert_eq!(guard.state_at(start + Duration::from_secs(61)), DrawdownState::Normal);
+
+        // Small reverted attempts add up to a streak long before the drawdown limit
+        let later = start + Duration::from_secs(120);
+        for _ in 0..3 {
+            guard.record_at(later, -2.0);
+        }
+        assert!(matches!(guard.record_at(later, -2.0), DrawdownState::Paused(why) if why.contains("4 losing")));
+        assert_eq!(guard.record_at(later, 5.0), DrawdownState::Normal);
+    }
+}
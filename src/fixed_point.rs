@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use ethers::types::{U256, U512};
+
+/// 1.0 with 18 decimals (token amounts, USD values)
+pub const WAD: U256 = U256([1_000_000_000_000_000_000, 0, 0, 0]);
+/// 1.0 with 27 decimals (Aave indexes and rates)
+pub const RAY: U256 = U256([11_515_845_246_265_065_472, 54_210_108, 0, 0]);
+
+const WAD_RAY_RATIO: U256 = U256([1_000_000_000, 0, 0, 0]);
+
+/// `a * b / denominator`, rounded down
+///
+/// The product is taken in 512 bits, so only a result that does not fit in
+/// U256 fails.
+pub fn mul_div(a: U256, b: U256, denominator: U256) -> Result<U256> {
+    anyhow::ensure!(!denominator.is_zero(), "Division by zero in {} * {} / 0", a, b);
+    let result = a.full_mul(b) / U512::from(denominator);
+    U256::try_from(result).map_err(|_| anyhow::anyhow!("{} * {} / {} overflows U256", a, b, denominator))
+}
+
+/// `a * b / denominator`, rounded up
+pub fn mul_div_up(a: U256, b: U256, denominator: U256) -> Result<U256> {
+    anyhow::ensure!(!denominator.is_zero(), "Division by zero in {} * {} / 0", a, b);
+    let denominator = U512::from(denominator);
+    let result = (a.full_mul(b) + denominator - U512::one()) / denominator;
+    U256::try_from(result).map_err(|_| anyhow::anyhow!("{} * {} / {} overflows U256", a, b, denominator))
+}
+
+pub fn wad_mul(a: U256, b: U256) -> Result<U256> {
+    mul_div(a, b, WAD)
+}
+
+pub fn wad_div(a: U256, b: U256) -> Result<U256> {
+    mul_div(a, WAD, b)
+}
+
+pub fn ray_mul(a: U256, b: U256) -> Result<U256> {
+    mul_div(a, b, RAY)
+}
+
+pub fn ray_div(a: U256, b: U256) -> Result<U256> {
+    mul_div(a, RAY, b)
+}
+
+pub fn wad_to_ray(a: U256) -> Result<U256> {
+    a.checked_mul(WAD_RAY_RATIO).with_context(|| format!("{} overflows as a ray", a))
+}
+
+/// Rounded down
+pub fn ray_to_wad(a: U256) -> U256 {
+    a / WAD_RAY_RATIO
+}
+
+/// Closest f64 to `value`, however large
+fn u256_to_f64(value: U256) -> f64 {
+    value.0.iter().rev().fold(0.0, |acc, limb| acc * 18_446_744_073_709_551_616.0 + *limb as f64)
+}
+
+/// Whole number `value` as U256, saturating
+fn f64_to_u256(value: f64) -> U256 {
+    if value < u128::MAX as f64 {
+        return U256::from(value as u128);
+    }
+    if value >= 2f64.powi(256) {
+        return U256::MAX;
+    }
+    // Exact: an f64 this large is its 53-bit mantissa shifted left
+    let shift = value.log2().floor() as i32 - 52;
+    U256::from((value / 2f64.powi(shift)) as u128) << shift as usize
+}
+
+/// `amount` as a number of `unit`s (e.g. `WAD` for an 18-decimal amount)
+///
+/// For reporting and scoring only: f64 keeps about 16 significant digits.
+pub fn to_f64(amount: U256, unit: U256) -> f64 {
+    let unit = unit.max(U256::one());
+    u256_to_f64(amount / unit) + u256_to_f64(amount % unit) / u256_to_f64(unit)
+}
+
+/// 18-decimal `amount` as a plain number
+pub fn wad_to_f64(amount: U256) -> f64 {
+    to_f64(amount, WAD)
+}
+
+/// `value` in units of `unit`, rounded; negative or NaN values are 0 and
+/// values too large for U256 saturate
+pub fn from_f64(value: f64, unit: U256) -> U256 {
+    if value.is_nan() || value <= 0.0 {
+        return U256::zero();
+    }
+    let whole = f64_to_u256(value.trunc()).saturating_mul(unit);
+    let fraction = f64_to_u256((value.fract() * u256_to_f64(unit)).round());
+    whole.saturating_add(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wad_ray_operations() {
+        assert_eq!(RAY, U256::exp10(27));
+        let one_and_half = WAD * 3 / 2;
+        assert_eq!(wad_mul(one_and_half, one_and_half).unwrap(), WAD * 9 / 4);
+        assert_eq!(wad_div(WAD, WAD * 3).unwrap(), U256::from(333_333_333_333_333_333u64));
+        assert_eq!(mul_div_up(WAD, WAD, WAD * 3).unwrap(), U256::from(333_333_333_333_333_334u64));
+        assert_eq!(mul_div_up(WAD, WAD * 3, WAD).unwrap(), WAD * 3);
+        assert_eq!(ray_mul(wad_to_ray(one_and_half).unwrap(), RAY * 2).unwrap(), RAY * 3);
+        assert_eq!(ray_to_wad(ray_div(RAY, RAY * 4).unwrap()), WAD / 4);
+
+        assert_eq!(wad_to_f64(WAD * 5 / 4), 1.25);
+        assert_eq!(from_f64(2000.5, U256::exp10(8)), U256::from(200_050_000_000u64));
+        assert_eq!(from_f64(-1.0, WAD), U256::zero());
+    }
+
+    #[test]
+    fn test_overflow() {
+        // The intermediate product exceeds 256 bits but the result fits
+        let big = U256::MAX / 3;
+        assert_eq!(mul_div(big, RAY, RAY).unwrap(), big);
+        assert!(wad_mul(U256::MAX, WAD * 2).is_err());
+        assert!(wad_div(WAD, U256::zero()).is_err());
+        assert!(wad_to_ray(U256::MAX / 2).is_err());
+
+        // Amounts past u128 convert instead of panicking
+        let huge = U256::from(u128::MAX) * WAD;
+        assert!((wad_to_f64(huge) / u128::MAX as f64 - 1.0).abs() < 1e-12);
+        assert!(wad_to_f64(U256::MAX).is_finite());
+        assert_eq!(from_f64(f64::MAX, WAD), U256::MAX);
+    }
+
+    proptest::proptest! {
+        // Converting to f64 and back only loses what 53 bits of mantissa can't hold,
+        // whatever the unit
+        #[test]
+        fn prop_f64_round_trip(limbs in proptest::array::uniform4(proptest::num::u64::ANY), decimals in 0usize..=70) {
+            let (amount, unit) = (U256(limbs), U256::exp10(decimals));
+            let back = from_f64(to_f64(amount, unit), unit);
+            let error = if back > amount { back - amount } else { amount - back };
+            proptest::prop_assert!(u256_to_f64(error) <= u256_to_f64(amount.max(unit)) * 2f64.powi(-51));
+        }
+    }
+}
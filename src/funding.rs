@@ -0,0 +1,324 @@
+use anyhow::{Context, Result};
+use ethers::{
+    contract::abigen,
+    types::{Address, U256},
+};
+use futures::future::join_all;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::blockchain::{BlockchainClient, ERC20};
+use crate::fixed_point::from_f64;
+use crate::tokens::TokenMetadata;
+use crate::valuation::{QuoteExactOutputSingleParams, QuoterV2};
+
+abigen!(
+    BalancerVault,
+    r#"[
+        function getProtocolFeesCollector() external view returns (address)
+    ]"#
+);
+
+abigen!(
+    BalancerFeesCollector,
+    r#"[
+        function getFlashLoanFeePercentage() external view returns (uint256)
+    ]"#
+);
+
+abigen!(
+    UniswapV3FlashPool,
+    r#"[
+        function token0() external view returns (address)
+        function token1() external view returns (address)
+        function fee() external view returns (uint24)
+    ]"#
+);
+
+/// Gas a Balancer flash loan adds around the liquidation (callback, transfer, repayment)
+const BALANCER_FLASH_GAS: u64 = 80_000;
+/// Gas a Uniswap V3 `flash` adds; higher than Balancer for the pool's balance checks
+const UNISWAP_V3_FLASH_GAS: u64 = 95_000;
+/// Gas of the separate Uniswap V3 swap that buys a missing repayment with wallet ETH
+const WALLET_SWAP_GAS: u64 = 150_000;
+/// Uniswap V3 fee tiers are in hundredths of a basis point
+const UNISWAP_FEE_SCALE: u64 = 1_000_000;
+
+/// Uniswap V3 pool a wallet buys the debt asset from with its own ETH
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapRoute {
+    /// SwapRouter02, which wraps the ETH sent with the swap
+    pub router: Address,
+    pub quoter: Address,
+    pub weth: Address,
+    pub fee: u32,
+}
+
+/// Where the debt asset repaid in a liquidation comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FundingSource {
+    /// Already held by the liquidator wallet
+    Inventory,
+    BalancerFlashLoan { vault: Address },
+    UniswapV3FlashSwap { pool: Address },
+    /// Held by the wallet once it buys the shortfall with ETH ahead of the liquidation
+    WalletSwap(SwapRoute),
+}
+
+impl FundingSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Inventory => "inventory",
+            Self::BalancerFlashLoan { .. } => "balancer",
+            Self::UniswapV3FlashSwap { .. } => "uniswap-v3",
+            Self::WalletSwap(_) => "wallet-swap",
+        }
+    }
+
+    /// Whether the repayment is borrowed inside the liquidation rather than paid by the wallet
+    pub fn is_flash(&self) -> bool {
+        matches!(self, Self::BalancerFlashLoan { .. } | Self::UniswapV3FlashSwap { .. })
+    }
+}
+
+/// What borrowing the repayment from one source costs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FundingQuote {
+    pub source: FundingSource,
+    /// Flash fee, or premium paid over the oracle price for a wallet swap, in debt asset units
+    pub fee: U256,
+    /// Gas on top of the bare liquidation
+    pub extra_gas: U256,
+}
+
+impl FundingQuote {
+    pub fn inventory() -> Self {
+        Self { source: FundingSource::Inventory, fee: U256::zero(), extra_gas: U256::zero() }
+    }
+
+    /// Fee plus extra gas in USD, the fee in `debt_token` (a USD stablecoin)
+    pub fn cost_usd(&self, debt_token: &TokenMetadata, gas_price: U256, eth_price_usd: f64) -> f64 {
+        let gas_eth = TokenMetadata::native().to_f64(self.extra_gas * gas_price);
+        debt_token.to_f64(self.fee) + gas_eth * eth_price_usd
+    }
+}
+
+/// Cheapest of `quotes` once fees and extra gas are priced
+pub fn cheapest(quotes: &[FundingQuote], debt_token: &TokenMetadata, gas_price: U256, eth_price_usd: f64) -> Option<FundingQuote> {
+    let cost = |quote: &FundingQuote| quote.cost_usd(debt_token, gas_price, eth_price_usd);
+    quotes.iter().min_by(|a, b| cost(a).total_cmp(&cost(b))).copied()
+}
+
+/// Fee Balancer charges on `amount` at `fee_percentage` (1e18 = 100%), rounded up like the vault
+pub fn balancer_fee(amount: U256, fee_percentage: U256) -> U256 {
+    (amount * fee_percentage + U256::exp10(18) - 1) / U256::exp10(18)
+}
+
+/// Fee a Uniswap V3 pool of `fee_tier` charges to flash `amount`, rounded up like the pool
+pub fn uniswap_v3_fee(amount: U256, fee_tier: u32) -> U256 {
+    let scale = U256::from(UNISWAP_FEE_SCALE);
+    (amount * fee_tier + scale - 1) / scale
+}
+
+/// What buying `amount_out` of the debt asset `debt_token` for `eth_in` costs
+/// above the oracle price (pool fee and price impact), in debt asset units
+pub fn swap_premium(eth_in: U256, amount_out: U256, debt_token: &TokenMetadata, eth_price_usd: f64) -> U256 {
+    let premium_usd = TokenMetadata::native().to_f64(eth_in) * eth_price_usd - debt_token.to_f64(amount_out);
+    from_f64(premium_usd, debt_token.unit())
+}
+
+/// Flash liquidity sources the simulator can fund liquidations from
+pub struct FundingRouter {
+    blockchain: Arc<BlockchainClient>,
+    sources: Vec<FundingSource>,
+    wallet_swap: Option<SwapRoute>,
+}
+
+impl FundingRouter {
+    pub fn new(blockchain: Arc<BlockchainClient>, sources: Vec<FundingSource>) -> Self {
+        Self { blockchain, sources, wallet_swap: None }
+    }
+
+    /// Let wallets short of the debt asset buy the difference with ETH through `route`
+    pub fn with_wallet_swap(mut self, route: SwapRoute) -> Self {
+        self.wallet_swap = Some(route);
+        self
+    }
+
+    /// Quotes from every source that can lend `amount` of `asset` right now
+    pub async fn quotes(&self, asset: Address, amount: U256) -> Vec<FundingQuote> {
+        let quotes = join_all(self.sources.iter().map(|source| self.quote(*source, asset, amount))).await;
+        self.sources
+            .iter()
+            .zip(quotes)
+            .filter_map(|(source, quote)| match quote {
+                Ok(quote) => quote,
+                Err(e) => {
+                    debug!("No {} quote for {}: {}", source.name(), amount, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Cheapest way to fund `amount` of `asset` (described by `token`)
+    ///
+    /// `held` is the best-funded wallet's balance of `asset`, if known. A wallet
+    /// holding enough pays from inventory; one that's short compares flash loans
+    /// with buying the shortfall. Inventory when nothing else can fund it.
+    pub async fn cheapest(
+        &self,
+        asset: Address,
+        token: &TokenMetadata,
+        amount: U256,
+        held: Option<U256>,
+        gas_price: U256,
+        eth_price_usd: f64,
+    ) -> FundingQuote {
+        if held.is_some_and(|held| held >= amount) {
+            return FundingQuote::inventory();
+        }
+        let mut quotes = self.quotes(asset, amount).await;
+        if let (Some(route), Some(held)) = (self.wallet_swap, held) {
+            match self.quote_wallet_swap(route, asset, token, amount - held, eth_price_usd).await {
+                Ok(quote) => quotes.push(quote),
+                Err(e) => debug!("No wallet swap quote for {}: {}", amount - held, e),
+            }
+        }
+        cheapest(&quotes, token, gas_price, eth_price_usd).unwrap_or_else(FundingQuote::inventory)
+    }
+
+    async fn quote_wallet_swap(
+        &self,
+        route: SwapRoute,
+        asset: Address,
+        token: &TokenMetadata,
+        shortfall: U256,
+        eth_price_usd: f64,
+    ) -> Result<FundingQuote> {
+        let params = QuoteExactOutputSingleParams {
+            token_in: route.weth,
+            token_out: asset,
+            amount: shortfall,
+            fee: route.fee,
+            sqrt_price_limit_x96: U256::zero(),
+        };
+        let (eth_in, ..) = QuoterV2::new(route.quoter, self.blockchain.http_provider.clone())
+            .quote_exact_output_single(params)
+            .call()
+            .await?;
+        Ok(FundingQuote {
+            source: FundingSource::WalletSwap(route),
+            fee: swap_premium(eth_in, shortfall, token, eth_price_usd),
+            extra_gas: U256::from(WALLET_SWAP_GAS),
+        })
+    }
+
+    async fn quote(&self, source: FundingSource, asset: Address, amount: U256) -> Result<Option<FundingQuote>> {
+        let provider = self.blockchain.http_provider.clone();
+        let (lender, fee, extra_gas) = match source {
+            FundingSource::Inventory => return Ok(Some(FundingQuote::inventory())),
+            FundingSource::WalletSwap(_) => return Ok(None),
+            FundingSource::BalancerFlashLoan { vault } => {
+                let collector = BalancerVault::new(vault, provider.clone()).get_protocol_fees_collector().call().await?;
+                let percentage = BalancerFeesCollector::new(collector, provider.clone())
+                    .get_flash_loan_fee_percentage()
+                    .call()
+                    .await?;
+                (vault, balancer_fee(amount, percentage), BALANCER_FLASH_GAS)
+            }
+            FundingSource::UniswapV3FlashSwap { pool } => {
+                let contract = UniswapV3FlashPool::new(pool, provider.clone());
+                let (token0, token1) = (contract.token_0().call().await?, contract.token_1().call().await?);
+                if asset != token0 && asset != token1 {
+                    return Ok(None);
+                }
+                let tier = contract.fee().call().await.context("fee()")?;
+                (pool, uniswap_v3_fee(amount, tier), UNISWAP_V3_FLASH_GAS)
+            }
+        };
+
+        // Neither lender can hand out more than it holds
+        let liquidity = ERC20::new(asset, provider).balance_of(lender).call().await?;
+        if liquidity < amount {
+            return Ok(None);
+        }
+        Ok(Some(FundingQuote { source, fee, extra_gas: U256::from(extra_gas) }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cheapest_route_counts_gas() {
+        let amount = U256::exp10(21); // $1000
+        // Balancer's fee is usually zero; 0.05% on a Uniswap pool is $0.50
+        assert_eq!(balancer_fee(amount, U256::zero()), U256::zero());
+        assert_eq!(balancer_fee(U256::from(3), U256::exp10(17)), U256::from(1));
+        assert_eq!(uniswap_v3_fee(amount, 500), U256::exp10(17) * 5);
+
+        let balancer = FundingQuote {
+            source: FundingSource::BalancerFlashLoan { vault: Address::from_low_u64_be(1) },
+            fee: U256::zero(),
+            extra_gas: U256::from(BALANCER_FLASH_GAS),
+        };
+        let uniswap = FundingQuote {
+            source: FundingSource::UniswapV3FlashSwap { pool: Address::from_low_u64_be(2) },
+            fee: uniswap_v3_fee(U256::exp10(18), 100),
+            extra_gas: U256::from(UNISWAP_V3_FLASH_GAS),
+        };
+        let gwei = U256::exp10(9);
+        let dai = TokenMetadata { symbol: "DAI".to_string(), decimals: 18 };
+
+        // At 1 gwei gas is cheap and Balancer's zero fee wins
+        assert_eq!(cheapest(&[uniswap, balancer], &dai, gwei, 2000.0), Some(balancer));
+        // ...but a $1 Balancer fee outweighs Uniswap's extra gas
+        let expensive = FundingQuote { fee: U256::exp10(18), ..balancer };
+        assert_eq!(cheapest(&[expensive, uniswap], &dai, gwei, 2000.0), Some(uniswap));
+        assert_eq!(cheapest(&[], &dai, gwei, 2000.0), None);
+
+        // Buying $1000 for 0.5015 ETH at $2000 costs a $3 premium, dearer than either flash loan
+        let eth_in = U256::exp10(15) * 501 + U256::exp10(14) * 5;
+        let premium = swap_premium(eth_in, amount, &dai, 2000.0);
+        assert!(premium > U256::exp10(18) * 2 && premium < U256::exp10(18) * 4);
+        assert_eq!(swap_premium(U256::exp10(17), amount, &dai, 2000.0), U256::zero());
+        let swap = FundingQuote {
+            source: FundingSource::WalletSwap(SwapRoute { router: Address::zero(), quoter: Address::zero(), weth: Address::zero(), fee: 500 }),
+            fee: premium,
+            extra_gas: U256::from(WALLET_SWAP_GAS),
+        };
+        assert_eq!(cheapest(&[swap, uniswap, balancer], &dai, gwei, 2000.0), Some(balancer));
+        assert!(!swap.source.is_flash() && balancer.source.is_flash());
+    }
+
+    #[test]
+    fn test_six_decimal_debt_costs() {
+        let usdc = TokenMetadata { symbol: "USDC".to_string(), decimals: 6 };
+        let amount = U256::from(1_000_000_000u64); // 1,000 USDC
+        let gwei = U256::exp10(9);
+
+        // 0.05% of 1,000 USDC is 0.5 USDC, priced at $0.50 rather than ~$0
+        let uniswap = FundingQuote {
+            source: FundingSource::UniswapV3FlashSwap { pool: Address::from_low_u64_be(2) },
+            fee: uniswap_v3_fee(amount, 500),
+            extra_gas: U256::zero(),
+        };
+        assert_eq!(uniswap.fee, U256::from(500_000u64));
+        assert_eq!(uniswap.cost_usd(&usdc, gwei, 2000.0), 0.5);
+
+        // The $3 premium on 0.5015 ETH comes back as 3 USDC in 6-decimal units
+        let eth_in = U256::exp10(15) * 501 + U256::exp10(14) * 5;
+        let premium = swap_premium(eth_in, amount, &usdc, 2000.0);
+        assert!(premium > U256::from(2_900_000u64) && premium < U256::from(3_100_000u64), "{}", premium);
+
+        // A $0.50 flash fee now loses to a zero-fee flash loan with slightly more gas
+        let balancer = FundingQuote {
+            source: FundingSource::BalancerFlashLoan { vault: Address::from_low_u64_be(1) },
+            fee: U256::zero(),
+            extra_gas: U256::from(BALANCER_FLASH_GAS),
+        };
+        assert_eq!(cheapest(&[uniswap, balancer], &usdc, gwei, 2000.0), Some(balancer));
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::Result;
+use ethers::{
+    contract::abigen,
+    types::{Address, Bytes, H160, U256},
+};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::blockchain::{BlockchainClient, RpcProvider};
+
+abigen!(
+    OpGasPriceOracle,
+    r#"[
+        function getL1Fee(bytes memory data) external view returns (uint256)
+    ]"#
+);
+
+abigen!(
+    ArbNodeInterface,
+    r#"[
+        function gasEstimateL1Component(address to, bool contractCreation, bytes calldata data) external payable returns (uint64 gasEstimateForL1, uint256 baseFee, uint256 l1BaseFeeEstimate)
+    ]"#
+);
+
+/// OP Stack `GasPriceOracle` predeploy (0x420000000000000000000000000000000000000F)
+const OP_GAS_PRICE_ORACLE: Address = H160([0x42, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f]);
+/// Arbitrum `NodeInterface` virtual contract (0x00000000000000000000000000000000000000C8)
+const ARB_NODE_INTERFACE: Address = H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xc8]);
+
+/// How a chain charges for a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GasModel {
+    /// gas used × gas price (Ethereum mainnet, Anvil)
+    #[default]
+    L1,
+    /// OP Stack: L2 execution plus an L1 data fee quoted by the GasPriceOracle predeploy
+    Optimism,
+    /// Arbitrum: `eth_estimateGas` already folds the L1 component into gas units;
+    /// NodeInterface splits it back out
+    Arbitrum,
+    /// BNB Chain: zero base fee, validators order by gas price and expect type-0 transactions
+    Bsc,
+}
+
+impl GasModel {
+    /// Model for a known chain id (anything unrecognised is treated as L1)
+    pub fn for_chain(chain_id: u64) -> Self {
+        match chain_id {
+            10 | 8453 | 11155420 | 84532 => GasModel::Optimism,
+            42161 | 42170 | 421614 => GasModel::Arbitrum,
+            56 | 97 => GasModel::Bsc,
+            _ => GasModel::L1,
+        }
+    }
+
+    /// Priority fee worth paying on this chain
+    pub fn priority_fee(&self) -> U256 {
+        match self {
+            GasModel::L1 => U256::from(2_000_000_000u64), // 2 gwei
+            GasModel::Optimism => U256::from(1_000_000u64), // 0.001 gwei; sequencer orders by fee but tips are tiny
+            GasModel::Arbitrum => U256::zero(),             // sequencer is first-come first-served
+            GasModel::Bsc => U256::zero(),                  // eth_gasPrice already meets the validators' minimum
+        }
+    }
+
+    /// Whether transactions must be sent as type 0 regardless of LEGACY_TRANSACTIONS
+    pub fn legacy_only(&self) -> bool {
+        matches!(self, GasModel::Bsc)
+    }
+}
+
+impl FromStr for GasModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "l1" | "ethereum" => Ok(GasModel::L1),
+            "optimism" | "op" => Ok(GasModel::Optimism),
+            "arbitrum" | "arb" => Ok(GasModel::Arbitrum),
+            "bsc" | "bnb" => Ok(GasModel::Bsc),
+            other => anyhow::bail!("Unknown gas model: {}", other),
+        }
+    }
+}
+
+/// Cost of one transaction split into its execution and L1 data components
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasCost {
+    pub execution_wei: U256,
+    pub l1_data_fee_wei: U256,
+}
+
+impl GasCost {
+    pub fn total_wei(&self) -> U256 {
+        self.execution_wei + self.l1_data_fee_wei
+    }
+}
+
+/// Chain-aware transaction cost estimator
+pub struct GasEstimator {
+    model: GasModel,
+    op_oracle: OpGasPriceOracle<RpcProvider>,
+    arb_node: ArbNodeInterface<RpcProvider>,
+}
+
+impl GasEstimator {
+    pub fn new(blockchain: &BlockchainClient, model: GasModel) -> Self {
+        let provider = blockchain.http_provider.clone();
+        Self {
+            model,
+            op_oracle: OpGasPriceOracle::new(OP_GAS_PRICE_ORACLE, Arc::clone(&provider)),
+            arb_node: ArbNodeInterface::new(ARB_NODE_INTERFACE, provider),
+        }
+    }
+
+    pub fn model(&self) -> GasModel {
+        self.model
+    }
+
+    /// Cost of sending `data` to `to` with `gas_limit` at `gas_price`
+    ///
+    /// If the L1 fee source can't be reached the L1 component is left at zero
+    /// (logged), so an L2 estimate never fails outright.
+    pub async fn estimate(&self, to: Address, data: Bytes, gas_limit: U256, gas_price: U256) -> GasCost {
+        match self.model {
+            GasModel::L1 | GasModel::Bsc => GasCost { execution_wei: gas_limit * gas_price, l1_data_fee_wei: U256::zero() },
+            GasModel::Optimism => {
+                let l1_data_fee_wei = match self.op_oracle.get_l1_fee(data).call().await {
+                    Ok(fee) => fee,
+                    Err(e) => {
+                        warn!("GasPriceOracle.getL1Fee failed, ignoring L1 data fee: {}", e);
+                        U256::zero()
+                    }
+                };
+                GasCost { execution_wei: gas_limit * gas_price, l1_data_fee_wei }
+            }
+            GasModel::Arbitrum => {
+                match self.arb_node.gas_estimate_l1_component(to, false, data).call().await {
+                    Ok((l1_gas, base_fee, _)) => {
+                        // l1_gas is already included in gas_limit, priced at the L2 base fee
+                        let l1_gas = U256::from(l1_gas);
+                        GasCost {
+                            execution_wei: gas_limit.saturating_sub(l1_gas) * gas_price,
+                            l1_data_fee_wei: l1_gas * base_fee,
+                        }
+                    }
+                    Err(e) => {
+                        warn!("NodeInterface.gasEstimateL1Component failed: {}", e);
+                        GasCost { execution_wei: gas_limit * gas_price, l1_data_fee_wei: U256::zero() }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_model_selection() {
+        assert_eq!(GasModel::for_chain(1), GasModel::L1);
+        assert_eq!(GasModel::for_chain(31337), GasModel::L1);
+        assert_eq!(GasModel::for_chain(10), GasModel::Optimism);
+        assert_eq!(GasModel::for_chain(8453), GasModel::Optimism);
+        assert_eq!(GasModel::for_chain(42161), GasModel::Arbitrum);
+        assert_eq!("arbitrum".parse::<GasModel>().unwrap(), GasModel::Arbitrum);
+        assert!("zksync".parse::<GasModel>().is_err());
+        assert!(GasModel::Arbitrum.priority_fee().is_zero());
+        assert_eq!(GasModel::for_chain(56), GasModel::Bsc);
+        assert!(GasModel::Bsc.legacy_only() && !GasModel::L1.legacy_only());
+    }
+}
@@ -0,0 +1,276 @@
+use anyhow::Result;
+use ethers::types::Address;
+use futures::Stream;
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::events::{BotEvent, EventKind};
+use crate::health::HealthState;
+use crate::metrics::MetricLabels;
+
+pub mod proto {
+    tonic::include_proto!("liquidio.v1");
+}
+
+use proto::liquidio_server::{Liquidio, LiquidioServer};
+
+/// Entries returned when a request's `limit` is 0, as on the REST endpoints
+const DEFAULT_LIMIT: usize = 20;
+/// Signals a stream holds while waiting for their simulation; the oldest go
+/// first, since a signal whose simulation failed never gets one
+const MAX_PENDING_SIGNALS: usize = 1_024;
+
+/// gRPC counterpart of the probe server, for systems that consume detection
+/// and simulation but route execution through their own order management
+pub struct LiquidioService {
+    state: Arc<HealthState>,
+    blockchain: Arc<BlockchainClient>,
+    labels: MetricLabels,
+}
+
+impl LiquidioService {
+    /// `labels` name the protocol and chain streamed opportunities belong to
+    pub fn new(state: Arc<HealthState>, blockchain: Arc<BlockchainClient>, labels: MetricLabels) -> Self {
+        Self { state, blockchain, labels }
+    }
+}
+
+/// Serve the `liquidio.v1.Liquidio` service on `port` until the task is dropped
+pub async fn serve(port: u16, service: LiquidioService) -> Result<()> {
+    info!("gRPC API listening on :{}", port);
+    tonic::transport::Server::builder()
+        .add_service(LiquidioServer::new(service))
+        .serve(([0, 0, 0, 0], port).into())
+        .await?;
+    Ok(())
+}
+
+fn limit(limit: u32) -> usize {
+    if limit == 0 { DEFAULT_LIMIT } else { limit as usize }
+}
+
+type OpportunityStream = Pin<Box<dyn Stream<Item = Result<proto::Opportunity, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Liquidio for LiquidioService {
+    async fn readiness(&self, _: Request<proto::ReadinessRequest>) -> Result<Response<proto::HealthReport>, Status> {
+        let report = self.state.report(&self.blockchain).await;
+        Ok(Response::new(proto::HealthReport {
+            ready: report.ready,
+            uptime_ms: report.uptime_ms,
+            rpc_connected: report.rpc_connected,
+            rpc_block: report.rpc_block,
+            ws_connected: report.ws_connected,
+            feed_lag_ms: report.feed_lag_ms,
+            last_processed_block: report.last_processed_block,
+            stale_data: report.stale_data,
+            queue_depths: report.queue_depths.into_iter().map(|(queue, depth)| (queue, depth as u64)).collect(),
+        }))
+    }
+
+    async fn metrics(&self, _: Request<proto::MetricsRequest>) -> Result<Response<proto::MetricsReply>, Status> {
+        Ok(Response::new(proto::MetricsReply { text: self.state.prometheus().render() }))
+    }
+
+    async fn deteriorating_positions(
+        &self,
+        request: Request<proto::LimitRequest>,
+    ) -> Result<Response<proto::DeterioratingPositionsReply>, Status> {
+        let history = self.state.position_history().ok_or_else(|| Status::unavailable("No position history"))?;
+        let positions = history
+            .deteriorating(limit(request.into_inner().limit))
+            .into_iter()
+            .map(|d| proto::Deterioration {
+                user: format!("{:?}", d.user),
+                first_health_factor: d.first_health_factor,
+                latest_health_factor: d.latest_health_factor,
+                samples: d.samples as u64,
+                signaled_at_ms: d.signaled_at_ms,
+            })
+            .collect();
+        Ok(Response::new(proto::DeterioratingPositionsReply { positions }))
+    }
+
+    async fn position_history(
+        &self,
+        request: Request<proto::PositionHistoryRequest>,
+    ) -> Result<Response<proto::PositionHistoryReply>, Status> {
+        let history = self.state.position_history().ok_or_else(|| Status::unavailable("No position history"))?;
+        let user: Address = request.into_inner().user.parse().map_err(|_| Status::invalid_argument("invalid address"))?;
+        let samples = history
+            .user_history(user)
+            .into_iter()
+            .map(|s| proto::HealthFactorSample {
+                timestamp_ms: s.timestamp_ms,
+                collateral: s.collateral.to_string(),
+                debt: s.debt.to_string(),
+                health_factor: s.health_factor.to_string(),
+                signaled: s.signaled,
+            })
+            .collect();
+        Ok(Response::new(proto::PositionHistoryReply { samples }))
+    }
+
+    async fn top_opportunities(&self, request: Request<proto::LimitRequest>) -> Result<Response<proto::TopOpportunitiesReply>, Status> {
+        let feed = self.state.opportunity_feed().ok_or_else(|| Status::unavailable("No opportunity feed"))?;
+        let opportunities = feed
+            .top(limit(request.into_inner().limit))
+            .into_iter()
+            .map(|o| proto::RankedOpportunity {
+                protocol: o.protocol,
+                chain: o.chain,
+                market: o.market,
+                user: format!("{:?}", o.user),
+                profitable: o.profitable,
+                expected_profit_usd: o.expected_profit_usd,
+                estimated_gas: o.estimated_gas,
+                profit_per_mgas_usd: o.profit_per_mgas_usd,
+            })
+            .collect();
+        Ok(Response::new(proto::TopOpportunitiesReply { opportunities }))
+    }
+
+    async fn protocol_opportunities(
+        &self,
+        _: Request<proto::ProtocolOpportunitiesRequest>,
+    ) -> Result<Response<proto::ProtocolOpportunitiesReply>, Status> {
+        let feed = self.state.opportunity_feed().ok_or_else(|| Status::unavailable("No opportunity feed"))?;
+        let protocols = feed
+            .protocols()
+            .into_iter()
+            .map(|p| proto::ProtocolTotals {
+                protocol: p.protocol,
+                opportunities: p.opportunities as u64,
+                profitable: p.profitable as u64,
+                expected_profit_usd: p.expected_profit_usd,
+                gas: p.gas,
+            })
+            .collect();
+        Ok(Response::new(proto::ProtocolOpportunitiesReply { protocols }))
+    }
+
+    type StreamOpportunitiesStream = OpportunityStream;
+
+    async fn stream_opportunities(
+        &self,
+        request: Request<proto::StreamOpportunitiesRequest>,
+    ) -> Result<Response<Self::StreamOpportunitiesStream>, Status> {
+        let events = self.state.events().subscribe();
+        Ok(Response::new(Box::pin(opportunities(events, self.labels.clone(), request.into_inner()))))
+    }
+}
+
+/// Opportunities simulated from now on, each joined with the signal it came from
+fn opportunities(
+    events: Receiver<BotEvent>,
+    labels: MetricLabels,
+    filter: proto::StreamOpportunitiesRequest,
+) -> impl Stream<Item = Result<proto::Opportunity, Status>> {
+    futures::stream::unfold((events, BTreeMap::new()), move |(mut events, mut pending)| {
+        let (labels, filter) = (labels.clone(), filter.clone());
+        async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("gRPC opportunity stream fell behind, {} events missed", missed);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+                match &event.kind {
+                    EventKind::SignalDetected { .. } => {
+                        pending.insert(event.opportunity, event);
+                        if pending.len() > MAX_PENDING_SIGNALS {
+                            pending.pop_first();
+                        }
+                    }
+                    EventKind::Simulation { .. } => {
+                        let Some(signal) = pending.remove(&event.opportunity) else { continue };
+                        match opportunity(&signal, &event, &labels) {
+                            Some(o) if (o.profitable || !filter.profitable_only) && o.expected_profit_usd >= filter.min_expected_profit_usd => {
+                                return Some((Ok(o), (events, pending)));
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    })
+}
+
+fn opportunity(signal: &BotEvent, simulation: &BotEvent, labels: &MetricLabels) -> Option<proto::Opportunity> {
+    let EventKind::SignalDetected { health_factor, collateral_asset, debt_asset, observed_block, .. } = &signal.kind else { return None };
+    let EventKind::Simulation { profitable, expected_profit_usd, debt_to_cover, collateral_to_seize, estimated_gas, estimated_gas_cost_usd } =
+        &simulation.kind
+    else {
+        return None;
+    };
+    let market = labels.for_market(*collateral_asset, *debt_asset);
+    Some(proto::Opportunity {
+        opportunity: signal.opportunity,
+        protocol: market.protocol,
+        chain: market.chain,
+        market: market.market,
+        user: format!("{:?}", signal.user),
+        collateral_asset: format!("{:?}", collateral_asset),
+        debt_asset: format!("{:?}", debt_asset),
+        health_factor: health_factor.to_string(),
+        observed_block: *observed_block,
+        debt_to_cover: debt_to_cover.to_string(),
+        collateral_to_seize: collateral_to_seize.to_string(),
+        profitable: *profitable,
+        expected_profit_usd: *expected_profit_usd,
+        estimated_gas: estimated_gas.low_u64(),
+        estimated_gas_cost_usd: *estimated_gas_cost_usd,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+    use ethers::types::U256;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_streams_simulated_opportunities() {
+        let bus = EventBus::new(16);
+        let filter = proto::StreamOpportunitiesRequest { profitable_only: true, min_expected_profit_usd: 0.0 };
+        let mut stream = Box::pin(opportunities(bus.subscribe(), MetricLabels::new("aave-v3", 1), filter));
+
+        let (user, weth, usdc) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2), Address::from_low_u64_be(3));
+        let simulation = |profitable| EventKind::Simulation {
+            profitable,
+            expected_profit_usd: 25.0,
+            debt_to_cover: U256::from(500),
+            collateral_to_seize: U256::from(7),
+            estimated_gas: U256::from(300_000),
+            estimated_gas_cost_usd: 4.0,
+        };
+        let signal = || EventKind::SignalDetected {
+            collateral: U256::from(10),
+            debt: U256::from(1_000),
+            health_factor: U256::from(95),
+            collateral_asset: weth,
+            debt_asset: usdc,
+            observed_block: Some(12),
+        };
+        // An unprofitable opportunity is filtered out; the next one arrives joined with its signal
+        for (id, profitable) in [(1, false), (2, true)] {
+            bus.publish(id, user, signal());
+            bus.publish(id, user, simulation(profitable));
+        }
+        let opportunity = stream.next().await.unwrap().unwrap();
+        assert_eq!((opportunity.opportunity, opportunity.protocol.as_str(), opportunity.chain.as_str()), (2, "aave-v3", "1"));
+        assert_eq!((opportunity.debt_to_cover.as_str(), opportunity.health_factor.as_str(), opportunity.observed_block), ("500", "95", Some(12)));
+        assert_eq!(opportunity.debt_asset, format!("{:?}", usdc));
+    }
+}
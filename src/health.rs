@@ -0,0 +1,328 @@
+use anyhow::Result;
+use ethers::providers::Middleware;
+use ethers::types::Address;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::dashboard::{self, DashboardSnapshot};
+use crate::events::{self, EventBus};
+use crate::history::PositionHistory;
+use crate::liquidation_detector::LiquidationDetector;
+use crate::metrics::{now_ms, LiveMetrics};
+use crate::opportunities::OpportunityFeed;
+use crate::prometheus::{self, PrometheusRegistry};
+
+/// How long a readiness probe waits on the RPC before reporting it down
+const RPC_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Entries returned by `/positions/deteriorating` and `/opportunities` without a `limit`
+const DEFAULT_DETERIORATING_LIMIT: usize = 20;
+/// Attempts whose latencies the dashboard's percentiles are taken over
+const LIVE_LATENCY_WINDOW: usize = 1_000;
+
+/// Liveness/readiness state updated by the pipeline and read by the probe server
+pub struct HealthState {
+    started_at_ms: u64,
+    last_feed_message_ms: AtomicU64,
+    last_processed_block: AtomicU64,
+    feed_started: AtomicBool,
+    stale_data: AtomicBool,
+    queue_depths: RwLock<BTreeMap<String, usize>>,
+    max_feed_lag_ms: u64,
+    history: Option<Arc<PositionHistory>>,
+    opportunities: Option<Arc<OpportunityFeed>>,
+    watchlist: Option<Arc<LiquidationDetector>>,
+    prometheus: Arc<PrometheusRegistry>,
+    live: Arc<LiveMetrics>,
+    dashboard_push_interval: Duration,
+    events: Arc<EventBus>,
+}
+
+/// JSON body returned by `/healthz` and `/readyz`
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub ready: bool,
+    pub uptime_ms: u64,
+    pub rpc_connected: bool,
+    pub rpc_block: Option<u64>,
+    pub ws_connected: bool,
+    pub feed_lag_ms: Option<u64>,
+    pub last_processed_block: Option<u64>,
+    pub stale_data: bool,
+    pub queue_depths: BTreeMap<String, usize>,
+}
+
+impl HealthState {
+    pub fn new(max_feed_lag_ms: u64) -> Self {
+        Self {
+            started_at_ms: now_ms(),
+            last_feed_message_ms: AtomicU64::new(0),
+            last_processed_block: AtomicU64::new(0),
+            feed_started: AtomicBool::new(false),
+            stale_data: AtomicBool::new(false),
+            queue_depths: RwLock::new(BTreeMap::new()),
+            max_feed_lag_ms,
+            history: None,
+            opportunities: None,
+            watchlist: None,
+            prometheus: Arc::new(PrometheusRegistry::new()),
+            live: Arc::new(LiveMetrics::new(LIVE_LATENCY_WINDOW)),
+            dashboard_push_interval: Duration::from_secs(1),
+            events: Arc::new(EventBus::new(1_024)),
+        }
+    }
+
+    /// Serve per-user health factor history under `/positions`
+    pub fn with_position_history(mut self, history: Arc<PositionHistory>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Serve opportunities from every protocol, ranked, under `/opportunities`
+    pub fn with_opportunity_feed(mut self, feed: Arc<OpportunityFeed>) -> Self {
+        self.opportunities = Some(feed);
+        self
+    }
+
+    pub fn position_history(&self) -> Option<&Arc<PositionHistory>> {
+        self.history.as_ref()
+    }
+
+    pub fn opportunity_feed(&self) -> Option<&Arc<OpportunityFeed>> {
+        self.opportunities.as_ref()
+    }
+
+    /// Show `detector`'s watchlist on the dashboard
+    pub fn with_watchlist(mut self, detector: Arc<LiquidationDetector>) -> Self {
+        self.watchlist = Some(detector);
+        self
+    }
+
+    pub fn watchlist(&self) -> Option<&Arc<LiquidationDetector>> {
+        self.watchlist.as_ref()
+    }
+
+    /// Shortest gap between two snapshots pushed to one dashboard
+    pub fn with_dashboard_push_interval(mut self, interval: Duration) -> Self {
+        self.dashboard_push_interval = interval;
+        self
+    }
+
+    pub fn dashboard_push_interval(&self) -> Duration {
+        self.dashboard_push_interval
+    }
+
+    /// Events a `/events` subscriber may fall behind by before missing some
+    pub fn with_event_buffer(mut self, capacity: usize) -> Self {
+        self.events = Arc::new(EventBus::new(capacity));
+        self
+    }
+
+    /// Pipeline events streamed on `/events`
+    pub fn events(&self) -> &Arc<EventBus> {
+        &self.events
+    }
+
+    /// Metrics served on `/metrics`
+    pub fn prometheus(&self) -> &Arc<PrometheusRegistry> {
+        &self.prometheus
+    }
+
+    /// Latencies and PnL pushed to `/dashboard/ws`
+    pub fn live_metrics(&self) -> &Arc<LiveMetrics> {
+        &self.live
+    }
+
+    /// Note that a transaction arrived from the mempool feed
+    pub fn record_feed_message(&self) {
+        self.feed_started.store(true, Ordering::Relaxed);
+        self.last_feed_message_ms.store(now_ms(), Ordering::Relaxed);
+    }
+
+    pub fn record_block(&self, block: u64) {
+        self.last_processed_block.fetch_max(block, Ordering::Relaxed);
+    }
+
+    pub fn last_processed_block(&self) -> Option<u64> {
+        let block = self.last_processed_block.load(Ordering::Relaxed);
+        (block > 0).then_some(block)
+    }
+
+    pub fn queue_depths(&self) -> BTreeMap<String, usize> {
+        self.queue_depths.read().unwrap().clone()
+    }
+
+    /// Flag set by the staleness monitor while the bot is running on lagging data
+    pub fn set_stale(&self, stale: bool) {
+        self.stale_data.store(stale, Ordering::Relaxed);
+    }
+
+    /// Current depth of `queue`, shown on `/readyz` and exported on `/metrics`
+    pub fn set_queue_depth(&self, queue: &str, depth: usize) {
+        self.queue_depths.write().unwrap().insert(queue.to_string(), depth);
+        self.prometheus.set(&prometheus::QUEUE_DEPTH, &[queue], depth as f64);
+    }
+
+    /// Milliseconds since the last feed message (None before the feed starts)
+    pub fn feed_lag_ms(&self) -> Option<u64> {
+        self.feed_started
+            .load(Ordering::Relaxed)
+            .then(|| now_ms().saturating_sub(self.last_feed_message_ms.load(Ordering::Relaxed)))
+    }
+
+    /// Probe the RPC and the WebSocket and assemble a report
+    pub async fn report(&self, blockchain: &BlockchainClient) -> HealthReport {
+        let rpc_probe = tokio::time::timeout(RPC_PROBE_TIMEOUT, blockchain.get_block_number());
+        // A configured WebSocket only counts as connected if it answers now
+        let ws_probe = async {
+            match &blockchain.ws_provider {
+                Some(ws) => matches!(tokio::time::timeout(RPC_PROBE_TIMEOUT, ws.get_block_number()).await, Ok(Ok(_))),
+                None => false,
+            }
+        };
+        let (rpc_block, ws_connected) = tokio::join!(rpc_probe, ws_probe);
+        let rpc_block = rpc_block.ok().and_then(|res| res.ok());
+        let feed_lag_ms = self.feed_lag_ms();
+
+        let feed_fresh = feed_lag_ms.map(|lag| lag <= self.max_feed_lag_ms).unwrap_or(true);
+        let stale_data = self.stale_data.load(Ordering::Relaxed);
+
+        HealthReport {
+            ready: rpc_block.is_some() && feed_fresh && !stale_data,
+            uptime_ms: now_ms().saturating_sub(self.started_at_ms),
+            rpc_connected: rpc_block.is_some(),
+            rpc_block,
+            ws_connected,
+            feed_lag_ms,
+            last_processed_block: self.last_processed_block(),
+            stale_data,
+            queue_depths: self.queue_depths(),
+        }
+    }
+}
+
+/// Serve `/healthz` (liveness), `/readyz` (readiness), `/metrics` (Prometheus) and, with a position
+/// history attached, `/positions/deteriorating[?limit=N]` and `/positions/<address>`,
+/// and with an opportunity feed `/opportunities[?limit=N]` and `/opportunities/protocols`,
+/// plus the live dashboard (`/dashboard`, its snapshot on `/dashboard/state` and pushes
+/// on the `/dashboard/ws` WebSocket) and the `/events[?types=...]` WebSocket, on `port`
+/// until the task is dropped
+pub async fn serve(port: u16, state: Arc<HealthState>, blockchain: Arc<BlockchainClient>) -> Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    info!("Health probes listening on :{}", port);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let state = state.clone();
+        let blockchain = blockchain.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_probe(stream, &state, &blockchain).await {
+                debug!("Health probe connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_probe(mut stream: TcpStream, state: &HealthState, blockchain: &BlockchainClient) -> Result<()> {
+    let mut buf = [0u8; 1024];
+    // Peek, so a WebSocket upgrade can still read its handshake
+    let n = stream.peek(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+    let target = request.split_whitespace().nth(1).unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    match path {
+        "/dashboard/ws" => return dashboard::push(stream, state).await,
+        "/events" => return events::stream(stream, &state.events, query).await,
+        _ => {}
+    }
+    let _ = stream.read(&mut buf).await?;
+
+    let limit = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("limit="))
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(DEFAULT_DETERIORATING_LIMIT);
+    if let (Some(feed), true) = (&state.opportunities, path.starts_with("/opportunities")) {
+        let body = match path {
+            "/opportunities/protocols" => serde_json::to_string(&feed.protocols())?,
+            _ => serde_json::to_string(&feed.top(limit))?,
+        };
+        return respond(&mut stream, "200 OK", path, &body).await;
+    }
+
+    let (status, body) = match (path, &state.history) {
+        // Liveness only says the process is serving; it must not depend on the RPC
+        ("/healthz", _) => ("200 OK", r#"{"status":"ok"}"#.to_string()),
+        ("/readyz", _) => {
+            let report = state.report(blockchain).await;
+            if !report.ready {
+                warn!("Readiness probe failing: {:?}", report);
+            }
+            let status = if report.ready { "200 OK" } else { "503 Service Unavailable" };
+            (status, serde_json::to_string(&report)?)
+        }
+        ("/metrics", _) => ("200 OK", state.prometheus.render()),
+        ("/dashboard", _) => ("200 OK", dashboard::PAGE.to_string()),
+        ("/dashboard/state", _) => ("200 OK", serde_json::to_string(&DashboardSnapshot::capture(state))?),
+        ("/positions/deteriorating", Some(history)) => ("200 OK", serde_json::to_string(&history.deteriorating(limit))?),
+        (path, Some(history)) if path.starts_with("/positions/") => {
+            match path["/positions/".len()..].parse::<Address>() {
+                Ok(user) => ("200 OK", serde_json::to_string(&history.user_history(user))?),
+                Err(_) => ("400 Bad Request", r#"{"error":"invalid address"}"#.to_string()),
+            }
+        }
+        _ => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+    };
+
+    respond(&mut stream, status, path, &body).await
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, path: &str, body: &str) -> Result<()> {
+    let content_type = match path {
+        "/metrics" => "text/plain; version=0.0.4",
+        "/dashboard" => "text/html; charset=utf-8",
+        _ => "application/json",
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body,
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::offline_client;
+
+    #[tokio::test]
+    async fn test_readiness_reflects_rpc_and_feed() {
+        let blockchain = offline_client().await;
+        let state = HealthState::new(5_000);
+        state.record_feed_message();
+        state.record_block(12);
+        state.record_block(7);
+        state.set_queue_depth("mempool", 3);
+
+        let report = state.report(&blockchain).await;
+
+        assert!(!report.rpc_connected);
+        assert!(!report.ws_connected);
+        assert!(!report.ready);
+        assert!(report.feed_lag_ms.unwrap() < 5_000);
+        assert_eq!(report.last_processed_block, Some(12));
+        assert_eq!(report.queue_depths["mempool"], 3);
+        assert!(state.prometheus().render().contains("liquidio_queue_depth{queue=\"mempool\"} 3\n"));
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::{Context, Result};
+use ethers::{
+    abi::{Abi, AbiEncode},
+    contract::{abigen, ContractFactory},
+    middleware::SignerMiddleware,
+    signers::LocalWallet,
+    types::{Address, Bytes, U256},
+};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::blockchain::BlockchainClient;
+use crate::funding::FundingSource;
+use crate::morpho::MorphoMarket;
+
+abigen!(
+    LiquidationHelper,
+    r#"[
+        struct MarketParams { address loanToken; address collateralToken; address oracle; address irm; uint256 lltv; }
+        function liquidate(address user, uint256 debtToCover, uint8 source, address lender, uint256 minProfit) external
+        function liquidateBatch(address[] users, uint256[] debtsToCover, uint8 source, address lender, uint256 minProfit) external
+        function liquidateMorpho(address morpho, MarketParams marketParams, address borrower, uint256 debtToCover, uint256 minProfit) external
+        function setOperator(address operator, bool allowed) external
+        function operators(address operator) external view returns (bool)
+        event Liquidated(address indexed user, uint8 source, uint256 debtRepaid, uint256 collateralSeized, uint256 fee, uint256 profit)
+        event BatchLiquidated(uint256 count, uint8 source, uint256 debtRepaid, uint256 collateralSeized, uint256 fee, uint256 profit)
+    ]"#
+);
+
+/// Where `forge build` writes the helper's ABI and bytecode
+pub const HELPER_ARTIFACT: &str = "out/LiquidationHelper.sol/LiquidationHelper.json";
+
+/// The helper's `source` id and lender for `funding`
+pub fn funding_args(funding: FundingSource) -> (u8, Address) {
+    match funding {
+        // A wallet swap tops up the wallet beforehand, so it repays like inventory
+        FundingSource::Inventory | FundingSource::WalletSwap(_) => (0, Address::zero()),
+        FundingSource::BalancerFlashLoan { vault } => (1, vault),
+        FundingSource::UniswapV3FlashSwap { pool } => (2, pool),
+    }
+}
+
+/// Encode the helper's `liquidate` call, funding `debt_to_cover` from `funding`
+pub fn encode_liquidate(user: Address, debt_to_cover: U256, funding: FundingSource, min_profit: U256) -> Bytes {
+    let (source, lender) = funding_args(funding);
+    LiquidateCall { user, debt_to_cover, source, lender, min_profit }.encode().into()
+}
+
+/// Encode the helper's `liquidateBatch` call: liquidate each `(user, debt_to_cover)`
+/// with one loan of the total from `funding`
+pub fn encode_liquidate_batch(liquidations: &[(Address, U256)], funding: FundingSource, min_profit: U256) -> Bytes {
+    let (source, lender) = funding_args(funding);
+    let (users, debts_to_cover) = liquidations.iter().copied().unzip();
+    LiquidateBatchCall { users, debts_to_cover, source, lender, min_profit }.encode().into()
+}
+
+/// Encode the helper's `liquidateMorpho` call: repay about `debt_to_cover` of
+/// `borrower`'s loan on `market`, paid for by selling the seized collateral
+/// inside Morpho's callback
+pub fn encode_liquidate_morpho(market: &MorphoMarket, borrower: Address, debt_to_cover: U256, min_profit: U256) -> Bytes {
+    let params = &market.params;
+    let market_params = MarketParams {
+        loan_token: params.loan_token,
+        collateral_token: params.collateral_token,
+        oracle: params.oracle,
+        irm: params.irm,
+        lltv: params.lltv,
+    };
+    LiquidateMorphoCall { morpho: market.morpho, market_params, borrower, debt_to_cover, min_profit }.encode().into()
+}
+
+/// ABI and creation bytecode from a `forge build` artifact
+pub fn read_artifact(artifact: &Path) -> Result<(Abi, Bytes)> {
+    let raw = std::fs::read_to_string(artifact)
+        .with_context(|| format!("Reading {} (run `forge build` first)", artifact.display()))?;
+    let json: serde_json::Value = serde_json::from_str(&raw)?;
+    let abi: Abi = serde_json::from_value(json["abi"].clone()).context("Artifact has no ABI")?;
+    let bytecode: Bytes = json["bytecode"]["object"]
+        .as_str()
+        .context("Artifact has no bytecode")?
+        .parse()
+        .context("Invalid bytecode")?;
+    Ok((abi, bytecode))
+}
+
+/// Deploy the helper compiled at `artifact` from `deployer` (its owner), selling
+/// seized collateral for the debt asset through `swap_pool`, and let `operators`
+/// send liquidations through it
+pub async fn deploy(
+    blockchain: &BlockchainClient,
+    deployer: LocalWallet,
+    artifact: &Path,
+    weth: Address,
+    swap_pool: Address,
+    operators: &[Address],
+) -> Result<Address> {
+    let (abi, bytecode) = read_artifact(artifact)?;
+    let client = Arc::new(SignerMiddleware::new(blockchain.http_provider.clone(), deployer));
+    let protocol = blockchain.lending_protocol.address();
+    let debt_asset = blockchain.token.address();
+    let contract = ContractFactory::new(abi, bytecode, client.clone())
+        .deploy((protocol, debt_asset, weth, swap_pool))?
+        .send()
+        .await?;
+    let address = contract.address();
+    info!("LiquidationHelper deployed at {:?}", address);
+
+    let helper = LiquidationHelper::new(address, client.clone());
+    for operator in operators.iter().filter(|operator| **operator != client.address()) {
+        helper.set_operator(*operator, true).send().await?.await?;
+        info!("   Operator {:?} allowed", operator);
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::AbiDecode;
+
+    #[test]
+    fn test_helper_call_routes_funding() {
+        let user = Address::from_low_u64_be(7);
+        let vault = Address::from_low_u64_be(0xba1);
+        let debt = U256::exp10(21);
+
+        let encoded = encode_liquidate(user, debt, FundingSource::BalancerFlashLoan { vault }, U256::exp10(18));
+        let decoded = LiquidateCall::decode(&encoded).unwrap();
+        assert_eq!((decoded.source, decoded.lender), (1, vault));
+        assert_eq!((decoded.user, decoded.debt_to_cover, decoded.min_profit), (user, debt, U256::exp10(18)));
+
+        assert_eq!(funding_args(FundingSource::Inventory), (0, Address::zero()));
+        let batch = [(user, debt), (Address::from_low_u64_be(8), debt / 2)];
+        let decoded = LiquidateBatchCall::decode(encode_liquidate_batch(&batch, FundingSource::BalancerFlashLoan { vault }, U256::zero())).unwrap();
+        assert_eq!(decoded.users, vec![user, batch[1].0]);
+        assert_eq!((decoded.debts_to_cover[1], decoded.lender), (debt / 2, vault));
+        let pool = Address::from_low_u64_be(0x3);
+        assert_eq!(funding_args(FundingSource::UniswapV3FlashSwap { pool }), (2, pool));
+
+        let params = crate::morpho::MarketParams {
+            loan_token: Address::from_low_u64_be(1),
+            collateral_token: Address::from_low_u64_be(2),
+            oracle: Address::from_low_u64_be(3),
+            irm: Address::from_low_u64_be(4),
+            lltv: U256::exp10(17) * 86 / 10,
+        };
+        let market = MorphoMarket { morpho: Address::from_low_u64_be(5), params };
+        let decoded = LiquidateMorphoCall::decode(encode_liquidate_morpho(&market, user, debt, U256::zero())).unwrap();
+        assert_eq!((decoded.morpho, decoded.borrower, decoded.debt_to_cover), (market.morpho, user, debt));
+        assert_eq!(decoded.market_params.lltv, market.params.lltv);
+    }
+}
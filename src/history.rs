@@ -0,0 +1,232 @@
+use anyhow::{Context, Result};
+use ethers::types::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use tracing::warn;
+
+use crate::liquidation_detector::UserPosition;
+use crate::metrics::now_ms;
+use crate::protocol::HealthFactorScale;
+
+/// One observation of a user's position
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HfSnapshot {
+    pub timestamp_ms: u64,
+    pub user: Address,
+    pub collateral: U256,
+    pub debt: U256,
+    /// As `getPosition` returns it (see `HealthFactorScale`)
+    pub health_factor: U256,
+    /// The detector emitted a liquidation signal at this point
+    #[serde(default)]
+    pub signaled: bool,
+}
+
+/// How far a position's health factor has fallen across its recorded history
+#[derive(Debug, Clone, Serialize)]
+pub struct Deterioration {
+    pub user: Address,
+    pub first_health_factor: f64,
+    pub latest_health_factor: f64,
+    pub samples: usize,
+    /// When the detector first signaled this user, if it did
+    pub signaled_at_ms: Option<u64>,
+}
+
+impl Deterioration {
+    pub fn drop(&self) -> f64 {
+        self.first_health_factor - self.latest_health_factor
+    }
+}
+
+/// Per-user health factor time series, kept in memory for the API and
+/// optionally appended to a JSONL file
+#[derive(Debug)]
+pub struct PositionHistory {
+    max_samples: usize,
+    series: RwLock<HashMap<Address, VecDeque<HfSnapshot>>>,
+    journal: Option<(PathBuf, Mutex<File>)>,
+    hf_scale: HealthFactorScale,
+}
+
+impl PositionHistory {
+    /// In-memory history keeping the latest `max_samples` snapshots per user
+    pub fn new(max_samples: usize) -> Self {
+        Self {
+            max_samples: max_samples.max(1),
+            series: RwLock::new(HashMap::new()),
+            journal: None,
+            hf_scale: HealthFactorScale::PERCENT,
+        }
+    }
+
+    /// Encoding of recorded health factors, for reporting them as plain numbers
+    pub fn with_health_factor_scale(mut self, hf_scale: HealthFactorScale) -> Self {
+        self.hf_scale = hf_scale;
+        self
+    }
+
+    /// History persisted to `path`, reloading what earlier runs wrote there
+    pub fn open(path: impl AsRef<Path>, max_samples: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let history = Self::new(max_samples);
+        if path.exists() {
+            let file = File::open(&path)?;
+            for (i, line) in BufReader::new(file).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let snapshot: HfSnapshot = serde_json::from_str(&line)
+                    .with_context(|| format!("Invalid snapshot on line {} of {}", i + 1, path.display()))?;
+                history.push(snapshot);
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open position history {}", path.display()))?;
+        Ok(Self { journal: Some((path, Mutex::new(file))), ..history })
+    }
+
+    /// Record `position` for `user`; unchanged health factors are not repeated
+    pub fn record(&self, user: Address, position: &UserPosition) {
+        let unchanged = self
+            .latest(user)
+            .is_some_and(|last| last.health_factor == position.health_factor && last.debt == position.debt);
+        if unchanged {
+            return;
+        }
+        self.append(HfSnapshot {
+            timestamp_ms: now_ms(),
+            user,
+            collateral: position.collateral,
+            debt: position.debt,
+            health_factor: position.health_factor,
+            signaled: false,
+        });
+    }
+
+    /// Mark that the detector signaled `user` at its current position
+    pub fn record_signal(&self, user: Address) {
+        if let Some(last) = self.latest(user) {
+            self.append(HfSnapshot { timestamp_ms: now_ms(), signaled: true, ..last });
+        }
+    }
+
+    pub fn latest(&self, user: Address) -> Option<HfSnapshot> {
+        self.series.read().unwrap().get(&user).and_then(|series| series.back().cloned())
+    }
+
+    /// Snapshots for `user`, oldest first
+    pub fn user_history(&self, user: Address) -> Vec<HfSnapshot> {
+        self.series
+            .read()
+            .unwrap()
+            .get(&user)
+            .map(|series| series.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Indebted users whose health factor fell the most, worst first
+    pub fn deteriorating(&self, limit: usize) -> Vec<Deterioration> {
+        let series = self.series.read().unwrap();
+        let mut worst: Vec<Deterioration> = series
+            .iter()
+            .filter_map(|(user, snapshots)| {
+                let indebted = || snapshots.iter().filter(|s| !s.debt.is_zero());
+                let first = indebted().next()?;
+                let latest = indebted().next_back()?;
+                Some(Deterioration {
+                    user: *user,
+                    first_health_factor: self.hf_scale.to_f64(first.health_factor),
+                    latest_health_factor: self.hf_scale.to_f64(latest.health_factor),
+                    samples: snapshots.len(),
+                    signaled_at_ms: snapshots.iter().find(|s| s.signaled).map(|s| s.timestamp_ms),
+                })
+            })
+            .filter(|d| d.drop() > 0.0)
+            .collect();
+
+        worst.sort_by(|a, b| b.drop().total_cmp(&a.drop()));
+        worst.truncate(limit);
+        worst
+    }
+
+    fn append(&self, snapshot: HfSnapshot) {
+        if let Some((path, file)) = &self.journal {
+            match serde_json::to_string(&snapshot) {
+                Ok(line) => {
+                    if let Err(e) = writeln!(file.lock().unwrap(), "{}", line) {
+                        warn!("Failed to write position history {}: {}", path.display(), e);
+                    }
+                }
+                Err(e) => warn!("Failed to serialize position snapshot: {}", e),
+            }
+        }
+        self.push(snapshot);
+    }
+
+    fn push(&self, snapshot: HfSnapshot) {
+        let mut series = self.series.write().unwrap();
+        let samples = series.entry(snapshot.user).or_default();
+        if samples.len() == self.max_samples {
+            samples.pop_front();
+        }
+        samples.push_back(snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(health_factor: u64) -> UserPosition {
+        UserPosition {
+            collateral: U256::exp10(18),
+            debt: U256::from(1000u64) * U256::exp10(18),
+            health_factor: U256::from(health_factor),
+            last_updated: 0,
+            updated_block: None,
+        }
+    }
+
+    #[test]
+    fn test_history_persists_and_ranks_deterioration() {
+        let path = std::env::temp_dir().join(format!("liquidio_history_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let (falling, steady) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+
+        let history = PositionHistory::open(&path, 3).unwrap();
+        for hf in [150, 150, 130, 110, 95] {
+            history.record(falling, &position(hf));
+        }
+        history.record_signal(falling);
+        history.record(steady, &position(140));
+        drop(history);
+
+        // Reloaded from disk, trimmed to the last 3 samples per user
+        let history = PositionHistory::open(&path, 3).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let series = history.user_history(falling);
+        assert_eq!(series.len(), 3);
+        assert!(series[2].signaled);
+        assert_eq!(series[1].health_factor, U256::from(95));
+
+        let worst = history.deteriorating(10);
+        assert_eq!(worst.len(), 1);
+        assert_eq!(worst[0].user, falling);
+        assert_eq!((worst[0].first_health_factor, worst[0].latest_health_factor), (1.1, 0.95));
+        assert!(worst[0].signaled_at_ms.is_some());
+    }
+}
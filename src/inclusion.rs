@@ -0,0 +1,351 @@
+use ethers::contract::parse_log;
+use ethers::types::{Address, TransactionReceipt, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::blockchain::{BlockchainClient, LiquidateFilter};
+use crate::fixed_point::wad_to_f64;
+use crate::simulator::SimulationResult;
+use crate::submission::RouteKind;
+
+/// How long a submission is watched before it counts as dropped
+const INCLUSION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const INCLUSION_POLL_ATTEMPTS: u32 = 60;
+/// How far seized collateral may fall short of the simulated amount (the
+/// simulation prices with the oracle, the contract with its own price)
+const SEIZE_TOLERANCE_BPS: u64 = 100;
+
+/// A liquidation the executor handed to the network
+#[derive(Debug, Clone)]
+pub struct SubmittedLiquidation {
+    pub tx_hash: H256,
+    pub liquidator: Address,
+    pub max_priority_fee_per_gas: U256,
+    /// Block the submission was aimed at (bundles) or sent at
+    pub target_block: Option<u64>,
+    pub route: RouteKind,
+}
+
+/// How far ahead of us the competitor that won a liquidation was
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LosingMargin {
+    /// Competitor's block minus our target block (negative: they landed earlier)
+    pub blocks: i64,
+    /// Competitor's priority fee minus ours, in gwei
+    pub priority_fee_gwei: f64,
+}
+
+/// What became of one submitted liquidation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InclusionOutcome {
+    /// Our transaction was mined and succeeded
+    Won { block: u64, position: u64, timestamp: u64 },
+    /// Another liquidator's `Liquidate` against the same user landed instead
+    Lost { competitor: Address, block: u64, position: u64, margin: LosingMargin },
+    /// Mined but reverted, with no competing liquidation found
+    Reverted { block: u64, position: u64 },
+    /// Neither our transaction nor a competitor's showed up in time
+    Dropped,
+}
+
+/// Win/loss counts over every tracked submission
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InclusionStats {
+    pub tracked: usize,
+    pub won: usize,
+    pub lost: usize,
+    pub reverted: usize,
+    pub dropped: usize,
+    /// Margin of every lost race, in the order they were recorded
+    pub losing_margins: Vec<LosingMargin>,
+}
+
+impl InclusionStats {
+    pub fn record(&mut self, outcome: &InclusionOutcome) {
+        self.tracked += 1;
+        match outcome {
+            InclusionOutcome::Won { .. } => self.won += 1,
+            InclusionOutcome::Lost { margin, .. } => {
+                self.lost += 1;
+                self.losing_margins.push(*margin);
+            }
+            InclusionOutcome::Reverted { .. } => self.reverted += 1,
+            InclusionOutcome::Dropped => self.dropped += 1,
+        }
+    }
+
+    /// Fraction of tracked submissions we won
+    pub fn win_rate(&self) -> f64 {
+        if self.tracked == 0 { 0.0 } else { self.won as f64 / self.tracked as f64 }
+    }
+
+    /// Median priority fee the winners paid above ours, in gwei
+    pub fn median_losing_fee_gap_gwei(&self) -> Option<f64> {
+        let mut gaps: Vec<f64> = self.losing_margins.iter().map(|m| m.priority_fee_gwei).collect();
+        if gaps.is_empty() {
+            return None;
+        }
+        gaps.sort_by(|a, b| a.total_cmp(b));
+        Some(gaps[gaps.len() / 2])
+    }
+
+    /// Races lost to a competitor in or before our target block
+    pub fn lost_same_block_or_earlier(&self) -> usize {
+        self.losing_margins.iter().filter(|m| m.blocks <= 0).count()
+    }
+}
+
+/// What a won liquidation actually did, read back from its receipt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settlement {
+    pub debt_repaid: U256,
+    pub collateral_seized: U256,
+    pub gas_used: U256,
+    pub effective_gas_price: U256,
+    /// Seized collateral minus debt repaid and gas paid, at the simulated price
+    pub realized_profit_usd: f64,
+    /// Repaid the simulated debt and seized at least the simulated collateral (within tolerance)
+    pub matches_simulation: bool,
+}
+
+impl Settlement {
+    /// Settlement recorded in `receipt`'s `Liquidate` event for `user`, if it has one
+    ///
+    /// A batch's gas is split evenly between the liquidations in it.
+    pub fn from_receipt(receipt: &TransactionReceipt, user: Address, simulation: &SimulationResult) -> Option<Self> {
+        let events: Vec<_> = receipt.logs.iter().filter_map(|log| parse_log::<LiquidateFilter>(log.clone()).ok()).collect();
+        let event = events.iter().find(|event| event.user == user)?;
+        let gas_used = receipt.gas_used.unwrap_or_default() / events.len();
+        let effective_gas_price = receipt.effective_gas_price.unwrap_or_default();
+
+        let price = simulation.eth_price_usd;
+        let seized_usd = simulation.collateral_tokens(event.collateral_seized) * price;
+        let repaid_usd = simulation.debt_tokens(event.debt_repaid);
+        let gas_usd = wad_to_f64(gas_used.saturating_mul(effective_gas_price)) * price;
+
+        let min_seized = simulation.collateral_to_seize.raw * (10_000 - SEIZE_TOLERANCE_BPS) / 10_000;
+        Some(Self {
+            debt_repaid: event.debt_repaid,
+            collateral_seized: event.collateral_seized,
+            gas_used,
+            effective_gas_price,
+            realized_profit_usd: seized_usd - repaid_usd - gas_usd,
+            matches_simulation: event.debt_repaid == simulation.debt_to_cover.raw && event.collateral_seized >= min_seized,
+        })
+    }
+}
+
+/// Realized results of every won liquidation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettlementStats {
+    pub verified: usize,
+    /// Settlements whose event disagreed with the simulation
+    pub mismatched: usize,
+    pub realized_profit_usd: f64,
+    /// Realized minus expected profit, summed
+    pub profit_error_usd: f64,
+}
+
+impl SettlementStats {
+    pub fn record(&mut self, settlement: &Settlement, expected_profit_usd: f64) {
+        self.verified += 1;
+        self.mismatched += !settlement.matches_simulation as usize;
+        self.realized_profit_usd += settlement.realized_profit_usd;
+        self.profit_error_usd += settlement.realized_profit_usd - expected_profit_usd;
+    }
+}
+
+/// Read back the receipt of a won liquidation and compare it with `simulation`
+pub async fn settle(blockchain: &BlockchainClient, tx_hash: H256, user: Address, simulation: &SimulationResult) -> Option<Settlement> {
+    let receipt = match blockchain.get_transaction_receipt(tx_hash).await {
+        Ok(Some(receipt)) => receipt,
+        Ok(None) => return None,
+        Err(e) => {
+            debug!("Receipt fetch for {:?} failed: {}", tx_hash, e);
+            return None;
+        }
+    };
+    let Some(settlement) = Settlement::from_receipt(&receipt, user, simulation) else {
+        warn!("Liquidation {:?} succeeded without a Liquidate event for {:?}", tx_hash, user);
+        return None;
+    };
+    if !settlement.matches_simulation {
+        warn!(
+            "Liquidation {:?} settled off simulation: repaid {} (expected {}), seized {} (expected {})",
+            tx_hash, settlement.debt_repaid, simulation.debt_to_cover.raw, settlement.collateral_seized, simulation.collateral_to_seize.raw,
+        );
+    }
+    info!(
+        "Liquidation {:?} settled: realized ${:.2} vs expected ${:.2}",
+        tx_hash, settlement.realized_profit_usd, simulation.expected_profit_usd,
+    );
+    Some(settlement)
+}
+
+/// Watch `submission` until it is mined, a competitor liquidates `user`, or we give up
+pub async fn resolve(blockchain: &BlockchainClient, user: Address, submission: &SubmittedLiquidation) -> InclusionOutcome {
+    let from_block = match submission.target_block {
+        Some(block) => block,
+        None => blockchain.get_block_number().await.unwrap_or_default(),
+    };
+    let mut ticker = tokio::time::interval(INCLUSION_POLL_INTERVAL);
+
+    for _ in 0..INCLUSION_POLL_ATTEMPTS {
+        ticker.tick().await;
+
+        match blockchain.get_transaction_receipt(submission.tx_hash).await {
+            Ok(Some(receipt)) => {
+                let block = receipt.block_number.unwrap_or_default().as_u64();
+                let position = receipt.transaction_index.as_u64();
+                if receipt.status.map(|s| s.as_u64()) == Some(1) {
+                    let timestamp = match blockchain.get_block(block).await {
+                        Ok(Some(header)) => header.timestamp.low_u64(),
+                        _ => 0,
+                    };
+                    return InclusionOutcome::Won { block, position, timestamp };
+                }
+                // A revert usually means someone liquidated the position first
+                let competitor = competing_liquidation(blockchain, user, submission, from_block).await;
+                return competitor.unwrap_or(InclusionOutcome::Reverted { block, position });
+            }
+            Ok(None) => {}
+            Err(e) => debug!("Receipt poll for {:?} failed: {}", submission.tx_hash, e),
+        }
+
+        if let Some(lost) = competing_liquidation(blockchain, user, submission, from_block).await {
+            return lost;
+        }
+    }
+
+    InclusionOutcome::Dropped
+}
+
+/// The first `Liquidate` against `user` since `from_block` sent by someone else
+async fn competing_liquidation(
+    blockchain: &BlockchainClient,
+    user: Address,
+    submission: &SubmittedLiquidation,
+    from_block: u64,
+) -> Option<InclusionOutcome> {
+    let events = match blockchain.liquidations_of(user, from_block).await {
+        Ok(events) => events,
+        Err(e) => {
+            debug!("Liquidate log query for {} failed: {}", user, e);
+            return None;
+        }
+    };
+    let (event, meta) = events.into_iter().find(|(event, _)| event.liquidator != submission.liquidator)?;
+
+    let competitor_fee = match blockchain.get_transaction(meta.transaction_hash).await {
+        Ok(Some(tx)) => tx.max_priority_fee_per_gas.or(tx.gas_price).unwrap_or_default(),
+        _ => U256::zero(),
+    };
+    let block = meta.block_number.as_u64();
+    let margin = LosingMargin {
+        blocks: block as i64 - from_block as i64,
+        priority_fee_gwei: (competitor_fee.as_u128() as f64 - submission.max_priority_fee_per_gas.as_u128() as f64) / 1e9,
+    };
+    info!(
+        "Lost liquidation of {} to {:?} in block {} ({:+} blocks, {:+.2} gwei tip)",
+        user, event.liquidator, block, margin.blocks, margin.priority_fee_gwei,
+    );
+
+    Some(InclusionOutcome::Lost {
+        competitor: event.liquidator,
+        block,
+        position: meta.transaction_index.as_u64(),
+        margin,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::funding::FundingQuote;
+    use crate::units::TokenAmount;
+
+    #[test]
+    fn test_win_rate_and_losing_margins() {
+        let mut stats = InclusionStats::default();
+        let lost = |blocks, priority_fee_gwei| InclusionOutcome::Lost {
+            competitor: Address::from_low_u64_be(9),
+            block: 100,
+            position: 0,
+            margin: LosingMargin { blocks, priority_fee_gwei },
+        };
+
+        stats.record(&InclusionOutcome::Won { block: 100, position: 3, timestamp: 1_700_000_000 });
+        stats.record(&lost(0, 2.5));
+        stats.record(&lost(1, 0.5));
+        stats.record(&lost(-1, 8.0));
+        stats.record(&InclusionOutcome::Dropped);
+
+        assert_eq!((stats.tracked, stats.won, stats.lost, stats.dropped), (5, 1, 3, 1));
+        assert_eq!(stats.win_rate(), 0.2);
+        assert_eq!(stats.median_losing_fee_gap_gwei(), Some(2.5));
+        assert_eq!(stats.lost_same_block_or_earlier(), 2);
+    }
+
+    #[test]
+    fn test_settlement_from_receipt() {
+        use ethers::abi::AbiEncode;
+        use ethers::contract::EthEvent;
+        use ethers::types::Log;
+
+        let eth = U256::exp10(18);
+        let simulation = SimulationResult {
+            profitable: true,
+            expected_profit_usd: 180.0,
+            worst_case_profit_usd: 180.0,
+            collateral_to_seize: TokenAmount::wad(eth * 11 / 10),
+            debt_to_cover: TokenAmount::wad(eth * 2000),
+            estimated_gas: U256::from(200_000),
+            estimated_gas_cost_usd: 20.0,
+            l1_data_fee_usd: 0.0,
+            liquidation_bonus_bps: 1_000,
+            close_factor_bps: 10_000,
+            eth_price_usd: 2000.0,
+            funding: FundingQuote::inventory(),
+            funding_cost_usd: 0.0,
+            slippage_usd: 0.0,
+        };
+        let receipt = |seized: U256| TransactionReceipt {
+            gas_used: Some(U256::from(100_000)),
+            effective_gas_price: Some(U256::exp10(11)), // 100 gwei
+            logs: vec![Log {
+                topics: vec![
+                    LiquidateFilter::signature(),
+                    H256::from(Address::from_low_u64_be(1)),
+                    H256::from(Address::from_low_u64_be(2)),
+                ],
+                data: (eth * 2000, seized).encode().into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // 1.1 ETH at $2000 for $2000 of debt, minus 0.01 ETH of gas
+        let user = Address::from_low_u64_be(2);
+        let settlement = Settlement::from_receipt(&receipt(eth * 11 / 10), user, &simulation).unwrap();
+        assert!(settlement.matches_simulation);
+        assert!((settlement.realized_profit_usd - 180.0).abs() < 1e-6);
+
+        let short = Settlement::from_receipt(&receipt(eth), user, &simulation).unwrap();
+        assert!(!short.matches_simulation);
+
+        let mut stats = SettlementStats::default();
+        stats.record(&settlement, simulation.expected_profit_usd);
+        stats.record(&short, simulation.expected_profit_usd);
+        assert_eq!((stats.verified, stats.mismatched), (2, 1));
+        assert!((stats.profit_error_usd + 200.0).abs() < 1e-6);
+
+        assert!(Settlement::from_receipt(&TransactionReceipt::default(), user, &simulation).is_none());
+        assert!(Settlement::from_receipt(&receipt(eth), Address::from_low_u64_be(3), &simulation).is_none());
+
+        // Two liquidations in one batch each carry half its gas
+        let mut batch = receipt(eth * 11 / 10);
+        batch.logs.push(batch.logs[0].clone());
+        assert_eq!(Settlement::from_receipt(&batch, user, &simulation).unwrap().gas_used, U256::from(50_000));
+    }
+}
@@ -0,0 +1,443 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::signers::LocalWallet;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
+
+use crate::blockchain::{BlockchainClient, RpcProvider, ERC20};
+use crate::funding::SwapRoute;
+use crate::protocol::NATIVE_ASSET;
+use crate::simulator::SimulationResult;
+use crate::valuation::{QuoteExactInputSingleParams, QuoteExactOutputSingleParams, QuoterV2};
+
+abigen!(
+    SwapRouter02,
+    r#"[
+        struct ExactOutputSingleParams { address tokenIn; address tokenOut; uint24 fee; address recipient; uint256 amountOut; uint256 amountInMaximum; uint160 sqrtPriceLimitX96; }
+        function exactOutputSingle(ExactOutputSingleParams calldata params) external payable returns (uint256 amountIn)
+        struct ExactInputSingleParams { address tokenIn; address tokenOut; uint24 fee; address recipient; uint256 amountIn; uint256 amountOutMinimum; uint160 sqrtPriceLimitX96; }
+        function exactInputSingle(ExactInputSingleParams calldata params) external payable returns (uint256 amountOut)
+        function refundETH() external payable
+        function multicall(bytes[] calldata data) external payable returns (bytes[] memory results)
+    ]"#
+);
+
+/// Capital limits and rebalancing thresholds for the executor wallets
+#[derive(Debug, Clone)]
+pub struct InventoryLimits {
+    /// Largest debt repayment committed to a single liquidation, in USD
+    pub max_capital_per_opportunity_usd: f64,
+    /// ETH kept back for gas; never swept or counted as free capital
+    pub gas_reserve: U256,
+    /// ETH above this (after the gas reserve) is swept to `cold_address`
+    pub max_eth_balance: Option<U256>,
+    /// Debt-asset balance above this is swept to `cold_address`
+    pub max_debt_asset_balance: Option<U256>,
+    /// Seized collateral above this is converted back into the debt asset
+    pub collateral_convert_threshold: Option<U256>,
+    pub cold_address: Option<Address>,
+    /// ETH sent above the quote when buying a missing repayment, and debt asset
+    /// accepted below the quote when converting collateral, in basis points
+    pub top_up_slippage_bps: u32,
+}
+
+impl Default for InventoryLimits {
+    fn default() -> Self {
+        Self {
+            max_capital_per_opportunity_usd: 100_000.0,
+            gas_reserve: U256::exp10(17) / 2, // 0.05 ETH
+            max_eth_balance: None,
+            max_debt_asset_balance: None,
+            collateral_convert_threshold: None,
+            cold_address: None,
+            top_up_slippage_bps: 50,
+        }
+    }
+}
+
+/// Balances held by one executor wallet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalletInventory {
+    pub eth: U256,
+    pub debt_asset: U256,
+    /// Collateral seized by liquidations and not yet converted (part of `eth` here,
+    /// since SimpleLendingProtocol pays out native ETH)
+    pub seized_collateral: U256,
+}
+
+/// Rebalancing step proposed by `InventoryManager::plan_rebalance`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryAction {
+    /// Move `amount` of `asset` from `wallet` to the cold address
+    Sweep { wallet: Address, asset: Address, amount: U256, to: Address },
+    /// Swap `amount` of seized collateral `asset` back into the debt asset
+    Convert { wallet: Address, asset: Address, amount: U256 },
+}
+
+/// Tracks executor wallet balances and gates liquidations on available capital
+pub struct InventoryManager {
+    blockchain: Arc<BlockchainClient>,
+    limits: InventoryLimits,
+    balances: RwLock<HashMap<Address, WalletInventory>>,
+    swap_route: Option<SwapRoute>,
+}
+
+impl InventoryManager {
+    pub fn new(blockchain: Arc<BlockchainClient>, limits: InventoryLimits) -> Self {
+        Self {
+            blockchain,
+            limits,
+            balances: RwLock::new(HashMap::new()),
+            swap_route: None,
+        }
+    }
+
+    /// Sell seized collateral back into the debt asset through `route`
+    /// (without one, conversions are only logged)
+    pub fn with_swap_route(mut self, route: SwapRoute) -> Self {
+        self.swap_route = Some(route);
+        self
+    }
+
+    pub fn limits(&self) -> &InventoryLimits {
+        &self.limits
+    }
+
+    pub fn inventory(&self, wallet: Address) -> Option<WalletInventory> {
+        self.balances.read().unwrap().get(&wallet).copied()
+    }
+
+    /// Reload `wallet`'s ETH and debt-asset balances from the chain
+    pub async fn refresh(&self, wallet: Address) -> Result<WalletInventory> {
+        let eth = self.blockchain.http_provider.get_balance(wallet, None).await?;
+        let debt_asset = self.blockchain.token.balance_of(wallet).call().await?;
+
+        let mut balances = self.balances.write().unwrap();
+        let inventory = balances.entry(wallet).or_default();
+        inventory.eth = eth;
+        inventory.debt_asset = debt_asset;
+        // Seized collateral can't exceed what the wallet still holds
+        inventory.seized_collateral = inventory.seized_collateral.min(eth);
+        Ok(*inventory)
+    }
+
+    /// Most of `asset` any known wallet holds; None if no wallet has been
+    /// loaded or `asset` isn't the tracked debt asset
+    pub fn largest_balance(&self, asset: Address) -> Option<U256> {
+        if asset != self.blockchain.token.address() {
+            return None;
+        }
+        self.balances.read().unwrap().values().map(|inventory| inventory.debt_asset).max()
+    }
+
+    /// Buy whatever `signer`'s wallet lacks of `needed` debt asset with its ETH
+    /// through `route`, waiting for the swap so a liquidation after it can repay
+    pub async fn top_up(&self, signer: &LocalWallet, route: SwapRoute, needed: U256) -> Result<Option<H256>> {
+        let wallet = signer.address();
+        let inventory = self.refresh(wallet).await?;
+        let shortfall = needed.saturating_sub(inventory.debt_asset);
+        if shortfall.is_zero() {
+            return Ok(None);
+        }
+
+        let provider = self.blockchain.http_provider.clone();
+        let token = self.blockchain.token.address();
+        let (eth_in, ..) = QuoterV2::new(route.quoter, provider.clone())
+            .quote_exact_output_single(QuoteExactOutputSingleParams {
+                token_in: route.weth,
+                token_out: token,
+                amount: shortfall,
+                fee: route.fee,
+                sqrt_price_limit_x96: U256::zero(),
+            })
+            .call()
+            .await?;
+        let max_eth_in = eth_in * (10_000 + self.limits.top_up_slippage_bps) / 10_000;
+        anyhow::ensure!(
+            inventory.eth.saturating_sub(self.limits.gas_reserve) >= max_eth_in,
+            "Wallet {:?} needs {} wei above the gas reserve to buy {} of the debt asset",
+            wallet,
+            max_eth_in,
+            shortfall,
+        );
+
+        let client = Arc::new(SignerMiddleware::new(provider, signer.clone()));
+        let router = SwapRouter02::new(route.router, client);
+        let swap = router
+            .exact_output_single(ExactOutputSingleParams {
+                token_in: route.weth,
+                token_out: token,
+                fee: route.fee,
+                recipient: wallet,
+                amount_out: shortfall,
+                amount_in_maximum: max_eth_in,
+                sqrt_price_limit_x96: U256::zero(),
+            })
+            .calldata()
+            .context("exactOutputSingle calldata")?;
+        // Whatever ETH the swap didn't need comes straight back
+        let refund = router.refund_eth().calldata().context("refundETH calldata")?;
+        let receipt = router
+            .multicall(vec![swap, refund])
+            .value(max_eth_in)
+            .send()
+            .await?
+            .await?
+            .context("Top-up swap dropped from the mempool")?;
+        anyhow::ensure!(receipt.status == Some(1.into()), "Top-up swap {:?} reverted", receipt.transaction_hash);
+
+        self.refresh(wallet).await?;
+        info!("Bought {} of the debt asset for {:?} with at most {} wei: {:?}", shortfall, wallet, max_eth_in, receipt.transaction_hash);
+        Ok(Some(receipt.transaction_hash))
+    }
+
+    /// Check that `wallet` may commit the capital `simulation` needs
+    pub async fn check_opportunity(&self, wallet: Address, simulation: &SimulationResult, flash_funded: bool) -> Result<()> {
+        self.check_batch(wallet, &[simulation], flash_funded).await
+    }
+
+    /// Check that `wallet` may commit the capital of every liquidation in a batch
+    /// sent as one transaction: each within the per-opportunity limit, and their
+    /// repayments together within the wallet's holdings
+    pub async fn check_batch(&self, wallet: Address, simulations: &[&SimulationResult], flash_funded: bool) -> Result<()> {
+        for simulation in simulations {
+            let repay_usd = simulation.repay_usd();
+            anyhow::ensure!(
+                repay_usd <= self.limits.max_capital_per_opportunity_usd,
+                "Repayment ${:.2} exceeds per-opportunity limit ${:.2}",
+                repay_usd,
+                self.limits.max_capital_per_opportunity_usd,
+            );
+        }
+        let needed = simulations.iter().fold(U256::zero(), |total, simulation| total + simulation.debt_to_cover.raw);
+
+        let inventory = match self.inventory(wallet) {
+            Some(inventory) => inventory,
+            None => self.refresh(wallet).await?,
+        };
+        // A flash loan brings its own repayment; only gas comes from the wallet
+        anyhow::ensure!(
+            flash_funded || inventory.debt_asset >= needed,
+            "Wallet {:?} holds {} of the debt asset, needs {}",
+            wallet,
+            inventory.debt_asset,
+            needed,
+        );
+        anyhow::ensure!(
+            inventory.eth >= self.limits.gas_reserve,
+            "Wallet {:?} is below the gas reserve",
+            wallet,
+        );
+        Ok(())
+    }
+
+    /// Apply a submitted liquidation to the local view until the next refresh
+    pub fn record_liquidation(&self, wallet: Address, simulation: &SimulationResult, flash_funded: bool) {
+        // Flash liquidations sell the collateral in the same transaction and pay out to the helper's owner
+        if flash_funded {
+            return;
+        }
+        let mut balances = self.balances.write().unwrap();
+        let inventory = balances.entry(wallet).or_default();
+        inventory.debt_asset = inventory.debt_asset.saturating_sub(simulation.debt_to_cover.raw);
+        inventory.eth += simulation.collateral_to_seize.raw;
+        inventory.seized_collateral += simulation.collateral_to_seize.raw;
+    }
+
+    /// Sweeps and conversions needed to bring every wallet back within limits
+    pub fn plan_rebalance(&self) -> Vec<InventoryAction> {
+        let balances = self.balances.read().unwrap();
+        let mut actions = Vec::new();
+
+        for (wallet, inventory) in balances.iter() {
+            let mut free_eth = inventory.eth.saturating_sub(self.limits.gas_reserve);
+
+            if let Some(threshold) = self.limits.collateral_convert_threshold {
+                if inventory.seized_collateral > threshold {
+                    let amount = inventory.seized_collateral.min(free_eth);
+                    free_eth -= amount;
+                    actions.push(InventoryAction::Convert { wallet: *wallet, asset: NATIVE_ASSET, amount });
+                }
+            }
+
+            let Some(cold) = self.limits.cold_address else { continue };
+            if let Some(max_eth) = self.limits.max_eth_balance {
+                if free_eth > max_eth {
+                    actions.push(InventoryAction::Sweep { wallet: *wallet, asset: NATIVE_ASSET, amount: free_eth - max_eth, to: cold });
+                }
+            }
+            if let Some(max_debt) = self.limits.max_debt_asset_balance {
+                if inventory.debt_asset > max_debt {
+                    let asset = self.blockchain.token.address();
+                    actions.push(InventoryAction::Sweep { wallet: *wallet, asset, amount: inventory.debt_asset - max_debt, to: cold });
+                }
+            }
+        }
+
+        actions
+    }
+
+    /// Carry out one rebalancing step signed by `signer` (the action's wallet)
+    /// with `nonce`, which the caller reserved for it; None if nothing was sent
+    pub async fn execute(&self, action: &InventoryAction, signer: &LocalWallet, nonce: U256) -> Result<Option<H256>> {
+        let client = Arc::new(SignerMiddleware::new(self.blockchain.http_provider.clone(), signer.clone()));
+
+        match *action {
+            InventoryAction::Sweep { wallet, asset, amount, to } => {
+                let tx_hash = if asset == NATIVE_ASSET {
+                    let tx = TransactionRequest::new().to(to).value(amount).from(wallet).nonce(nonce);
+                    client.send_transaction(tx, None).await?.tx_hash()
+                } else {
+                    let token = ERC20::new(asset, client);
+                    token.transfer(to, amount).nonce(nonce).send().await?.tx_hash()
+                };
+
+                let mut balances = self.balances.write().unwrap();
+                let inventory = balances.entry(wallet).or_default();
+                if asset == NATIVE_ASSET {
+                    inventory.eth = inventory.eth.saturating_sub(amount);
+                    inventory.seized_collateral = inventory.seized_collateral.min(inventory.eth);
+                } else {
+                    inventory.debt_asset = inventory.debt_asset.saturating_sub(amount);
+                }
+                info!("Swept {} of {:?} from {:?} to {:?}: {:?}", amount, asset, wallet, to, tx_hash);
+                Ok(Some(tx_hash))
+            }
+            InventoryAction::Convert { wallet, asset, amount } => {
+                let Some(route) = self.swap_route.filter(|_| asset == NATIVE_ASSET) else {
+                    warn!("Conversion of {} of {:?} held by {:?} needs a swap route; leaving in place", amount, asset, wallet);
+                    return Ok(None);
+                };
+                let tx_hash = self.convert(client, route, wallet, amount, nonce).await?;
+                {
+                    let mut balances = self.balances.write().unwrap();
+                    let inventory = balances.entry(wallet).or_default();
+                    inventory.eth = inventory.eth.saturating_sub(amount);
+                    inventory.seized_collateral = inventory.seized_collateral.saturating_sub(amount);
+                }
+                self.refresh(wallet).await?;
+                Ok(Some(tx_hash))
+            }
+        }
+    }
+
+    /// Sell `amount` of `wallet`'s ETH for the debt asset with `exactInputSingle`,
+    /// accepting up to the slippage allowance below the quote, and wait for it to land
+    async fn convert(
+        &self,
+        client: Arc<SignerMiddleware<Arc<RpcProvider>, LocalWallet>>,
+        route: SwapRoute,
+        wallet: Address,
+        amount: U256,
+        nonce: U256,
+    ) -> Result<H256> {
+        let token = self.blockchain.token.address();
+        let (quoted_out, ..) = QuoterV2::new(route.quoter, self.blockchain.http_provider.clone())
+            .quote_exact_input_single(QuoteExactInputSingleParams {
+                token_in: route.weth,
+                token_out: token,
+                amount_in: amount,
+                fee: route.fee,
+                sqrt_price_limit_x96: U256::zero(),
+            })
+            .call()
+            .await?;
+        let min_out = quoted_out * (10_000 - self.limits.top_up_slippage_bps.min(10_000)) / 10_000;
+
+        let router = SwapRouter02::new(route.router, client);
+        let receipt = router
+            .exact_input_single(ExactInputSingleParams {
+                token_in: route.weth,
+                token_out: token,
+                fee: route.fee,
+                recipient: wallet,
+                amount_in: amount,
+                amount_out_minimum: min_out,
+                sqrt_price_limit_x96: U256::zero(),
+            })
+            .value(amount)
+            .nonce(nonce)
+            .send()
+            .await?
+            .await?
+            .context("Conversion swap dropped from the mempool")?;
+        anyhow::ensure!(receipt.status == Some(1.into()), "Conversion swap {:?} reverted", receipt.transaction_hash);
+
+        info!("Converted {} wei of seized collateral held by {:?} for at least {} of the debt asset: {:?}", amount, wallet, min_out, receipt.transaction_hash);
+        Ok(receipt.transaction_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::funding::FundingQuote;
+    use crate::units::TokenAmount;
+    use crate::testkit::offline_client;
+
+    fn simulation(debt_usd: u64, seized_eth: u64) -> SimulationResult {
+        SimulationResult {
+            profitable: true,
+            expected_profit_usd: 0.0,
+            worst_case_profit_usd: 0.0,
+            collateral_to_seize: TokenAmount::wad(U256::from(seized_eth) * U256::exp10(18)),
+            debt_to_cover: TokenAmount::wad(U256::from(debt_usd) * U256::exp10(18)),
+            estimated_gas: U256::zero(),
+            estimated_gas_cost_usd: 0.0,
+            l1_data_fee_usd: 0.0,
+            liquidation_bonus_bps: 1_000,
+            close_factor_bps: 10_000,
+            eth_price_usd: 2000.0,
+            funding: FundingQuote::inventory(),
+            funding_cost_usd: 0.0,
+            slippage_usd: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capital_limits_and_rebalance_plan() {
+        let blockchain = offline_client().await;
+        let cold = Address::from_low_u64_be(0xc01d);
+        let limits = InventoryLimits {
+            max_capital_per_opportunity_usd: 5_000.0,
+            max_eth_balance: Some(U256::exp10(18)),
+            collateral_convert_threshold: Some(U256::from(2u64) * U256::exp10(18)),
+            cold_address: Some(cold),
+            ..Default::default()
+        };
+        let manager = InventoryManager::new(blockchain, limits);
+        let wallet = Address::from_low_u64_be(1);
+
+        manager.balances.write().unwrap().insert(wallet, WalletInventory {
+            eth: U256::exp10(18),
+            debt_asset: U256::from(4_000u64) * U256::exp10(18),
+            seized_collateral: U256::zero(),
+        });
+        assert!(manager.check_opportunity(wallet, &simulation(3_000, 2), false).await.is_ok());
+        assert!(manager.check_opportunity(wallet, &simulation(4_500, 3), false).await.is_err());
+        // ...unless a flash loan covers the repayment
+        assert!(manager.check_opportunity(wallet, &simulation(4_500, 3), true).await.is_ok());
+        assert!(manager.check_opportunity(wallet, &simulation(6_000, 4), true).await.is_err());
+        // A batch needs its repayments together, not just each one, in the wallet
+        assert!(manager.check_batch(wallet, &[&simulation(2_000, 1), &simulation(1_500, 1)], false).await.is_ok());
+        assert!(manager.check_batch(wallet, &[&simulation(3_000, 2), &simulation(3_000, 2)], false).await.is_err());
+        assert!(manager.check_batch(wallet, &[&simulation(3_000, 2), &simulation(3_000, 2)], true).await.is_ok());
+
+        // 3 ETH seized on top of 1 ETH: convert the 3 ETH; the ~0.95 ETH left after the
+        // gas reserve stays under the 1 ETH sweep limit
+        manager.record_liquidation(wallet, &simulation(3_000, 3), false);
+        let actions = manager.plan_rebalance();
+        assert_eq!(actions[0], InventoryAction::Convert {
+            wallet,
+            asset: NATIVE_ASSET,
+            amount: U256::from(3u64) * U256::exp10(18),
+        });
+        assert_eq!(actions.len(), 1);
+        assert_eq!(manager.inventory(wallet).unwrap().debt_asset, U256::from(1_000u64) * U256::exp10(18));
+
+        // Without a swap route the collateral is left in place and nothing is sent
+        let signer = LocalWallet::from_bytes(H256::from_low_u64_be(1).as_bytes()).unwrap();
+        assert_eq!(manager.execute(&actions[0], &signer, U256::zero()).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,71 @@
+pub mod address_policy;
+pub mod allocation;
+pub mod audit;
+pub mod backpressure;
+pub mod batching;
+pub mod blockchain;
+pub mod calibration;
+pub mod builders;
+pub mod comparison;
+pub mod competition;
+pub mod config;
+pub mod control;
+pub mod dashboard;
+pub mod doctor;
+pub mod liquidation_detector;
+pub mod market_status;
+pub mod simulator;
+pub mod executor;
+pub mod euler;
+pub mod events;
+pub mod exposure;
+pub mod fixed_point;
+pub mod funding;
+pub mod gas;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health;
+pub mod helper;
+pub mod history;
+pub mod inclusion;
+pub mod inventory;
+pub mod mempool_capture;
+pub mod mempool_streamer;
+pub mod metrics;
+pub mod monte_carlo;
+pub mod morpho;
+pub mod nested_calls;
+pub mod opportunities;
+pub mod oracle;
+pub mod pending_state;
+pub mod performance;
+pub mod population;
+pub mod presign;
+pub mod priority;
+pub mod prometheus;
+pub mod protocol;
+pub mod rate_limit;
+pub mod read_cache;
+pub mod replay;
+pub mod rpc_batch;
+pub mod run_metadata;
+pub mod seeding;
+pub mod selectors;
+pub mod sequencer_feed;
+pub mod setup;
+pub mod simulation_pool;
+pub mod backtesting;
+pub mod staleness;
+pub mod stress;
+pub mod subgraph;
+pub mod submission;
+pub mod sweep;
+pub mod targeting;
+pub mod wallets;
+pub mod testkit;
+pub mod tokens;
+pub mod transport;
+pub mod units;
+pub mod user_operations;
+pub mod valuation;
+pub mod venus;
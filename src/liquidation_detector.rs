@@ -1,15 +1,84 @@
-use anyhow::Result;
-use ethers::types::{Address, U256, Transaction};
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use dashmap::{DashMap, DashSet};
+use ethers::abi::AbiDecode;
+use ethers::contract::{parse_log, EthCall};
+use ethers::types::{Address, Log, U256, Transaction};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
-use crate::blockchain::BlockchainClient;
+use crate::blockchain::{BlockchainClient, LendingProtocolEvents, RpcError, SetEthPriceCall};
+use crate::fixed_point::wad_to_f64;
+use crate::history::PositionHistory;
 use crate::mempool_streamer::{TransactionClassifier, TransactionType};
 use crate::metrics::LatencyMetrics;
+use crate::population::UserPopulation;
+use crate::protocol::{HealthFactorScale, NATIVE_ASSET};
+use crate::subgraph::SubgraphBorrower;
+use crate::tokens::TokenRegistry;
+use crate::units::{HealthFactor, TokenAmount};
 
-const LIQUIDATION_THRESHOLD: u64 = 100; // 100% = HF < 1.0
+const DEFAULT_WATCH_BAND: f64 = 1.05;
+/// Headroom over the liquidation threshold a price tick must leave a position's
+/// predicted health factor for its re-read to be skipped, covering interest
+/// accrued since it was read
+const SENSITIVITY_MARGIN: f64 = 0.005;
+
+pub type Result<T, E = DetectorError> = std::result::Result<T, E>;
+
+/// Why a position could not be checked or a signal may no longer be acted on
+#[derive(Debug, thiserror::Error)]
+pub enum DetectorError {
+    /// Reading the position (or the chain head) failed
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+    #[error("Signal for {user:?} expired: {age:?} old (max {max_age:?})")]
+    SignalTooOld { user: Address, age: Duration, max_age: Duration },
+    #[error("Signal for {user:?} expired: observed at block {observed}, head is {head} (max {max_blocks} behind)")]
+    SignalBehindHead { user: Address, observed: u64, head: u64, max_blocks: u64 },
+    /// The decimals of a position's assets couldn't be read
+    #[error("{0:#}")]
+    Token(anyhow::Error),
+}
+
+impl DetectorError {
+    /// The signal itself is too old; re-detecting, not retrying, is the fix
+    pub fn is_expired(&self) -> bool {
+        matches!(self, DetectorError::SignalTooOld { .. } | DetectorError::SignalBehindHead { .. })
+    }
+}
+
+/// Anything that can leave a position liquidatable, fed to the detector
+/// through `LiquidationDetector::detect`
+#[derive(Debug, Clone)]
+pub enum DetectorInput {
+    /// A mempool transaction, checked for protocol calls that move a position
+    PendingTx(Transaction),
+    /// A mined protocol event naming the position it moved
+    MinedLog(Log),
+    /// The ETH price moved: watchlisted positions are re-read, in the state
+    /// after `trigger` when the update is still pending. With the new price
+    /// known, only those the sensitivity index predicts to cross are.
+    PriceUpdate { trigger: Option<Transaction>, eth_price_usd: Option<f64> },
+    /// A new chain head, on which the watchlist is swept once
+    NewBlock(u64),
+}
+
+impl DetectorInput {
+    /// Order inputs that arrive together are handled in, lowest first: a price
+    /// move can make many positions liquidatable at once, a new head only
+    /// re-reads what a price move would
+    pub fn priority(&self) -> u8 {
+        match self {
+            DetectorInput::PriceUpdate { .. } => 0,
+            DetectorInput::PendingTx(_) => 1,
+            DetectorInput::MinedLog(_) => 2,
+            DetectorInput::NewBlock(_) => 3,
+        }
+    }
+}
 
 /// Position tracker for users in the lending protocol
 #[derive(Debug, Clone, Default)]
@@ -18,32 +87,333 @@ pub struct UserPosition {
     pub debt: U256,
     pub health_factor: U256,
     pub last_updated: u64,
+    /// Chain head when it was read, if known
+    pub updated_block: Option<u64>,
 }
 
 /// Liquidation opportunity signal
 #[derive(Debug, Clone)]
 pub struct LiquidationSignal {
     pub user: Address,
-    pub collateral: U256,
-    pub debt: U256,
-    pub health_factor: U256,
+    pub collateral: TokenAmount,
+    pub debt: TokenAmount,
+    pub health_factor: HealthFactor,
+    /// Asset seized on liquidation (`NATIVE_ASSET` for ETH)
+    pub collateral_asset: Address,
+    /// Asset repaid on liquidation
+    pub debt_asset: Address,
+    /// Pending transaction this signal reacts to; the liquidation is simulated
+    /// in the state it leaves once mined
+    pub trigger: Option<Transaction>,
+    /// Chain head when the position was read (None for synthetic positions)
+    pub observed_block: Option<u64>,
+    /// Built from a position older than the staleness limit; ranked below fresh signals
+    pub stale: bool,
     pub metrics: LatencyMetrics,
 }
 
+/// How long a signal may still be acted on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignalTtl {
+    /// Since the transaction behind the signal was received
+    pub max_age: Option<Duration>,
+    /// Blocks the chain head may advance past `observed_block`
+    pub max_blocks: Option<u64>,
+}
+
+impl SignalTtl {
+    /// Fail if `signal` is older than `max_age` or `head` is more than
+    /// `max_blocks` past the block it was observed at
+    pub fn check(&self, signal: &LiquidationSignal, head: Option<u64>) -> Result<()> {
+        let age = signal.metrics.t_received.elapsed();
+        if let Some(max_age) = self.max_age.filter(|max_age| age > *max_age) {
+            return Err(DetectorError::SignalTooOld { user: signal.user, age, max_age });
+        }
+        if let (Some(max_blocks), Some(observed), Some(head)) = (self.max_blocks, signal.observed_block, head) {
+            if head.saturating_sub(observed) > max_blocks {
+                return Err(DetectorError::SignalBehindHead { user: signal.user, observed, head, max_blocks });
+            }
+        }
+        Ok(())
+    }
+    
+    /// `check` against the current chain head, only read when a block limit applies
+    pub async fn check_now(&self, signal: &LiquidationSignal, blockchain: &BlockchainClient) -> Result<()> {
+        let head = match (self.max_blocks, signal.observed_block) {
+            (Some(_), Some(_)) => Some(blockchain.get_block_number().await?),
+            _ => None,
+        };
+        self.check(signal, head)
+    }
+}
+
+/// What to do with a liquidatable position read too many blocks ago
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StalePolicy {
+    /// Re-read it before signalling (falling back to `DownRank` if that fails)
+    Refresh,
+    /// Signal it as is, marked stale so fresher opportunities go first
+    DownRank,
+}
+
+/// Positions read more than `max_blocks` before the chain head are stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionStaleness {
+    pub max_blocks: u64,
+    pub policy: StalePolicy,
+}
+
+/// Stale positions found when checking for liquidations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaleStats {
+    pub hits: usize,
+    pub refreshed: usize,
+    pub down_ranked: usize,
+}
+
+#[derive(Debug, Default)]
+struct StaleCounters {
+    hits: AtomicUsize,
+    refreshed: AtomicUsize,
+    down_ranked: AtomicUsize,
+}
+
+/// Where the detector reads collateral/debt/health factor from
+pub enum PositionSource {
+    /// `getPosition` on the lending protocol
+    Chain,
+    /// Synthetic user population driven by the backtest streamer
+    Synthetic(Arc<UserPopulation>),
+}
+
+/// Size of the near-liquidation watchlist and how much it has changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchlistStats {
+    pub size: usize,
+    pub added: usize,
+    pub removed: usize,
+    /// Blocks the whole watchlist was re-read on
+    pub block_sweeps: usize,
+    /// Re-reads on price ticks skipped because the position's predicted
+    /// health factor stayed clear of the threshold
+    pub reads_skipped: usize,
+}
+
+impl WatchlistStats {
+    pub fn churn(&self) -> usize {
+        self.added + self.removed
+    }
+}
+
+/// How a watched position's health factor moves with its collateral price
+///
+/// A health factor is proportional to the collateral's price while the debt
+/// is a stablecoin, so one read gives the slope at every price.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceSensitivity {
+    pub collateral_asset: Address,
+    /// Collateral price (USD) when the position was read
+    pub price_usd: f64,
+    pub health_factor: f64,
+    /// dHF/dPrice, health factor per USD of collateral price
+    pub dhf_dprice: f64,
+}
+
+impl PriceSensitivity {
+    pub fn new(collateral_asset: Address, price_usd: f64, health_factor: f64) -> Self {
+        Self { collateral_asset, price_usd, health_factor, dhf_dprice: health_factor / price_usd }
+    }
+
+    /// Health factor the position would have with its collateral at `price_usd`
+    pub fn health_factor_at(&self, price_usd: f64) -> f64 {
+        self.health_factor + self.dhf_dprice * (price_usd - self.price_usd)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Watchlist {
+    users: DashSet<Address>,
+    // Watched positions read at a known ETH price
+    sensitivities: DashMap<Address, PriceSensitivity>,
+    skipped: AtomicUsize,
+    added: AtomicUsize,
+    removed: AtomicUsize,
+    // Highest block swept, so each block is swept once
+    last_sweep: AtomicU64,
+    sweeps: AtomicUsize,
+}
+
 /// Detects liquidation opportunities by monitoring user positions
 pub struct LiquidationDetector {
     blockchain: Arc<BlockchainClient>,
-    positions: Arc<RwLock<HashMap<Address, UserPosition>>>,
+    // Sharded by address so concurrent updates and price-tick re-reads of
+    // different users don't serialize on one lock
+    positions: DashMap<Address, UserPosition>,
+    source: PositionSource,
+    // Positions between the liquidation threshold and watch_band, re-read on every price tick
+    watchlist: Watchlist,
+    // f64 bits, so parameter sweeps can retune it between runs
+    watch_band: AtomicU64,
+    hf_scale: HealthFactorScale,
+    // Last synthetic ETH price seen, to spot ticks
+    last_price: Mutex<Option<f64>>,
+    // f64 bits of the on-chain ETH price after the last mined tick (0 = unknown)
+    eth_price: AtomicU64,
+    history: Option<Arc<PositionHistory>>,
+    // Latest chain head seen (0 = unknown), to age positions by
+    head: AtomicU64,
+    staleness: Option<PositionStaleness>,
+    stale: StaleCounters,
+    tokens: TokenRegistry,
 }
 
 impl LiquidationDetector {
     pub fn new(blockchain: Arc<BlockchainClient>) -> Self {
+        Self::with_source(blockchain, PositionSource::Chain)
+    }
+    
+    pub fn with_source(blockchain: Arc<BlockchainClient>, source: PositionSource) -> Self {
         Self {
+            tokens: TokenRegistry::new(&blockchain),
             blockchain,
-            positions: Arc::new(RwLock::new(HashMap::new())),
+            positions: DashMap::new(),
+            source,
+            watchlist: Watchlist::default(),
+            watch_band: AtomicU64::new(DEFAULT_WATCH_BAND.to_bits()),
+            hf_scale: HealthFactorScale::PERCENT,
+            last_price: Mutex::new(None),
+            eth_price: AtomicU64::new(0),
+            history: None,
+            head: AtomicU64::new(0),
+            staleness: None,
+            stale: StaleCounters::default(),
+        }
+    }
+    
+    /// Watch positions with a health factor below `health_factor` (e.g. 1.05)
+    pub fn with_watch_band(self, health_factor: f64) -> Self {
+        self.set_watch_band(health_factor);
+        self
+    }
+    
+    pub fn watch_band(&self) -> f64 {
+        f64::from_bits(self.watch_band.load(Ordering::Relaxed))
+    }
+    
+    /// Change the watch band for positions read from now on
+    pub fn set_watch_band(&self, health_factor: f64) {
+        self.watch_band.store(health_factor.to_bits(), Ordering::Relaxed);
+    }
+    
+    /// Encoding and liquidation threshold of the health factors positions are
+    /// read with (the synthetic population always uses `PERCENT`)
+    pub fn with_health_factor_scale(mut self, scale: HealthFactorScale) -> Self {
+        self.hf_scale = scale;
+        self
+    }
+    
+    pub fn health_factor_scale(&self) -> HealthFactorScale {
+        self.hf_scale
+    }
+    
+    /// Record every position update (and signal) in `history`
+    pub fn with_history(mut self, history: Arc<PositionHistory>) -> Self {
+        self.history = Some(history);
+        self
+    }
+    
+    /// Refresh or down-rank liquidatable positions read more than
+    /// `staleness.max_blocks` blocks ago
+    pub fn with_staleness(mut self, staleness: PositionStaleness) -> Self {
+        self.staleness = Some(staleness);
+        self
+    }
+    
+    /// Read token decimals through `tokens` (e.g. with known tokens pre-seeded)
+    pub fn with_tokens(mut self, tokens: TokenRegistry) -> Self {
+        self.tokens = tokens;
+        self
+    }
+    
+    /// Record the chain head positions are aged against
+    pub fn observe_block(&self, block: u64) {
+        self.head.fetch_max(block, Ordering::Relaxed);
+    }
+    
+    pub fn stale_stats(&self) -> StaleStats {
+        StaleStats {
+            hits: self.stale.hits.load(Ordering::Relaxed),
+            refreshed: self.stale.refreshed.load(Ordering::Relaxed),
+            down_ranked: self.stale.down_ranked.load(Ordering::Relaxed),
+        }
+    }
+    
+    /// Signals raised by one input to the detector
+    pub async fn detect(&self, input: &DetectorInput, protocol_address: Address) -> Result<Vec<LiquidationSignal>> {
+        match input {
+            DetectorInput::PendingTx(tx) => Ok(self.process_transaction(tx, protocol_address).await?.into_iter().collect()),
+            DetectorInput::MinedLog(log) => self.process_log(log, protocol_address).await,
+            DetectorInput::PriceUpdate { trigger, eth_price_usd } => {
+                // A pending update hasn't moved the state positions are read in yet
+                if trigger.as_ref().is_none_or(|tx| tx.block_number.is_some()) {
+                    if let Some(price_usd) = eth_price_usd {
+                        self.observe_eth_price(*price_usd);
+                    }
+                }
+                let trigger = trigger.as_ref().and_then(|tx| self.pending_trigger(tx));
+                Ok(self.reevaluate(trigger, *eth_price_usd).await)
+            }
+            DetectorInput::NewBlock(block) => Ok(self.sweep_block(*block).await),
         }
     }
     
+    /// Signals raised by inputs that arrived together, handled in priority
+    /// order; a user is signaled at most once
+    pub async fn detect_all(&self, inputs: &[DetectorInput], protocol_address: Address) -> Vec<LiquidationSignal> {
+        let mut inputs: Vec<_> = inputs.iter().collect();
+        inputs.sort_by_key(|input| input.priority());
+        let mut signals: Vec<LiquidationSignal> = Vec::new();
+        for input in inputs {
+            match self.detect(input, protocol_address).await {
+                Ok(found) => {
+                    for signal in found {
+                        if !signals.iter().any(|seen| seen.user == signal.user) {
+                            signals.push(signal);
+                        }
+                    }
+                }
+                Err(e) => warn!("Detection error: {}", e),
+            }
+        }
+        signals
+    }
+    
+    /// Re-read the position a mined protocol event moved
+    async fn process_log(&self, log: &Log, protocol_address: Address) -> Result<Vec<LiquidationSignal>> {
+        if log.address != protocol_address {
+            return Ok(Vec::new());
+        }
+        let user = match parse_log::<LendingProtocolEvents>(log.clone()) {
+            Ok(LendingProtocolEvents::DepositFilter(event)) => event.user,
+            Ok(LendingProtocolEvents::WithdrawFilter(event)) => event.user,
+            Ok(LendingProtocolEvents::BorrowFilter(event)) => event.user,
+            Ok(LendingProtocolEvents::RepayFilter(event)) => event.user,
+            Ok(LendingProtocolEvents::LiquidateFilter(event)) => {
+                // Someone else liquidated; only our tracking needs updating
+                let _ = self.update_position(event.user).await;
+                return Ok(Vec::new());
+            }
+            Err(_) => return Ok(Vec::new()),
+        };
+        if let Some(block) = log.block_number {
+            self.observe_block(block.as_u64());
+        }
+        self.update_position(user).await?;
+        let mut metrics = LatencyMetrics::new();
+        metrics.mark_decoded();
+        Ok(self.check_liquidation(user, &mut metrics).await?.into_iter().collect())
+    }
+    
     /// Process incoming transaction and check for liquidation opportunities
     /// This is the core O(1) detection logic
     pub async fn process_transaction(
@@ -53,54 +423,240 @@ impl LiquidationDetector {
     ) -> Result<Option<LiquidationSignal>> {
         let mut metrics = LatencyMetrics::new();
         
-        // Quick filter: only process protocol transactions
-        if !TransactionClassifier::is_protocol_transaction(tx, protocol_address) {
-            return Ok(None);
+        // Direct calls need no decoding; wrapped ones are only decoded if they name the protocol
+        let calls = TransactionClassifier::protocol_calls(tx, protocol_address);
+        for (i, call) in calls.iter().enumerate() {
+            // Classify transaction type
+            let tx_type = match TransactionClassifier::classify_calldata(&call.input) {
+                Some(t) => t,
+                None => continue,
+            };
+            
+            metrics.mark_decoded();
+            
+            // Only check positions for transactions that change collateral/debt
+            match tx_type {
+                TransactionType::Deposit | 
+                TransactionType::Withdraw | 
+                TransactionType::Borrow | 
+                TransactionType::Repay => {
+                    // A batch may touch the same position more than once; read it after the last
+                    let user = call.caller;
+                    if calls[i + 1..].iter().any(|later| later.caller == user) {
+                        continue;
+                    }
+                    
+                    // Update position from blockchain (in production, use events for efficiency)
+                    if let Err(e) = self.update_position(user).await {
+                        warn!("Failed to update position for {}: {}", user, e);
+                        continue;
+                    }
+                    
+                    // O(1) check: is this position liquidatable?
+                    let signal = self.check_liquidation(user, &mut metrics).await?
+                        .map(|signal| LiquidationSignal { trigger: self.pending_trigger(tx), ..signal });
+                    
+                    if signal.is_some() {
+                        metrics.mark_signal();
+                        return Ok(signal);
+                    }
+                }
+                TransactionType::Liquidate => {
+                    // Someone else is liquidating, update our tracking
+                    let _ = self.update_position(call.caller).await;
+                }
+            }
         }
-        
-        // Classify transaction type
-        let tx_type = match TransactionClassifier::classify_transaction(tx) {
-            Some(t) => t,
-            None => return Ok(None),
+        Ok(None)
+    }
+    
+    /// The pending trigger to simulate after; only on-chain positions can be
+    /// re-read with it applied
+    fn pending_trigger(&self, tx: &Transaction) -> Option<Transaction> {
+        match &self.source {
+            PositionSource::Chain => Some(tx.clone()),
+            PositionSource::Synthetic(_) => None,
+        }
+    }
+    
+    /// Whether `tx` moves the ETH price: a `setEthPrice` call on chain, or a
+    /// step of the synthetic population's price since the last call
+    pub fn is_price_tick(&self, tx: &Transaction, protocol_address: Address) -> bool {
+        match &self.source {
+            PositionSource::Chain => {
+                TransactionClassifier::is_protocol_transaction(tx, protocol_address)
+                    && tx.input.starts_with(&SetEthPriceCall::selector())
+            }
+            PositionSource::Synthetic(population) => {
+                let price = population.eth_price_usd();
+                self.last_price.lock().unwrap().replace(price) != Some(price)
+            }
+        }
+    }
+    
+    /// ETH price (USD) `tx` sets: a `setEthPrice` argument, or the synthetic
+    /// population's current price
+    pub fn tick_price(&self, tx: &Transaction) -> Option<f64> {
+        match &self.source {
+            PositionSource::Chain => SetEthPriceCall::decode(&tx.input).ok().map(|call| wad_to_f64(call.new_price)),
+            PositionSource::Synthetic(population) => Some(population.eth_price_usd()),
+        }
+    }
+    
+    /// Record the on-chain ETH price positions are read at from now on; a
+    /// synthetic population's price is read from the population instead
+    pub fn observe_eth_price(&self, price_usd: f64) {
+        self.eth_price.store(price_usd.to_bits(), Ordering::Relaxed);
+    }
+    
+    fn eth_price_usd(&self) -> Option<f64> {
+        match &self.source {
+            PositionSource::Chain => Some(f64::from_bits(self.eth_price.load(Ordering::Relaxed))).filter(|price| *price > 0.0),
+            PositionSource::Synthetic(population) => Some(population.eth_price_usd()),
+        }
+    }
+    
+    /// How `user`'s health factor moves with the ETH price, while watchlisted
+    /// and read at a known price
+    pub fn sensitivity(&self, user: Address) -> Option<PriceSensitivity> {
+        self.watchlist.sensitivities.get(&user).map(|entry| *entry)
+    }
+    
+    /// Re-read watchlisted positions and signal the ones now below HF 1.0
+    pub async fn reevaluate_watchlist(&self, price_tick: &Transaction) -> Vec<LiquidationSignal> {
+        self.reevaluate(self.pending_trigger(price_tick), self.tick_price(price_tick)).await
+    }
+    
+    /// Re-read the watchlist once per new `block`: prices and accrued interest
+    /// move every block, not only when a watched user transacts
+    pub async fn sweep_block(&self, block: u64) -> Vec<LiquidationSignal> {
+        self.observe_block(block);
+        if self.watchlist.last_sweep.fetch_max(block, Ordering::Relaxed) >= block {
+            return Vec::new();
+        }
+        self.watchlist.sweeps.fetch_add(1, Ordering::Relaxed);
+        self.reevaluate(None, None).await
+    }
+    
+    /// Re-read the watchlist, skipping positions the sensitivity index shows
+    /// still healthy at `eth_price_usd` when it is given
+    async fn reevaluate(&self, trigger: Option<Transaction>, eth_price_usd: Option<f64>) -> Vec<LiquidationSignal> {
+        let threshold = self.hf_scale.to_f64(self.hf_scale.threshold()) * (1.0 + SENSITIVITY_MARGIN);
+        let clear = |user: &Address| {
+            let Some(price_usd) = eth_price_usd else { return false };
+            self.watchlist
+                .sensitivities
+                .get(user)
+                .is_some_and(|s| s.collateral_asset == NATIVE_ASSET && s.health_factor_at(price_usd) >= threshold)
         };
+        let watched = self.watchlist.users.len();
+        let users: Vec<Address> = self.watchlist.users.iter().map(|user| *user).filter(|user| !clear(user)).collect();
+        self.watchlist.skipped.fetch_add(watched.saturating_sub(users.len()), Ordering::Relaxed);
+        let mut signals = Vec::new();
         
+        // Re-read concurrently so the lookups can share RPC batches
+        let mut metrics = LatencyMetrics::new();
+        let updates = join_all(users.iter().map(|user| self.update_position(*user))).await;
         metrics.mark_decoded();
+        for (user, update) in users.into_iter().zip(updates) {
+            if let Err(e) = update {
+                warn!("Failed to re-read watchlisted position {}: {}", user, e);
+                continue;
+            }
+            let mut metrics = metrics.clone();
+            if let Ok(Some(signal)) = self.check_liquidation(user, &mut metrics).await {
+                signals.push(LiquidationSignal { trigger: trigger.clone(), ..signal });
+            }
+        }
         
-        // Only check positions for transactions that change collateral/debt
-        match tx_type {
-            TransactionType::Deposit | 
-            TransactionType::Withdraw | 
-            TransactionType::Borrow | 
-            TransactionType::Repay => {
-                let user = TransactionClassifier::extract_user_address(tx);
-                
-                // Update position from blockchain (in production, use events for efficiency)
-                if let Err(e) = self.update_position(user).await {
-                    warn!("Failed to update position for {}: {}", user, e);
-                    return Ok(None);
+        signals
+    }
+    
+    pub fn watchlist_stats(&self) -> WatchlistStats {
+        WatchlistStats {
+            size: self.watchlist.users.len(),
+            added: self.watchlist.added.load(Ordering::Relaxed),
+            removed: self.watchlist.removed.load(Ordering::Relaxed),
+            block_sweeps: self.watchlist.sweeps.load(Ordering::Relaxed),
+            reads_skipped: self.watchlist.skipped.load(Ordering::Relaxed),
+        }
+    }
+    
+    fn update_watchlist(&self, user: Address, position: &UserPosition) {
+        let near = !position.debt.is_zero()
+            && position.health_factor >= self.hf_scale.threshold()
+            && position.health_factor < self.hf_scale.encode(self.watch_band());
+        
+        if near {
+            if self.watchlist.users.insert(user) {
+                self.watchlist.added.fetch_add(1, Ordering::Relaxed);
+            }
+            match self.eth_price_usd() {
+                Some(price_usd) => {
+                    let health_factor = self.hf_scale.to_f64(position.health_factor);
+                    self.watchlist.sensitivities.insert(user, PriceSensitivity::new(NATIVE_ASSET, price_usd, health_factor));
                 }
-                
-                // O(1) check: is this position liquidatable?
-                let signal = self.check_liquidation(user, &mut metrics).await?;
-                
-                if signal.is_some() {
-                    metrics.mark_signal();
+                None => {
+                    self.watchlist.sensitivities.remove(&user);
                 }
-                
-                Ok(signal)
             }
-            TransactionType::Liquidate => {
-                // Someone else is liquidating, update our tracking
-                let user = TransactionClassifier::extract_user_address(tx);
-                let _ = self.update_position(user).await;
-                Ok(None)
+        } else {
+            self.watchlist.sensitivities.remove(&user);
+            if self.watchlist.users.remove(&user).is_some() {
+                self.watchlist.removed.fetch_add(1, Ordering::Relaxed);
             }
         }
     }
     
+    /// Chain head a signal is observed at; synthetic positions have none
+    async fn observed_block(&self) -> Option<u64> {
+        let block = match &self.source {
+            PositionSource::Chain => self.blockchain.get_block_number().await.ok(),
+            PositionSource::Synthetic(_) => None,
+        };
+        if let Some(block) = block {
+            self.observe_block(block);
+        }
+        block
+    }
+    
+    /// Blocks the head has moved since `position` was read, if both are known
+    fn block_lag(&self, position: &UserPosition) -> Option<u64> {
+        let head = self.head.load(Ordering::Relaxed);
+        position.updated_block.filter(|_| head > 0).map(|block| head.saturating_sub(block))
+    }
+    
+    /// `position` of `user`, re-read if stale under the `Refresh` policy, and
+    /// whether it is still stale
+    async fn apply_staleness(&self, user: Address, position: UserPosition) -> (UserPosition, bool) {
+        let Some(staleness) = self.staleness else { return (position, false) };
+        let Some(lag) = self.block_lag(&position).filter(|lag| *lag > staleness.max_blocks) else {
+            return (position, false);
+        };
+        self.stale.hits.fetch_add(1, Ordering::Relaxed);
+        
+        if staleness.policy == StalePolicy::Refresh {
+            match self.update_position(user).await {
+                Ok(()) => {
+                    if let Some(fresh) = self.positions.get(&user).map(|p| p.clone()) {
+                        self.stale.refreshed.fetch_add(1, Ordering::Relaxed);
+                        return (fresh, false);
+                    }
+                }
+                Err(e) => warn!("Failed to refresh stale position {}: {}", user, e),
+            }
+        }
+        debug!("Position of {} is {} blocks old", user, lag);
+        self.stale.down_ranked.fetch_add(1, Ordering::Relaxed);
+        (position, true)
+    }
+    
     /// Update position data from blockchain (O(1) operation)
     async fn update_position(&self, user: Address) -> Result<()> {
-        let (collateral, debt, health_factor) = self.blockchain.get_position(user).await?;
+        let (collateral, debt, health_factor) = match &self.source {
+            PositionSource::Chain => self.blockchain.get_position(user).await?,
+            PositionSource::Synthetic(population) => population.position(user),
+        };
         
         let position = UserPosition {
             collateral,
@@ -110,10 +666,14 @@ impl LiquidationDetector {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            updated_block: Some(self.head.load(Ordering::Relaxed)).filter(|head| *head > 0),
         };
         
-        let mut positions = self.positions.write().await;
-        positions.insert(user, position);
+        self.update_watchlist(user, &position);
+        if let Some(history) = &self.history {
+            history.record(user, &position);
+        }
+        self.positions.insert(user, position);
         
         debug!("Updated position for {}: collateral={}, debt={}, HF={}", 
             user, collateral, debt, health_factor);
@@ -121,33 +681,46 @@ impl LiquidationDetector {
         Ok(())
     }
     
-    /// O(1) check if position is liquidatable
-    async fn check_liquidation(
+    /// O(1) check if the tracked position of `user` is liquidatable
+    pub async fn check_liquidation(
         &self,
         user: Address,
         metrics: &mut LatencyMetrics,
     ) -> Result<Option<LiquidationSignal>> {
-        let positions = self.positions.read().await;
-        let position = match positions.get(&user) {
+        // Clone out so the shard lock is released before logging
+        let position = match self.positions.get(&user) {
             Some(p) => p.clone(),
             None => return Ok(None),
         };
-        drop(positions);
+        if !self.is_liquidatable(&position) {
+            return Ok(None);
+        }
+        let (position, stale) = self.apply_staleness(user, position).await;
         
         // Check if health factor is below threshold
-        if position.health_factor < U256::from(LIQUIDATION_THRESHOLD) && position.debt > U256::zero() {
+        if self.is_liquidatable(&position) {
             info!("[LIQUIDATION OPPORTUNITY] Detected for {}", user);
             info!("   Collateral: {} ETH", position.collateral);
             info!("   Debt: {} USD", position.debt);
             info!("   Health Factor: {}", position.health_factor);
             
             metrics.mark_signal();
+            if let Some(history) = &self.history {
+                history.record_signal(user);
+            }
             
+            let (collateral, debt) = self.position_amounts(&position).await?;
             return Ok(Some(LiquidationSignal {
                 user,
-                collateral: position.collateral,
-                debt: position.debt,
-                health_factor: position.health_factor,
+                collateral,
+                debt,
+                health_factor: HealthFactor::new(position.health_factor, self.hf_scale),
+                collateral_asset: NATIVE_ASSET,
+                debt_asset: self.blockchain.token.address(),
+                trigger: None,
+                // A down-ranked signal is as old as the position behind it
+                observed_block: if stale { position.updated_block } else { self.observed_block().await },
+                stale,
                 metrics: metrics.clone(),
             }));
         }
@@ -155,21 +728,50 @@ impl LiquidationDetector {
         Ok(None)
     }
     
+    /// `position`'s collateral (ETH) and debt in the decimals of their tokens
+    ///
+    /// Synthetic positions are modelled in 18 decimals; on-chain debt takes the
+    /// decimals the debt token reports.
+    pub async fn position_amounts(&self, position: &UserPosition) -> Result<(TokenAmount, TokenAmount)> {
+        let collateral = TokenAmount::wad(position.collateral);
+        let debt_decimals = match &self.source {
+            PositionSource::Chain => {
+                let token = self.tokens.metadata(self.blockchain.token.address()).await.map_err(DetectorError::Token)?;
+                token.decimals
+            }
+            PositionSource::Synthetic(_) => 18,
+        };
+        Ok((collateral, TokenAmount::new(position.debt, debt_decimals)))
+    }
+    
     /// Bulk check all positions for liquidation opportunities (for backtesting)
     pub async fn scan_all_positions(&self) -> Result<Vec<LiquidationSignal>> {
+        let observed_block = self.observed_block().await;
+        // Collect first so no shard lock is held while stale positions are re-read
+        let candidates: Vec<(Address, UserPosition)> = self
+            .positions
+            .iter()
+            .filter(|entry| self.is_liquidatable(entry.value()))
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
         let mut signals = Vec::new();
-        let positions = self.positions.read().await;
-        
-        for (user, position) in positions.iter() {
-            if position.health_factor < U256::from(LIQUIDATION_THRESHOLD) && position.debt > U256::zero() {
+        for (user, position) in candidates {
+            let (position, stale) = self.apply_staleness(user, position).await;
+            if self.is_liquidatable(&position) {
                 let mut metrics = LatencyMetrics::new();
                 metrics.mark_signal();
+                let (collateral, debt) = self.position_amounts(&position).await?;
                 
                 signals.push(LiquidationSignal {
-                    user: *user,
-                    collateral: position.collateral,
-                    debt: position.debt,
-                    health_factor: position.health_factor,
+                    user,
+                    collateral,
+                    debt,
+                    health_factor: HealthFactor::new(position.health_factor, self.hf_scale),
+                    collateral_asset: NATIVE_ASSET,
+                    debt_asset: self.blockchain.token.address(),
+                    trigger: None,
+                    observed_block: if stale { position.updated_block } else { observed_block },
+                    stale,
                     metrics,
                 });
             }
@@ -178,31 +780,290 @@ impl LiquidationDetector {
         Ok(signals)
     }
     
+    fn is_liquidatable(&self, position: &UserPosition) -> bool {
+        self.hf_scale.is_liquidatable(position.health_factor, position.debt)
+    }
+    
+    /// Current positions of every watchlisted user
+    pub fn watchlisted_positions(&self) -> Vec<(Address, UserPosition)> {
+        self.watchlist
+            .users
+            .iter()
+            .filter_map(|user| self.positions.get(&*user).map(|position| (*user, position.clone())))
+            .collect()
+    }
+    
+    /// Indebted tracked positions closest to liquidation, lowest health factor first
+    pub fn riskiest_positions(&self, limit: usize) -> Vec<(Address, UserPosition)> {
+        let mut positions: Vec<(Address, UserPosition)> = self
+            .positions
+            .iter()
+            .filter(|entry| !entry.debt.is_zero())
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+        positions.sort_by_key(|(_, position)| position.health_factor);
+        positions.truncate(limit);
+        positions
+    }
+    
+    /// Track `borrowers` listed by a subgraph: those inside the watch band are
+    /// re-read now, the rest keep their indexed health factor until they next
+    /// transact. Returns how many were re-read
+    pub async fn seed_positions(&self, borrowers: &[SubgraphBorrower]) -> usize {
+        let (near, far): (Vec<&SubgraphBorrower>, Vec<_>) = borrowers
+            .iter()
+            .partition(|borrower| borrower.health_factor < self.watch_band());
+        for borrower in far {
+            self.positions.entry(borrower.user).or_insert_with(|| UserPosition {
+                health_factor: self.hf_scale.encode(borrower.health_factor),
+                ..Default::default()
+            });
+        }
+        
+        // Concurrently, like block sweeps, so the reads can share RPC batches
+        let updates = join_all(near.iter().map(|borrower| self.update_position(borrower.user))).await;
+        let mut refreshed = 0;
+        for (borrower, update) in near.into_iter().zip(updates) {
+            match update {
+                Ok(()) => refreshed += 1,
+                Err(e) => warn!("Failed to read bootstrapped position {}: {}", borrower.user, e),
+            }
+        }
+        refreshed
+    }
+    
     /// Get number of tracked positions
     pub async fn get_position_count(&self) -> usize {
-        self.positions.read().await.len()
+        self.positions.len()
     }
     
     /// Clear all tracked positions (for testing)
     pub async fn clear_positions(&self) {
-        self.positions.write().await.clear();
+        self.positions.clear();
+        self.watchlist.users.clear();
+        self.watchlist.sensitivities.clear();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testkit::offline_client;
 
     #[test]
     fn test_position_tracking() {
         let position = UserPosition {
             collateral: U256::from(10u64.pow(18)), // 1 ETH
-            debt: U256::from(1000 * 10u64.pow(18)), // 1000 USD
+            debt: U256::from(1000) * U256::exp10(18), // 1000 USD
             health_factor: U256::from(150), // 150%
             last_updated: 0,
+            updated_block: None,
+        };
+        
+        assert!(position.health_factor >= HealthFactorScale::PERCENT.threshold());
+    }
+    
+    #[tokio::test]
+    async fn test_position_amounts_take_token_decimals() {
+        use crate::tokens::TokenMetadata;
+        
+        let position = UserPosition {
+            collateral: U256::exp10(18),
+            debt: U256::from(1_000_000_000u64), // 1000 USDC
+            health_factor: U256::from(90),
+            last_updated: 0,
+            updated_block: None,
+        };
+        // The offline client's debt token is the zero address, read as native ETH
+        let token = Address::from_low_u64_be(0x05dc);
+        let blockchain = Arc::new(BlockchainClient::new("http://127.0.0.1:1", None, Address::zero(), token).await.unwrap());
+        let usdc = TokenMetadata { symbol: "USDC".to_string(), decimals: 6 };
+        let tokens = TokenRegistry::new(&blockchain).with_token(token, usdc);
+        let detector = LiquidationDetector::new(blockchain.clone()).with_tokens(tokens);
+        let (collateral, debt) = detector.position_amounts(&position).await.unwrap();
+        assert_eq!((collateral.to_tokens(), debt.to_tokens()), (1.0, 1000.0));
+        
+        // A debt token whose decimals can't be read is not taken to have 18
+        let unread = LiquidationDetector::new(blockchain);
+        assert!(matches!(unread.position_amounts(&position).await, Err(DetectorError::Token(_))));
+    }
+    
+    #[test]
+    fn test_signal_ttl() {
+        let signal = LiquidationSignal {
+            user: Address::from_low_u64_be(1),
+            collateral: TokenAmount::wad(U256::exp10(18)),
+            debt: TokenAmount::wad(U256::exp10(21)),
+            health_factor: HealthFactor::new(U256::from(90), HealthFactorScale::PERCENT),
+            collateral_asset: NATIVE_ASSET,
+            debt_asset: Address::zero(),
+            trigger: None,
+            observed_block: Some(100),
+            stale: false,
+            metrics: LatencyMetrics::new(),
         };
+        let ttl = SignalTtl { max_age: Some(Duration::from_secs(60)), max_blocks: Some(2) };
+        assert!(ttl.check(&signal, Some(102)).is_ok());
+        assert!(ttl.check(&signal, Some(103)).is_err());
+        // Without a head there is nothing to compare the block against
+        assert!(ttl.check(&signal, None).is_ok());
+        
+        let expired = SignalTtl { max_age: Some(Duration::ZERO), max_blocks: None };
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(expired.check(&signal, Some(100)).is_err());
+        assert!(SignalTtl::default().check(&signal, Some(1_000)).is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_stale_positions() {
+        use crate::mempool_streamer::{TrafficProfile, TransactionType};
+        use crate::population::PopulationModel;
+        use rand::{rngs::StdRng, SeedableRng};
+        
+        // Borrowers opened at HF ~0.9 are liquidatable from the start
+        let model = PopulationModel { target_health_factor: (0.9, 0.9), ..Default::default() };
+        let population = Arc::new(UserPopulation::new(model, 2, 3));
+        let profile = TrafficProfile { borrow_usd: (1e9, 1e9), ..Default::default() };
+        let mut rng = StdRng::seed_from_u64(3);
+        for user in population.users().to_vec() {
+            population.apply(&mut rng, user, TransactionType::Deposit, &profile);
+            population.apply(&mut rng, user, TransactionType::Borrow, &profile);
+        }
+        let blockchain = offline_client().await;
+        
+        for policy in [StalePolicy::DownRank, StalePolicy::Refresh] {
+            let detector = LiquidationDetector::with_source(blockchain.clone(), PositionSource::Synthetic(population.clone()))
+                .with_staleness(PositionStaleness { max_blocks: 5, policy });
+            detector.observe_block(10);
+            for user in population.users() {
+                detector.update_position(*user).await.unwrap();
+            }
+            detector.observe_block(15);
+            assert!(detector.scan_all_positions().await.unwrap().iter().all(|s| !s.stale));
+            
+            detector.observe_block(16);
+            let signals = detector.scan_all_positions().await.unwrap();
+            assert_eq!(signals.len(), 2);
+            let stats = detector.stale_stats();
+            assert_eq!(stats.hits, 2);
+            match policy {
+                StalePolicy::DownRank => {
+                    assert!(signals.iter().all(|s| s.stale && s.observed_block == Some(10)));
+                    assert_eq!(stats.down_ranked, 2);
+                }
+                // Re-read at block 16, so fresh again
+                StalePolicy::Refresh => {
+                    assert!(signals.iter().all(|s| !s.stale));
+                    assert_eq!(stats.refreshed, 2);
+                    assert_eq!(detector.scan_all_positions().await.unwrap().len(), 2);
+                    assert_eq!(detector.stale_stats().hits, 2);
+                }
+            }
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_watchlist_signals_on_price_tick() {
+        use crate::mempool_streamer::{TrafficProfile, TransactionType};
+        use crate::population::PopulationModel;
+        use rand::{rngs::StdRng, SeedableRng};
+        
+        // Borrowers land at HF 1.02-1.04; each price step is a 5% drop
+        let model = PopulationModel {
+            price_volatility: 0.0,
+            price_drift: -0.05,
+            target_health_factor: (1.02, 1.04),
+            ..Default::default()
+        };
+        let population = Arc::new(UserPopulation::new(model, 5, 7));
+        let profile = TrafficProfile { borrow_usd: (1e9, 1e9), ..Default::default() };
+        let mut rng = StdRng::seed_from_u64(7);
+        for user in population.users().to_vec() {
+            population.apply(&mut rng, user, TransactionType::Deposit, &profile);
+            population.apply(&mut rng, user, TransactionType::Borrow, &profile);
+        }
+        
+        let blockchain = offline_client().await;
+        let detector = LiquidationDetector::with_source(blockchain, PositionSource::Synthetic(population.clone()))
+            .with_watch_band(1.05);
+        for user in population.users() {
+            detector.update_position(*user).await.unwrap();
+        }
+        assert_eq!(detector.watchlist_stats().size, 5);
+        
+        // Still healthy, and a block is only swept once
+        assert!(detector.sweep_block(1).await.is_empty());
+        assert!(detector.sweep_block(1).await.is_empty());
+        assert_eq!(detector.watchlist_stats().block_sweeps, 1);
+        
+        let tx = Transaction::default();
+        assert!(detector.is_price_tick(&tx, Address::zero()));
+        assert!(!detector.is_price_tick(&tx, Address::zero()));
+        
+        // Health factors scale with the price, so halving it halves them
+        let sensitivity = detector.sensitivity(population.users()[0]).unwrap();
+        assert!((sensitivity.health_factor_at(sensitivity.price_usd / 2.0) - sensitivity.health_factor / 2.0).abs() < 1e-9);
+        // At an unchanged price every position is predicted clear and not re-read
+        assert!(detector.reevaluate_watchlist(&tx).await.is_empty());
+        assert_eq!(detector.watchlist_stats().reads_skipped, 5);
+        
+        population.step_price(&mut rng);
+        assert!(detector.is_price_tick(&tx, Address::zero()));
+        assert_eq!(detector.reevaluate_watchlist(&tx).await.len(), 5);
+        
+        // Liquidatable positions leave the watchlist
+        let stats = detector.watchlist_stats();
+        assert_eq!(stats.size, 0);
+        assert_eq!(stats.churn(), 10);
+    }
+    
+    #[tokio::test]
+    async fn test_inputs_through_one_entry_point() {
+        use crate::blockchain::BorrowFilter;
+        use crate::mempool_streamer::{TrafficProfile, TransactionType};
+        use crate::population::PopulationModel;
+        use ethers::abi::{encode, Token};
+        use ethers::contract::EthEvent;
+        use ethers::types::H256;
+        use rand::{rngs::StdRng, SeedableRng};
+        
+        let model = PopulationModel { price_volatility: 0.0, price_drift: -0.05, target_health_factor: (1.02, 1.04), ..Default::default() };
+        let population = Arc::new(UserPopulation::new(model, 3, 7));
+        let profile = TrafficProfile { borrow_usd: (1e9, 1e9), ..Default::default() };
+        let mut rng = StdRng::seed_from_u64(7);
+        for user in population.users().to_vec() {
+            population.apply(&mut rng, user, TransactionType::Deposit, &profile);
+            population.apply(&mut rng, user, TransactionType::Borrow, &profile);
+        }
+        let blockchain = offline_client().await;
+        let detector = LiquidationDetector::with_source(blockchain, PositionSource::Synthetic(population.clone()));
+        
+        let protocol = Address::from_low_u64_be(0x1e4d);
+        let borrow = |address: Address, user: Address| DetectorInput::MinedLog(Log {
+            address,
+            topics: vec![BorrowFilter::signature(), H256::from(user)],
+            data: encode(&[Token::Uint(U256::one())]).into(),
+            ..Default::default()
+        });
+        let users = population.users().to_vec();
+        
+        // A mined Borrow re-reads its user; other contracts' logs are ignored
+        assert!(detector.detect(&borrow(protocol, users[0]), protocol).await.unwrap().is_empty());
+        assert!(detector.detect(&borrow(Address::zero(), users[1]), protocol).await.unwrap().is_empty());
+        assert_eq!(detector.watchlist_stats().size, 1);
         
-        assert!(position.health_factor >= U256::from(LIQUIDATION_THRESHOLD));
+        // The price update runs first and signals the watched user; the log
+        // then reads another, and the same user is not signaled twice
+        population.step_price(&mut rng);
+        let inputs = [
+            DetectorInput::NewBlock(1),
+            borrow(protocol, users[0]),
+            borrow(protocol, users[2]),
+            DetectorInput::PriceUpdate { trigger: None, eth_price_usd: None },
+        ];
+        let signals = detector.detect_all(&inputs, protocol).await;
+        assert_eq!(signals.iter().map(|signal| signal.user).collect::<Vec<_>>(), vec![users[0], users[2]]);
+        assert_eq!(detector.watchlist_stats().block_sweeps, 1);
     }
 }
 
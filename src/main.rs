@@ -1,23 +1,49 @@
-mod blockchain;
-mod config;
-mod liquidation_detector;
-mod simulator;
-mod executor;
-mod mempool_streamer;
-mod metrics;
-mod backtesting;
-
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tracing::{info, error};
-use tracing_subscriber;
+use std::time::Duration;
+use tracing::{error, info, warn};
 
-use crate::blockchain::BlockchainClient;
-use crate::config::Config;
-use crate::liquidation_detector::LiquidationDetector;
-use crate::simulator::LiquidationSimulator;
-use crate::executor::LiquidationExecutor;
-use crate::backtesting::BacktestEngine;
+use ethers::signers::{LocalWallet, Signer};
+use liquidio::audit::{read_journal, AuditJournal};
+use liquidio::blockchain::{BlockchainClient, WsProvider};
+use liquidio::builders::BuilderRelay;
+use liquidio::comparison::{load_metrics, ComparisonReport, RegressionThresholds};
+use liquidio::config::Config;
+use liquidio::control::{self, ControlPanel};
+use liquidio::doctor;
+use liquidio::liquidation_detector::{LiquidationDetector, PositionSource};
+use liquidio::market_status::MarketGuard;
+use liquidio::simulator::LiquidationSimulator;
+use liquidio::executor::LiquidationExecutor;
+use liquidio::backtesting::{BacktestEngine, MempoolSource};
+use liquidio::calibration::CalibrationTracker;
+use liquidio::exposure::{DrawdownGuard, ExposureLimiter};
+use liquidio::funding::FundingRouter;
+use liquidio::health::{self, HealthState};
+use liquidio::helper;
+use liquidio::history::PositionHistory;
+use liquidio::inventory::InventoryManager;
+use liquidio::mempool_capture;
+use liquidio::metrics;
+use liquidio::oracle::PriceOracle;
+use liquidio::staleness::{StalenessMonitor, StalenessThresholds};
+use liquidio::submission::{BundleRoute, ProtectRoute, PublicRoute, RouteKind, SubmissionRoute};
+use liquidio::population::{PopulationModel, UserPopulation};
+use liquidio::presign::PresignCache;
+use liquidio::protocol::{HealthFactorScale, ProtocolAdapter, SimpleLendingAdapter};
+use liquidio::venus::VenusAdapter;
+use liquidio::morpho::MorphoAdapter;
+use liquidio::euler::EulerAdapter;
+use liquidio::opportunities::OpportunityFeed;
+use liquidio::replay;
+use liquidio::run_metadata::RunMetadata;
+use liquidio::seeding::SeedSpec;
+use liquidio::setup::{self, SetupOptions};
+use liquidio::targeting::TargetStrategy;
+use liquidio::transport::RpcTransport;
+use liquidio::valuation::{CollateralPricer, SaleModel};
+use liquidio::wallets::WalletPool;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -29,44 +55,401 @@ async fn main() -> Result<()> {
     info!("Liquidio - Low-Latency DeFi Liquidation Bot");
     info!("================================================");
     
+    // Subcommands that don't need a node connection
+    let mut args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("compare") {
+        return run_compare(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("replay") {
+        return run_replay(args.split_off(2)).await;
+    }
+    if args.get(1).map(String::as_str) == Some("dashboard") {
+        return run_dashboard(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("deploy-helper") {
+        return run_deploy_helper(&args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("record") {
+        return run_record(&args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("doctor") {
+        return run_doctor().await;
+    }
+    if args.get(1).map(String::as_str) == Some("setup-local") {
+        return run_setup_local(args.split_off(2)).await;
+    }
+    
+    // `bench` sets up the same pipeline, then runs only the latency stress test
+    let bench = match args.get(1).map(String::as_str) {
+        Some("bench") => Some(BenchOptions::take(&mut args)?),
+        _ => None,
+    };
+    
     // Load configuration
-    let config = Config::from_env()?;
+    let mut config = Config::from_env()?;
+    apply_report_flags(&mut config, &mut args)?;
+    if let Some(unknown) = args.get(1) {
+        anyhow::bail!("Unknown argument {}", unknown);
+    }
     info!("[OK] Configuration loaded");
+    metrics::set_nanosecond_precision(config.latency_nanoseconds);
     
-    // Connect to blockchain
-    let blockchain = Arc::new(
-        BlockchainClient::new(
-            &config.anvil_rpc_url,
-            Some(&config.anvil_ws_url),
-            config.lending_protocol_address,
-            config.mock_token_address,
-        )
-        .await?
-    );
-    info!("[OK] Connected to blockchain");
+    // Connect to blockchain, within the provider's request-rate limit
+    let rate_limits = config.rate_limits();
+    let mut transport = RpcTransport::connect(&config.anvil_rpc_url).await?;
+    if let Some(bucket) = rate_limits.bucket(&config.anvil_rpc_url) {
+        info!("Limiting RPC requests to {}/s", bucket.rate());
+        transport = transport.with_rate_limit(bucket);
+    }
+    let mut blockchain = BlockchainClient::with_transport(
+        transport,
+        Some(&config.anvil_ws_url),
+        config.lending_protocol_address,
+        config.mock_token_address,
+    )
+    .await?;
+    if config.rpc_batch_window_ms > 0 {
+        info!("Batching RPC lookups within {}ms", config.rpc_batch_window_ms);
+        blockchain = blockchain.with_batching(Duration::from_millis(config.rpc_batch_window_ms), config.rpc_batch_max_size);
+    }
+    if config.read_cache_ttl_ms > 0 {
+        info!("Caching reads per block for up to {}ms", config.read_cache_ttl_ms);
+        blockchain = blockchain.with_read_cache(Duration::from_millis(config.read_cache_ttl_ms));
+    }
+    let blockchain = Arc::new(blockchain);
+    let chain_id = blockchain.ensure_chain_id(config.chain_id).await?;
+    info!("[OK] Connected to blockchain (chain id {})", chain_id);
+    
+    // Morpho markets are immutable, so their params are read once up front
+    let mut morpho_market = None;
+    let morpho = config.morpho_blue_address.zip(config.morpho_market_id);
+    let adapter: Arc<dyn ProtocolAdapter> = match (config.venus_comptroller_address, morpho, config.euler_liability_vault) {
+        (Some(comptroller), _, _) => {
+            info!("Reading Venus risk params from comptroller {:?}", comptroller);
+            let mut adapter = VenusAdapter::new(&blockchain, comptroller).with_health_factor_scale(config.health_factor_scale);
+            if let Some(market) = config.venus_market() {
+                adapter = adapter.with_market(market);
+            }
+            Arc::new(adapter)
+        }
+        (None, Some((morpho, market_id)), _) => {
+            let adapter = MorphoAdapter::new(&blockchain, morpho, market_id);
+            let market = adapter.market().await?;
+            info!("Morpho Blue market {:?}: LLTV {}", market_id, liquidio::fixed_point::wad_to_f64(market.params.lltv));
+            morpho_market = Some(market);
+            Arc::new(adapter)
+        }
+        (None, None, Some(vault)) => {
+            info!("Pricing liquidations on the Euler vault {:?} discount curve", vault);
+            Arc::new(EulerAdapter::new(&blockchain, vault))
+        }
+        (None, None, None) => Arc::new(
+            SimpleLendingAdapter::new(blockchain.clone()).with_health_factor_scale(config.health_factor_scale),
+        ),
+    };
+    // Synthetic borrowers are percent-scaled like SimpleLendingProtocol
+    let hf_scale = match config.synthetic_population {
+        true => HealthFactorScale::PERCENT,
+        false => adapter.health_factor_scale(),
+    };
+    
+    // Per-user health factor time series, served under /positions
+    let history = Arc::new(match &config.position_history_path {
+        Some(path) => PositionHistory::open(path, config.position_history_max_samples)?,
+        None => PositionHistory::new(config.position_history_max_samples),
+    }.with_health_factor_scale(hf_scale));
+    
+    // Synthetic borrowers shared by the backtest streamer and the detector
+    let population = config.synthetic_population.then(|| Arc::new(UserPopulation::new(
+        PopulationModel::default(),
+        config.synthetic_user_population,
+        config.backtest_seed,
+    )));
     
     // Initialize components
-    let detector = Arc::new(LiquidationDetector::new(blockchain.clone()));
-    let simulator = Arc::new(LiquidationSimulator::new(
+    let position_source = match &population {
+        Some(population) => PositionSource::Synthetic(population.clone()),
+        None => PositionSource::Chain,
+    };
+    let mut detector = LiquidationDetector::with_source(blockchain.clone(), position_source)
+        .with_watch_band(config.watchlist_hf_band)
+        .with_health_factor_scale(hf_scale)
+        .with_history(history.clone());
+    if let Some(staleness) = config.position_staleness() {
+        info!("Positions older than {} blocks are stale ({:?})", staleness.max_blocks, staleness.policy);
+        detector = detector.with_staleness(staleness);
+    }
+    let detector = Arc::new(detector);
+    // Liveness/readiness probes and the live dashboard
+    let health = Arc::new(
+        HealthState::new(config.max_feed_lag_ms)
+            .with_position_history(history.clone())
+            .with_opportunity_feed(Arc::new(OpportunityFeed::new(config.opportunity_feed_size)))
+            .with_watchlist(detector.clone())
+            .with_dashboard_push_interval(Duration::from_millis(config.dashboard_push_ms))
+            .with_event_buffer(config.event_buffer_size),
+    );
+    // Rolling 1- and 5-minute summaries of detections, wins, latency and PnL
+    tokio::spawn(health.live_metrics().clone().report_windows(
+        Duration::from_millis(config.health_check_interval_ms),
+        config.live_summary_path.as_ref().map(PathBuf::from),
+    ));
+    if let Some(port) = config.health_port {
+        let (health, blockchain) = (health.clone(), blockchain.clone());
+        tokio::spawn(async move {
+            if let Err(e) = health::serve(port, health, blockchain).await {
+                error!("Health server stopped: {}", e);
+            }
+        });
+    }
+    
+    // Seed on-chain borrowers from a subgraph instead of waiting for them to transact
+    if let (Some(bootstrap), None) = (config.subgraph_bootstrap(), &population) {
+        match bootstrap.fetch_borrowers().await {
+            Ok(borrowers) => {
+                let refreshed = detector.seed_positions(&borrowers).await;
+                info!("Bootstrapped {} borrowers from the subgraph ({} re-read near liquidation)", borrowers.len(), refreshed);
+            }
+            Err(e) => warn!("Subgraph bootstrap failed, starting without it: {:#}", e),
+        }
+    }
+    // Capital limits and sweeps for the executor wallets; their balances decide
+    // whether a repayment needs funding at all
+    let mut inventory = InventoryManager::new(blockchain.clone(), config.inventory_limits());
+    // Seized collateral over the threshold is sold back through the same router
+    if let Some(route) = config.wallet_swap_route() {
+        inventory = inventory.with_swap_route(route);
+    }
+    let inventory = Arc::new(inventory);
+    for key in &config.liquidator_private_keys {
+        let wallet = LocalWallet::from_bytes(key.as_bytes())?.address();
+        if let Err(e) = inventory.refresh(wallet).await {
+            warn!("Couldn't load balances of {:?}: {:#}", wallet, e);
+        }
+    }
+    let mut simulator = LiquidationSimulator::with_backend(
         blockchain.clone(),
         config.min_profit_threshold_usd,
-    ));
-    let executor = Arc::new(LiquidationExecutor::new(
+        config.simulation_backend,
+    )
+    .with_adapter(adapter.clone())
+    .with_gas_model(config.gas_model)
+    .with_confidence_band(config.price_confidence_band)
+    .with_signal_ttl(config.signal_ttl())
+    .with_profit_thresholds(config.profit_thresholds())
+    .with_inventory(inventory.clone());
+    // Chainlink/Pyth prices cross-checked against a Uniswap V3 TWAP
+    let oracle_sources = config.oracle_sources();
+    if oracle_sources.is_configured() {
+        simulator = simulator.with_price_oracle(Arc::new(PriceOracle::new(&blockchain, oracle_sources)));
+    }
+    let funding_sources = config.funding_sources();
+    let wallet_swap = config.wallet_swap_route();
+    if !funding_sources.is_empty() || wallet_swap.is_some() {
+        let mut router = FundingRouter::new(blockchain.clone(), funding_sources);
+        // Wallets short of the debt asset may buy it with ETH before liquidating
+        if let Some(route) = wallet_swap {
+            info!("Topping up the debt asset through SwapRouter {:?}", route.router);
+            router = router.with_wallet_swap(route);
+        }
+        simulator = simulator.with_funding(Arc::new(router));
+    }
+    let swap_venues = config.swap_venues();
+    if !swap_venues.is_empty() {
+        simulator = simulator.with_collateral_pricer(Arc::new(CollateralPricer::new(
+            blockchain.clone(),
+            swap_venues,
+            config.slippage_min_value_usd,
+        )));
+    }
+    let simulator = Arc::new(simulator);
+    // Signers rotated across submissions; without keys execution is dry-run only
+    let wallets = WalletPool::from_keys(&config.liquidator_private_keys, chain_id, config.wallet_rotation)?;
+    if wallets.is_empty() {
+        info!("No liquidator keys configured, running without signing");
+    } else {
+        info!("Signing with {} wallet(s), {:?} rotation", wallets.len(), wallets.strategy());
+    }
+    let mut executor = LiquidationExecutor::new(
         blockchain.clone(),
-        None, // No wallet for simulation mode
+        wallets,
         config.max_gas_price_gwei,
-    )    );
+    ).with_chain_id(chain_id)
+    .with_gas_model(config.gas_model)
+    .with_gas_limit_multiplier(config.gas_limit_multiplier)
+    .with_legacy_transactions(config.legacy_transactions)
+    .with_signal_ttl(config.signal_ttl())
+    .with_target_strategy(TargetStrategy::new(config.bundle_target_blocks))
+    .with_submission_retries(config.submission_retries())
+    .with_route_policy(config.route_policy());
+    // Flash loan, liquidation and collateral sale in one transaction
+    if let Some(helper) = config.liquidation_helper_address {
+        info!("Routing liquidations through helper {:?}", helper);
+        executor = executor.with_helper(helper);
+    }
+    if let Some(market) = config.venus_market() {
+        info!("Liquidating Venus {:?}, seizing {:?}", market.borrowed, market.collateral);
+        executor = executor.with_venus_market(market);
+    }
+    if let Some(market) = morpho_market {
+        executor = executor.with_morpho_market(market);
+    }
+    // Private submission to several builders in parallel
+    let builders = (!config.builders.is_empty()).then(|| {
+        let auth_signer = match config.builder_auth_key {
+            Some(key) => LocalWallet::from_bytes(key.as_bytes()),
+            None => Ok(LocalWallet::new(&mut rand::thread_rng())),
+        };
+        auth_signer.map(|signer| Arc::new(BuilderRelay::new(config.builders.clone(), signer)))
+    }).transpose()?;
+    if let Some(builders) = &builders {
+        info!("Bundling for builders: {:?}", builders.builders().iter().map(|b| &b.name).collect::<Vec<_>>());
+    }
+    // Each opportunity goes out on whichever of these suits its competition risk
+    for kind in config.routes() {
+        let route: Arc<dyn SubmissionRoute> = match (kind, &builders) {
+            (RouteKind::Public, _) => Arc::new(PublicRoute::new(blockchain.clone())),
+            (RouteKind::Protect, _) => Arc::new(ProtectRoute::new(config.protect_rpc_url.clone())),
+            (RouteKind::Bundle, Some(builders)) => Arc::new(BundleRoute::new(builders.clone(), blockchain.clone())),
+            (RouteKind::Bundle, None) | (RouteKind::LogOnly, _) => continue,
+        };
+        info!("Submitting via the {} route", kind);
+        executor = executor.with_route(route);
+    }
+    executor = executor.with_inventory(inventory);
+    // Liquidations for watchlisted positions signed before they are triggered
+    if config.presign_max_age_ms > 0 {
+        executor = executor.with_presign_cache(Arc::new(PresignCache::new(Duration::from_millis(config.presign_max_age_ms))));
+    }
+    // Sign each liquidation in parallel with its simulation
+    if config.speculative_build {
+        executor = executor.with_speculative_build();
+    }
+    let executor = Arc::new(executor);
     
     info!("[OK] Components initialized");
     
     // Create backtest engine
-    let backtest_engine = BacktestEngine::new(
+    let mut backtest_engine = BacktestEngine::new(
         blockchain.clone(),
         detector.clone(),
         simulator.clone(),
         executor.clone(),
         config.lending_protocol_address,
-    );
+        config.max_concurrent_executions,
+    )
+    .with_simulation_workers(config.simulation_pool_size())
+    .with_traffic_profile(config.traffic_profile())
+    .with_chain_id(chain_id)
+    .with_run_metadata(RunMetadata::capture(Config::env_snapshot()).with_chain_id(chain_id))
+    .with_report_sink(config.report_sink(chain_id))
+    .with_exposure_limits(Arc::new(ExposureLimiter::new(config.exposure_limits())))
+    .with_buffer(config.buffer_config())
+    .with_collateral_sale(SaleModel { fee_bps: config.backtest_sale_fee_bps, depth_usd: config.backtest_sale_depth_usd })
+    .with_opportunity_queue(config.opportunity_queue_capacity, Duration::from_millis(config.opportunity_max_age_ms))
+    .with_health(health.clone())
+    .with_calibration(Arc::new(CalibrationTracker::new(config.calibration_window, config.drift_thresholds())));
+    // Synthetic positions only move with the synthetic stream
+    match population {
+        Some(population) => backtest_engine = backtest_engine.with_population(population),
+        None if config.watchlist_block_sweep => backtest_engine = backtest_engine.with_block_sweeps(),
+        None => {}
+    }
+    let address_policy = config.address_policy();
+    if !address_policy.is_empty() {
+        info!(
+            "Address policy: {} blocked, {} allowed, {} labeled",
+            address_policy.blocklist.len(),
+            address_policy.allowlist.len(),
+            address_policy.labels.len(),
+        );
+        backtest_engine = backtest_engine.with_address_policy(Arc::new(address_policy));
+    }
+    if let Some(competitors) = config.competitor_model() {
+        info!("Racing {} competitor bots in backtests", competitors.rivals);
+        backtest_engine = backtest_engine.with_competitors(competitors);
+    }
+    if let Some(path) = &config.audit_log_path {
+        let journal = AuditJournal::open(path)?;
+        info!("Audit journal: {}", journal.path().display());
+        backtest_engine = backtest_engine.with_audit(Arc::new(journal));
+    }
+    if config.market_status_ttl_ms > 0 {
+        let guard = MarketGuard::new(adapter.clone(), Duration::from_millis(config.market_status_ttl_ms))
+            .with_prometheus(health.prometheus().clone());
+        backtest_engine = backtest_engine.with_market_guard(Arc::new(guard));
+    }
+    let batching = config.batch_policy();
+    if batching.enabled() {
+        if executor.can_batch() {
+            info!("Batching up to {} liquidations of at most ${:.0} each", batching.max_size, batching.max_repay_usd);
+        } else {
+            warn!("BATCH_MAX_SIZE needs LIQUIDATION_HELPER_ADDRESS on SimpleLendingProtocol; liquidations go out one by one");
+        }
+        backtest_engine = backtest_engine.with_batching(batching);
+    }
+    if let Some(limits) = config.drawdown_limits() {
+        info!(
+            "Drawdown guard: max loss {:?} USD, max losing streak {:?} over {}s",
+            limits.max_drawdown_usd, limits.max_losing_streak, limits.window.as_secs()
+        );
+        backtest_engine = backtest_engine.with_drawdown_guard(Arc::new(DrawdownGuard::new(limits)));
+    }
+    let scenarios = config.stress_scenarios()?;
+    if let Some(name) = &config.backtest_gas_scenario {
+        let scenario = scenarios
+            .iter()
+            .find(|scenario| &scenario.name == name)
+            .with_context(|| format!("Unknown BACKTEST_GAS_SCENARIO: {}", name))?;
+        info!("Backtest gas follows the {} base-fee trajectory", name);
+        backtest_engine = backtest_engine.with_gas_scenario(scenario.clone());
+    }
+    
+    // gRPC API for stacks that route execution through their own order management
+    #[cfg(feature = "grpc")]
+    if let Some(port) = config.grpc_port {
+        let labels = liquidio::metrics::MetricLabels::new(simulator.protocol_name(), chain_id);
+        let service = liquidio::grpc::LiquidioService::new(health.clone(), blockchain.clone(), labels);
+        tokio::spawn(async move {
+            if let Err(e) = liquidio::grpc::serve(port, service).await {
+                error!("gRPC server stopped: {}", e);
+            }
+        });
+    }
+    #[cfg(not(feature = "grpc"))]
+    if config.grpc_port.is_some() {
+        warn!("GRPC_PORT is set, but this build has no gRPC API (build with --features grpc)");
+    }
+    
+    // Block lag / feed staleness monitoring across all configured RPCs
+    let mut staleness = StalenessMonitor::new(
+        blockchain.clone(),
+        &config.monitor_rpc_urls,
+        health,
+        StalenessThresholds {
+            max_block_lag: config.max_block_lag,
+            max_feed_lag_ms: config.max_feed_lag_ms,
+        },
+    )?
+    .with_rate_limits(&rate_limits);
+    if config.pause_on_stale_data {
+        staleness = staleness.with_pause_switch(backtest_engine.pause_switch());
+    }
+    
+    // Operator pause/resume and threshold tuning
+    if let Some(path) = config.control_socket_path.clone() {
+        let panel = Arc::new(ControlPanel::new(backtest_engine.pause_switch(), simulator.clone(), detector.clone()));
+        tokio::spawn(async move {
+            if let Err(e) = control::serve(path, panel).await {
+                error!("Control socket stopped: {}", e);
+            }
+        });
+    }
+    tokio::spawn(staleness.run(Duration::from_millis(config.health_check_interval_ms)));
+    
+    if let Some(bench) = bench {
+        return run_bench(&backtest_engine, &config, bench).await;
+    }
     
     // Run backtesting suite
     info!("\nStarting Backtesting Suite");
@@ -75,73 +458,347 @@ async fn main() -> Result<()> {
     // Test 1: Full transaction stream backtest
     info!("\nTest 1: Transaction Stream Backtest (50k transactions)");
     let metrics_1 = backtest_engine.run_backtest(50_000).await?;
-    backtest_engine.generate_report(&metrics_1, "benchmark_results/transaction_stream_backtest").await?;
+    backtest_engine.generate_report(&metrics_1, "transaction_stream_backtest").await?;
     
     // Test 2: Latency stress test
     info!("\nTest 2: Latency Stress Test (10k iterations)");
     let metrics_2 = backtest_engine.run_latency_stress_test(10_000).await?;
-    backtest_engine.generate_report(&metrics_2, "benchmark_results/latency_stress_test").await?;
+    backtest_engine.generate_report(&metrics_2, "latency_stress_test").await?;
+    
+    // Test 3: Price-shock stress scenarios
+    info!("\nTest 3: Stress Scenarios");
+    for report in backtest_engine.run_stress_scenarios(&scenarios).await? {
+        report.print();
+        let path = backtest_engine.report_sink().path(&format!("stress_{}", report.scenario), "json")?;
+        report.export_to_json(&path.to_string_lossy(), backtest_engine.run_metadata())?;
+    }
+    
+    // Test 4: Live sequencer feed (Arbitrum)
+    if let Some(url) = &config.sequencer_feed_url {
+        info!("\nTest 4: Sequencer Feed ({} transactions)", config.sequencer_feed_max_txs);
+        let metrics_4 = backtest_engine.run_sequencer_feed(url, config.sequencer_feed_max_txs).await?;
+        backtest_engine.generate_report(&metrics_4, "sequencer_feed").await?;
+    }
+    
+    // Test 4b: Recorded mempool capture
+    if let Some(path) = &config.mempool_capture_path {
+        info!("\nTest 4b: Mempool Capture Replay ({}x)", config.mempool_capture_speed);
+        let source = MempoolSource::FromFile { path: path.into(), speed: config.mempool_capture_speed };
+        let metrics_4b = backtest_engine.run_source(&source, config.mempool_capture_max_txs).await?;
+        backtest_engine.generate_report(&metrics_4b, "mempool_capture").await?;
+    }
+    
+    // Test 4c: ERC-4337 UserOperation mempool
+    if let Some(url) = &config.bundler_url {
+        info!("\nTest 4c: UserOperation Mempool ({} operations)", config.user_operation_max_ops);
+        let source = MempoolSource::UserOperations {
+            bundler_url: url.clone(),
+            entry_point: config.entry_point_address,
+            poll_interval: Duration::from_millis(config.user_operation_poll_ms),
+        };
+        let metrics_4c = backtest_engine.run_source(&source, config.user_operation_max_ops).await?;
+        backtest_engine.generate_report(&metrics_4c, "user_operations").await?;
+    }
+    
+    // Test 5: Monte Carlo over randomized seeds
+    if config.monte_carlo_runs > 0 {
+        info!("\nTest 5: Monte Carlo Backtest ({} runs)", config.monte_carlo_runs);
+        let report = backtest_engine
+            .run_monte_carlo(config.monte_carlo_runs, config.monte_carlo_txs_per_run)
+            .await?;
+        report.print();
+        let path = backtest_engine.report_sink().path("monte_carlo", "json")?;
+        report.export_to_json(&path.to_string_lossy(), &backtest_engine.run_metadata().clone().with_seed(config.backtest_seed))?;
+    }
+    
+    // Test 6: Strategy parameter sweep
+    if let Some(grid) = config.sweep_grid() {
+        info!("\nTest 6: Parameter Sweep ({} combinations)", grid.combinations().len());
+        let report = backtest_engine.run_parameter_sweep(&grid, config.sweep_txs_per_run).await?;
+        report.print();
+        let run = backtest_engine.run_metadata().clone().with_seed(config.backtest_seed);
+        let sink = backtest_engine.report_sink();
+        report.export_to_csv(&sink.path("parameter_sweep", "csv")?.to_string_lossy(), &run)?;
+        report.export_to_json(&sink.path("parameter_sweep", "json")?.to_string_lossy(), &run)?;
+    }
     
     // Final summary
     info!("\nAll tests complete!");
     info!("=====================");
-    info!("Results saved to benchmark_results/");
+    info!("Results saved to {}/", backtest_engine.report_sink().dir().display());
+    if let Some(builders) = &builders {
+        builders.print_stats();
+    }
     
     // Validate performance targets
-    validate_performance_targets(&metrics_2)?;
+    config.performance_targets().check(&metrics_2, None).print();
     
     Ok(())
 }
 
-fn validate_performance_targets(metrics: &metrics::AggregateMetrics) -> Result<()> {
-    info!("\nValidating Performance Targets");
-    info!("==================================");
-    
-    let mut all_targets_met = true;
-    
-    // Target 1: End-to-end latency < 10ms (P99)
-    if let Some(p99) = metrics.percentile("end_to_end_us", 99.0) {
-        let p99_ms = p99 / 1000.0;
-        let target_met = p99_ms < 10.0;
-        info!("End-to-end latency (P99): {:.2}ms [Target: <10ms] {}", 
-            p99_ms, if target_met { "[OK]" } else { "[FAIL]" });
-        all_targets_met &= target_met;
-    }
-    
-    // Target 2: Signal detection < 2ms (P99)
-    if let Some(p99) = metrics.percentile("signal_detection_us", 99.0) {
-        let p99_ms = p99 / 1000.0;
-        let target_met = p99_ms < 2.0;
-        info!("Signal detection (P99): {:.2}ms [Target: <2ms] {}", 
-            p99_ms, if target_met { "[OK]" } else { "[FAIL]" });
-        all_targets_met &= target_met;
-    }
-    
-    // Target 3: Simulation < 5ms (P99)
-    if let Some(p99) = metrics.percentile("simulation_us", 99.0) {
-        let p99_ms = p99 / 1000.0;
-        let target_met = p99_ms < 5.0;
-        info!("Simulation (P99): {:.2}ms [Target: <5ms] {}", 
-            p99_ms, if target_met { "[OK]" } else { "[FAIL]" });
-        all_targets_met &= target_met;
-    }
-    
-    // Target 4: Transaction construction < 1ms (P99)
-    if let Some(p99) = metrics.percentile("construction_us", 99.0) {
-        let p99_ms = p99 / 1000.0;
-        let target_met = p99_ms < 1.0;
-        info!("Transaction construction (P99): {:.2}ms [Target: <1ms] {}", 
-            p99_ms, if target_met { "[OK]" } else { "[FAIL]" });
-        all_targets_met &= target_met;
-    }
-    
-    if all_targets_met {
-        info!("\nALL PERFORMANCE TARGETS MET!");
+/// Flags of `liquidio bench`
+struct BenchOptions {
+    /// Fail when a stage misses its target or regresses against the baseline
+    assert: bool,
+    baseline: Option<String>,
+    /// Save this run as the baseline (unless `--assert` fails it)
+    update_baseline: bool,
+    iterations: usize,
+}
+
+impl BenchOptions {
+    /// Take `bench` and its flags out of `args`
+    fn take(args: &mut Vec<String>) -> Result<Self> {
+        args.remove(1);
+        let baseline = take_flag(args, "--baseline")?;
+        let iterations = match take_flag(args, "--iterations")? {
+            Some(iterations) => iterations.parse().context("Invalid --iterations")?,
+            None => 10_000,
+        };
+        let mut switch = |flag: &str| {
+            let set = args.iter().any(|arg| arg == flag);
+            args.retain(|arg| arg != flag);
+            set
+        };
+        let (assert, update_baseline) = (switch("--assert"), switch("--update-baseline"));
+        Ok(Self { assert, baseline, update_baseline, iterations })
+    }
+}
+
+/// `liquidio bench [--assert] [--baseline <report.json>] [--update-baseline] [--iterations <n>]`:
+/// run the latency stress test and check each stage's P99 against its target
+/// and the baseline run
+async fn run_bench(engine: &BacktestEngine, config: &Config, options: BenchOptions) -> Result<()> {
+    info!("\nLatency Stress Test ({} iterations)", options.iterations);
+    let metrics = engine.run_latency_stress_test(options.iterations).await?;
+    engine.generate_report(&metrics, "latency_stress_test").await?;
+    
+    let path = options.baseline.unwrap_or_else(|| config.bench_baseline_path.clone());
+    let path = Path::new(&path);
+    let baseline = if path.exists() {
+        Some(load_metrics(path)?)
     } else {
-        info!("\nSome performance targets not met (see above)");
+        warn!("No baseline at {}, checking targets only", path.display());
+        None
+    };
+    let targets = config.performance_targets();
+    let report = targets.check(&metrics, baseline.as_ref());
+    report.print();
+    if options.assert && !report.passed() {
+        anyhow::bail!(
+            "{} stage(s) missed their P99 target or regressed more than {:.0}% against {}",
+            report.failures().count(),
+            targets.max_regression * 100.0,
+            path.display(),
+        );
     }
     
+    if options.update_baseline {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&metrics)?)?;
+        info!("Baseline saved to {}", path.display());
+    }
     Ok(())
 }
 
+/// `liquidio compare run_a.json run_b.json`: diff two reports and fail on regressions
+fn run_compare(args: &[String]) -> Result<()> {
+    let (baseline, candidate) = match args {
+        [a, b] => (Path::new(a), Path::new(b)),
+        _ => anyhow::bail!("Usage: liquidio compare <run_a.json> <run_b.json>"),
+    };
+    
+    let (baseline, candidate) = (load_metrics(baseline)?, load_metrics(candidate)?);
+    if let (Some(a), Some(b)) = (&baseline.run, &candidate.run) {
+        for difference in a.differences(b) {
+            warn!("Runs differ in {}", difference);
+        }
+    }
+    let report = ComparisonReport::compare(&baseline, &candidate, &RegressionThresholds::default());
+    report.print();
+    
+    if report.has_regressions() {
+        anyhow::bail!("{} metric(s) regressed beyond threshold", report.regressions().count());
+    }
+    info!("\nNo regressions detected");
+    Ok(())
+}
+
+/// `liquidio dashboard [path] [datasource-uid]`: write a Grafana dashboard for `/metrics`
+fn run_dashboard(args: &[String]) -> Result<()> {
+    let path = args.first().map(String::as_str).unwrap_or("benchmark_results/grafana_dashboard.json");
+    let datasource = args.get(1).map(String::as_str).unwrap_or("prometheus");
+    if let Some(dir) = Path::new(path).parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&metrics::grafana_dashboard(datasource))?)?;
+    info!("Grafana dashboard saved to {}", path);
+    Ok(())
+}
+
+/// `liquidio deploy-helper [artifact]`: deploy the LiquidationHelper contract owned by
+/// the first liquidator key, with the other keys as operators
+async fn run_deploy_helper(args: &[String]) -> Result<()> {
+    let artifact = Path::new(args.first().map(String::as_str).unwrap_or(helper::HELPER_ARTIFACT));
+    let config = Config::from_env()?;
+    let blockchain = BlockchainClient::new(
+        &config.anvil_rpc_url,
+        None,
+        config.lending_protocol_address,
+        config.mock_token_address,
+    ).await?;
+    let chain_id = blockchain.ensure_chain_id(config.chain_id).await?;
+    
+    let wallets = config.liquidator_private_keys
+        .iter()
+        .map(|key| LocalWallet::from_bytes(key.as_bytes()).map(|wallet| wallet.with_chain_id(chain_id)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(deployer) = wallets.first().cloned() else {
+        anyhow::bail!("Set LIQUIDATOR_PRIVATE_KEY(S) to deploy the helper");
+    };
+    let operators: Vec<_> = wallets.iter().map(|wallet| wallet.address()).collect();
+    if config.helper_swap_pool.is_none() {
+        warn!("HELPER_SWAP_POOL not set: the helper can only liquidate from inventory");
+    }
+    
+    let address = helper::deploy(
+        &blockchain,
+        deployer,
+        artifact,
+        config.weth_address.unwrap_or_default(),
+        config.helper_swap_pool.unwrap_or_default(),
+        &operators,
+    ).await?;
+    info!("Set LIQUIDATION_HELPER_ADDRESS={:?} to route liquidations through it", address);
+    Ok(())
+}
 
+/// `liquidio replay <journal.jsonl>`: re-simulate recorded opportunities with the current code
+async fn run_replay(mut args: Vec<String>) -> Result<()> {
+    let mut config = Config::from_env()?;
+    apply_report_flags(&mut config, &mut args)?;
+    let journal = match args.as_slice() {
+        [path] => Path::new(path),
+        _ => anyhow::bail!("Usage: liquidio replay <journal-file> [--out-dir <dir>] [--report-name <template>]"),
+    };
+    
+    let opportunities = replay::load_opportunities(&read_journal(journal)?)?;
+    info!("Replaying {} opportunities from {}", opportunities.len(), journal.display());
+    
+    // Gas price, gas estimates and risk params come from the node if it's reachable
+    let blockchain = Arc::new(BlockchainClient::new(
+        &config.anvil_rpc_url,
+        None,
+        config.lending_protocol_address,
+        config.mock_token_address,
+    ).await?);
+    let mut run = RunMetadata::capture(Config::env_snapshot());
+    let mut reports = config.report_sink(config.chain_id);
+    if let Ok(chain_id) = blockchain.get_chain_id().await {
+        run = run.with_chain_id(chain_id);
+        reports = reports.with_chain_id(chain_id);
+    }
+    let simulator = LiquidationSimulator::with_backend(
+        blockchain,
+        config.min_profit_threshold_usd,
+        config.simulation_backend,
+    ).with_gas_model(config.gas_model)
+    .with_profit_thresholds(config.profit_thresholds());
+    
+    let report = replay::replay(&simulator, &opportunities).await;
+    report.print();
+    let path = reports.path("replay_report", "json")?;
+    report.export_to_json(&path.to_string_lossy(), &run)?;
+    info!("Replay report saved to {}", path.display());
+    Ok(())
+}
+
+/// Take `--out-dir <dir>` and `--report-name <template>` out of `args`, over
+/// `REPORT_DIR` and `REPORT_NAME_TEMPLATE`
+fn apply_report_flags(config: &mut Config, args: &mut Vec<String>) -> Result<()> {
+    if let Some(dir) = take_flag(args, "--out-dir")? {
+        config.report_dir = dir;
+    }
+    if let Some(template) = take_flag(args, "--report-name")? {
+        config.report_name_template = template;
+    }
+    Ok(())
+}
+
+/// Remove `flag <value>` from `args`, returning the value
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Result<Option<String>> {
+    let Some(i) = args.iter().position(|arg| arg == flag) else { return Ok(None) };
+    if i + 1 >= args.len() {
+        anyhow::bail!("{} needs a value", flag);
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(value))
+}
+
+/// `liquidio record --out <capture.jsonl>`: record the live mempool and heads for
+/// later replay through `MEMPOOL_CAPTURE_PATH`
+async fn run_record(args: &[String]) -> Result<()> {
+    let out = match args {
+        [flag, path] if flag == "--out" => Path::new(path),
+        _ => anyhow::bail!("Usage: liquidio record --out <capture-file>"),
+    };
+    
+    let config = Config::from_env()?;
+    let ws = WsProvider::connect(&config.anvil_ws_url)
+        .await
+        .with_context(|| format!("Cannot connect to {}", config.anvil_ws_url))?;
+    let (transactions, blocks) = mempool_capture::record(&ws, out).await?;
+    info!("Recorded {} transactions over {} blocks to {}", transactions, blocks, out.display());
+    Ok(())
+}
+
+/// `liquidio doctor`: check connectivity, contracts, signers, relays and the
+/// oracle, failing if any check fails
+async fn run_doctor() -> Result<()> {
+    let config = Config::from_env()?;
+    let report = doctor::run(&config).await;
+    report.print();
+    if !report.passed() {
+        anyhow::bail!("{} startup checks failed", report.failures());
+    }
+    Ok(())
+}
+
+/// `liquidio setup-local`: deploy the mock protocol to Anvil, fund the liquidator,
+/// open test positions and write the `.env` for a local end-to-end run
+async fn run_setup_local(mut args: Vec<String>) -> Result<()> {
+    let out = take_flag(&mut args, "--out")?.unwrap_or_else(|| ".env".to_string());
+    let mut options = SetupOptions::default();
+    match (take_flag(&mut args, "--health-factors")?, take_flag(&mut args, "--spec")?) {
+        (Some(_), Some(_)) => anyhow::bail!("Pass --health-factors or --spec, not both"),
+        (Some(list), None) => {
+            let health_factors: Vec<f64> = list
+                .split(',')
+                .map(|hf| hf.trim().parse())
+                .collect::<Result<_, _>>()
+                .context("Invalid --health-factors")?;
+            options.positions = SeedSpec::at_health_factors(&health_factors, 10.0);
+        }
+        (None, Some(path)) => options.positions = SeedSpec::load(Path::new(&path))?,
+        (None, None) => {}
+    }
+    let force = args.iter().any(|arg| arg == "--force");
+    args.retain(|arg| arg != "--force");
+    if !args.is_empty() {
+        anyhow::bail!("Usage: liquidio setup-local [--health-factors 1.5,1.1,0.95 | --spec <positions.toml>] [--out <env-file>] [--force]");
+    }
+    let out = Path::new(&out);
+    if out.exists() && !force {
+        anyhow::bail!("{} already exists; pass --force to overwrite it", out.display());
+    }
+    
+    let rpc_url = std::env::var("ANVIL_RPC_URL").unwrap_or_else(|_| "http://127.0.0.1:8545".to_string());
+    let ws_url = std::env::var("ANVIL_WS_URL").unwrap_or_else(|_| "ws://127.0.0.1:8545".to_string());
+    let deployment = setup::run(&rpc_url, &options).await?;
+    std::fs::write(out, deployment.env_file(&rpc_url, &ws_url))?;
+    info!("Wrote {}; run `cargo run --release` to start the bot against it", out.display());
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use ethers::types::Address;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::metrics::MetricLabels;
+use crate::prometheus::{PrometheusRegistry, MARKET_HALTED};
+use crate::protocol::{MarketStatus, ProtocolAdapter};
+
+/// Remembers which markets the protocol has paused or frozen, re-reading each
+/// at most once per `ttl`, and alerts when one halts or resumes
+pub struct MarketGuard {
+    adapter: Arc<dyn ProtocolAdapter>,
+    ttl: Duration,
+    // (collateral, debt) -> status and when it was read
+    markets: Mutex<HashMap<(Address, Address), (MarketStatus, Instant)>>,
+    prometheus: Option<Arc<PrometheusRegistry>>,
+}
+
+impl MarketGuard {
+    pub fn new(adapter: Arc<dyn ProtocolAdapter>, ttl: Duration) -> Self {
+        Self { adapter, ttl, markets: Mutex::new(HashMap::new()), prometheus: None }
+    }
+
+    /// Export each market's halted state as a gauge
+    pub fn with_prometheus(mut self, prometheus: Arc<PrometheusRegistry>) -> Self {
+        self.prometheus = Some(prometheus);
+        self
+    }
+
+    /// Why liquidating `collateral_asset` for `debt_asset` would revert, if it would
+    ///
+    /// A status that can't be read keeps the last known one (active if none),
+    /// so an RPC hiccup never suppresses liquidations.
+    pub async fn halted(&self, collateral_asset: Address, debt_asset: Address) -> Option<String> {
+        let key = (collateral_asset, debt_asset);
+        let previous = self.markets.lock().unwrap().get(&key).cloned();
+        if let Some((status, read_at)) = &previous {
+            if read_at.elapsed() < self.ttl {
+                return reason(status);
+            }
+        }
+
+        let last_known = previous.map(|(status, _)| status);
+        let status = match self.adapter.market_status(collateral_asset, debt_asset).await {
+            Ok(status) => status,
+            Err(e) => {
+                warn!("Cannot read {} market status: {}", self.adapter.name(), e);
+                last_known.clone().unwrap_or(MarketStatus::Active)
+            }
+        };
+        self.markets.lock().unwrap().insert(key, (status.clone(), Instant::now()));
+
+        let market = MetricLabels::new(self.adapter.name(), 0).for_market(collateral_asset, debt_asset).market;
+        if status != last_known.unwrap_or(MarketStatus::Active) {
+            match &status {
+                MarketStatus::Halted(why) => {
+                    warn!("[ALERT] {} market {} halted ({}); suppressing its liquidations", self.adapter.name(), market, why)
+                }
+                MarketStatus::Active => info!("{} market {} active again", self.adapter.name(), market),
+            }
+        }
+        if let Some(prometheus) = &self.prometheus {
+            let halted = matches!(status, MarketStatus::Halted(_));
+            prometheus.set(&MARKET_HALTED, &[self.adapter.name(), &market], halted as u8 as f64);
+        }
+        reason(&status)
+    }
+
+    /// Markets last seen halted, with the reason
+    pub fn halted_markets(&self) -> Vec<(Address, Address, String)> {
+        self.markets
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(&(collateral, debt), (status, _))| reason(status).map(|why| (collateral, debt, why)))
+            .collect()
+    }
+}
+
+fn reason(status: &MarketStatus) -> Option<String> {
+    match status {
+        MarketStatus::Halted(why) => Some(why.clone()),
+        MarketStatus::Active => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::AssetRiskParams;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct PausableAdapter {
+        paused: AtomicBool,
+        reads: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ProtocolAdapter for PausableAdapter {
+        fn name(&self) -> &str {
+            "pausable"
+        }
+
+        async fn asset_params(&self, _asset: Address) -> anyhow::Result<AssetRiskParams> {
+            Ok(AssetRiskParams::default())
+        }
+
+        async fn market_status(&self, _collateral: Address, _debt: Address) -> anyhow::Result<MarketStatus> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            Ok(match self.paused.load(Ordering::Relaxed) {
+                true => MarketStatus::Halted("reserve paused".to_string()),
+                false => MarketStatus::Active,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_halted_market_is_cached_and_cleared() {
+        let adapter = Arc::new(PausableAdapter::default());
+        adapter.paused.store(true, Ordering::Relaxed);
+        let (collateral, debt) = (Address::zero(), Address::from_low_u64_be(1));
+
+        let cached = MarketGuard::new(adapter.clone(), Duration::from_secs(60));
+        assert_eq!(cached.halted(collateral, debt).await.as_deref(), Some("reserve paused"));
+        adapter.paused.store(false, Ordering::Relaxed);
+        assert!(cached.halted(collateral, debt).await.is_some());
+        assert_eq!(adapter.reads.load(Ordering::Relaxed), 1);
+        assert_eq!(cached.halted_markets().len(), 1);
+
+        let uncached = MarketGuard::new(adapter.clone(), Duration::ZERO);
+        assert_eq!(uncached.halted(collateral, debt).await, None);
+        assert!(uncached.halted_markets().is_empty());
+    }
+}
@@ -0,0 +1,276 @@
+use anyhow::{Context, Result};
+use ethers::providers::Middleware;
+use ethers::types::{Transaction, U64};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::backpressure::{self, BufferConfig, TxReceiver, TxSender};
+use crate::blockchain::WsProvider;
+use crate::metrics::now_ms;
+
+/// One line of a mempool capture file: a pending transaction and when it was
+/// received, in milliseconds since the Unix epoch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedTx {
+    pub received_ms: u64,
+    pub tx: Transaction,
+}
+
+/// A new head seen while recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedBlock {
+    pub received_ms: u64,
+    pub block: u64,
+}
+
+/// One line of a capture file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CaptureRecord {
+    Tx(Box<CapturedTx>),
+    Block(CapturedBlock),
+}
+
+impl CaptureRecord {
+    pub fn received_ms(&self) -> u64 {
+        match self {
+            CaptureRecord::Tx(captured) => captured.received_ms,
+            CaptureRecord::Block(captured) => captured.received_ms,
+        }
+    }
+}
+
+/// Appends capture records to a JSONL file
+pub struct CaptureWriter {
+    out: BufWriter<File>,
+    transactions: usize,
+    blocks: usize,
+}
+
+impl CaptureWriter {
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = File::create(path).with_context(|| format!("Cannot create capture file {}", path.display()))?;
+        Ok(Self { out: BufWriter::new(file), transactions: 0, blocks: 0 })
+    }
+
+    pub fn write(&mut self, record: &CaptureRecord) -> Result<()> {
+        serde_json::to_writer(&mut self.out, record)?;
+        self.out.write_all(b"\n")?;
+        match record {
+            CaptureRecord::Tx(_) => self.transactions += 1,
+            CaptureRecord::Block(_) => {
+                self.blocks += 1;
+                // A head is a natural checkpoint; keeps an interrupted capture usable
+                self.out.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Transactions and blocks written so far
+    pub fn counts(&self) -> (usize, usize) {
+        (self.transactions, self.blocks)
+    }
+
+    pub fn finish(mut self) -> Result<(usize, usize)> {
+        self.out.flush()?;
+        Ok(self.counts())
+    }
+}
+
+/// Record pending transactions and new heads from `ws` into `out` until
+/// Ctrl-C or until either subscription ends
+pub async fn record(ws: &WsProvider, out: &Path) -> Result<(usize, usize)> {
+    let mut writer = CaptureWriter::create(out)?;
+    let mut pending = ws.subscribe_pending_txs().await.context("Cannot subscribe to pending transactions")?;
+    let mut blocks = ws.subscribe_blocks().await.context("Cannot subscribe to new heads")?;
+    info!("Recording mempool to {} (Ctrl-C to stop)", out.display());
+
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            hash = pending.next() => {
+                let Some(hash) = hash else { break };
+                // Stamp on arrival; the lookup below must not skew the timing
+                let received_ms = now_ms();
+                match ws.get_transaction(hash).await {
+                    Ok(Some(tx)) => writer.write(&CaptureRecord::Tx(Box::new(CapturedTx { received_ms, tx })))?,
+                    // Already dropped or replaced
+                    Ok(None) => debug!("Pending transaction {:?} disappeared", hash),
+                    Err(e) => warn!("Cannot fetch pending transaction {:?}: {}", hash, e),
+                }
+            }
+            block = blocks.next() => {
+                let Some(block) = block else { break };
+                if let Some(number) = block.number {
+                    writer.write(&CaptureRecord::Block(CapturedBlock { received_ms: now_ms(), block: number.as_u64() }))?;
+                    let (transactions, blocks) = writer.counts();
+                    debug!("Block {}: {} transactions, {} blocks recorded", number, transactions, blocks);
+                }
+            }
+            _ = &mut shutdown => break,
+        }
+    }
+
+    writer.finish()
+}
+
+/// Delay of a transaction received at `received_ms` from the start of a replay,
+/// given the first capture timestamp and a speed multiplier; speed 0 replays
+/// without pauses
+pub fn replay_offset(first_ms: u64, received_ms: u64, speed: f64) -> Duration {
+    if speed <= 0.0 {
+        return Duration::ZERO;
+    }
+    // Captures merged from several nodes can be slightly out of order
+    let elapsed = received_ms.saturating_sub(first_ms);
+    Duration::from_secs_f64(elapsed as f64 / 1000.0 / speed)
+}
+
+/// Mempool source that replays a JSONL capture of pending transactions,
+/// keeping the original spacing between them (scaled by `speed`)
+///
+/// Transactions without a block number are tagged with the last head recorded
+/// before them, so block-driven state advances as it did live.
+pub struct CaptureReplay {
+    path: PathBuf,
+    speed: f64,
+    tx_sender: TxSender,
+    max_transactions: Option<usize>,
+}
+
+impl CaptureReplay {
+    pub fn new(path: &Path, speed: f64) -> (Self, TxReceiver) {
+        Self::with_buffer(path, speed, BufferConfig::default())
+    }
+
+    /// Forward into a buffer that applies `buffer.policy` when the detector falls behind
+    pub fn with_buffer(path: &Path, speed: f64, buffer: BufferConfig) -> (Self, TxReceiver) {
+        let (tx_sender, rx) = backpressure::channel(buffer);
+        (
+            Self {
+                path: path.to_path_buf(),
+                speed,
+                tx_sender,
+                max_transactions: None,
+            },
+            rx,
+        )
+    }
+
+    /// Stop (closing the channel) after forwarding `max` transactions
+    pub fn with_limit(mut self, max: usize) -> Self {
+        self.max_transactions = Some(max);
+        self
+    }
+
+    /// Forward captured transactions until the file ends, the limit is hit or
+    /// the receiver is dropped; lines that do not parse are skipped
+    pub async fn run(self) -> Result<usize> {
+        let file = tokio::fs::File::open(&self.path)
+            .await
+            .with_context(|| format!("Cannot open mempool capture {}", self.path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+        let start = Instant::now();
+        let mut first_ms = None;
+        let mut head: Option<u64> = None;
+        let mut forwarded = 0usize;
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: CaptureRecord = match serde_json::from_str(&line) {
+                Ok(record) => record,
+                Err(e) => {
+                    debug!("Skipping unreadable capture line: {}", e);
+                    continue;
+                }
+            };
+
+            let first_ms = *first_ms.get_or_insert(record.received_ms());
+            tokio::time::sleep_until(start + replay_offset(first_ms, record.received_ms(), self.speed)).await;
+
+            let mut tx = match record {
+                CaptureRecord::Tx(captured) => captured.tx,
+                CaptureRecord::Block(captured) => {
+                    head = Some(captured.block);
+                    continue;
+                }
+            };
+            if tx.block_number.is_none() {
+                tx.block_number = head.map(U64::from);
+            }
+            if self.tx_sender.send(tx).await.is_err() {
+                break;
+            }
+            forwarded += 1;
+            if self.max_transactions.is_some_and(|max| forwarded >= max) {
+                break;
+            }
+        }
+
+        info!("Mempool capture replay forwarded {} transactions", forwarded);
+        Ok(forwarded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_replays_capture_file() {
+        assert_eq!(replay_offset(1_000, 3_000, 1.0), Duration::from_secs(2));
+        assert_eq!(replay_offset(1_000, 3_000, 4.0), Duration::from_millis(500));
+        assert_eq!(replay_offset(1_000, 3_000, 0.0), Duration::ZERO);
+        assert_eq!(replay_offset(3_000, 1_000, 1.0), Duration::ZERO);
+
+        let path = std::env::temp_dir().join(format!("mempool_capture_{}.jsonl", std::process::id()));
+        let mut contents = String::new();
+        for (i, received_ms) in [1_000u64, 1_020, 1_010].into_iter().enumerate() {
+            let tx = Transaction { nonce: i.into(), ..Default::default() };
+            contents.push_str(&serde_json::to_string(&CapturedTx { received_ms, tx }).unwrap());
+            contents.push('\n');
+        }
+        contents.push_str("not json\n");
+        std::fs::write(&path, contents).unwrap();
+
+        let (replay, mut rx) = CaptureReplay::new(&path, 0.0);
+        assert_eq!(replay.run().await.unwrap(), 3);
+        let mut nonces = Vec::new();
+        while let Some(tx) = rx.recv().await {
+            nonces.push(tx.nonce.as_u64());
+        }
+        assert_eq!(nonces, vec![0, 1, 2]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recorded_capture_replays_with_heads() {
+        let path = std::env::temp_dir().join(format!("mempool_record_{}.jsonl", std::process::id()));
+        let mut writer = CaptureWriter::create(&path).unwrap();
+        let tx = |nonce: u64| Transaction { nonce: nonce.into(), ..Default::default() };
+        writer.write(&CaptureRecord::Tx(Box::new(CapturedTx { received_ms: 1_000, tx: tx(0) }))).unwrap();
+        writer.write(&CaptureRecord::Block(CapturedBlock { received_ms: 1_005, block: 42 })).unwrap();
+        writer.write(&CaptureRecord::Tx(Box::new(CapturedTx { received_ms: 1_010, tx: tx(1) }))).unwrap();
+        assert_eq!(writer.finish().unwrap(), (2, 1));
+
+        let (replay, mut rx) = CaptureReplay::new(&path, 0.0);
+        assert_eq!(replay.run().await.unwrap(), 2);
+        assert_eq!(rx.recv().await.unwrap().block_number, None);
+        assert_eq!(rx.recv().await.unwrap().block_number, Some(U64::from(42)));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
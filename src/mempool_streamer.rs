@@ -1,56 +1,272 @@
 use anyhow::Result;
+use ethers::abi::AbiEncode;
 use ethers::types::{Address, Transaction, H256, U256, Bytes};
-use tokio::sync::mpsc;
-use tracing::{debug, info};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+use tracing::info;
 use std::time::Duration;
 
+use crate::backpressure::{self, BufferConfig, TxReceiver, TxSender};
+use crate::nested_calls::{self, ProtocolCall};
+use crate::blockchain::{BorrowCall, DepositCall, RepayCall, TransferCall, WithdrawCall};
+use crate::population::UserPopulation;
+use crate::selectors::selector;
+
+/// How synthetic transactions are spaced in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArrivalProcess {
+    /// Evenly spaced at the target rate
+    Fixed,
+    /// Exponential gaps averaging the target rate
+    Poisson,
+    /// Runs of `burst_size` back-to-back transactions, the runs arriving as a
+    /// Poisson process so the mean rate is still the target
+    Bursty { burst_size: usize },
+    /// No delay at all, for pure throughput benchmarks
+    Firehose,
+}
+
+/// Distributions driving synthetic mempool traffic
+///
+/// The same profile and seed always produce the same transaction stream,
+/// so backtests are reproducible and can be swept over parameters.
+#[derive(Debug, Clone)]
+pub struct TrafficProfile {
+    /// RNG seed for senders, amounts, transaction types and arrival jitter
+    pub seed: u64,
+    /// Relative weights for deposit / borrow / withdraw / repay
+    pub tx_mix: [u32; 4],
+    /// Number of distinct sender addresses
+    pub user_population: usize,
+    /// Deposit size range in ETH
+    pub deposit_eth: (f64, f64),
+    /// Borrow size range in USD
+    pub borrow_usd: (f64, f64),
+    /// Withdraw size range in ETH
+    pub withdraw_eth: (f64, f64),
+    /// Repay size range in USD
+    pub repay_usd: (f64, f64),
+    /// Mean transactions per second (ignored by `Firehose`)
+    pub target_tps: f64,
+    pub arrivals: ArrivalProcess,
+    /// Share of transactions that are unrelated traffic (token transfers, DEX
+    /// swaps, other contracts) the classifier has to filter out
+    pub noise_ratio: f64,
+}
+
+impl Default for TrafficProfile {
+    fn default() -> Self {
+        // Mirrors the original fixed pattern: 40% deposit, 30% borrow, 20% withdraw, 10% repay
+        Self {
+            seed: 42,
+            tx_mix: [4, 3, 2, 1],
+            user_population: 1_000,
+            deposit_eth: (1.0, 1.0),
+            borrow_usd: (1000.0, 1000.0),
+            withdraw_eth: (0.5, 0.5),
+            repay_usd: (500.0, 500.0),
+            target_tps: 10_000.0,
+            arrivals: ArrivalProcess::Fixed,
+            noise_ratio: 0.0,
+        }
+    }
+}
+
+impl TrafficProfile {
+    /// Pick a transaction type according to `tx_mix`
+    fn sample_type(&self, rng: &mut StdRng) -> TransactionType {
+        let total: u32 = self.tx_mix.iter().sum();
+        let mut roll = rng.gen_range(0..total.max(1));
+        let types = [
+            TransactionType::Deposit,
+            TransactionType::Borrow,
+            TransactionType::Withdraw,
+            TransactionType::Repay,
+        ];
+        
+        for (weight, tx_type) in self.tx_mix.iter().zip(types) {
+            if roll < *weight {
+                return tx_type;
+            }
+            roll -= weight;
+        }
+        TransactionType::Deposit
+    }
+    
+    /// Gap after the `index`th transaction
+    fn sample_interarrival(&self, index: usize, rng: &mut StdRng) -> Duration {
+        let mean_us = 1e6 / self.target_tps.max(f64::MIN_POSITIVE);
+        let gap_us = match self.arrivals {
+            ArrivalProcess::Fixed => mean_us,
+            ArrivalProcess::Poisson => exponential(rng, mean_us),
+            ArrivalProcess::Bursty { burst_size } => {
+                let burst_size = burst_size.max(1);
+                if !(index + 1).is_multiple_of(burst_size) {
+                    return Duration::ZERO;
+                }
+                exponential(rng, mean_us * burst_size as f64)
+            }
+            ArrivalProcess::Firehose => return Duration::ZERO,
+        };
+        Duration::from_secs_f64(gap_us.min(u64::MAX as f64) / 1e6)
+    }
+}
+
+/// Mainnet USDC, USDT, DAI and WETH, targets of synthetic token transfers
+const NOISE_TOKENS: [&str; 4] = [
+    "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+    "0xdac17f958d2ee523a2206206994597c13d831ec7",
+    "0x6b175474e89094c44da98b954eedeac495271d0f",
+    "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+];
+/// Mainnet Uniswap V2 router, target of synthetic swaps
+const UNISWAP_V2_ROUTER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+const SWAP_EXACT_TOKENS_SELECTOR: [u8; 4] = selector("swapExactTokensForTokens(uint256,uint256,address[],address,uint256)");
+
+/// Calldata and target of one unrelated transaction: an ERC20 transfer, a
+/// Uniswap V2 swap or a call to an arbitrary contract
+fn noise_call(rng: &mut StdRng, from: Address) -> (Address, Bytes) {
+    let token = |rng: &mut StdRng| NOISE_TOKENS[rng.gen_range(0..NOISE_TOKENS.len())].parse().unwrap();
+    match rng.gen_range(0..3) {
+        0 => {
+            let to = Address::from(rng.gen::<[u8; 20]>());
+            let amount = sample_amount(rng, (1.0, 10_000.0));
+            (token(rng), TransferCall { to, amount }.encode().into())
+        }
+        1 => {
+            use ethers::abi::Token;
+            let path = vec![Token::Address(token(rng)), Token::Address(token(rng))];
+            let args = ethers::abi::encode(&[
+                Token::Uint(sample_amount(rng, (1.0, 10_000.0))),
+                Token::Uint(U256::zero()),
+                Token::Array(path),
+                Token::Address(from),
+                Token::Uint(U256::MAX),
+            ]);
+            (UNISWAP_V2_ROUTER.parse().unwrap(), [&SWAP_EXACT_TOKENS_SELECTOR[..], &args].concat().into())
+        }
+        _ => {
+            let input: Vec<u8> = (0..4 + 32 * rng.gen_range(0..4)).map(|_| rng.gen()).collect();
+            (Address::from(rng.gen::<[u8; 20]>()), input.into())
+        }
+    }
+}
+
+/// Inverse-CDF sample of an exponential distribution with mean `mean`
+fn exponential(rng: &mut StdRng, mean: f64) -> f64 {
+    let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+    -u.ln() * mean
+}
+
+/// Uniform sample from `range`, scaled to 18-decimal fixed point
+fn sample_amount(rng: &mut StdRng, range: (f64, f64)) -> U256 {
+    let value = if range.1 > range.0 { rng.gen_range(range.0..range.1) } else { range.0 };
+    U256::from((value * 1e6) as u128) * U256::exp10(12)
+}
+
 /// Simulated mempool transaction streamer
 /// In production, this would connect to a real mempool provider (Alchemy, Infura, etc.)
 pub struct MempoolStreamer {
     protocol_address: Address,
-    tx_sender: mpsc::Sender<Transaction>,
+    tx_sender: TxSender,
+    profile: TrafficProfile,
+    population: Option<Arc<UserPopulation>>,
 }
 
 impl MempoolStreamer {
-    pub fn new(protocol_address: Address) -> (Self, mpsc::Receiver<Transaction>) {
-        let (tx_sender, rx) = mpsc::channel(1000);
+    pub fn new(protocol_address: Address) -> (Self, TxReceiver) {
+        Self::with_profile(protocol_address, TrafficProfile::default())
+    }
+    
+    pub fn with_profile(
+        protocol_address: Address,
+        profile: TrafficProfile,
+    ) -> (Self, TxReceiver) {
+        Self::with_buffer(protocol_address, profile, BufferConfig::default())
+    }
+    
+    /// Stream into a buffer that applies `buffer.policy` when the detector falls behind
+    pub fn with_buffer(
+        protocol_address: Address,
+        profile: TrafficProfile,
+        buffer: BufferConfig,
+    ) -> (Self, TxReceiver) {
+        let (tx_sender, rx) = backpressure::channel(buffer);
         
         (
             Self {
                 protocol_address,
                 tx_sender,
+                profile,
+                population: None,
             },
             rx,
         )
     }
     
+    /// Stream traffic from a persistent user population whose positions evolve
+    /// with every generated transaction (share the same population with the detector)
+    pub fn with_population(
+        protocol_address: Address,
+        profile: TrafficProfile,
+        population: Arc<UserPopulation>,
+        buffer: BufferConfig,
+    ) -> (Self, TxReceiver) {
+        let (mut streamer, rx) = Self::with_buffer(protocol_address, profile, buffer);
+        streamer.population = Some(population);
+        (streamer, rx)
+    }
+    
     /// Start streaming simulated transactions
     /// This generates synthetic mempool traffic for testing
     pub async fn start_simulation(&self, num_transactions: usize) -> Result<()> {
-        info!("Starting mempool simulation with {} transactions", num_transactions);
+        info!("Starting mempool simulation with {} transactions (seed {})",
+            num_transactions, self.profile.seed);
+        
+        let mut rng = StdRng::seed_from_u64(self.profile.seed);
+        let users = match &self.population {
+            Some(population) => population.users().to_vec(),
+            None => Self::generate_users(&mut rng, self.profile.user_population),
+        };
         
         for i in 0..num_transactions {
-            let tx = self.generate_synthetic_transaction(i);
+            let tx = self.generate_synthetic_transaction(i, &users, &mut rng);
             
-            if let Err(e) = self.tx_sender.send(tx).await {
-                tracing::error!("Failed to send transaction: {}", e);
+            if self.tx_sender.send(tx).await.is_err() {
+                tracing::error!("Failed to send transaction: receiver dropped");
                 break;
             }
             
-            // Simulate realistic transaction arrival rate (10ms between txs)
-            tokio::time::sleep(Duration::from_micros(100)).await;
+            let gap = self.profile.sample_interarrival(i, &mut rng);
+            if !gap.is_zero() {
+                tokio::time::sleep(gap).await;
+            }
         }
         
         info!("Mempool simulation complete");
         Ok(())
     }
     
+    /// Deterministic sender population drawn from the seeded RNG
+    fn generate_users(rng: &mut StdRng, count: usize) -> Vec<Address> {
+        (0..count.max(1))
+            .map(|_| Address::from(rng.gen::<[u8; 20]>()))
+            .collect()
+    }
+    
     /// Generate a synthetic transaction for testing
-    fn generate_synthetic_transaction(&self, nonce: usize) -> Transaction {
+    fn generate_synthetic_transaction(
+        &self,
+        nonce: usize,
+        users: &[Address],
+        rng: &mut StdRng,
+    ) -> Transaction {
         use ethers::utils::keccak256;
         
         // Generate different transaction types
-        let tx_type = nonce % 10;
+        let tx_type = self.profile.sample_type(rng);
+        let from = users[rng.gen_range(0..users.len())];
         
         let mut tx = Transaction {
             hash: H256::from_slice(&keccak256(nonce.to_le_bytes())),
@@ -58,7 +274,7 @@ impl MempoolStreamer {
             block_hash: None,
             block_number: None,
             transaction_index: None,
-            from: Address::random(),
+            from,
             to: Some(self.protocol_address),
             value: U256::zero(),
             gas_price: Some(U256::from(50_000_000_000u64)), // 50 gwei
@@ -75,24 +291,42 @@ impl MempoolStreamer {
             other: Default::default(),
         };
         
+        // Unrelated traffic only costs the classifier; it leaves positions alone
+        if self.profile.noise_ratio > 0.0 && rng.gen_bool(self.profile.noise_ratio.min(1.0)) {
+            let (to, input) = noise_call(rng, from);
+            tx.to = Some(to);
+            tx.input = input;
+            return tx;
+        }
+        
+        // Stateful users size amounts against their position; otherwise draw independently
+        let amount = match &self.population {
+            Some(population) => {
+                population.step_price(rng);
+                population.apply(rng, from, tx_type, &self.profile)
+            }
+            None => sample_amount(rng, match tx_type {
+                TransactionType::Deposit => self.profile.deposit_eth,
+                TransactionType::Borrow => self.profile.borrow_usd,
+                TransactionType::Withdraw => self.profile.withdraw_eth,
+                _ => self.profile.repay_usd,
+            }),
+        };
+        
         // Generate different function calls
         match tx_type {
-            0..=3 => {
-                // Deposit transaction
+            TransactionType::Deposit => {
                 tx.input = self.encode_deposit_call();
-                tx.value = U256::from(1_000_000_000_000_000_000u64); // 1 ETH
+                tx.value = amount;
             }
-            4..=6 => {
-                // Borrow transaction
-                tx.input = self.encode_borrow_call(U256::from(1000) * U256::from(10u64.pow(18)));
+            TransactionType::Borrow => {
+                tx.input = self.encode_borrow_call(amount);
             }
-            7..=8 => {
-                // Withdraw transaction
-                tx.input = self.encode_withdraw_call(U256::from(500_000_000_000_000_000u64));
+            TransactionType::Withdraw => {
+                tx.input = self.encode_withdraw_call(amount);
             }
             _ => {
-                // Repay transaction
-                tx.input = self.encode_repay_call(U256::from(500) * U256::from(10u64.pow(18)));
+                tx.input = self.encode_repay_call(amount);
             }
         }
         
@@ -100,38 +334,32 @@ impl MempoolStreamer {
     }
     
     fn encode_deposit_call(&self) -> Bytes {
-        // deposit() function selector: 0xd0e30db0
-        Bytes::from(hex::decode("d0e30db0").unwrap())
+        DepositCall.encode().into()
     }
     
     fn encode_borrow_call(&self, amount: U256) -> Bytes {
-        // borrow(uint256) function selector: 0xc5ebeaec
-        let mut data = hex::decode("c5ebeaec").unwrap();
-        let mut amount_bytes = [0u8; 32];
-        amount.to_big_endian(&mut amount_bytes);
-        data.extend_from_slice(&amount_bytes);
-        Bytes::from(data)
+        BorrowCall { amount }.encode().into()
     }
     
     fn encode_withdraw_call(&self, amount: U256) -> Bytes {
-        // withdraw(uint256) function selector: 0x2e1a7d4d
-        let mut data = hex::decode("2e1a7d4d").unwrap();
-        let mut amount_bytes = [0u8; 32];
-        amount.to_big_endian(&mut amount_bytes);
-        data.extend_from_slice(&amount_bytes);
-        Bytes::from(data)
+        WithdrawCall { amount }.encode().into()
     }
     
     fn encode_repay_call(&self, amount: U256) -> Bytes {
-        // repay(uint256) function selector: 0x371fd8e6
-        let mut data = hex::decode("371fd8e6").unwrap();
-        let mut amount_bytes = [0u8; 32];
-        amount.to_big_endian(&mut amount_bytes);
-        data.extend_from_slice(&amount_bytes);
-        Bytes::from(data)
+        RepayCall { amount }.encode().into()
     }
 }
 
+// Selectors as constants so classification is a plain match, derived from
+// their signatures at compile time; the tests check them against the ABI
+// bindings in `blockchain`
+
+pub const DEPOSIT_SELECTOR: [u8; 4] = selector("deposit()");
+pub const BORROW_SELECTOR: [u8; 4] = selector("borrow(uint256)");
+pub const WITHDRAW_SELECTOR: [u8; 4] = selector("withdraw(uint256)");
+pub const REPAY_SELECTOR: [u8; 4] = selector("repay(uint256)");
+pub const LIQUIDATE_SELECTOR: [u8; 4] = selector("liquidate(address,uint256)");
+
 /// Transaction classifier to identify relevant transactions
 pub struct TransactionClassifier;
 
@@ -141,24 +369,37 @@ impl TransactionClassifier {
         tx.to.map(|addr| addr == protocol_address).unwrap_or(false)
     }
     
+    /// Calls `tx` makes to the protocol, directly or through multicalls, smart
+    /// accounts and 4337 bundles, each with the caller whose position it changes
+    pub fn protocol_calls(tx: &Transaction, protocol_address: Address) -> Vec<ProtocolCall> {
+        match tx.to {
+            Some(to) => nested_calls::protocol_calls(tx.from, to, &tx.input, protocol_address),
+            None => Vec::new(),
+        }
+    }
+    
     /// Classify transaction type based on function selector
     pub fn classify_transaction(tx: &Transaction) -> Option<TransactionType> {
-        if tx.input.len() < 4 {
-            return None;
-        }
-        
-        let selector = &tx.input[..4];
-        
-        match selector {
-            [0xd0, 0xe3, 0x0d, 0xb0] => Some(TransactionType::Deposit),
-            [0xc5, 0xeb, 0xea, 0xec] => Some(TransactionType::Borrow),
-            [0x2e, 0x1a, 0x7d, 0x4d] => Some(TransactionType::Withdraw),
-            [0x37, 0x1f, 0xd8, 0xe6] => Some(TransactionType::Repay),
-            [0x26, 0xcd, 0xbe, 0x1a] => Some(TransactionType::Liquidate),
+        Self::classify_calldata(&tx.input)
+    }
+    
+    /// Classify raw calldata by its selector without copying or allocating
+    pub fn classify_calldata(input: &[u8]) -> Option<TransactionType> {
+        match *input.first_chunk::<4>()? {
+            DEPOSIT_SELECTOR => Some(TransactionType::Deposit),
+            BORROW_SELECTOR => Some(TransactionType::Borrow),
+            WITHDRAW_SELECTOR => Some(TransactionType::Withdraw),
+            REPAY_SELECTOR => Some(TransactionType::Repay),
+            LIQUIDATE_SELECTOR => Some(TransactionType::Liquidate),
             _ => None,
         }
     }
     
+    /// The `uint256` amount argument of borrow/withdraw/repay calldata
+    pub fn decode_amount(input: &[u8]) -> Option<U256> {
+        input.get(4..36).map(U256::from_big_endian)
+    }
+    
     /// Extract user address from transaction for position tracking
     pub fn extract_user_address(tx: &Transaction) -> Address {
         tx.from
@@ -180,15 +421,145 @@ mod tests {
 
     #[test]
     fn test_transaction_classification() {
-        let mut tx = Transaction::default();
-        
         // Test deposit
-        tx.input = Bytes::from(hex::decode("d0e30db0").unwrap());
+        let mut tx = Transaction {
+            input: Bytes::from(hex::decode("d0e30db0").unwrap()),
+            ..Default::default()
+        };
         assert_eq!(TransactionClassifier::classify_transaction(&tx), Some(TransactionType::Deposit));
         
         // Test borrow
         tx.input = Bytes::from(hex::decode("c5ebeaec0000000000000000000000000000000000000000000000000000000000000001").unwrap());
         assert_eq!(TransactionClassifier::classify_transaction(&tx), Some(TransactionType::Borrow));
+        assert_eq!(TransactionClassifier::decode_amount(&tx.input), Some(U256::one()));
+        assert_eq!(BorrowCall { amount: U256::one() }.encode(), tx.input.to_vec());
+        
+        // Truncated calldata is rejected, not panicked on
+        assert_eq!(TransactionClassifier::classify_calldata(&[0xd0, 0xe3]), None);
+        assert_eq!(TransactionClassifier::decode_amount(&tx.input[..20]), None);
     }
-}
+    
+    #[test]
+    fn test_selectors_and_abi_round_trip() {
+        use crate::blockchain::{LendingProtocolCalls, LiquidateCall};
+        use ethers::abi::AbiDecode;
+        use ethers::contract::EthCall;
 
+        assert_eq!(DEPOSIT_SELECTOR, DepositCall::selector());
+        assert_eq!(BORROW_SELECTOR, BorrowCall::selector());
+        assert_eq!(WITHDRAW_SELECTOR, WithdrawCall::selector());
+        assert_eq!(REPAY_SELECTOR, RepayCall::selector());
+        assert_eq!(LIQUIDATE_SELECTOR, LiquidateCall::selector());
+
+        let (streamer, _rx) = MempoolStreamer::new(Address::zero());
+        let amount = U256::from(1_234_567u64) * U256::exp10(18);
+        let calls = [
+            (streamer.encode_deposit_call(), TransactionType::Deposit, LendingProtocolCalls::Deposit(DepositCall)),
+            (streamer.encode_borrow_call(amount), TransactionType::Borrow, LendingProtocolCalls::Borrow(BorrowCall { amount })),
+            (streamer.encode_withdraw_call(amount), TransactionType::Withdraw, LendingProtocolCalls::Withdraw(WithdrawCall { amount })),
+            (streamer.encode_repay_call(amount), TransactionType::Repay, LendingProtocolCalls::Repay(RepayCall { amount })),
+        ];
+        for (input, kind, call) in calls {
+            assert_eq!(TransactionClassifier::classify_calldata(&input), Some(kind));
+            assert_eq!(LendingProtocolCalls::decode(&input).unwrap(), call);
+            if kind != TransactionType::Deposit {
+                assert_eq!(TransactionClassifier::decode_amount(&input), Some(amount));
+            }
+        }
+    }
+    
+    proptest::proptest! {
+        // Mempool calldata is whatever anyone sent; any bytes, with or without a
+        // known selector in front, must classify or not without panicking
+        #[test]
+        fn prop_classifier_accepts_arbitrary_calldata(
+            selector in proptest::sample::select(vec![DEPOSIT_SELECTOR, BORROW_SELECTOR, WITHDRAW_SELECTOR, REPAY_SELECTOR, LIQUIDATE_SELECTOR, [0xff; 4]]),
+            prefix in 0usize..=4,
+            tail in proptest::collection::vec(proptest::num::u8::ANY, 0..80),
+        ) {
+            let input = [&selector[..prefix], &tail[..]].concat();
+            let kind = TransactionClassifier::classify_calldata(&input);
+            if prefix == 4 {
+                proptest::prop_assert_eq!(kind.is_some(), selector != [0xff; 4]);
+            }
+            proptest::prop_assert_eq!(TransactionClassifier::decode_amount(&input).is_some(), input.len() >= 36);
+            let tx = Transaction { input: input.into(), ..Default::default() };
+            proptest::prop_assert_eq!(TransactionClassifier::classify_transaction(&tx), kind);
+        }
+    }
+    
+    #[test]
+    fn test_arrival_processes() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let profile = |arrivals| TrafficProfile { target_tps: 10_000.0, arrivals, ..Default::default() };
+        
+        assert_eq!(profile(ArrivalProcess::Fixed).sample_interarrival(0, &mut rng), Duration::from_micros(100));
+        assert_eq!(profile(ArrivalProcess::Firehose).sample_interarrival(0, &mut rng), Duration::ZERO);
+        
+        // Both average the target rate over many arrivals
+        for arrivals in [ArrivalProcess::Poisson, ArrivalProcess::Bursty { burst_size: 5 }] {
+            let profile = profile(arrivals);
+            let gaps: Vec<Duration> = (0..50_000).map(|i| profile.sample_interarrival(i, &mut rng)).collect();
+            let mean_us = gaps.iter().sum::<Duration>().as_secs_f64() * 1e6 / gaps.len() as f64;
+            assert!((mean_us - 100.0).abs() < 5.0, "{:?}: mean gap {}µs", arrivals, mean_us);
+        }
+        
+        // Only the last transaction of each burst waits
+        let bursty = profile(ArrivalProcess::Bursty { burst_size: 5 });
+        assert!((0..4).all(|i| bursty.sample_interarrival(i, &mut rng).is_zero()));
+    }
+    
+    #[tokio::test]
+    async fn test_noise_traffic_is_filtered() {
+        let protocol = Address::from_low_u64_be(0xbeef);
+        let profile = TrafficProfile { arrivals: ArrivalProcess::Firehose, noise_ratio: 0.5, ..Default::default() };
+        let (streamer, mut rx) = MempoolStreamer::with_buffer(
+            protocol,
+            profile,
+            BufferConfig { capacity: 2_000, ..Default::default() },
+        );
+        streamer.start_simulation(1_000).await.unwrap();
+        drop(streamer);
+        
+        let (mut relevant, mut noise) = (0, 0);
+        while let Some(tx) = rx.recv().await {
+            if TransactionClassifier::is_protocol_transaction(&tx, protocol) {
+                assert!(TransactionClassifier::classify_transaction(&tx).is_some());
+                relevant += 1;
+            } else {
+                noise += 1;
+            }
+        }
+        assert_eq!(relevant + noise, 1_000);
+        assert!((400..600).contains(&noise), "{} noise transactions", noise);
+    }
+    
+    #[tokio::test]
+    async fn test_seeded_stream_is_reproducible() {
+        let profile = TrafficProfile {
+            seed: 7,
+            arrivals: ArrivalProcess::Firehose,
+            borrow_usd: (100.0, 5000.0),
+            ..Default::default()
+        };
+        
+        let collect = |profile: TrafficProfile| async move {
+            let (streamer, mut rx) = MempoolStreamer::with_profile(Address::zero(), profile);
+            streamer.start_simulation(50).await.unwrap();
+            drop(streamer);
+            let mut txs = Vec::new();
+            while let Some(tx) = rx.recv().await {
+                txs.push((tx.from, tx.input, tx.value));
+            }
+            txs
+        };
+        
+        let first = collect(profile.clone()).await;
+        let second = collect(profile.clone()).await;
+        let reseeded = collect(TrafficProfile { seed: 8, ..profile }).await;
+        
+        assert_eq!(first.len(), 50);
+        assert_eq!(first, second);
+        assert_ne!(first, reseeded);
+    }
+}
@@ -1,7 +1,85 @@
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, Instant};
-use std::collections::HashMap;
-use tracing::info;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use tokio::sync::watch;
+use ethers::types::Address;
+use tracing::{info, warn};
+
+use crate::allocation::{AllocMark, MemoryProfile};
+use crate::backpressure::BackpressureStats;
+use crate::calibration::{CalibrationStats, SimulationDelta};
+use crate::competition::CompetitionStats;
+use crate::inclusion::{InclusionOutcome, InclusionStats, Settlement, SettlementStats, SubmittedLiquidation};
+use crate::liquidation_detector::StaleStats;
+use crate::opportunities::{self, ProtocolOpportunities, RankedOpportunity};
+use crate::protocol::NATIVE_ASSET;
+use crate::priority::QueueStats;
+use crate::read_cache::CacheStats;
+use crate::rpc_batch::BatchStats;
+use crate::run_metadata::RunMetadata;
+use crate::simulation_pool::WorkerStats;
+use crate::submission::RouteStats;
+use crate::prometheus::{MetricKind, EXPECTED_PROFIT, INCLUSION_OUTCOMES, METRICS, STAGES};
+
+/// Wall clock and monotonic clock may disagree by this much over one pipeline
+/// run before it counts as a clock step (NTP slew stays well below it)
+const CLOCK_STEP_TOLERANCE: Duration = Duration::from_millis(1);
+
+/// Stages allocations are attributed to, each between consecutive marks
+/// (received, decoded, signal, simulated, constructed, sent)
+const ALLOCATION_STAGES: [&str; 5] = ["decode", "signal_detection", "simulation", "construction", "submission"];
+
+/// Report stage latencies with nanosecond instead of whole-microsecond precision
+static NANOSECOND_PRECISION: AtomicBool = AtomicBool::new(false);
+
+/// Switch every `LatencyMetrics` to nanosecond-precision reporting
+///
+/// Stages are always timed with `Instant` (CLOCK_MONOTONIC, nanosecond
+/// resolution on Linux); by default they are truncated to whole microseconds.
+pub fn set_nanosecond_precision(enabled: bool) {
+    NANOSECOND_PRECISION.store(enabled, Ordering::Relaxed);
+}
+
+/// Wall-clock milliseconds since the Unix epoch, as stamped on journals and probes
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn unix_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Time from sending a transaction to the timestamp of the block including it
+///
+/// Block timestamps have one-second resolution, so this is accurate to about a
+/// second; a block stamped before the send counts as zero.
+pub fn inclusion_latency(sent_unix_ns: u64, block_timestamp: u64) -> Duration {
+    Duration::from_nanos((block_timestamp * 1_000_000_000).saturating_sub(sent_unix_ns))
+}
+
+/// Timing inconsistency found by `LatencyMetrics::clock_anomalies`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClockAnomaly {
+    /// A stage was marked before the stage preceding it
+    OutOfOrder { stage: &'static str },
+    /// Wall-clock time between receive and send differs from the monotonic time
+    /// by more than the tolerance (the system clock was stepped)
+    WallClockStep { drift_us: i64 },
+    /// The including block is stamped more than a second before the send,
+    /// meaning the host clock runs ahead of the chain's
+    InclusionBeforeSend { skew_ms: u64 },
+}
 
 /// High-precision latency tracking for liquidation pipeline
 #[derive(Debug, Clone)]
@@ -18,6 +96,13 @@ pub struct LatencyMetrics {
     pub t_constructed: Option<Instant>,
     #[allow(dead_code)]
     pub t_sent: Option<Instant>,
+    /// Wall-clock anchors, to compare against block timestamps
+    pub received_unix_ns: u64,
+    pub sent_unix_ns: Option<u64>,
+    /// Timestamp (seconds) of the block that included the transaction
+    pub included_block_timestamp: Option<u64>,
+    /// Thread allocation counters at each mark, with allocation profiling on
+    alloc_marks: [Option<AllocMark>; 6],
 }
 
 impl LatencyMetrics {
@@ -29,27 +114,52 @@ impl LatencyMetrics {
             t_simulated: None,
             t_constructed: None,
             t_sent: None,
+            received_unix_ns: unix_ns(),
+            sent_unix_ns: None,
+            included_block_timestamp: None,
+            alloc_marks: [AllocMark::now(), None, None, None, None, None],
         }
     }
     
     pub fn mark_decoded(&mut self) {
         self.t_decoded = Some(Instant::now());
+        self.alloc_marks[1] = AllocMark::now();
     }
     
     pub fn mark_signal(&mut self) {
         self.t_signal = Some(Instant::now());
+        self.alloc_marks[2] = AllocMark::now();
     }
     
     pub fn mark_simulated(&mut self) {
         self.t_simulated = Some(Instant::now());
+        self.alloc_marks[3] = AllocMark::now();
     }
     
     pub fn mark_constructed(&mut self) {
         self.t_constructed = Some(Instant::now());
+        self.alloc_marks[4] = AllocMark::now();
     }
     
     pub fn mark_sent(&mut self) {
         self.t_sent = Some(Instant::now());
+        self.sent_unix_ns = Some(unix_ns());
+        self.alloc_marks[5] = AllocMark::now();
+    }
+    
+    /// Allocations, bytes and microseconds of each stage that ran on one thread
+    pub fn stage_allocations(&self) -> impl Iterator<Item = (&'static str, u64, u64, f64)> + '_ {
+        let times = [Some(self.t_received), self.t_decoded, self.t_signal, self.t_simulated, self.t_constructed, self.t_sent];
+        ALLOCATION_STAGES.iter().enumerate().filter_map(move |(i, stage)| {
+            let (allocations, bytes) = self.alloc_marks[i]?.until(&self.alloc_marks[i + 1]?)?;
+            let busy = times[i + 1]?.saturating_duration_since(times[i]?);
+            Some((*stage, allocations, bytes, busy.as_secs_f64() * 1e6))
+        })
+    }
+    
+    /// Record the timestamp of the block the transaction landed in
+    pub fn mark_included(&mut self, block_timestamp: u64) {
+        self.included_block_timestamp = Some(block_timestamp);
     }
     
     /// Calculate latency from received to decoded
@@ -89,24 +199,94 @@ impl LatencyMetrics {
         self.t_sent.map(|t| t.duration_since(self.t_received))
     }
     
+    /// Time the submission route took to accept the constructed transaction, resends included
+    pub fn latency_submission(&self) -> Option<Duration> {
+        if let (Some(constructed), Some(sent)) = (self.t_constructed, self.t_sent) {
+            Some(sent.duration_since(constructed))
+        } else {
+            None
+        }
+    }
+    
+    /// Time from send to the including block's timestamp
+    pub fn latency_submit_to_inclusion(&self) -> Option<Duration> {
+        match (self.sent_unix_ns, self.included_block_timestamp) {
+            (Some(sent), Some(block_timestamp)) => Some(inclusion_latency(sent, block_timestamp)),
+            _ => None,
+        }
+    }
+    
+    /// Check the recorded stages for ordering violations and clock steps
+    pub fn clock_anomalies(&self) -> Vec<ClockAnomaly> {
+        let mut anomalies = Vec::new();
+        
+        let stages = [
+            ("decoded", self.t_decoded),
+            ("signal", self.t_signal),
+            ("simulated", self.t_simulated),
+            ("constructed", self.t_constructed),
+            ("sent", self.t_sent),
+        ];
+        let mut previous = self.t_received;
+        for (stage, t) in stages {
+            if let Some(t) = t {
+                if t < previous {
+                    anomalies.push(ClockAnomaly::OutOfOrder { stage });
+                }
+                previous = previous.max(t);
+            }
+        }
+        
+        if let (Some(sent), Some(monotonic)) = (self.sent_unix_ns, self.latency_end_to_end()) {
+            let wall_ns = sent as i128 - self.received_unix_ns as i128;
+            let drift_ns = wall_ns - monotonic.as_nanos() as i128;
+            if drift_ns.unsigned_abs() > CLOCK_STEP_TOLERANCE.as_nanos() {
+                anomalies.push(ClockAnomaly::WallClockStep { drift_us: (drift_ns / 1000) as i64 });
+            }
+        }
+        
+        if let (Some(sent), Some(block_timestamp)) = (self.sent_unix_ns, self.included_block_timestamp) {
+            // Allow for the block timestamp's one-second resolution
+            let skew_ns = sent.saturating_sub((block_timestamp + 1) * 1_000_000_000);
+            if skew_ns > 0 {
+                anomalies.push(ClockAnomaly::InclusionBeforeSend { skew_ms: skew_ns / 1_000_000 });
+            }
+        }
+        
+        anomalies
+    }
+    
     /// Get all latencies as a map
     pub fn get_all_latencies(&self) -> HashMap<String, f64> {
         let mut map = HashMap::new();
+        let micros = if NANOSECOND_PRECISION.load(Ordering::Relaxed) {
+            |d: Duration| d.as_nanos() as f64 / 1000.0
+        } else {
+            |d: Duration| d.as_micros() as f64
+        };
         
         if let Some(d) = self.latency_decode() {
-            map.insert("decode_us".to_string(), d.as_micros() as f64);
+            map.insert("decode_us".to_string(), micros(d));
         }
         if let Some(d) = self.latency_signal_detection() {
-            map.insert("signal_detection_us".to_string(), d.as_micros() as f64);
+            map.insert("signal_detection_us".to_string(), micros(d));
         }
         if let Some(d) = self.latency_simulation() {
-            map.insert("simulation_us".to_string(), d.as_micros() as f64);
+            map.insert("simulation_us".to_string(), micros(d));
         }
         if let Some(d) = self.latency_construction() {
-            map.insert("construction_us".to_string(), d.as_micros() as f64);
+            map.insert("construction_us".to_string(), micros(d));
         }
         if let Some(d) = self.latency_end_to_end() {
-            map.insert("end_to_end_us".to_string(), d.as_micros() as f64);
+            map.insert("end_to_end_us".to_string(), micros(d));
+        }
+        if let Some(d) = self.latency_submit_to_inclusion() {
+            map.insert("submit_to_inclusion_us".to_string(), micros(d));
+        }
+        
+        let anomalies = self.clock_anomalies();
+        if !anomalies.is_empty() {
+            map.insert("clock_anomalies".to_string(), anomalies.len() as f64);
         }
         
         map
@@ -119,22 +299,166 @@ impl Default for LatencyMetrics {
     }
 }
 
+/// Dimensions latency and outcome metrics are broken down by
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct MetricLabels {
+    /// `ProtocolAdapter::name` of the lending market
+    pub protocol: String,
+    pub chain: String,
+    /// Collateral/debt asset pair, e.g. `ETH/0x5fbd…`
+    pub market: String,
+}
+
+impl MetricLabels {
+    pub fn new(protocol: &str, chain_id: u64) -> Self {
+        Self { protocol: protocol.to_string(), chain: chain_id.to_string(), market: String::new() }
+    }
+
+    /// These labels narrowed to the market seizing `collateral_asset` for `debt_asset`
+    pub fn for_market(&self, collateral_asset: Address, debt_asset: Address) -> Self {
+        let asset = |a: Address| if a == NATIVE_ASSET { "ETH".to_string() } else { format!("{:?}", a) };
+        Self { market: format!("{}/{}", asset(collateral_asset), asset(debt_asset)), ..self.clone() }
+    }
+
+    /// `protocol/chain/market`, the key of `AggregateMetrics::markets`
+    pub fn key(&self) -> String {
+        format!("{}/{}/{}", self.protocol, self.chain, self.market)
+    }
+}
+
+/// Attempts, outcomes and latency of one market
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketMetrics {
+    pub labels: MetricLabels,
+    pub attempts: usize,
+    pub successful: usize,
+    pub expected_profit_usd: f64,
+    pub inclusion: InclusionStats,
+    pub end_to_end_us: Vec<f64>,
+}
+
+impl MarketMetrics {
+    pub fn p99_end_to_end_us(&self) -> Option<f64> {
+        let mut values = self.end_to_end_us.clone();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        Some(values[((values.len() as f64 * 0.99) as usize).min(values.len() - 1)])
+    }
+}
+
 /// Aggregate metrics across multiple liquidation attempts
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregateMetrics {
+    /// Commit, config, chain, blocks and seed the run was produced with
+    #[serde(default)]
+    pub run: Option<RunMetadata>,
     pub total_attempts: usize,
     pub successful_liquidations: usize,
     pub failed_liquidations: usize,
+    #[serde(default)]
+    pub transactions_processed: usize,
+    #[serde(default)]
+    pub total_expected_profit_usd: f64,
+    /// Near-liquidation positions watched at the end of the run
+    #[serde(default)]
+    pub watchlist_size: usize,
+    /// Positions added to or removed from the watchlist during the run
+    #[serde(default)]
+    pub watchlist_churn: usize,
+    /// Blocks the watchlist was re-read on during the run
+    #[serde(default)]
+    pub block_sweeps: usize,
+    /// Watchlist re-reads on price ticks the sensitivity index skipped
+    #[serde(default)]
+    pub watchlist_reads_skipped: usize,
+    /// Oracle value of seized collateral lost selling it (fee and price impact);
+    /// already taken out of profit
+    #[serde(default)]
+    pub collateral_sale_cost_usd: f64,
+    /// Signals queued for a worker, and how many expired or were pushed out
+    #[serde(default)]
+    pub opportunity_queue: QueueStats,
+    /// How the buffer ahead of the detector handled overload
+    #[serde(default)]
+    pub mempool_backpressure: BackpressureStats,
+    /// RPC lookups sent in JSON-RPC batches, when batching is enabled
+    #[serde(default)]
+    pub rpc_batching: Option<BatchStats>,
+    /// Position, gas price and ETH price reads answered within their block
+    #[serde(default)]
+    pub read_cache: Option<CacheStats>,
+    /// Simulations each simulation worker ran, indexed by worker id
+    #[serde(default)]
+    pub simulation_workers: Vec<WorkerStats>,
+    /// Liquidatable positions found too many blocks old, and how they were handled
+    #[serde(default)]
+    pub stale_positions: StaleStats,
+    /// Attempts whose timestamps failed the monotonic clock audit
+    #[serde(default)]
+    pub clock_anomalies: usize,
+    /// Won/lost races of on-chain submissions
+    #[serde(default)]
+    pub inclusion: InclusionStats,
+    /// Receipts of won submissions checked against their simulation
+    #[serde(default)]
+    pub settlements: SettlementStats,
+    /// Realized against simulated profit and gas of those settlements
+    #[serde(default)]
+    pub calibration: CalibrationStats,
+    /// Auctions against rival bots, when the backtest models them
+    #[serde(default)]
+    pub competition: Option<CompetitionStats>,
+    /// Breakdown by `MetricLabels::key`, for attempts recorded with labels
+    #[serde(default)]
+    pub markets: BTreeMap<String, MarketMetrics>,
+    /// On-chain submissions by the route they were sent on
+    #[serde(default)]
+    pub routes: BTreeMap<String, RouteStats>,
+    /// Simulated opportunities per protocol, for ranking protocols by profit per gas
+    #[serde(default)]
+    pub protocols: BTreeMap<String, ProtocolOpportunities>,
+    /// Peak RSS and allocations per stage, in builds with `alloc-profiling`
+    #[serde(default)]
+    pub memory: Option<MemoryProfile>,
     pub latencies: Vec<HashMap<String, f64>>,
+    /// Market of each entry in `latencies`, for the CSV export
+    #[serde(skip)]
+    attempt_markets: Vec<Option<String>>,
 }
 
 impl AggregateMetrics {
     pub fn new() -> Self {
         Self {
+            run: None,
             total_attempts: 0,
             successful_liquidations: 0,
             failed_liquidations: 0,
+            transactions_processed: 0,
+            total_expected_profit_usd: 0.0,
+            watchlist_size: 0,
+            watchlist_churn: 0,
+            block_sweeps: 0,
+            watchlist_reads_skipped: 0,
+            collateral_sale_cost_usd: 0.0,
+            opportunity_queue: QueueStats::default(),
+            mempool_backpressure: BackpressureStats::default(),
+            rpc_batching: None,
+            read_cache: None,
+            simulation_workers: Vec::new(),
+            stale_positions: StaleStats::default(),
+            clock_anomalies: 0,
+            inclusion: InclusionStats::default(),
+            settlements: SettlementStats::default(),
+            calibration: CalibrationStats::default(),
+            competition: None,
+            markets: BTreeMap::new(),
+            routes: BTreeMap::new(),
+            protocols: BTreeMap::new(),
+            memory: crate::allocation::enabled().then(MemoryProfile::default),
             latencies: Vec::new(),
+            attempt_markets: Vec::new(),
         }
     }
     
@@ -145,7 +469,108 @@ impl AggregateMetrics {
         } else {
             self.failed_liquidations += 1;
         }
+        let anomalies = metrics.clock_anomalies();
+        if !anomalies.is_empty() {
+            warn!("Clock anomalies in pipeline timestamps: {:?}", anomalies);
+            self.clock_anomalies += 1;
+        }
+        if let Some(memory) = &mut self.memory {
+            for (stage, allocations, bytes, busy_us) in metrics.stage_allocations() {
+                memory.stage(stage).record(allocations, bytes, busy_us);
+            }
+        }
         self.latencies.push(metrics.get_all_latencies());
+        self.attempt_markets.push(None);
+    }
+    
+    /// `record_attempt`, also counted under `labels`' market
+    pub fn record_market_attempt(&mut self, labels: &MetricLabels, metrics: &LatencyMetrics, success: bool) {
+        self.record_attempt(metrics, success);
+        if let Some(market) = self.attempt_markets.last_mut() {
+            *market = Some(labels.key());
+        }
+        let market = self.market(labels);
+        market.attempts += 1;
+        market.successful += success as usize;
+        if let Some(e2e) = metrics.latency_end_to_end() {
+            market.end_to_end_us.push(e2e.as_secs_f64() * 1e6);
+        }
+    }
+    
+    /// `record_inclusion`, also counted under `labels`' market
+    pub fn record_market_inclusion(&mut self, labels: &MetricLabels, outcome: &InclusionOutcome) {
+        self.record_inclusion(outcome);
+        self.market(labels).inclusion.record(outcome);
+    }
+    
+    /// `record_profit`, also counted under `labels`' market
+    pub fn record_market_profit(&mut self, labels: &MetricLabels, profit_usd: f64) {
+        self.record_profit(profit_usd);
+        self.market(labels).expected_profit_usd += profit_usd;
+    }
+    
+    /// Count a simulated opportunity towards its protocol
+    pub fn record_opportunity(&mut self, opportunity: &RankedOpportunity) {
+        self.protocols
+            .entry(opportunity.protocol.clone())
+            .or_insert_with(|| ProtocolOpportunities { protocol: opportunity.protocol.clone(), ..Default::default() })
+            .record(opportunity);
+    }
+    
+    /// Protocols best first by expected profit per gas
+    pub fn protocol_ranking(&self) -> Vec<ProtocolOpportunities> {
+        opportunities::rank_protocols(self.protocols.values())
+    }
+    
+    fn market(&mut self, labels: &MetricLabels) -> &mut MarketMetrics {
+        self.markets
+            .entry(labels.key())
+            .or_insert_with(|| MarketMetrics { labels: labels.clone(), ..Default::default() })
+    }
+    
+    /// Record how an on-chain submission's race ended
+    pub fn record_inclusion(&mut self, outcome: &InclusionOutcome) {
+        self.inclusion.record(outcome);
+    }
+    
+    /// Count an on-chain submission, and how its race ended if it has resolved, towards its route
+    pub fn record_route(&mut self, submission: &SubmittedLiquidation, metrics: &LatencyMetrics, inclusion: Option<&InclusionOutcome>) {
+        let submission_us = metrics.latency_submission().map(|d| d.as_secs_f64() * 1e6);
+        self.routes
+            .entry(submission.route.name().to_string())
+            .or_default()
+            .record(submission_us, submission.target_block, inclusion);
+    }
+    
+    /// Record what a won liquidation realized against the profit it was expected to make
+    pub fn record_settlement(&mut self, settlement: &Settlement, expected_profit_usd: f64) {
+        self.settlements.record(settlement, expected_profit_usd);
+    }
+    
+    /// Record how far a settlement landed from its simulation
+    pub fn record_calibration(&mut self, delta: &SimulationDelta) {
+        self.calibration.record(delta);
+    }
+    
+    /// Add simulated profit from a successful liquidation
+    pub fn record_profit(&mut self, profit_usd: f64) {
+        self.total_expected_profit_usd += profit_usd;
+    }
+    
+    /// Opportunities per processed transaction (0 when no stream was processed)
+    pub fn detection_rate(&self) -> f64 {
+        if self.transactions_processed == 0 {
+            return 0.0;
+        }
+        self.total_attempts as f64 / self.transactions_processed as f64
+    }
+    
+    /// Fraction of attempts that succeeded
+    pub fn success_rate(&self) -> f64 {
+        if self.total_attempts == 0 {
+            return 0.0;
+        }
+        self.successful_liquidations as f64 / self.total_attempts as f64
     }
     
     /// Calculate percentile for a given metric
@@ -185,6 +610,117 @@ impl AggregateMetrics {
         info!("Failed: {}", self.failed_liquidations);
         info!("Success Rate: {:.2}%", 
             (self.successful_liquidations as f64 / self.total_attempts as f64) * 100.0);
+        if self.transactions_processed > 0 {
+            info!("Transactions Processed: {}", self.transactions_processed);
+            info!("Detection Rate: {:.4}%", self.detection_rate() * 100.0);
+        }
+        info!("Expected Profit: ${:.2}", self.total_expected_profit_usd);
+        if let Some(competition) = &self.competition {
+            info!("Auctions: captured {}/{} ({:.2}%), {} too slow, {} outbid",
+                competition.won, competition.races, competition.capture_rate() * 100.0,
+                competition.too_slow, competition.outbid);
+            info!("   Priority fees paid: ${:.2}, profit lost to rivals: ${:.2}",
+                competition.priority_fees_paid_usd, competition.forgone_profit_usd);
+        }
+        if self.watchlist_churn > 0 {
+            info!("Watchlist: {} positions ({} churn, {} price-tick re-reads skipped)",
+                self.watchlist_size, self.watchlist_churn, self.watchlist_reads_skipped);
+        }
+        if self.collateral_sale_cost_usd > 0.0 {
+            info!("Collateral sale cost: ${:.2} (fees and price impact)", self.collateral_sale_cost_usd);
+        }
+        if self.block_sweeps > 0 {
+            info!("Watchlist swept on {} blocks", self.block_sweeps);
+        }
+        let buffer = &self.mempool_backpressure;
+        if buffer.dropped() + buffer.blocked_sends + buffer.expansions > 0 {
+            info!("Mempool Buffer ({}): peak {} of {}, {} blocked sends, {} dropped",
+                buffer.policy, buffer.high_water_mark, buffer.capacity, buffer.blocked_sends, buffer.dropped());
+        }
+        if let Some(batching) = self.rpc_batching.filter(|b| b.batches > 0) {
+            info!("RPC Batching: {} lookups in {} batches ({:.1} per round trip)",
+                batching.requests, batching.batches, batching.requests as f64 / batching.batches as f64);
+        }
+        if let Some(cache) = self.read_cache.filter(|c| c.hits + c.misses > 0) {
+            info!("Read Cache: {} of {} reads served from memory",
+                cache.hits, cache.hits + cache.misses);
+        }
+        if self.simulation_workers.iter().filter(|worker| worker.simulations > 0).count() > 1 {
+            info!("Simulation Workers:");
+            for (id, worker) in self.simulation_workers.iter().enumerate() {
+                info!("   #{}: {} simulations ({} failed), mean {:.0}µs, max {:.0}µs",
+                    id, worker.simulations, worker.failures, worker.mean_us(), worker.max_us);
+            }
+        }
+        let stale = &self.stale_positions;
+        if stale.hits > 0 {
+            info!("Stale Positions: {} found, {} refreshed, {} down-ranked",
+                stale.hits, stale.refreshed, stale.down_ranked);
+        }
+        let queue = &self.opportunity_queue;
+        if queue.dropped_stale + queue.dropped_full > 0 {
+            info!("Opportunity Queue: {} queued, {} stale, {} pushed out when full",
+                queue.queued, queue.dropped_stale, queue.dropped_full);
+        }
+        if self.clock_anomalies > 0 {
+            info!("Clock anomalies: {} attempts", self.clock_anomalies);
+        }
+        if self.inclusion.tracked > 0 {
+            let inclusion = &self.inclusion;
+            info!("Win Rate: {:.2}% ({} won, {} lost, {} reverted, {} dropped)",
+                inclusion.win_rate() * 100.0, inclusion.won, inclusion.lost, inclusion.reverted, inclusion.dropped);
+            if let Some(gap) = inclusion.median_losing_fee_gap_gwei() {
+                info!("Losing Margin: median {:+.2} gwei tip, {}/{} lost in or before our target block",
+                    gap, inclusion.lost_same_block_or_earlier(), inclusion.lost);
+            }
+        }
+        if self.settlements.verified > 0 {
+            let settlements = &self.settlements;
+            info!("Realized Profit: ${:.2} over {} settled ({:+.2} vs expected, {} off simulation)",
+                settlements.realized_profit_usd, settlements.verified, settlements.profit_error_usd, settlements.mismatched);
+        }
+        if self.calibration.samples > 0 {
+            let calibration = &self.calibration;
+            info!("Simulation Accuracy: profit bias {:+.2} USD (MAE {:.2}), gas bias {:+.1}% (MAE {:.1}%)",
+                calibration.profit_bias_usd, calibration.profit_mae_usd,
+                calibration.gas_bias_ratio * 100.0, calibration.gas_mae_ratio * 100.0);
+        }
+        if !self.routes.is_empty() {
+            info!("\n=== Per Route ===");
+            for (route, stats) in &self.routes {
+                info!("{}: {} submitted, {:.0}us to submit, inclusion rate {:.2}%, {} blocks to inclusion",
+                    route, stats.submitted, stats.mean_submission_us(), stats.inclusion_rate() * 100.0,
+                    stats.mean_blocks_to_inclusion().map(|blocks| format!("{:.1}", blocks)).unwrap_or_else(|| "-".to_string()));
+            }
+        }
+        if !self.markets.is_empty() {
+            info!("\n=== Per Market ===");
+            for (key, market) in &self.markets {
+                info!("{}: {}/{} succeeded, ${:.2} profit, win rate {:.2}%, P99 end-to-end {}",
+                    key, market.successful, market.attempts, market.expected_profit_usd,
+                    market.inclusion.win_rate() * 100.0,
+                    market.p99_end_to_end_us().map(|us| format!("{:.2}us", us)).unwrap_or_else(|| "-".to_string()));
+            }
+        }
+        if let Some(memory) = &self.memory {
+            info!("\n=== Memory ===");
+            info!("Peak RSS: {}, {} allocations ({:.0}/s, {:.2} MiB)",
+                memory.peak_rss_bytes.map(|b| format!("{:.1} MiB", b as f64 / 1048576.0)).unwrap_or_else(|| "-".to_string()),
+                memory.allocations, memory.allocations_per_sec, memory.allocated_bytes as f64 / 1048576.0);
+            for (stage, allocations) in &memory.stages {
+                info!("{}: {:.2} allocs/op, {:.0} bytes/op, {:.0} allocs/s over {} samples",
+                    stage, allocations.allocations_per_op(), allocations.bytes_per_op(),
+                    allocations.allocations_per_sec(), allocations.samples);
+            }
+        }
+        if self.protocols.len() > 1 {
+            info!("\n=== Opportunities by Protocol (profit per Mgas) ===");
+            for (rank, protocol) in self.protocol_ranking().iter().enumerate() {
+                info!("{}. {}: ${:.2}/Mgas, {}/{} profitable, ${:.2} expected",
+                    rank + 1, protocol.protocol, protocol.profit_per_mgas_usd(),
+                    protocol.profitable, protocol.opportunities, protocol.expected_profit_usd);
+            }
+        }
         
         info!("\n=== Latency Metrics (microseconds) ===");
         
@@ -194,6 +730,7 @@ impl AggregateMetrics {
             "simulation_us",
             "construction_us",
             "end_to_end_us",
+            "submit_to_inclusion_us",
         ];
         
         for metric in metrics {
@@ -214,28 +751,37 @@ impl AggregateMetrics {
         use std::fs::File;
         use csv::Writer;
         
-        let file = File::create(filename)?;
+        use std::io::Write;
+        
+        let mut file = File::create(filename)?;
+        if let Some(run) = &self.run {
+            file.write_all(run.csv_comment().as_bytes())?;
+        }
         let mut writer = Writer::from_writer(file);
         
         // Write headers
-        writer.write_record(&[
+        writer.write_record([
             "attempt",
             "decode_us",
             "signal_detection_us",
             "simulation_us",
             "construction_us",
             "end_to_end_us",
+            "submit_to_inclusion_us",
+            "market",
         ])?;
         
         // Write data
         for (i, latency) in self.latencies.iter().enumerate() {
-            writer.write_record(&[
+            writer.write_record([
                 i.to_string(),
                 latency.get("decode_us").map(|v| v.to_string()).unwrap_or_default(),
                 latency.get("signal_detection_us").map(|v| v.to_string()).unwrap_or_default(),
                 latency.get("simulation_us").map(|v| v.to_string()).unwrap_or_default(),
                 latency.get("construction_us").map(|v| v.to_string()).unwrap_or_default(),
                 latency.get("end_to_end_us").map(|v| v.to_string()).unwrap_or_default(),
+                latency.get("submit_to_inclusion_us").map(|v| v.to_string()).unwrap_or_default(),
+                self.attempt_markets.get(i).cloned().flatten().unwrap_or_default(),
             ])?;
         }
         
@@ -250,3 +796,441 @@ impl Default for AggregateMetrics {
     }
 }
 
+/// Median and tail of one pipeline stage, in microseconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StageLatency {
+    pub p50_us: f64,
+    pub p99_us: f64,
+}
+
+/// Running PnL and recent stage latencies of the live pipeline
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LiveSnapshot {
+    pub attempts: usize,
+    pub successful: usize,
+    pub expected_profit_usd: f64,
+    /// Profit of settled liquidations, from their receipts
+    pub realized_profit_usd: f64,
+    /// Over the last `window` attempts, keyed by stage
+    pub latencies: BTreeMap<String, StageLatency>,
+}
+
+/// Spans of the rolling summaries logged in live mode
+pub const SUMMARY_WINDOWS: [Duration; 2] = [Duration::from_secs(60), Duration::from_secs(300)];
+
+/// Most events kept for the rolling summaries; past it the oldest go first
+const MAX_WINDOW_EVENTS: usize = 100_000;
+
+/// Live activity over the last `window_secs`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WindowSummary {
+    pub unix_ms: u64,
+    pub window_secs: u64,
+    /// Liquidation signals raised
+    pub detections: usize,
+    pub attempts: usize,
+    pub successful: usize,
+    /// Submissions that won their position's liquidation on chain
+    pub wins: usize,
+    pub profit_usd: f64,
+    /// Profit of settled liquidations, from their receipts
+    pub realized_profit_usd: f64,
+    pub latencies: BTreeMap<String, StageLatency>,
+}
+
+impl WindowSummary {
+    pub fn log(&self) {
+        let p99_ms = |stage: &str| self.latencies.get(stage).map(|latency| latency.p99_us / 1000.0).unwrap_or(0.0);
+        info!(
+            "Last {}s: {} detections, {} attempts ({} ok), {} wins, PnL ${:.2} (${:.2} settled), P99 end-to-end {:.2}ms, simulation {:.2}ms",
+            self.window_secs,
+            self.detections,
+            self.attempts,
+            self.successful,
+            self.wins,
+            self.profit_usd,
+            self.realized_profit_usd,
+            p99_ms("end_to_end"),
+            p99_ms("simulation"),
+        );
+    }
+}
+
+#[derive(Debug, Clone)]
+enum WindowEvent {
+    Detections(usize),
+    Attempt { success: bool, latencies: HashMap<String, f64> },
+    Win,
+    Profit(f64),
+    Settlement(f64),
+}
+
+#[derive(Debug, Default)]
+struct LiveTotals {
+    attempts: usize,
+    successful: usize,
+    expected_profit_usd: f64,
+    realized_profit_usd: f64,
+    recent: VecDeque<HashMap<String, f64>>,
+    // Timestamped, for the rolling summaries
+    events: VecDeque<(Instant, WindowEvent)>,
+}
+
+impl LiveTotals {
+    fn push(&mut self, event: WindowEvent) {
+        let now = Instant::now();
+        let longest = SUMMARY_WINDOWS.iter().max().copied().unwrap_or_default();
+        while let Some((at, _)) = self.events.front() {
+            if self.events.len() < MAX_WINDOW_EVENTS && now.duration_since(*at) <= longest {
+                break;
+            }
+            self.events.pop_front();
+        }
+        self.events.push_back((now, event));
+    }
+}
+
+/// P50 and P99 of each pipeline stage across `samples`
+fn stage_latencies<'a>(samples: impl Iterator<Item = &'a HashMap<String, f64>> + Clone) -> BTreeMap<String, StageLatency> {
+    let mut latencies = BTreeMap::new();
+    for stage in STAGES {
+        let key = format!("{}_us", stage);
+        let mut values: Vec<f64> = samples.clone().filter_map(|l| l.get(&key).copied()).collect();
+        if values.is_empty() {
+            continue;
+        }
+        values.sort_by(f64::total_cmp);
+        let at = |p: f64| values[((values.len() as f64 * p) as usize).min(values.len() - 1)];
+        latencies.insert(stage.to_string(), StageLatency { p50_us: at(0.5), p99_us: at(0.99) });
+    }
+    latencies
+}
+
+/// Live counterpart of `AggregateMetrics`, shared across backtest passes and
+/// the dashboard: each recorded attempt, profit or settlement notifies
+/// subscribers so they can push a fresh snapshot
+#[derive(Debug)]
+pub struct LiveMetrics {
+    window: usize,
+    totals: Mutex<LiveTotals>,
+    updates: watch::Sender<u64>,
+}
+
+impl LiveMetrics {
+    /// Keep latencies of the last `window` attempts
+    pub fn new(window: usize) -> Self {
+        Self { window: window.max(1), totals: Mutex::new(LiveTotals::default()), updates: watch::channel(0).0 }
+    }
+
+    pub fn record_attempt(&self, metrics: &LatencyMetrics, success: bool) {
+        {
+            let mut totals = self.totals.lock().unwrap();
+            totals.attempts += 1;
+            totals.successful += success as usize;
+            if totals.recent.len() == self.window {
+                totals.recent.pop_front();
+            }
+            let latencies = metrics.get_all_latencies();
+            totals.recent.push_back(latencies.clone());
+            totals.push(WindowEvent::Attempt { success, latencies });
+        }
+        self.notify();
+    }
+
+    /// Liquidation signals raised, before any is simulated
+    pub fn record_detections(&self, count: usize) {
+        if count > 0 {
+            self.totals.lock().unwrap().push(WindowEvent::Detections(count));
+            self.notify();
+        }
+    }
+
+    pub fn record_inclusion(&self, outcome: &InclusionOutcome) {
+        if let InclusionOutcome::Won { .. } = outcome {
+            self.totals.lock().unwrap().push(WindowEvent::Win);
+            self.notify();
+        }
+    }
+
+    pub fn record_profit(&self, profit_usd: f64) {
+        let mut totals = self.totals.lock().unwrap();
+        totals.expected_profit_usd += profit_usd;
+        totals.push(WindowEvent::Profit(profit_usd));
+        drop(totals);
+        self.notify();
+    }
+
+    pub fn record_settlement(&self, settlement: &Settlement) {
+        let mut totals = self.totals.lock().unwrap();
+        totals.realized_profit_usd += settlement.realized_profit_usd;
+        totals.push(WindowEvent::Settlement(settlement.realized_profit_usd));
+        drop(totals);
+        self.notify();
+    }
+
+    /// Changes each time something is recorded
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.updates.subscribe()
+    }
+
+    pub fn snapshot(&self) -> LiveSnapshot {
+        let totals = self.totals.lock().unwrap();
+        LiveSnapshot {
+            attempts: totals.attempts,
+            successful: totals.successful,
+            expected_profit_usd: totals.expected_profit_usd,
+            realized_profit_usd: totals.realized_profit_usd,
+            latencies: stage_latencies(totals.recent.iter()),
+        }
+    }
+
+    /// What was recorded over the last `window`
+    pub fn window_summary(&self, window: Duration) -> WindowSummary {
+        let now = Instant::now();
+        let totals = self.totals.lock().unwrap();
+        let events = totals.events.iter().rev().take_while(|(at, _)| now.duration_since(*at) <= window);
+        let mut summary = WindowSummary { unix_ms: unix_ns() / 1_000_000, window_secs: window.as_secs(), ..Default::default() };
+        for (_, event) in events.clone() {
+            match event {
+                WindowEvent::Detections(count) => summary.detections += count,
+                WindowEvent::Attempt { success, .. } => {
+                    summary.attempts += 1;
+                    summary.successful += *success as usize;
+                }
+                WindowEvent::Win => summary.wins += 1,
+                WindowEvent::Profit(profit_usd) => summary.profit_usd += profit_usd,
+                WindowEvent::Settlement(profit_usd) => summary.realized_profit_usd += profit_usd,
+            }
+        }
+        summary.latencies = stage_latencies(events.filter_map(|(_, event)| match event {
+            WindowEvent::Attempt { latencies, .. } => Some(latencies),
+            _ => None,
+        }));
+        summary
+    }
+
+    /// Every `interval` that something was recorded, log a summary of each of
+    /// `SUMMARY_WINDOWS` and append it to `path` as a JSON line
+    pub async fn report_windows(self: Arc<Self>, interval: Duration, path: Option<PathBuf>) {
+        let mut file = path.and_then(|path| {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    warn!("Can't open live summary file {}: {}", path.display(), e);
+                    None
+                }
+            }
+        });
+        let mut updates = self.subscribe();
+        let mut ticker = tokio::time::interval(interval.max(Duration::from_millis(1)));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            // An idle bot stays quiet
+            if !updates.has_changed().unwrap_or(false) {
+                continue;
+            }
+            updates.borrow_and_update();
+            for window in SUMMARY_WINDOWS {
+                let summary = self.window_summary(window);
+                summary.log();
+                if let Some(out) = &mut file {
+                    if let Err(e) = writeln!(out, "{}", serde_json::to_string(&summary).unwrap_or_default()) {
+                        warn!("Can't write live summary: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    fn notify(&self) {
+        self.updates.send_modify(|version| *version += 1);
+    }
+}
+
+
+/// Grafana dashboard for the metrics served on `/metrics`
+///
+/// Panels are generated from `prometheus::METRICS`, so a renamed or new metric
+/// shows up here without hand-editing the dashboard. `datasource` is the uid of
+/// the Prometheus datasource scraping the bot.
+pub fn grafana_dashboard(datasource: &str) -> serde_json::Value {
+    use serde_json::json;
+
+    let target = |expr: String, legend: &str| json!({ "expr": expr, "legendFormat": legend, "refId": "A" });
+    let panel = |title: &str, kind: &str, unit: &str, targets: Vec<serde_json::Value>| {
+        json!({
+            "title": title,
+            "type": kind,
+            "datasource": { "type": "prometheus", "uid": datasource },
+            "fieldConfig": { "defaults": { "unit": unit }, "overrides": [] },
+            "targets": targets,
+        })
+    };
+
+    // Headline stats first: win rate and PnL over the last day
+    let outcomes = INCLUSION_OUTCOMES.name;
+    let mut panels = vec![
+        panel("Win rate (24h)", "stat", "percentunit", vec![target(
+            format!("sum(increase({outcomes}{{outcome=\"won\"}}[24h])) / sum(increase({outcomes}[24h]))"),
+            "win rate",
+        )]),
+        panel("PnL (24h)", "stat", EXPECTED_PROFIT.unit, vec![target(
+            format!("sum(increase({}[24h]))", EXPECTED_PROFIT.name),
+            "expected profit",
+        )]),
+    ];
+
+    for metric in METRICS {
+        let by = metric.labels.join(", ");
+        let legend = metric.labels.iter().map(|l| format!("{{{{{}}}}}", l)).collect::<Vec<_>>().join(" ");
+        let targets = match metric.kind {
+            MetricKind::Histogram => [0.5, 0.99]
+                .iter()
+                .map(|q| {
+                    let by = if by.is_empty() { "le".to_string() } else { format!("le, {}", by) };
+                    target(
+                        format!("histogram_quantile({}, sum by ({}) (rate({}_bucket[5m])))", q, by, metric.name),
+                        &format!("{} p{}", legend, q * 100.0),
+                    )
+                })
+                .collect(),
+            MetricKind::Counter if by.is_empty() => vec![target(format!("rate({}[5m])", metric.name), metric.name)],
+            MetricKind::Counter => vec![target(format!("sum by ({}) (rate({}[5m]))", by, metric.name), &legend)],
+            MetricKind::Gauge => vec![target(metric.name.to_string(), metric.name)],
+        };
+        panels.push(panel(metric.help, "timeseries", metric.unit, targets));
+    }
+
+    // Lay panels out two per row
+    for (i, panel) in panels.iter_mut().enumerate() {
+        panel["id"] = json!(i + 1);
+        panel["gridPos"] = json!({ "h": 8, "w": 12, "x": (i % 2) * 12, "y": (i / 2) * 8 });
+    }
+
+    json!({
+        "title": "Liquidio",
+        "uid": "liquidio",
+        "tags": ["liquidio"],
+        "timezone": "utc",
+        "refresh": "10s",
+        "time": { "from": "now-6h", "to": "now" },
+        "schemaVersion": 39,
+        "panels": panels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inclusion_latency_and_clock_audit() {
+        let mut metrics = LatencyMetrics::new();
+        metrics.mark_decoded();
+        metrics.mark_signal();
+        metrics.mark_sent();
+        assert!(metrics.clock_anomalies().is_empty());
+
+        // Sent at t=100.4s, included in a block stamped t=112s
+        metrics.sent_unix_ns = Some(100_400_000_000);
+        metrics.received_unix_ns = 100_400_000_000 - metrics.latency_end_to_end().unwrap().as_nanos() as u64;
+        metrics.mark_included(112);
+        assert_eq!(metrics.latency_submit_to_inclusion(), Some(Duration::from_millis(11_600)));
+        assert!(metrics.clock_anomalies().is_empty());
+
+        // Same-second inclusion is fine; a block two seconds before the send is skew
+        metrics.mark_included(100);
+        assert_eq!(metrics.latency_submit_to_inclusion(), Some(Duration::ZERO));
+        assert!(metrics.clock_anomalies().is_empty());
+        metrics.mark_included(98);
+        assert_eq!(metrics.clock_anomalies(), vec![ClockAnomaly::InclusionBeforeSend { skew_ms: 1_400 }]);
+
+        // Wall clock stepped back 5s while the pipeline ran
+        metrics.mark_included(112);
+        metrics.received_unix_ns += 5_000_000_000;
+        assert!(matches!(metrics.clock_anomalies()[..], [ClockAnomaly::WallClockStep { drift_us }] if drift_us < -4_900_000));
+
+        metrics.t_signal = Some(metrics.t_received);
+        assert!(metrics.clock_anomalies().contains(&ClockAnomaly::OutOfOrder { stage: "signal" }));
+    }
+
+    #[test]
+    fn test_grafana_dashboard_tracks_registry() {
+        let dashboard = grafana_dashboard("prom");
+        let exprs: Vec<&str> = dashboard["panels"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|p| p["targets"].as_array().unwrap())
+            .map(|t| t["expr"].as_str().unwrap())
+            .collect();
+
+        // Every exported metric is charted, and nothing is charted that isn't exported
+        for metric in METRICS {
+            assert!(exprs.iter().any(|e| e.contains(metric.name)), "{} has no panel", metric.name);
+        }
+        for expr in &exprs {
+            assert!(expr.contains("liquidio_"), "{}", expr);
+            let names = expr.split(|c: char| !(c.is_alphanumeric() || c == '_')).filter(|w| w.starts_with("liquidio_"));
+            for name in names {
+                let base = name.strip_suffix("_bucket").unwrap_or(name);
+                assert!(METRICS.iter().any(|m| m.name == base), "unknown metric {}", name);
+            }
+        }
+        assert!(exprs.contains(&"histogram_quantile(0.99, sum by (le, stage, protocol, chain, market) (rate(liquidio_pipeline_latency_microseconds_bucket[5m])))"));
+    }
+
+    #[test]
+    fn test_market_breakdown() {
+        let base = MetricLabels::new("simple-lending", 1);
+        let eth_dai = base.for_market(NATIVE_ASSET, Address::from_low_u64_be(0xda1));
+        let mut metrics = LatencyMetrics::new();
+        metrics.mark_sent();
+
+        let mut aggregate = AggregateMetrics::new();
+        aggregate.record_market_attempt(&eth_dai, &metrics, true);
+        aggregate.record_market_profit(&eth_dai, 40.0);
+        aggregate.record_market_inclusion(&eth_dai, &InclusionOutcome::Dropped);
+        aggregate.record_attempt(&metrics, false);
+
+        assert_eq!(eth_dai.key(), "simple-lending/1/ETH/0x0000000000000000000000000000000000000da1");
+        let market = &aggregate.markets[&eth_dai.key()];
+        assert_eq!((market.attempts, market.successful, market.expected_profit_usd), (1, 1, 40.0));
+        assert_eq!(market.inclusion.dropped, 1);
+        assert!(market.p99_end_to_end_us().is_some());
+        // Unlabeled attempts only count toward the totals
+        assert_eq!((aggregate.total_attempts, aggregate.markets.len()), (2, 1));
+        assert_eq!(aggregate.attempt_markets, vec![Some(eth_dai.key()), None]);
+    }
+
+    #[test]
+    fn test_rolling_window_summary() {
+        let live = LiveMetrics::new(10);
+        let mut metrics = LatencyMetrics::new();
+        metrics.mark_sent();
+        live.record_detections(3);
+        live.record_detections(0);
+        live.record_attempt(&metrics, true);
+        live.record_attempt(&metrics, false);
+        live.record_inclusion(&InclusionOutcome::Won { block: 1, position: 0, timestamp: 0 });
+        live.record_inclusion(&InclusionOutcome::Dropped);
+        live.record_profit(25.0);
+
+        let summary = live.window_summary(SUMMARY_WINDOWS[0]);
+        assert_eq!(summary.window_secs, 60);
+        assert_eq!((summary.detections, summary.attempts, summary.successful, summary.wins), (3, 2, 1, 1));
+        assert_eq!(summary.profit_usd, 25.0);
+        assert!(summary.latencies.contains_key("end_to_end"));
+        assert_eq!(live.window_summary(SUMMARY_WINDOWS[1]).attempts, 2);
+
+        // Only the most recent events are kept
+        for _ in 0..MAX_WINDOW_EVENTS {
+            live.record_detections(1);
+        }
+        assert_eq!(live.window_summary(SUMMARY_WINDOWS[1]).detections, MAX_WINDOW_EVENTS);
+    }
+}
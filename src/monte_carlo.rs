@@ -0,0 +1,141 @@
+use anyhow::Result;
+use serde::Serialize;
+use tracing::info;
+
+use crate::metrics::AggregateMetrics;
+use crate::run_metadata::RunMetadata;
+
+/// Summary statistics of one quantity across Monte Carlo runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Distribution {
+    pub mean: f64,
+    pub variance: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub p5: f64,
+    pub p95: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    /// Population statistics of `samples` (all zero when empty)
+    pub fn from_samples(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let variance = sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        let percentile = |p: f64| sorted[((p / 100.0) * n).floor().min(n - 1.0) as usize];
+
+        Self {
+            mean,
+            variance,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            p5: percentile(5.0),
+            p95: percentile(95.0),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// Headline numbers of one randomized backtest
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub seed: u64,
+    pub transactions: usize,
+    pub attempts: usize,
+    pub success_rate: f64,
+    pub profit_usd: f64,
+    pub p99_end_to_end_us: Option<f64>,
+    pub mean_end_to_end_us: Option<f64>,
+}
+
+impl RunSummary {
+    pub fn new(seed: u64, metrics: &AggregateMetrics) -> Self {
+        Self {
+            seed,
+            transactions: metrics.transactions_processed,
+            attempts: metrics.total_attempts,
+            success_rate: metrics.success_rate(),
+            profit_usd: metrics.total_expected_profit_usd,
+            p99_end_to_end_us: metrics.percentile("end_to_end_us", 99.0),
+            mean_end_to_end_us: metrics.mean("end_to_end_us"),
+        }
+    }
+}
+
+/// Profit and latency distributions over many seeded backtests
+#[derive(Debug, Clone, Serialize)]
+pub struct MonteCarloReport {
+    pub runs: Vec<RunSummary>,
+    pub profit_usd: Distribution,
+    pub attempts: Distribution,
+    /// Distribution of each run's P99 end-to-end latency
+    pub p99_end_to_end_us: Distribution,
+}
+
+impl MonteCarloReport {
+    pub fn from_runs(runs: Vec<RunSummary>) -> Self {
+        let collect = |f: fn(&RunSummary) -> Option<f64>| runs.iter().filter_map(f).collect::<Vec<_>>();
+        Self {
+            profit_usd: Distribution::from_samples(&collect(|r| Some(r.profit_usd))),
+            attempts: Distribution::from_samples(&collect(|r| Some(r.attempts as f64))),
+            p99_end_to_end_us: Distribution::from_samples(&collect(|r| r.p99_end_to_end_us)),
+            runs,
+        }
+    }
+
+    /// Run with the lowest profit
+    pub fn worst_run(&self) -> Option<&RunSummary> {
+        self.runs.iter().min_by(|a, b| a.profit_usd.total_cmp(&b.profit_usd))
+    }
+
+    pub fn print(&self) {
+        info!("=== Monte Carlo Backtest ({} runs) ===", self.runs.len());
+        let p = &self.profit_usd;
+        info!("Profit: mean ${:.2}, std dev ${:.2}, P5 ${:.2}, worst ${:.2}, best ${:.2}", p.mean, p.std_dev, p.p5, p.min, p.max);
+        let a = &self.attempts;
+        info!("Attempts: mean {:.1}, std dev {:.1}, min {:.0}, max {:.0}", a.mean, a.std_dev, a.min, a.max);
+        let l = &self.p99_end_to_end_us;
+        info!("P99 end-to-end: mean {:.2}us, std dev {:.2}us, worst {:.2}us", l.mean, l.std_dev, l.max);
+        if let Some(worst) = self.worst_run() {
+            info!("Worst run: seed {} (${:.2} over {} attempts)", worst.seed, worst.profit_usd, worst.attempts);
+        }
+    }
+
+    pub fn export_to_json(&self, filename: &str, run: &RunMetadata) -> Result<()> {
+        run.write_json(filename, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_and_worst_run() {
+        let d = Distribution::from_samples(&[4.0, 2.0, 8.0, 6.0]);
+        assert_eq!((d.mean, d.variance, d.min, d.max), (5.0, 5.0, 2.0, 8.0));
+        assert_eq!((d.p5, d.p95), (2.0, 8.0));
+        assert_eq!(Distribution::from_samples(&[]), Distribution::default());
+
+        let run = |seed, profit_usd, p99| RunSummary {
+            seed,
+            transactions: 100,
+            attempts: 3,
+            success_rate: 1.0,
+            profit_usd,
+            p99_end_to_end_us: p99,
+            mean_end_to_end_us: None,
+        };
+        let report = MonteCarloReport::from_runs(vec![run(1, 50.0, Some(900.0)), run(2, -10.0, None), run(3, 20.0, Some(700.0))]);
+        assert_eq!(report.worst_run().unwrap().seed, 2);
+        assert_eq!(report.profit_usd.min, -10.0);
+        // Runs without executions don't skew the latency distribution
+        assert_eq!(report.p99_end_to_end_us.mean, 800.0);
+    }
+}
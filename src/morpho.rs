@@ -0,0 +1,139 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::{
+    abi::AbiEncode,
+    contract::abigen,
+    types::{Address, H256, U256},
+    utils::keccak256,
+};
+use tokio::sync::OnceCell;
+
+use crate::blockchain::{BlockchainClient, RpcProvider};
+use crate::fixed_point::{self, WAD};
+use crate::protocol::{AssetRiskParams, HealthFactorScale, ProtocolAdapter, BPS};
+
+abigen!(
+    MorphoBlue,
+    r#"[
+        struct MarketParams { address loanToken; address collateralToken; address oracle; address irm; uint256 lltv; }
+        function idToMarketParams(bytes32 id) external view returns (address loanToken, address collateralToken, address oracle, address irm, uint256 lltv)
+        function liquidate(MarketParams marketParams, address borrower, uint256 seizedAssets, uint256 repaidShares, bytes data) external returns (uint256, uint256)
+    ]"#
+);
+
+/// Cap on the liquidation incentive factor (1.15)
+const MAX_LIQUIDATION_INCENTIVE_FACTOR: U256 = U256([1_150_000_000_000_000_000, 0, 0, 0]);
+/// Share of the distance to 100% LLTV paid as incentive (0.3)
+const LIQUIDATION_CURSOR: U256 = U256([300_000_000_000_000_000, 0, 0, 0]);
+
+/// Id of the market `params` describe: keccak256 of their ABI encoding
+pub fn market_id(params: &MarketParams) -> H256 {
+    H256(keccak256(params.clone().encode()))
+}
+
+/// Liquidation incentive factor of a market with `lltv` (1e18 scale):
+/// min(1.15, 1 / (1 - 0.3 * (1 - lltv)))
+pub fn liquidation_incentive_factor(lltv: U256) -> Result<U256> {
+    anyhow::ensure!(lltv <= WAD, "LLTV {} above 100%", lltv);
+    let discount = fixed_point::wad_mul(LIQUIDATION_CURSOR, WAD - lltv)?;
+    Ok(fixed_point::wad_div(WAD, WAD - discount)?.min(MAX_LIQUIDATION_INCENTIVE_FACTOR))
+}
+
+/// Morpho has no close factor; the bonus follows from the market's LLTV
+pub fn risk_params(lltv: U256) -> Result<AssetRiskParams> {
+    let incentive = liquidation_incentive_factor(lltv)?;
+    let bonus = fixed_point::mul_div(incentive - WAD, U256::from(BPS), WAD)?;
+    Ok(AssetRiskParams { liquidation_bonus_bps: bonus.as_u32(), close_factor_bps: BPS })
+}
+
+/// A Morpho Blue market the executor liquidates on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MorphoMarket {
+    pub morpho: Address,
+    pub params: MarketParams,
+}
+
+/// Adapter for one Morpho Blue market: isolated, with its own LLTV and oracle
+pub struct MorphoAdapter {
+    morpho: MorphoBlue<RpcProvider>,
+    market_id: H256,
+    params: OnceCell<MarketParams>,
+}
+
+impl MorphoAdapter {
+    pub fn new(blockchain: &BlockchainClient, morpho: Address, market_id: H256) -> Self {
+        Self {
+            morpho: MorphoBlue::new(morpho, blockchain.http_provider.clone()),
+            market_id,
+            params: OnceCell::new(),
+        }
+    }
+
+    /// Loan token, collateral token, oracle, IRM and LLTV, read once (markets are immutable)
+    pub async fn market_params(&self) -> Result<MarketParams> {
+        let params = self
+            .params
+            .get_or_try_init(|| async {
+                let (loan_token, collateral_token, oracle, irm, lltv) =
+                    self.morpho.id_to_market_params(self.market_id.0).call().await?;
+                anyhow::ensure!(loan_token != Address::zero(), "Morpho market {:?} does not exist", self.market_id);
+                Ok(MarketParams { loan_token, collateral_token, oracle, irm, lltv })
+            })
+            .await?;
+        Ok(params.clone())
+    }
+
+    /// The market as the executor needs it
+    pub async fn market(&self) -> Result<MorphoMarket> {
+        Ok(MorphoMarket { morpho: self.morpho.address(), params: self.market_params().await? })
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for MorphoAdapter {
+    fn name(&self) -> &str {
+        "morpho-blue"
+    }
+
+    async fn asset_params(&self, _asset: Address) -> Result<AssetRiskParams> {
+        risk_params(self.market_params().await?.lltv)
+    }
+
+    fn health_factor_scale(&self) -> HealthFactorScale {
+        HealthFactorScale::WAD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_morpho_market_math() {
+        // 86% LLTV: 1 / (1 - 0.3 * 0.14) = 1.0438...
+        let params = risk_params(WAD * 86 / 100).unwrap();
+        assert_eq!(params, AssetRiskParams { liquidation_bonus_bps: 438, close_factor_bps: BPS });
+        // Low LLTVs hit the 15% cap
+        assert_eq!(liquidation_incentive_factor(WAD / 2).unwrap(), MAX_LIQUIDATION_INCENTIVE_FACTOR);
+        assert!(risk_params(WAD * 2).is_err());
+    }
+
+    #[test]
+    fn test_market_id() {
+        let params = MarketParams {
+            loan_token: Address::from_low_u64_be(1),
+            collateral_token: Address::from_low_u64_be(2),
+            oracle: Address::from_low_u64_be(3),
+            irm: Address::from_low_u64_be(4),
+            lltv: WAD * 945 / 1000,
+        };
+        // abi.encode of a static struct is its five fields as 32-byte words
+        let mut words = Vec::new();
+        for address in [params.loan_token, params.collateral_token, params.oracle, params.irm] {
+            words.extend_from_slice(H256::from(address).as_bytes());
+        }
+        words.extend_from_slice(&<[u8; 32]>::from(params.lltv));
+        assert_eq!(market_id(&params), H256(keccak256(&words)));
+        assert_ne!(market_id(&params), market_id(&MarketParams { lltv: WAD * 86 / 100, ..params.clone() }));
+    }
+}
@@ -0,0 +1,269 @@
+use ethers::abi::{self, ParamType, Token};
+use ethers::types::{Address, Bytes};
+
+use crate::selectors::selector;
+
+/// On Multicall/Multicall3
+pub const AGGREGATE_SELECTOR: [u8; 4] = selector("aggregate((address,bytes)[])");
+pub const TRY_AGGREGATE_SELECTOR: [u8; 4] = selector("tryAggregate(bool,(address,bytes)[])");
+pub const AGGREGATE3_SELECTOR: [u8; 4] = selector("aggregate3((address,bool,bytes)[])");
+pub const AGGREGATE3_VALUE_SELECTOR: [u8; 4] = selector("aggregate3Value((address,bool,uint256,bytes)[])");
+/// Delegatecalling the contract itself (routers, position managers)
+pub const MULTICALL_SELECTOR: [u8; 4] = selector("multicall(bytes[])");
+/// multicall with a leading deadline
+pub const MULTICALL_DEADLINE_SELECTOR: [u8; 4] = selector("multicall(uint256,bytes[])");
+/// On smart accounts
+pub const EXECUTE_SELECTOR: [u8; 4] = selector("execute(address,uint256,bytes)");
+pub const EXECUTE_BATCH_SELECTOR: [u8; 4] = selector("executeBatch(address[],bytes[])");
+/// On a Safe
+pub const SAFE_EXEC_SELECTOR: [u8; 4] =
+    selector("execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)");
+/// handleOps(UserOperation[],address) on the ERC-4337 v0.6 EntryPoint
+pub const HANDLE_OPS_SELECTOR: [u8; 4] =
+    selector("handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)");
+
+/// Wrappers inside wrappers unwrapped before giving up
+const MAX_DEPTH: usize = 4;
+
+/// A call to the protocol found in a transaction, directly or wrapped in others
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolCall {
+    /// `msg.sender` the protocol sees, whose position the call changes
+    pub caller: Address,
+    pub input: Bytes,
+    /// Wrappers around the call (0 = the transaction itself)
+    pub depth: usize,
+}
+
+/// Every call to `protocol` made by `input` sent from `sender` to `to`,
+/// unwrapping multicalls, smart account executions and 4337 bundles
+pub fn protocol_calls(sender: Address, to: Address, input: &Bytes, protocol: Address) -> Vec<ProtocolCall> {
+    let mut calls = Vec::new();
+    if to == protocol {
+        calls.push(ProtocolCall { caller: sender, input: input.clone(), depth: 0 });
+    } else if input.windows(20).any(|window| window == protocol.as_bytes()) {
+        // Any wrapped call names the protocol as its target; skip decoding everything else
+        collect(sender, to, input, protocol, 1, &mut calls);
+    }
+    calls
+}
+
+fn collect(sender: Address, to: Address, input: &[u8], protocol: Address, depth: usize, calls: &mut Vec<ProtocolCall>) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    for (caller, target, data) in inner_calls(sender, to, input) {
+        if target == protocol {
+            calls.push(ProtocolCall { caller, input: data.into(), depth });
+        } else {
+            collect(caller, target, &data, protocol, depth + 1, calls);
+        }
+    }
+}
+
+/// (caller, target, calldata) of each call `input` makes when `sender` sends it to `to`
+fn inner_calls(sender: Address, to: Address, input: &[u8]) -> Vec<(Address, Address, Vec<u8>)> {
+    let Some((selector, args)) = input.split_first_chunk::<4>() else { return Vec::new() };
+    let bytes = || Box::new(ParamType::Bytes);
+    let (params, shape) = match *selector {
+        AGGREGATE_SELECTOR => (vec![array(vec![ParamType::Address, ParamType::Bytes])], Shape::Aggregate(0, 1)),
+        TRY_AGGREGATE_SELECTOR => (vec![ParamType::Bool, array(vec![ParamType::Address, ParamType::Bytes])], Shape::Aggregate(0, 1)),
+        AGGREGATE3_SELECTOR => (vec![array(vec![ParamType::Address, ParamType::Bool, ParamType::Bytes])], Shape::Aggregate(0, 2)),
+        AGGREGATE3_VALUE_SELECTOR => (
+            vec![array(vec![ParamType::Address, ParamType::Bool, ParamType::Uint(256), ParamType::Bytes])],
+            Shape::Aggregate(0, 3),
+        ),
+        MULTICALL_SELECTOR => (vec![ParamType::Array(bytes())], Shape::SelfMulticall),
+        MULTICALL_DEADLINE_SELECTOR => (vec![ParamType::Uint(256), ParamType::Array(bytes())], Shape::SelfMulticall),
+        EXECUTE_SELECTOR => (vec![ParamType::Address, ParamType::Uint(256), ParamType::Bytes], Shape::Execute),
+        EXECUTE_BATCH_SELECTOR => (vec![ParamType::Array(Box::new(ParamType::Address)), ParamType::Array(bytes())], Shape::ExecuteBatch),
+        SAFE_EXEC_SELECTOR => (
+            vec![
+                ParamType::Address,
+                ParamType::Uint(256),
+                ParamType::Bytes,
+                ParamType::Uint(8),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Address,
+                ParamType::Address,
+                ParamType::Bytes,
+            ],
+            Shape::Execute,
+        ),
+        HANDLE_OPS_SELECTOR => {
+            let user_op = vec![
+                ParamType::Address,
+                ParamType::Uint(256),
+                ParamType::Bytes,
+                ParamType::Bytes,
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Bytes,
+                ParamType::Bytes,
+            ];
+            (vec![array(user_op), ParamType::Address], Shape::Aggregate(0, 3))
+        }
+        _ => return Vec::new(),
+    };
+    let Ok(tokens) = abi::decode(&params, args) else { return Vec::new() };
+
+    match shape {
+        // Each entry is called by the aggregator (for handleOps, the EntryPoint calling the account)
+        Shape::Aggregate(target, data) => last_array(tokens)
+            .into_iter()
+            .filter_map(|entry| {
+                let fields = entry.into_tuple()?;
+                Some((to, fields.get(target)?.clone().into_address()?, fields.get(data)?.clone().into_bytes()?))
+            })
+            .collect(),
+        // Delegatecalls keep the original sender
+        Shape::SelfMulticall => last_array(tokens)
+            .into_iter()
+            .filter_map(|data| Some((sender, to, data.into_bytes()?)))
+            .collect(),
+        // The account itself calls the target
+        Shape::Execute => {
+            let mut tokens = tokens.into_iter();
+            let target = tokens.next().and_then(Token::into_address);
+            let data = tokens.nth(1).and_then(Token::into_bytes);
+            target.zip(data).map(|(target, data)| (to, target, data)).into_iter().collect()
+        }
+        Shape::ExecuteBatch => {
+            let mut tokens = tokens.into_iter();
+            let targets = tokens.next().and_then(Token::into_array).unwrap_or_default();
+            let data = tokens.next().and_then(Token::into_array).unwrap_or_default();
+            targets
+                .into_iter()
+                .zip(data)
+                .filter_map(|(target, data)| Some((to, target.into_address()?, data.into_bytes()?)))
+                .collect()
+        }
+    }
+}
+
+/// Where the inner calls sit in a wrapper's decoded arguments
+enum Shape {
+    /// The last argument is an array of tuples with the target and calldata at these indices
+    Aggregate(usize, usize),
+    /// The last argument is calldata for the contract itself
+    SelfMulticall,
+    /// The first argument is the target and the third the calldata
+    Execute,
+    /// Parallel arrays of targets and calldata
+    ExecuteBatch,
+}
+
+fn array(fields: Vec<ParamType>) -> ParamType {
+    ParamType::Array(Box::new(ParamType::Tuple(fields)))
+}
+
+fn last_array(tokens: Vec<Token>) -> Vec<Token> {
+    tokens.into_iter().rev().find_map(Token::into_array).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{BorrowCall, DepositCall};
+    use ethers::abi::AbiEncode;
+    use ethers::types::U256;
+
+    fn call(selector: [u8; 4], tokens: &[Token]) -> Vec<u8> {
+        [&selector[..], &abi::encode(tokens)].concat()
+    }
+
+    #[test]
+    fn test_unwraps_multicall_and_user_operations() {
+        let (user, account, multicall, entry_point, protocol) =
+            (Address::from_low_u64_be(1), Address::from_low_u64_be(2), Address::from_low_u64_be(3), Address::from_low_u64_be(4), Address::from_low_u64_be(5));
+        let borrow = BorrowCall { amount: U256::one() }.encode();
+
+        // Multicall3: the aggregator is the caller
+        let aggregate3 = call(AGGREGATE3_SELECTOR, &[Token::Array(vec![Token::Tuple(vec![
+            Token::Address(protocol),
+            Token::Bool(false),
+            Token::Bytes(borrow.clone()),
+        ])])]);
+        let calls = protocol_calls(user, multicall, &aggregate3.clone().into(), protocol);
+        assert_eq!(calls, vec![ProtocolCall { caller: multicall, input: borrow.clone().into(), depth: 1 }]);
+
+        // A 4337 bundle executing a batch on the account: the account is the caller
+        let batch = call(EXECUTE_BATCH_SELECTOR, &[
+            Token::Array(vec![Token::Address(protocol), Token::Address(protocol)]),
+            Token::Array(vec![Token::Bytes(DepositCall.encode()), Token::Bytes(borrow.clone())]),
+        ]);
+        let uint = |n: u64| Token::Uint(n.into());
+        let op = Token::Tuple(vec![
+            Token::Address(account), uint(0), Token::Bytes(vec![]), Token::Bytes(batch),
+            uint(1), uint(1), uint(1), uint(1), uint(1), Token::Bytes(vec![]), Token::Bytes(vec![]),
+        ]);
+        let handle_ops = call(HANDLE_OPS_SELECTOR, &[Token::Array(vec![op]), Token::Address(user)]);
+        let calls = protocol_calls(user, entry_point, &handle_ops.into(), protocol);
+        assert_eq!(calls.len(), 2);
+        assert!(calls.iter().all(|call| call.caller == account && call.depth == 2));
+        assert_eq!(calls[1].input, Bytes::from(borrow.clone()));
+
+        // Direct calls and unrelated wrappers
+        assert_eq!(protocol_calls(user, protocol, &borrow.clone().into(), protocol)[0].caller, user);
+        assert!(protocol_calls(user, multicall, &aggregate3.into(), Address::from_low_u64_be(6)).is_empty());
+        assert!(protocol_calls(user, multicall, &Bytes::from(vec![0xde, 0xad]), protocol).is_empty());
+    }
+
+    fn wrapper_selectors() -> Vec<[u8; 4]> {
+        vec![
+            AGGREGATE_SELECTOR,
+            TRY_AGGREGATE_SELECTOR,
+            AGGREGATE3_SELECTOR,
+            AGGREGATE3_VALUE_SELECTOR,
+            MULTICALL_SELECTOR,
+            MULTICALL_DEADLINE_SELECTOR,
+            EXECUTE_SELECTOR,
+            EXECUTE_BATCH_SELECTOR,
+            SAFE_EXEC_SELECTOR,
+            HANDLE_OPS_SELECTOR,
+        ]
+    }
+
+    proptest::proptest! {
+        // Garbage behind a wrapper selector, with the protocol address planted so
+        // the decoder doesn't skip it, finds nothing rather than panicking
+        #[test]
+        fn prop_arbitrary_wrapper_arguments(
+            selector in proptest::sample::select(wrapper_selectors()),
+            body in proptest::collection::vec(proptest::num::u8::ANY, 0..600),
+        ) {
+            let protocol = Address::from_low_u64_be(5);
+            let input = [&selector[..], &body, protocol.as_bytes()].concat();
+            let calls = protocol_calls(Address::from_low_u64_be(1), Address::from_low_u64_be(3), &input.into(), protocol);
+            proptest::prop_assert!(calls.iter().all(|call| (1..=MAX_DEPTH).contains(&call.depth)));
+        }
+
+        // Offsets and lengths corrupted in a valid nested encoding
+        #[test]
+        fn prop_corrupted_nested_encoding(
+            corruptions in proptest::collection::vec((proptest::num::usize::ANY, proptest::num::u8::ANY), 1..8),
+            keep in proptest::num::usize::ANY,
+        ) {
+            let protocol = Address::from_low_u64_be(5);
+            let borrow = BorrowCall { amount: U256::one() }.encode();
+            let execute = call(EXECUTE_SELECTOR, &[Token::Address(protocol), Token::Uint(U256::zero()), Token::Bytes(borrow)]);
+            let mut input = call(AGGREGATE3_SELECTOR, &[Token::Array(vec![Token::Tuple(vec![
+                Token::Address(Address::from_low_u64_be(2)),
+                Token::Bool(false),
+                Token::Bytes(execute),
+            ])])]);
+            for (at, byte) in corruptions {
+                let len = input.len();
+                input[at % len] = byte;
+            }
+            input.truncate(keep % (input.len() + 1));
+            let calls = protocol_calls(Address::from_low_u64_be(1), Address::from_low_u64_be(3), &input.into(), protocol);
+            proptest::prop_assert!(calls.len() <= 1);
+        }
+    }
+}
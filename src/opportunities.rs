@@ -0,0 +1,163 @@
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::metrics::MetricLabels;
+use crate::simulator::SimulationResult;
+
+/// Expected profit per million gas, the figure opportunities are ranked by
+pub fn profit_per_mgas(profit_usd: f64, gas: u64) -> f64 {
+    if gas == 0 {
+        return 0.0;
+    }
+    profit_usd * 1e6 / gas as f64
+}
+
+/// One simulated liquidation, in any protocol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankedOpportunity {
+    pub protocol: String,
+    pub chain: String,
+    pub market: String,
+    pub user: Address,
+    pub profitable: bool,
+    pub expected_profit_usd: f64,
+    pub estimated_gas: u64,
+    pub profit_per_mgas_usd: f64,
+}
+
+impl RankedOpportunity {
+    pub fn new(labels: &MetricLabels, user: Address, simulation: &SimulationResult) -> Self {
+        let estimated_gas = simulation.estimated_gas.min(u64::MAX.into()).as_u64();
+        Self {
+            protocol: labels.protocol.clone(),
+            chain: labels.chain.clone(),
+            market: labels.market.clone(),
+            user,
+            profitable: simulation.profitable,
+            expected_profit_usd: simulation.expected_profit_usd,
+            estimated_gas,
+            profit_per_mgas_usd: profit_per_mgas(simulation.expected_profit_usd, estimated_gas),
+        }
+    }
+}
+
+/// Opportunities one protocol produced; profit and gas count profitable ones only
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolOpportunities {
+    pub protocol: String,
+    pub opportunities: usize,
+    pub profitable: usize,
+    pub expected_profit_usd: f64,
+    pub gas: u64,
+}
+
+impl ProtocolOpportunities {
+    pub fn record(&mut self, opportunity: &RankedOpportunity) {
+        self.opportunities += 1;
+        if opportunity.profitable {
+            self.profitable += 1;
+            self.expected_profit_usd += opportunity.expected_profit_usd;
+            self.gas = self.gas.saturating_add(opportunity.estimated_gas);
+        }
+    }
+
+    pub fn profit_per_mgas_usd(&self) -> f64 {
+        profit_per_mgas(self.expected_profit_usd, self.gas)
+    }
+}
+
+/// `protocols` best first by profit per gas, where capital earns the most
+pub fn rank_protocols<'a>(protocols: impl IntoIterator<Item = &'a ProtocolOpportunities>) -> Vec<ProtocolOpportunities> {
+    let mut ranked: Vec<_> = protocols.into_iter().cloned().collect();
+    ranked.sort_by(|a, b| b.profit_per_mgas_usd().total_cmp(&a.profit_per_mgas_usd()));
+    ranked
+}
+
+/// Recent opportunities from every configured protocol, served ranked on
+/// `/opportunities`
+#[derive(Debug)]
+pub struct OpportunityFeed {
+    capacity: usize,
+    recent: Mutex<VecDeque<RankedOpportunity>>,
+    protocols: Mutex<BTreeMap<String, ProtocolOpportunities>>,
+}
+
+impl OpportunityFeed {
+    /// Keep the latest `capacity` opportunities (protocol totals cover all of them)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            recent: Mutex::new(VecDeque::new()),
+            protocols: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn record(&self, opportunity: RankedOpportunity) {
+        self.protocols
+            .lock()
+            .unwrap()
+            .entry(opportunity.protocol.clone())
+            .or_insert_with(|| ProtocolOpportunities { protocol: opportunity.protocol.clone(), ..Default::default() })
+            .record(&opportunity);
+
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == self.capacity {
+            recent.pop_front();
+        }
+        recent.push_back(opportunity);
+    }
+
+    /// Up to `limit` recent opportunities, best profit per gas first
+    pub fn top(&self, limit: usize) -> Vec<RankedOpportunity> {
+        let mut ranked: Vec<_> = self.recent.lock().unwrap().iter().cloned().collect();
+        ranked.sort_by(|a, b| b.profit_per_mgas_usd.total_cmp(&a.profit_per_mgas_usd));
+        ranked.truncate(limit);
+        ranked
+    }
+
+    /// Totals per protocol, best profit per gas first
+    pub fn protocols(&self) -> Vec<ProtocolOpportunities> {
+        rank_protocols(self.protocols.lock().unwrap().values())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opportunity(protocol: &str, profit_usd: f64, gas: u64) -> RankedOpportunity {
+        RankedOpportunity {
+            protocol: protocol.to_string(),
+            chain: "1".to_string(),
+            market: "ETH/USDC".to_string(),
+            user: Address::zero(),
+            profitable: profit_usd > 0.0,
+            expected_profit_usd: profit_usd,
+            estimated_gas: gas,
+            profit_per_mgas_usd: profit_per_mgas(profit_usd, gas),
+        }
+    }
+
+    #[test]
+    fn test_ranks_across_protocols_by_profit_per_gas() {
+        let feed = OpportunityFeed::new(3);
+        feed.record(opportunity("aave", 100.0, 500_000)); // $200/Mgas
+        feed.record(opportunity("morpho-blue", 60.0, 200_000)); // $300/Mgas
+        feed.record(opportunity("venus", -5.0, 300_000));
+        feed.record(opportunity("aave", 20.0, 400_000)); // $50/Mgas, pushes out the first
+
+        let top = feed.top(2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].protocol, "morpho-blue");
+        assert_eq!(top[1].expected_profit_usd, 20.0);
+
+        // Totals keep everything; unprofitable opportunities add no profit or gas
+        let protocols = feed.protocols();
+        let order: Vec<_> = protocols.iter().map(|p| p.protocol.as_str()).collect();
+        assert_eq!(order, ["morpho-blue", "aave", "venus"]);
+        assert_eq!((protocols[1].opportunities, protocols[1].gas), (2, 900_000));
+        assert_eq!((protocols[2].profitable, protocols[2].profit_per_mgas_usd()), (0, 0.0));
+    }
+}
@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use ethers::{
+    contract::abigen,
+    types::{Address, H256, I256},
+};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::OnceCell;
+use tracing::{debug, warn};
+
+use crate::blockchain::{BlockchainClient, RpcProvider};
+use crate::protocol::BPS;
+
+abigen!(
+    ChainlinkAggregator,
+    r#"[
+        function decimals() external view returns (uint8)
+        function latestRoundData() external view returns (uint80 roundId, int256 answer, uint256 startedAt, uint256 updatedAt, uint80 answeredInRound)
+    ]"#
+);
+
+abigen!(
+    UniswapV3Pool,
+    r#"[
+        function token0() external view returns (address)
+        function token1() external view returns (address)
+        function observe(uint32[] secondsAgos) external view returns (int56[] tickCumulatives, uint160[] secondsPerLiquidityCumulativeX128s)
+    ]"#
+);
+
+abigen!(
+    Pyth,
+    r#"[
+        struct Price { int64 price; uint64 conf; int32 expo; uint256 publishTime; }
+        function getPriceUnsafe(bytes32 id) external view returns (Price memory price)
+    ]"#
+);
+
+abigen!(
+    ERC20Decimals,
+    r#"[
+        function decimals() external view returns (uint8)
+    ]"#
+);
+
+/// Where ETH/USD prices come from and how far they may disagree
+#[derive(Debug, Clone)]
+pub struct OracleSources {
+    /// Chainlink ETH/USD aggregator (primary)
+    pub chainlink_feed: Option<Address>,
+    /// Chainlink answers older than this are treated as unavailable
+    pub chainlink_max_age: Duration,
+    /// Pyth contract and the ETH/USD price id to read from it
+    pub pyth_contract: Option<Address>,
+    pub pyth_price_id: H256,
+    /// Pyth prices published longer ago than this are treated as unavailable
+    pub pyth_max_age: Duration,
+    /// Uniswap V3 ETH/stablecoin pool used as TWAP cross-check and fallback
+    pub twap_pool: Option<Address>,
+    pub twap_window_secs: u32,
+    /// Whether ETH (WETH) is the pool's token0; the canonical USDC/WETH pools have it as token1
+    pub twap_eth_is_token0: bool,
+    /// Refuse to price when the two sources differ by more than this
+    pub max_divergence_bps: u32,
+}
+
+impl OracleSources {
+    pub fn is_configured(&self) -> bool {
+        self.chainlink_feed.is_some() || self.pyth_contract.is_some() || self.twap_pool.is_some()
+    }
+}
+
+/// A price and the uncertainty its source reports around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceQuote {
+    pub price_usd: f64,
+    /// Half-width of the confidence band (0 for sources that don't report one)
+    pub confidence_usd: f64,
+}
+
+impl PriceQuote {
+    pub fn exact(price_usd: f64) -> Self {
+        Self { price_usd, confidence_usd: 0.0 }
+    }
+
+    /// Lowest and highest price within `multiplier` confidence intervals
+    pub fn band(&self, multiplier: f64) -> (f64, f64) {
+        let width = self.confidence_usd * multiplier;
+        ((self.price_usd - width).max(0.0), self.price_usd + width)
+    }
+}
+
+/// ETH/USD price from Chainlink and Pyth, cross-checked against a Uniswap V3 TWAP
+///
+/// Any source alone is used as-is; with several available the price is
+/// rejected when one diverges from the preferred source beyond `max_divergence_bps`.
+pub struct PriceOracle {
+    sources: OracleSources,
+    chainlink: Option<ChainlinkAggregator<RpcProvider>>,
+    pyth: Option<Pyth<RpcProvider>>,
+    pool: Option<UniswapV3Pool<RpcProvider>>,
+    provider: Arc<RpcProvider>,
+    // Token decimals never change, so they are read once
+    chainlink_decimals: OnceCell<u8>,
+    pool_decimals: OnceCell<(u8, u8)>,
+}
+
+impl PriceOracle {
+    pub fn new(blockchain: &BlockchainClient, sources: OracleSources) -> Self {
+        let provider = blockchain.http_provider.clone();
+        Self {
+            chainlink: sources.chainlink_feed.map(|feed| ChainlinkAggregator::new(feed, provider.clone())),
+            pyth: sources.pyth_contract.map(|pyth| Pyth::new(pyth, provider.clone())),
+            pool: sources.twap_pool.map(|pool| UniswapV3Pool::new(pool, provider.clone())),
+            provider,
+            sources,
+            chainlink_decimals: OnceCell::new(),
+            pool_decimals: OnceCell::new(),
+        }
+    }
+
+    /// Current ETH price in USD, or an error when no source is usable or they disagree
+    pub async fn eth_price_usd(&self) -> Result<PriceQuote> {
+        let (chainlink, pyth, twap) = tokio::join!(self.chainlink_price(), self.pyth_price(), self.twap_price());
+        let available = |name: &str, quote: Result<Option<PriceQuote>>| {
+            quote.unwrap_or_else(|e| {
+                warn!("{} price unavailable: {}", name, e);
+                None
+            })
+        };
+        let quotes = [
+            ("chainlink", available("Chainlink", chainlink)),
+            ("pyth", available("Pyth", pyth)),
+            ("twap", available("Uniswap TWAP", twap)),
+        ];
+        debug!("ETH/USD quotes: {:?}", quotes);
+
+        cross_check(&quotes, self.sources.max_divergence_bps)
+    }
+
+    async fn chainlink_price(&self) -> Result<Option<PriceQuote>> {
+        let Some(feed) = &self.chainlink else { return Ok(None) };
+        let decimals = *self.chainlink_decimals.get_or_try_init(|| async { feed.decimals().call().await }).await?;
+        let (_, answer, _, updated_at, _) = feed.latest_round_data().call().await?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let age = now.saturating_sub(updated_at.low_u64());
+        anyhow::ensure!(age <= self.sources.chainlink_max_age.as_secs(), "answer is {}s old", age);
+        anyhow::ensure!(answer > I256::zero(), "non-positive answer {}", answer);
+
+        Ok(Some(PriceQuote::exact(answer.as_u128() as f64 / 10f64.powi(decimals as i32))))
+    }
+
+    async fn pyth_price(&self) -> Result<Option<PriceQuote>> {
+        let Some(pyth) = &self.pyth else { return Ok(None) };
+        let (price, conf, expo, publish_time) = pyth.get_price_unsafe(self.sources.pyth_price_id.0).call().await?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let age = now.saturating_sub(publish_time.low_u64());
+        anyhow::ensure!(age <= self.sources.pyth_max_age.as_secs(), "price is {}s old", age);
+        anyhow::ensure!(price > 0, "non-positive price {}", price);
+
+        // price and conf are both fixed-point with exponent `expo`
+        let scale = 10f64.powi(expo);
+        Ok(Some(PriceQuote {
+            price_usd: price as f64 * scale,
+            confidence_usd: conf as f64 * scale,
+        }))
+    }
+
+    async fn twap_price(&self) -> Result<Option<PriceQuote>> {
+        let Some(pool) = &self.pool else { return Ok(None) };
+        let (decimals0, decimals1) = *self
+            .pool_decimals
+            .get_or_try_init(|| async {
+                let decimals = |token| ERC20Decimals::new(token, self.provider.clone());
+                let decimals0 = decimals(pool.token_0().call().await?).decimals().call().await?;
+                let decimals1 = decimals(pool.token_1().call().await?).decimals().call().await?;
+                anyhow::Ok((decimals0, decimals1))
+            })
+            .await?;
+
+        let window = self.sources.twap_window_secs;
+        let (tick_cumulatives, _) = pool.observe(vec![window, 0]).call().await?;
+        let [then, now] = tick_cumulatives[..] else {
+            anyhow::bail!("observe returned {} points", tick_cumulatives.len());
+        };
+
+        let price_1_per_0 = twap_price(then, now, window, decimals0, decimals1).context("Empty TWAP window")?;
+        let price_usd = if self.sources.twap_eth_is_token0 { price_1_per_0 } else { 1.0 / price_1_per_0 };
+        Ok(Some(PriceQuote::exact(price_usd)))
+    }
+}
+
+/// Human-unit price of token0 in token1 from two tick cumulatives `window` seconds apart
+pub fn twap_price(tick_cumulative_then: i64, tick_cumulative_now: i64, window: u32, decimals0: u8, decimals1: u8) -> Option<f64> {
+    if window == 0 {
+        return None;
+    }
+    let mean_tick = (tick_cumulative_now - tick_cumulative_then) as f64 / window as f64;
+    Some(1.0001f64.powf(mean_tick) * 10f64.powi(decimals0 as i32 - decimals1 as i32))
+}
+
+/// Pick the price to use from named quotes, most preferred first
+///
+/// The first available quote sets the price; every other available quote must
+/// lie within `max_divergence_bps` of it. The widest reported confidence band
+/// is kept so callers can price the worst case.
+pub fn cross_check(quotes: &[(&str, Option<PriceQuote>)], max_divergence_bps: u32) -> Result<PriceQuote> {
+    let mut available = quotes.iter().filter_map(|(name, quote)| quote.map(|quote| (*name, quote)));
+    let (reference_name, reference) = available.next().context("No usable ETH/USD price source")?;
+    let mut confidence_usd = reference.confidence_usd;
+
+    for (name, quote) in available {
+        let divergence_bps = (reference.price_usd - quote.price_usd).abs()
+            / reference.price_usd.min(quote.price_usd)
+            * BPS as f64;
+        anyhow::ensure!(
+            divergence_bps <= max_divergence_bps as f64,
+            "Price sources diverge by {:.0} bps ({} ${:.2}, {} ${:.2})",
+            divergence_bps,
+            reference_name,
+            reference.price_usd,
+            name,
+            quote.price_usd,
+        );
+        confidence_usd = confidence_usd.max(quote.confidence_usd);
+    }
+
+    Ok(PriceQuote { price_usd: reference.price_usd, confidence_usd })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_twap_divergence_and_confidence_band() {
+        // USDC (6 decimals) as token0, WETH (18) as token1 at ~$2000/ETH:
+        // price_1_per_0 = 1e12 / 2000 => tick ≈ 200311
+        let window = 1800;
+        let tick = 200_311i64;
+        let usdc_per_weth = 1.0 / twap_price(0, tick * window as i64, window, 6, 18).unwrap();
+        assert!((usdc_per_weth - 2000.0).abs() < 1.0, "{}", usdc_per_weth);
+
+        let chainlink = Some(PriceQuote::exact(2000.0));
+        let pyth = Some(PriceQuote { price_usd: 2010.0, confidence_usd: 4.0 });
+        let quote = cross_check(&[("chainlink", chainlink), ("pyth", pyth), ("twap", None)], 100).unwrap();
+        assert_eq!(quote, PriceQuote { price_usd: 2000.0, confidence_usd: 4.0 });
+        assert_eq!(quote.band(1.5), (1994.0, 2006.0));
+
+        let twap = Some(PriceQuote::exact(2100.0));
+        assert!(cross_check(&[("chainlink", chainlink), ("twap", twap)], 100).is_err());
+        assert_eq!(cross_check(&[("chainlink", None), ("twap", twap)], 100).unwrap().price_usd, 2100.0);
+        assert!(cross_check(&[("chainlink", None), ("twap", None)], 100).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::Result;
+use ethers::abi::{AbiDecode, AbiEncode};
+use ethers::providers::spoof;
+use ethers::types::{Address, Transaction, H256, U256};
+use ethers::utils::keccak256;
+
+use crate::blockchain::{BlockchainClient, LendingProtocolCalls};
+
+/// `ethPriceUSD` storage slot in SimpleLendingProtocol (the stablecoin is immutable)
+const ETH_PRICE_SLOT: u64 = 0;
+/// `positions` mapping storage slot
+const POSITIONS_SLOT: u64 = 1;
+/// Collateral bonus paid to liquidators, in percent of the debt's value
+const LIQUIDATION_BONUS: u64 = 110;
+
+/// Storage slot of `positions[user].collateral`; `debt` is the next slot
+pub fn position_slot(user: Address) -> H256 {
+    H256::from(keccak256((user, U256::from(POSITIONS_SLOT)).encode()))
+}
+
+fn word(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256(bytes)
+}
+
+/// Protocol storage a pending transaction writes once mined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateWrite {
+    EthPrice(U256),
+    Position { user: Address, collateral: U256, debt: U256 },
+}
+
+/// Which position `call` sent by `sender` touches, if any
+pub fn touched_position(call: &LendingProtocolCalls, sender: Address) -> Option<Address> {
+    match call {
+        LendingProtocolCalls::Deposit(_)
+        | LendingProtocolCalls::Withdraw(_)
+        | LendingProtocolCalls::Borrow(_)
+        | LendingProtocolCalls::Repay(_) => Some(sender),
+        LendingProtocolCalls::Liquidate(call) => Some(call.user),
+        _ => None,
+    }
+}
+
+/// What `call` writes, given the touched position's `(collateral, debt)` and the
+/// price before it lands. Mirrors the contract's arithmetic; reverts are not modelled.
+pub fn effect(
+    call: &LendingProtocolCalls,
+    sender: Address,
+    value: U256,
+    (collateral, debt): (U256, U256),
+    eth_price: U256,
+) -> Option<StateWrite> {
+    let position = |collateral, debt| StateWrite::Position { user: sender, collateral, debt };
+    Some(match call {
+        LendingProtocolCalls::SetEthPrice(call) => StateWrite::EthPrice(call.new_price),
+        LendingProtocolCalls::Deposit(_) => position(collateral.saturating_add(value), debt),
+        LendingProtocolCalls::Withdraw(call) => position(collateral.saturating_sub(call.amount), debt),
+        LendingProtocolCalls::Borrow(call) => position(collateral, debt.saturating_add(call.amount)),
+        LendingProtocolCalls::Repay(call) => position(collateral, debt.saturating_sub(call.amount)),
+        LendingProtocolCalls::Liquidate(call) if !eth_price.is_zero() => {
+            let seized = call.debt_to_cover * U256::exp10(18) / eth_price * LIQUIDATION_BONUS / 100;
+            StateWrite::Position {
+                user: call.user,
+                collateral: collateral.saturating_sub(seized),
+                debt: debt.saturating_sub(call.debt_to_cover),
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// Protocol state as it will be once a pending transaction is mined, as
+/// `eth_call` state overrides
+#[derive(Debug, Clone, Default)]
+pub struct PendingState {
+    pub overrides: spoof::State,
+    /// Price set by the transaction, if it was a price update
+    pub eth_price_usd: Option<f64>,
+}
+
+impl PendingState {
+    /// Overrides for `trigger`'s writes, or `None` if it does not touch protocol storage
+    pub async fn after(blockchain: &BlockchainClient, trigger: &Transaction) -> Result<Option<Self>> {
+        let protocol = blockchain.lending_protocol.address();
+        if trigger.to != Some(protocol) {
+            return Ok(None);
+        }
+        let call = match LendingProtocolCalls::decode(&trigger.input) {
+            Ok(call) => call,
+            Err(_) => return Ok(None),
+        };
+
+        let before = match touched_position(&call, trigger.from) {
+            Some(user) => {
+                let (collateral, debt, _) = blockchain.get_position(user).await?;
+                (collateral, debt)
+            }
+            None => (U256::zero(), U256::zero()),
+        };
+        let eth_price = match call {
+            LendingProtocolCalls::Liquidate(_) => blockchain.lending_protocol.eth_price_usd().call().await?,
+            _ => U256::zero(),
+        };
+
+        let mut state = Self::default();
+        match effect(&call, trigger.from, trigger.value, before, eth_price) {
+            Some(StateWrite::EthPrice(price)) => {
+                state.overrides.account(protocol).store(H256::from_low_u64_be(ETH_PRICE_SLOT), word(price));
+                state.eth_price_usd = Some(price.as_u128() as f64 / 1e18);
+            }
+            Some(StateWrite::Position { user, collateral, debt }) => {
+                let slot = position_slot(user);
+                let debt_slot = word(U256::from_big_endian(slot.as_bytes()) + 1);
+                state
+                    .overrides
+                    .account(protocol)
+                    .store(slot, word(collateral))
+                    .store(debt_slot, word(debt));
+            }
+            None => return Ok(None),
+        }
+        Ok(Some(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{BorrowCall, LiquidateCall, SetEthPriceCall};
+
+    #[test]
+    fn test_effects_follow_contract() {
+        let user = Address::from_low_u64_be(7);
+        let eth = U256::exp10(18);
+        let before = (eth * 5, eth * 6000);
+
+        let borrow = LendingProtocolCalls::Borrow(BorrowCall { amount: eth * 500 });
+        assert_eq!(
+            effect(&borrow, user, U256::zero(), before, U256::zero()),
+            Some(StateWrite::Position { user, collateral: eth * 5, debt: eth * 6500 })
+        );
+
+        let price = LendingProtocolCalls::SetEthPrice(SetEthPriceCall { new_price: eth * 1500 });
+        assert_eq!(effect(&price, user, U256::zero(), before, U256::zero()), Some(StateWrite::EthPrice(eth * 1500)));
+
+        // $3000 at $1500/ETH is 2 ETH, plus the 10% bonus
+        let other_bot = Address::from_low_u64_be(9);
+        let liquidate = LendingProtocolCalls::Liquidate(LiquidateCall { user, debt_to_cover: eth * 3000 });
+        assert_eq!(touched_position(&liquidate, other_bot), Some(user));
+        assert_eq!(
+            effect(&liquidate, other_bot, U256::zero(), before, eth * 1500),
+            Some(StateWrite::Position { user, collateral: eth * 5 - eth * 22 / 10, debt: eth * 3000 })
+        );
+
+        // Mapping slot for a Solidity `mapping(address => ...)` at slot 1
+        let mut preimage = [0u8; 64];
+        preimage[12..32].copy_from_slice(user.as_bytes());
+        preimage[63] = 1;
+        assert_eq!(position_slot(user), H256::from(keccak256(preimage)));
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::metrics::AggregateMetrics;
+
+/// P99 ceiling (ms) for each pipeline stage
+pub const DEFAULT_TARGETS_MS: [(&str, f64); 4] = [
+    ("end_to_end_us", 10.0),
+    ("signal_detection_us", 2.0),
+    ("simulation_us", 5.0),
+    ("construction_us", 1.0),
+];
+
+/// P99 latency a stage must stay under
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageTarget {
+    /// Latency metric, as recorded in `AggregateMetrics` (e.g. `simulation_us`)
+    pub metric: String,
+    pub p99_ms: f64,
+}
+
+/// Per-stage P99 targets, and how much slower than a baseline run a stage may get
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerformanceTargets {
+    pub stages: Vec<StageTarget>,
+    /// Largest relative P99 increase over the baseline (0.10 = +10%)
+    pub max_regression: f64,
+}
+
+impl Default for PerformanceTargets {
+    fn default() -> Self {
+        Self::new(&HashMap::new(), 0.10)
+    }
+}
+
+impl PerformanceTargets {
+    /// The default targets, with any stage in `overrides_ms` replaced and
+    /// stages not among the defaults added
+    pub fn new(overrides_ms: &HashMap<String, f64>, max_regression: f64) -> Self {
+        let mut stages: Vec<StageTarget> = DEFAULT_TARGETS_MS
+            .iter()
+            .map(|(metric, p99_ms)| StageTarget {
+                metric: metric.to_string(),
+                p99_ms: overrides_ms.get(*metric).copied().unwrap_or(*p99_ms),
+            })
+            .collect();
+        let mut extra: Vec<_> = overrides_ms
+            .iter()
+            .filter(|(metric, _)| !stages.iter().any(|stage| &stage.metric == *metric))
+            .map(|(metric, p99_ms)| StageTarget { metric: metric.clone(), p99_ms: *p99_ms })
+            .collect();
+        extra.sort_by(|a, b| a.metric.cmp(&b.metric));
+        stages.extend(extra);
+        Self { stages, max_regression }
+    }
+
+    /// Each stage of `metrics` against its target and, when given, the same
+    /// stage of `baseline`; stages without samples are left out
+    pub fn check(&self, metrics: &AggregateMetrics, baseline: Option<&AggregateMetrics>) -> TargetReport {
+        let stages = self
+            .stages
+            .iter()
+            .filter_map(|target| {
+                let p99_ms = metrics.percentile(&target.metric, 99.0)? / 1000.0;
+                let baseline_ms = baseline.and_then(|baseline| baseline.percentile(&target.metric, 99.0)).map(|us| us / 1000.0);
+                Some(StageResult {
+                    metric: target.metric.clone(),
+                    p99_ms,
+                    target_ms: target.p99_ms,
+                    baseline_ms,
+                    met_target: p99_ms < target.p99_ms,
+                    regressed: baseline_ms.is_some_and(|baseline_ms| p99_ms > baseline_ms * (1.0 + self.max_regression)),
+                })
+            })
+            .collect();
+        TargetReport { stages }
+    }
+}
+
+/// One stage's P99 against its target and the baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageResult {
+    pub metric: String,
+    pub p99_ms: f64,
+    pub target_ms: f64,
+    pub baseline_ms: Option<f64>,
+    pub met_target: bool,
+    /// Slower than the baseline by more than the allowed regression
+    pub regressed: bool,
+}
+
+impl StageResult {
+    pub fn passed(&self) -> bool {
+        self.met_target && !self.regressed
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TargetReport {
+    pub stages: Vec<StageResult>,
+}
+
+impl TargetReport {
+    pub fn failures(&self) -> impl Iterator<Item = &StageResult> {
+        self.stages.iter().filter(|stage| !stage.passed())
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    pub fn print(&self) {
+        info!("\nValidating Performance Targets");
+        info!("==================================");
+        for stage in &self.stages {
+            let baseline = match stage.baseline_ms {
+                Some(baseline_ms) => format!(" [Baseline: {:.2}ms]", baseline_ms),
+                None => String::new(),
+            };
+            let status = match (stage.met_target, stage.regressed) {
+                (true, false) => "[OK]",
+                (false, _) => "[FAIL]",
+                (true, true) => "[REGRESSED]",
+            };
+            info!("{} (P99): {:.2}ms [Target: <{}ms]{} {}", stage.metric, stage.p99_ms, stage.target_ms, baseline, status);
+        }
+        if self.passed() {
+            info!("\nALL PERFORMANCE TARGETS MET!");
+        } else {
+            info!("\nSome performance targets not met (see above)");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(simulation_us: f64) -> AggregateMetrics {
+        let mut metrics = AggregateMetrics::new();
+        metrics.latencies = (0..100)
+            .map(|_| HashMap::from([("simulation_us".to_string(), simulation_us)]))
+            .collect();
+        metrics
+    }
+
+    #[test]
+    fn test_targets_and_baseline_regressions() {
+        let overrides = HashMap::from([("simulation_us".to_string(), 3.0), ("decode_us".to_string(), 0.5)]);
+        let targets = PerformanceTargets::new(&overrides, 0.10);
+        assert_eq!(targets.stages.len(), 5);
+        assert_eq!(targets.stages[2], StageTarget { metric: "simulation_us".to_string(), p99_ms: 3.0 });
+        assert_eq!(targets.stages[4].metric, "decode_us");
+
+        // Without a baseline only the targets apply
+        let report = targets.check(&run(2_000.0), None);
+        assert_eq!(report.stages.len(), 1);
+        assert!(report.passed());
+        assert!(!targets.check(&run(3_500.0), None).passed());
+
+        // Within 10% of the baseline passes; beyond it fails though under target
+        assert!(targets.check(&run(2_150.0), Some(&run(2_000.0))).passed());
+        let report = targets.check(&run(2_500.0), Some(&run(2_000.0)));
+        assert!(report.stages[0].met_target && report.stages[0].regressed);
+        assert_eq!(report.failures().count(), 1);
+    }
+}
@@ -0,0 +1,206 @@
+use ethers::types::{Address, U256};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::mempool_streamer::{TrafficProfile, TransactionType};
+use crate::protocol::SIMPLE_LENDING_LIQUIDATION_THRESHOLD as LIQUIDATION_THRESHOLD;
+
+/// Parameters for how synthetic users and the ETH price evolve
+#[derive(Debug, Clone)]
+pub struct PopulationModel {
+    /// ETH price at the start of the run
+    pub initial_eth_price_usd: f64,
+    /// Maximum relative price move per transaction (uniform in ±volatility)
+    pub price_volatility: f64,
+    /// Constant relative price drift per transaction (negative = bear market)
+    pub price_drift: f64,
+    /// Health factor range borrowers aim for when taking on debt
+    pub target_health_factor: (f64, f64),
+}
+
+impl Default for PopulationModel {
+    fn default() -> Self {
+        Self {
+            initial_eth_price_usd: 2000.0,
+            price_volatility: 0.002,
+            price_drift: -0.00001,
+            target_health_factor: (1.02, 1.60),
+        }
+    }
+}
+
+/// Synthetic position in the same units as the protocol (wei collateral, 18-decimal USD debt)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyntheticPosition {
+    pub collateral: U256,
+    pub debt: U256,
+}
+
+#[derive(Debug)]
+struct PopulationState {
+    eth_price_usd: f64,
+    positions: HashMap<Address, SyntheticPosition>,
+}
+
+/// Persistent set of synthetic borrowers whose positions build up over a backtest
+///
+/// The streamer applies each generated transaction here and the detector reads
+/// positions back, so price moves organically push users below HF 1.0.
+#[derive(Debug)]
+pub struct UserPopulation {
+    model: PopulationModel,
+    users: Vec<Address>,
+    state: RwLock<PopulationState>,
+}
+
+impl UserPopulation {
+    pub fn new(model: PopulationModel, user_count: usize, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let users = (0..user_count.max(1))
+            .map(|_| Address::from(rng.gen::<[u8; 20]>()))
+            .collect();
+
+        let state = RwLock::new(PopulationState {
+            eth_price_usd: model.initial_eth_price_usd,
+            positions: HashMap::new(),
+        });
+
+        Self { model, users, state }
+    }
+
+    pub fn users(&self) -> &[Address] {
+        &self.users
+    }
+
+    pub fn eth_price_usd(&self) -> f64 {
+        self.state.read().unwrap().eth_price_usd
+    }
+
+    /// Drop every position and restore the initial ETH price
+    pub fn reset(&self) {
+        let mut state = self.state.write().unwrap();
+        state.eth_price_usd = self.model.initial_eth_price_usd;
+        state.positions.clear();
+    }
+
+    /// Advance the ETH price by one random-walk step
+    pub fn step_price(&self, rng: &mut StdRng) {
+        let shock = if self.model.price_volatility > 0.0 {
+            rng.gen_range(-self.model.price_volatility..self.model.price_volatility)
+        } else {
+            0.0
+        };
+        let mut state = self.state.write().unwrap();
+        state.eth_price_usd = (state.eth_price_usd * (1.0 + self.model.price_drift + shock)).max(1.0);
+    }
+
+    /// Apply a transaction of `tx_type` from `user` and return the amount it carries
+    ///
+    /// Amounts are drawn from `profile` and then clamped the way the protocol
+    /// would allow (borrows up to the user's target HF, withdrawals keeping HF >= 1.0).
+    pub fn apply(
+        &self,
+        rng: &mut StdRng,
+        user: Address,
+        tx_type: TransactionType,
+        profile: &TrafficProfile,
+    ) -> U256 {
+        let target_hf = rng.gen_range(self.model.target_health_factor.0..=self.model.target_health_factor.1);
+        let mut state = self.state.write().unwrap();
+        let price = state.eth_price_usd;
+        let position = state.positions.entry(user).or_default();
+
+        let collateral_eth = to_f64(position.collateral);
+        let debt_usd = to_f64(position.debt);
+        let max_borrow_usd = collateral_eth * price / LIQUIDATION_THRESHOLD;
+
+        match tx_type {
+            TransactionType::Deposit => {
+                let amount = sample(rng, profile.deposit_eth);
+                position.collateral += from_f64(amount);
+                from_f64(amount)
+            }
+            TransactionType::Borrow => {
+                let headroom = (max_borrow_usd / target_hf - debt_usd).max(0.0);
+                let amount = sample(rng, profile.borrow_usd).min(headroom);
+                position.debt += from_f64(amount);
+                from_f64(amount)
+            }
+            TransactionType::Withdraw => {
+                let locked_eth = debt_usd * LIQUIDATION_THRESHOLD / price;
+                let free_eth = (collateral_eth - locked_eth).max(0.0);
+                let amount = from_f64(sample(rng, profile.withdraw_eth).min(free_eth));
+                position.collateral = position.collateral.saturating_sub(amount);
+                amount
+            }
+            TransactionType::Repay | TransactionType::Liquidate => {
+                let amount = from_f64(sample(rng, profile.repay_usd)).min(position.debt);
+                position.debt -= amount;
+                amount
+            }
+        }
+    }
+
+    /// Current (collateral, debt, health factor) for `user`, health factor scaled by 100
+    /// like `getPosition` on the protocol
+    pub fn position(&self, user: Address) -> (U256, U256, U256) {
+        let state = self.state.read().unwrap();
+        let position = state.positions.get(&user).copied().unwrap_or_default();
+
+        if position.debt.is_zero() {
+            return (position.collateral, position.debt, U256::MAX);
+        }
+
+        let max_borrow_usd = to_f64(position.collateral) * state.eth_price_usd / LIQUIDATION_THRESHOLD;
+        let health_factor = (max_borrow_usd / to_f64(position.debt) * 100.0) as u64;
+        (position.collateral, position.debt, U256::from(health_factor))
+    }
+
+    /// Number of users currently below HF 1.0
+    pub fn liquidatable_count(&self) -> usize {
+        self.users
+            .iter()
+            .filter(|user| {
+                let (_, debt, health_factor) = self.position(**user);
+                !debt.is_zero() && health_factor < U256::from(100)
+            })
+            .count()
+    }
+}
+
+fn sample(rng: &mut StdRng, range: (f64, f64)) -> f64 {
+    if range.1 > range.0 { rng.gen_range(range.0..range.1) } else { range.0 }
+}
+
+fn to_f64(amount: U256) -> f64 {
+    amount.as_u128() as f64 / 1e18
+}
+
+fn from_f64(amount: f64) -> U256 {
+    U256::from((amount.max(0.0) * 1e6) as u128) * U256::exp10(12)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_crash_makes_borrowers_liquidatable() {
+        let model = PopulationModel { price_volatility: 0.0, price_drift: 0.0, ..Default::default() };
+        let population = UserPopulation::new(model, 10, 1);
+        let profile = TrafficProfile { borrow_usd: (50_000.0, 50_000.0), ..Default::default() };
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for user in population.users().to_vec() {
+            population.apply(&mut rng, user, TransactionType::Deposit, &profile);
+            population.apply(&mut rng, user, TransactionType::Borrow, &profile);
+        }
+        assert_eq!(population.liquidatable_count(), 0);
+
+        // Borrowers sit at HF <= 1.6, so a 50% crash pushes all of them under
+        population.state.write().unwrap().eth_price_usd = 1000.0;
+        assert_eq!(population.liquidatable_count(), 10);
+    }
+}
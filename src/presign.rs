@@ -0,0 +1,195 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{transaction::eip2718::TypedTransaction, Address, Bytes, H256, U256};
+use ethers::utils::keccak256;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// A liquidation signed ahead of its trigger
+#[derive(Debug, Clone)]
+pub struct PresignedLiquidation {
+    pub user: Address,
+    pub wallet: Address,
+    pub debt_to_cover: U256,
+    /// Request that was signed, nonce and fees included
+    pub tx: TypedTransaction,
+    pub raw: Bytes,
+    pub tx_hash: H256,
+    pub signed_at: Instant,
+}
+
+impl PresignedLiquidation {
+    /// Sign `tx` (which must carry its nonce) with `wallet`
+    pub fn sign(user: Address, debt_to_cover: U256, wallet: &LocalWallet, mut tx: TypedTransaction) -> Result<Self> {
+        tx.set_from(wallet.address());
+        let signature = wallet.sign_transaction_sync(&tx)?;
+        let raw = tx.rlp_signed(&signature);
+        Ok(Self {
+            user,
+            wallet: wallet.address(),
+            debt_to_cover,
+            tx_hash: H256::from(keccak256(&raw)),
+            tx,
+            raw,
+            signed_at: Instant::now(),
+        })
+    }
+
+    /// This liquidation sent with `nonce` repaying `debt_to_cover` through `data`
+    /// under at least `gas_limit`; re-signs (keeping the presigned fees) only if
+    /// the nonce or calldata differs from what was signed, or its gas limit is
+    /// too low. Returns whether it had to.
+    pub fn patch(self, wallet: &LocalWallet, nonce: U256, debt_to_cover: U256, data: Bytes, gas_limit: U256) -> Result<(Self, bool)> {
+        let enough_gas = self.tx.gas().is_some_and(|gas| *gas >= gas_limit);
+        if self.tx.nonce() == Some(&nonce) && self.tx.data() == Some(&data) && enough_gas {
+            return Ok((self, false));
+        }
+        let mut tx = self.tx;
+        tx.set_nonce(nonce).set_gas(gas_limit).set_data(data);
+        let patched = Self::sign(self.user, debt_to_cover, wallet, tx)?;
+        Ok((Self { signed_at: self.signed_at, ..patched }, true))
+    }
+}
+
+/// How often triggered liquidations found a usable presigned transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PresignStats {
+    pub size: usize,
+    /// Sent exactly as presigned
+    pub hits: usize,
+    /// Presigned fees reused, but re-signed for a new nonce or calldata
+    pub patched: usize,
+    /// Nothing cached (or too old), built from scratch
+    pub misses: usize,
+    /// Signed but never sent, since the opportunity was dropped
+    pub discarded: usize,
+}
+
+/// Presigned liquidations for watchlisted users
+///
+/// Entries older than `max_age` are not used, since their fees no longer
+/// reflect the base fee.
+#[derive(Debug)]
+pub struct PresignCache {
+    entries: DashMap<Address, PresignedLiquidation>,
+    max_age: Duration,
+    hits: AtomicUsize,
+    patched: AtomicUsize,
+    misses: AtomicUsize,
+    discarded: AtomicUsize,
+}
+
+impl PresignCache {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            entries: DashMap::new(),
+            max_age,
+            hits: AtomicUsize::new(0),
+            patched: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+            discarded: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn insert(&self, entry: PresignedLiquidation) {
+        self.entries.insert(entry.user, entry);
+    }
+
+    /// Whether `user` has an entry young enough to be used
+    pub fn is_fresh(&self, user: Address) -> bool {
+        self.entries.get(&user).is_some_and(|entry| entry.signed_at.elapsed() <= self.max_age)
+    }
+
+    /// Remove and return `user`'s entry if it is still fresh
+    pub fn take(&self, user: Address) -> Option<PresignedLiquidation> {
+        let entry = self.entries.remove(&user).map(|(_, entry)| entry);
+        let fresh = entry.filter(|entry| entry.signed_at.elapsed() <= self.max_age);
+        if fresh.is_none() {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        fresh
+    }
+
+    /// Count a taken entry as sent as-is or re-signed
+    pub fn record_use(&self, patched: bool) {
+        let counter = if patched { &self.patched } else { &self.hits };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Drop `user`'s entry without sending it; returns whether there was one
+    pub fn discard(&self, user: Address) -> bool {
+        let removed = self.entries.remove(&user).is_some();
+        if removed {
+            self.discarded.fetch_add(1, Ordering::Relaxed);
+        }
+        removed
+    }
+
+    /// Drop entries for users no longer near liquidation
+    pub fn retain(&self, keep: impl Fn(&Address) -> bool) {
+        self.entries.retain(|user, _| keep(user));
+    }
+
+    pub fn stats(&self) -> PresignStats {
+        PresignStats {
+            size: self.entries.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            patched: self.patched.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            discarded: self.discarded.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::LiquidationExecutor;
+    use ethers::types::Eip1559TransactionRequest;
+
+    #[test]
+    fn test_patch_resigns_only_on_change() {
+        let wallet: LocalWallet = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let user = Address::from_low_u64_be(7);
+        let debt = U256::exp10(21);
+        let tx = Eip1559TransactionRequest::new()
+            .to(Address::from_low_u64_be(1))
+            .data(LiquidationExecutor::encode_liquidate_call(user, debt))
+            .nonce(5u64)
+            .gas(350_000u64)
+            .max_fee_per_gas(100u64)
+            .max_priority_fee_per_gas(2u64)
+            .chain_id(31337u64)
+            .into();
+        let signed = PresignedLiquidation::sign(user, debt, &wallet, tx).unwrap();
+        assert_eq!(signed.tx_hash, H256::from(keccak256(&signed.raw)));
+
+        let call = |debt| LiquidationExecutor::encode_liquidate_call(user, debt);
+        let (same, resigned) = signed.clone().patch(&wallet, U256::from(5), debt, call(debt), U256::from(300_000)).unwrap();
+        assert!(!resigned);
+        assert_eq!(same.raw, signed.raw);
+
+        // A higher simulated gas need than presigned forces a re-sign on its own
+        let (raised, resigned) = signed.clone().patch(&wallet, U256::from(5), debt, call(debt), U256::from(400_000)).unwrap();
+        assert!(resigned);
+        assert_eq!(raised.tx.gas(), Some(&U256::from(400_000)));
+
+        let (bumped, resigned) = signed.clone().patch(&wallet, U256::from(6), debt / 2, call(debt / 2), U256::from(300_000)).unwrap();
+        assert!(resigned);
+        assert_ne!(bumped.tx_hash, signed.tx_hash);
+        assert_eq!(bumped.tx.nonce(), Some(&U256::from(6)));
+        assert_eq!(bumped.tx.gas_price(), signed.tx.gas_price());
+        assert_eq!(bumped.tx.data(), Some(&call(debt / 2)));
+
+        let cache = PresignCache::new(Duration::ZERO);
+        cache.insert(signed);
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(cache.take(user).is_none());
+        assert_eq!(cache.stats(), PresignStats { size: 0, hits: 0, patched: 0, misses: 1, discarded: 0 });
+        cache.insert(bumped);
+        assert!(cache.discard(user));
+        assert!(!cache.discard(user));
+        assert_eq!(cache.stats().discarded, 1);
+    }
+}
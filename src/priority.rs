@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::liquidation_detector::LiquidationSignal;
+
+/// Collateral bonus assumed before the simulator has read the real one
+const ASSUMED_BONUS: f64 = 0.10;
+/// Least chance of winning assumed for a deeply underwater position
+const MIN_WIN_PROBABILITY: f64 = 0.2;
+/// Weight of a signal built from a stale position, which may no longer hold
+const STALE_DISCOUNT: f64 = 0.5;
+
+/// How much a signal is worth processing before the others
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpportunityScore {
+    /// Bonus on the debt, capped by the collateral, before gas
+    pub expected_profit_usd: f64,
+    /// Chance no rival liquidates first
+    pub win_probability: f64,
+    /// Share of the signal's lifetime left (1 when new, 0 once expired)
+    pub freshness: f64,
+}
+
+impl OpportunityScore {
+    /// Score `signal` at `eth_price_usd`, `age` into a lifetime of `max_age`
+    pub fn new(signal: &LiquidationSignal, eth_price_usd: f64, age: Duration, max_age: Duration) -> Self {
+        // The debt is a stablecoin, so its token count is its USD value
+        let debt_usd = signal.debt.to_tokens();
+        let collateral_usd = signal.collateral.value_usd(eth_price_usd);
+        // The further below the threshold, the longer the position has been open to every rival
+        let health_factor = signal.health_factor.to_threshold().min(1.0);
+        Self {
+            expected_profit_usd: (debt_usd * (1.0 + ASSUMED_BONUS)).min(collateral_usd) - debt_usd,
+            win_probability: health_factor.max(MIN_WIN_PROBABILITY) * if signal.stale { STALE_DISCOUNT } else { 1.0 },
+            freshness: 1.0 - (age.as_secs_f64() / max_age.as_secs_f64().max(f64::EPSILON)).min(1.0),
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.expected_profit_usd * self.win_probability * self.freshness
+    }
+}
+
+/// What happened to the signals offered to the queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueStats {
+    pub queued: usize,
+    pub dispatched: usize,
+    /// Expired before a worker was free
+    pub dropped_stale: usize,
+    /// Lowest-scoring signal pushed out of a full queue
+    pub dropped_full: usize,
+}
+
+struct Queued {
+    signal: LiquidationSignal,
+    eth_price_usd: f64,
+    received: Instant,
+}
+
+/// Bounded queue handing out the highest-scoring signal first
+///
+/// Scores decay with age, so they are recomputed on every pop; the queue is
+/// small enough for a linear scan.
+pub struct OpportunityQueue {
+    entries: Vec<Queued>,
+    capacity: usize,
+    max_age: Duration,
+    stats: QueueStats,
+}
+
+impl OpportunityQueue {
+    pub fn new(capacity: usize, max_age: Duration) -> Self {
+        Self {
+            entries: Vec::new(),
+            capacity: capacity.max(1),
+            max_age,
+            stats: QueueStats::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        self.stats
+    }
+
+    /// Queue `signal`, pushing out the lowest-scoring entry (possibly `signal`
+    /// itself) when full
+    pub fn push(&mut self, signal: LiquidationSignal, eth_price_usd: f64) {
+        let received = signal.metrics.t_signal.unwrap_or(signal.metrics.t_received);
+        self.stats.queued += 1;
+        self.entries.push(Queued { signal, eth_price_usd, received });
+        if self.entries.len() > self.capacity {
+            let lowest = self.rank(Instant::now()).min_by(|a, b| a.1.total_cmp(&b.1)).map(|(i, _)| i);
+            if let Some(lowest) = lowest {
+                self.entries.swap_remove(lowest);
+                self.stats.dropped_full += 1;
+            }
+        }
+    }
+
+    /// Highest-scoring signal still within its lifetime, dropping expired ones
+    pub fn pop(&mut self) -> Option<LiquidationSignal> {
+        let now = Instant::now();
+        let before = self.entries.len();
+        self.entries.retain(|entry| now.duration_since(entry.received) < self.max_age);
+        self.stats.dropped_stale += before - self.entries.len();
+
+        let best = self.rank(now).max_by(|a, b| a.1.total_cmp(&b.1)).map(|(i, _)| i)?;
+        self.stats.dispatched += 1;
+        Some(self.entries.swap_remove(best).signal)
+    }
+
+    fn rank(&self, now: Instant) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.entries.iter().enumerate().map(move |(i, entry)| {
+            let age = now.duration_since(entry.received);
+            (i, OpportunityScore::new(&entry.signal, entry.eth_price_usd, age, self.max_age).value())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::LatencyMetrics;
+    use crate::protocol::HealthFactorScale;
+    use crate::units::{HealthFactor, TokenAmount};
+    use ethers::types::{Address, U256};
+
+    fn signal(user: u64, debt_usd: u64, health_factor: u64) -> LiquidationSignal {
+        LiquidationSignal {
+            user: Address::from_low_u64_be(user),
+            collateral: TokenAmount::wad(U256::from(100u64) * U256::exp10(18)),
+            debt: TokenAmount::wad(U256::from(debt_usd) * U256::exp10(18)),
+            health_factor: HealthFactor::new(U256::from(health_factor), HealthFactorScale::PERCENT),
+            collateral_asset: Address::zero(),
+            debt_asset: Address::zero(),
+            trigger: None,
+            observed_block: None,
+            stale: false,
+            metrics: LatencyMetrics::new(),
+        }
+    }
+
+    #[test]
+    fn test_queue_orders_and_drops() {
+        let score = OpportunityScore::new(&signal(1, 10_000, 90), 2000.0, Duration::from_secs(3), Duration::from_secs(12));
+        assert!((score.expected_profit_usd - 1_000.0).abs() < 1e-6);
+        assert!((score.value() - 1_000.0 * 0.9 * 0.75).abs() < 1e-6);
+
+        let mut queue = OpportunityQueue::new(2, Duration::from_secs(12));
+        queue.push(signal(1, 5_000, 95), 2000.0);
+        queue.push(signal(2, 50_000, 95), 2000.0);
+        // Full: the smallest opportunity is the one pushed out
+        queue.push(signal(3, 1_000, 95), 2000.0);
+        assert_eq!(queue.pop().map(|s| s.user), Some(Address::from_low_u64_be(2)));
+        assert_eq!(queue.pop().map(|s| s.user), Some(Address::from_low_u64_be(1)));
+        assert!(queue.pop().is_none());
+        assert_eq!(queue.stats(), QueueStats { queued: 3, dispatched: 2, dropped_stale: 0, dropped_full: 1 });
+
+        let mut expiring = OpportunityQueue::new(4, Duration::ZERO);
+        expiring.push(signal(4, 5_000, 95), 2000.0);
+        assert!(expiring.pop().is_none());
+        assert_eq!(expiring.stats().dropped_stale, 1);
+    }
+}
@@ -0,0 +1,453 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use crate::backpressure::BackpressureStats;
+use crate::calibration::CalibrationStats;
+use crate::inclusion::{InclusionOutcome, Settlement, SubmittedLiquidation};
+use crate::liquidation_detector::StaleStats;
+use crate::metrics::{LatencyMetrics, MetricLabels};
+
+/// Prometheus metric type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// One exported metric; the dashboard is generated from these definitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricDef {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: MetricKind,
+    pub labels: &'static [&'static str],
+    /// Grafana unit id for panels showing this metric
+    pub unit: &'static str,
+}
+
+pub const PIPELINE_LATENCY: MetricDef = MetricDef {
+    name: "liquidio_pipeline_latency_microseconds",
+    help: "Latency of each pipeline stage",
+    kind: MetricKind::Histogram,
+    labels: &["stage", "protocol", "chain", "market"],
+    unit: "µs",
+};
+
+pub const TRANSACTIONS_PROCESSED: MetricDef = MetricDef {
+    name: "liquidio_transactions_processed_total",
+    help: "Mempool transactions run through the detector",
+    kind: MetricKind::Counter,
+    labels: &[],
+    unit: "short",
+};
+
+pub const LIQUIDATION_ATTEMPTS: MetricDef = MetricDef {
+    name: "liquidio_liquidation_attempts_total",
+    help: "Liquidation opportunities dispatched, by result",
+    kind: MetricKind::Counter,
+    labels: &["result", "protocol", "chain", "market"],
+    unit: "short",
+};
+
+pub const INCLUSION_OUTCOMES: MetricDef = MetricDef {
+    name: "liquidio_inclusion_outcomes_total",
+    help: "On-chain submissions by how their race ended",
+    kind: MetricKind::Counter,
+    labels: &["outcome", "protocol", "chain", "market"],
+    unit: "short",
+};
+
+pub const EXPECTED_PROFIT: MetricDef = MetricDef {
+    name: "liquidio_expected_profit_usd_total",
+    help: "Expected profit of executed liquidations",
+    kind: MetricKind::Counter,
+    labels: &["protocol", "chain", "market"],
+    unit: "currencyUSD",
+};
+
+pub const REALIZED_PROFIT: MetricDef = MetricDef {
+    name: "liquidio_realized_profit_usd_total",
+    help: "Profit of won liquidations as settled on chain",
+    kind: MetricKind::Counter,
+    labels: &["protocol", "chain", "market"],
+    unit: "currencyUSD",
+};
+
+pub const SETTLEMENT_MISMATCHES: MetricDef = MetricDef {
+    name: "liquidio_settlement_mismatches_total",
+    help: "Won liquidations whose Liquidate event disagreed with the simulation",
+    kind: MetricKind::Counter,
+    labels: &["protocol", "chain", "market"],
+    unit: "short",
+};
+
+pub const WATCHLIST_SIZE: MetricDef = MetricDef {
+    name: "liquidio_watchlist_size",
+    help: "Positions near liquidation re-read on every price tick",
+    kind: MetricKind::Gauge,
+    labels: &[],
+    unit: "short",
+};
+
+pub const CLOCK_ANOMALIES: MetricDef = MetricDef {
+    name: "liquidio_clock_anomalies_total",
+    help: "Attempts whose timestamps failed the monotonic clock audit",
+    kind: MetricKind::Counter,
+    labels: &[],
+    unit: "short",
+};
+
+pub const MEMPOOL_DROPPED: MetricDef = MetricDef {
+    name: "liquidio_mempool_dropped_total",
+    help: "Transactions dropped by the backpressure policy, by reason",
+    kind: MetricKind::Counter,
+    labels: &["reason"],
+    unit: "short",
+};
+
+pub const MEMPOOL_BUFFER_CAPACITY: MetricDef = MetricDef {
+    name: "liquidio_mempool_buffer_capacity",
+    help: "Transactions the buffer ahead of the detector holds",
+    kind: MetricKind::Gauge,
+    labels: &[],
+    unit: "short",
+};
+
+pub const STALE_POSITIONS: MetricDef = MetricDef {
+    name: "liquidio_stale_positions_total",
+    help: "Liquidatable positions found too many blocks old, by action taken",
+    kind: MetricKind::Counter,
+    labels: &["action"],
+    unit: "short",
+};
+
+pub const STAGE_THROUGHPUT: MetricDef = MetricDef {
+    name: "liquidio_stage_items_total",
+    help: "Items through each pipeline stage: transactions classified, signals raised, simulations run",
+    kind: MetricKind::Counter,
+    labels: &["stage"],
+    unit: "ops",
+};
+
+pub const QUEUE_DEPTH: MetricDef = MetricDef {
+    name: "liquidio_queue_depth",
+    help: "Items currently waiting in each pipeline queue",
+    kind: MetricKind::Gauge,
+    labels: &["queue"],
+    unit: "short",
+};
+
+pub const SIMULATION_BIAS: MetricDef = MetricDef {
+    name: "liquidio_simulation_bias",
+    help: "Mean realized minus simulated value over recent settlements: profit in USD, gas as a ratio of the estimate",
+    kind: MetricKind::Gauge,
+    labels: &["quantity"],
+    unit: "short",
+};
+
+pub const SIMULATION_DRIFT_ALERTS: MetricDef = MetricDef {
+    name: "liquidio_simulation_drift_alerts_total",
+    help: "Settlements after which simulator bias exceeded its threshold",
+    kind: MetricKind::Counter,
+    labels: &[],
+    unit: "short",
+};
+
+pub const MARKET_HALTED: MetricDef = MetricDef {
+    name: "liquidio_market_halted",
+    help: "1 while the protocol is paused or a market asset frozen, so its liquidations are suppressed",
+    kind: MetricKind::Gauge,
+    labels: &["protocol", "market"],
+    unit: "short",
+};
+
+pub const SIMULATION_WORKER_LATENCY: MetricDef = MetricDef {
+    name: "liquidio_simulation_worker_latency_microseconds",
+    help: "Time each simulation worker spent per simulation",
+    kind: MetricKind::Histogram,
+    labels: &["worker"],
+    unit: "µs",
+};
+
+pub const ROUTE_SUBMISSION_LATENCY: MetricDef = MetricDef {
+    name: "liquidio_route_submission_latency_microseconds",
+    help: "Time each submission route took to accept a signed liquidation, resends included",
+    kind: MetricKind::Histogram,
+    labels: &["route"],
+    unit: "µs",
+};
+
+pub const ROUTE_INCLUSION_OUTCOMES: MetricDef = MetricDef {
+    name: "liquidio_route_inclusion_outcomes_total",
+    help: "On-chain submissions by route and how their race ended",
+    kind: MetricKind::Counter,
+    labels: &["route", "outcome"],
+    unit: "short",
+};
+
+pub const ROUTE_BLOCKS_TO_INCLUSION: MetricDef = MetricDef {
+    name: "liquidio_route_blocks_to_inclusion_total",
+    help: "Blocks from send to inclusion summed over won submissions; divide by won outcomes for the mean",
+    kind: MetricKind::Counter,
+    labels: &["route"],
+    unit: "short",
+};
+
+/// Every metric the bot exports
+pub const METRICS: &[MetricDef] = &[
+    PIPELINE_LATENCY,
+    TRANSACTIONS_PROCESSED,
+    LIQUIDATION_ATTEMPTS,
+    INCLUSION_OUTCOMES,
+    EXPECTED_PROFIT,
+    REALIZED_PROFIT,
+    SETTLEMENT_MISMATCHES,
+    WATCHLIST_SIZE,
+    CLOCK_ANOMALIES,
+    MEMPOOL_DROPPED,
+    MEMPOOL_BUFFER_CAPACITY,
+    STALE_POSITIONS,
+    STAGE_THROUGHPUT,
+    QUEUE_DEPTH,
+    SIMULATION_BIAS,
+    SIMULATION_DRIFT_ALERTS,
+    MARKET_HALTED,
+    SIMULATION_WORKER_LATENCY,
+    ROUTE_SUBMISSION_LATENCY,
+    ROUTE_INCLUSION_OUTCOMES,
+    ROUTE_BLOCKS_TO_INCLUSION,
+];
+
+/// Bucket upper bounds for latency histograms, in microseconds
+const LATENCY_BUCKETS_US: [f64; 12] = [
+    10.0, 50.0, 100.0, 250.0, 500.0, 1_000.0, 2_500.0, 5_000.0, 10_000.0, 50_000.0, 100_000.0, 1_000_000.0,
+];
+
+/// Pipeline stages as keyed by `LatencyMetrics::get_all_latencies`
+pub const STAGES: [&str; 6] = [
+    "decode",
+    "signal_detection",
+    "simulation",
+    "construction",
+    "end_to_end",
+    "submit_to_inclusion",
+];
+
+#[derive(Debug, Clone)]
+enum Sample {
+    Value(f64),
+    Histogram { buckets: [u64; LATENCY_BUCKETS_US.len()], sum: f64, count: u64 },
+}
+
+/// Current values of every metric in `METRICS`, rendered in the Prometheus text format
+#[derive(Debug, Default)]
+pub struct PrometheusRegistry {
+    // (metric name, rendered label set) -> sample
+    samples: Mutex<BTreeMap<(&'static str, String), Sample>>,
+}
+
+impl PrometheusRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `by` to a counter
+    pub fn inc(&self, metric: &MetricDef, labels: &[&str], by: f64) {
+        let mut samples = self.samples.lock().unwrap();
+        if let Sample::Value(v) = samples.entry(key(metric, labels)).or_insert(Sample::Value(0.0)) {
+            *v += by;
+        }
+    }
+
+    pub fn set(&self, metric: &MetricDef, labels: &[&str], value: f64) {
+        self.samples.lock().unwrap().insert(key(metric, labels), Sample::Value(value));
+    }
+
+    pub fn observe(&self, metric: &MetricDef, labels: &[&str], value: f64) {
+        let mut samples = self.samples.lock().unwrap();
+        let empty = Sample::Histogram { buckets: [0; LATENCY_BUCKETS_US.len()], sum: 0.0, count: 0 };
+        if let Sample::Histogram { buckets, sum, count } = samples.entry(key(metric, labels)).or_insert(empty) {
+            for (bucket, bound) in buckets.iter_mut().zip(LATENCY_BUCKETS_US) {
+                *bucket += (value <= bound) as u64;
+            }
+            *sum += value;
+            *count += 1;
+        }
+    }
+
+    /// Record one dispatched opportunity's stage latencies and result in its market
+    pub fn record_attempt(&self, labels: &MetricLabels, metrics: &LatencyMetrics, success: bool) {
+        let (protocol, chain, market) = (labels.protocol.as_str(), labels.chain.as_str(), labels.market.as_str());
+        let latencies = metrics.get_all_latencies();
+        for stage in STAGES {
+            if let Some(us) = latencies.get(&format!("{}_us", stage)) {
+                self.observe(&PIPELINE_LATENCY, &[stage, protocol, chain, market], *us);
+            }
+        }
+        let result = if success { "success" } else { "failure" };
+        self.inc(&LIQUIDATION_ATTEMPTS, &[result, protocol, chain, market], 1.0);
+        if latencies.contains_key("clock_anomalies") {
+            self.inc(&CLOCK_ANOMALIES, &[], 1.0);
+        }
+    }
+
+    /// Count `items` through `stage` (`classified`, `signaled` or `simulated`)
+    pub fn record_stage(&self, stage: &str, items: usize) {
+        self.inc(&STAGE_THROUGHPUT, &[stage], items as f64);
+    }
+
+    /// Attribute one simulation's latency to the worker that ran it
+    pub fn record_simulation(&self, worker: usize, elapsed_us: f64) {
+        self.inc(&STAGE_THROUGHPUT, &["simulated"], 1.0);
+        self.observe(&SIMULATION_WORKER_LATENCY, &[&worker.to_string()], elapsed_us);
+    }
+
+    /// Mirror the mempool buffer's running totals
+    pub fn record_backpressure(&self, stats: &BackpressureStats) {
+        self.set(&MEMPOOL_DROPPED, &["oldest"], stats.dropped_oldest as f64);
+        self.set(&MEMPOOL_DROPPED, &["non_protocol"], stats.dropped_non_protocol as f64);
+        self.set(&MEMPOOL_BUFFER_CAPACITY, &[], stats.capacity as f64);
+    }
+    
+    /// Calibration over the tracker's window, and whether it has drifted
+    pub fn record_calibration(&self, stats: &CalibrationStats, drifted: bool) {
+        self.set(&SIMULATION_BIAS, &["profit_usd"], stats.profit_bias_usd);
+        self.set(&SIMULATION_BIAS, &["gas"], stats.gas_bias_ratio);
+        if drifted {
+            self.inc(&SIMULATION_DRIFT_ALERTS, &[], 1.0);
+        }
+    }
+    
+    pub fn record_stale_positions(&self, stats: &StaleStats) {
+        self.set(&STALE_POSITIONS, &["refreshed"], stats.refreshed as f64);
+        self.set(&STALE_POSITIONS, &["down_ranked"], stats.down_ranked as f64);
+    }
+    
+    pub fn record_inclusion(&self, labels: &MetricLabels, outcome: &InclusionOutcome) {
+        self.inc(&INCLUSION_OUTCOMES, &[outcome_label(outcome), &labels.protocol, &labels.chain, &labels.market], 1.0);
+    }
+
+    /// An on-chain submission's time on its route, and its race once resolved
+    pub fn record_route(&self, submission: &SubmittedLiquidation, metrics: &LatencyMetrics, inclusion: Option<&InclusionOutcome>) {
+        let route = submission.route.name();
+        if let Some(elapsed) = metrics.latency_submission() {
+            self.observe(&ROUTE_SUBMISSION_LATENCY, &[route], elapsed.as_secs_f64() * 1e6);
+        }
+        let Some(outcome) = inclusion else { return };
+        self.inc(&ROUTE_INCLUSION_OUTCOMES, &[route, outcome_label(outcome)], 1.0);
+        if let (InclusionOutcome::Won { block, .. }, Some(target_block)) = (outcome, submission.target_block) {
+            self.inc(&ROUTE_BLOCKS_TO_INCLUSION, &[route], (block + 1).saturating_sub(target_block) as f64);
+        }
+    }
+
+    pub fn record_profit(&self, labels: &MetricLabels, profit_usd: f64) {
+        self.inc(&EXPECTED_PROFIT, &[&labels.protocol, &labels.chain, &labels.market], profit_usd);
+    }
+
+    pub fn record_settlement(&self, labels: &MetricLabels, settlement: &Settlement) {
+        let labels = [labels.protocol.as_str(), &labels.chain, &labels.market];
+        self.inc(&REALIZED_PROFIT, &labels, settlement.realized_profit_usd);
+        if !settlement.matches_simulation {
+            self.inc(&SETTLEMENT_MISMATCHES, &labels, 1.0);
+        }
+    }
+
+    /// Text exposition of every defined metric (HELP/TYPE even before a sample exists)
+    pub fn render(&self) -> String {
+        let samples = self.samples.lock().unwrap();
+        let mut out = String::new();
+
+        for metric in METRICS {
+            let _ = writeln!(out, "# HELP {} {}", metric.name, metric.help);
+            let _ = writeln!(out, "# TYPE {} {}", metric.name, metric.kind.as_str());
+
+            for ((_, labels), sample) in samples.range((metric.name, String::new())..).take_while(|((name, _), _)| *name == metric.name) {
+                match sample {
+                    Sample::Value(v) => {
+                        let _ = writeln!(out, "{}{} {}", metric.name, braces(labels), v);
+                    }
+                    Sample::Histogram { buckets, sum, count } => {
+                        let with_le = |le: &str| {
+                            let le = format!("le=\"{}\"", le);
+                            braces(&if labels.is_empty() { le } else { format!("{},{}", labels, le) })
+                        };
+                        for (bucket, bound) in buckets.iter().zip(LATENCY_BUCKETS_US) {
+                            let _ = writeln!(out, "{}_bucket{} {}", metric.name, with_le(&bound.to_string()), bucket);
+                        }
+                        let _ = writeln!(out, "{}_bucket{} {}", metric.name, with_le("+Inf"), count);
+                        let _ = writeln!(out, "{}_sum{} {}", metric.name, braces(labels), sum);
+                        let _ = writeln!(out, "{}_count{} {}", metric.name, braces(labels), count);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn key(metric: &MetricDef, values: &[&str]) -> (&'static str, String) {
+    let labels = metric
+        .labels
+        .iter()
+        .zip(values)
+        .map(|(label, value)| format!("{}=\"{}\"", label, value.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(",");
+    (metric.name, labels)
+}
+
+fn outcome_label(outcome: &InclusionOutcome) -> &'static str {
+    match outcome {
+        InclusionOutcome::Won { .. } => "won",
+        InclusionOutcome::Lost { .. } => "lost",
+        InclusionOutcome::Reverted { .. } => "reverted",
+        InclusionOutcome::Dropped => "dropped",
+    }
+}
+
+fn braces(labels: &str) -> String {
+    if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Address;
+
+    #[test]
+    fn test_exposition_format() {
+        let registry = PrometheusRegistry::new();
+        let labels = MetricLabels::new("simple-lending", 1).for_market(Address::zero(), Address::from_low_u64_be(0xdead));
+        registry.inc(&LIQUIDATION_ATTEMPTS, &["success", "aave", "1", "ETH/USDC"], 1.0);
+        registry.inc(&LIQUIDATION_ATTEMPTS, &["success", "aave", "1", "ETH/USDC"], 2.0);
+        registry.set(&WATCHLIST_SIZE, &[], 12.0);
+        registry.observe(&PIPELINE_LATENCY, &["decode", "aave", "1", "ETH/USDC"], 75.0);
+        registry.record_inclusion(&labels, &InclusionOutcome::Dropped);
+
+        let text = registry.render();
+        assert!(text.contains("# TYPE liquidio_pipeline_latency_microseconds histogram\n"));
+        assert!(text.contains("liquidio_liquidation_attempts_total{result=\"success\",protocol=\"aave\",chain=\"1\",market=\"ETH/USDC\"} 3\n"));
+        assert!(text.contains("liquidio_watchlist_size 12\n"));
+        let decode = r#"stage="decode",protocol="aave",chain="1",market="ETH/USDC""#;
+        assert!(text.contains(&format!("liquidio_pipeline_latency_microseconds_bucket{{{},le=\"50\"}} 0\n", decode)));
+        assert!(text.contains(&format!("liquidio_pipeline_latency_microseconds_bucket{{{},le=\"100\"}} 1\n", decode)));
+        assert!(text.contains(&format!("liquidio_pipeline_latency_microseconds_count{{{}}} 1\n", decode)));
+        assert!(text.contains(
+            "liquidio_inclusion_outcomes_total{outcome=\"dropped\",protocol=\"simple-lending\",chain=\"1\",market=\"ETH/0x000000000000000000000000000000000000dead\"} 1\n"
+        ));
+        // Metrics without samples still advertise themselves
+        assert!(text.contains("# HELP liquidio_clock_anomalies_total"));
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::types::{Address, U256};
+use std::sync::Arc;
+
+use crate::blockchain::BlockchainClient;
+use crate::fixed_point;
+
+/// Basis-point denominator (10_000 = 100%)
+pub const BPS: u32 = 10_000;
+
+/// Native ETH collateral is keyed by the zero address
+pub const NATIVE_ASSET: Address = Address::zero();
+
+/// Liquidation parameters a protocol applies to one asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetRiskParams {
+    /// Extra collateral paid to the liquidator on top of the repaid value (1_000 = 10%)
+    pub liquidation_bonus_bps: u32,
+    /// Share of a position's debt repayable in one liquidation (10_000 = all of it)
+    pub close_factor_bps: u32,
+}
+
+impl Default for AssetRiskParams {
+    fn default() -> Self {
+        Self {
+            liquidation_bonus_bps: 1_000,
+            close_factor_bps: BPS,
+        }
+    }
+}
+
+impl AssetRiskParams {
+    /// Largest amount of `debt` that can be repaid in one liquidation
+    pub fn max_repayable(&self, debt: U256) -> U256 {
+        // The close factor is at most 100%, so this cannot overflow
+        fixed_point::mul_div(debt, U256::from(self.close_factor_bps.min(BPS)), U256::from(BPS)).unwrap_or(debt)
+    }
+
+    /// Collateral received for repaying `repaid_value` worth of debt, bonus
+    /// included (saturating at U256::MAX)
+    pub fn with_bonus(&self, repaid_value: U256) -> U256 {
+        let factor = U256::from(BPS) + U256::from(self.liquidation_bonus_bps);
+        fixed_point::mul_div(repaid_value, factor, U256::from(BPS)).unwrap_or(U256::MAX)
+    }
+}
+
+/// Whether liquidations in a market can go through right now
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketStatus {
+    Active,
+    /// The protocol is paused or an asset of the market is frozen, so a
+    /// liquidation would revert; the reason says which
+    Halted(String),
+}
+
+/// How a protocol encodes health factors and where liquidation starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthFactorScale {
+    /// Encoded value of HF 1.0 (100 for SimpleLendingProtocol, 1e18 for Aave)
+    pub one: U256,
+    /// Health factor below which a position can be liquidated
+    pub liquidation_threshold: f64,
+}
+
+impl HealthFactorScale {
+    /// Percent-scaled, liquidatable below 1.0 (SimpleLendingProtocol)
+    pub const PERCENT: Self = Self { one: U256([100, 0, 0, 0]), liquidation_threshold: 1.0 };
+    /// 18-decimal fixed point, liquidatable below 1.0 (Aave, Spark)
+    pub const WAD: Self = Self { one: U256([1_000_000_000_000_000_000, 0, 0, 0]), liquidation_threshold: 1.0 };
+
+    /// Encoded `health_factor` as a plain number (1.0 = at the liquidation line for most protocols)
+    pub fn to_f64(&self, health_factor: U256) -> f64 {
+        // Debt-free positions report uint256::MAX on Aave
+        fixed_point::to_f64(health_factor, self.one)
+    }
+
+    /// `health_factor` encoded in this scale
+    pub fn encode(&self, health_factor: f64) -> U256 {
+        fixed_point::from_f64(health_factor, self.one)
+    }
+
+    /// Encoded health factor below which a position can be liquidated
+    pub fn threshold(&self) -> U256 {
+        self.encode(self.liquidation_threshold)
+    }
+
+    pub fn is_liquidatable(&self, health_factor: U256, debt: U256) -> bool {
+        !debt.is_zero() && health_factor < self.threshold()
+    }
+}
+
+impl Default for HealthFactorScale {
+    fn default() -> Self {
+        Self::PERCENT
+    }
+}
+
+/// Protocol-specific reads the pipeline needs from a lending market
+#[async_trait]
+pub trait ProtocolAdapter: Send + Sync {
+    /// Short identifier used in logs and metrics
+    fn name(&self) -> &str;
+
+    /// Liquidation bonus and close factor for `asset`
+    async fn asset_params(&self, asset: Address) -> Result<AssetRiskParams>;
+
+    /// Encoding of the health factors `getPosition` returns
+    fn health_factor_scale(&self) -> HealthFactorScale {
+        HealthFactorScale::PERCENT
+    }
+
+    /// `params` as they apply to a position at `health_factor` (1.0 = the
+    /// liquidation line); protocols whose bonus grows as a position sinks override this
+    fn params_at_health(&self, params: AssetRiskParams, _health_factor: f64) -> AssetRiskParams {
+        params
+    }
+
+    /// Whether liquidating `collateral_asset` for `debt_asset` can succeed;
+    /// protocols without pause switches are always active
+    async fn market_status(&self, _collateral_asset: Address, _debt_asset: Address) -> Result<MarketStatus> {
+        Ok(MarketStatus::Active)
+    }
+}
+
+/// SimpleLendingProtocol.LIQUIDATION_THRESHOLD: collateral must be worth 150%
+/// of the debt
+pub const SIMPLE_LENDING_LIQUIDATION_THRESHOLD: f64 = 1.5;
+
+/// Adapter for SimpleLendingProtocol: one global bonus, full-debt liquidations
+pub struct SimpleLendingAdapter {
+    blockchain: Arc<BlockchainClient>,
+    health_factor_scale: HealthFactorScale,
+}
+
+impl SimpleLendingAdapter {
+    pub fn new(blockchain: Arc<BlockchainClient>) -> Self {
+        Self { blockchain, health_factor_scale: HealthFactorScale::PERCENT }
+    }
+
+    /// For deployments (or forks) that encode health factors differently
+    pub fn with_health_factor_scale(mut self, scale: HealthFactorScale) -> Self {
+        self.health_factor_scale = scale;
+        self
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for SimpleLendingAdapter {
+    fn name(&self) -> &str {
+        "simple-lending"
+    }
+
+    async fn asset_params(&self, _asset: Address) -> Result<AssetRiskParams> {
+        let protocol = &self.blockchain.lending_protocol;
+        let bonus = protocol.liquidation_bonus().call().await?;
+        let precision = protocol.precision().call().await?;
+        anyhow::ensure!(!precision.is_zero() && bonus >= precision, "Unexpected bonus {} / {}", bonus, precision);
+
+        // LIQUIDATION_BONUS = 110 with PRECISION = 100 means a 10% bonus
+        let bonus_bps = fixed_point::mul_div(bonus - precision, U256::from(BPS), precision)?;
+        anyhow::ensure!(bonus_bps <= U256::from(u32::MAX), "Unexpected bonus {} / {}", bonus, precision);
+        Ok(AssetRiskParams {
+            liquidation_bonus_bps: bonus_bps.as_u32(),
+            close_factor_bps: BPS,
+        })
+    }
+
+    fn health_factor_scale(&self) -> HealthFactorScale {
+        self.health_factor_scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_factor_and_bonus() {
+        let params = AssetRiskParams { liquidation_bonus_bps: 500, close_factor_bps: 5_000 };
+        let debt = U256::from(8_000u64);
+
+        assert_eq!(params.max_repayable(debt), U256::from(4_000u64));
+        assert_eq!(params.with_bonus(U256::from(4_000u64)), U256::from(4_200u64));
+        assert_eq!(AssetRiskParams::default().max_repayable(debt), debt);
+    }
+
+    #[test]
+    fn test_health_factor_scales() {
+        let percent = HealthFactorScale::PERCENT;
+        assert_eq!(percent.to_f64(U256::from(95u64)), 0.95);
+        assert!(percent.is_liquidatable(U256::from(99u64), U256::one()));
+        assert!(!percent.is_liquidatable(U256::from(99u64), U256::zero()));
+
+        let wad = HealthFactorScale::WAD;
+        assert_eq!(wad.threshold(), U256::exp10(18));
+        assert_eq!(wad.to_f64(U256::exp10(18) * 3 / 2), 1.5);
+        assert!(wad.is_liquidatable(U256::exp10(17) * 9, U256::one()));
+        assert!(!wad.is_liquidatable(U256::MAX, U256::one()));
+
+        // Some markets liquidate above 1.0
+        let early = HealthFactorScale { liquidation_threshold: 1.05, ..wad };
+        assert!(early.is_liquidatable(U256::exp10(16) * 104, U256::one()));
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// Token bucket allowing `rate` requests per second with bursts of up to one
+/// second's worth
+///
+/// Callers that find the bucket empty reserve their tokens anyway (the balance
+/// goes negative) and sleep until it recovers, so waiters are served in order.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    state: Mutex<BucketState>,
+}
+
+impl TokenBucket {
+    pub fn new(rate_per_sec: f64) -> Self {
+        let rate = rate_per_sec.max(f64::EPSILON);
+        let burst = rate.max(1.0);
+        Self { rate, burst, state: Mutex::new(BucketState { tokens: burst, refilled: Instant::now() }) }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Take `n` tokens at `now`, returning how long the caller must wait before using them
+    fn reserve(&self, n: usize, now: Instant) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let elapsed = now.saturating_duration_since(state.refilled).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.rate).min(self.burst) - n as f64;
+        state.refilled = now;
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / self.rate)
+        }
+    }
+
+    /// Wait until `n` requests may be sent
+    pub async fn acquire(&self, n: usize) {
+        let wait = self.reserve(n, Instant::now());
+        if !wait.is_zero() {
+            debug!("RPC rate limit reached, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Requests per second allowed per RPC endpoint
+///
+/// Endpoints sharing a limit key share one bucket, so several clients of the
+/// same provider stay under its limit together.
+#[derive(Debug, Default)]
+pub struct RateLimits {
+    /// Limit for endpoints matching no key (None = unlimited)
+    default_rps: Option<f64>,
+    /// Limits keyed by a substring of the endpoint, usually the provider's host
+    per_endpoint: HashMap<String, f64>,
+    buckets: Mutex<HashMap<String, Arc<TokenBucket>>>,
+}
+
+impl RateLimits {
+    pub fn new(default_rps: Option<f64>, per_endpoint: HashMap<String, f64>) -> Self {
+        Self { default_rps, per_endpoint, buckets: Mutex::new(HashMap::new()) }
+    }
+
+    /// Bucket for `endpoint`: the longest key it contains, else the default
+    pub fn bucket(&self, endpoint: &str) -> Option<Arc<TokenBucket>> {
+        let matched = self
+            .per_endpoint
+            .iter()
+            .filter(|(key, _)| endpoint.contains(key.as_str()))
+            .max_by_key(|(key, _)| key.len());
+        let (key, rate) = match matched {
+            Some((key, rate)) => (key.clone(), *rate),
+            None => (endpoint.to_string(), self.default_rps?),
+        };
+        let mut buckets = self.buckets.lock().unwrap();
+        Some(buckets.entry(key).or_insert_with(|| Arc::new(TokenBucket::new(rate))).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_and_limits() {
+        let bucket = TokenBucket::new(10.0);
+        let start = Instant::now();
+        // A full second's burst goes out at once, then requests are spaced 100ms apart
+        assert_eq!(bucket.reserve(10, start), Duration::ZERO);
+        assert!((bucket.reserve(1, start).as_secs_f64() - 0.1).abs() < 1e-6);
+        assert!((bucket.reserve(2, start).as_secs_f64() - 0.3).abs() < 1e-6);
+        // Refills at the rate, never beyond the burst
+        assert_eq!(bucket.reserve(1, start + Duration::from_secs(10)), Duration::ZERO);
+
+        let limits = RateLimits::new(None, HashMap::from([("alchemy.com".to_string(), 25.0)]));
+        let a = limits.bucket("https://eth-mainnet.g.alchemy.com/v2/key").unwrap();
+        let b = limits.bucket("https://arb-mainnet.g.alchemy.com/v2/key").unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(a.rate(), 25.0);
+        assert!(limits.bucket("http://127.0.0.1:8545").is_none());
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// What a cached read was
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheKey {
+    Position(Address),
+    GasPrice,
+    EthPrice,
+}
+
+/// Cache lookups answered from memory and those that went to the node
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+struct Entry {
+    block: u64,
+    stored: Instant,
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+/// Read results valid for the block they were read in
+///
+/// Chain state only changes between blocks, so a read is reused until a newer
+/// block is observed. `ttl` bounds how long that can be when no new head is
+/// reported.
+pub struct ReadCache {
+    ttl: Duration,
+    head: AtomicU64,
+    entries: DashMap<CacheKey, Entry>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl ReadCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            head: AtomicU64::new(0),
+            entries: DashMap::new(),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Record the chain head; a newer block drops every cached read
+    pub fn observe_block(&self, block: u64) {
+        if self.head.fetch_max(block, Ordering::AcqRel) < block {
+            self.entries.clear();
+        }
+    }
+
+    /// Drop every cached read (state changed without a new block, e.g. a revert)
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+
+    pub fn get<T: Clone + 'static>(&self, key: CacheKey) -> Option<T> {
+        let head = self.head.load(Ordering::Acquire);
+        let value = self
+            .entries
+            .get(&key)
+            .filter(|entry| entry.block == head && entry.stored.elapsed() < self.ttl)
+            .and_then(|entry| entry.value.downcast_ref::<T>().cloned());
+        let counter = if value.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Store `value`, read while `block` was the head
+    pub fn insert<T: Send + Sync + 'static>(&self, key: CacheKey, block: u64, value: T) {
+        self.entries.insert(key, Entry { block, stored: Instant::now(), value: Arc::new(value) });
+    }
+
+    /// Cached value for `key`, or the result of `fetch` (cached if it succeeds)
+    pub async fn get_or_fetch<T, E, F, Fut>(&self, key: CacheKey, fetch: F) -> Result<T, E>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some(value) = self.get(key) {
+            return Ok(value);
+        }
+        // A block observed mid-fetch leaves this entry already stale
+        let block = self.head.load(Ordering::Acquire);
+        let value = fetch().await?;
+        self.insert(key, block, value.clone());
+        Ok(value)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats { hits: self.hits.load(Ordering::Relaxed), misses: self.misses.load(Ordering::Relaxed) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_scoped_to_block() {
+        let cache = ReadCache::new(Duration::from_secs(60));
+        let user = CacheKey::Position(Address::from_low_u64_be(1));
+        cache.observe_block(10);
+        cache.insert(user, 10, 42u64);
+        assert_eq!(cache.get::<u64>(user), Some(42));
+        // Read before the head moved on: not served
+        cache.insert(CacheKey::GasPrice, 9, 7u64);
+        assert_eq!(cache.get::<u64>(CacheKey::GasPrice), None);
+
+        // An older head changes nothing; a newer one invalidates
+        cache.observe_block(8);
+        assert_eq!(cache.get::<u64>(user), Some(42));
+        cache.observe_block(11);
+        assert_eq!(cache.get::<u64>(user), None);
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 2 });
+
+        let expired = ReadCache::new(Duration::ZERO);
+        expired.insert(user, 0, 1u64);
+        assert_eq!(expired.get::<u64>(user), None);
+    }
+}
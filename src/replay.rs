@@ -0,0 +1,253 @@
+use anyhow::{Context, Result};
+use ethers::types::{Address, U256};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::audit::{AuditEvent, AuditStage};
+use crate::liquidation_detector::LiquidationSignal;
+use crate::metrics::LatencyMetrics;
+use crate::protocol::HealthFactorScale;
+use crate::run_metadata::RunMetadata;
+use crate::units::{HealthFactor, TokenAmount};
+use crate::simulator::LiquidationSimulator;
+
+/// What the pipeline decided for an opportunity when it was recorded
+#[derive(Debug, Clone)]
+pub struct RecordedDecision {
+    pub profitable: bool,
+    pub expected_profit_usd: f64,
+    pub debt_to_cover: U256,
+}
+
+/// Opportunity reconstructed from its journal events
+#[derive(Debug, Clone)]
+pub struct RecordedOpportunity {
+    pub opportunity_id: String,
+    pub signal: LiquidationSignal,
+    /// None when the recorded simulation itself failed
+    pub decision: Option<RecordedDecision>,
+    /// Last stage the opportunity reached
+    pub final_stage: AuditStage,
+}
+
+fn field<T: DeserializeOwned>(details: &Value, key: &str) -> Result<T> {
+    serde_json::from_value(details[key].clone()).with_context(|| format!("Missing or invalid `{}`", key))
+}
+
+/// `key`, or `default` in journals written before it was recorded
+fn field_or<T: DeserializeOwned>(details: &Value, key: &str, default: T) -> Result<T> {
+    match details.get(key) {
+        Some(_) => field(details, key),
+        None => Ok(default),
+    }
+}
+
+/// Group journal events into opportunities, in the order they were detected
+///
+/// Opportunities whose `detected` event is missing (e.g. a truncated journal)
+/// are skipped.
+pub fn load_opportunities(events: &[AuditEvent]) -> Result<Vec<RecordedOpportunity>> {
+    let mut opportunities: Vec<RecordedOpportunity> = Vec::new();
+    let mut index = std::collections::HashMap::new();
+
+    for event in events {
+        if event.stage == AuditStage::Detected {
+            let details = &event.details;
+            let signal = LiquidationSignal {
+                user: event.user,
+                collateral: TokenAmount::new(field(details, "collateral")?, field_or(details, "collateral_decimals", 18)?),
+                debt: TokenAmount::new(field(details, "debt")?, field_or(details, "debt_decimals", 18)?),
+                health_factor: HealthFactor::new(
+                    field(details, "health_factor")?,
+                    HealthFactorScale { one: field_or(details, "health_factor_one", HealthFactorScale::PERCENT.one)?, ..Default::default() },
+                ),
+                collateral_asset: field::<Address>(details, "collateral_asset")?,
+                debt_asset: field::<Address>(details, "debt_asset")?,
+                trigger: None,
+                observed_block: None,
+                stale: false,
+                metrics: LatencyMetrics::new(),
+            };
+            index.insert(event.opportunity_id.clone(), opportunities.len());
+            opportunities.push(RecordedOpportunity {
+                opportunity_id: event.opportunity_id.clone(),
+                signal,
+                decision: None,
+                final_stage: AuditStage::Detected,
+            });
+            continue;
+        }
+
+        let Some(&i) = index.get(&event.opportunity_id) else { continue };
+        let opportunity = &mut opportunities[i];
+        opportunity.final_stage = event.stage;
+        if event.stage == AuditStage::Simulated {
+            opportunity.decision = Some(RecordedDecision {
+                profitable: field(&event.details, "profitable")?,
+                expected_profit_usd: field(&event.details, "expected_profit_usd")?,
+                debt_to_cover: field(&event.details, "debt_to_cover")?,
+            });
+        }
+    }
+
+    Ok(opportunities)
+}
+
+/// Recorded vs replayed decision for one opportunity
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayResult {
+    pub opportunity_id: String,
+    pub user: Address,
+    pub recorded_profitable: Option<bool>,
+    pub replayed_profitable: Option<bool>,
+    pub recorded_profit_usd: Option<f64>,
+    pub replayed_profit_usd: Option<f64>,
+    pub error: Option<String>,
+}
+
+impl ReplayResult {
+    pub fn changed(&self) -> bool {
+        self.recorded_profitable != self.replayed_profitable
+    }
+}
+
+/// Summary of replaying a journal through the current simulator
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    pub opportunities: usize,
+    pub unchanged: usize,
+    /// Recorded as skipped (or failed to simulate), now taken
+    pub newly_taken: usize,
+    /// Recorded as taken, now skipped (or failed to simulate)
+    pub newly_skipped: usize,
+    pub errors: usize,
+    /// Change in expected profit across opportunities taken in both runs
+    pub profit_delta_usd: f64,
+    pub results: Vec<ReplayResult>,
+}
+
+impl ReplayReport {
+    pub fn print(&self) {
+        info!("=== Replay Report ===");
+        info!("Opportunities: {}", self.opportunities);
+        info!("Unchanged decisions: {}", self.unchanged);
+        info!("Newly taken: {}", self.newly_taken);
+        info!("Newly skipped: {}", self.newly_skipped);
+        info!("Replay errors: {}", self.errors);
+        info!("Expected profit delta (taken in both): ${:+.2}", self.profit_delta_usd);
+        for result in self.results.iter().filter(|r| r.changed()) {
+            warn!(
+                "[CHANGED] {} user {:?}: {:?} -> {:?} (profit {:?} -> {:?})",
+                result.opportunity_id,
+                result.user,
+                result.recorded_profitable,
+                result.replayed_profitable,
+                result.recorded_profit_usd,
+                result.replayed_profit_usd,
+            );
+        }
+    }
+
+    pub fn export_to_json(&self, filename: &str, run: &RunMetadata) -> Result<()> {
+        run.write_json(filename, self)
+    }
+}
+
+/// Re-run every recorded opportunity through `simulator` and diff the decisions
+pub async fn replay(simulator: &LiquidationSimulator, opportunities: &[RecordedOpportunity]) -> ReplayReport {
+    let mut report = ReplayReport { opportunities: opportunities.len(), ..Default::default() };
+
+    for opportunity in opportunities {
+        let recorded = opportunity.decision.as_ref();
+        let replayed = simulator.simulate_liquidation(&opportunity.signal).await;
+
+        let result = ReplayResult {
+            opportunity_id: opportunity.opportunity_id.clone(),
+            user: opportunity.signal.user,
+            recorded_profitable: recorded.map(|d| d.profitable),
+            replayed_profitable: replayed.as_ref().ok().map(|s| s.profitable),
+            recorded_profit_usd: recorded.map(|d| d.expected_profit_usd),
+            replayed_profit_usd: replayed.as_ref().ok().map(|s| s.expected_profit_usd),
+            error: replayed.as_ref().err().map(|e| e.to_string()),
+        };
+
+        let was_taken = result.recorded_profitable == Some(true);
+        let now_taken = result.replayed_profitable == Some(true);
+        match (was_taken, now_taken) {
+            (true, true) => {
+                report.profit_delta_usd += result.replayed_profit_usd.unwrap_or(0.0) - result.recorded_profit_usd.unwrap_or(0.0);
+            }
+            (false, true) => report.newly_taken += 1,
+            (true, false) => report.newly_skipped += 1,
+            (false, false) => {}
+        }
+        if !result.changed() {
+            report.unchanged += 1;
+        }
+        if result.error.is_some() {
+            report.errors += 1;
+        }
+        report.results.push(result);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::AssetRiskParams;
+    use crate::testkit::{offline_client, FixedRiskParams};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn event(id: &str, stage: AuditStage, details: Value) -> AuditEvent {
+        AuditEvent {
+            opportunity_id: id.to_string(),
+            stage,
+            timestamp_ms: 0,
+            user: Address::from_low_u64_be(9),
+            details,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_flags_changed_decisions() {
+        let detected = |id| event(id, AuditStage::Detected, json!({
+            "collateral": U256::from(5u64) * U256::exp10(18),
+            "debt": U256::from(8000u64) * U256::exp10(18),
+            "health_factor": U256::from(80),
+            "collateral_asset": Address::zero(),
+            "debt_asset": Address::zero(),
+        }));
+        let simulated = |id, profitable| event(id, AuditStage::Simulated, json!({
+            "profitable": profitable,
+            "expected_profit_usd": 10.0,
+            "debt_to_cover": U256::from(8000u64) * U256::exp10(18),
+        }));
+        let events = vec![
+            detected("a"),
+            detected("b"),
+            simulated("a", true),
+            simulated("b", false),
+            event("orphan", AuditStage::Skipped, json!({})),
+        ];
+
+        let opportunities = load_opportunities(&events).unwrap();
+        assert_eq!(opportunities.len(), 2);
+        assert_eq!(opportunities[1].final_stage, AuditStage::Simulated);
+
+        // Unreachable RPC: the simulator falls back to default gas, which makes
+        // this underwater position clearly profitable at a 10% bonus
+        let blockchain = offline_client().await;
+        let simulator = LiquidationSimulator::new(blockchain, 10.0)
+            .with_adapter(Arc::new(FixedRiskParams(AssetRiskParams::default())));
+        let report = replay(&simulator, &opportunities).await;
+
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.newly_taken, 1);
+        assert!(report.results[1].changed());
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::debug;
+
+use crate::rate_limit::TokenBucket;
+
+struct Pending {
+    method: &'static str,
+    params: Value,
+    reply: oneshot::Sender<Result<Value, String>>,
+}
+
+/// Requests sent and the batches they went out in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchStats {
+    pub requests: usize,
+    pub batches: usize,
+}
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicUsize,
+    batches: AtomicUsize,
+}
+
+struct Batching {
+    window: Duration,
+    max_batch: usize,
+    rate_limit: Option<Arc<TokenBucket>>,
+}
+
+/// Coalesces JSON-RPC requests issued within `window` of each other into one
+/// batch request, so a burst of lookups costs one round trip
+pub struct RpcBatcher {
+    requests: mpsc::UnboundedSender<Pending>,
+    counters: Arc<Counters>,
+}
+
+impl RpcBatcher {
+    /// Start batching requests to `url`, each counted against `rate_limit`;
+    /// must be called inside a Tokio runtime
+    pub fn new(url: &str, window: Duration, max_batch: usize, rate_limit: Option<Arc<TokenBucket>>) -> Self {
+        let (requests, rx) = mpsc::unbounded_channel();
+        let counters = Arc::new(Counters::default());
+        let batching = Batching { window, max_batch: max_batch.max(1), rate_limit };
+        tokio::spawn(run(reqwest::Client::new(), url.to_string(), rx, batching, counters.clone()));
+        Self { requests, counters }
+    }
+
+    pub async fn request<P: Serialize, T: DeserializeOwned>(&self, method: &'static str, params: P) -> Result<T> {
+        let (reply, response) = oneshot::channel();
+        // `()` serializes to null, which some nodes reject as params
+        let params = match serde_json::to_value(params)? {
+            Value::Null => json!([]),
+            params => params,
+        };
+        self.requests
+            .send(Pending { method, params, reply })
+            .map_err(|_| anyhow::anyhow!("RPC batcher stopped"))?;
+        let result = response
+            .await
+            .context("RPC batch dropped")?
+            .map_err(|e| anyhow::anyhow!("{} failed: {}", method, e))?;
+        serde_json::from_value(result).with_context(|| format!("Invalid {} response", method))
+    }
+
+    pub fn stats(&self) -> BatchStats {
+        BatchStats {
+            requests: self.counters.requests.load(Ordering::Relaxed),
+            batches: self.counters.batches.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Collect requests for `window` after the first one (or until `max_batch`)
+/// and send each batch without waiting for the previous one to return
+async fn run(
+    client: reqwest::Client,
+    url: String,
+    mut rx: mpsc::UnboundedReceiver<Pending>,
+    batching: Batching,
+    counters: Arc<Counters>,
+) {
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::sleep(batching.window);
+        tokio::pin!(deadline);
+        while batch.len() < batching.max_batch {
+            tokio::select! {
+                _ = &mut deadline => break,
+                next = rx.recv() => match next {
+                    Some(pending) => batch.push(pending),
+                    None => break,
+                },
+            }
+        }
+        // Providers count every call in a batch; requests keep queueing meanwhile
+        if let Some(bucket) = &batching.rate_limit {
+            bucket.acquire(batch.len()).await;
+        }
+        counters.requests.fetch_add(batch.len(), Ordering::Relaxed);
+        counters.batches.fetch_add(1, Ordering::Relaxed);
+        tokio::spawn(send(client.clone(), url.clone(), batch));
+    }
+}
+
+async fn send(client: reqwest::Client, url: String, batch: Vec<Pending>) {
+    let body: Vec<Value> = batch
+        .iter()
+        .enumerate()
+        .map(|(id, pending)| json!({ "jsonrpc": "2.0", "id": id, "method": pending.method, "params": pending.params }))
+        .collect();
+    debug!("Sending JSON-RPC batch of {}", body.len());
+
+    let responses = match client.post(&url).json(&body).send().await {
+        Ok(response) => response.json::<Vec<Value>>().await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let results = match responses {
+        Ok(responses) => split_responses(responses, batch.len()),
+        Err(e) => vec![Err(e); batch.len()],
+    };
+    for (pending, result) in batch.into_iter().zip(results) {
+        let _ = pending.reply.send(result);
+    }
+}
+
+/// Match batch responses back to requests `0..len` by id (nodes may reorder them)
+pub fn split_responses(responses: Vec<Value>, len: usize) -> Vec<Result<Value, String>> {
+    let mut results = vec![Err("No response in batch".to_string()); len];
+    for response in responses {
+        let Some(id) = response["id"].as_u64().map(|id| id as usize).filter(|id| *id < len) else { continue };
+        results[id] = match response.get("error") {
+            Some(error) => Err(error["message"].as_str().unwrap_or("RPC error").to_string()),
+            None => Ok(response["result"].clone()),
+        };
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_responses_by_id() {
+        let responses = vec![
+            json!({ "jsonrpc": "2.0", "id": 2, "result": "0x2" }),
+            json!({ "jsonrpc": "2.0", "id": 0, "result": "0x0" }),
+            json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32000, "message": "execution reverted" } }),
+        ];
+        let results = split_responses(responses, 4);
+        assert_eq!(results[0], Ok(json!("0x0")));
+        assert_eq!(results[1], Err("execution reverted".to_string()));
+        assert_eq!(results[2], Ok(json!("0x2")));
+        // A node that drops an entry fails only that request
+        assert!(results[3].is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Everything needed to reproduce a report or judge whether two are comparable
+///
+/// Written as the first key of JSON reports and as `#` comment lines at the top
+/// of CSV exports.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    pub version: String,
+    /// `GIT_COMMIT`, else `git rev-parse HEAD` of the working directory
+    pub git_commit: Option<String>,
+    /// Uncommitted changes in the working tree
+    #[serde(default)]
+    pub git_dirty: bool,
+    pub created_unix: u64,
+    pub chain_id: Option<u64>,
+    pub start_block: Option<u64>,
+    pub end_block: Option<u64>,
+    pub seed: Option<u64>,
+    /// Configuration variables set for the run (unset ones took the defaults of
+    /// `git_commit`), secrets redacted
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+}
+
+impl RunMetadata {
+    /// Metadata of a run of this build with `config`
+    pub fn capture(config: BTreeMap<String, String>) -> Self {
+        let (git_commit, git_dirty) = match std::env::var("GIT_COMMIT") {
+            Ok(commit) if !commit.trim().is_empty() => (Some(commit.trim().to_string()), false),
+            _ => (git(&["rev-parse", "HEAD"]), git(&["status", "--porcelain"]).is_some_and(|status| !status.is_empty())),
+        };
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit,
+            git_dirty,
+            created_unix: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Blocks the chain head moved through while the run read from it
+    pub fn with_blocks(mut self, start: Option<u64>, end: Option<u64>) -> Self {
+        self.start_block = start;
+        self.end_block = end;
+        self
+    }
+
+    /// `# key: value` lines to put above a CSV header
+    pub fn csv_comment(&self) -> String {
+        let optional = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_default();
+        let mut lines = vec![
+            format!("# version: {}", self.version),
+            format!("# git_commit: {}{}", self.git_commit.as_deref().unwrap_or("unknown"), if self.git_dirty { " (dirty)" } else { "" }),
+            format!("# created_unix: {}", self.created_unix),
+            format!("# chain_id: {}", optional(self.chain_id)),
+            format!("# blocks: {}-{}", optional(self.start_block), optional(self.end_block)),
+            format!("# seed: {}", optional(self.seed)),
+        ];
+        lines.extend(self.config.iter().map(|(name, value)| format!("# config.{}: {}", name, value)));
+        lines.iter().map(|line| format!("{}\n", line.replace('\n', " "))).collect()
+    }
+
+    /// What differs between the setups of two runs, beyond the blocks they covered
+    pub fn differences(&self, other: &RunMetadata) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.git_commit != other.git_commit || self.git_dirty || other.git_dirty {
+            differences.push(format!("commit {:?} vs {:?}", self.git_commit, other.git_commit));
+        }
+        if self.chain_id != other.chain_id {
+            differences.push(format!("chain {:?} vs {:?}", self.chain_id, other.chain_id));
+        }
+        if self.seed != other.seed {
+            differences.push(format!("seed {:?} vs {:?}", self.seed, other.seed));
+        }
+        let names: BTreeSet<_> = self.config.keys().chain(other.config.keys()).collect();
+        for name in names {
+            let (a, b) = (self.config.get(name), other.config.get(name));
+            if a != b {
+                differences.push(format!("{} {:?} vs {:?}", name, a, b));
+            }
+        }
+        differences
+    }
+
+    /// Write `report` as pretty JSON to `filename` with this metadata under a leading `run` key
+    pub fn write_json<T: Serialize>(&self, filename: &str, report: &T) -> Result<()> {
+        #[derive(Serialize)]
+        struct WithRun<'a, T> {
+            run: &'a RunMetadata,
+            #[serde(flatten)]
+            report: &'a T,
+        }
+        std::fs::write(filename, serde_json::to_string_pretty(&WithRun { run: self, report })?)?;
+        Ok(())
+    }
+}
+
+/// Config value as it may appear in a report: secrets are replaced and URLs
+/// cut to scheme and host, since RPC paths and queries often carry API keys
+pub fn redact(name: &str, value: &str) -> String {
+    if ["_KEY", "_KEYS", "_SECRET", "_PASSWORD"].iter().any(|suffix| name.ends_with(suffix)) {
+        return "<redacted>".to_string();
+    }
+    value
+        .split(',')
+        .map(|part| match part.split_once("://") {
+            Some((scheme, rest)) => {
+                let host = rest.split(['/', '?']).next().unwrap_or_default();
+                // Drop `user:password@` too
+                let host = host.rsplit('@').next().unwrap_or_default();
+                format!("{}://{}", scheme, host)
+            }
+            None => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_header_and_redaction() {
+        assert_eq!(redact("LIQUIDATOR_PRIVATE_KEYS", "0xabc"), "<redacted>");
+        assert_eq!(redact("ANVIL_RPC_URL", "https://eth.example.com/v2/secret?x=1"), "https://eth.example.com");
+        assert_eq!(redact("MONITOR_RPC_URLS", "https://u:p@a.io/k,ws://b.io"), "https://a.io,ws://b.io");
+        assert_eq!(redact("MOCK_TOKEN_ADDRESS", "0x01"), "0x01");
+
+        let run = RunMetadata {
+            git_commit: Some("abc123".to_string()),
+            config: BTreeMap::from([("BACKTEST_SEED".to_string(), "7".to_string())]),
+            ..Default::default()
+        }
+        .with_chain_id(1)
+        .with_seed(7)
+        .with_blocks(Some(100), Some(120));
+        let comment = run.csv_comment();
+        assert!(comment.lines().all(|line| line.starts_with("# ")));
+        assert!(comment.contains("# blocks: 100-120\n") && comment.contains("# config.BACKTEST_SEED: 7\n"));
+
+        let path = std::env::temp_dir().join(format!("liquidio_run_{}.json", std::process::id()));
+        run.write_json(path.to_str().unwrap(), &serde_json::json!({ "total_attempts": 3 })).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        // The metadata heads the report, which still reads back as before
+        assert!(written.trim_start_matches(['{', '\n', ' ']).starts_with("\"run\""));
+        let value: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(value["total_attempts"], 3);
+        assert_eq!(serde_json::from_value::<RunMetadata>(value["run"].clone()).unwrap(), run);
+
+        let reseeded = run.clone().with_seed(8).with_blocks(Some(200), Some(220));
+        assert_eq!(run.differences(&reseeded), vec!["seed Some(7) vs Some(8)".to_string()]);
+    }
+}
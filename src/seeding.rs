@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::Middleware,
+    signers::Signer,
+    types::{Address, U256},
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::blockchain::{BlockchainClient, LendingProtocol, ERC20};
+use crate::fixed_point::{from_f64, to_f64, wad_to_f64};
+use crate::setup::anvil_account;
+
+/// Anvil account of the first borrower; 0 deploys and 1 liquidates
+const FIRST_BORROWER: u32 = 2;
+/// ETH a borrower keeps on top of its collateral for gas
+const BORROWER_GAS_ETH: u64 = 1;
+
+/// Positions to open on the mock protocol, written as TOML:
+///
+/// ```toml
+/// eth_price_usd = 1800
+///
+/// [[user]]
+/// name = "at-threshold"
+/// deposit_eth = 10
+/// health_factor = 1.0
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedSpec {
+    /// ETH price (USD) the protocol is left at once every position is open
+    pub eth_price_usd: Option<f64>,
+    #[serde(default, rename = "user")]
+    pub users: Vec<UserSpec>,
+}
+
+/// One borrower: a deposit, then a fixed borrow or one sized to a health factor
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserSpec {
+    /// Label listed next to the position in the generated `.env`
+    pub name: Option<String>,
+    /// Anvil account index (default: 2 for the first user, 3 for the next, ...)
+    pub account: Option<u32>,
+    pub deposit_eth: f64,
+    /// Stablecoin borrowed...
+    pub borrow: Option<f64>,
+    /// ...or as much as leaves this health factor at the price before `eth_price_usd` applies
+    pub health_factor: Option<f64>,
+}
+
+impl SeedSpec {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+        Self::parse(&raw).with_context(|| format!("Invalid position spec {}", path.display()))
+    }
+
+    pub fn parse(raw: &str) -> Result<Self> {
+        let spec: Self = toml::from_str(raw)?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// One `deposit_eth` position at each health factor
+    pub fn at_health_factors(health_factors: &[f64], deposit_eth: f64) -> Self {
+        let users = health_factors
+            .iter()
+            .map(|hf| UserSpec { deposit_eth, health_factor: Some(*hf), ..Default::default() })
+            .collect();
+        Self { eth_price_usd: None, users }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let positive = |value: f64| value.is_finite() && value > 0.0;
+        anyhow::ensure!(!self.users.is_empty(), "No [[user]] entries");
+        anyhow::ensure!(self.eth_price_usd.is_none_or(positive), "eth_price_usd must be positive");
+        let mut accounts = HashSet::new();
+        for (index, user) in self.users.iter().enumerate() {
+            let label = user.name.clone().unwrap_or_else(|| format!("user {}", index + 1));
+            anyhow::ensure!(positive(user.deposit_eth), "{}: deposit_eth must be positive", label);
+            anyhow::ensure!(
+                user.borrow.is_none() || user.health_factor.is_none(),
+                "{}: set borrow or health_factor, not both",
+                label,
+            );
+            anyhow::ensure!(user.borrow.is_none_or(|borrow| borrow.is_finite() && borrow >= 0.0), "{}: invalid borrow", label);
+            anyhow::ensure!(user.health_factor.is_none_or(positive), "{}: health_factor must be positive", label);
+            let account = self.account(index);
+            anyhow::ensure!(account >= FIRST_BORROWER, "{}: accounts 0 and 1 deploy and liquidate", label);
+            anyhow::ensure!(accounts.insert(account), "{}: account {} is already used", label, account);
+        }
+        Ok(())
+    }
+
+    fn account(&self, index: usize) -> u32 {
+        self.users[index].account.unwrap_or(FIRST_BORROWER + index as u32)
+    }
+}
+
+/// A seeded borrower and the health factor the protocol reports for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestPosition {
+    pub user: Address,
+    pub name: Option<String>,
+    pub health_factor: f64,
+}
+
+/// Most SimpleLendingProtocol lends against `collateral` wei with ETH at `eth_price` (wad USD)
+fn max_borrow(collateral: U256, eth_price: U256) -> U256 {
+    collateral * eth_price / U256::exp10(18) * 100 / 150
+}
+
+/// Debt that leaves `collateral` wei at `health_factor`, rounded down like the
+/// protocol's own check so that 1.0 lands exactly on the threshold
+pub fn debt_for_health_factor(collateral: U256, eth_price: U256, health_factor: f64) -> U256 {
+    let bps = U256::from((health_factor * 10_000.0).round().max(1.0) as u64);
+    max_borrow(collateral, eth_price) * 10_000 / bps
+}
+
+/// Open `spec`'s positions on `blockchain`'s mock protocol from Anvil dev accounts
+///
+/// Collateral is set with `anvil_setBalance`, and the protocol is minted any
+/// stablecoin it lacks, so whales don't depend on what the accounts hold.
+pub async fn seed(blockchain: &BlockchainClient, spec: &SeedSpec) -> Result<Vec<TestPosition>> {
+    spec.validate()?;
+    let provider = blockchain.http_provider.clone();
+    let deployer = Arc::new(SignerMiddleware::new(provider.clone(), anvil_account(0)?));
+    let lending = LendingProtocol::new(blockchain.lending_protocol.address(), deployer.clone());
+    let price = lending.eth_price_usd().call().await?;
+
+    let mut plans = Vec::new();
+    for (index, user) in spec.users.iter().enumerate() {
+        let wallet = anvil_account(spec.account(index))?;
+        let collateral = from_f64(user.deposit_eth, U256::exp10(18));
+        let debt = match (user.borrow, user.health_factor) {
+            (Some(borrow), _) => from_f64(borrow, U256::exp10(18)),
+            (None, Some(health_factor)) => debt_for_health_factor(collateral, price, health_factor),
+            (None, None) => U256::zero(),
+        };
+        plans.push((wallet, collateral, debt));
+    }
+
+    // borrow() won't leave a position below 1.0, so those meant to be
+    // liquidatable borrow at a raised price that is put back afterwards
+    let lowest = plans
+        .iter()
+        .filter(|(_, _, debt)| !debt.is_zero())
+        .map(|(_, collateral, debt)| wad_to_f64(max_borrow(*collateral, price)) / wad_to_f64(*debt))
+        .fold(f64::INFINITY, f64::min);
+    if lowest < 1.0 {
+        lending.set_eth_price(from_f64(wad_to_f64(price) / lowest * 1.01, U256::exp10(18))).send().await?.await?;
+    }
+    let total_debt = plans.iter().fold(U256::zero(), |total, (_, _, debt)| total + debt);
+    let liquidity = blockchain.token.balance_of(lending.address()).call().await?;
+    if liquidity < total_debt {
+        ERC20::new(blockchain.token.address(), deployer.clone())
+            .mint(lending.address(), total_debt - liquidity)
+            .send()
+            .await?
+            .await?;
+    }
+
+    for (wallet, collateral, debt) in &plans {
+        let needed = *collateral + U256::exp10(18) * BORROWER_GAS_ETH;
+        if provider.get_balance(wallet.address(), None).await? < needed {
+            provider.request::<_, ()>("anvil_setBalance", (wallet.address(), needed)).await?;
+        }
+        let position = LendingProtocol::new(lending.address(), Arc::new(SignerMiddleware::new(provider.clone(), wallet.clone())));
+        position.deposit().value(*collateral).send().await?.await?;
+        if !debt.is_zero() {
+            position.borrow(*debt).send().await?.await?;
+        }
+    }
+
+    let final_price = spec.eth_price_usd.map(|usd| from_f64(usd, U256::exp10(18))).unwrap_or(price);
+    if lowest < 1.0 || final_price != price {
+        lending.set_eth_price(final_price).send().await?.await?;
+    }
+
+    let mut positions = Vec::new();
+    for ((wallet, _, debt), user) in plans.iter().zip(&spec.users) {
+        let (_, _, health_factor) = lending.get_position(wallet.address()).call().await?;
+        // getPosition reports percent (100 = 1.0), and the max for a position without debt
+        let health_factor = if debt.is_zero() { f64::INFINITY } else { to_f64(health_factor, U256::from(100)) };
+        let position = TestPosition { user: wallet.address(), name: user.name.clone(), health_factor };
+        info!("   Position {:?} at health factor {:.2}", position.user, position.health_factor);
+        positions.push(position);
+    }
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_parsing_and_sizing() {
+        let spec = SeedSpec::parse(
+            r#"
+            eth_price_usd = 1800
+
+            [[user]]
+            name = "at-threshold"
+            deposit_eth = 10
+            health_factor = 1.0
+
+            [[user]]
+            name = "dust"
+            deposit_eth = 0.01
+            borrow = 0.000001
+
+            [[user]]
+            name = "whale"
+            account = 9
+            deposit_eth = 50000
+            health_factor = 1.2
+            "#,
+        )
+        .unwrap();
+        assert_eq!(spec.eth_price_usd, Some(1800.0));
+        assert_eq!(spec.users.len(), 3);
+        assert_eq!(spec.users[1].borrow, Some(0.000001));
+        assert_eq!((spec.account(0), spec.account(1), spec.account(2)), (2, 3, 9));
+
+        // 10 ETH at $2000 lends up to $13,333.33; exactly that is health factor 1.0
+        let collateral = U256::exp10(19);
+        let price = U256::from(2000u64) * U256::exp10(18);
+        let at_threshold = debt_for_health_factor(collateral, price, 1.0);
+        assert_eq!(at_threshold, max_borrow(collateral, price));
+        assert_eq!(max_borrow(collateral, price) * 100 / at_threshold, U256::from(100));
+        assert!((wad_to_f64(debt_for_health_factor(collateral, price, 1.25)) - 10_666.67).abs() < 0.01);
+
+        assert!(SeedSpec::parse("[[user]]\ndeposit_eth = 1\nborrow = 10\nhealth_factor = 1.1\n").is_err());
+        assert!(SeedSpec::parse("[[user]]\ndeposit_eth = 1\naccount = 1\n").is_err());
+        assert!(SeedSpec::parse("[[user]]\ndeposit_eth = 1\ncolateral = 2\n").is_err());
+    }
+}
@@ -0,0 +1,36 @@
+use keccak_const::Keccak256;
+
+/// Function selector of `signature` (e.g. `"liquidate(address,uint256)"`),
+/// computed at compile time when used in a `const`
+pub const fn selector(signature: &str) -> [u8; 4] {
+    let hash = Keccak256::new().update(signature.as_bytes()).finalize();
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nested_calls::*;
+    use ethers::utils::id;
+
+    #[test]
+    fn test_wrapper_selectors_match_published() {
+        // Wrappers have no bindings to check against, so check their published selectors
+        let wrappers = [
+            (AGGREGATE_SELECTOR, 0x252dba42u32),
+            (TRY_AGGREGATE_SELECTOR, 0xbce38bd7),
+            (AGGREGATE3_SELECTOR, 0x82ad56cb),
+            (AGGREGATE3_VALUE_SELECTOR, 0x174dea71),
+            (MULTICALL_SELECTOR, 0xac9650d8),
+            (MULTICALL_DEADLINE_SELECTOR, 0x5ae401dc),
+            (EXECUTE_SELECTOR, 0xb61d27f6),
+            (EXECUTE_BATCH_SELECTOR, 0x18dfb3c7),
+            (SAFE_EXEC_SELECTOR, 0x6a761202),
+            (HANDLE_OPS_SELECTOR, 0x1fad948c),
+        ];
+        for (derived, published) in wrappers {
+            assert_eq!(derived, published.to_be_bytes());
+        }
+        assert_eq!(selector("liquidate(address,uint256)"), id("liquidate(address,uint256)"));
+    }
+}
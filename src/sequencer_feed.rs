@@ -0,0 +1,315 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use ethers::types::Transaction;
+use ethers::utils::rlp;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use crate::backpressure::{self, BufferConfig, TxReceiver, TxSender};
+
+/// L1 message kind carrying an L2 message from the sequencer
+const L1_MESSAGE_L2: u8 = 3;
+/// L2 message kinds (see Nitro `arbos/incomingmessage.go`)
+const L2_MESSAGE_BATCH: u8 = 3;
+const L2_MESSAGE_SIGNED_TX: u8 = 4;
+/// Batches can nest; Nitro itself stops at this depth
+const MAX_BATCH_DEPTH: usize = 16;
+
+/// Consecutive connection failures tolerated before the feed gives up
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, Deserialize)]
+struct BroadcastMessage {
+    #[serde(default)]
+    messages: Vec<BroadcastFeedMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BroadcastFeedMessage {
+    sequence_number: u64,
+    message: MessageWithMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageWithMetadata {
+    message: L1IncomingMessage,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct L1IncomingMessage {
+    header: L1IncomingMessageHeader,
+    /// Base64-encoded L2 message
+    #[serde(default)]
+    l2_msg: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct L1IncomingMessageHeader {
+    kind: u8,
+}
+
+/// Decode the signed transactions in one sequencer feed frame
+///
+/// Returns the highest sequence number seen alongside the transactions, so the
+/// caller can detect gaps after a reconnect.
+pub fn decode_feed_message(raw: &str) -> Result<(Option<u64>, Vec<Transaction>)> {
+    let broadcast: BroadcastMessage = serde_json::from_str(raw).context("Invalid sequencer feed frame")?;
+    let mut transactions = Vec::new();
+    let mut last_sequence = None;
+
+    for feed_message in broadcast.messages {
+        last_sequence = last_sequence.max(Some(feed_message.sequence_number));
+        let message = feed_message.message.message;
+        if message.header.kind != L1_MESSAGE_L2 {
+            continue;
+        }
+        let l2_msg = base64::engine::general_purpose::STANDARD
+            .decode(message.l2_msg.as_bytes())
+            .context("Invalid base64 l2Msg")?;
+        decode_l2_message(&l2_msg, 0, &mut transactions)?;
+    }
+
+    Ok((last_sequence, transactions))
+}
+
+fn decode_l2_message(bytes: &[u8], depth: usize, out: &mut Vec<Transaction>) -> Result<()> {
+    let Some((&kind, payload)) = bytes.split_first() else {
+        return Ok(());
+    };
+
+    match kind {
+        L2_MESSAGE_SIGNED_TX => {
+            let mut tx: Transaction = rlp::decode(payload).context("Invalid signed transaction in l2Msg")?;
+            tx.recover_from_mut().context("Unrecoverable sender in l2Msg")?;
+            out.push(tx);
+        }
+        L2_MESSAGE_BATCH => {
+            anyhow::ensure!(depth < MAX_BATCH_DEPTH, "l2Msg batch nested too deeply");
+            // Batch: repeated [u64 big-endian length][nested L2 message]
+            let mut rest = payload;
+            while rest.len() >= 8 {
+                let (len, tail) = rest.split_at(8);
+                let len = u64::from_be_bytes(len.try_into().unwrap()) as usize;
+                anyhow::ensure!(len <= tail.len(), "Truncated l2Msg batch entry");
+                let (item, tail) = tail.split_at(len);
+                decode_l2_message(item, depth + 1, out)?;
+                rest = tail;
+            }
+        }
+        // Heartbeats, unsigned/contract txs and compressed txs carry nothing we act on
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Mempool source for Arbitrum, which has no public mempool: the sequencer
+/// feed broadcasts transactions as they are sequenced, ahead of block publication
+pub struct SequencerFeed {
+    url: String,
+    tx_sender: TxSender,
+    max_transactions: Option<usize>,
+}
+
+impl SequencerFeed {
+    pub fn new(url: &str) -> (Self, TxReceiver) {
+        Self::with_buffer(url, BufferConfig::default())
+    }
+
+    /// Forward into a buffer that applies `buffer.policy` when the detector falls behind
+    pub fn with_buffer(url: &str, buffer: BufferConfig) -> (Self, TxReceiver) {
+        let (tx_sender, rx) = backpressure::channel(buffer);
+        (
+            Self {
+                url: url.to_string(),
+                tx_sender,
+                max_transactions: None,
+            },
+            rx,
+        )
+    }
+
+    /// Stop (closing the channel) after forwarding `max` transactions
+    pub fn with_limit(mut self, max: usize) -> Self {
+        self.max_transactions = Some(max);
+        self
+    }
+
+    /// Forward feed transactions until the limit is hit, the receiver is
+    /// dropped, or the feed is unreachable for `MAX_RECONNECT_ATTEMPTS` tries
+    pub async fn run(self) -> Result<()> {
+        let mut forwarded = 0usize;
+        let mut failures = 0u32;
+        let mut last_sequence: Option<u64> = None;
+
+        loop {
+            info!("Connecting to sequencer feed at {}", self.url);
+            let mut socket = match tokio_tungstenite::connect_async(self.url.as_str()).await {
+                Ok((socket, _)) => {
+                    failures = 0;
+                    socket
+                }
+                Err(e) => {
+                    failures += 1;
+                    anyhow::ensure!(failures < MAX_RECONNECT_ATTEMPTS, "Sequencer feed unreachable: {}", e);
+                    warn!("Sequencer feed connection failed ({}), retrying", e);
+                    tokio::time::sleep(Duration::from_secs(failures as u64)).await;
+                    continue;
+                }
+            };
+
+            while let Some(frame) = socket.next().await {
+                let text = match frame {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        warn!("Sequencer feed error: {}", e);
+                        break;
+                    }
+                };
+
+                let (sequence, transactions) = match decode_feed_message(&text) {
+                    Ok(decoded) => decoded,
+                    Err(e) => {
+                        debug!("Skipping undecodable feed frame: {}", e);
+                        continue;
+                    }
+                };
+                if let (Some(prev), Some(seq)) = (last_sequence, sequence) {
+                    if seq > prev + 1 {
+                        warn!("Sequencer feed gap: {} -> {}", prev, seq);
+                    }
+                }
+                last_sequence = sequence.or(last_sequence);
+
+                for tx in transactions {
+                    if self.tx_sender.send(tx).await.is_err() {
+                        return Ok(());
+                    }
+                    forwarded += 1;
+                    if self.max_transactions.is_some_and(|max| forwarded >= max) {
+                        info!("Sequencer feed forwarded {} transactions", forwarded);
+                        return Ok(());
+                    }
+                }
+            }
+
+            warn!("Sequencer feed disconnected, reconnecting");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::{Address, Eip1559TransactionRequest};
+    use serde_json::json;
+    use crate::mempool_streamer::LIQUIDATE_SELECTOR;
+
+    #[tokio::test]
+    async fn test_decodes_batched_signed_transactions() {
+        let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(42161u64);
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::from_low_u64_be(0xbeef))
+            .data(LIQUIDATE_SELECTOR.to_vec())
+            .nonce(3)
+            .gas(100_000)
+            .chain_id(42161)
+            .into();
+        let signature = wallet.sign_transaction(&tx).await.unwrap();
+        let raw = tx.rlp_signed(&signature);
+
+        // Batch of [signed tx, heartbeat]
+        let mut signed = vec![L2_MESSAGE_SIGNED_TX];
+        signed.extend_from_slice(&raw);
+        let mut batch = vec![L2_MESSAGE_BATCH];
+        for item in [signed, vec![6u8]] {
+            batch.extend_from_slice(&(item.len() as u64).to_be_bytes());
+            batch.extend_from_slice(&item);
+        }
+
+        let frame = json!({
+            "version": 1,
+            "messages": [{
+                "sequenceNumber": 77,
+                "message": {
+                    "message": {
+                        "header": { "kind": 3, "sender": "0xa4b000000000000000000073657175656e636572", "blockNumber": 1 },
+                        "l2Msg": base64::engine::general_purpose::STANDARD.encode(&batch),
+                    },
+                    "delayedMessagesRead": 1
+                },
+                "signature": null
+            }]
+        });
+
+        let (sequence, transactions) = decode_feed_message(&frame.to_string()).unwrap();
+        assert_eq!(sequence, Some(77));
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].from, wallet.address());
+        assert_eq!(transactions[0].to, Some(Address::from_low_u64_be(0xbeef)));
+        assert_eq!(&transactions[0].input[..4], &LIQUIDATE_SELECTOR);
+    }
+
+    fn signed_liquidation() -> Vec<u8> {
+        let wallet = LocalWallet::from_bytes(&[7u8; 32]).unwrap().with_chain_id(42161u64);
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::from_low_u64_be(0xbeef))
+            .data(LIQUIDATE_SELECTOR.to_vec())
+            .chain_id(42161)
+            .into();
+        tx.rlp_signed(&wallet.sign_transaction_sync(&tx).unwrap()).to_vec()
+    }
+
+    proptest::proptest! {
+        // The feed is an untrusted socket: malformed RLP, batch lengths and
+        // frames are errors, never panics
+        #[test]
+        fn prop_arbitrary_l2_messages(
+            kind in proptest::sample::select(vec![L2_MESSAGE_BATCH, L2_MESSAGE_SIGNED_TX, 0xff]),
+            payload in proptest::collection::vec(proptest::num::u8::ANY, 0..300),
+        ) {
+            let mut transactions = Vec::new();
+            let _ = decode_l2_message(&[&[kind][..], &payload].concat(), 0, &mut transactions);
+            proptest::prop_assert!(transactions.len() <= 1 || kind == L2_MESSAGE_BATCH);
+        }
+
+        #[test]
+        fn prop_corrupted_signed_transactions(
+            corruptions in proptest::collection::vec((proptest::num::usize::ANY, proptest::num::u8::ANY), 1..6),
+            keep in proptest::num::usize::ANY,
+        ) {
+            let mut raw = signed_liquidation();
+            for (at, byte) in corruptions {
+                let len = raw.len();
+                raw[at % len] = byte;
+            }
+            raw.truncate(keep % (raw.len() + 1));
+            let mut transactions = Vec::new();
+            let _ = decode_l2_message(&[&[L2_MESSAGE_SIGNED_TX][..], &raw].concat(), 0, &mut transactions);
+        }
+
+        #[test]
+        fn prop_arbitrary_feed_frames(raw in "\\PC{0,200}", l2_msg in proptest::collection::vec(proptest::num::u8::ANY, 0..200)) {
+            let _ = decode_feed_message(&raw);
+            let frame = json!({
+                "messages": [{
+                    "sequenceNumber": 1,
+                    "message": { "message": {
+                        "header": { "kind": L1_MESSAGE_L2 },
+                        "l2Msg": base64::engine::general_purpose::STANDARD.encode(&l2_msg),
+                    } }
+                }]
+            });
+            let _ = decode_feed_message(&frame.to_string());
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use ethers::{
+    contract::ContractFactory,
+    middleware::SignerMiddleware,
+    signers::{coins_bip39::English, LocalWallet, MnemonicBuilder, Signer},
+    types::{Address, H256, U256},
+};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::blockchain::{BlockchainClient, ERC20};
+use crate::fixed_point::from_f64;
+use crate::helper;
+use crate::seeding::{self, SeedSpec, TestPosition};
+
+/// Mnemonic a default Anvil node derives its funded dev accounts from
+const ANVIL_MNEMONIC: &str = "test test test test test test test test test test test junk";
+/// Setup refuses to deploy or spend on any other chain
+pub const ANVIL_CHAIN_ID: u64 = 31337;
+const TOKEN_ARTIFACT: &str = "out/MockERC20.sol/MockERC20.json";
+const PROTOCOL_ARTIFACT: &str = "out/SimpleLendingProtocol.sol/SimpleLendingProtocol.json";
+
+/// What `liquidio setup-local` deploys and opens
+#[derive(Debug, Clone)]
+pub struct SetupOptions {
+    /// Test borrowers to open once the contracts are deployed
+    pub positions: SeedSpec,
+    /// Stablecoin the protocol has to lend
+    pub protocol_liquidity: f64,
+    /// Stablecoin minted to the liquidator for inventory-funded liquidations
+    pub liquidator_inventory: f64,
+}
+
+impl Default for SetupOptions {
+    fn default() -> Self {
+        Self {
+            positions: SeedSpec::at_health_factors(&[1.5, 1.1, 1.02, 0.98, 0.9], 10.0),
+            protocol_liquidity: 500_000.0,
+            liquidator_inventory: 100_000.0,
+        }
+    }
+}
+
+/// Contracts and liquidator a local end-to-end run points at
+#[derive(Debug, Clone)]
+pub struct LocalDeployment {
+    pub protocol: Address,
+    pub token: Address,
+    pub helper: Address,
+    pub liquidator_key: H256,
+    pub positions: Vec<TestPosition>,
+}
+
+impl LocalDeployment {
+    /// `.env` running the bot against this deployment; everything else keeps its default
+    pub fn env_file(&self, rpc_url: &str, ws_url: &str) -> String {
+        let mut env = format!(
+            "# Liquidio Configuration\n\
+             # Auto-generated by liquidio setup-local\n\
+             \n\
+             ANVIL_RPC_URL={}\n\
+             ANVIL_WS_URL={}\n\
+             CHAIN_ID={}\n\
+             \n\
+             LENDING_PROTOCOL_ADDRESS={:?}\n\
+             MOCK_TOKEN_ADDRESS={:?}\n\
+             LIQUIDATION_HELPER_ADDRESS={:?}\n\
+             \n\
+             LIQUIDATOR_PRIVATE_KEY={:?}\n\
+             MIN_PROFIT_THRESHOLD_USD=10.0\n\
+             \n\
+             # Test positions and their health factors at setup\n",
+            rpc_url, ws_url, ANVIL_CHAIN_ID, self.protocol, self.token, self.helper, self.liquidator_key,
+        );
+        for position in &self.positions {
+            let name = position.name.as_deref().unwrap_or_default();
+            env.push_str(format!("# {:?} {:.2} {}", position.user, position.health_factor, name).trim_end());
+            env.push('\n');
+        }
+        env.push_str("\nRUST_LOG=info,liquidio=debug\n");
+        env
+    }
+}
+
+/// Anvil dev account `index` (0 deploys, 1 liquidates, 2.. borrow)
+pub fn anvil_account(index: u32) -> Result<LocalWallet> {
+    let wallet = MnemonicBuilder::<English>::default().phrase(ANVIL_MNEMONIC).index(index)?.build()?;
+    Ok(wallet.with_chain_id(ANVIL_CHAIN_ID))
+}
+
+/// Deploy the mock token, SimpleLendingProtocol and the helper to the Anvil
+/// node at `rpc_url`, fund the liquidator and open the test positions
+///
+/// Needs `forge build` to have written the contract artifacts.
+pub async fn run(rpc_url: &str, options: &SetupOptions) -> Result<LocalDeployment> {
+    let blockchain = BlockchainClient::new(rpc_url, None, Address::zero(), Address::zero()).await?;
+    blockchain.ensure_chain_id(ANVIL_CHAIN_ID).await.context("setup-local only runs against a local Anvil")?;
+    let provider = blockchain.http_provider.clone();
+    let deployer = anvil_account(0)?;
+    let liquidator = anvil_account(1)?;
+    let client = Arc::new(SignerMiddleware::new(provider.clone(), deployer.clone()));
+    let wad = |amount: f64| from_f64(amount, U256::exp10(18));
+
+    let (abi, bytecode) = helper::read_artifact(Path::new(TOKEN_ARTIFACT))?;
+    let token = ContractFactory::new(abi, bytecode, client.clone())
+        .deploy(("USD Stablecoin".to_string(), "USDC".to_string(), wad(options.protocol_liquidity)))?
+        .send()
+        .await?
+        .address();
+    info!("MockERC20 deployed at {:?}", token);
+    let (abi, bytecode) = helper::read_artifact(Path::new(PROTOCOL_ARTIFACT))?;
+    let protocol = ContractFactory::new(abi, bytecode, client.clone()).deploy(token)?.send().await?.address();
+    info!("SimpleLendingProtocol deployed at {:?}", protocol);
+
+    let stablecoin = ERC20::new(token, client.clone());
+    stablecoin.transfer(protocol, wad(options.protocol_liquidity)).send().await?.await?;
+    stablecoin.mint(liquidator.address(), wad(options.liquidator_inventory)).send().await?.await?;
+    info!("Protocol holds {} and liquidator {:?} {} of the stablecoin",
+        options.protocol_liquidity, liquidator.address(), options.liquidator_inventory);
+
+    let blockchain = BlockchainClient::new(rpc_url, None, protocol, token).await?;
+    let helper = helper::deploy(
+        &blockchain,
+        deployer,
+        Path::new(helper::HELPER_ARTIFACT),
+        Address::zero(),
+        Address::zero(),
+        &[liquidator.address()],
+    )
+    .await?;
+    // No swap pool locally, so the helper repays from the liquidator's inventory
+    let liquidator_client = Arc::new(SignerMiddleware::new(provider.clone(), liquidator.clone()));
+    ERC20::new(token, liquidator_client).approve(helper, U256::MAX).send().await?.await?;
+
+    let positions = seeding::seed(&blockchain, &options.positions).await?;
+
+    Ok(LocalDeployment {
+        protocol,
+        token,
+        helper,
+        liquidator_key: H256::from_slice(&liquidator.signer().to_bytes()),
+        positions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accounts_and_env_file() {
+        // The same accounts Anvil funds and prints at startup
+        let liquidator = anvil_account(1).unwrap();
+        assert_eq!(liquidator.address(), "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse::<Address>().unwrap());
+
+        let deployment = LocalDeployment {
+            protocol: Address::from_low_u64_be(1),
+            token: Address::from_low_u64_be(2),
+            helper: Address::from_low_u64_be(3),
+            liquidator_key: H256::from_slice(&liquidator.signer().to_bytes()),
+            positions: vec![
+                TestPosition { user: Address::from_low_u64_be(4), name: None, health_factor: 0.98 },
+                TestPosition { user: Address::from_low_u64_be(5), name: Some("whale".to_string()), health_factor: 1.2 },
+            ],
+        };
+        let env = deployment.env_file("http://127.0.0.1:8545", "ws://127.0.0.1:8545");
+        assert!(env.contains("LENDING_PROTOCOL_ADDRESS=0x0000000000000000000000000000000000000001\n"));
+        assert!(env.contains("LIQUIDATOR_PRIVATE_KEY=0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d\n"));
+        assert!(env.contains("# 0x0000000000000000000000000000000000000004 0.98\n"));
+        assert!(env.contains("# 0x0000000000000000000000000000000000000005 1.20 whale\n"));
+    }
+}
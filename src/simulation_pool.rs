@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::Semaphore;
+
+use crate::liquidation_detector::LiquidationSignal;
+use crate::simulator::{LiquidationSimulator, Result, SimulationResult};
+
+/// Simulations one worker ran and how long they held it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkerStats {
+    pub simulations: usize,
+    pub failures: usize,
+    pub total_us: f64,
+    pub max_us: f64,
+}
+
+impl WorkerStats {
+    pub fn mean_us(&self) -> f64 {
+        if self.simulations == 0 {
+            return 0.0;
+        }
+        self.total_us / self.simulations as f64
+    }
+
+    fn record(&mut self, elapsed_us: f64, failed: bool) {
+        self.simulations += 1;
+        self.failures += failed as usize;
+        self.total_us += elapsed_us;
+        self.max_us = self.max_us.max(elapsed_us);
+    }
+}
+
+/// Fixed set of simulation workers shared by every dispatched opportunity
+///
+/// A burst of signals simulates on up to `workers` at once, so one slow gas
+/// estimate holds up a single worker rather than the whole burst. Each run is
+/// attributed to the worker that ran it.
+pub struct SimulationPool {
+    simulator: Arc<LiquidationSimulator>,
+    permits: Semaphore,
+    // Ids of the workers not running a simulation
+    idle: Mutex<Vec<usize>>,
+    stats: Mutex<Vec<WorkerStats>>,
+}
+
+/// One simulation and the worker that ran it
+#[derive(Debug)]
+pub struct SimulationRun {
+    pub worker: usize,
+    /// Time spent on the worker, not waiting for it
+    pub elapsed_us: f64,
+    pub result: Result<SimulationResult>,
+}
+
+impl SimulationPool {
+    pub fn new(simulator: Arc<LiquidationSimulator>, workers: usize) -> Self {
+        let workers = workers.max(1);
+        Self {
+            simulator,
+            permits: Semaphore::new(workers),
+            idle: Mutex::new((0..workers).rev().collect()),
+            stats: Mutex::new(vec![WorkerStats::default(); workers]),
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.stats.lock().unwrap().len()
+    }
+
+    /// Number of workers currently running a simulation
+    pub fn busy_workers(&self) -> usize {
+        self.workers() - self.permits.available_permits()
+    }
+
+    /// Wait for a free worker and simulate `signal` on it, in the state its
+    /// trigger leaves behind when it has one
+    pub async fn simulate(&self, signal: &LiquidationSignal) -> SimulationRun {
+        let _permit = self.permits.acquire().await.expect("simulation pool semaphore closed");
+        let worker = self.idle.lock().unwrap().pop().expect("a permit always leaves an idle worker");
+        // Handed back even if the caller drops the simulation halfway
+        let _checkout = Checkout { idle: &self.idle, worker };
+        let started = Instant::now();
+        let result = match &signal.trigger {
+            Some(trigger) => self.simulator.simulate_with_pending_state(signal, trigger).await,
+            None => self.simulator.simulate_liquidation(signal).await,
+        };
+        let elapsed_us = started.elapsed().as_secs_f64() * 1e6;
+        self.stats.lock().unwrap()[worker].record(elapsed_us, result.is_err());
+        SimulationRun { worker, elapsed_us, result }
+    }
+
+    /// Per-worker totals since the last call, indexed by worker id
+    pub fn take_stats(&self) -> Vec<WorkerStats> {
+        let mut stats = self.stats.lock().unwrap();
+        let taken = stats.clone();
+        stats.iter_mut().for_each(|worker| *worker = WorkerStats::default());
+        taken
+    }
+}
+
+struct Checkout<'a> {
+    idle: &'a Mutex<Vec<usize>>,
+    worker: usize,
+}
+
+impl Drop for Checkout<'_> {
+    fn drop(&mut self) {
+        self.idle.lock().unwrap().push(self.worker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::BlockchainClient;
+    use crate::metrics::LatencyMetrics;
+    use crate::protocol::HealthFactorScale;
+    use crate::units::{HealthFactor, TokenAmount};
+    use ethers::types::{Address, U256};
+    use futures::future;
+
+    #[tokio::test]
+    async fn test_burst_spreads_over_workers() {
+        let blockchain = Arc::new(BlockchainClient::new("http://127.0.0.1:8545", None, Address::zero(), Address::zero()).await.unwrap());
+        let pool = SimulationPool::new(Arc::new(LiquidationSimulator::new(blockchain, 10.0)), 2);
+        let signal = LiquidationSignal {
+            user: Address::from_low_u64_be(1),
+            collateral: TokenAmount::wad(U256::from(5u64) * U256::exp10(18)),
+            debt: TokenAmount::wad(U256::from(8000u64) * U256::exp10(18)),
+            health_factor: HealthFactor::new(U256::from(80), HealthFactorScale::PERCENT),
+            collateral_asset: Address::zero(),
+            debt_asset: Address::zero(),
+            trigger: None,
+            observed_block: None,
+            stale: false,
+            metrics: LatencyMetrics::new(),
+        };
+
+        let runs = future::join_all((0..5).map(|_| pool.simulate(&signal))).await;
+        assert!(runs.iter().all(|run| run.worker < 2));
+        assert_eq!(pool.busy_workers(), 0);
+
+        let stats = pool.take_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats.iter().map(|worker| worker.simulations).sum::<usize>(), 5);
+        assert!(stats.iter().all(|worker| worker.max_us >= worker.mean_us()));
+        assert_eq!(pool.take_stats(), vec![WorkerStats::default(); 2]);
+    }
+}
@@ -1,38 +1,449 @@
-use anyhow::Result;
-use ethers::types::{Address, U256};
-use std::sync::Arc;
-use tracing::{debug, info};
+use ethers::{
+    contract::parse_log,
+    providers::{Middleware, ProviderError},
+    types::{Address, Transaction, TransactionRequest, H256, U256},
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
 
-use crate::blockchain::BlockchainClient;
-use crate::liquidation_detector::LiquidationSignal;
+use crate::blockchain::{BlockchainClient, LiquidateFilter, RpcError};
+use crate::funding::{FundingQuote, FundingRouter};
+use crate::inventory::InventoryManager;
+use crate::gas::{GasCost, GasEstimator, GasModel};
+use crate::liquidation_detector::{DetectorError, LiquidationSignal, SignalTtl};
+use crate::fixed_point::{from_f64, mul_div, wad_to_f64};
+use crate::oracle::{PriceOracle, PriceQuote};
+use crate::read_cache::CacheKey;
+use crate::pending_state::PendingState;
+use crate::protocol::{AssetRiskParams, ProtocolAdapter, SimpleLendingAdapter};
+use crate::tokens::{TokenMetadata, TokenRegistry};
+use crate::units::{HealthFactor, TokenAmount, WadUsd};
+use crate::valuation::CollateralPricer;
 
-const ETH_PRICE_USD: u64 = 2000; // Simplified price oracle
-const LIQUIDATION_BONUS: u64 = 110; // 10% bonus
-const PRECISION: u64 = 100;
+const ETH_PRICE_USD: u64 = 2000; // Used when no price oracle is configured
+
+pub type Result<T, E = SimulationError> = std::result::Result<T, E>;
+
+/// Gas assumed for a liquidation the node can't estimate
+pub const FALLBACK_LIQUIDATION_GAS: u64 = 300_000;
+/// Gas price assumed when the node can't report one (50 gwei)
+const FALLBACK_GAS_PRICE: u64 = 50_000_000_000;
+
+/// Why a liquidation could not be simulated
+#[derive(Debug, thiserror::Error)]
+pub enum SimulationError {
+    /// The signal expired before it was simulated
+    #[error(transparent)]
+    Signal(#[from] DetectorError),
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+    /// No usable ETH price, or the price sources disagree
+    #[error("No usable ETH price: {0:#}")]
+    Price(anyhow::Error),
+    #[error("{user:?} is not liquidatable after {trigger:?} (hf {health_factor})")]
+    NotLiquidatable { user: Address, trigger: H256, health_factor: U256 },
+    #[error("Reading risk params of {asset:?} on {protocol}: {source:#}")]
+    RiskParams { asset: Address, protocol: String, source: anyhow::Error },
+    /// Decimals of the collateral or debt asset couldn't be read
+    #[error("{0:#}")]
+    Token(anyhow::Error),
+    #[error("Pricing {debt_to_cover} of debt at ${eth_price_usd}: {source:#}")]
+    Pricing { debt_to_cover: U256, eth_price_usd: f64, source: anyhow::Error },
+    #[error("Trigger replay failed on snapshot: {0}")]
+    TriggerReplay(Box<SimulationError>),
+    /// The liquidation did not go through on the Anvil snapshot
+    #[error("{0}")]
+    Snapshot(&'static str),
+}
+
+impl SimulationError {
+    /// May succeed if simulated again: the node or the price sources failed,
+    /// rather than the liquidation itself
+    pub fn is_transient(&self) -> bool {
+        match self {
+            SimulationError::Rpc(e) => e.is_transient(),
+            SimulationError::Price(_) | SimulationError::Token(_) | SimulationError::RiskParams { .. } => true,
+            SimulationError::TriggerReplay(e) => e.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+impl From<ProviderError> for SimulationError {
+    fn from(e: ProviderError) -> Self {
+        SimulationError::Rpc(e.into())
+    }
+}
 
 /// Simulation result for liquidation profitability
 #[derive(Debug, Clone)]
 pub struct SimulationResult {
     pub profitable: bool,
     pub expected_profit_usd: f64,
-    pub collateral_to_seize: U256,
-    pub debt_to_cover: U256,
+    pub collateral_to_seize: TokenAmount,
+    pub debt_to_cover: TokenAmount,
     pub estimated_gas: U256,
     pub estimated_gas_cost_usd: f64,
+    /// Profit at the unfavourable edge of the price confidence band; this is
+    /// what `profitable` is judged on
+    pub worst_case_profit_usd: f64,
+    /// Part of `estimated_gas_cost_usd` paid for L1 data on rollups (0 on L1)
+    pub l1_data_fee_usd: f64,
+    /// Bonus applied to seized collateral, in basis points
+    pub liquidation_bonus_bps: u32,
+    /// Share of the debt repaid, in basis points
+    pub close_factor_bps: u32,
+    /// ETH price the profit was computed at
+    pub eth_price_usd: f64,
+    /// Cheapest way to fund `debt_to_cover`; its cost is already taken out of profit
+    pub funding: FundingQuote,
+    pub funding_cost_usd: f64,
+    /// Collateral value lost to price impact when selling it on-chain, already
+    /// taken out of profit
+    pub slippage_usd: f64,
+}
+
+impl SimulationResult {
+    /// `amount` of the debt asset in whole tokens (USD, for a stablecoin)
+    pub fn debt_tokens(&self, amount: U256) -> f64 {
+        TokenAmount { raw: amount, ..self.debt_to_cover }.to_tokens()
+    }
+    
+    /// `amount` of the collateral asset in whole tokens
+    pub fn collateral_tokens(&self, amount: U256) -> f64 {
+        TokenAmount { raw: amount, ..self.collateral_to_seize }.to_tokens()
+    }
+    
+    /// USD committed to repaying `debt_to_cover`
+    pub fn repay_usd(&self) -> f64 {
+        self.debt_to_cover.to_tokens()
+    }
+    
+    /// `usd` of the debt asset (a stablecoin) in its base units, rounded down
+    pub fn debt_amount(&self, usd: WadUsd) -> anyhow::Result<TokenAmount> {
+        TokenAmount::from_usd(usd, self.debt_to_cover.decimals)
+    }
+}
+
+/// Minimum worst-case profit overrides for particular markets
+///
+/// When several overrides match a liquidation the strictest (highest) applies;
+/// with none, the simulator's global threshold does.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfitThresholds {
+    /// Keyed by `ProtocolAdapter::name`
+    pub protocol_usd: HashMap<String, f64>,
+    pub debt_asset_usd: HashMap<Address, f64>,
+    /// Higher for volatile collateral, which can move before it is sold
+    pub collateral_asset_usd: HashMap<Address, f64>,
+}
+
+impl ProfitThresholds {
+    /// Threshold for liquidating on `protocol`, seizing `collateral_asset` for `debt_asset`
+    pub fn for_market(&self, default_usd: f64, protocol: &str, collateral_asset: Address, debt_asset: Address) -> f64 {
+        [
+            self.protocol_usd.get(protocol),
+            self.debt_asset_usd.get(&debt_asset),
+            self.collateral_asset_usd.get(&collateral_asset),
+        ]
+        .into_iter()
+        .flatten()
+        .copied()
+        .reduce(f64::max)
+        .unwrap_or(default_usd)
+    }
+}
+
+/// How the simulator determines seized collateral and gas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationBackend {
+    /// Closed-form estimate from the protocol's bonus and `eth_estimateGas`
+    Analytical,
+    /// Execute the real liquidation on Anvil inside `evm_snapshot`/`evm_revert`,
+    /// impersonating `liquidator` and minting it the mock stablecoin it needs
+    Snapshot { liquidator: Address },
+}
+
+/// Share `fraction` (0-1] of `debt_to_cover`, to the nearest basis point
+pub fn throttled_repayment(debt_to_cover: U256, fraction: f64) -> U256 {
+    if fraction >= 1.0 {
+        return debt_to_cover;
+    }
+    let bps = U256::from((fraction.max(0.0) * 10_000.0).round() as u64);
+    // Below 1.0 the result is smaller than the debt, so it always fits
+    mul_div(debt_to_cover, bps, U256::from(10_000)).unwrap_or(debt_to_cover)
+}
+
+/// Collateral (in its base units, bonus included) that repaying
+/// `debt_to_cover` of a USD stablecoin seizes with ETH at `eth_price_e8`
+///
+/// Every step is rounded down, so the estimate never overstates the seizure.
+pub fn collateral_for_debt(
+    debt_to_cover: U256,
+    debt_token: &TokenMetadata,
+    collateral_token: &TokenMetadata,
+    eth_price_e8: U256,
+    params: &AssetRiskParams,
+) -> anyhow::Result<U256> {
+    // The debt asset is a USD stablecoin, so its wad amount is its USD value
+    let collateral_value = debt_token
+        .to_wad(debt_to_cover)
+        .and_then(|debt_usd| mul_div(debt_usd, U256::exp10(8), eth_price_e8))
+        .and_then(|collateral_wad| collateral_token.from_wad(collateral_wad))?;
+    Ok(params.with_bonus(collateral_value))
+}
+
+/// Expected and worst-case profit of seizing `collateral_eth` for
+/// `debt_value_usd` of debt
+///
+/// The worst case sells collateral at the bottom of the confidence band and
+/// pays gas (priced at `quote`) at the top.
+pub fn liquidation_profit_usd(
+    collateral_eth: f64,
+    debt_value_usd: f64,
+    gas_cost_usd: f64,
+    quote: PriceQuote,
+    confidence_band: f64,
+) -> (f64, f64) {
+    let expected = collateral_eth * quote.price_usd - debt_value_usd - gas_cost_usd;
+    
+    let (price_low, price_high) = quote.band(confidence_band);
+    let worst_case = collateral_eth * price_low - debt_value_usd - gas_cost_usd / quote.price_usd * price_high;
+    (expected, worst_case)
 }
 
 /// Simulates liquidation transactions to verify profitability
 pub struct LiquidationSimulator {
     blockchain: Arc<BlockchainClient>,
-    min_profit_threshold: f64,
+    // f64 bits, so operators can retune it while the pipeline runs
+    min_profit_threshold: AtomicU64,
+    // Gas price in wei a backtest scenario imposes (0 asks the RPC)
+    gas_price_override: AtomicU64,
+    // f64 bits: share of the close-factor maximum to repay (1.0 unless drawdown throttles it)
+    repay_fraction: AtomicU64,
+    backend: SimulationBackend,
+    // Snapshots share one chain state, so fork executions run one at a time
+    snapshot_lock: Mutex<()>,
+    adapter: Arc<dyn ProtocolAdapter>,
+    // Risk parameters rarely change, so they are fetched once per asset
+    risk_params: RwLock<HashMap<Address, AssetRiskParams>>,
+    gas: GasEstimator,
+    oracle: Option<Arc<PriceOracle>>,
+    // Confidence intervals the price may move against us before profit is judged
+    confidence_band: f64,
+    funding: Option<Arc<FundingRouter>>,
+    // Wallet balances deciding whether a repayment needs funding at all
+    inventory: Option<Arc<InventoryManager>>,
+    collateral_pricer: Option<Arc<CollateralPricer>>,
+    ttl: SignalTtl,
+    tokens: TokenRegistry,
+    profit_thresholds: ProfitThresholds,
 }
 
 impl LiquidationSimulator {
     pub fn new(blockchain: Arc<BlockchainClient>, min_profit_threshold: f64) -> Self {
+        Self::with_backend(blockchain, min_profit_threshold, SimulationBackend::Analytical)
+    }
+    
+    pub fn with_backend(
+        blockchain: Arc<BlockchainClient>,
+        min_profit_threshold: f64,
+        backend: SimulationBackend,
+    ) -> Self {
         Self {
+            adapter: Arc::new(SimpleLendingAdapter::new(blockchain.clone())),
+            gas: GasEstimator::new(&blockchain, GasModel::L1),
+            tokens: TokenRegistry::new(&blockchain),
             blockchain,
-            min_profit_threshold,
+            min_profit_threshold: AtomicU64::new(min_profit_threshold.to_bits()),
+            gas_price_override: AtomicU64::new(0),
+            repay_fraction: AtomicU64::new(1f64.to_bits()),
+            backend,
+            snapshot_lock: Mutex::new(()),
+            risk_params: RwLock::new(HashMap::new()),
+            oracle: None,
+            confidence_band: 1.0,
+            funding: None,
+            inventory: None,
+            collateral_pricer: None,
+            ttl: SignalTtl::default(),
+            profit_thresholds: ProfitThresholds::default(),
+        }
+    }
+    
+    /// Price gas with `model` (L2 execution plus L1 data fee on rollups)
+    pub fn with_gas_model(mut self, model: GasModel) -> Self {
+        self.gas = GasEstimator::new(&self.blockchain, model);
+        self
+    }
+    
+    /// Price ETH from Chainlink/TWAP instead of the fixed $2000, refusing to
+    /// simulate when the sources disagree
+    pub fn with_price_oracle(mut self, oracle: Arc<PriceOracle>) -> Self {
+        self.oracle = Some(oracle);
+        self
+    }
+    
+    /// Require profit to hold across `multiplier` confidence intervals of the
+    /// ETH price (0 judges profit at the quoted price only)
+    pub fn with_confidence_band(mut self, multiplier: f64) -> Self {
+        self.confidence_band = multiplier;
+        self
+    }
+    
+    /// Fund repayments from the cheapest flash source in `router` rather than
+    /// assuming the wallet holds the debt asset
+    pub fn with_funding(mut self, router: Arc<FundingRouter>) -> Self {
+        self.funding = Some(router);
+        self
+    }
+    
+    /// Repay from the executor wallets' own debt asset when they hold enough,
+    /// pricing a flash loan or wallet swap only for what they lack
+    pub fn with_inventory(mut self, inventory: Arc<InventoryManager>) -> Self {
+        self.inventory = Some(inventory);
+        self
+    }
+    
+    /// Value seized collateral at what DEX pools would pay for it rather than
+    /// `amount * price`, which overstates large liquidations
+    pub fn with_collateral_pricer(mut self, pricer: Arc<CollateralPricer>) -> Self {
+        self.collateral_pricer = Some(pricer);
+        self
+    }
+    
+    /// Refuse to simulate signals past `ttl`
+    pub fn with_signal_ttl(mut self, ttl: SignalTtl) -> Self {
+        self.ttl = ttl;
+        self
+    }
+    
+    /// Require a different minimum profit for some protocols or assets
+    pub fn with_profit_thresholds(mut self, thresholds: ProfitThresholds) -> Self {
+        self.profit_thresholds = thresholds;
+        self
+    }
+    
+    /// Global minimum worst-case profit, used where no override applies
+    pub fn min_profit_threshold(&self) -> f64 {
+        f64::from_bits(self.min_profit_threshold.load(Ordering::Relaxed))
+    }
+    
+    /// Change the minimum worst-case profit for subsequent simulations
+    pub fn set_min_profit_threshold(&self, usd: f64) {
+        self.min_profit_threshold.store(usd.to_bits(), Ordering::Relaxed);
+    }
+    
+    /// Price gas at `base_fee_wei` plus the gas model's priority fee instead
+    /// of the RPC's gas price (None goes back to the RPC)
+    pub fn set_base_fee_override(&self, base_fee_wei: Option<u64>) {
+        let gas_price = base_fee_wei.map_or(0, |base_fee| base_fee + self.gas.model().priority_fee().as_u64());
+        self.gas_price_override.store(gas_price, Ordering::Relaxed);
+    }
+    
+    /// Gas price simulations are priced at: the scenario override, else the node's
+    async fn gas_price(&self) -> U256 {
+        match self.gas_price_override() {
+            Some(gas_price) => gas_price,
+            None => self.blockchain.get_gas_price().await.unwrap_or(U256::from(FALLBACK_GAS_PRICE)),
+        }
+    }
+    
+    /// Cost under the gas model of a liquidation of `user` using `gas`
+    async fn liquidation_gas_cost(&self, user: Address, debt_to_cover: U256, gas: U256, gas_price: U256) -> GasCost {
+        let calldata = self.blockchain.lending_protocol
+            .liquidate(user, debt_to_cover)
+            .calldata()
+            .unwrap_or_default();
+        self.gas.estimate(self.blockchain.lending_protocol.address(), calldata, gas, gas_price).await
+    }
+    
+    /// USD cost of a typical liquidation (the fallback gas estimate) at the
+    /// current gas price and ETH price, for models that don't simulate each one
+    pub async fn typical_gas_cost_usd(&self) -> Result<f64> {
+        let eth_price_usd = self.eth_price_usd().await?.price_usd;
+        let gas_price = self.gas_price().await;
+        let gas_cost = self.liquidation_gas_cost(Address::zero(), U256::zero(), U256::from(FALLBACK_LIQUIDATION_GAS), gas_price).await;
+        Ok(wad_to_f64(gas_cost.total_wei()) * eth_price_usd)
+    }
+    
+    /// Gas price the next simulation will use, if a scenario overrides it
+    pub fn gas_price_override(&self) -> Option<U256> {
+        match self.gas_price_override.load(Ordering::Relaxed) {
+            0 => None,
+            wei => Some(U256::from(wei)),
+        }
+    }
+    
+    /// Share of the close-factor maximum subsequent simulations repay
+    pub fn repay_fraction(&self) -> f64 {
+        f64::from_bits(self.repay_fraction.load(Ordering::Relaxed))
+    }
+    
+    /// Repay only `fraction` (0-1] of what the close factor allows, e.g. while
+    /// losses are being cut back
+    pub fn set_repay_fraction(&self, fraction: f64) {
+        self.repay_fraction.store(fraction.clamp(f64::MIN_POSITIVE, 1.0).to_bits(), Ordering::Relaxed);
+    }
+    
+    /// Minimum worst-case profit required to liquidate `signal`
+    pub fn min_profit_for(&self, signal: &LiquidationSignal) -> f64 {
+        self.profit_thresholds.for_market(
+            self.min_profit_threshold(),
+            self.adapter.name(),
+            signal.collateral_asset,
+            signal.debt_asset,
+        )
+    }
+    
+    /// ETH price used for this simulation
+    pub async fn eth_price_usd(&self) -> Result<PriceQuote> {
+        match &self.oracle {
+            Some(oracle) => self.blockchain
+                .cached(CacheKey::EthPrice, || oracle.eth_price_usd())
+                .await
+                .map_err(SimulationError::Price),
+            None => Ok(PriceQuote::exact(ETH_PRICE_USD as f64)),
+        }
+    }
+    
+    /// Name of the protocol this simulator prices liquidations for
+    pub fn protocol_name(&self) -> &str {
+        self.adapter.name()
+    }
+    
+    /// Read liquidation bonus / close factor from `adapter` instead of SimpleLendingProtocol
+    pub fn with_adapter(mut self, adapter: Arc<dyn ProtocolAdapter>) -> Self {
+        self.adapter = adapter;
+        self.risk_params.write().unwrap().clear();
+        self
+    }
+    
+    /// Debt a simulation of `signal` repays: as much as the close factor
+    /// allows, less while drawdown throttles size
+    pub async fn repayment(&self, signal: &LiquidationSignal) -> Result<U256> {
+        let debt_params = self.risk_params(signal.debt_asset).await?;
+        Ok(throttled_repayment(debt_params.max_repayable(signal.debt.raw), self.repay_fraction()))
+    }
+    
+    /// Risk parameters for `asset`, cached after the first successful fetch
+    pub async fn risk_params(&self, asset: Address) -> Result<AssetRiskParams> {
+        if let Some(params) = self.risk_params.read().unwrap().get(&asset) {
+            return Ok(*params);
         }
+        
+        // No defaults on failure: another protocol's bonus and close factor
+        // would misprice the liquidation
+        let params = self.adapter.asset_params(asset).await.map_err(|source| SimulationError::RiskParams {
+            asset,
+            protocol: self.adapter.name().to_string(),
+            source,
+        })?;
+        self.risk_params.write().unwrap().insert(asset, params);
+        Ok(params)
     }
     
     /// Simulate liquidation and calculate profitability
@@ -40,43 +451,148 @@ impl LiquidationSimulator {
     pub async fn simulate_liquidation(
         &self,
         signal: &LiquidationSignal,
+    ) -> Result<SimulationResult> {
+        self.ttl.check_now(signal, &self.blockchain).await?;
+        // Fails when the price sources diverge, so nothing gets executed on a bad price
+        let quote = self.eth_price_usd().await?;
+        self.simulate_at(signal, quote, None).await
+    }
+    
+    /// Simulate the liquidation in the state `trigger` leaves behind once mined,
+    /// rather than the current one, since that is where the liquidation lands
+    ///
+    /// The position is re-read through `eth_call` state overrides; the snapshot
+    /// backend replays `trigger` itself before liquidating. Falls back to the
+    /// current state if the node rejects overrides.
+    pub async fn simulate_with_pending_state(
+        &self,
+        signal: &LiquidationSignal,
+        trigger: &Transaction,
+    ) -> Result<SimulationResult> {
+        self.ttl.check_now(signal, &self.blockchain).await?;
+        let pending = match PendingState::after(&self.blockchain, trigger).await {
+            Ok(Some(pending)) => pending,
+            Ok(None) => return self.simulate_liquidation(signal).await,
+            Err(e) => {
+                warn!("Could not derive state after {:?}, simulating on current state: {}", trigger.hash, e);
+                return self.simulate_liquidation(signal).await;
+            }
+        };
+        
+        let (collateral, debt, health_factor) = match self.blockchain.get_position_at(signal.user, &pending.overrides).await {
+            Ok(position) => position,
+            Err(e) => {
+                warn!("State override call failed, simulating on current state: {}", e);
+                return self.simulate_liquidation(signal).await;
+            }
+        };
+        let landed = LiquidationSignal {
+            collateral: TokenAmount { raw: collateral, ..signal.collateral },
+            debt: TokenAmount { raw: debt, ..signal.debt },
+            health_factor: HealthFactor::new(health_factor, self.adapter.health_factor_scale()),
+            ..signal.clone()
+        };
+        if !landed.health_factor.is_liquidatable(landed.debt) {
+            return Err(SimulationError::NotLiquidatable { user: signal.user, trigger: trigger.hash, health_factor });
+        }
+        
+        // A price update is the price the liquidation executes at
+        let quote = match pending.eth_price_usd {
+            Some(price_usd) => PriceQuote::exact(price_usd),
+            None => self.eth_price_usd().await?,
+        };
+        debug!(
+            "Pending state of {:?}: collateral {} debt {} hf {} (was hf {})",
+            signal.user, collateral, debt, health_factor, signal.health_factor.raw
+        );
+        self.simulate_at(&landed, quote, Some(trigger)).await
+    }
+    
+    async fn simulate_at(
+        &self,
+        signal: &LiquidationSignal,
+        quote: PriceQuote,
+        trigger: Option<&Transaction>,
     ) -> Result<SimulationResult> {
         let start = std::time::Instant::now();
         
-        // Calculate optimal debt to cover (start with full debt)
-        let debt_to_cover = signal.debt;
+        let eth_price_usd = quote.price_usd;
+        // 8 decimals, as Chainlink reports USD prices
+        let eth_price_e8 = from_f64(eth_price_usd, U256::exp10(8));
         
-        // Calculate collateral to seize with bonus
-        let collateral_value = (debt_to_cover * U256::from(10u64.pow(18))) / U256::from(ETH_PRICE_USD * 10u64.pow(18));
-        let collateral_to_seize = (collateral_value * U256::from(LIQUIDATION_BONUS)) / U256::from(PRECISION);
+        // Bonus is paid in the collateral asset; close factor caps the debt repaid
+        let health_factor = signal.health_factor.to_f64();
+        let collateral_params = self.adapter.params_at_health(self.risk_params(signal.collateral_asset).await?, health_factor);
+        let debt_params = self.risk_params(signal.debt_asset).await?;
+        let debt_to_cover = self.repayment(signal).await?;
+        let collateral_token = self.tokens.metadata(signal.collateral_asset).await.map_err(SimulationError::Token)?;
+        let debt_token = self.tokens.metadata(signal.debt_asset).await.map_err(SimulationError::Token)?;
         
-        // Estimate gas cost
-        let gas_estimate = match self.blockchain.estimate_gas_liquidation(signal.user, debt_to_cover).await {
-            Ok(gas) => gas,
-            Err(_) => U256::from(300_000), // Fallback estimate
+        let (collateral_to_seize, gas_estimate) = match self.backend {
+            SimulationBackend::Analytical => {
+                let collateral_to_seize =
+                    collateral_for_debt(debt_to_cover, &debt_token, &collateral_token, eth_price_e8, &collateral_params)
+                        .map_err(|source| SimulationError::Pricing { debt_to_cover, eth_price_usd, source })?;
+                
+                // Estimate gas cost
+                let gas_estimate = match self.blockchain.estimate_gas_liquidation(signal.user, debt_to_cover).await {
+                    Ok(gas) => gas,
+                    Err(_) => U256::from(FALLBACK_LIQUIDATION_GAS),
+                };
+                (collateral_to_seize, gas_estimate)
+            }
+            SimulationBackend::Snapshot { liquidator } => {
+                self.execute_on_snapshot(signal.user, debt_to_cover, liquidator, trigger).await?
+            }
         };
         
-        let gas_price = self.blockchain.get_gas_price().await.unwrap_or(U256::from(50_000_000_000u64)); // 50 gwei
-        let gas_cost_wei = gas_estimate * gas_price;
-        let gas_cost_eth = gas_cost_wei.as_u128() as f64 / 1e18;
-        let gas_cost_usd = gas_cost_eth * ETH_PRICE_USD as f64;
+        let gas_price = self.gas_price().await;
+        let gas_cost = self.liquidation_gas_cost(signal.user, debt_to_cover, gas_estimate, gas_price).await;
+        let gas_cost_eth = wad_to_f64(gas_cost.total_wei());
+        let gas_cost_usd = gas_cost_eth * eth_price_usd;
+        let l1_data_fee_usd = wad_to_f64(gas_cost.l1_data_fee_wei) * eth_price_usd;
+        
+        let funding = match &self.funding {
+            Some(router) => {
+                let held = self.inventory.as_ref().and_then(|inventory| inventory.largest_balance(signal.debt_asset));
+                router.cheapest(signal.debt_asset, &debt_token, debt_to_cover, held, gas_price, eth_price_usd).await
+            }
+            None => FundingQuote::inventory(),
+        };
+        let funding_cost_usd = funding.cost_usd(&debt_token, gas_price, eth_price_usd);
         
         // Calculate profit
-        let collateral_value_usd = (collateral_to_seize.as_u128() as f64 / 1e18) * ETH_PRICE_USD as f64;
-        let debt_value_usd = debt_to_cover.as_u128() as f64 / 1e18;
-        let expected_profit_usd = collateral_value_usd - debt_value_usd - gas_cost_usd;
+        let collateral = collateral_token.to_f64(collateral_to_seize);
+        let collateral_value_usd = collateral * eth_price_usd;
+        let debt_value_usd = debt_token.to_f64(debt_to_cover);
+        let (expected_profit_usd, worst_case_profit_usd) =
+            liquidation_profit_usd(collateral, debt_value_usd, gas_cost_usd, quote, self.confidence_band);
         
-        let profitable = expected_profit_usd >= self.min_profit_threshold;
+        // The same share of the value is lost to price impact at either edge of the band
+        let slippage = match &self.collateral_pricer {
+            Some(pricer) => pricer.slippage(collateral_to_seize, eth_price_usd).await,
+            None => 0.0,
+        };
+        let slippage_usd = slippage * collateral_value_usd;
+        let worst_case_slippage_usd = slippage * collateral * quote.band(self.confidence_band).0;
+        let expected_profit_usd = expected_profit_usd - funding_cost_usd - slippage_usd;
+        let worst_case_profit_usd = worst_case_profit_usd - funding_cost_usd - worst_case_slippage_usd;
+        
+        let profitable = worst_case_profit_usd >= self.min_profit_for(signal);
         
         let elapsed = start.elapsed();
         debug!("Simulation completed in {:?}", elapsed);
         
         if profitable {
             info!("[PROFITABLE] Liquidation opportunity");
-            info!("   Expected profit: ${:.2}", expected_profit_usd);
+            info!("   Expected profit: ${:.2} (worst case ${:.2})", expected_profit_usd, worst_case_profit_usd);
             info!("   Collateral value: ${:.2}", collateral_value_usd);
             info!("   Debt to cover: ${:.2}", debt_value_usd);
             info!("   Gas cost: ${:.2}", gas_cost_usd);
+            info!("   Funding: {} (${:.2})", funding.source.name(), funding_cost_usd);
+            if slippage_usd > 0.0 {
+                info!("   Slippage: ${:.2} ({:.2}%)", slippage_usd, slippage * 100.0);
+            }
         } else {
             debug!("[UNPROFITABLE] Liquidation (profit: ${:.2})", expected_profit_usd);
         }
@@ -84,24 +600,126 @@ impl LiquidationSimulator {
         Ok(SimulationResult {
             profitable,
             expected_profit_usd,
-            collateral_to_seize,
-            debt_to_cover,
+            worst_case_profit_usd,
+            collateral_to_seize: TokenAmount::new(collateral_to_seize, collateral_token.decimals),
+            debt_to_cover: TokenAmount::new(debt_to_cover, debt_token.decimals),
             estimated_gas: gas_estimate,
             estimated_gas_cost_usd: gas_cost_usd,
+            l1_data_fee_usd,
+            liquidation_bonus_bps: collateral_params.liquidation_bonus_bps,
+            close_factor_bps: debt_params.close_factor_bps,
+            eth_price_usd,
+            funding,
+            funding_cost_usd,
+            slippage_usd,
         })
     }
     
+    /// Run the liquidation for real against Anvil (after `trigger`, if given) and roll it back
+    /// Returns (collateral actually seized, gas actually used)
+    async fn execute_on_snapshot(
+        &self,
+        user: Address,
+        debt_to_cover: U256,
+        liquidator: Address,
+        trigger: Option<&Transaction>,
+    ) -> Result<(U256, U256)> {
+        let _guard = self.snapshot_lock.lock().await;
+        
+        let snapshot_id = self.blockchain.snapshot().await?;
+        let result = match trigger {
+            Some(trigger) => match self.replay_as_sender(trigger).await {
+                Ok(()) => self.execute_liquidation_as(user, debt_to_cover, liquidator).await,
+                Err(e) => Err(SimulationError::TriggerReplay(Box::new(e))),
+            },
+            None => self.execute_liquidation_as(user, debt_to_cover, liquidator).await,
+        };
+        
+        let _ = self.blockchain.stop_impersonating_account(liquidator).await;
+        if !self.blockchain.revert_to_snapshot(snapshot_id).await? {
+            warn!("evm_revert to snapshot {} failed; chain state may be dirty", snapshot_id);
+        }
+        
+        result
+    }
+    
+    /// Mine `tx`'s call on the snapshot, impersonating its sender
+    async fn replay_as_sender(&self, tx: &Transaction) -> Result<()> {
+        self.blockchain.impersonate_account(tx.from).await?;
+        let mut replay = TransactionRequest::new().from(tx.from).data(tx.input.clone()).value(tx.value);
+        if let Some(to) = tx.to {
+            replay = replay.to(to);
+        }
+        let receipt = self.blockchain.http_provider.send_transaction(replay, None).await?.await;
+        let _ = self.blockchain.stop_impersonating_account(tx.from).await;
+        if receipt?.and_then(|r| r.status) != Some(1.into()) {
+            return Err(SimulationError::Snapshot("Trigger reverted on snapshot"));
+        }
+        Ok(())
+    }
+    
+    async fn execute_liquidation_as(
+        &self,
+        user: Address,
+        debt_to_cover: U256,
+        liquidator: Address,
+    ) -> Result<(U256, U256)> {
+        let provider = &self.blockchain.http_provider;
+        let protocol = self.blockchain.lending_protocol.address();
+        let token = &self.blockchain.token;
+        
+        self.blockchain.impersonate_account(liquidator).await?;
+        
+        // Fund and approve the debt asset, then liquidate, all as the impersonated liquidator
+        let setup = [
+            token.mint(liquidator, debt_to_cover).calldata(),
+            token.approve(protocol, debt_to_cover).calldata(),
+        ];
+        for calldata in setup {
+            let tx = TransactionRequest::new()
+                .from(liquidator)
+                .to(token.address())
+                .data(calldata.ok_or(SimulationError::Snapshot("Setup call could not be encoded"))?);
+            provider.send_transaction(tx, None).await?.await?;
+        }
+        
+        let calldata = self.blockchain.lending_protocol
+            .liquidate(user, debt_to_cover)
+            .calldata()
+            .ok_or(SimulationError::Snapshot("Liquidation could not be encoded"))?;
+        let tx = TransactionRequest::new().from(liquidator).to(protocol).data(calldata);
+        let receipt = provider
+            .send_transaction(tx, None)
+            .await?
+            .await?
+            .ok_or(SimulationError::Snapshot("Liquidation dropped from snapshot"))?;
+        
+        if receipt.status != Some(1.into()) {
+            return Err(SimulationError::Snapshot("Liquidation reverted on snapshot"));
+        }
+        
+        let seized = receipt.logs
+            .into_iter()
+            .find_map(|log| parse_log::<LiquidateFilter>(log).ok())
+            .map(|event| event.collateral_seized)
+            .ok_or(SimulationError::Snapshot("No Liquidate event in receipt"))?;
+        let gas_used = receipt.gas_used.unwrap_or_default();
+        
+        debug!("Snapshot liquidation of {}: seized {} wei using {} gas", user, seized, gas_used);
+        Ok((seized, gas_used))
+    }
+    
     /// Quick profitability check without full simulation (ultra-fast)
     pub fn quick_profitability_check(&self, signal: &LiquidationSignal) -> bool {
         // Simple heuristic: check if liquidation bonus covers gas costs
-        let collateral_value_usd = (signal.collateral.as_u128() as f64 / 1e18) * ETH_PRICE_USD as f64;
-        let debt_value_usd = signal.debt.as_u128() as f64 / 1e18;
+        let collateral_value_usd = signal.collateral.value_usd(ETH_PRICE_USD as f64);
+        let debt_value_usd = signal.debt.to_tokens();
         let bonus_value = (collateral_value_usd * 0.10) - (debt_value_usd * 0.0); // 10% bonus
         
         // Rough gas cost estimate
         let estimated_gas_cost_usd = (300_000.0 * 50.0) / 1e9 * ETH_PRICE_USD as f64;
         
-        bonus_value > estimated_gas_cost_usd + self.min_profit_threshold
+        bonus_value > estimated_gas_cost_usd + self.min_profit_for(signal)
     }
     
     /// Optimize debt amount to cover for maximum profit
@@ -110,24 +728,32 @@ impl LiquidationSimulator {
         &self,
         signal: &LiquidationSignal,
     ) -> Result<U256> {
-        // For this POC, we liquidate the full debt
-        // In production, you might liquidate partial amounts
-        Ok(signal.debt)
+        // Repay the most the protocol's close factor allows
+        Ok(self.risk_params(signal.debt_asset).await?.max_repayable(signal.debt.raw))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ethers::types::Address;
+    use crate::fixed_point;
     use crate::metrics::LatencyMetrics;
+    use crate::protocol::HealthFactorScale;
+    use crate::testkit::{offline_client, FixedRiskParams};
 
     #[test]
     fn test_profitability_calculation() {
         let signal = LiquidationSignal {
             user: Address::zero(),
-            collateral: U256::from(5 * 10u64.pow(18)), // 5 ETH
-            debt: U256::from(8000 * 10u64.pow(18)), // $8000
-            health_factor: U256::from(80), // 80%
+            collateral: TokenAmount::wad(U256::from(5 * 10u64.pow(18))), // 5 ETH
+            debt: TokenAmount::wad(U256::from(8000) * U256::exp10(18)), // $8000
+            health_factor: HealthFactor::new(U256::from(80), HealthFactorScale::PERCENT), // 80%
+            collateral_asset: Address::zero(),
+            debt_asset: Address::zero(),
+            trigger: None,
+            observed_block: None,
+            stale: false,
             metrics: LatencyMetrics::new(),
         };
         
@@ -136,8 +762,150 @@ mod tests {
         // With 10% bonus, liquidator gets $8,800 worth of ETH for $8,000 debt
         // Profit = $800 - gas (should be profitable)
         
-        assert!(signal.health_factor < U256::from(100));
+        assert!(signal.health_factor.to_f64() < 1.0);
+    }
+    
+    #[test]
+    fn test_profit_thresholds_per_market() {
+        let (weth, usdc, dai) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2), Address::from_low_u64_be(3));
+        let thresholds = ProfitThresholds {
+            protocol_usd: HashMap::from([("aave".to_string(), 20.0)]),
+            debt_asset_usd: HashMap::from([(usdc, 5.0)]),
+            collateral_asset_usd: HashMap::from([(weth, 50.0)]),
+        };
+        assert_eq!(thresholds.for_market(10.0, "simple-lending", Address::zero(), dai), 10.0);
+        // A single match applies even below the global default
+        assert_eq!(thresholds.for_market(10.0, "simple-lending", Address::zero(), usdc), 5.0);
+        assert_eq!(thresholds.for_market(10.0, "aave", weth, usdc), 50.0);
+    }
+    
+    #[tokio::test]
+    async fn test_adapter_params_applied() {
+        let blockchain = offline_client().await;
+        // Params that can't be read fail the simulation rather than default
+        let unread = LiquidationSimulator::new(blockchain.clone(), 10.0).risk_params(Address::zero()).await;
+        assert!(matches!(&unread, Err(e @ SimulationError::RiskParams { .. }) if e.is_transient()));
+        
+        let params = AssetRiskParams { liquidation_bonus_bps: 500, close_factor_bps: 5_000 };
+        let simulator = LiquidationSimulator::new(blockchain, 10.0)
+            .with_adapter(Arc::new(FixedRiskParams(params)));
+        
+        let signal = LiquidationSignal {
+            user: Address::zero(),
+            collateral: TokenAmount::wad(U256::from(5 * 10u64.pow(18))), // 5 ETH
+            debt: TokenAmount::wad(U256::from(8000) * U256::exp10(18)), // $8000
+            health_factor: HealthFactor::new(U256::from(80), HealthFactorScale::PERCENT),
+            collateral_asset: Address::zero(),
+            debt_asset: Address::zero(),
+            trigger: None,
+            observed_block: None,
+            stale: false,
+            metrics: LatencyMetrics::new(),
+        };
+        
+        let result = simulator.simulate_liquidation(&signal).await.unwrap();
+        
+        // Half the debt ($4000) buys 2 ETH, plus 5% bonus
+        assert_eq!(result.debt_to_cover, TokenAmount::wad(U256::from(4000) * U256::exp10(18)));
+        assert_eq!(result.collateral_to_seize, TokenAmount::wad(U256::from(21) * U256::exp10(17)));
+        assert_eq!(result.close_factor_bps, 5_000);
+        
+        // The helper's profit floor is in the debt asset's own decimals
+        let floor = WadUsd::from_usd(12.5);
+        assert_eq!(result.debt_amount(floor).unwrap(), TokenAmount::wad(U256::from(125) * U256::exp10(17)));
+        let usdc_debt = SimulationResult { debt_to_cover: TokenAmount::new(U256::from(4_000_000_000u64), 6), ..result };
+        assert_eq!(usdc_debt.debt_amount(floor).unwrap(), TokenAmount::new(U256::from(12_500_000), 6));
+        assert_eq!(usdc_debt.repay_usd(), 4000.0);
+    }
+    
+    fn token(decimals: u8) -> TokenMetadata {
+        TokenMetadata { symbol: format!("T{}", decimals), decimals }
+    }
+    
+    fn any_u256() -> impl proptest::strategy::Strategy<Value = U256> {
+        proptest::strategy::Strategy::prop_map(proptest::array::uniform4(proptest::num::u64::ANY), U256)
+    }
+    
+    proptest::proptest! {
+        // A larger bonus never seizes less collateral, nor earns less
+        #[test]
+        fn prop_profit_monotonic_in_bonus(
+            debt_usd in 0u64..10_000_000,
+            price_usd in 1.0f64..100_000.0,
+            bonus in 0u32..5_000,
+            extra in 0u32..5_000,
+            decimals in 0u8..=24,
+        ) {
+            let debt = U256::from(debt_usd) * U256::exp10(18);
+            let price_e8 = from_f64(price_usd, U256::exp10(8));
+            let seize = |liquidation_bonus_bps| {
+                let params = AssetRiskParams { liquidation_bonus_bps, close_factor_bps: 10_000 };
+                collateral_for_debt(debt, &token(18), &token(decimals), price_e8, &params).unwrap()
+            };
+            let (lower, higher) = (seize(bonus), seize(bonus + extra));
+            proptest::prop_assert!(lower <= higher);
+            // Rounding down, the seizure is worth at most the debt plus the bonus
+            let value_usd = token(decimals).to_f64(higher) * fixed_point::to_f64(price_e8, U256::exp10(8));
+            proptest::prop_assert!(value_usd <= debt_usd as f64 * (1.0 + (bonus + extra) as f64 / 10_000.0) * (1.0 + 1e-12));
+            
+            let quote = PriceQuote { price_usd, confidence_usd: price_usd / 100.0 };
+            let profit = |seized| liquidation_profit_usd(token(decimals).to_f64(seized), debt_usd as f64, 5.0, quote, 2.0);
+            let (low, high) = (profit(lower), profit(higher));
+            proptest::prop_assert!(low.0 <= high.0 && low.1 <= high.1);
+            proptest::prop_assert!(high.1 <= high.0);
+        }
+        
+        // Any amounts and prices either price or fail cleanly; nothing panics,
+        // and nothing seized is worth less than the debt repaid
+        #[test]
+        fn prop_no_overflow_across_u256(
+            debt in any_u256(),
+            price_e8 in any_u256(),
+            fraction in 0.0f64..=1.0,
+            bonus in proptest::num::u32::ANY,
+            close_factor in proptest::num::u32::ANY,
+            debt_decimals in 0u8..=36,
+            collateral_decimals in 0u8..=36,
+        ) {
+            let params = AssetRiskParams { liquidation_bonus_bps: bonus, close_factor_bps: close_factor };
+            let repaid = throttled_repayment(params.max_repayable(debt), fraction);
+            proptest::prop_assert!(repaid <= debt);
+            let (debt_token, collateral_token) = (token(debt_decimals), token(collateral_decimals));
+            let seized = collateral_for_debt(repaid, &debt_token, &collateral_token, price_e8, &params);
+            proptest::prop_assert_eq!(seized.is_err() && price_e8.is_zero(), price_e8.is_zero());
+            if let Ok(seized) = seized {
+                let unbonused = AssetRiskParams { liquidation_bonus_bps: 0, ..params };
+                let value = collateral_for_debt(repaid, &debt_token, &collateral_token, price_e8, &unbonused).unwrap();
+                proptest::prop_assert!(seized >= value);
+                proptest::prop_assert!(collateral_token.to_f64(seized).is_finite());
+            }
+        }
+        
+        // The same dollar amount seizes the same collateral whatever either
+        // token's decimals, up to rounding in the coarser token
+        #[test]
+        fn prop_decimals_symmetry(
+            micros in 0u64..u64::MAX,
+            price_usd in 1.0f64..100_000.0,
+            bonus in 0u32..5_000,
+            collateral_decimals in 0u8..=18,
+        ) {
+            let params = AssetRiskParams { liquidation_bonus_bps: bonus, close_factor_bps: 10_000 };
+            let price_e8 = from_f64(price_usd, U256::exp10(8));
+            let six = collateral_for_debt(U256::from(micros), &token(6), &token(18), price_e8, &params).unwrap();
+            let wad = U256::from(micros) * U256::exp10(12);
+            proptest::prop_assert_eq!(six, collateral_for_debt(wad, &token(18), &token(18), price_e8, &params).unwrap());
+            
+            let coarse = collateral_for_debt(wad, &token(18), &token(collateral_decimals), price_e8, &params).unwrap();
+            let scale = U256::exp10(18 - collateral_decimals as usize);
+            // Truncating before and after the bonus loses at most one coarse
+            // unit each time, plus the (at most 50%) bonus on the first
+            let rescaled = coarse * scale;
+            proptest::prop_assert!(rescaled <= six && six - rescaled < scale * 3);
+            
+            let amount = U256::from(micros);
+            let relative = (token(6).to_f64(amount) - token(18).to_f64(wad)).abs() / token(18).to_f64(wad).max(1e-18);
+            proptest::prop_assert!(relative <= 1e-15);
+        }
     }
 }
-
-
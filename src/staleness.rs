@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::blockchain::{BlockchainClient, RpcProvider};
+use crate::executor::PauseSwitch;
+use crate::health::HealthState;
+use crate::rate_limit::RateLimits;
+use crate::transport::RpcTransport;
+
+/// Reason recorded on the pause switch while data is stale
+const PAUSE_REASON: &str = "stale-data";
+
+/// How long a head probe may take before the RPC counts as unreachable
+const HEAD_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Limits beyond which the bot is considered to be working on stale data
+#[derive(Debug, Clone, Copy)]
+pub struct StalenessThresholds {
+    /// Blocks the primary RPC may trail the highest head seen on any RPC
+    pub max_block_lag: u64,
+    /// Milliseconds since the last mempool message
+    pub max_feed_lag_ms: u64,
+}
+
+/// Head reported by one configured RPC (None when it did not answer)
+#[derive(Debug, Clone)]
+pub struct RpcHead {
+    pub url: String,
+    pub head: Option<u64>,
+}
+
+/// Result of one staleness check
+#[derive(Debug, Clone)]
+pub struct StalenessReport {
+    pub local_block: Option<u64>,
+    pub heads: Vec<RpcHead>,
+    pub block_lag: Option<u64>,
+    pub feed_lag_ms: Option<u64>,
+    /// Human-readable reasons the data is stale; empty when fresh
+    pub reasons: Vec<String>,
+}
+
+impl StalenessReport {
+    /// Compare the primary block and feed lag against every RPC head
+    pub fn evaluate(
+        local_block: Option<u64>,
+        heads: Vec<RpcHead>,
+        feed_lag_ms: Option<u64>,
+        thresholds: StalenessThresholds,
+    ) -> Self {
+        let network_head = heads.iter().filter_map(|h| h.head).chain(local_block).max();
+        let block_lag = local_block.zip(network_head).map(|(local, head)| head.saturating_sub(local));
+
+        let mut reasons = Vec::new();
+        match block_lag {
+            None => reasons.push("primary RPC unreachable".to_string()),
+            Some(lag) if lag > thresholds.max_block_lag => {
+                reasons.push(format!("primary RPC {} blocks behind head", lag));
+            }
+            _ => {}
+        }
+        if let Some(lag) = feed_lag_ms.filter(|lag| *lag > thresholds.max_feed_lag_ms) {
+            reasons.push(format!("no mempool message for {}ms", lag));
+        }
+
+        Self { local_block, heads, block_lag, feed_lag_ms, reasons }
+    }
+
+    pub fn is_stale(&self) -> bool {
+        !self.reasons.is_empty()
+    }
+}
+
+/// Polls every configured RPC and the mempool feed, alerting (and optionally
+/// pausing execution) while the bot is operating on stale data
+pub struct StalenessMonitor {
+    blockchain: Arc<BlockchainClient>,
+    peers: Vec<(String, RpcProvider)>,
+    health: Arc<HealthState>,
+    thresholds: StalenessThresholds,
+    pause: Option<PauseSwitch>,
+}
+
+impl StalenessMonitor {
+    /// `peer_urls` are additional RPCs used only to establish the network head
+    pub fn new(
+        blockchain: Arc<BlockchainClient>,
+        peer_urls: &[String],
+        health: Arc<HealthState>,
+        thresholds: StalenessThresholds,
+    ) -> Result<Self> {
+        let peers = peer_urls
+            .iter()
+            .map(|url| {
+                let provider = Provider::new(
+                    RpcTransport::http(url).with_context(|| format!("Invalid monitor RPC URL {}", url))?,
+                );
+                Ok((url.clone(), provider))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { blockchain, peers, health, thresholds, pause: None })
+    }
+
+    /// Keep head probes within each peer's request-rate limit
+    pub fn with_rate_limits(mut self, limits: &RateLimits) -> Self {
+        for (url, provider) in &mut self.peers {
+            if let Some(bucket) = limits.bucket(url) {
+                *provider = Provider::new(provider.as_ref().clone().with_rate_limit(bucket));
+            }
+        }
+        self
+    }
+    
+    /// Suspend submissions on `pause` while data is stale
+    pub fn with_pause_switch(mut self, pause: PauseSwitch) -> Self {
+        self.pause = Some(pause);
+        self
+    }
+
+    pub async fn check(&self) -> StalenessReport {
+        let local_block = tokio::time::timeout(HEAD_PROBE_TIMEOUT, self.blockchain.get_block_number())
+            .await
+            .ok()
+            .and_then(|res| res.ok());
+
+        let probes = self.peers.iter().map(|(url, provider)| async move {
+            let head = tokio::time::timeout(HEAD_PROBE_TIMEOUT, provider.get_block_number())
+                .await
+                .ok()
+                .and_then(|res| res.ok())
+                .map(|block| block.as_u64());
+            RpcHead { url: url.clone(), head }
+        });
+        let heads = futures::future::join_all(probes).await;
+
+        StalenessReport::evaluate(local_block, heads, self.health.feed_lag_ms(), self.thresholds)
+    }
+
+    /// Check every `interval` until the task is dropped, logging on each transition
+    pub async fn run(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        let mut was_stale = false;
+
+        loop {
+            ticker.tick().await;
+            let report = self.check().await;
+            let stale = report.is_stale();
+            self.health.set_stale(stale);
+
+            if stale && !was_stale {
+                warn!("[ALERT] Operating on stale data: {}", report.reasons.join("; "));
+                for head in report.heads.iter().filter(|h| h.head.is_none()) {
+                    warn!("Monitor RPC {} did not report a head", head.url);
+                }
+                if let Some(pause) = &self.pause {
+                    pause.pause(PAUSE_REASON);
+                    warn!("Execution paused until data is fresh");
+                }
+            } else if !stale && was_stale {
+                info!("Data fresh again (block lag {:?}, feed lag {:?}ms)", report.block_lag, report.feed_lag_ms);
+                if let Some(pause) = &self.pause {
+                    pause.resume(PAUSE_REASON);
+                }
+            }
+            was_stale = stale;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: StalenessThresholds = StalenessThresholds { max_block_lag: 2, max_feed_lag_ms: 1_000 };
+
+    fn head(block: Option<u64>) -> RpcHead {
+        RpcHead { url: "http://peer".to_string(), head: block }
+    }
+
+    #[test]
+    fn test_block_and_feed_lag_detection() {
+        let fresh = StalenessReport::evaluate(Some(100), vec![head(Some(102)), head(None)], Some(200), THRESHOLDS);
+        assert!(!fresh.is_stale());
+        assert_eq!(fresh.block_lag, Some(2));
+
+        let behind = StalenessReport::evaluate(Some(100), vec![head(Some(105))], None, THRESHOLDS);
+        assert_eq!(behind.block_lag, Some(5));
+        assert!(behind.is_stale());
+
+        let quiet_feed = StalenessReport::evaluate(Some(100), vec![], Some(5_000), THRESHOLDS);
+        assert_eq!(quiet_feed.reasons, vec!["no mempool message for 5000ms".to_string()]);
+
+        assert!(StalenessReport::evaluate(None, vec![head(Some(100))], None, THRESHOLDS).is_stale());
+    }
+}
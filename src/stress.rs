@@ -0,0 +1,346 @@
+use anyhow::{Context, Result};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::info;
+
+use crate::population::UserPopulation;
+use crate::protocol::{AssetRiskParams, BPS, SIMPLE_LENDING_LIQUIDATION_THRESHOLD as LIQUIDATION_THRESHOLD};
+use crate::run_metadata::RunMetadata;
+use crate::simulator::FALLBACK_LIQUIDATION_GAS;
+use crate::units::TokenAmount;
+use crate::valuation::SaleModel;
+
+/// Price move applied to one asset over a block range
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceShock {
+    pub asset: String,
+    /// Total relative move by the end of the shock (-0.30 = -30%)
+    pub total_change: f64,
+    pub start_block: u64,
+    pub duration_blocks: u64,
+}
+
+impl PriceShock {
+    /// Cumulative price multiplier at `block`, compounding evenly across the duration
+    fn multiplier_at(&self, block: u64) -> f64 {
+        if block < self.start_block {
+            return 1.0;
+        }
+        let elapsed = (block - self.start_block).min(self.duration_blocks) as f64;
+        let progress = if self.duration_blocks == 0 { 1.0 } else { elapsed / self.duration_blocks as f64 };
+        (1.0 + self.total_change).max(0.0).powf(progress)
+    }
+}
+
+/// Base fee at one block of a scenario's gas trajectory
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct BaseFeePoint {
+    pub block: u64,
+    pub gwei: f64,
+}
+
+/// Set of correlated price shocks replayed block by block
+#[derive(Debug, Clone, Deserialize)]
+pub struct StressScenario {
+    pub name: String,
+    pub blocks: u64,
+    pub initial_prices: HashMap<String, f64>,
+    pub shocks: Vec<PriceShock>,
+    /// Assets that follow another asset's shocks with a beta: (follower, leader, beta)
+    #[serde(default)]
+    pub correlations: Vec<(String, String, f64)>,
+    /// Base-fee trajectory, interpolated linearly between points; empty keeps
+    /// the caller's flat gas cost
+    #[serde(default)]
+    pub base_fee_gwei: Vec<BaseFeePoint>,
+}
+
+impl StressScenario {
+    fn base_prices() -> HashMap<String, f64> {
+        HashMap::from([("ETH".to_string(), 2000.0), ("USDC".to_string(), 1.0)])
+    }
+
+    /// ETH falls 30% over 100 blocks
+    pub fn eth_crash() -> Self {
+        Self {
+            name: "eth_crash_30pct".to_string(),
+            blocks: 100,
+            initial_prices: Self::base_prices(),
+            shocks: vec![PriceShock { asset: "ETH".to_string(), total_change: -0.30, start_block: 0, duration_blocks: 100 }],
+            correlations: Vec::new(),
+            base_fee_gwei: Vec::new(),
+        }
+    }
+
+    /// The ETH crash with the base fee spiking from 30 to 300 gwei as it sells off
+    pub fn eth_crash_gas_spike() -> Self {
+        Self {
+            name: "eth_crash_gas_spike".to_string(),
+            base_fee_gwei: vec![
+                BaseFeePoint { block: 0, gwei: 30.0 },
+                BaseFeePoint { block: 40, gwei: 300.0 },
+                BaseFeePoint { block: 100, gwei: 60.0 },
+            ],
+            ..Self::eth_crash()
+        }
+    }
+
+    /// USDC depegs to $0.88 while ETH sells off with it (beta 0.8 to the depeg)
+    pub fn usdc_depeg() -> Self {
+        Self {
+            name: "usdc_depeg".to_string(),
+            blocks: 60,
+            initial_prices: Self::base_prices(),
+            shocks: vec![PriceShock { asset: "USDC".to_string(), total_change: -0.12, start_block: 10, duration_blocks: 20 }],
+            correlations: vec![("ETH".to_string(), "USDC".to_string(), 0.8)],
+            base_fee_gwei: Vec::new(),
+        }
+    }
+
+    /// Built-in scenarios run by the backtesting suite
+    pub fn builtin() -> Vec<Self> {
+        vec![Self::eth_crash(), Self::eth_crash_gas_spike(), Self::usdc_depeg()]
+    }
+
+    /// Load a scenario from a JSON file shaped like the struct
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read scenario {}", path.display()))?;
+        let scenario: Self = serde_json::from_str(&raw).with_context(|| format!("Invalid scenario {}", path.display()))?;
+        if scenario.base_fee_gwei.windows(2).any(|pair| pair[1].block <= pair[0].block) {
+            anyhow::bail!("Scenario {} base fee points must have increasing blocks", path.display());
+        }
+        Ok(scenario)
+    }
+
+    /// Base fee at `block`, held flat before the first and after the last point
+    pub fn base_fee_gwei_at(&self, block: u64) -> Option<f64> {
+        let first = self.base_fee_gwei.first()?;
+        if block <= first.block {
+            return Some(first.gwei);
+        }
+        let next = self.base_fee_gwei.iter().position(|point| point.block > block);
+        Some(match next {
+            Some(i) => {
+                let (from, to) = (self.base_fee_gwei[i - 1], self.base_fee_gwei[i]);
+                let progress = (block - from.block) as f64 / (to.block - from.block) as f64;
+                from.gwei + (to.gwei - from.gwei) * progress
+            }
+            None => self.base_fee_gwei.last()?.gwei,
+        })
+    }
+
+    /// Price of every asset at `block`, with correlated followers applied
+    pub fn prices_at(&self, block: u64) -> HashMap<String, f64> {
+        let mut multipliers: HashMap<&str, f64> = HashMap::new();
+        for shock in &self.shocks {
+            *multipliers.entry(shock.asset.as_str()).or_insert(1.0) *= shock.multiplier_at(block);
+        }
+        for (follower, leader, beta) in &self.correlations {
+            let leader_move = multipliers.get(leader.as_str()).copied().unwrap_or(1.0) - 1.0;
+            *multipliers.entry(follower.as_str()).or_insert(1.0) *= (1.0 + beta * leader_move).max(0.0);
+        }
+
+        self.initial_prices
+            .iter()
+            .map(|(asset, price)| (asset.clone(), price * multipliers.get(asset.as_str()).copied().unwrap_or(1.0)))
+            .collect()
+    }
+}
+
+/// Multi-asset position valued during a stress run
+#[derive(Debug, Clone)]
+pub struct StressPosition {
+    pub user: Address,
+    /// Collateral amounts by asset, in whole units
+    pub collateral: HashMap<String, f64>,
+    /// Debt amounts by asset, in whole units
+    pub debt: HashMap<String, f64>,
+}
+
+impl StressPosition {
+    fn value(amounts: &HashMap<String, f64>, prices: &HashMap<String, f64>) -> f64 {
+        amounts.iter().map(|(asset, amount)| amount * prices.get(asset).copied().unwrap_or(0.0)).sum()
+    }
+
+    /// Health factor (1.0 = liquidation boundary) at the given prices
+    pub fn health_factor(&self, prices: &HashMap<String, f64>) -> f64 {
+        let debt = Self::value(&self.debt, prices);
+        if debt <= 0.0 {
+            return f64::INFINITY;
+        }
+        Self::value(&self.collateral, prices) / LIQUIDATION_THRESHOLD / debt
+    }
+
+    /// Snapshot the synthetic population as ETH-collateral / USDC-debt positions
+    pub fn from_population(population: &UserPopulation) -> Vec<Self> {
+        population
+            .users()
+            .iter()
+            .filter_map(|user| {
+                let (collateral, debt, _) = population.position(*user);
+                (!debt.is_zero()).then(|| Self {
+                    user: *user,
+                    collateral: HashMap::from([("ETH".to_string(), TokenAmount::wad(collateral).to_tokens())]),
+                    debt: HashMap::from([("USDC".to_string(), TokenAmount::wad(debt).to_tokens())]),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Per-block liquidation activity during a stress run
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockStress {
+    pub block: u64,
+    pub prices: HashMap<String, f64>,
+    pub newly_liquidatable: usize,
+    pub capturable_profit_usd: f64,
+    pub gas_cost_usd: f64,
+}
+
+/// Outcome of replaying one scenario against a set of positions
+#[derive(Debug, Clone, Serialize)]
+pub struct StressReport {
+    pub scenario: String,
+    pub positions: usize,
+    pub liquidatable_at_start: usize,
+    pub liquidatable_by_end: usize,
+    pub debt_at_risk_usd: f64,
+    pub capturable_profit_usd: f64,
+    pub blocks: Vec<BlockStress>,
+}
+
+impl StressReport {
+    pub fn print(&self) {
+        info!("=== Stress Scenario: {} ===", self.scenario);
+        info!("Positions: {}", self.positions);
+        info!("Liquidatable at start: {}", self.liquidatable_at_start);
+        info!("Liquidatable by end: {}", self.liquidatable_by_end);
+        info!("Debt at risk: ${:.2}", self.debt_at_risk_usd);
+        info!("Capturable profit: ${:.2}", self.capturable_profit_usd);
+    }
+
+    pub fn export_to_json(&self, filename: &str, run: &RunMetadata) -> Result<()> {
+        run.write_json(filename, self)
+    }
+}
+
+/// Replay `scenario` block by block; each position is liquidated once, at the
+/// first block it crosses HF 1.0, for `params.close_factor_bps` of its debt,
+/// and the seized collateral is sold through `sale` at that block's prices.
+/// Gas costs `gas_cost_usd` unless the scenario has a base-fee trajectory
+pub fn run_scenario(
+    scenario: &StressScenario,
+    positions: &[StressPosition],
+    params: AssetRiskParams,
+    gas_cost_usd: f64,
+    sale: SaleModel,
+) -> StressReport {
+    let mut liquidated = vec![false; positions.len()];
+    let mut blocks = Vec::with_capacity(scenario.blocks as usize + 1);
+    let mut debt_at_risk_usd = 0.0;
+    let mut capturable_profit_usd = 0.0;
+    let close_factor = params.close_factor_bps as f64 / BPS as f64;
+    let bonus = params.liquidation_bonus_bps as f64 / BPS as f64;
+
+    for block in 0..=scenario.blocks {
+        let prices = scenario.prices_at(block);
+        let gas_cost_usd = scenario.base_fee_gwei_at(block).map_or(gas_cost_usd, |gwei| {
+            FALLBACK_LIQUIDATION_GAS as f64 * gwei / 1e9 * prices.get("ETH").copied().unwrap_or(0.0)
+        });
+        let mut newly_liquidatable = 0;
+        let mut block_profit = 0.0;
+
+        for (position, done) in positions.iter().zip(liquidated.iter_mut()) {
+            if *done || position.health_factor(&prices) >= 1.0 {
+                continue;
+            }
+            *done = true;
+            newly_liquidatable += 1;
+
+            let debt_value = StressPosition::value(&position.debt, &prices);
+            let collateral_value = StressPosition::value(&position.collateral, &prices);
+            let repaid = debt_value * close_factor;
+            let seized = (repaid * (1.0 + bonus)).min(collateral_value);
+
+            debt_at_risk_usd += debt_value;
+            block_profit += (sale.proceeds_usd(seized) - repaid - gas_cost_usd).max(0.0);
+        }
+
+        capturable_profit_usd += block_profit;
+        blocks.push(BlockStress { block, prices, newly_liquidatable, capturable_profit_usd: block_profit, gas_cost_usd });
+    }
+
+    StressReport {
+        scenario: scenario.name.clone(),
+        positions: positions.len(),
+        liquidatable_at_start: blocks.first().map(|b| b.newly_liquidatable).unwrap_or(0),
+        liquidatable_by_end: liquidated.iter().filter(|l| **l).count(),
+        debt_at_risk_usd,
+        capturable_profit_usd,
+        blocks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(eth: f64, usdc_debt: f64) -> StressPosition {
+        StressPosition {
+            user: Address::random(),
+            collateral: HashMap::from([("ETH".to_string(), eth)]),
+            debt: HashMap::from([("USDC".to_string(), usdc_debt)]),
+        }
+    }
+
+    #[test]
+    fn test_eth_crash_liquidates_thin_positions() {
+        // 1 ETH at $2000 backs $1333 at HF 1.0: $1200 debt is HF 1.11, $600 is HF 2.2
+        let positions = vec![position(1.0, 1200.0), position(1.0, 600.0)];
+        let report = run_scenario(&StressScenario::eth_crash(), &positions, AssetRiskParams::default(), 5.0, SaleModel::ORACLE);
+
+        assert_eq!(report.liquidatable_at_start, 0);
+        assert_eq!(report.liquidatable_by_end, 1);
+        assert!(report.capturable_profit_usd > 0.0);
+
+        // Selling into a shallow pool eats into the bonus
+        let shallow = SaleModel { fee_bps: 30, depth_usd: 20_000.0 };
+        let sold = run_scenario(&StressScenario::eth_crash(), &positions, AssetRiskParams::default(), 5.0, shallow);
+        assert!(sold.capturable_profit_usd < report.capturable_profit_usd);
+
+        let final_eth = report.blocks.last().unwrap().prices["ETH"];
+        assert!((final_eth - 1400.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_correlated_depeg_moves_eth() {
+        let scenario = StressScenario::usdc_depeg();
+        let end = scenario.prices_at(scenario.blocks);
+
+        assert!((end["USDC"] - 0.88).abs() < 1e-9);
+        assert!((end["ETH"] - 2000.0 * (1.0 - 0.8 * 0.12)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_gas_spike_eats_crash_profit() {
+        let spike = StressScenario::eth_crash_gas_spike();
+        assert_eq!(spike.base_fee_gwei_at(0), Some(30.0));
+        assert_eq!(spike.base_fee_gwei_at(20), Some(165.0));
+        assert_eq!(spike.base_fee_gwei_at(70), Some(180.0));
+        assert_eq!(spike.base_fee_gwei_at(500), Some(60.0));
+        assert_eq!(StressScenario::eth_crash().base_fee_gwei_at(20), None);
+
+        // 300k gas at 30 gwei and $2000 ETH is $18, what the flat run assumes
+        let positions: Vec<_> = (0..20).map(|i| position(1.0, 1000.0 + 15.0 * i as f64)).collect();
+        let flat = run_scenario(&StressScenario::eth_crash(), &positions, AssetRiskParams::default(), 18.0, SaleModel::ORACLE);
+        let spiked = run_scenario(&spike, &positions, AssetRiskParams::default(), 18.0, SaleModel::ORACLE);
+
+        assert_eq!(spiked.liquidatable_by_end, flat.liquidatable_by_end);
+        assert!((spiked.blocks[0].gas_cost_usd - 18.0).abs() < 1e-9);
+        assert!(spiked.capturable_profit_usd < flat.capturable_profit_usd);
+    }
+}
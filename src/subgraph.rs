@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use ethers::types::Address;
+use serde_json::{json, Value};
+use tracing::{debug, info};
+
+/// A borrower listed by a subgraph, with the health factor it last indexed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubgraphBorrower {
+    pub user: Address,
+    pub health_factor: f64,
+}
+
+/// Seeds the detector with a protocol's active borrowers from its subgraph,
+/// where replaying events from genesis would take too long
+pub struct SubgraphBootstrap {
+    client: reqwest::Client,
+    url: String,
+    entity: String,
+    health_factor_field: String,
+    page_size: usize,
+}
+
+impl SubgraphBootstrap {
+    /// Page through `users` entities with a non-null `healthFactor`
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            entity: "users".to_string(),
+            health_factor_field: "healthFactor".to_string(),
+            page_size: 1000,
+        }
+    }
+
+    /// Entity collection borrowers are listed in and the field holding their
+    /// health factor (as a decimal, 1.0 = liquidation line)
+    pub fn with_entity(mut self, entity: &str, health_factor_field: &str) -> Self {
+        self.entity = entity.to_string();
+        self.health_factor_field = health_factor_field.to_string();
+        self
+    }
+
+    /// Entities per request; hosted graph nodes cap `first` at 1000
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Query for the page after `cursor`; ordering by id lets each page resume
+    /// where the last ended, which `skip` can't past a few thousand entities
+    fn query(&self, cursor: &str) -> Value {
+        let hf = &self.health_factor_field;
+        json!({
+            "query": format!(
+                "query Borrowers($cursor: String!) {{ {}(first: {}, orderBy: id, orderDirection: asc, where: {{ id_gt: $cursor, {}_not: null }}) {{ id {} }} }}",
+                self.entity, self.page_size, hf, hf,
+            ),
+            "variables": { "cursor": cursor },
+        })
+    }
+
+    /// Every borrower the subgraph lists
+    pub async fn fetch_borrowers(&self) -> Result<Vec<SubgraphBorrower>> {
+        let mut borrowers = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let response: Value = self
+                .client
+                .post(&self.url)
+                .json(&self.query(&cursor))
+                .send()
+                .await
+                .with_context(|| format!("Subgraph request to {} failed", self.url))?
+                .error_for_status()?
+                .json()
+                .await
+                .context("Invalid subgraph response")?;
+            let (page, last_id) = parse_page(&response, &self.entity, &self.health_factor_field)?;
+            debug!("Subgraph page of {} borrowers after {:?}", page.len(), cursor);
+            let done = page.len() < self.page_size;
+            borrowers.extend(page);
+            match last_id {
+                Some(id) if !done => cursor = id,
+                _ => break,
+            }
+        }
+        info!("Subgraph listed {} borrowers", borrowers.len());
+        Ok(borrowers)
+    }
+}
+
+/// Borrowers in one page of results and the last entity id, the next cursor
+fn parse_page(response: &Value, entity: &str, health_factor_field: &str) -> Result<(Vec<SubgraphBorrower>, Option<String>)> {
+    if let Some(errors) = response.get("errors") {
+        anyhow::bail!("Subgraph query failed: {}", errors);
+    }
+    let rows = response["data"][entity]
+        .as_array()
+        .with_context(|| format!("Subgraph response has no {}", entity))?;
+
+    let mut borrowers = Vec::with_capacity(rows.len());
+    let mut last_id = None;
+    for row in rows {
+        let id = row["id"].as_str().context("Subgraph entity without an id")?;
+        // BigDecimal fields arrive as strings
+        let health_factor = match &row[health_factor_field] {
+            Value::String(raw) => raw.parse().ok(),
+            value => value.as_f64(),
+        }
+        .with_context(|| format!("Invalid {} of {}", health_factor_field, id))?;
+        // Some schemas suffix account ids (e.g. `<address>-<market>`)
+        let user = id
+            .get(..42)
+            .and_then(|address| address.parse().ok())
+            .with_context(|| format!("Subgraph id {} is not an address", id))?;
+        borrowers.push(SubgraphBorrower { user, health_factor });
+        last_id = Some(id.to_string());
+    }
+    Ok((borrowers, last_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page() {
+        let response = json!({ "data": { "users": [
+            { "id": "0x0000000000000000000000000000000000000001", "healthFactor": "1.02" },
+            { "id": "0x0000000000000000000000000000000000000002-0xmarket", "healthFactor": 0.97 },
+        ]}});
+        let (borrowers, last_id) = parse_page(&response, "users", "healthFactor").unwrap();
+        assert_eq!(borrowers, vec![
+            SubgraphBorrower { user: Address::from_low_u64_be(1), health_factor: 1.02 },
+            SubgraphBorrower { user: Address::from_low_u64_be(2), health_factor: 0.97 },
+        ]);
+        assert_eq!(last_id.as_deref(), Some("0x0000000000000000000000000000000000000002-0xmarket"));
+
+        assert!(parse_page(&json!({ "errors": [{ "message": "bad" }] }), "users", "healthFactor").is_err());
+        let bad_id = json!({ "data": { "users": [{ "id": "alice", "healthFactor": "1" }] }});
+        assert!(parse_page(&bad_id, "users", "healthFactor").is_err());
+        // Empty page: nothing more to read
+        assert_eq!(parse_page(&json!({ "data": { "users": [] }}), "users", "healthFactor").unwrap().1, None);
+    }
+}
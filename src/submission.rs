@@ -0,0 +1,329 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use ethers::types::{transaction::eip2718::TypedTransaction, Bytes};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info};
+
+use crate::blockchain::BlockchainClient;
+use crate::builders::BuilderRelay;
+use crate::executor::{ExecutionError, Result};
+use crate::inclusion::{InclusionOutcome, InclusionStats};
+use crate::presign::PresignedLiquidation;
+
+/// Flashbots Protect's fast mode, which shares with every builder it works with
+pub const FLASHBOTS_PROTECT_URL: &str = "https://rpc.flashbots.net/fast";
+/// Per-request timeout for a private RPC
+const SEND_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// How a signed liquidation reaches a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteKind {
+    /// Logged and never sent, when no route is configured (POC)
+    LogOnly,
+    /// `eth_sendRawTransaction` to our own node, into the public mempool
+    Public,
+    /// `eth_sendRawTransaction` to Flashbots Protect, kept out of the public mempool
+    Protect,
+    /// `eth_sendBundle` to every configured builder, one bundle per target block
+    Bundle,
+}
+
+impl RouteKind {
+    pub fn parse(raw: &str) -> anyhow::Result<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "public" => Ok(Self::Public),
+            "protect" => Ok(Self::Protect),
+            "bundle" => Ok(Self::Bundle),
+            other => anyhow::bail!("Unknown submission route: {}", other),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LogOnly => "log-only",
+            Self::Public => "public",
+            Self::Protect => "protect",
+            Self::Bundle => "bundle",
+        }
+    }
+
+    /// Whether a submission on this route reaches the chain (and so produces a receipt)
+    pub fn is_onchain(self) -> bool {
+        self != Self::LogOnly
+    }
+}
+
+impl fmt::Display for RouteKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Submissions on one route: how long the route took to accept them and how
+/// their races ended
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteStats {
+    pub submitted: usize,
+    /// Summed time from the signed transaction to the route accepting it, resends included
+    pub total_submission_us: f64,
+    pub inclusion: InclusionStats,
+    /// Summed blocks from send to inclusion of won submissions (1 = the first block targeted)
+    pub total_blocks_to_inclusion: u64,
+}
+
+impl RouteStats {
+    /// Count a submission aimed at `target_block`, and its race once it resolved
+    pub fn record(&mut self, submission_us: Option<f64>, target_block: Option<u64>, inclusion: Option<&InclusionOutcome>) {
+        self.submitted += 1;
+        self.total_submission_us += submission_us.unwrap_or_default();
+        if let Some(outcome) = inclusion {
+            self.inclusion.record(outcome);
+            if let (InclusionOutcome::Won { block, .. }, Some(target_block)) = (outcome, target_block) {
+                self.total_blocks_to_inclusion += (block + 1).saturating_sub(target_block);
+            }
+        }
+    }
+
+    pub fn mean_submission_us(&self) -> f64 {
+        if self.submitted == 0 { 0.0 } else { self.total_submission_us / self.submitted as f64 }
+    }
+
+    /// Share of resolved submissions that landed and succeeded
+    pub fn inclusion_rate(&self) -> f64 {
+        self.inclusion.win_rate()
+    }
+
+    pub fn mean_blocks_to_inclusion(&self) -> Option<f64> {
+        (self.inclusion.won > 0).then(|| self.total_blocks_to_inclusion as f64 / self.inclusion.won as f64)
+    }
+}
+
+/// One way of handing a signed liquidation to the network
+#[async_trait]
+pub trait SubmissionRoute: Send + Sync {
+    fn kind(&self) -> RouteKind;
+
+    /// Send `signed` once, aimed at `target_blocks` (earliest first); resends
+    /// of the same transaction go through here again
+    async fn send(&self, signed: &PresignedLiquidation, target_blocks: &[u64]) -> Result<()>;
+}
+
+/// Picks the route for each opportunity from those configured, by how exposed
+/// it is to competing liquidators
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoutePolicy {
+    /// Expected profit above which a liquidation stays out of the public mempool
+    pub public_max_profit_usd: f64,
+}
+
+impl Default for RoutePolicy {
+    fn default() -> Self {
+        Self { public_max_profit_usd: 25.0 }
+    }
+}
+
+impl RoutePolicy {
+    /// Routes to use for an opportunity, most suitable first
+    ///
+    /// A backrun must land right behind its trigger, which only a bundle
+    /// guarantees. A liquidation worth more than `public_max_profit_usd` would
+    /// be copied and outbid from the public mempool, so it goes private.
+    /// Anything smaller is cheapest and most widely included in public.
+    pub fn preference(&self, backrun: bool, expected_profit_usd: f64) -> [RouteKind; 3] {
+        if backrun {
+            [RouteKind::Bundle, RouteKind::Protect, RouteKind::Public]
+        } else if expected_profit_usd > self.public_max_profit_usd {
+            [RouteKind::Protect, RouteKind::Bundle, RouteKind::Public]
+        } else {
+            [RouteKind::Public, RouteKind::Protect, RouteKind::Bundle]
+        }
+    }
+}
+
+/// Logs the transaction instead of sending it; in production it would go through
+/// one of the other routes
+pub struct LogOnlyRoute;
+
+#[async_trait]
+impl SubmissionRoute for LogOnlyRoute {
+    fn kind(&self) -> RouteKind {
+        RouteKind::LogOnly
+    }
+
+    async fn send(&self, signed: &PresignedLiquidation, _target_blocks: &[u64]) -> Result<()> {
+        let tx = &signed.tx;
+        info!("Transaction constructed:");
+        info!("   To: {:?}", tx.to());
+        info!("   Value: {:?}", tx.value());
+        info!("   Gas limit: {:?}", tx.gas());
+        match tx {
+            TypedTransaction::Eip1559(tx) => {
+                info!("   Max fee per gas: {:?}", tx.max_fee_per_gas);
+                info!("   Max priority fee: {:?}", tx.max_priority_fee_per_gas);
+            }
+            tx => info!("   Gas price: {:?}", tx.gas_price()),
+        }
+        info!("   Nonce: {:?}", tx.nonce());
+        Ok(())
+    }
+}
+
+/// `eth_sendRawTransaction` through the node we read from
+pub struct PublicRoute {
+    blockchain: Arc<BlockchainClient>,
+}
+
+impl PublicRoute {
+    pub fn new(blockchain: Arc<BlockchainClient>) -> Self {
+        Self { blockchain }
+    }
+}
+
+#[async_trait]
+impl SubmissionRoute for PublicRoute {
+    fn kind(&self) -> RouteKind {
+        RouteKind::Public
+    }
+
+    async fn send(&self, signed: &PresignedLiquidation, _target_blocks: &[u64]) -> Result<()> {
+        match self.blockchain.send_raw_transaction(signed.raw.clone()).await {
+            Ok(_) => Ok(()),
+            // A resend of a transaction the node still holds
+            Err(e) if already_known(&e.to_string()) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// `eth_sendRawTransaction` to Flashbots Protect (or another private RPC with
+/// the same interface)
+///
+/// The transaction skips the public mempool and is dropped rather than mined
+/// if it would revert, but can't be ordered behind a trigger.
+pub struct ProtectRoute {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl ProtectRoute {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { client: reqwest::Client::new(), url: url.into() }
+    }
+
+    async fn send_raw(&self, raw: &Bytes) -> anyhow::Result<()> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_sendRawTransaction",
+            "params": [raw],
+        });
+        let reply: Value = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .timeout(SEND_TIMEOUT)
+            .send()
+            .await?
+            .json()
+            .await
+            .context("Invalid response")?;
+        match reply.get("error") {
+            Some(error) if !already_known(&error.to_string()) => anyhow::bail!("{}", error),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl SubmissionRoute for ProtectRoute {
+    fn kind(&self) -> RouteKind {
+        RouteKind::Protect
+    }
+
+    async fn send(&self, signed: &PresignedLiquidation, _target_blocks: &[u64]) -> Result<()> {
+        self.send_raw(&signed.raw).await.map_err(|e| ExecutionError::Refused {
+            route: RouteKind::Protect,
+            reason: format!("{:#}", e),
+        })?;
+        debug!("Sent {:?} to {}", signed.tx_hash, self.url);
+        Ok(())
+    }
+}
+
+/// One `eth_sendBundle` per target block to every configured builder, the
+/// rest cancelled once one is included
+pub struct BundleRoute {
+    relay: Arc<BuilderRelay>,
+    blockchain: Arc<BlockchainClient>,
+}
+
+impl BundleRoute {
+    pub fn new(relay: Arc<BuilderRelay>, blockchain: Arc<BlockchainClient>) -> Self {
+        Self { relay, blockchain }
+    }
+}
+
+#[async_trait]
+impl SubmissionRoute for BundleRoute {
+    fn kind(&self) -> RouteKind {
+        RouteKind::Bundle
+    }
+
+    async fn send(&self, signed: &PresignedLiquidation, target_blocks: &[u64]) -> Result<()> {
+        let bundles = self.relay.submit_bundles(std::slice::from_ref(&signed.raw), target_blocks).await;
+        if !bundles.iter().any(|bundle| bundle.accepted()) {
+            return Err(ExecutionError::Rejected { blocks: target_blocks.to_vec() });
+        }
+        let sent_unix_ns = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or_default();
+        tokio::spawn(self.relay.clone().watch_inclusion(self.blockchain.clone(), signed.tx_hash, bundles, sent_unix_ns));
+        Ok(())
+    }
+}
+
+/// Nodes and relays reject a transaction they already hold with "already known"
+fn already_known(error: &str) -> bool {
+    error.to_ascii_lowercase().contains("already known")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_preference_by_competition_risk() {
+        assert_eq!(RouteKind::parse(" Protect ").unwrap(), RouteKind::Protect);
+        assert!(RouteKind::parse("mempool").is_err());
+        assert!(!RouteKind::LogOnly.is_onchain());
+
+        let policy = RoutePolicy { public_max_profit_usd: 25.0 };
+        // Backruns are bundled whatever they are worth
+        assert_eq!(policy.preference(true, 5.0)[0], RouteKind::Bundle);
+        assert_eq!(policy.preference(false, 500.0)[0], RouteKind::Protect);
+        assert_eq!(policy.preference(false, 5.0), [RouteKind::Public, RouteKind::Protect, RouteKind::Bundle]);
+
+        assert!(already_known("{\"code\":-32000,\"message\":\"already known\"}"));
+        assert!(!already_known("nonce too low"));
+    }
+
+    #[test]
+    fn test_route_stats() {
+        let mut stats = RouteStats::default();
+        stats.record(Some(400.0), Some(101), Some(&InclusionOutcome::Won { block: 101, position: 0, timestamp: 0 }));
+        stats.record(Some(800.0), Some(101), Some(&InclusionOutcome::Won { block: 103, position: 2, timestamp: 0 }));
+        stats.record(Some(600.0), Some(102), Some(&InclusionOutcome::Dropped));
+        // Sent but not yet resolved
+        stats.record(Some(200.0), Some(104), None);
+
+        assert_eq!(stats.submitted, 4);
+        assert_eq!(stats.mean_submission_us(), 500.0);
+        assert_eq!(stats.inclusion.tracked, 3);
+        assert!((stats.inclusion_rate() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.mean_blocks_to_inclusion(), Some(2.0));
+        assert_eq!(RouteStats::default().mean_blocks_to_inclusion(), None);
+    }
+}
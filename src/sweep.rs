@@ -0,0 +1,159 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use tracing::info;
+
+use crate::metrics::AggregateMetrics;
+use crate::run_metadata::RunMetadata;
+
+/// Strategy parameters one sweep backtest ran with
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SweepParams {
+    /// Minimum worst-case profit to liquidate
+    pub min_profit_usd: f64,
+    /// Share of gross profit bid as priority fee
+    pub bid_share: f64,
+    /// Health factor below which positions are watched
+    pub watch_band: f64,
+}
+
+/// Values tried for each parameter; every combination is backtested
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SweepGrid {
+    pub min_profit_usd: Vec<f64>,
+    pub bid_share: Vec<f64>,
+    pub watch_band: Vec<f64>,
+}
+
+impl SweepGrid {
+    /// Every combination, varying the watch band fastest
+    pub fn combinations(&self) -> Vec<SweepParams> {
+        let mut combinations = Vec::new();
+        for &min_profit_usd in &self.min_profit_usd {
+            for &bid_share in &self.bid_share {
+                for &watch_band in &self.watch_band {
+                    combinations.push(SweepParams { min_profit_usd, bid_share, watch_band });
+                }
+            }
+        }
+        combinations
+    }
+}
+
+/// Profit and latency of one combination
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SweepRow {
+    pub min_profit_usd: f64,
+    pub bid_share: f64,
+    pub watch_band: f64,
+    pub attempts: usize,
+    pub success_rate: f64,
+    /// After the collateral sale and, when racing rivals, the priority fee
+    pub profit_usd: f64,
+    /// Races against rival bots won, of those run
+    pub races_won: usize,
+    pub races: usize,
+    pub p50_end_to_end_us: Option<f64>,
+    pub p99_end_to_end_us: Option<f64>,
+}
+
+impl SweepRow {
+    pub fn new(params: SweepParams, metrics: &AggregateMetrics) -> Self {
+        let (races_won, races) = metrics.competition.as_ref().map_or((0, 0), |c| (c.won, c.races));
+        Self {
+            min_profit_usd: params.min_profit_usd,
+            bid_share: params.bid_share,
+            watch_band: params.watch_band,
+            attempts: metrics.total_attempts,
+            success_rate: metrics.success_rate(),
+            profit_usd: metrics.total_expected_profit_usd,
+            races_won,
+            races,
+            p50_end_to_end_us: metrics.percentile("end_to_end_us", 50.0),
+            p99_end_to_end_us: metrics.percentile("end_to_end_us", 99.0),
+        }
+    }
+}
+
+/// Backtests of the same seeded traffic under every combination of a `SweepGrid`
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepReport {
+    pub transactions_per_run: usize,
+    pub rows: Vec<SweepRow>,
+}
+
+impl SweepReport {
+    /// Most profitable combination
+    pub fn best(&self) -> Option<&SweepRow> {
+        self.rows.iter().max_by(|a, b| a.profit_usd.total_cmp(&b.profit_usd))
+    }
+
+    pub fn print(&self) {
+        info!("=== Parameter Sweep ({} combinations, {} transactions each) ===", self.rows.len(), self.transactions_per_run);
+        info!("{:>10} {:>9} {:>10} {:>8} {:>8} {:>12} {:>9} {:>12} {:>12}", "min_profit", "bid_share", "watch_band", "attempts", "success", "profit_usd", "races_won", "p50_e2e_us", "p99_e2e_us");
+        let latency = |us: Option<f64>| us.map(|us| format!("{:.2}", us)).unwrap_or_else(|| "-".to_string());
+        for row in &self.rows {
+            info!(
+                "{:>10.2} {:>9.2} {:>10.3} {:>8} {:>7.1}% {:>12.2} {:>9} {:>12} {:>12}",
+                row.min_profit_usd,
+                row.bid_share,
+                row.watch_band,
+                row.attempts,
+                row.success_rate * 100.0,
+                row.profit_usd,
+                format!("{}/{}", row.races_won, row.races),
+                latency(row.p50_end_to_end_us),
+                latency(row.p99_end_to_end_us),
+            );
+        }
+        if let Some(best) = self.best() {
+            info!(
+                "Best: min_profit ${:.2}, bid share {:.2}, watch band {:.3} (${:.2})",
+                best.min_profit_usd, best.bid_share, best.watch_band, best.profit_usd
+            );
+        }
+    }
+
+    /// One CSV row per combination, below `run`'s comment lines
+    pub fn export_to_csv(&self, filename: &str, run: &RunMetadata) -> Result<()> {
+        let mut file = File::create(filename)?;
+        file.write_all(run.csv_comment().as_bytes())?;
+        let mut writer = csv::Writer::from_writer(file);
+        for row in &self.rows {
+            writer.serialize(row)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn export_to_json(&self, filename: &str, run: &RunMetadata) -> Result<()> {
+        run.write_json(filename, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_and_table() {
+        let grid = SweepGrid { min_profit_usd: vec![5.0, 25.0], bid_share: vec![0.3], watch_band: vec![1.05, 1.2] };
+        let combinations = grid.combinations();
+        assert_eq!(combinations.len(), 4);
+        assert_eq!(combinations[1], SweepParams { min_profit_usd: 5.0, bid_share: 0.3, watch_band: 1.2 });
+
+        let mut rows: Vec<SweepRow> = combinations.iter().map(|params| SweepRow::new(*params, &AggregateMetrics::new())).collect();
+        rows[2].profit_usd = 40.0;
+        let report = SweepReport { transactions_per_run: 100, rows };
+        assert_eq!(report.best().map(|row| (row.min_profit_usd, row.watch_band)), Some((25.0, 1.05)));
+
+        let path = std::env::temp_dir().join(format!("liquidio_sweep_{}.csv", std::process::id()));
+        report.export_to_csv(path.to_str().unwrap(), &RunMetadata::default()).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let mut lines = csv.lines().filter(|line| !line.starts_with('#'));
+        assert!(lines.next().unwrap().starts_with("min_profit_usd,bid_share,watch_band,attempts"));
+        assert_eq!(lines.count(), 4);
+    }
+}
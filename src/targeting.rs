@@ -0,0 +1,102 @@
+use ethers::types::{Transaction, U256};
+
+/// Most the base fee can rise (or fall) from one block to the next under EIP-1559
+const MAX_BASE_FEE_CHANGE: f64 = 0.125;
+
+/// Next block's base fee and how it has been moving
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BaseFeeTrend {
+    pub next_base_fee: U256,
+    /// Average ratio between consecutive base fees
+    pub growth_per_block: f64,
+}
+
+impl BaseFeeTrend {
+    /// Trend of `eth_feeHistory` base fees (oldest first, ending with the next block's)
+    pub fn from_history(base_fees: &[U256]) -> Option<Self> {
+        let next_base_fee = *base_fees.last()?;
+        let first = base_fees.iter().find(|fee| !fee.is_zero())?;
+        let steps = base_fees.len() - 1 - base_fees.iter().position(|fee| fee == first)?;
+        let growth = if steps == 0 {
+            1.0
+        } else {
+            (next_base_fee.as_u128() as f64 / first.as_u128() as f64).powf(1.0 / steps as f64)
+        };
+        Some(Self {
+            next_base_fee,
+            growth_per_block: growth.clamp(1.0 - MAX_BASE_FEE_CHANGE, 1.0 + MAX_BASE_FEE_CHANGE),
+        })
+    }
+
+    /// Expected base fee `blocks_ahead` blocks after the next one
+    pub fn projected(&self, blocks_ahead: u64) -> f64 {
+        self.next_base_fee.as_u128() as f64 * self.growth_per_block.powi(blocks_ahead as i32)
+    }
+}
+
+/// Picks which upcoming blocks a liquidation bundle is submitted for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetStrategy {
+    /// Most consecutive blocks one liquidation is submitted for
+    pub max_blocks: u64,
+}
+
+impl Default for TargetStrategy {
+    fn default() -> Self {
+        Self { max_blocks: 3 }
+    }
+}
+
+impl TargetStrategy {
+    pub fn new(max_blocks: u64) -> Self {
+        Self { max_blocks: max_blocks.max(1) }
+    }
+
+    /// Blocks after `head` to target with a liquidation paying up to `max_fee_per_gas`
+    ///
+    /// Targeting starts at the first block `trigger` can afford under the
+    /// projected base fee (the liquidation only works once it has landed) and
+    /// ends where our own fee cap would no longer cover the base fee. Empty if
+    /// the two never overlap.
+    pub fn plan(&self, head: u64, trend: &BaseFeeTrend, max_fee_per_gas: U256, trigger: Option<&Transaction>) -> Vec<u64> {
+        let affordable = |blocks_ahead: u64, fee_cap: U256| trend.projected(blocks_ahead) <= fee_cap.as_u128() as f64;
+        let trigger_cap = trigger.and_then(|tx| tx.max_fee_per_gas.or(tx.gas_price));
+
+        let first = match trigger_cap {
+            Some(cap) => match (0..self.max_blocks).find(|ahead| affordable(*ahead, cap)) {
+                Some(ahead) => ahead,
+                None => return Vec::new(),
+            },
+            None => 0,
+        };
+        (first..self.max_blocks)
+            .take_while(|ahead| affordable(*ahead, max_fee_per_gas))
+            .map(|ahead| head + 1 + ahead)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_follows_fees() {
+        let gwei = |n: u64| U256::from(n) * U256::exp10(9);
+        let rising = BaseFeeTrend::from_history(&[gwei(64), gwei(72), gwei(81)]).unwrap();
+        assert!((rising.growth_per_block - 1.125).abs() < 1e-9);
+        let flat = BaseFeeTrend::from_history(&[gwei(10), gwei(10)]).unwrap();
+
+        let strategy = TargetStrategy::new(4);
+        assert_eq!(strategy.plan(100, &flat, gwei(20), None), vec![101, 102, 103, 104]);
+        // 81 -> 91.1 -> 102.5 gwei: our 100 gwei cap covers two blocks
+        assert_eq!(strategy.plan(100, &rising, gwei(100), None), vec![101, 102]);
+
+        // A trigger underpricing the next block pushes the window out
+        let trigger = Transaction { max_fee_per_gas: Some(gwei(9)), ..Default::default() };
+        let falling = BaseFeeTrend::from_history(&[gwei(12), gwei(10)]).unwrap();
+        assert_eq!(strategy.plan(100, &falling, gwei(20), Some(&trigger)), vec![102, 103, 104]);
+        // ... and one that can never land means there is nothing to target
+        assert!(strategy.plan(100, &rising, gwei(200), Some(&trigger)).is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+use anyhow::{Context, Result};
+use ethers::{
+    abi::Abi,
+    contract::ContractFactory,
+    middleware::SignerMiddleware,
+    providers::{Http, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, Transaction, U256},
+    utils::{Anvil, AnvilInstance},
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::blockchain::{BlockchainClient, LendingProtocol, ERC20};
+use crate::executor::{ExecutionOutcome, ExecutionPool, LiquidationExecutor};
+use crate::liquidation_detector::LiquidationDetector;
+use crate::protocol::{AssetRiskParams, ProtocolAdapter};
+use crate::simulator::LiquidationSimulator;
+use crate::wallets::WalletPool;
+
+/// Initial stablecoin supply minted to the deployer (1M tokens)
+const INITIAL_SUPPLY: u64 = 1_000_000;
+/// Stablecoin liquidity handed to the protocol for borrowing (500k tokens)
+const PROTOCOL_LIQUIDITY: u64 = 500_000;
+/// Mirrors SimpleLendingProtocol.LIQUIDATION_THRESHOLD / PRECISION
+const LIQUIDATION_THRESHOLD: u64 = 150;
+const PRECISION: u64 = 100;
+
+type SignerClient = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+/// Client for tests that need no node: its RPC points at a port nothing listens
+/// on, so every call fails fast
+pub async fn offline_client() -> Arc<BlockchainClient> {
+    Arc::new(
+        BlockchainClient::new("http://127.0.0.1:1", None, Address::zero(), Address::zero())
+            .await
+            .expect("offline client"),
+    )
+}
+
+/// Local end-to-end environment: a spawned Anvil node with the mock
+/// lending protocol and stablecoin deployed and funded
+///
+/// Requires `anvil` on PATH and compiled Foundry artifacts (`forge build`).
+pub struct TestEnvironment {
+    pub anvil: AnvilInstance,
+    pub blockchain: Arc<BlockchainClient>,
+    pub protocol_address: Address,
+    pub token_address: Address,
+    deployer: Arc<SignerClient>,
+}
+
+impl TestEnvironment {
+    /// Spawn Anvil, deploy MockERC20 + SimpleLendingProtocol and fund the protocol
+    pub async fn spawn() -> Result<Self> {
+        let anvil = Anvil::new().spawn();
+        let deployer = Arc::new(signer_client(&anvil, 0)?);
+
+        let (token_abi, token_bytecode) = load_artifact("MockERC20")?;
+        let token = ContractFactory::new(token_abi, token_bytecode, deployer.clone())
+            .deploy((
+                "USD Stablecoin".to_string(),
+                "USDC".to_string(),
+                U256::from(INITIAL_SUPPLY) * U256::exp10(18),
+            ))?
+            .send()
+            .await?;
+        let token_address = token.address();
+
+        let (protocol_abi, protocol_bytecode) = load_artifact("SimpleLendingProtocol")?;
+        let protocol = ContractFactory::new(protocol_abi, protocol_bytecode, deployer.clone())
+            .deploy(token_address)?
+            .send()
+            .await?;
+        let protocol_address = protocol.address();
+
+        ERC20::new(token_address, deployer.clone())
+            .transfer(protocol_address, U256::from(PROTOCOL_LIQUIDITY) * U256::exp10(18))
+            .send()
+            .await?
+            .await?;
+
+        info!("Test environment deployed: protocol={:?} token={:?}", protocol_address, token_address);
+
+        let blockchain = Arc::new(
+            BlockchainClient::new(&anvil.endpoint(), None, protocol_address, token_address).await?,
+        );
+
+        Ok(Self {
+            anvil,
+            blockchain,
+            protocol_address,
+            token_address,
+            deployer,
+        })
+    }
+
+    /// Address of the Anvil dev account at `index`
+    pub fn account(&self, index: usize) -> Address {
+        self.anvil.addresses()[index]
+    }
+
+    /// Open a position for dev account `index` with `collateral` wei of ETH,
+    /// borrowing so that the health factor lands at `health_factor` (100 = 1.0)
+    ///
+    /// The protocol rejects borrows below HF 1.0, so liquidatable positions
+    /// are created by opening them healthy and then calling `set_eth_price`.
+    pub async fn open_position(
+        &self,
+        index: usize,
+        collateral: U256,
+        health_factor: u64,
+    ) -> Result<Address> {
+        anyhow::ensure!(health_factor >= PRECISION, "Borrows below HF 1.0 are rejected by the protocol");
+
+        let client = Arc::new(signer_client(&self.anvil, index)?);
+        let protocol = LendingProtocol::new(self.protocol_address, client.clone());
+
+        protocol.deposit().value(collateral).send().await?.await?;
+
+        let price = protocol.eth_price_usd().call().await?;
+        let debt = borrow_for_health_factor(collateral, price, health_factor);
+        protocol.borrow(debt).send().await?.await?;
+
+        Ok(client.address())
+    }
+
+    /// Move the protocol's ETH price (18 decimals) to push positions across the threshold
+    pub async fn set_eth_price(&self, price_usd: u64) -> Result<()> {
+        LendingProtocol::new(self.protocol_address, self.deployer.clone())
+            .set_eth_price(U256::from(price_usd) * U256::exp10(18))
+            .send()
+            .await?
+            .await?;
+        Ok(())
+    }
+
+    /// Protocol transaction from `user` as the detector would see it in the mempool
+    pub fn deposit_transaction(&self, user: Address) -> Transaction {
+        Transaction {
+            from: user,
+            to: Some(self.protocol_address),
+            input: Bytes::from(vec![0xd0, 0xe3, 0x0d, 0xb0]),
+            ..Default::default()
+        }
+    }
+
+    /// Run transactions through detector -> simulator -> executor and collect outcomes
+    pub async fn drive_pipeline(
+        &self,
+        transactions: &[Transaction],
+        min_profit_threshold_usd: f64,
+    ) -> Result<Vec<ExecutionOutcome>> {
+        let detector = LiquidationDetector::new(self.blockchain.clone());
+        let simulator = Arc::new(LiquidationSimulator::new(self.blockchain.clone(), min_profit_threshold_usd));
+        let executor = Arc::new(LiquidationExecutor::new(self.blockchain.clone(), WalletPool::empty(), 100));
+        let pool = ExecutionPool::new(simulator, executor, 4);
+
+        let mut in_flight = Vec::new();
+        for tx in transactions {
+            if let Some(signal) = detector.process_transaction(tx, self.protocol_address).await? {
+                in_flight.push(pool.dispatch(signal).await);
+            }
+        }
+
+        let mut outcomes = Vec::with_capacity(in_flight.len());
+        for handle in in_flight {
+            outcomes.push(handle.await?);
+        }
+        Ok(outcomes)
+    }
+}
+
+/// Adapter with the same risk params for every asset, for simulating without a node
+pub struct FixedRiskParams(pub AssetRiskParams);
+
+#[async_trait::async_trait]
+impl ProtocolAdapter for FixedRiskParams {
+    fn name(&self) -> &str {
+        "fixed"
+    }
+
+    async fn asset_params(&self, _asset: Address) -> Result<AssetRiskParams> {
+        Ok(self.0)
+    }
+}
+
+/// Debt that puts a position at `health_factor` given the protocol's formula
+/// HF = (collateral * price / 1e18) * PRECISION / THRESHOLD * PRECISION / debt
+pub fn borrow_for_health_factor(collateral: U256, eth_price: U256, health_factor: u64) -> U256 {
+    let collateral_value = collateral * eth_price / U256::exp10(18);
+    let max_borrow = collateral_value * U256::from(PRECISION) / U256::from(LIQUIDATION_THRESHOLD);
+    max_borrow * U256::from(PRECISION) / U256::from(health_factor)
+}
+
+fn signer_client(anvil: &AnvilInstance, index: usize) -> Result<SignerClient> {
+    let provider = Provider::<Http>::try_from(anvil.endpoint())?;
+    let wallet: LocalWallet = anvil.keys()[index].clone().into();
+    Ok(SignerMiddleware::new(provider, wallet.with_chain_id(anvil.chain_id())))
+}
+
+fn artifact_path(contract: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("out")
+        .join(format!("{}.sol", contract))
+        .join(format!("{}.json", contract))
+}
+
+/// Load ABI and creation bytecode from a Foundry build artifact
+fn load_artifact(contract: &str) -> Result<(Abi, Bytes)> {
+    let path = artifact_path(contract);
+    let raw = std::fs::read_to_string(&path)
+        .with_context(|| format!("Missing artifact {} (run `forge build`)", path.display()))?;
+    let artifact: serde_json::Value = serde_json::from_str(&raw)?;
+
+    let abi: Abi = serde_json::from_value(artifact["abi"].clone())?;
+    let bytecode = artifact["bytecode"]["object"]
+        .as_str()
+        .context("Artifact has no bytecode")?
+        .parse::<Bytes>()?;
+
+    Ok((abi, bytecode))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::HealthFactorScale;
+    use crate::units::{HealthFactor, TokenAmount};
+
+    #[test]
+    fn test_borrow_for_health_factor() {
+        // 10 ETH at $2000 -> $20k collateral, $13,333 max borrow at HF 1.0
+        let collateral = U256::from(10u64) * U256::exp10(18);
+        let price = U256::from(2000u64) * U256::exp10(18);
+
+        let at_par = borrow_for_health_factor(collateral, price, 100);
+        let at_double = borrow_for_health_factor(collateral, price, 200);
+
+        assert_eq!(at_par / U256::exp10(18), U256::from(13_333u64));
+        assert_eq!(at_double * 2 / U256::exp10(18), U256::from(13_333u64));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs anvil and forge artifacts"]
+    async fn test_liquidatable_position_end_to_end() {
+        let env = TestEnvironment::spawn().await.unwrap();
+        let user = env.open_position(1, U256::exp10(19), 110).await.unwrap();
+
+        // -20% price move drops the position below HF 1.0
+        env.set_eth_price(1600).await.unwrap();
+        assert!(env.blockchain.is_liquidatable(user).await.unwrap());
+
+        let outcomes = env
+            .drive_pipeline(&[env.deposit_transaction(user)], 10.0)
+            .await
+            .unwrap();
+
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].signal.user, user);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs anvil and forge artifacts"]
+    async fn test_snapshot_simulation_leaves_state_untouched() {
+        use crate::liquidation_detector::LiquidationSignal;
+        use crate::metrics::LatencyMetrics;
+        use crate::simulator::SimulationBackend;
+
+        let env = TestEnvironment::spawn().await.unwrap();
+        let user = env.open_position(1, U256::exp10(19), 105).await.unwrap();
+        env.set_eth_price(1800).await.unwrap();
+
+        let (collateral, debt, health_factor) = env.blockchain.get_position(user).await.unwrap();
+        let simulator = LiquidationSimulator::with_backend(
+            env.blockchain.clone(),
+            0.0,
+            SimulationBackend::Snapshot { liquidator: env.account(2) },
+        );
+        let signal = LiquidationSignal {
+            user,
+            collateral: TokenAmount::wad(collateral),
+            debt: TokenAmount::wad(debt),
+            health_factor: HealthFactor::new(health_factor, HealthFactorScale::PERCENT),
+            collateral_asset: crate::protocol::NATIVE_ASSET,
+            debt_asset: env.token_address,
+            trigger: None,
+            observed_block: None,
+            stale: false,
+            metrics: LatencyMetrics::new(),
+        };
+
+        let result = simulator.simulate_liquidation(&signal).await.unwrap();
+        assert!(!result.collateral_to_seize.is_zero());
+        assert!(!result.estimated_gas.is_zero());
+        assert_eq!(env.blockchain.get_position(user).await.unwrap().1, debt);
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use ethers::{contract::abigen, types::{Address, U256}};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::debug;
+
+use crate::blockchain::{BlockchainClient, RpcProvider};
+use crate::fixed_point::{self, WAD};
+use crate::protocol::NATIVE_ASSET;
+
+abigen!(
+    ERC20Metadata,
+    r#"[
+        function symbol() external view returns (string)
+        function decimals() external view returns (uint8)
+    ]"#
+);
+
+/// Most decimals a token can have: one whole token, 10^decimals, must fit in a U256
+const MAX_DECIMALS: u8 = 77;
+
+/// Symbol and decimals of a token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl TokenMetadata {
+    pub fn native() -> Self {
+        Self { symbol: "ETH".to_string(), decimals: 18 }
+    }
+
+    /// One whole token in base units
+    pub fn unit(&self) -> U256 {
+        U256::exp10(self.decimals as usize)
+    }
+
+    /// `amount` in whole tokens
+    pub fn to_f64(&self, amount: U256) -> f64 {
+        fixed_point::to_f64(amount, self.unit())
+    }
+
+    /// `amount` rescaled to 18 decimals
+    pub fn to_wad(&self, amount: U256) -> Result<U256> {
+        fixed_point::mul_div(amount, WAD, self.unit())
+    }
+
+    /// 18-decimal `wad` in this token's base units, rounded down
+    pub fn from_wad(&self, wad: U256) -> Result<U256> {
+        fixed_point::mul_div(wad, self.unit(), WAD)
+    }
+}
+
+/// Token metadata read once per token through ERC20 `symbol`/`decimals`
+pub struct TokenRegistry {
+    provider: Arc<RpcProvider>,
+    tokens: RwLock<HashMap<Address, TokenMetadata>>,
+}
+
+impl TokenRegistry {
+    pub fn new(blockchain: &BlockchainClient) -> Self {
+        let tokens = HashMap::from([(NATIVE_ASSET, TokenMetadata::native())]);
+        Self { provider: blockchain.http_provider.clone(), tokens: RwLock::new(tokens) }
+    }
+
+    /// Use `metadata` for `token` instead of reading it
+    pub fn with_token(self, token: Address, metadata: TokenMetadata) -> Self {
+        self.tokens.write().unwrap().insert(token, metadata);
+        self
+    }
+
+    /// Metadata of `token`, fetched on first use
+    ///
+    /// A token that can't be read is an error (retried next time) rather than
+    /// a guess: valuing a 6-decimal debt as 18 decimals would make it look
+    /// 1e12 times cheaper to repay.
+    pub async fn metadata(&self, token: Address) -> Result<TokenMetadata> {
+        if let Some(metadata) = self.tokens.read().unwrap().get(&token) {
+            return Ok(metadata.clone());
+        }
+
+        let metadata = self.fetch(token).await.with_context(|| format!("Reading decimals of {:?}", token))?;
+        debug!("Token {:?} is {} with {} decimals", token, metadata.symbol, metadata.decimals);
+        self.tokens.write().unwrap().insert(token, metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn fetch(&self, token: Address) -> Result<TokenMetadata> {
+        let contract = ERC20Metadata::new(token, self.provider.clone());
+        let decimals = contract.decimals().call().await?;
+        anyhow::ensure!(decimals <= MAX_DECIMALS, "{} decimals is more than a U256 amount can hold", decimals);
+        // Some older tokens (e.g. MKR) return a bytes32 symbol
+        let symbol = contract.symbol().call().await.unwrap_or_else(|_| format!("{:?}", token));
+        Ok(TokenMetadata { symbol, decimals })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::offline_client;
+
+    #[tokio::test]
+    async fn test_six_decimal_amounts() {
+        let usdc = TokenMetadata { symbol: "USDC".to_string(), decimals: 6 };
+        let amount = U256::from(2_500_000_000u64); // 2,500 USDC
+        assert_eq!(usdc.to_f64(amount), 2500.0);
+        assert_eq!(usdc.to_wad(amount).unwrap(), U256::from(2500u64) * WAD);
+        assert_eq!(usdc.from_wad(U256::from(2500u64) * WAD).unwrap(), amount);
+
+        let blockchain = offline_client().await;
+        let token = Address::from_low_u64_be(1);
+        let registry = TokenRegistry::new(&blockchain).with_token(token, usdc.clone());
+        assert_eq!(registry.metadata(token).await.unwrap(), usdc);
+        assert_eq!(registry.metadata(NATIVE_ASSET).await.unwrap().decimals, 18);
+        // An unreadable token is not guessed at
+        assert!(registry.metadata(Address::from_low_u64_be(2)).await.is_err());
+    }
+}
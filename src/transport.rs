@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::providers::{Http, Ipc, JsonRpcClient, ProviderError};
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::rate_limit::TokenBucket;
+
+/// Where the node answers JSON-RPC requests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Http(Url),
+    /// Unix socket (or Windows named pipe) of a co-located geth/reth node
+    Ipc(PathBuf),
+}
+
+impl Endpoint {
+    /// `http(s)://` URLs are HTTP; `ipc://<path>` or a bare path is an IPC socket
+    pub fn parse(endpoint: &str) -> Result<Self> {
+        if let Some(path) = endpoint.strip_prefix("ipc://") {
+            return Ok(Endpoint::Ipc(PathBuf::from(path)));
+        }
+        if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+            return Ok(Endpoint::Http(endpoint.parse().with_context(|| format!("Invalid RPC URL {}", endpoint))?));
+        }
+        anyhow::ensure!(!endpoint.contains("://"), "Unsupported RPC endpoint {}", endpoint);
+        Ok(Endpoint::Ipc(PathBuf::from(endpoint)))
+    }
+}
+
+/// Request/response transport behind `RpcProvider`
+///
+/// IPC skips the TCP and HTTP overhead of every call, which matters when the
+/// node runs on the same host.
+#[derive(Debug, Clone)]
+pub struct RpcTransport {
+    connection: Connection,
+    rate_limit: Option<Arc<TokenBucket>>,
+}
+
+#[derive(Debug, Clone)]
+enum Connection {
+    Http(Http),
+    Ipc(Ipc),
+}
+
+impl RpcTransport {
+    pub async fn connect(endpoint: &str) -> Result<Self> {
+        let connection = match Endpoint::parse(endpoint)? {
+            Endpoint::Http(url) => Connection::Http(Http::new(url)),
+            Endpoint::Ipc(path) => Connection::Ipc(
+                Ipc::connect(&path).await.with_context(|| format!("Connecting to IPC socket {}", path.display()))?,
+            ),
+        };
+        Ok(Self { connection, rate_limit: None })
+    }
+
+    /// HTTP transport to `url`, without connecting
+    pub fn http(url: &str) -> Result<Self> {
+        let url: Url = url.parse().with_context(|| format!("Invalid RPC URL {}", url))?;
+        Ok(Self { connection: Connection::Http(Http::new(url)), rate_limit: None })
+    }
+
+    /// Wait for a token from `bucket` before every request
+    pub fn with_rate_limit(mut self, bucket: Arc<TokenBucket>) -> Self {
+        self.rate_limit = Some(bucket);
+        self
+    }
+
+    pub fn rate_limit(&self) -> Option<&Arc<TokenBucket>> {
+        self.rate_limit.as_ref()
+    }
+
+    /// HTTP endpoint, if this is the HTTP transport
+    pub fn url(&self) -> Option<&Url> {
+        match &self.connection {
+            Connection::Http(http) => Some(http.url()),
+            Connection::Ipc(_) => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match &self.connection {
+            Connection::Http(_) => "http",
+            Connection::Ipc(_) => "ipc",
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for RpcTransport {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, ProviderError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        if let Some(bucket) = &self.rate_limit {
+            bucket.acquire(1).await;
+        }
+        match &self.connection {
+            Connection::Http(http) => http.request(method, params).await.map_err(Into::into),
+            Connection::Ipc(ipc) => ipc.request(method, params).await.map_err(Into::into),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_parse() {
+        assert!(matches!(Endpoint::parse("http://127.0.0.1:8545").unwrap(), Endpoint::Http(_)));
+        assert_eq!(Endpoint::parse("/var/run/reth.ipc").unwrap(), Endpoint::Ipc(PathBuf::from("/var/run/reth.ipc")));
+        assert_eq!(Endpoint::parse("ipc:///tmp/geth.ipc").unwrap(), Endpoint::Ipc(PathBuf::from("/tmp/geth.ipc")));
+        assert!(Endpoint::parse("ws://127.0.0.1:8546").is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::Result;
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::fixed_point::{self, to_f64, wad_to_f64, WAD};
+use crate::protocol::HealthFactorScale;
+
+/// A USD value with 18 decimals, as the protocol reports prices
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WadUsd(pub U256);
+
+impl WadUsd {
+    pub fn from_usd(usd: f64) -> Self {
+        Self(fixed_point::from_f64(usd, WAD))
+    }
+
+    pub fn to_usd(self) -> f64 {
+        wad_to_f64(self.0)
+    }
+}
+
+/// An amount of one token in its smallest unit, with the decimals it is
+/// counted in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenAmount {
+    pub raw: U256,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn new(raw: U256, decimals: u8) -> Self {
+        Self { raw, decimals }
+    }
+
+    /// An 18-decimal amount (ETH, and the mock protocol's stablecoin)
+    pub fn wad(raw: U256) -> Self {
+        Self::new(raw, 18)
+    }
+
+    /// `usd` of a USD stablecoin with `decimals`, rounded down
+    pub fn from_usd(usd: WadUsd, decimals: u8) -> Result<Self> {
+        Ok(Self::new(fixed_point::mul_div(usd.0, U256::exp10(decimals as usize), WAD)?, decimals))
+    }
+
+    pub fn from_tokens(tokens: f64, decimals: u8) -> Self {
+        Self::new(fixed_point::from_f64(tokens, U256::exp10(decimals as usize)), decimals)
+    }
+
+    /// Whole tokens, for reporting and scoring
+    pub fn to_tokens(self) -> f64 {
+        to_f64(self.raw, U256::exp10(self.decimals as usize))
+    }
+
+    /// USD value at `price_usd` per whole token
+    pub fn value_usd(self, price_usd: f64) -> f64 {
+        self.to_tokens() * price_usd
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.raw.is_zero()
+    }
+}
+
+/// A health factor in the encoding of the protocol it was read from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthFactor {
+    pub raw: U256,
+    pub scale: HealthFactorScale,
+}
+
+impl HealthFactor {
+    pub fn new(raw: U256, scale: HealthFactorScale) -> Self {
+        Self { raw, scale }
+    }
+
+    /// As a plain number, 1.0 being the liquidation line for most protocols
+    pub fn to_f64(self) -> f64 {
+        self.scale.to_f64(self.raw)
+    }
+
+    /// Relative to the protocol's liquidation threshold (below 1.0 is liquidatable)
+    pub fn to_threshold(self) -> f64 {
+        self.to_f64() / self.scale.liquidation_threshold.max(f64::EPSILON)
+    }
+
+    pub fn is_liquidatable(self, debt: TokenAmount) -> bool {
+        self.scale.is_liquidatable(self.raw, debt.raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_units_carry_their_scale() {
+        // 1,500 USDC (6 decimals) and 1,500 DAI (18) are the same value
+        let usdc = TokenAmount::new(U256::from(1_500_000_000u64), 6);
+        let dai = TokenAmount::wad(U256::from(1_500u64) * WAD);
+        assert_ne!(usdc, dai);
+        assert_eq!(usdc.value_usd(1.0), dai.value_usd(1.0));
+        assert_eq!(TokenAmount::from_tokens(1_500.0, 6), usdc);
+        assert_eq!(WadUsd::from_usd(2_000.5).to_usd(), 2_000.5);
+
+        // A USD value becomes each stablecoin's own base units
+        assert_eq!(TokenAmount::from_usd(WadUsd::from_usd(1_500.0), 6).unwrap(), usdc);
+        assert_eq!(TokenAmount::from_usd(WadUsd::from_usd(1_500.0), 18).unwrap(), dai);
+
+        // 95 percent and 0.95 wad are the same health factor
+        let percent = HealthFactor::new(U256::from(95), HealthFactorScale::PERCENT);
+        let wad = HealthFactor::new(U256::from(95) * U256::exp10(16), HealthFactorScale::WAD);
+        assert_eq!(percent.to_f64(), wad.to_f64());
+        assert!(percent.is_liquidatable(dai) && wad.is_liquidatable(dai));
+        assert!(!percent.is_liquidatable(TokenAmount::default()));
+    }
+}
@@ -0,0 +1,253 @@
+use anyhow::{Context, Result};
+use ethers::abi::{self, Token};
+use ethers::types::{Address, Bytes, Transaction, H256, U256, U64};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::backpressure::{self, BufferConfig, TxReceiver, TxSender};
+use crate::nested_calls;
+
+/// Canonical ERC-4337 v0.6 EntryPoint, deployed at the same address on every chain
+pub const ENTRY_POINT_V06: &str = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789";
+
+/// Consecutive failed polls tolerated before the feed gives up
+const MAX_POLL_FAILURES: u32 = 5;
+
+/// A v0.6 UserOperation as bundlers return it over JSON-RPC
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// `getUserOpHash` of the EntryPoint: the id bundlers and explorers use
+    pub fn hash(&self, entry_point: Address, chain_id: u64) -> H256 {
+        let hashed = |bytes: &Bytes| Token::FixedBytes(keccak256(bytes).to_vec());
+        let packed = abi::encode(&[
+            Token::Address(self.sender),
+            Token::Uint(self.nonce),
+            hashed(&self.init_code),
+            hashed(&self.call_data),
+            Token::Uint(self.call_gas_limit),
+            Token::Uint(self.verification_gas_limit),
+            Token::Uint(self.pre_verification_gas),
+            Token::Uint(self.max_fee_per_gas),
+            Token::Uint(self.max_priority_fee_per_gas),
+            hashed(&self.paymaster_and_data),
+        ]);
+        keccak256(abi::encode(&[
+            Token::FixedBytes(keccak256(packed).to_vec()),
+            Token::Address(entry_point),
+            Token::Uint(chain_id.into()),
+        ]))
+        .into()
+    }
+
+    /// The call the EntryPoint will make to the account, as a pending
+    /// transaction: the classifier then sees the account, not the bundler, as
+    /// the caller of any protocol call inside it
+    pub fn to_transaction(&self, entry_point: Address, chain_id: u64) -> Transaction {
+        Transaction {
+            hash: self.hash(entry_point, chain_id),
+            nonce: self.nonce,
+            from: entry_point,
+            to: Some(self.sender),
+            gas: self.call_gas_limit,
+            input: self.call_data.clone(),
+            gas_price: Some(self.max_fee_per_gas),
+            max_fee_per_gas: Some(self.max_fee_per_gas),
+            max_priority_fee_per_gas: Some(self.max_priority_fee_per_gas),
+            transaction_type: Some(U64::from(2)),
+            chain_id: Some(chain_id.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Alt-mempool source: polls a bundler's UserOperation mempool
+/// (`debug_bundler_dumpMempool`) and forwards each new operation, so borrowers
+/// behind smart accounts are seen before their operation is bundled
+pub struct UserOperationFeed {
+    client: reqwest::Client,
+    url: String,
+    entry_point: Address,
+    chain_id: u64,
+    protocol: Option<Address>,
+    poll_interval: Duration,
+    tx_sender: TxSender,
+    max_transactions: Option<usize>,
+}
+
+impl UserOperationFeed {
+    pub fn new(url: &str, entry_point: Address, chain_id: u64) -> (Self, TxReceiver) {
+        Self::with_buffer(url, entry_point, chain_id, BufferConfig::default())
+    }
+
+    /// Forward into a buffer that applies `buffer.policy` when the detector falls behind
+    pub fn with_buffer(url: &str, entry_point: Address, chain_id: u64, buffer: BufferConfig) -> (Self, TxReceiver) {
+        let (tx_sender, rx) = backpressure::channel(buffer);
+        (
+            Self {
+                client: reqwest::Client::new(),
+                url: url.to_string(),
+                entry_point,
+                chain_id,
+                protocol: None,
+                poll_interval: Duration::from_millis(500),
+                tx_sender,
+                max_transactions: None,
+            },
+            rx,
+        )
+    }
+
+    /// Only forward operations that call `protocol` (possibly through a batch)
+    pub fn with_protocol(mut self, protocol: Address) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Stop (closing the channel) after forwarding `max` operations
+    pub fn with_limit(mut self, max: usize) -> Self {
+        self.max_transactions = Some(max);
+        self
+    }
+
+    /// Operations pending in the bundler for our EntryPoint
+    async fn dump_mempool(&self) -> Result<Vec<UserOperation>> {
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "debug_bundler_dumpMempool",
+                "params": [self.entry_point],
+            }))
+            .send()
+            .await
+            .with_context(|| format!("Bundler request to {} failed", self.url))?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Invalid bundler response")?;
+        if let Some(error) = response.get("error") {
+            anyhow::bail!("Bundler rejected debug_bundler_dumpMempool: {}", error);
+        }
+        serde_json::from_value(response["result"].clone()).context("Invalid UserOperations in bundler mempool")
+    }
+
+    /// Forward new operations until the limit is hit, the receiver is dropped,
+    /// or the bundler fails `MAX_POLL_FAILURES` polls in a row
+    pub async fn run(self) -> Result<()> {
+        info!("Watching UserOperations for EntryPoint {:?} at {}", self.entry_point, self.url);
+        let mut forwarded = 0usize;
+        let mut failures = 0u32;
+        // Operations from the last poll; anything bundled or dropped since falls out
+        let mut seen = HashSet::new();
+
+        loop {
+            let operations = match self.dump_mempool().await {
+                Ok(operations) => {
+                    failures = 0;
+                    operations
+                }
+                Err(e) => {
+                    failures += 1;
+                    anyhow::ensure!(failures < MAX_POLL_FAILURES, "Bundler mempool unreachable: {:#}", e);
+                    warn!("Bundler mempool poll failed ({:#}), retrying", e);
+                    tokio::time::sleep(self.poll_interval * failures).await;
+                    continue;
+                }
+            };
+
+            let mut current = HashSet::with_capacity(operations.len());
+            for op in operations {
+                let tx = op.to_transaction(self.entry_point, self.chain_id);
+                current.insert(tx.hash);
+                if seen.contains(&tx.hash) {
+                    continue;
+                }
+                if let Some(protocol) = self.protocol {
+                    if nested_calls::protocol_calls(tx.from, op.sender, &tx.input, protocol).is_empty() {
+                        continue;
+                    }
+                }
+                debug!("UserOperation {:?} from {:?}", tx.hash, op.sender);
+                if self.tx_sender.send(tx).await.is_err() {
+                    return Ok(());
+                }
+                forwarded += 1;
+                if self.max_transactions.is_some_and(|max| forwarded >= max) {
+                    info!("UserOperation feed forwarded {} operations", forwarded);
+                    return Ok(());
+                }
+            }
+            seen = current;
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::BorrowCall;
+    use crate::mempool_streamer::{TransactionClassifier, TransactionType};
+    use crate::nested_calls::EXECUTE_SELECTOR;
+    use ethers::abi::AbiEncode;
+
+    #[test]
+    fn test_operation_as_transaction() {
+        let op: UserOperation = serde_json::from_value(json!({
+            "sender": "0x0000000000000000000000000000000000000002",
+            "nonce": "0x0",
+            "initCode": "0x",
+            "callData": "0x",
+            "callGasLimit": "0x5208",
+            "verificationGasLimit": "0x186a0",
+            "preVerificationGas": "0xb5c8",
+            "maxFeePerGas": "0x3b9aca00",
+            "maxPriorityFeePerGas": "0x3b9aca00",
+            "paymasterAndData": "0x",
+            "signature": "0x",
+        }))
+        .unwrap();
+        let entry_point: Address = ENTRY_POINT_V06.parse().unwrap();
+        assert_ne!(op.hash(entry_point, 1), op.hash(entry_point, 137));
+
+        // execute(protocol, 0, borrow(1)) from the account
+        let protocol = Address::from_low_u64_be(0xbeef);
+        let call_data = [
+            &EXECUTE_SELECTOR[..],
+            &abi::encode(&[Token::Address(protocol), Token::Uint(U256::zero()), Token::Bytes(BorrowCall { amount: U256::one() }.encode())]),
+        ]
+        .concat();
+        let tx = UserOperation { call_data: call_data.into(), ..op.clone() }.to_transaction(entry_point, 1);
+        let calls = TransactionClassifier::protocol_calls(&tx, protocol);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].caller, op.sender);
+        assert_eq!(TransactionClassifier::classify_calldata(&calls[0].input), Some(TransactionType::Borrow));
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use ethers::{
+    contract::abigen,
+    types::{Address, U256},
+};
+use futures::future::join_all;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::blockchain::BlockchainClient;
+use crate::simulator::SimulationResult;
+
+abigen!(
+    QuoterV2,
+    r#"[
+        struct QuoteExactInputSingleParams { address tokenIn; address tokenOut; uint256 amountIn; uint24 fee; uint160 sqrtPriceLimitX96; }
+        function quoteExactInputSingle(QuoteExactInputSingleParams memory params) external returns (uint256 amountOut, uint160 sqrtPriceX96After, uint32 initializedTicksCrossed, uint256 gasEstimate)
+        struct QuoteExactOutputSingleParams { address tokenIn; address tokenOut; uint256 amount; uint24 fee; uint160 sqrtPriceLimitX96; }
+        function quoteExactOutputSingle(QuoteExactOutputSingleParams memory params) external returns (uint256 amountIn, uint160 sqrtPriceX96After, uint32 initializedTicksCrossed, uint256 gasEstimate)
+    ]"#
+);
+
+abigen!(
+    CurvePool,
+    r#"[
+        function get_dy(int128 i, int128 j, uint256 dx) external view returns (uint256)
+    ]"#
+);
+
+/// Where seized ETH could be sold for the debt asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapVenue {
+    /// Uniswap V3 WETH/debt asset pool of `fee` tier, quoted through QuoterV2
+    UniswapV3 { quoter: Address, weth: Address, fee: u32 },
+    /// Curve pool holding ETH at `eth_index` and the debt asset at `debt_index`
+    Curve { pool: Address, eth_index: i128, debt_index: i128 },
+}
+
+/// Share of the oracle value of `amount` wei at `price_usd` lost by selling it
+/// for `proceeds` (18-decimal debt asset); 0 if the sale beats the oracle
+pub fn slippage(amount: U256, price_usd: f64, proceeds: U256) -> f64 {
+    let oracle_value = amount.as_u128() as f64 / 1e18 * price_usd;
+    if oracle_value <= 0.0 {
+        return 0.0;
+    }
+    (1.0 - proceeds.as_u128() as f64 / 1e18 / oracle_value).clamp(0.0, 1.0)
+}
+
+/// Sale of seized collateral in backtests: a swap fee, then constant-product
+/// price impact against a pool holding `depth_usd` on each side
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SaleModel {
+    pub fee_bps: u32,
+    pub depth_usd: f64,
+}
+
+impl SaleModel {
+    /// Sells at the oracle price: no fee, no price impact
+    pub const ORACLE: SaleModel = SaleModel { fee_bps: 0, depth_usd: f64::INFINITY };
+
+    /// USD received for collateral worth `value_usd` at the scenario price
+    pub fn proceeds_usd(&self, value_usd: f64) -> f64 {
+        let after_fee = value_usd.max(0.0) * (1.0 - self.fee_bps.min(10_000) as f64 / 10_000.0);
+        if !self.depth_usd.is_finite() {
+            return after_fee;
+        }
+        after_fee * self.depth_usd / (self.depth_usd + after_fee)
+    }
+
+    /// Net USD from repaying as `simulation` says and selling the seized
+    /// collateral at `price_usd`, and what the sale cost against that price
+    pub fn realized_profit_usd(&self, simulation: &SimulationResult, price_usd: f64) -> (f64, f64) {
+        let value_usd = simulation.collateral_to_seize.to_tokens() * price_usd;
+        let proceeds_usd = self.proceeds_usd(value_usd);
+        let profit_usd = proceeds_usd
+            - simulation.repay_usd()
+            - simulation.estimated_gas_cost_usd
+            - simulation.funding_cost_usd;
+        (profit_usd, value_usd - proceeds_usd)
+    }
+}
+
+/// Prices seized collateral at what the configured venues would actually pay for it
+pub struct CollateralPricer {
+    blockchain: Arc<BlockchainClient>,
+    venues: Vec<SwapVenue>,
+    /// Collateral worth less than this at the oracle price is valued linearly
+    min_value_usd: f64,
+}
+
+impl CollateralPricer {
+    pub fn new(blockchain: Arc<BlockchainClient>, venues: Vec<SwapVenue>, min_value_usd: f64) -> Self {
+        Self { blockchain, venues, min_value_usd }
+    }
+
+    /// Best debt asset amount any venue returns for `amount` wei
+    pub async fn proceeds(&self, amount: U256) -> Option<U256> {
+        let quotes = join_all(self.venues.iter().map(|venue| self.quote(*venue, amount))).await;
+        self.venues
+            .iter()
+            .zip(quotes)
+            .filter_map(|(venue, quote)| match quote {
+                Ok(proceeds) => Some(proceeds),
+                Err(e) => {
+                    debug!("No sale quote from {:?}: {}", venue, e);
+                    None
+                }
+            })
+            .max()
+    }
+
+    /// Slippage selling `amount` wei would incur against `price_usd`; 0 for small
+    /// amounts or when no venue can quote
+    pub async fn slippage(&self, amount: U256, price_usd: f64) -> f64 {
+        if amount.as_u128() as f64 / 1e18 * price_usd < self.min_value_usd {
+            return 0.0;
+        }
+        match self.proceeds(amount).await {
+            Some(proceeds) => slippage(amount, price_usd, proceeds),
+            None => 0.0,
+        }
+    }
+
+    async fn quote(&self, venue: SwapVenue, amount: U256) -> Result<U256> {
+        let provider = self.blockchain.http_provider.clone();
+        Ok(match venue {
+            SwapVenue::UniswapV3 { quoter, weth, fee } => {
+                let params = QuoteExactInputSingleParams {
+                    token_in: weth,
+                    token_out: self.blockchain.token.address(),
+                    amount_in: amount,
+                    fee,
+                    sqrt_price_limit_x96: U256::zero(),
+                };
+                // QuoterV2 reverts internally to compute the quote, so it is only ever eth_call'd
+                let (amount_out, ..) = QuoterV2::new(quoter, provider).quote_exact_input_single(params).call().await?;
+                amount_out
+            }
+            SwapVenue::Curve { pool, eth_index, debt_index } => {
+                CurvePool::new(pool, provider).get_dy(eth_index, debt_index, amount).call().await?
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slippage_against_oracle() {
+        let eth = U256::exp10(18);
+        // 100 ETH at $2000 selling for $194,000 loses 3%
+        assert!((slippage(eth * 100, 2000.0, eth * 194_000) - 0.03).abs() < 1e-9);
+        // A sale above the oracle price is not counted as a gain
+        assert_eq!(slippage(eth, 2000.0, eth * 2010), 0.0);
+        assert_eq!(slippage(U256::zero(), 2000.0, U256::zero()), 0.0);
+
+        // $1M into a $9M pool after a 30 bps fee
+        let sale = SaleModel { fee_bps: 30, depth_usd: 9_000_000.0 };
+        let after_fee = 997_000.0;
+        assert!((sale.proceeds_usd(1_000_000.0) - after_fee * 9e6 / (9e6 + after_fee)).abs() < 1e-6);
+        assert!(sale.proceeds_usd(100.0) > 99.6);
+        assert_eq!(SaleModel::ORACLE.proceeds_usd(1_000_000.0), 1_000_000.0);
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::{
+    abi::AbiEncode,
+    contract::abigen,
+    types::{Address, Bytes, U256},
+};
+
+use crate::blockchain::{BlockchainClient, RpcProvider};
+use crate::fixed_point::{self, WAD};
+use crate::protocol::{AssetRiskParams, HealthFactorScale, MarketStatus, ProtocolAdapter, BPS};
+
+abigen!(
+    VenusComptroller,
+    r#"[
+        function closeFactorMantissa() external view returns (uint256)
+        function liquidationIncentiveMantissa() external view returns (uint256)
+        function protocolPaused() external view returns (bool)
+        function actionPaused(address market, uint8 action) external view returns (bool)
+    ]"#
+);
+
+/// `Comptroller.Action` values checked before liquidating
+const ACTION_SEIZE: u8 = 4;
+const ACTION_LIQUIDATE: u8 = 5;
+
+abigen!(
+    VToken,
+    r#"[
+        function liquidateBorrow(address borrower, uint256 repayAmount, address vTokenCollateral) external returns (uint256)
+    ]"#
+);
+
+/// The vToken markets one liquidation touches: debt is repaid into
+/// `borrowed`, collateral is seized as `collateral` vTokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VenusMarket {
+    pub borrowed: Address,
+    pub collateral: Address,
+}
+
+impl VenusMarket {
+    /// Call repaying `repay_amount` of `borrower`'s debt and seizing collateral
+    /// vTokens; BEP-20 markets only (vBNB takes the repayment as value)
+    pub fn liquidate_borrow_call(&self, borrower: Address, repay_amount: U256) -> (Address, Bytes) {
+        let call = LiquidateBorrowCall { borrower, repay_amount, v_token_collateral: self.collateral };
+        (self.borrowed, call.encode().into())
+    }
+}
+
+/// Liquidation parameters from Comptroller mantissas (1e18 = 100%); Venus
+/// applies one close factor and incentive across the core pool
+pub fn risk_params(close_factor_mantissa: U256, incentive_mantissa: U256) -> Result<AssetRiskParams> {
+    anyhow::ensure!(incentive_mantissa >= WAD, "Unexpected liquidation incentive {}", incentive_mantissa);
+    let bps = U256::from(BPS);
+    // liquidationIncentiveMantissa = 1.1e18 means a 10% bonus
+    let bonus = fixed_point::mul_div(incentive_mantissa - WAD, bps, WAD)?;
+    let close_factor = fixed_point::mul_div(close_factor_mantissa, bps, WAD)?.min(bps);
+    Ok(AssetRiskParams {
+        liquidation_bonus_bps: bonus.min(U256::from(u32::MAX)).as_u32(),
+        close_factor_bps: close_factor.as_u32(),
+    })
+}
+
+/// Adapter for Venus on BNB Chain: a Compound V2 fork where a liquidator repays
+/// debt on the borrowed vToken and is paid in the borrower's collateral vTokens
+pub struct VenusAdapter {
+    comptroller: VenusComptroller<RpcProvider>,
+    health_factor_scale: HealthFactorScale,
+    market: Option<VenusMarket>,
+}
+
+impl VenusAdapter {
+    pub fn new(blockchain: &BlockchainClient, comptroller: Address) -> Self {
+        Self {
+            comptroller: VenusComptroller::new(comptroller, blockchain.http_provider.clone()),
+            health_factor_scale: HealthFactorScale::WAD,
+            market: None,
+        }
+    }
+
+    /// Also check whether liquidating or seizing is paused on `market`'s vTokens
+    pub fn with_market(mut self, market: VenusMarket) -> Self {
+        self.market = Some(market);
+        self
+    }
+
+    /// Scale of the health factors the position lens reports
+    pub fn with_health_factor_scale(mut self, scale: HealthFactorScale) -> Self {
+        self.health_factor_scale = scale;
+        self
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for VenusAdapter {
+    fn name(&self) -> &str {
+        "venus"
+    }
+
+    async fn asset_params(&self, _asset: Address) -> Result<AssetRiskParams> {
+        let close_factor = self.comptroller.close_factor_mantissa().call().await?;
+        let incentive = self.comptroller.liquidation_incentive_mantissa().call().await?;
+        risk_params(close_factor, incentive)
+    }
+
+    fn health_factor_scale(&self) -> HealthFactorScale {
+        self.health_factor_scale
+    }
+
+    async fn market_status(&self, _collateral_asset: Address, _debt_asset: Address) -> Result<MarketStatus> {
+        if self.comptroller.protocol_paused().call().await? {
+            return Ok(MarketStatus::Halted("comptroller paused".to_string()));
+        }
+        let Some(market) = self.market else { return Ok(MarketStatus::Active) };
+        if self.comptroller.action_paused(market.borrowed, ACTION_LIQUIDATE).call().await? {
+            return Ok(MarketStatus::Halted(format!("liquidations paused on {:?}", market.borrowed)));
+        }
+        if self.comptroller.action_paused(market.collateral, ACTION_SEIZE).call().await? {
+            return Ok(MarketStatus::Halted(format!("seizing paused on {:?}", market.collateral)));
+        }
+        Ok(MarketStatus::Active)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::AbiDecode;
+
+    #[test]
+    fn test_venus_params_and_liquidate_call() {
+        // Core pool: 50% close factor, 1.1 incentive
+        let params = risk_params(WAD / 2, WAD * 11 / 10).unwrap();
+        assert_eq!(params, AssetRiskParams { liquidation_bonus_bps: 1_000, close_factor_bps: 5_000 });
+        assert!(risk_params(WAD, WAD / 2).is_err());
+
+        let market = VenusMarket { borrowed: Address::from_low_u64_be(1), collateral: Address::from_low_u64_be(2) };
+        let borrower = Address::from_low_u64_be(3);
+        let (to, data) = market.liquidate_borrow_call(borrower, WAD);
+        assert_eq!(to, market.borrowed);
+        // keccak("liquidateBorrow(address,uint256,address)")
+        assert_eq!(&data[..4], &hex::decode("f5e3c462").unwrap());
+        let decoded = LiquidateBorrowCall::decode(&data).unwrap();
+        assert_eq!((decoded.borrower, decoded.repay_amount, decoded.v_token_collateral), (borrower, WAD, market.collateral));
+    }
+}
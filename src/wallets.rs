@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, H256};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// How the executor picks the signer for the next submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationStrategy {
+    /// Cycle through the keys in configuration order
+    #[default]
+    RoundRobin,
+    /// Pick the key that has gone longest without signing
+    LeastRecentlyUsed,
+}
+
+impl FromStr for RotationStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "round_robin" | "round-robin" | "rr" => Ok(Self::RoundRobin),
+            "lru" | "least_recently_used" | "least-recently-used" => Ok(Self::LeastRecentlyUsed),
+            other => anyhow::bail!("Unknown wallet rotation strategy: {}", other),
+        }
+    }
+}
+
+/// Set of liquidator signers rotated across submissions
+///
+/// Spreading submissions over several keys means one stuck nonce or one
+/// flagged address doesn't serialize every liquidation behind it.
+#[derive(Debug)]
+pub struct WalletPool {
+    wallets: Vec<LocalWallet>,
+    strategy: RotationStrategy,
+    cursor: AtomicUsize,
+    last_used: Mutex<Vec<Option<Instant>>>,
+}
+
+impl WalletPool {
+    pub fn new(wallets: Vec<LocalWallet>, strategy: RotationStrategy) -> Self {
+        let last_used = Mutex::new(vec![None; wallets.len()]);
+        Self {
+            wallets,
+            strategy,
+            cursor: AtomicUsize::new(0),
+            last_used,
+        }
+    }
+
+    /// Pool with no signers (simulation / dry-run mode)
+    pub fn empty() -> Self {
+        Self::new(Vec::new(), RotationStrategy::default())
+    }
+
+    /// Build signers from raw private keys, binding each to `chain_id`
+    pub fn from_keys(keys: &[H256], chain_id: u64, strategy: RotationStrategy) -> Result<Self> {
+        let wallets = keys
+            .iter()
+            .map(|key| {
+                LocalWallet::from_bytes(key.as_bytes())
+                    .map(|wallet| wallet.with_chain_id(chain_id))
+                    .context("Invalid liquidator private key")
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::new(wallets, strategy))
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+
+    pub fn strategy(&self) -> RotationStrategy {
+        self.strategy
+    }
+
+    /// Signer for `address`, if it belongs to the pool
+    pub fn get(&self, address: Address) -> Option<&LocalWallet> {
+        self.wallets.iter().find(|wallet| wallet.address() == address)
+    }
+
+    /// Select the signer for the next submission
+    pub fn next(&self) -> Option<&LocalWallet> {
+        if self.wallets.is_empty() {
+            return None;
+        }
+
+        let index = match self.strategy {
+            RotationStrategy::RoundRobin => {
+                self.cursor.fetch_add(1, Ordering::Relaxed) % self.wallets.len()
+            }
+            RotationStrategy::LeastRecentlyUsed => {
+                let mut last_used = self.last_used.lock().unwrap();
+                // Never-used wallets (None) sort before any timestamp
+                let index = (0..last_used.len())
+                    .min_by_key(|&i| last_used[i])
+                    .unwrap_or(0);
+                last_used[index] = Some(Instant::now());
+                index
+            }
+        };
+
+        self.wallets.get(index)
+    }
+}
+
+impl Default for WalletPool {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_pool(strategy: RotationStrategy) -> WalletPool {
+        let keys: Vec<H256> = (1..=3u64).map(H256::from_low_u64_be).collect();
+        WalletPool::from_keys(&keys, 31337, strategy).unwrap()
+    }
+
+    #[test]
+    fn test_round_robin_rotation() {
+        let pool = test_pool(RotationStrategy::RoundRobin);
+        let picks: Vec<_> = (0..4).map(|_| pool.next().unwrap().address()).collect();
+
+        assert_ne!(picks[0], picks[1]);
+        assert_ne!(picks[1], picks[2]);
+        assert_eq!(picks[0], picks[3]);
+    }
+
+    #[test]
+    fn test_lru_rotation_visits_every_wallet() {
+        let pool = test_pool(RotationStrategy::LeastRecentlyUsed);
+        let mut picks: Vec<_> = (0..3).map(|_| pool.next().unwrap().address()).collect();
+        picks.sort();
+        picks.dedup();
+
+        assert_eq!(picks.len(), 3);
+        assert!(WalletPool::empty().next().is_none());
+    }
+}